@@ -5,6 +5,9 @@ use axum::{
     routing::{get, patch, post, put},
     Router,
 };
+
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+use axum::{extract::DefaultBodyLimit, middleware, routing::any};
 use tower_http::{cors::CorsLayer, timeout::TimeoutLayer, trace::TraceLayer};
 
 use crate::config::Config;
@@ -15,12 +18,18 @@ use calendsync_auth::auth_routes;
 use crate::{
     handlers::{
         calendar_react::{calendar_react_ssr, calendar_react_ssr_entry},
-        calendars::{create_calendar, delete_calendar, get_calendar, update_calendar},
+        calendars::{
+            create_calendar, delete_calendar, duplicate_calendar, get_calendar, refresh_calendar,
+            update_calendar,
+        },
         entries::{
-            create_entry, delete_entry, get_entry, list_entries, toggle_entry, update_entry,
+            batch_entries, create_entry, delete_entry, get_entry, list_entries, move_entry,
+            toggle_entry, update_entry,
         },
         events::events_sse,
         health::{healthz, livez, readyz},
+        history::{get_entry_history, revert_entry},
+        ics::{export_calendar, import_calendar},
         settings::update_settings,
         static_files::serve_static,
     },
@@ -28,7 +37,20 @@ use crate::{
 };
 
 #[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
-use crate::handlers::calendars::list_my_calendars;
+use crate::handlers::{
+    admin::{export_backup, restore_backup, MAX_RESTORE_BYTES},
+    caldav::{
+        basic_auth, dav_calendar, dav_home, dav_principal, dav_resource, dav_root,
+        well_known_caldav,
+    },
+    calendars::list_my_calendars,
+    entries::rsvp_entry,
+    feeds::{create_feed, get_feed, revoke_feed, serve_feed},
+    freebusy::get_freebusy,
+    search::search_entries,
+    trash::{list_trash, restore_calendar, restore_entry},
+    users::update_my_timezone,
+};
 
 #[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
 use crate::handlers::{login::login_page, root::root_redirect};
@@ -66,21 +88,49 @@ pub fn create_app(state: AppState, config: &Config) -> Router {
                 .put(update_calendar)
                 .delete(delete_calendar),
         )
+        .route("/calendars/{id}/duplicate", post(duplicate_calendar))
+        .route("/calendars/{id}/export.ics", get(export_calendar))
+        .route("/calendars/{id}/import", post(import_calendar))
+        .route("/calendars/{id}/refresh", post(refresh_calendar))
         .route("/calendars/{id}/settings", put(update_settings))
         // Entry routes
         .route("/entries", get(list_entries).post(create_entry))
+        .route("/entries/batch", post(batch_entries))
         .route(
             "/entries/{id}",
             get(get_entry).put(update_entry).delete(delete_entry),
         )
         .route("/entries/{id}/toggle", patch(toggle_entry))
+        .route("/entries/{id}/move", post(move_entry))
+        .route("/entries/{id}/history", get(get_entry_history))
+        .route(
+            "/entries/{id}/history/{revision}/revert",
+            post(revert_entry),
+        )
         // SSE events stream for real-time updates
         .route("/events", get(events_sse));
 
     // Add auth-required API routes if auth is enabled
     #[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
     {
-        api_routes = api_routes.route("/calendars/me", get(list_my_calendars));
+        api_routes = api_routes
+            .route("/admin/backup", get(export_backup))
+            .route(
+                "/admin/restore",
+                post(restore_backup).layer(DefaultBodyLimit::max(MAX_RESTORE_BYTES)),
+            )
+            .route("/calendars/me", get(list_my_calendars))
+            .route(
+                "/calendars/{id}/feed",
+                get(get_feed).post(create_feed).delete(revoke_feed),
+            )
+            .route("/entries/{id}/rsvp", put(rsvp_entry))
+            .route("/search", get(search_entries))
+            .route("/trash", get(list_trash))
+            .route("/trash/calendars/{id}/restore", post(restore_calendar))
+            .route("/trash/entries/{id}/restore", post(restore_entry))
+            .route("/users/me/timezone", put(update_my_timezone))
+            .route("/users/{id}/freebusy", get(get_freebusy));
     }
 
     let api_routes = api_routes.layer(cors);
@@ -169,10 +219,30 @@ pub fn create_app(state: AppState, config: &Config) -> Router {
         // Add login and root redirect routes
         router = router
             .route("/", get(root_redirect))
-            .route("/login", get(login_page));
+            .route("/login", get(login_page))
+            // Subscription feeds authenticate by token, not session
+            .route("/feeds/{file}", get(serve_feed));
+
+        // CalDAV clients send Basic credentials and use PROPFIND and REPORT,
+        // so each path takes any method. Collections answer with and
+        // without their trailing slash.
+        let dav_routes = Router::new()
+            .route("/dav", any(dav_root))
+            .route("/dav/", any(dav_root))
+            .route("/dav/principals/{user_id}", any(dav_principal))
+            .route("/dav/principals/{user_id}/", any(dav_principal))
+            .route("/dav/calendars", any(dav_home))
+            .route("/dav/calendars/", any(dav_home))
+            .route("/dav/calendars/{calendar_id}", any(dav_calendar))
+            .route("/dav/calendars/{calendar_id}/", any(dav_calendar))
+            .route("/dav/calendars/{calendar_id}/{name}", any(dav_resource))
+            .layer(middleware::from_fn(basic_auth));
+        router = router
+            .merge(dav_routes)
+            .route("/.well-known/caldav", any(well_known_caldav));
 
         tracing::info!(
-            "Auth routes enabled: /auth/*, /, /login, /api/calendars/me endpoints available"
+            "Auth routes enabled: /auth/*, /, /login, /api/calendars/me, /api/calendars/{{id}}/feed, /feeds/*, /dav/*, /.well-known/caldav, /api/search, /api/trash/*, /api/users/{{id}}/freebusy, /api/admin/* endpoints available"
         );
     }
 
@@ -305,4 +375,1283 @@ mod tests {
 
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
     }
+
+    #[cfg(not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")))]
+    #[tokio::test]
+    async fn test_delete_single_occurrence() {
+        use calendsync_core::calendar::{CalendarEntry, RecurrenceRule};
+        use chrono::NaiveDate;
+
+        let state = AppState::default();
+        let config = Config::default();
+        let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let entry = CalendarEntry::all_day(uuid::Uuid::new_v4(), "Standup", date)
+            .with_recurrence(RecurrenceRule::weekly());
+        state.entry_repo.create_entry(&entry).await.unwrap();
+
+        let delete = |uri: String| {
+            create_app(state.clone(), &config).oneshot(
+                Request::builder()
+                    .method("DELETE")
+                    .uri(uri)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+
+        // A scoped delete needs the occurrence date
+        let response = delete(format!("/api/entries/{}?scope=this", entry.id))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // 2024-01-02 is not an occurrence of the weekly series
+        let response = delete(format!(
+            "/api/entries/{}?scope=this&occurrence_date=2024-01-02",
+            entry.id
+        ))
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = delete(format!(
+            "/api/entries/{}?scope=this&occurrence_date=2024-01-08",
+            entry.id
+        ))
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let overrides = state
+            .entry_repo
+            .get_occurrence_overrides(entry.id)
+            .await
+            .unwrap();
+        assert_eq!(overrides.len(), 1);
+        assert!(overrides[0].is_cancelled());
+        assert!(state
+            .entry_repo
+            .get_entry(entry.id)
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[cfg(not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")))]
+    #[tokio::test]
+    async fn test_list_entries_in_viewer_timezone() {
+        use calendsync_core::calendar::{Calendar, CalendarEntry};
+        use chrono::{NaiveDate, NaiveTime};
+
+        let state = AppState::default();
+        let config = Config::default();
+        let calendar =
+            Calendar::new("Work", "#3B82F6").with_timezone(chrono_tz::America::Montevideo);
+        state
+            .calendar_repo
+            .create_calendar(&calendar)
+            .await
+            .unwrap();
+
+        // 21:00 in Montevideo is 01:00 the next day in Madrid
+        let entry = CalendarEntry::timed(
+            calendar.id,
+            "Late call",
+            NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+            NaiveTime::from_hms_opt(21, 0, 0).unwrap(),
+            NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+        );
+        state.entry_repo.create_entry(&entry).await.unwrap();
+
+        let response = create_app(state, &config)
+            .oneshot(
+                Request::builder()
+                    .uri(format!(
+                        "/api/entries?calendar_id={}&highlighted_day=2024-01-16&before=1&after=0&timezone=Europe/Madrid",
+                        calendar.id
+                    ))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let days: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(days[0]["date"], "2024-01-15");
+        assert!(days[0]["entries"].as_array().unwrap().is_empty());
+        let moved = &days[1]["entries"][0];
+        assert_eq!(moved["startDate"], "2024-01-16");
+        assert_eq!(moved["startTime"], "01:00");
+        assert_eq!(moved["timezone"], "Europe/Madrid");
+    }
+
+    #[cfg(not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")))]
+    #[tokio::test]
+    async fn test_list_entries_splits_overnight_entry() {
+        use calendsync_core::calendar::CalendarEntry;
+        use chrono::{NaiveDate, NaiveTime};
+
+        let state = AppState::default();
+        let config = Config::default();
+        let calendar_id = uuid::Uuid::new_v4();
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let shift = CalendarEntry::timed(
+            calendar_id,
+            "Night shift",
+            date,
+            NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+            NaiveTime::from_hms_opt(6, 0, 0).unwrap(),
+        )
+        .with_end_date(date.succ_opt().unwrap());
+        state.entry_repo.create_entry(&shift).await.unwrap();
+
+        let response = create_app(state, &config)
+            .oneshot(
+                Request::builder()
+                    .uri(format!(
+                        "/api/entries?calendar_id={calendar_id}&highlighted_day=2024-01-15&before=0&after=1"
+                    ))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let days: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+
+        let first = &days[0]["entries"][0];
+        assert_eq!(first["segmentStartTime"], "22:00");
+        assert_eq!(first["segmentEndTime"], "24:00");
+        assert_eq!(first["continues"], true);
+        assert_eq!(first["continued"], false);
+
+        let second = &days[1]["entries"][0];
+        assert_eq!(second["segmentDate"], "2024-01-16");
+        assert_eq!(second["segmentStartTime"], "00:00");
+        assert_eq!(second["segmentEndTime"], "06:00");
+        assert_eq!(second["continued"], true);
+        assert_eq!(second["startTime"], "22:00");
+    }
+
+    #[cfg(not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")))]
+    #[tokio::test]
+    async fn test_list_entries_filtered_by_tags() {
+        use calendsync_core::calendar::CalendarEntry;
+        use chrono::NaiveDate;
+
+        let state = AppState::default();
+        let config = Config::default();
+        let calendar_id = uuid::Uuid::new_v4();
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let trip =
+            CalendarEntry::all_day(calendar_id, "Trip", date).with_tag("travel".parse().unwrap());
+        let shift =
+            CalendarEntry::all_day(calendar_id, "Shift", date).with_tag("oncall".parse().unwrap());
+        state.entry_repo.create_entry(&trip).await.unwrap();
+        state.entry_repo.create_entry(&shift).await.unwrap();
+
+        let response = create_app(state, &config)
+            .oneshot(
+                Request::builder()
+                    .uri(format!(
+                        "/api/entries?calendar_id={calendar_id}&highlighted_day=2024-01-15&before=0&after=0&tags=Travel,personal"
+                    ))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let days: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+        let entries = days[0]["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0]["title"], "Trip");
+        assert_eq!(entries[0]["tags"], serde_json::json!(["travel"]));
+    }
+
+    #[cfg(not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")))]
+    #[tokio::test]
+    async fn test_toggle_checklist_item() {
+        use calendsync_core::calendar::{CalendarEntry, ChecklistItem, TaskPriority};
+        use chrono::NaiveDate;
+
+        let state = AppState::default();
+        let config = Config::default();
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let task = CalendarEntry::task(uuid::Uuid::new_v4(), "Pack", date, false)
+            .with_priority(TaskPriority::High)
+            .with_checklist_item(ChecklistItem::new("Passport"))
+            .with_checklist_item(ChecklistItem::new("Charger"));
+        state.entry_repo.create_entry(&task).await.unwrap();
+        let app = create_app(state, &config);
+
+        let toggle = |query: &str| {
+            Request::builder()
+                .method("PATCH")
+                .uri(format!("/api/entries/{}/toggle{query}", task.id))
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app.clone().oneshot(toggle("?item=1")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let entry: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(entry["completed"], false);
+        assert_eq!(entry["priority"], "high");
+        assert_eq!(
+            entry["checklist"],
+            serde_json::json!([
+                {"text": "Passport", "completed": false},
+                {"text": "Charger", "completed": true},
+            ])
+        );
+
+        let response = app.oneshot(toggle("?item=2")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[cfg(not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")))]
+    #[tokio::test]
+    async fn test_create_entry_reports_conflicts() {
+        use calendsync_core::calendar::{Calendar, CalendarEntry};
+        use chrono::{NaiveDate, NaiveTime};
+
+        let state = AppState::default();
+        let config = Config::default();
+        let calendar = Calendar::new("Work", "#3B82F6");
+        state
+            .calendar_repo
+            .create_calendar(&calendar)
+            .await
+            .unwrap();
+        let standup = CalendarEntry::timed(
+            calendar.id,
+            "Standup",
+            NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+            NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            NaiveTime::from_hms_opt(10, 0, 0).unwrap(),
+        );
+        state.entry_repo.create_entry(&standup).await.unwrap();
+        let app = create_app(state, &config);
+
+        let create = |extra: &str| {
+            Request::builder()
+                .method("POST")
+                .uri("/api/entries")
+                .header("content-type", "application/x-www-form-urlencoded")
+                .body(Body::from(format!(
+                    "calendar_id={}&title=Review&start_date=2024-01-15&entry_type=timed&start_time=09:30&end_time=10:30{extra}",
+                    calendar.id
+                )))
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(create("&reject_on_conflict=true"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let response = app.oneshot(create("")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let entry: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let conflicts = entry["conflicts"].as_array().unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0]["id"], standup.id.to_string());
+    }
+
+    #[cfg(not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")))]
+    #[tokio::test]
+    async fn test_revert_entry_to_revision() {
+        use calendsync_core::calendar::{CalendarEntry, EntryRevision, RevisionAction};
+        use chrono::{NaiveDate, Utc};
+
+        let state = AppState::default();
+        let config = Config::default();
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let original = CalendarEntry::all_day(uuid::Uuid::new_v4(), "Standup", date);
+        let mut renamed = original.clone().with_location("Room 1");
+        renamed.title = "Daily standup".to_string();
+        state.entry_repo.create_entry(&renamed).await.unwrap();
+        let first = EntryRevision::new(1, RevisionAction::Created, original, None, Utc::now());
+        state
+            .entry_repo
+            .append_entry_revision(&first)
+            .await
+            .unwrap();
+
+        let post = |uri: String| {
+            create_app(state.clone(), &config).oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(uri)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+
+        let response = post(format!("/api/entries/{}/history/7/revert", renamed.id))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = post(format!("/api/entries/{}/history/1/revert", renamed.id))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let reverted = state
+            .entry_repo
+            .get_entry(renamed.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(reverted.title, "Standup");
+        assert_eq!(reverted.location, None);
+    }
+
+    #[cfg(not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")))]
+    #[tokio::test]
+    async fn test_update_entry_merges_with_base_revision() {
+        use calendsync_core::calendar::{Calendar, CalendarEntry, EntryRevision, RevisionAction};
+        use chrono::{NaiveDate, Utc};
+
+        let state = AppState::default();
+        let config = Config::default();
+        let calendar = Calendar::new("Work", "#3B82F6");
+        state
+            .calendar_repo
+            .create_calendar(&calendar)
+            .await
+            .unwrap();
+
+        // Someone moved the entry to Room 1 after revision 1
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let base = CalendarEntry::all_day(calendar.id, "Standup", date);
+        let current = base.clone().with_location("Room 1");
+        state.entry_repo.create_entry(&current).await.unwrap();
+        let first = EntryRevision::new(1, RevisionAction::Created, base, None, Utc::now());
+        state
+            .entry_repo
+            .append_entry_revision(&first)
+            .await
+            .unwrap();
+
+        let put = |body: &str| {
+            create_app(state.clone(), &config).oneshot(
+                Request::builder()
+                    .method("PUT")
+                    .uri(format!("/api/entries/{}", current.id))
+                    .header("content-type", "application/x-www-form-urlencoded")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+        };
+
+        // A different field merges cleanly
+        let response = put("title=Daily+standup&base_revision=1").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let saved = state
+            .entry_repo
+            .get_entry(current.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(saved.title, "Daily standup");
+        assert_eq!(saved.location.as_deref(), Some("Room 1"));
+
+        // The same field conflicts
+        let response = put("location=Room+2&base_revision=1").await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let response = put("title=Retro&base_revision=9").await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[cfg(not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")))]
+    #[tokio::test]
+    async fn test_move_entry_between_calendars() {
+        use calendsync_core::calendar::{Calendar, CalendarEntry};
+        use chrono::NaiveDate;
+
+        let state = AppState::default();
+        let config = Config::default();
+        let personal = Calendar::new("Personal", "#3B82F6");
+        let family = Calendar::new("Family", "#10B981");
+        for calendar in [&personal, &family] {
+            state.calendar_repo.create_calendar(calendar).await.unwrap();
+        }
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let entry = CalendarEntry::all_day(personal.id, "Dinner", date);
+        state.entry_repo.create_entry(&entry).await.unwrap();
+
+        let post = |calendar_id: uuid::Uuid| {
+            create_app(state.clone(), &config).oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/entries/{}/move", entry.id))
+                    .header("content-type", "application/x-www-form-urlencoded")
+                    .body(Body::from(format!("calendar_id={calendar_id}")))
+                    .unwrap(),
+            )
+        };
+
+        let response = post(family.id).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["id"], entry.id.to_string());
+        assert_eq!(json["calendarId"], family.id.to_string());
+
+        let saved = state.entry_repo.get_entry(entry.id).await.unwrap().unwrap();
+        assert_eq!(saved.calendar_id, family.id);
+
+        // Already there, and nowhere to go
+        let response = post(family.id).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = post(uuid::Uuid::new_v4()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[cfg(not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")))]
+    #[tokio::test]
+    async fn test_export_calendar_ics() {
+        use calendsync_core::calendar::{Calendar, CalendarEntry};
+        use chrono::{NaiveDate, NaiveTime};
+
+        let state = AppState::default();
+        let config = Config::default();
+        let calendar = Calendar::new("Team Offsite", "#3B82F6");
+        state
+            .calendar_repo
+            .create_calendar(&calendar)
+            .await
+            .unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let time = |h| NaiveTime::from_hms_opt(h, 0, 0).unwrap();
+        for entry in [
+            CalendarEntry::all_day(calendar.id, "Arrival", date),
+            CalendarEntry::timed(calendar.id, "Standup", date, time(9), time(10))
+                .with_recurrence("FREQ=DAILY;COUNT=3".parse().unwrap()),
+            CalendarEntry::task(calendar.id, "Book hotel", date, false),
+        ] {
+            state.entry_repo.create_entry(&entry).await.unwrap();
+        }
+
+        let export = |calendar_id: uuid::Uuid| {
+            create_app(state.clone(), &config).oneshot(
+                Request::builder()
+                    .uri(format!("/api/calendars/{calendar_id}/export.ics"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+
+        let response = export(calendar.id).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()["content-type"],
+            "text/calendar; charset=utf-8"
+        );
+        assert_eq!(
+            response.headers()["content-disposition"],
+            "attachment; filename=\"team-offsite.ics\""
+        );
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let ics = String::from_utf8(body.to_vec()).unwrap();
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+        assert_eq!(ics.matches("BEGIN:VTODO").count(), 1);
+        assert!(ics.contains("\r\nRRULE:FREQ=DAILY;COUNT=3\r\n"));
+
+        let response = export(uuid::Uuid::new_v4()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[cfg(not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")))]
+    #[tokio::test]
+    async fn test_import_calendar_ics() {
+        use calendsync_core::calendar::{import_entry_id, Calendar, ImportReport};
+
+        let state = AppState::default();
+        let config = Config::default();
+        let calendar = Calendar::new("Migrated", "#3B82F6");
+        state
+            .calendar_repo
+            .create_calendar(&calendar)
+            .await
+            .unwrap();
+
+        let import = |ics: String, query: &str| {
+            create_app(state.clone(), &config).oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/calendars/{}/import{query}", calendar.id))
+                    .header("content-type", "text/calendar")
+                    .body(Body::from(ics))
+                    .unwrap(),
+            )
+        };
+        let ics = |title: &str| {
+            format!(
+                "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n\
+                 BEGIN:VEVENT\r\nUID:standup@example.com\r\n\
+                 DTSTART:20240115T090000Z\r\nDTEND:20240115T091500Z\r\n\
+                 RRULE:FREQ=DAILY;COUNT=5\r\nEXDATE:20240117T090000Z\r\n\
+                 SUMMARY:{title}\r\nX-APPLE-TRAVEL-ADVISORY-BEHAVIOR:AUTOMATIC\r\n\
+                 END:VEVENT\r\n\
+                 BEGIN:VTODO\r\nUID:hotel@example.com\r\n\
+                 DUE;VALUE=DATE:20240110\r\nSUMMARY:Book hotel\r\nSTATUS:COMPLETED\r\n\
+                 END:VTODO\r\n\
+                 END:VCALENDAR\r\n"
+            )
+        };
+        let report = |response: axum::response::Response| async move {
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            serde_json::from_slice::<ImportReport>(&body).unwrap()
+        };
+        let standup_id = import_entry_id(calendar.id, "standup@example.com");
+
+        // A dry run reports without writing
+        let dry_run = report(import(ics("Standup"), "?dry_run=true").await.unwrap()).await;
+        assert!(dry_run.dry_run);
+        assert_eq!(dry_run.created, 2);
+        assert_eq!(dry_run.overrides, 1);
+        assert_eq!(dry_run.unsupported["X-APPLE-TRAVEL-ADVISORY-BEHAVIOR"], 1);
+        assert!(state
+            .entry_repo
+            .get_entry(standup_id)
+            .await
+            .unwrap()
+            .is_none());
+
+        let first = report(import(ics("Standup"), "").await.unwrap()).await;
+        assert_eq!(first.created, 2);
+        let standup = state
+            .entry_repo
+            .get_entry(standup_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(standup.calendar_id, calendar.id);
+        assert_eq!(
+            state
+                .entry_repo
+                .get_occurrence_overrides(standup_id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        let hotel = state
+            .entry_repo
+            .get_entry(import_entry_id(calendar.id, "hotel@example.com"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(hotel.kind.is_completed());
+
+        // Re-importing matches entries by UID instead of duplicating them
+        let again = report(import(ics("Standup"), "").await.unwrap()).await;
+        assert_eq!((again.created, again.updated, again.unchanged), (0, 0, 2));
+        let renamed = report(import(ics("Daily standup"), "").await.unwrap()).await;
+        assert_eq!(
+            (renamed.created, renamed.updated, renamed.unchanged),
+            (0, 1, 1)
+        );
+        let stored = state
+            .entry_repo
+            .get_stored_entries_by_calendar(calendar.id)
+            .await
+            .unwrap();
+        assert_eq!(stored.len(), 2);
+        assert!(stored.iter().any(|e| e.title == "Daily standup"));
+
+        let response = import("not a calendar".to_string(), "").await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[cfg(not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")))]
+    #[tokio::test]
+    async fn test_duplicate_calendar() {
+        use calendsync_core::calendar::{Calendar, CalendarEntry};
+        use calendsync_core::storage::DateRange;
+        use chrono::NaiveDate;
+
+        let state = AppState::default();
+        let config = Config::default();
+        let source = Calendar::new("Q1 Project", "#3B82F6");
+        state.calendar_repo.create_calendar(&source).await.unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let entry = CalendarEntry::all_day(source.id, "Kickoff", date);
+        state.entry_repo.create_entry(&entry).await.unwrap();
+
+        let post = |calendar_id: uuid::Uuid, body: &'static str| {
+            create_app(state.clone(), &config).oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/calendars/{calendar_id}/duplicate"))
+                    .header("content-type", "application/x-www-form-urlencoded")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+        };
+
+        let response = post(
+            source.id,
+            "name=Q2+Project&include_entries=true&shift_days=91",
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["calendar"]["name"], "Q2 Project");
+        assert_eq!(json["entries"], 1);
+
+        let copy_id: uuid::Uuid = json["calendar"]["id"].as_str().unwrap().parse().unwrap();
+        assert_ne!(copy_id, source.id);
+        let copied = state
+            .entry_repo
+            .get_entries_by_calendar(copy_id, DateRange::month(2024, 4))
+            .await
+            .unwrap();
+        assert_eq!(copied.len(), 1);
+        assert_eq!(
+            copied[0].start_date,
+            NaiveDate::from_ymd_opt(2024, 4, 15).unwrap()
+        );
+
+        let response = post(source.id, "shift_days=100000").await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = post(uuid::Uuid::new_v4(), "").await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[cfg(not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")))]
+    #[tokio::test]
+    async fn test_subscribed_calendar() {
+        use calendsync_core::calendar::Calendar;
+
+        // Stand-in for a remote calendar server
+        let feed = Router::new().route(
+            "/holidays.ics",
+            get(|| async { include_str!("../tests/fixtures/subscriptions/holidays-v1.ics") }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let feed_url = format!("http://{}/holidays.ics", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, feed).await });
+
+        let mut state = AppState::default();
+        state.subscription_allowed_hosts = vec!["127.0.0.1".to_string()];
+        let config = Config::default();
+        let post = |uri: String, body: String| {
+            create_app(state.clone(), &config).oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(uri)
+                    .header("content-type", "application/x-www-form-urlencoded")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+        };
+
+        let response = post(
+            "/api/calendars".to_string(),
+            "name=Holidays&color=%2310B981&subscription_url=ftp%3A%2F%2Fexample.com".to_string(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = post(
+            "/api/calendars".to_string(),
+            "name=Metadata&color=%2310B981&subscription_url=http%3A%2F%2F169.254.169.254%2F"
+                .to_string(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let calendar = Calendar::new("Holidays", "#10B981").with_subscription(&feed_url);
+        state
+            .calendar_repo
+            .create_calendar(&calendar)
+            .await
+            .unwrap();
+        let response = post(
+            format!("/api/calendars/{}/refresh", calendar.id),
+            String::new(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["created"], 3);
+
+        let plain = Calendar::new("Work", "#3B82F6");
+        state.calendar_repo.create_calendar(&plain).await.unwrap();
+        let response = post(
+            format!("/api/calendars/{}/refresh", plain.id),
+            String::new(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[cfg(not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")))]
+    #[tokio::test]
+    async fn test_batch_entries() {
+        use calendsync_core::calendar::{Calendar, CalendarEntry};
+        use chrono::NaiveDate;
+
+        let state = AppState::default();
+        let config = Config::default();
+        let calendar = Calendar::new("Semester", "#3B82F6");
+        state
+            .calendar_repo
+            .create_calendar(&calendar)
+            .await
+            .unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 9, 2).unwrap();
+        let physics = CalendarEntry::all_day(calendar.id, "Physics", date);
+        let chemistry = CalendarEntry::all_day(calendar.id, "Chemistry", date);
+        for entry in [&physics, &chemistry] {
+            state.entry_repo.create_entry(entry).await.unwrap();
+        }
+
+        let post = |body: serde_json::Value| {
+            create_app(state.clone(), &config).oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/entries/batch")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+        };
+
+        let response = post(serde_json::json!({
+            "operations": [
+                {
+                    "op": "create",
+                    "calendar_id": calendar.id,
+                    "title": "Biology",
+                    "start_date": "2024-09-03",
+                    "entry_type": "all_day",
+                },
+                { "op": "update", "id": physics.id, "version": 1, "title": "Physics I" },
+                { "op": "delete", "id": chemistry.id },
+            ]
+        }))
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["created"][0]["title"], "Biology");
+        assert_eq!(json["updated"][0]["title"], "Physics I");
+        assert_eq!(json["updated"][0]["version"], 2);
+        assert_eq!(json["deleted"][0], chemistry.id.to_string());
+
+        let saved = state
+            .entry_repo
+            .get_entry(physics.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(saved.title, "Physics I");
+        assert!(state
+            .entry_repo
+            .get_entry(chemistry.id)
+            .await
+            .unwrap()
+            .is_none());
+
+        // A stale version fails the whole batch
+        let response = post(serde_json::json!({
+            "operations": [
+                { "op": "update", "id": physics.id, "version": 1, "title": "Physics II" },
+            ]
+        }))
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+
+        let response = post(serde_json::json!({
+            "operations": [
+                { "op": "delete", "id": physics.id },
+                { "op": "update", "id": physics.id, "title": "Physics II" },
+            ]
+        }))
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = post(serde_json::json!({ "operations": [] })).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[cfg(not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")))]
+    #[tokio::test]
+    async fn test_update_entry_honours_if_match() {
+        use calendsync_core::calendar::CalendarEntry;
+        use chrono::NaiveDate;
+
+        let state = AppState::default();
+        let config = Config::default();
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let entry = CalendarEntry::all_day(uuid::Uuid::new_v4(), "Standup", date);
+        state.entry_repo.create_entry(&entry).await.unwrap();
+
+        let response = create_app(state.clone(), &config)
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/entries/{}", entry.id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["etag"], "\"1\"");
+
+        let put = |if_match: &str, body: &str| {
+            create_app(state.clone(), &config).oneshot(
+                Request::builder()
+                    .method("PUT")
+                    .uri(format!("/api/entries/{}", entry.id))
+                    .header("content-type", "application/x-www-form-urlencoded")
+                    .header("if-match", if_match)
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+        };
+
+        let response = put("\"1\"", "title=Daily+standup").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["version"], 2);
+
+        // A writer still holding version 1 is turned away
+        let response = put("\"1\"", "title=Retro").await.unwrap();
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+
+        let response = create_app(state.clone(), &config)
+            .oneshot(
+                Request::builder()
+                    .method("DELETE")
+                    .uri(format!("/api/entries/{}", entry.id))
+                    .header("if-match", "\"1\"")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+
+        let saved = state.entry_repo.get_entry(entry.id).await.unwrap().unwrap();
+        assert_eq!(saved.title, "Daily standup");
+        assert_eq!(saved.version, 2);
+    }
+
+    /// Reads an entry through the cached repository, so handlers see that
+    /// version, then has another writer store the next one behind the cache.
+    #[cfg(all(
+        feature = "inmemory",
+        feature = "memory",
+        not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))
+    ))]
+    async fn change_behind_cache(
+        state: &AppState,
+        entry: &calendsync_core::calendar::CalendarEntry,
+    ) {
+        state.entry_repo.get_entry(entry.id).await.unwrap().unwrap();
+        state.entry_storage.update_entry(entry).await.unwrap();
+    }
+
+    #[cfg(all(
+        feature = "inmemory",
+        feature = "memory",
+        not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))
+    ))]
+    #[tokio::test]
+    async fn test_writes_racing_a_change_after_the_check_fail() {
+        use calendsync_core::calendar::{CalendarEntry, RecurrenceRule};
+        use chrono::NaiveDate;
+
+        let state = AppState::new(&Config::default()).await.unwrap();
+        let config = Config::default();
+        let calendar_id = uuid::Uuid::new_v4();
+        let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let task = CalendarEntry::task(calendar_id, "Report", date, false);
+        let series = CalendarEntry::all_day(calendar_id, "Standup", date)
+            .with_recurrence(RecurrenceRule::weekly());
+        for entry in [&task, &series] {
+            state.entry_storage.create_entry(entry).await.unwrap();
+        }
+
+        let send = |method: &str, uri: String, body: &str| {
+            create_app(state.clone(), &config).oneshot(
+                Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .header("content-type", "application/x-www-form-urlencoded")
+                    .header("if-match", "\"1\"")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+        };
+
+        // Each request passes If-Match against the version it read, but
+        // another writer has stored version 2 by the time it writes
+        change_behind_cache(&state, &task).await;
+        let response = send("PATCH", format!("/api/entries/{}/toggle", task.id), "")
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+
+        change_behind_cache(&state, &series).await;
+        let response = send(
+            "PUT",
+            format!("/api/entries/{}", series.id),
+            "title=Retro&scope=following&occurrence_date=2024-01-15",
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+        let response = send(
+            "PUT",
+            format!("/api/entries/{}", series.id),
+            "title=Retro&scope=this&occurrence_date=2024-01-15",
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+        for scope in ["this", "following"] {
+            let uri = format!(
+                "/api/entries/{}?scope={scope}&occurrence_date=2024-01-15",
+                series.id
+            );
+            let response = send("DELETE", uri, "").await.unwrap();
+            assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+        }
+        let response = send("DELETE", format!("/api/entries/{}", series.id), "")
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+
+        // Nothing was written over the other writer's version
+        let stored = state
+            .entry_storage
+            .get_entry(task.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.kind, task.kind);
+        assert_eq!(stored.version, 2);
+        let stored = state
+            .entry_storage
+            .get_entry(series.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.recurrence, series.recurrence);
+        assert_eq!(stored.version, 2);
+        assert!(state
+            .entry_storage
+            .get_occurrence_overrides(series.id)
+            .await
+            .unwrap()
+            .is_empty());
+        let entries = state
+            .entry_storage
+            .get_stored_entries_by_calendar(calendar_id)
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 2);
+
+        // An entry deleted after the check is gone rather than a server error
+        state.entry_repo.get_entry(task.id).await.unwrap().unwrap();
+        state.entry_storage.delete_entry(task.id).await.unwrap();
+        let response = create_app(state.clone(), &config)
+            .oneshot(
+                Request::builder()
+                    .method("PATCH")
+                    .uri(format!("/api/entries/{}/toggle", task.id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[cfg(all(
+        feature = "inmemory",
+        feature = "memory",
+        not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))
+    ))]
+    #[tokio::test]
+    async fn test_occurrence_edits_show_in_history_and_revert() {
+        use calendsync_core::calendar::{CalendarEntry, EntryRevision, RecurrenceRule};
+        use chrono::NaiveDate;
+
+        let state = AppState::new(&Config::default()).await.unwrap();
+        let config = Config::default();
+        let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let series = CalendarEntry::all_day(uuid::Uuid::new_v4(), "Standup", date)
+            .with_recurrence(RecurrenceRule::weekly());
+        state.entry_repo.create_entry(&series).await.unwrap();
+
+        let send = |method: &str, uri: String, body: &str| {
+            create_app(state.clone(), &config).oneshot(
+                Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .header("content-type", "application/x-www-form-urlencoded")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+        };
+
+        let response = send(
+            "PUT",
+            format!("/api/entries/{}", series.id),
+            "title=Retro&scope=this&occurrence_date=2024-01-08",
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = send(
+            "DELETE",
+            format!(
+                "/api/entries/{}?scope=this&occurrence_date=2024-01-15",
+                series.id
+            ),
+            "",
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Each occurrence edit is an edit of the series
+        let stored = state
+            .entry_repo
+            .get_entry(series.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.version, 3);
+        let response = send("GET", format!("/api/entries/{}/history", series.id), "")
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let history: Vec<EntryRevision> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(history.len(), 3);
+        for revision in &history[1..] {
+            assert_eq!(revision.changed_fields, vec!["overrides".to_string()]);
+        }
+        assert_eq!(history[2].overrides.as_ref().map(Vec::len), Some(2));
+
+        // Reverting to the edit of 2024-01-08 brings back the cancelled occurrence
+        let response = send(
+            "POST",
+            format!("/api/entries/{}/history/2/revert", series.id),
+            "",
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let overrides = state
+            .entry_repo
+            .get_occurrence_overrides(series.id)
+            .await
+            .unwrap();
+        assert_eq!(Some(overrides), history[1].overrides);
+        let history = state
+            .entry_repo
+            .get_entry_revisions(series.id)
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 4);
+        assert_eq!(history[3].changed_fields, vec!["overrides".to_string()]);
+    }
+
+    /// Signs `user` in to a state with in-memory auth, returning the bearer
+    /// token of their session.
+    #[cfg(all(feature = "inmemory", feature = "memory", feature = "auth-mock"))]
+    async fn sign_in(state: &mut AppState, user: &calendsync_core::calendar::User) -> String {
+        use calendsync_auth::{AuthConfig, AuthState, SessionStore};
+        use calendsync_core::auth::{OidcProvider, Session, SessionId, SessionRepository};
+        use chrono::{Duration, Utc};
+
+        let sessions = std::sync::Arc::new(SessionStore::new());
+        let config = AuthConfig {
+            google: None,
+            apple: None,
+            session_ttl: std::time::Duration::from_secs(3600),
+            base_url: "http://localhost:3000".parse().unwrap(),
+            cookie_name: "session".to_string(),
+            cookie_secure: false,
+        };
+        let auth = AuthState::new(
+            sessions.clone(),
+            state.user_repo.clone(),
+            state.calendar_repo.clone(),
+            state.membership_repo.clone(),
+            config,
+        )
+        .await
+        .unwrap();
+        state.auth = Some(auth);
+
+        state.user_repo.create_user(user).await.unwrap();
+        let token = format!("session-{}", user.id);
+        let now = Utc::now();
+        let session = Session {
+            id: SessionId::new(token.clone()),
+            user_id: user.id.to_string(),
+            provider: OidcProvider::Google,
+            created_at: now,
+            expires_at: now + Duration::hours(1),
+        };
+        sessions.create_session(&session).await.unwrap();
+        token
+    }
+
+    #[cfg(all(feature = "inmemory", feature = "memory", feature = "auth-mock"))]
+    #[tokio::test]
+    async fn test_batch_entries_checks_access_before_anything_else() {
+        use calendsync_core::calendar::{
+            Calendar, CalendarEntry, CalendarMembership, CalendarRole, User,
+        };
+        use chrono::NaiveDate;
+
+        let mut state = AppState::new(&Config::default()).await.unwrap();
+        let config = Config::default();
+        let user = User::new("Grace", "grace@example.com");
+        let token = sign_in(&mut state, &user).await;
+
+        // Someone else's calendar, which the user is not a member of
+        let private = Calendar::new("Private", "#3B82F6");
+        state.calendar_repo.create_calendar(&private).await.unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 9, 2).unwrap();
+        let hidden = CalendarEntry::all_day(private.id, "Interview", date);
+        state.entry_repo.create_entry(&hidden).await.unwrap();
+
+        let own = Calendar::new("Work", "#10B981");
+        state.calendar_repo.create_calendar(&own).await.unwrap();
+        state
+            .membership_repo
+            .create_membership(&CalendarMembership::new(
+                own.id,
+                user.id,
+                CalendarRole::Owner,
+            ))
+            .await
+            .unwrap();
+        let visible = CalendarEntry::all_day(own.id, "Standup", date);
+        state.entry_repo.create_entry(&visible).await.unwrap();
+
+        let post = |operations: serde_json::Value| {
+            create_app(state.clone(), &config).oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/entries/batch")
+                    .header("authorization", format!("Bearer {token}"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({ "operations": operations }).to_string(),
+                    ))
+                    .unwrap(),
+            )
+        };
+        let read = |response: axum::response::Response| async move {
+            let status = response.status();
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            (status, String::from_utf8(body.to_vec()).unwrap())
+        };
+
+        // A stale version or an attendee on an entry the user can't write to
+        // reads exactly like an entry that doesn't exist
+        let missing = uuid::Uuid::new_v4();
+        for id in [hidden.id, missing] {
+            let (status, message) = read(
+                post(serde_json::json!([
+                    { "op": "update", "id": visible.id, "title": "Daily standup" },
+                    {
+                        "op": "update",
+                        "id": id,
+                        "version": 7,
+                        "attendees": "grace@example.com",
+                    },
+                ]))
+                .await
+                .unwrap(),
+            )
+            .await;
+            assert_eq!(status, StatusCode::NOT_FOUND);
+            assert_eq!(message, format!("Operation 1: entry {id} not found"));
+        }
+        let (status, _) = read(
+            post(serde_json::json!([{ "op": "delete", "id": hidden.id }]))
+                .await
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        // Nothing in the batch was applied
+        let stored = state
+            .entry_repo
+            .get_entry(hidden.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.version, 1);
+        assert!(stored.attendees.is_empty());
+        let stored = state
+            .entry_repo
+            .get_entry(visible.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.title, "Standup");
+
+        // The user's own entries still go through, and their versions are checked
+        let (status, _) = read(
+            post(serde_json::json!([
+                { "op": "update", "id": visible.id, "version": 7, "title": "Daily standup" },
+            ]))
+            .await
+            .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::PRECONDITION_FAILED);
+        let (status, _) = read(
+            post(serde_json::json!([
+                { "op": "update", "id": visible.id, "version": 1, "title": "Daily standup" },
+            ]))
+            .await
+            .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
 }
@@ -0,0 +1,685 @@
+//! Dumping a server's data to a backup and restoring it.
+//!
+//! Both walk the repository traits, so they work the same on every storage
+//! backend. A restore keeps every record's ID and skips records the store
+//! already holds unchanged, so an interrupted restore can simply be run
+//! again; a `Checkpoint` lets it skip the records it got through instead of
+//! reading them back. Users, calendars, memberships and entries stored with
+//! different contents are left as they are; settings and occurrence
+//! overrides are replaced. Every restore ends with a verification pass that
+//! reads the store back and compares it with the backup.
+//!
+//! Records are written to the storage behind the cache, so restored entries
+//! get no revision history and open clients aren't sent an event per entry.
+//! Once the records are written, the cache of every calendar in the backup is
+//! invalidated and each calendar gets one `entry_batch` event.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinSet;
+use uuid::Uuid;
+
+use calendsync_core::backup::{
+    verify_backup, Backup, BackupCounts, BackupRecord, RestoreReport, SettingsRecord,
+};
+use calendsync_core::cache::{calendar_entries_pattern, calendar_key, Cache, CachePubSub};
+use calendsync_core::calendar::{CalendarEntry, CalendarEvent};
+use calendsync_core::storage::{
+    CalendarRepository, EntryRepository, MembershipRepository, RepositoryError, Result,
+    SettingsRepository, UserRepository,
+};
+
+use crate::state::AppState;
+
+/// The repositories a backup is read from or restored into, and the cache
+/// and channel in front of them.
+#[derive(Clone)]
+pub struct BackupStore {
+    pub users: Arc<dyn UserRepository>,
+    pub calendars: Arc<dyn CalendarRepository>,
+    pub memberships: Arc<dyn MembershipRepository>,
+    pub settings: Arc<dyn SettingsRepository>,
+    pub entries: Arc<dyn EntryRepository>,
+    pub cache: Arc<dyn Cache>,
+    pub pubsub: Arc<dyn CachePubSub>,
+}
+
+impl BackupStore {
+    /// The storage the server runs on, bypassing its cache.
+    pub fn from_state(state: &AppState) -> Self {
+        Self {
+            users: state.user_repo.clone(),
+            calendars: state.calendar_storage.clone(),
+            memberships: state.membership_repo.clone(),
+            settings: state.settings_repo.clone(),
+            entries: state.entry_storage.clone(),
+            cache: state.cache.clone(),
+            pubsub: state.cache_pubsub.clone(),
+        }
+    }
+}
+
+/// How a restore writes its records.
+#[derive(Debug, Clone)]
+pub struct RestoreOptions {
+    /// Records written concurrently. A batch never mixes kinds, so records
+    /// are still written after the ones they refer to.
+    pub batch_size: usize,
+    /// Wait between batches, to stay under the store's write capacity.
+    pub pause: Duration,
+}
+
+impl Default for RestoreOptions {
+    fn default() -> Self {
+        Self {
+            batch_size: 1,
+            pause: Duration::ZERO,
+        }
+    }
+}
+
+/// How far a restore got, taken after every batch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Checkpoint {
+    /// When the backup being restored was taken.
+    pub backup_created_at: DateTime<Utc>,
+    /// Records of the backup gone through, in order.
+    pub done: usize,
+    pub restored: BackupCounts,
+    pub skipped: BackupCounts,
+    pub conflicts: BackupCounts,
+}
+
+impl Checkpoint {
+    /// A checkpoint at the start of `backup`.
+    pub fn new(backup: &Backup) -> Self {
+        Self {
+            backup_created_at: backup.created_at,
+            done: 0,
+            restored: BackupCounts::default(),
+            skipped: BackupCounts::default(),
+            conflicts: BackupCounts::default(),
+        }
+    }
+
+    /// Returns whether this checkpoint was taken restoring `backup`.
+    pub fn is_for(&self, backup: &Backup) -> bool {
+        self.backup_created_at == backup.created_at && self.done <= backup.counts().total()
+    }
+}
+
+/// What restoring a single record did.
+enum Outcome {
+    Restored,
+    Skipped,
+    Conflict,
+}
+
+/// Reads every user, live calendar, membership, member's settings, stored
+/// entry and occurrence override.
+pub async fn dump(store: &BackupStore) -> Result<Backup> {
+    let mut backup = Backup::new(Utc::now());
+    backup.users = store.users.list_users().await?;
+    backup.calendars = store.calendars.list_calendars().await?;
+
+    for calendar in &backup.calendars {
+        for (user, _) in store
+            .memberships
+            .get_users_for_calendar(calendar.id)
+            .await?
+        {
+            if let Some(membership) = store
+                .memberships
+                .get_membership(calendar.id, user.id)
+                .await?
+            {
+                backup.memberships.push(membership);
+            }
+            if let Some(settings) = store.settings.get_settings(calendar.id, user.id).await? {
+                backup.settings.push(SettingsRecord {
+                    calendar_id: calendar.id,
+                    user_id: user.id,
+                    settings,
+                });
+            }
+        }
+
+        let entries = store
+            .entries
+            .get_stored_entries_by_calendar(calendar.id)
+            .await?;
+        for entry in entries.iter().filter(|e| e.is_recurring()) {
+            let overrides = store.entries.get_occurrence_overrides(entry.id).await?;
+            backup.overrides.extend(overrides);
+        }
+        backup.entries.extend(entries);
+    }
+
+    backup.sort();
+    Ok(backup)
+}
+
+/// Counts the live entries of every calendar with the store's own count
+/// rather than through `dump`, so a backup missing entries can be told apart.
+pub async fn count_entries(store: &BackupStore) -> Result<usize> {
+    let mut count = 0;
+    for calendar in store.calendars.list_calendars().await? {
+        count += store.entries.count_entries_by_calendar(calendar.id).await?;
+    }
+    Ok(count)
+}
+
+/// Writes the records of a validated backup, in order, then verifies them.
+///
+/// Stops at the first record that can't be written.
+#[cfg_attr(
+    not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")),
+    allow(dead_code)
+)]
+pub async fn restore(store: &BackupStore, backup: &Backup) -> Result<RestoreReport> {
+    resume(store, backup, &RestoreOptions::default(), None, |_| {}).await
+}
+
+/// Like `restore`, but writes records in batches and carries on from
+/// `checkpoint` when it was taken restoring the same backup. `save` gets a
+/// new checkpoint after every batch.
+///
+/// Stops at the first batch with a record that can't be written; the
+/// records it did write are skipped when the restore is resumed.
+pub async fn resume(
+    store: &BackupStore,
+    backup: &Backup,
+    options: &RestoreOptions,
+    checkpoint: Option<Checkpoint>,
+    mut save: impl FnMut(&Checkpoint),
+) -> Result<RestoreReport> {
+    let mut progress = checkpoint
+        .filter(|c| c.is_for(backup))
+        .unwrap_or_else(|| Checkpoint::new(backup));
+    let records = backup.records();
+    let batch_size = options.batch_size.max(1);
+
+    let mut added: BTreeMap<Uuid, Vec<CalendarEntry>> = BTreeMap::new();
+    let mut pending = &records[progress.done..];
+    while let Some(first) = pending.first() {
+        let kind = first.kind();
+        let len = pending
+            .iter()
+            .take(batch_size)
+            .take_while(|record| record.kind() == kind)
+            .count();
+        let (batch, rest) = pending.split_at(len);
+
+        let mut tasks = JoinSet::new();
+        for record in batch {
+            let store = store.clone();
+            let record = record.clone();
+            tasks.spawn(async move {
+                let outcome = restore_record(&store, &record).await?;
+                Ok::<_, RepositoryError>((outcome, record))
+            });
+        }
+        let mut outcomes = Vec::with_capacity(len);
+        let mut failure = None;
+        while let Some(joined) = tasks.join_next().await {
+            match joined.map_err(|e| RepositoryError::QueryFailed(e.to_string())) {
+                Ok(Ok(outcome)) => outcomes.push(outcome),
+                Ok(Err(e)) | Err(e) => failure = failure.or(Some(e)),
+            }
+        }
+        if let Some(e) = failure {
+            // The batches before this one were written
+            notify_restored(store, backup, added).await;
+            return Err(e);
+        }
+
+        for (outcome, record) in outcomes {
+            if let (Outcome::Restored, BackupRecord::Entry(entry)) = (&outcome, record) {
+                added.entry(entry.calendar_id).or_default().push(entry);
+            }
+            let counts = match outcome {
+                Outcome::Restored => &mut progress.restored,
+                Outcome::Skipped => &mut progress.skipped,
+                Outcome::Conflict => &mut progress.conflicts,
+            };
+            counts.add(kind);
+        }
+        progress.done += len;
+        save(&progress);
+
+        pending = rest;
+        if !pending.is_empty() && !options.pause.is_zero() {
+            tokio::time::sleep(options.pause).await;
+        }
+    }
+    notify_restored(store, backup, added).await;
+
+    let stored = dump(store).await?;
+    Ok(RestoreReport {
+        restored: progress.restored,
+        skipped: progress.skipped,
+        conflicts: progress.conflicts,
+        mismatches: verify_backup(backup, &stored),
+    })
+}
+
+async fn restore_record(store: &BackupStore, record: &BackupRecord) -> Result<Outcome> {
+    match record {
+        BackupRecord::User(user) => match store.users.create_user(user).await {
+            Err(RepositoryError::AlreadyExists { .. }) => {
+                Ok(compare(store.users.get_user(user.id).await?, user))
+            }
+            result => result.map(|()| Outcome::Restored),
+        },
+        BackupRecord::Calendar(calendar) => match store.calendars.create_calendar(calendar).await {
+            Err(RepositoryError::AlreadyExists { .. }) => Ok(compare(
+                store.calendars.get_calendar(calendar.id).await?,
+                calendar,
+            )),
+            result => result.map(|()| Outcome::Restored),
+        },
+        BackupRecord::Membership(membership) => {
+            match store.memberships.create_membership(membership).await {
+                Err(RepositoryError::AlreadyExists { .. }) => Ok(compare(
+                    store
+                        .memberships
+                        .get_membership(membership.calendar_id, membership.user_id)
+                        .await?,
+                    membership,
+                )),
+                result => result.map(|()| Outcome::Restored),
+            }
+        }
+        BackupRecord::Settings(record) => {
+            let stored = store
+                .settings
+                .get_settings(record.calendar_id, record.user_id)
+                .await?;
+            if stored.as_ref() == Some(&record.settings) {
+                return Ok(Outcome::Skipped);
+            }
+            store
+                .settings
+                .upsert_settings(record.calendar_id, record.user_id, &record.settings)
+                .await?;
+            Ok(Outcome::Restored)
+        }
+        BackupRecord::Entry(entry) => match store.entries.create_entry(entry).await {
+            Err(RepositoryError::AlreadyExists { .. }) => {
+                Ok(compare(store.entries.get_entry(entry.id).await?, entry))
+            }
+            result => result.map(|()| Outcome::Restored),
+        },
+        BackupRecord::Override(occurrence) => {
+            let stored = store
+                .entries
+                .get_occurrence_overrides(occurrence.series_id)
+                .await?;
+            if stored.contains(occurrence) {
+                return Ok(Outcome::Skipped);
+            }
+            store.entries.upsert_occurrence_override(occurrence).await?;
+            Ok(Outcome::Restored)
+        }
+    }
+}
+
+/// Invalidates the cache of every calendar in `backup` and sends each one
+/// that got entries back a single `entry_batch` event.
+async fn notify_restored(
+    store: &BackupStore,
+    backup: &Backup,
+    mut added: BTreeMap<Uuid, Vec<CalendarEntry>>,
+) {
+    for calendar in &backup.calendars {
+        let invalidated = async {
+            store.cache.delete(&calendar_key(calendar.id)).await?;
+            store
+                .cache
+                .delete_pattern(&calendar_entries_pattern(calendar.id))
+                .await
+        }
+        .await;
+        if let Err(err) = invalidated {
+            tracing::warn!(
+                calendar_id = %calendar.id,
+                error = %err,
+                "Failed to invalidate restored calendar cache"
+            );
+        }
+
+        let Some(entries) = added.remove(&calendar.id) else {
+            continue;
+        };
+        let event = CalendarEvent::entry_batch(entries, Vec::new(), Vec::new());
+        if let Err(err) = store.pubsub.publish(calendar.id, &event).await {
+            tracing::warn!(
+                calendar_id = %calendar.id,
+                error = %err,
+                "Failed to publish entry_batch event"
+            );
+        }
+    }
+}
+
+/// Classifies a record that couldn't be created because its ID is taken.
+fn compare<T: PartialEq>(stored: Option<T>, record: &T) -> Outcome {
+    if stored.as_ref() == Some(record) {
+        Outcome::Skipped
+    } else {
+        Outcome::Conflict
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calendsync_core::calendar::{
+        Calendar, CalendarEntry, CalendarMembership, CalendarSettings, OccurrenceOverride,
+        RecurrenceRule, User,
+    };
+    use chrono::NaiveDate;
+
+    /// Seeds a store with one record of every kind.
+    async fn seed(store: &BackupStore) {
+        let user = User::new("Alice", "alice@example.com");
+        let calendar = Calendar::new("Work", "#3B82F6");
+        let date = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        let series = CalendarEntry::all_day(calendar.id, "Standup", date)
+            .with_recurrence(RecurrenceRule::daily());
+        let settings = CalendarSettings {
+            show_tasks: false,
+            ..CalendarSettings::default()
+        };
+
+        store.users.create_user(&user).await.unwrap();
+        store.calendars.create_calendar(&calendar).await.unwrap();
+        store
+            .memberships
+            .create_membership(&CalendarMembership::owner(calendar.id, user.id))
+            .await
+            .unwrap();
+        store
+            .settings
+            .upsert_settings(calendar.id, user.id, &settings)
+            .await
+            .unwrap();
+        store.entries.create_entry(&series).await.unwrap();
+        store
+            .entries
+            .upsert_occurrence_override(&OccurrenceOverride::cancelled(
+                series.id,
+                date.succ_opt().unwrap(),
+            ))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_dump_and_restore_round_trip() {
+        let source = BackupStore::from_state(&AppState::default());
+        seed(&source).await;
+
+        let backup = dump(&source).await.unwrap();
+        let counts = backup.counts();
+        assert_eq!(counts.total(), 6);
+        assert_eq!(counts.overrides, 1);
+        assert_eq!(count_entries(&source).await.unwrap(), counts.entries);
+
+        let backup = Backup::from_jsonl(&backup.to_jsonl().unwrap()).unwrap();
+        let target = BackupStore::from_state(&AppState::default());
+        let report = restore(&target, &backup).await.unwrap();
+
+        assert!(report.is_verified());
+        assert_eq!(report.restored, counts);
+        assert_eq!(dump(&target).await.unwrap().records(), backup.records());
+    }
+
+    #[tokio::test]
+    async fn test_resume_batches_and_checkpoints() {
+        let source = BackupStore::from_state(&AppState::default());
+        seed(&source).await;
+        let backup = dump(&source).await.unwrap();
+
+        let target = BackupStore::from_state(&AppState::default());
+        let options = RestoreOptions {
+            batch_size: 4,
+            ..RestoreOptions::default()
+        };
+        let mut checkpoints = Vec::new();
+        let report = resume(&target, &backup, &options, None, |c| {
+            checkpoints.push(c.clone())
+        })
+        .await
+        .unwrap();
+
+        // One record of each kind, and batches never mix kinds
+        assert_eq!(checkpoints.len(), 6);
+        assert_eq!(checkpoints[5].done, 6);
+        assert_eq!(checkpoints[5].restored, backup.counts());
+        assert!(report.is_verified());
+    }
+
+    #[tokio::test]
+    async fn test_resume_skips_checkpointed_records() {
+        let source = BackupStore::from_state(&AppState::default());
+        seed(&source).await;
+        let backup = dump(&source).await.unwrap();
+
+        let mut checkpoint = Checkpoint::new(&backup);
+        checkpoint.done = 1;
+        checkpoint.restored.users = 1;
+
+        let target = BackupStore::from_state(&AppState::default());
+        let report = resume(
+            &target,
+            &backup,
+            &RestoreOptions::default(),
+            Some(checkpoint),
+            |_| {},
+        )
+        .await
+        .unwrap();
+
+        // The user was taken as done, so it was never written
+        assert_eq!(report.restored, backup.counts());
+        assert!(target.users.list_users().await.unwrap().is_empty());
+        assert_eq!(target.calendars.list_calendars().await.unwrap().len(), 1);
+        assert!(!report.is_verified());
+    }
+
+    #[tokio::test]
+    async fn test_resume_ignores_checkpoint_of_other_backup() {
+        let source = BackupStore::from_state(&AppState::default());
+        seed(&source).await;
+        let backup = dump(&source).await.unwrap();
+
+        let mut checkpoint = Checkpoint::new(&backup);
+        checkpoint.backup_created_at -= chrono::Duration::hours(1);
+        checkpoint.done = 2;
+
+        let target = BackupStore::from_state(&AppState::default());
+        let report = resume(
+            &target,
+            &backup,
+            &RestoreOptions::default(),
+            Some(checkpoint),
+            |_| {},
+        )
+        .await
+        .unwrap();
+
+        assert!(report.is_verified());
+    }
+
+    #[cfg(feature = "inmemory")]
+    fn inmemory_store() -> BackupStore {
+        let repo = Arc::new(crate::storage::InMemoryRepository::new());
+        BackupStore {
+            users: repo.clone(),
+            calendars: repo.clone(),
+            memberships: repo.clone(),
+            settings: repo.clone(),
+            entries: repo,
+            cache: Arc::new(crate::cache::memory::MemoryCache::new(100)),
+            pubsub: Arc::new(crate::cache::memory::MemoryPubSub::new()),
+        }
+    }
+
+    #[cfg(all(feature = "sqlite", feature = "memory"))]
+    async fn sqlite_store() -> BackupStore {
+        let repo = Arc::new(
+            crate::storage::SqliteRepository::new_in_memory()
+                .await
+                .unwrap(),
+        );
+        BackupStore {
+            users: repo.clone(),
+            calendars: repo.clone(),
+            memberships: repo.clone(),
+            settings: repo.clone(),
+            entries: repo,
+            cache: Arc::new(crate::cache::memory::MemoryCache::new(100)),
+            pubsub: Arc::new(crate::cache::memory::MemoryPubSub::new()),
+        }
+    }
+
+    #[cfg(all(feature = "sqlite", feature = "memory"))]
+    #[tokio::test]
+    async fn test_sqlite_round_trip_through_another_store() {
+        let source = sqlite_store().await;
+        seed(&source).await;
+        let owner = source.users.list_users().await.unwrap()[0].id;
+        let personal = Calendar::new("Personal", "#10B981").as_default();
+        source.calendars.create_calendar(&personal).await.unwrap();
+        source
+            .memberships
+            .create_membership(&CalendarMembership::owner(personal.id, owner))
+            .await
+            .unwrap();
+
+        let backup = Backup::from_jsonl(&dump(&source).await.unwrap().to_jsonl().unwrap()).unwrap();
+        assert!(backup.calendars.iter().any(|c| c.is_default));
+        assert_eq!(count_entries(&source).await.unwrap(), backup.entries.len());
+
+        // SQLite to the in-memory test store, and back into a fresh database
+        let memory = BackupStore::from_state(&AppState::default());
+        let report = restore(&memory, &backup).await.unwrap();
+        assert!(report.is_verified());
+        assert_eq!(report.restored, backup.counts());
+
+        let copy = Backup::from_jsonl(&dump(&memory).await.unwrap().to_jsonl().unwrap()).unwrap();
+        assert_eq!(copy.records(), backup.records());
+
+        let target = sqlite_store().await;
+        let report = restore(&target, &copy).await.unwrap();
+        assert!(report.is_verified());
+        assert_eq!(report.restored, backup.counts());
+        assert_eq!(dump(&target).await.unwrap().records(), backup.records());
+    }
+
+    #[cfg(feature = "inmemory")]
+    #[tokio::test]
+    async fn test_restore_again_skips_everything() {
+        let source = inmemory_store();
+        seed(&source).await;
+        let backup = dump(&source).await.unwrap();
+
+        let report = restore(&source, &backup).await.unwrap();
+
+        assert!(report.is_verified());
+        assert_eq!(report.restored.total(), 0);
+        assert_eq!(report.skipped, backup.counts());
+    }
+
+    #[cfg(feature = "inmemory")]
+    #[tokio::test]
+    async fn test_restore_reports_conflicts() {
+        use calendsync_core::backup::{BackupMismatch, MismatchKind, RecordKind};
+
+        let source = inmemory_store();
+        seed(&source).await;
+        let backup = dump(&source).await.unwrap();
+
+        let target = inmemory_store();
+        let mut renamed = backup.calendars[0].clone();
+        renamed.name = "Renamed".to_string();
+        target.calendars.create_calendar(&renamed).await.unwrap();
+
+        let report = restore(&target, &backup).await.unwrap();
+
+        assert_eq!(report.conflicts.calendars, 1);
+        assert_eq!(report.restored.total(), backup.counts().total() - 1);
+        assert_eq!(
+            report.mismatches,
+            vec![BackupMismatch {
+                kind: RecordKind::Calendar,
+                id: renamed.id.to_string(),
+                problem: MismatchKind::Different,
+            }]
+        );
+        assert!(!report.is_verified());
+    }
+
+    #[cfg(all(feature = "inmemory", feature = "memory"))]
+    #[tokio::test]
+    async fn test_restore_bypasses_revisions_and_notifies_once() {
+        use calendsync_core::calendar::CalendarEvent;
+
+        let source = BackupStore::from_state(&AppState::default());
+        seed(&source).await;
+        let mut backup = dump(&source).await.unwrap();
+        let calendar_id = backup.calendars[0].id;
+        let date = NaiveDate::from_ymd_opt(2024, 3, 6).unwrap();
+        backup
+            .entries
+            .push(CalendarEntry::all_day(calendar_id, "Retro", date));
+
+        // The cached repositories would record a revision and publish an
+        // event per entry
+        let state = AppState::new(&crate::config::Config::default())
+            .await
+            .unwrap();
+        let range = calendsync_core::storage::DateRange {
+            start: date,
+            end: date,
+        };
+        assert!(state
+            .entry_repo
+            .get_entries_by_calendar(calendar_id, range)
+            .await
+            .unwrap()
+            .is_empty());
+        let mut receiver = state.cache_pubsub.subscribe(calendar_id).await.unwrap();
+
+        let report = restore(&BackupStore::from_state(&state), &backup)
+            .await
+            .unwrap();
+        assert!(report.is_verified());
+
+        let events: Vec<_> = std::iter::from_fn(|| receiver.try_recv().ok()).collect();
+        assert_eq!(events.len(), 1);
+        assert!(matches!(&events[0], CalendarEvent::EntryBatch { added, .. } if added.len() == 2));
+        for entry in &backup.entries {
+            let history = state
+                .entry_repo
+                .get_entry_revisions(entry.id)
+                .await
+                .unwrap();
+            assert!(history.is_empty());
+        }
+
+        // The range cached before the restore was invalidated
+        let entries = state
+            .entry_repo
+            .get_entries_by_calendar(calendar_id, range)
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 2);
+    }
+}
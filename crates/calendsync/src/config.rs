@@ -1,5 +1,7 @@
 use std::{env, time::Duration};
 
+use calendsync_core::calendar::DEFAULT_TRASH_RETENTION_DAYS;
+
 /// Application configuration loaded from environment variables.
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -29,6 +31,25 @@ pub struct Config {
     /// Note: Only used when the `dev-annotations` feature is enabled.
     #[allow(dead_code)]
     pub dev_annotations_db_path: String,
+    /// How often the reminder scheduler checks for due reminders, in seconds (default: 30)
+    pub reminder_poll_interval_seconds: u64,
+    /// How far back the scheduler looks for reminders missed while the server
+    /// was down, in seconds (default: 86,400)
+    pub reminder_lookback_seconds: u64,
+    /// Days deleted calendars and entries stay in the trash before they are
+    /// purged for good (default: 30)
+    pub trash_retention_days: u32,
+    /// How often expired trash is purged, in seconds (default: 3,600)
+    pub trash_purge_interval_seconds: u64,
+    /// How often subscribed calendars are refreshed from their feeds, in
+    /// seconds (default: 3,600)
+    pub subscription_refresh_interval_seconds: u64,
+    /// Hosts feeds may be fetched from even though they are, or resolve to,
+    /// private or loopback addresses, lowercased (comma-separated, default: none)
+    pub subscription_allowed_hosts: Vec<String>,
+    /// Emails of the users allowed to back up and restore the whole server,
+    /// lowercased (comma-separated, default: none)
+    pub admin_emails: Vec<String>,
 }
 
 impl Config {
@@ -42,6 +63,13 @@ impl Config {
     /// - `REDIS_URL` - Redis connection URL (default: "redis://localhost:6379")
     /// - `AUTH_SQLITE_PATH` - Auth sessions SQLite path (default: "data/sessions.db")
     /// - `CORS_ORIGINS` - Comma-separated allowed origins (default: "http://localhost:5173,tauri://localhost")
+    /// - `REMINDER_POLL_INTERVAL_SECONDS` - Reminder scheduler interval (default: 30)
+    /// - `REMINDER_LOOKBACK_SECONDS` - Missed reminder catch-up window (default: 86,400)
+    /// - `TRASH_RETENTION_DAYS` - Days deleted items can be restored (default: 30)
+    /// - `TRASH_PURGE_INTERVAL_SECONDS` - Trash purge interval (default: 3,600)
+    /// - `SUBSCRIPTION_REFRESH_INTERVAL_SECONDS` - Feed refresh interval (default: 3,600)
+    /// - `SUBSCRIPTION_ALLOWED_HOSTS` - Comma-separated private feed hosts to allow (default: none)
+    /// - `ADMIN_EMAILS` - Comma-separated emails of server administrators (default: none)
     pub fn from_env() -> Self {
         Self {
             cache_ttl_seconds: env::var("CACHE_TTL_SECONDS")
@@ -72,6 +100,47 @@ impl Config {
                 }),
             dev_annotations_db_path: env::var("DEV_ANNOTATIONS_DB_PATH")
                 .unwrap_or_else(|_| "data/dev-annotations.db".to_string()),
+            reminder_poll_interval_seconds: env::var("REMINDER_POLL_INTERVAL_SECONDS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|&v| v > 0)
+                .unwrap_or(30),
+            reminder_lookback_seconds: env::var("REMINDER_LOOKBACK_SECONDS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(86_400),
+            trash_retention_days: env::var("TRASH_RETENTION_DAYS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_TRASH_RETENTION_DAYS),
+            trash_purge_interval_seconds: env::var("TRASH_PURGE_INTERVAL_SECONDS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|&v| v > 0)
+                .unwrap_or(3_600),
+            subscription_refresh_interval_seconds: env::var(
+                "SUBSCRIPTION_REFRESH_INTERVAL_SECONDS",
+            )
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|&v| v > 0)
+            .unwrap_or(3_600),
+            subscription_allowed_hosts: env::var("SUBSCRIPTION_ALLOWED_HOSTS")
+                .map(|v| {
+                    v.split(',')
+                        .map(|s| s.trim().to_lowercase())
+                        .filter(|s| !s.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            admin_emails: env::var("ADMIN_EMAILS")
+                .map(|v| {
+                    v.split(',')
+                        .map(|s| s.trim().to_lowercase())
+                        .filter(|s| !s.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
         }
     }
 
@@ -79,6 +148,26 @@ impl Config {
     pub fn cache_ttl(&self) -> Duration {
         Duration::from_secs(self.cache_ttl_seconds)
     }
+
+    /// Get the reminder scheduler interval as a Duration.
+    pub fn reminder_poll_interval(&self) -> Duration {
+        Duration::from_secs(self.reminder_poll_interval_seconds)
+    }
+
+    /// Get the missed reminder catch-up window as a Duration.
+    pub fn reminder_lookback(&self) -> Duration {
+        Duration::from_secs(self.reminder_lookback_seconds)
+    }
+
+    /// Get the trash purge interval as a Duration.
+    pub fn trash_purge_interval(&self) -> Duration {
+        Duration::from_secs(self.trash_purge_interval_seconds)
+    }
+
+    /// Get the subscription refresh interval as a Duration.
+    pub fn subscription_refresh_interval(&self) -> Duration {
+        Duration::from_secs(self.subscription_refresh_interval_seconds)
+    }
 }
 
 impl Default for Config {
@@ -103,6 +192,13 @@ mod tests {
             auth_sqlite_path: "data/sessions.db".to_string(),
             cors_origins: vec!["http://localhost:5173".to_string()],
             dev_annotations_db_path: "data/dev-annotations.db".to_string(),
+            reminder_poll_interval_seconds: 30,
+            reminder_lookback_seconds: 86_400,
+            trash_retention_days: 30,
+            trash_purge_interval_seconds: 3_600,
+            subscription_refresh_interval_seconds: 3_600,
+            subscription_allowed_hosts: Vec::new(),
+            admin_emails: Vec::new(),
         };
 
         assert_eq!(config.cache_ttl(), Duration::from_secs(600));
@@ -119,6 +215,13 @@ mod tests {
         env::remove_var("REDIS_URL");
         env::remove_var("AUTH_SQLITE_PATH");
         env::remove_var("CORS_ORIGINS");
+        env::remove_var("REMINDER_POLL_INTERVAL_SECONDS");
+        env::remove_var("REMINDER_LOOKBACK_SECONDS");
+        env::remove_var("TRASH_RETENTION_DAYS");
+        env::remove_var("TRASH_PURGE_INTERVAL_SECONDS");
+        env::remove_var("SUBSCRIPTION_REFRESH_INTERVAL_SECONDS");
+        env::remove_var("SUBSCRIPTION_ALLOWED_HOSTS");
+        env::remove_var("ADMIN_EMAILS");
 
         let config = Config::from_env();
 
@@ -136,5 +239,15 @@ mod tests {
                 "tauri://localhost".to_string()
             ]
         );
+        assert_eq!(config.reminder_poll_interval(), Duration::from_secs(30));
+        assert_eq!(config.reminder_lookback(), Duration::from_secs(86_400));
+        assert_eq!(config.trash_retention_days, 30);
+        assert_eq!(config.trash_purge_interval(), Duration::from_secs(3_600));
+        assert_eq!(
+            config.subscription_refresh_interval(),
+            Duration::from_secs(3_600)
+        );
+        assert!(config.subscription_allowed_hosts.is_empty());
+        assert!(config.admin_emails.is_empty());
     }
 }
@@ -0,0 +1,118 @@
+//! Server administration handlers.
+//!
+//! Administrators, listed by email in `ADMIN_EMAILS`, can download a backup of
+//! everything the server stores and restore one into it. See
+//! `crate::backup` for what a restore does.
+
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use calendsync_auth::CurrentUser;
+use calendsync_core::backup::{Backup, RestoreReport, BACKUP_CONTENT_TYPE};
+
+use super::authz::require_server_admin;
+use crate::backup::{dump, restore, BackupStore};
+use crate::state::AppState;
+
+/// Largest backup the restore endpoint accepts, in bytes. Bigger backups are
+/// restored with `calendsync restore`.
+pub const MAX_RESTORE_BYTES: usize = 256 * 1024 * 1024;
+
+/// Error response with message.
+fn error_response(status: StatusCode, message: impl Into<String>) -> (StatusCode, String) {
+    let msg = message.into();
+    tracing::warn!(status = %status, message = %msg, "API error");
+    (status, msg)
+}
+
+// ============================================================================
+// Backup
+// ============================================================================
+
+/// Download a backup of the whole server (GET /api/admin/backup).
+///
+/// Requires server admin permission.
+pub async fn export_backup(
+    CurrentUser(user): CurrentUser,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, Response> {
+    require_server_admin(&state.admin_emails, &user).map_err(IntoResponse::into_response)?;
+
+    export_backup_impl(&state)
+        .await
+        .map_err(IntoResponse::into_response)
+}
+
+async fn export_backup_impl(state: &AppState) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let backup = dump(&BackupStore::from_state(state))
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let jsonl = backup
+        .to_jsonl()
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    tracing::info!(counts = ?backup.counts(), "Exported backup");
+
+    let disposition = format!(
+        "attachment; filename=\"calendsync-{}.jsonl\"",
+        backup.created_at.format("%Y%m%dT%H%M%SZ")
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, BACKUP_CONTENT_TYPE.to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        jsonl,
+    ))
+}
+
+// ============================================================================
+// Restore
+// ============================================================================
+
+/// Restore a backup into the server (POST /api/admin/restore).
+///
+/// Requires server admin permission. Responds with what was restored and
+/// what the verification pass found: 200 OK when every record verified,
+/// 409 Conflict otherwise.
+pub async fn restore_backup(
+    CurrentUser(user): CurrentUser,
+    State(state): State<AppState>,
+    body: String,
+) -> Result<(StatusCode, Json<RestoreReport>), Response> {
+    require_server_admin(&state.admin_emails, &user).map_err(IntoResponse::into_response)?;
+
+    restore_backup_impl(&state, &body)
+        .await
+        .map_err(IntoResponse::into_response)
+}
+
+async fn restore_backup_impl(
+    state: &AppState,
+    body: &str,
+) -> Result<(StatusCode, Json<RestoreReport>), (StatusCode, String)> {
+    let backup = Backup::from_jsonl(body)
+        .map_err(|e| error_response(StatusCode::BAD_REQUEST, e.to_string()))?;
+
+    let report = restore(&BackupStore::from_state(state), &backup)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    tracing::info!(
+        restored = report.restored.total(),
+        skipped = report.skipped.total(),
+        conflicts = report.conflicts.total(),
+        mismatches = report.mismatches.len(),
+        "Restored backup"
+    );
+
+    let status = if report.is_verified() {
+        StatusCode::OK
+    } else {
+        StatusCode::CONFLICT
+    };
+    Ok((status, Json(report)))
+}
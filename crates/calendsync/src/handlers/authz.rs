@@ -10,7 +10,7 @@ use axum::{
 use uuid::Uuid;
 
 use calendsync_auth::AuthState;
-use calendsync_core::calendar::CalendarRole;
+use calendsync_core::calendar::{find_attendee, CalendarEntry, CalendarRole, User};
 
 /// Authorization error that maps to HTTP 403 Forbidden.
 #[derive(Debug)]
@@ -22,6 +22,12 @@ pub enum AuthzError {
         calendar_id: Uuid,
         required: &'static str,
     },
+    /// User is not invited to the entry they are answering.
+    NotAnAttendee { entry_id: Uuid },
+    /// The calendar mirrors an iCalendar feed, so its entries can't be edited.
+    ReadOnlyCalendar { calendar_id: Uuid },
+    /// User is not one of the server's administrators.
+    NotServerAdmin { user_id: Uuid },
     /// Failed to look up membership (internal error).
     LookupFailed { calendar_id: Uuid, error: String },
 }
@@ -48,6 +54,22 @@ impl IntoResponse for AuthzError {
                 )
                     .into_response()
             }
+            Self::NotAnAttendee { entry_id } => {
+                tracing::warn!(entry_id = %entry_id, "Authorization denied: not an attendee");
+                (StatusCode::FORBIDDEN, "Not an attendee of this entry").into_response()
+            }
+            Self::ReadOnlyCalendar { calendar_id } => {
+                tracing::warn!(calendar_id = %calendar_id, "Authorization denied: read-only calendar");
+                (
+                    StatusCode::FORBIDDEN,
+                    "Calendar is read-only (it mirrors an iCalendar feed)",
+                )
+                    .into_response()
+            }
+            Self::NotServerAdmin { user_id } => {
+                tracing::warn!(user_id = %user_id, "Authorization denied: not a server admin");
+                (StatusCode::FORBIDDEN, "Requires server admin permission").into_response()
+            }
             Self::LookupFailed { calendar_id, error } => {
                 tracing::error!(
                     calendar_id = %calendar_id,
@@ -85,11 +107,39 @@ pub async fn require_read_access(
     }
 }
 
-/// Requires write access (Owner or Writer) to a calendar.
+/// Requires write access (Owner or Writer) to a calendar's entries.
+///
+/// Calendars that mirror an iCalendar feed are read-only: only refreshes
+/// change their entries.
 pub async fn require_write_access(
     auth: &AuthState,
     calendar_id: Uuid,
     user_id: Uuid,
+) -> Result<CalendarRole, AuthzError> {
+    let role = require_calendar_edit_access(auth, calendar_id, user_id).await?;
+
+    let calendar = auth
+        .calendars
+        .get_calendar(calendar_id)
+        .await
+        .map_err(|e| AuthzError::LookupFailed {
+            calendar_id,
+            error: e.to_string(),
+        })?;
+    match calendar {
+        Some(calendar) if calendar.is_read_only() => {
+            Err(AuthzError::ReadOnlyCalendar { calendar_id })
+        }
+        _ => Ok(role),
+    }
+}
+
+/// Requires write access (Owner or Writer) to a calendar's own settings,
+/// such as its name and color, which stay editable on read-only calendars.
+pub async fn require_calendar_edit_access(
+    auth: &AuthState,
+    calendar_id: Uuid,
+    user_id: Uuid,
 ) -> Result<CalendarRole, AuthzError> {
     let membership = auth
         .memberships
@@ -110,6 +160,18 @@ pub async fn require_write_access(
     }
 }
 
+/// Requires write access to both the calendar an entry is in and the one it
+/// moves to.
+pub async fn require_move_access(
+    auth: &AuthState,
+    from_calendar_id: Uuid,
+    to_calendar_id: Uuid,
+    user_id: Uuid,
+) -> Result<CalendarRole, AuthzError> {
+    require_write_access(auth, from_calendar_id, user_id).await?;
+    require_write_access(auth, to_calendar_id, user_id).await
+}
+
 /// Requires admin access (Owner only) to a calendar.
 pub async fn require_admin_access(
     auth: &AuthState,
@@ -134,3 +196,31 @@ pub async fn require_admin_access(
         None => Err(AuthzError::NoMembership { calendar_id }),
     }
 }
+
+/// Requires that the user is one of the server's administrators, listed by
+/// email in `ADMIN_EMAILS`.
+pub fn require_server_admin(admin_emails: &[String], user: &User) -> Result<(), AuthzError> {
+    let email = user.email.to_lowercase();
+    if admin_emails.contains(&email) {
+        Ok(())
+    } else {
+        Err(AuthzError::NotServerAdmin { user_id: user.id })
+    }
+}
+
+/// Requires that the user may answer an invitation to an entry.
+///
+/// Answering only needs read access (any role) to the entry's calendar, so
+/// readers who are invited can respond even though they cannot edit entries.
+pub async fn require_rsvp_access(
+    auth: &AuthState,
+    entry: &CalendarEntry,
+    user: &User,
+) -> Result<CalendarRole, AuthzError> {
+    let role = require_read_access(auth, entry.calendar_id, user.id).await?;
+
+    match find_attendee(&entry.attendees, user) {
+        Some(_) => Ok(role),
+        None => Err(AuthzError::NotAnAttendee { entry_id: entry.id }),
+    }
+}
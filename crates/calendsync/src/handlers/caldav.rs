@@ -0,0 +1,832 @@
+//! CalDAV handlers for native calendar clients.
+//!
+//! Thunderbird, DAVx5 and macOS Calendar find the server through
+//! `/.well-known/caldav` and sync the current user's calendars under `/dav/`.
+//! They sign in with HTTP Basic credentials whose password is a session
+//! token; [`basic_auth`] turns those into the bearer token the session
+//! extractor reads. PROPFIND and REPORT aren't methods axum routes by, so
+//! each path has one handler that dispatches on the method.
+
+use std::collections::HashSet;
+
+use axum::{
+    extract::{Path, Request, State},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use calendsync_auth::CurrentUser;
+use calendsync_core::caldav::{
+    calendar_path, collection_ctag, entry_component, etag_matches, format_multistatus,
+    parse_propfind, parse_report, parse_resource_href, plan_resource_put, principal_path,
+    resource_entry_id, resource_etag, resource_object, resource_path, resource_response,
+    CalDavError, DavProperty, DavResource, DavResponse, Depth, PropfindRequest, ReportRequest,
+    CALENDAR_HOME_PATH, DAV_COMPLIANCE, DAV_ROOT_PATH, DAV_XML_CONTENT_TYPE,
+};
+use calendsync_core::calendar::{
+    format_ics, overrides_changed, parse_ics, Calendar, CalendarEntry, CalendarRole, EntryWrite,
+    OccurrenceOverride, User, ICS_CONTENT_TYPE,
+};
+use calendsync_core::storage::DateRange;
+use chrono::Utc;
+use uuid::Uuid;
+
+use super::authz::{require_read_access, require_write_access};
+use super::entries::link_attendee_users;
+use super::preconditions::conditional_write_error;
+use crate::state::AppState;
+
+/// Methods every CalDAV path answers, as listed in `Allow`.
+const ALLOWED_METHODS: &str = "OPTIONS, GET, HEAD, PUT, DELETE, PROPFIND, REPORT";
+
+/// Error response with message.
+fn error_response(status: StatusCode, message: impl Into<String>) -> (StatusCode, String) {
+    let msg = message.into();
+    tracing::warn!(status = %status, message = %msg, "API error");
+    (status, msg)
+}
+
+/// Maps a request CalDAV can't take. Bodies that aren't well-formed are bad
+/// requests; well-formed ones this server won't store fail a precondition,
+/// which RFC 4791 reports as 403.
+fn dav_error(err: CalDavError) -> (StatusCode, String) {
+    let status = match err {
+        CalDavError::InvalidXml(_)
+        | CalDavError::UnexpectedElement(_)
+        | CalDavError::InvalidTimeRange(_) => StatusCode::BAD_REQUEST,
+        CalDavError::UnsupportedReport(_)
+        | CalDavError::InvalidCalendarData(_)
+        | CalDavError::ComponentCount
+        | CalDavError::MixedUids => StatusCode::FORBIDDEN,
+    };
+    error_response(status, err.to_string())
+}
+
+fn options_response() -> Response {
+    (
+        StatusCode::OK,
+        [
+            (header::ALLOW, ALLOWED_METHODS),
+            (header::HeaderName::from_static("dav"), DAV_COMPLIANCE),
+        ],
+    )
+        .into_response()
+}
+
+fn method_not_allowed(method: &Method) -> (StatusCode, String) {
+    error_response(
+        StatusCode::METHOD_NOT_ALLOWED,
+        format!("{method} is not supported here"),
+    )
+}
+
+fn multistatus(responses: &[DavResponse]) -> Response {
+    (
+        StatusCode::MULTI_STATUS,
+        [(header::CONTENT_TYPE, DAV_XML_CONTENT_TYPE)],
+        format_multistatus(responses),
+    )
+        .into_response()
+}
+
+// ============================================================================
+// Authentication
+// ============================================================================
+
+/// Accepts HTTP Basic credentials on CalDAV paths.
+///
+/// The password is a session token, sent on as a bearer token; the user name
+/// is ignored since the session identifies the user. Unauthenticated
+/// responses carry a Basic challenge so clients prompt for credentials.
+pub async fn basic_auth(mut request: Request, next: Next) -> Response {
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(basic_password);
+    if let Some(value) = token.and_then(|t| HeaderValue::from_str(&format!("Bearer {t}")).ok()) {
+        request.headers_mut().insert(header::AUTHORIZATION, value);
+    }
+
+    let mut response = next.run(request).await;
+    if response.status() == StatusCode::UNAUTHORIZED {
+        response.headers_mut().insert(
+            header::WWW_AUTHENTICATE,
+            HeaderValue::from_static(r#"Basic realm="calendsync""#),
+        );
+    }
+    response
+}
+
+/// Returns the password of a `Basic` authorization header value.
+fn basic_password(value: &str) -> Option<String> {
+    let encoded = value.strip_prefix("Basic ")?.trim();
+    let decoded = String::from_utf8(STANDARD.decode(encoded).ok()?).ok()?;
+    let (_, password) = decoded.split_once(':')?;
+    (!password.is_empty()).then(|| password.to_string())
+}
+
+/// Points clients at the CalDAV root (any method on /.well-known/caldav).
+pub async fn well_known_caldav() -> Response {
+    (
+        StatusCode::MOVED_PERMANENTLY,
+        [(header::LOCATION, DAV_ROOT_PATH)],
+    )
+        .into_response()
+}
+
+// ============================================================================
+// Loading Resources
+// ============================================================================
+
+/// An entry as a CalDAV resource.
+struct StoredResource {
+    entry: CalendarEntry,
+    overrides: Vec<OccurrenceOverride>,
+    etag: String,
+}
+
+impl StoredResource {
+    /// Describes the resource at `href`, rendering its data if asked for.
+    fn response(
+        &self,
+        href: String,
+        calendar: &Calendar,
+        user: &User,
+        request: &PropfindRequest,
+    ) -> DavResponse {
+        let data = request
+            .wants(&DavProperty::CalendarData)
+            .then(|| self.ics(calendar));
+        let resource = DavResource::Entry {
+            entry: &self.entry,
+            etag: &self.etag,
+            data: data.as_deref(),
+        };
+        resource_response(href, &resource, user, request)
+    }
+
+    fn ics(&self, calendar: &Calendar) -> String {
+        format_ics(
+            calendar,
+            std::slice::from_ref(&self.entry),
+            &self.overrides,
+            Utc::now(),
+        )
+    }
+}
+
+async fn load_resource(
+    state: &AppState,
+    entry: CalendarEntry,
+) -> Result<StoredResource, (StatusCode, String)> {
+    let overrides = if entry.is_recurring() {
+        state
+            .entry_repo
+            .get_occurrence_overrides(entry.id)
+            .await
+            .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    } else {
+        Vec::new()
+    };
+    let etag = resource_etag(&entry, &overrides);
+    Ok(StoredResource {
+        entry,
+        overrides,
+        etag,
+    })
+}
+
+/// Loads every live entry of a calendar as a resource.
+async fn load_resources(
+    state: &AppState,
+    calendar_id: Uuid,
+) -> Result<Vec<StoredResource>, (StatusCode, String)> {
+    let entries = state
+        .entry_repo
+        .get_stored_entries_by_calendar(calendar_id)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let mut resources = Vec::with_capacity(entries.len());
+    for entry in entries {
+        resources.push(load_resource(state, entry).await?);
+    }
+    Ok(resources)
+}
+
+/// Loads the entry stored as `entry_id` if it belongs to the calendar.
+async fn find_resource(
+    state: &AppState,
+    calendar_id: Uuid,
+    entry_id: Uuid,
+) -> Result<Option<StoredResource>, (StatusCode, String)> {
+    let entry = state
+        .entry_repo
+        .get_entry(entry_id)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    match entry {
+        Some(entry) if entry.calendar_id == calendar_id => {
+            Ok(Some(load_resource(state, entry).await?))
+        }
+        _ => Ok(None),
+    }
+}
+
+async fn get_calendar(
+    state: &AppState,
+    calendar_id: Uuid,
+) -> Result<Calendar, (StatusCode, String)> {
+    state
+        .calendar_repo
+        .get_calendar(calendar_id)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| error_response(StatusCode::NOT_FOUND, "Calendar not found"))
+}
+
+/// Describes a calendar collection, computing its ctag only when asked for.
+async fn calendar_response(
+    state: &AppState,
+    user: &User,
+    calendar: &Calendar,
+    role: CalendarRole,
+    request: &PropfindRequest,
+    resources: Option<&[StoredResource]>,
+) -> Result<DavResponse, (StatusCode, String)> {
+    let ctag = if request.wants(&DavProperty::GetCtag) {
+        let etags: Vec<(Uuid, String)> = match resources {
+            Some(resources) => resources
+                .iter()
+                .map(|r| (r.entry.id, r.etag.clone()))
+                .collect(),
+            None => load_resources(state, calendar.id)
+                .await?
+                .into_iter()
+                .map(|r| (r.entry.id, r.etag))
+                .collect(),
+        };
+        Some(collection_ctag(calendar, &etags))
+    } else {
+        None
+    };
+    let resource = DavResource::Calendar {
+        calendar,
+        role,
+        ctag: ctag.as_deref(),
+    };
+    Ok(resource_response(
+        calendar_path(calendar.id),
+        &resource,
+        user,
+        request,
+    ))
+}
+
+fn depth(headers: &HeaderMap) -> Depth {
+    Depth::from_header(headers.get("depth").and_then(|v| v.to_str().ok()))
+}
+
+// ============================================================================
+// Discovery
+// ============================================================================
+
+/// The CalDAV root (/dav/), where clients look up the current user's principal.
+pub async fn dav_root(
+    CurrentUser(user): CurrentUser,
+    method: Method,
+    body: String,
+) -> Result<Response, (StatusCode, String)> {
+    match method.as_str() {
+        "OPTIONS" => Ok(options_response()),
+        "PROPFIND" => {
+            let request = parse_propfind(&body).map_err(dav_error)?;
+            let response = resource_response(DAV_ROOT_PATH, &DavResource::Root, &user, &request);
+            Ok(multistatus(&[response]))
+        }
+        _ => Err(method_not_allowed(&method)),
+    }
+}
+
+/// The current user's principal (/dav/principals/{id}/), which names their
+/// calendar home. Other users' principals are not found.
+pub async fn dav_principal(
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<Uuid>,
+    method: Method,
+    body: String,
+) -> Result<Response, (StatusCode, String)> {
+    if id != user.id {
+        return Err(error_response(StatusCode::NOT_FOUND, "Principal not found"));
+    }
+    match method.as_str() {
+        "OPTIONS" => Ok(options_response()),
+        "PROPFIND" => {
+            let request = parse_propfind(&body).map_err(dav_error)?;
+            let response = resource_response(
+                principal_path(user.id),
+                &DavResource::Principal,
+                &user,
+                &request,
+            );
+            Ok(multistatus(&[response]))
+        }
+        _ => Err(method_not_allowed(&method)),
+    }
+}
+
+/// The calendar home (/dav/calendars/), listing the calendars the current
+/// user is a member of.
+pub async fn dav_home(
+    CurrentUser(user): CurrentUser,
+    State(state): State<AppState>,
+    method: Method,
+    headers: HeaderMap,
+    body: String,
+) -> Result<Response, (StatusCode, String)> {
+    match method.as_str() {
+        "OPTIONS" => Ok(options_response()),
+        "PROPFIND" => propfind_home_impl(&state, &user, &headers, &body).await,
+        _ => Err(method_not_allowed(&method)),
+    }
+}
+
+async fn propfind_home_impl(
+    state: &AppState,
+    user: &User,
+    headers: &HeaderMap,
+    body: &str,
+) -> Result<Response, (StatusCode, String)> {
+    let request = parse_propfind(body).map_err(dav_error)?;
+    let mut responses = vec![resource_response(
+        CALENDAR_HOME_PATH,
+        &DavResource::Home,
+        user,
+        &request,
+    )];
+
+    if depth(headers).includes_members() {
+        let calendars = state
+            .membership_repo
+            .get_calendars_for_user(user.id)
+            .await
+            .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        for (calendar, role) in &calendars {
+            responses.push(calendar_response(state, user, calendar, *role, &request, None).await?);
+        }
+    }
+
+    Ok(multistatus(&responses))
+}
+
+// ============================================================================
+// Calendar Collections
+// ============================================================================
+
+/// A calendar collection (/dav/calendars/{id}/): PROPFIND lists its entries
+/// with their ETags and REPORT fetches them. Requires read access.
+pub async fn dav_calendar(
+    CurrentUser(user): CurrentUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    method: Method,
+    headers: HeaderMap,
+    body: String,
+) -> Result<Response, Response> {
+    let auth = state.auth.as_ref().expect("Auth state required");
+    let role = require_read_access(auth, id, user.id)
+        .await
+        .map_err(IntoResponse::into_response)?;
+
+    let result = match method.as_str() {
+        "OPTIONS" => Ok(options_response()),
+        "PROPFIND" => propfind_calendar_impl(&state, &user, id, role, &headers, &body).await,
+        "REPORT" => report_impl(&state, &user, id, &body).await,
+        _ => Err(method_not_allowed(&method)),
+    };
+    result.map_err(IntoResponse::into_response)
+}
+
+async fn propfind_calendar_impl(
+    state: &AppState,
+    user: &User,
+    calendar_id: Uuid,
+    role: CalendarRole,
+    headers: &HeaderMap,
+    body: &str,
+) -> Result<Response, (StatusCode, String)> {
+    let request = parse_propfind(body).map_err(dav_error)?;
+    let calendar = get_calendar(state, calendar_id).await?;
+
+    if !depth(headers).includes_members() {
+        let response = calendar_response(state, user, &calendar, role, &request, None).await?;
+        return Ok(multistatus(&[response]));
+    }
+
+    let resources = load_resources(state, calendar.id).await?;
+    let mut responses =
+        vec![calendar_response(state, user, &calendar, role, &request, Some(&resources)).await?];
+    responses.extend(resources.iter().map(|r| {
+        r.response(
+            resource_path(calendar.id, r.entry.id),
+            &calendar,
+            user,
+            &request,
+        )
+    }));
+
+    tracing::debug!(
+        calendar_id = %calendar.id,
+        resource_count = resources.len(),
+        "Listed CalDAV calendar"
+    );
+
+    Ok(multistatus(&responses))
+}
+
+async fn report_impl(
+    state: &AppState,
+    user: &User,
+    calendar_id: Uuid,
+    body: &str,
+) -> Result<Response, (StatusCode, String)> {
+    let report = parse_report(body).map_err(dav_error)?;
+    let request = PropfindRequest::Prop(report.properties().to_vec());
+    let calendar = get_calendar(state, calendar_id).await?;
+
+    let responses = match report {
+        ReportRequest::CalendarQuery {
+            component,
+            time_range,
+            ..
+        } => {
+            let mut resources = load_resources(state, calendar.id).await?;
+            if let Some((start, end)) = time_range.and_then(|range| range.dates()) {
+                // Expanding the window finds series with an occurrence in it
+                let range = DateRange::new(start, end)
+                    .map_err(|e| error_response(StatusCode::BAD_REQUEST, e.to_string()))?;
+                let in_range: HashSet<Uuid> = state
+                    .entry_repo
+                    .get_entries_by_calendar(calendar.id, range)
+                    .await
+                    .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+                    .into_iter()
+                    .map(|entry| entry.id)
+                    .collect();
+                resources.retain(|r| in_range.contains(&r.entry.id));
+            }
+            if let Some(component) = component {
+                resources.retain(|r| entry_component(&r.entry) == component);
+            }
+            resources
+                .iter()
+                .map(|r| {
+                    r.response(
+                        resource_path(calendar.id, r.entry.id),
+                        &calendar,
+                        user,
+                        &request,
+                    )
+                })
+                .collect()
+        }
+        ReportRequest::CalendarMultiget { hrefs, .. } => {
+            let mut responses = Vec::with_capacity(hrefs.len());
+            for href in hrefs {
+                let resource = match parse_resource_href(&href) {
+                    Some((id, entry_id)) if id == calendar.id => {
+                        find_resource(state, calendar.id, entry_id).await?
+                    }
+                    _ => None,
+                };
+                responses.push(match resource {
+                    Some(r) => r.response(href, &calendar, user, &request),
+                    None => DavResponse::not_found(href),
+                });
+            }
+            responses
+        }
+    };
+
+    Ok(multistatus(&responses))
+}
+
+// ============================================================================
+// Calendar Resources
+// ============================================================================
+
+/// An entry as an iCalendar resource (/dav/calendars/{id}/{name}.ics).
+///
+/// Reading requires read access and writing requires write access. Writes
+/// honor `If-Match` and `If-None-Match` against the resource's ETag.
+pub async fn dav_resource(
+    CurrentUser(user): CurrentUser,
+    State(state): State<AppState>,
+    Path((calendar_id, name)): Path<(Uuid, String)>,
+    method: Method,
+    headers: HeaderMap,
+    body: String,
+) -> Result<Response, Response> {
+    let auth = state.auth.as_ref().expect("Auth state required");
+    let writes = matches!(method.as_str(), "PUT" | "DELETE");
+    let role = if writes {
+        require_write_access(auth, calendar_id, user.id).await
+    } else {
+        require_read_access(auth, calendar_id, user.id).await
+    };
+    role.map_err(IntoResponse::into_response)?;
+
+    let entry_id = resource_entry_id(calendar_id, &name).ok_or_else(|| {
+        error_response(StatusCode::NOT_FOUND, "Resource not found").into_response()
+    })?;
+
+    let result = match method.as_str() {
+        "OPTIONS" => Ok(options_response()),
+        "GET" | "HEAD" => get_resource_impl(&state, calendar_id, entry_id).await,
+        "PROPFIND" => propfind_resource_impl(&state, &user, calendar_id, entry_id, &body).await,
+        "PUT" => put_resource_impl(&state, &user, calendar_id, entry_id, &headers, &body).await,
+        "DELETE" => delete_resource_impl(&state, &user, calendar_id, entry_id, &headers).await,
+        _ => Err(method_not_allowed(&method)),
+    };
+    result.map_err(IntoResponse::into_response)
+}
+
+fn resource_not_found() -> (StatusCode, String) {
+    error_response(StatusCode::NOT_FOUND, "Resource not found")
+}
+
+async fn get_resource_impl(
+    state: &AppState,
+    calendar_id: Uuid,
+    entry_id: Uuid,
+) -> Result<Response, (StatusCode, String)> {
+    let calendar = get_calendar(state, calendar_id).await?;
+    let resource = find_resource(state, calendar_id, entry_id)
+        .await?
+        .ok_or_else(resource_not_found)?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, ICS_CONTENT_TYPE.to_string()),
+            (header::ETAG, resource.etag.clone()),
+        ],
+        resource.ics(&calendar),
+    )
+        .into_response())
+}
+
+async fn propfind_resource_impl(
+    state: &AppState,
+    user: &User,
+    calendar_id: Uuid,
+    entry_id: Uuid,
+    body: &str,
+) -> Result<Response, (StatusCode, String)> {
+    let request = parse_propfind(body).map_err(dav_error)?;
+    let calendar = get_calendar(state, calendar_id).await?;
+    let resource = find_resource(state, calendar_id, entry_id)
+        .await?
+        .ok_or_else(resource_not_found)?;
+
+    let response = resource.response(
+        resource_path(calendar_id, entry_id),
+        &calendar,
+        user,
+        &request,
+    );
+    Ok(multistatus(&[response]))
+}
+
+/// Checks `If-Match` and `If-None-Match` against the stored resource's ETag,
+/// `None` when nothing is stored at the path.
+fn check_preconditions(
+    headers: &HeaderMap,
+    etag: Option<&str>,
+) -> Result<(), (StatusCode, String)> {
+    let header = |name| {
+        headers
+            .get(name)
+            .and_then(|v: &HeaderValue| v.to_str().ok())
+    };
+    let failed = |msg: &str| Err(error_response(StatusCode::PRECONDITION_FAILED, msg));
+
+    if let Some(value) = header(header::IF_MATCH) {
+        if !etag.is_some_and(|etag| etag_matches(value, etag)) {
+            return failed("Resource has changed");
+        }
+    }
+    if let (Some(value), Some(etag)) = (header(header::IF_NONE_MATCH), etag) {
+        if etag_matches(value, etag) {
+            return failed("Resource already exists");
+        }
+    }
+    Ok(())
+}
+
+async fn put_resource_impl(
+    state: &AppState,
+    user: &User,
+    calendar_id: Uuid,
+    entry_id: Uuid,
+    headers: &HeaderMap,
+    body: &str,
+) -> Result<Response, (StatusCode, String)> {
+    let object = parse_ics(body, calendar_id)
+        .map_err(CalDavError::from)
+        .and_then(resource_object)
+        .map_err(dav_error)?;
+
+    let stored_entry = state
+        .entry_repo
+        .get_entry(entry_id)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if stored_entry
+        .as_ref()
+        .is_some_and(|entry| entry.calendar_id != calendar_id)
+    {
+        return Err(error_response(
+            StatusCode::CONFLICT,
+            "Entry belongs to another calendar",
+        ));
+    }
+    let stored = match stored_entry {
+        Some(entry) => Some(load_resource(state, entry).await?),
+        None => None,
+    };
+    check_preconditions(headers, stored.as_ref().map(|r| r.etag.as_str()))?;
+    let created = stored.is_none();
+
+    // A client re-creating an entry that was deleted gets it back from the
+    // trash, as its ID is still taken there
+    let stored = match stored {
+        Some(stored) => Some(stored),
+        None => restore_trashed(state, user, calendar_id, entry_id).await?,
+    };
+
+    let mut entry = object.series.to_entry(entry_id);
+    link_attendee_users(state, &mut entry.attendees).await?;
+    let mut overrides = object.overrides(entry_id);
+    for occurrence in &mut overrides {
+        if let Some(replacement) = &mut occurrence.replacement {
+            link_attendee_users(state, &mut replacement.attendees).await?;
+        }
+    }
+
+    // Repository calls handle cache invalidation and event publishing. A
+    // change to the overrides of a stored series is saved as an edit of the
+    // series, so it is recorded in its history.
+    let stored_overrides = stored.as_ref().map(|r| r.overrides.as_slice());
+    let overrides_changed = overrides_changed(stored_overrides.unwrap_or_default(), &overrides);
+    match plan_resource_put(entry, stored.as_ref().map(|r| &r.entry)) {
+        Some(EntryWrite::Create(entry)) => {
+            state
+                .entry_repo
+                .create_entry_as(&entry, Some(user.id))
+                .await
+                .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            for occurrence in &overrides {
+                state
+                    .entry_repo
+                    .upsert_occurrence_override(occurrence)
+                    .await
+                    .map_err(|e| {
+                        error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+                    })?;
+            }
+        }
+        Some(EntryWrite::Update {
+            entry,
+            expected_version,
+        }) if overrides_changed => state
+            .entry_repo
+            .update_series_if_version_as(&entry, &overrides, expected_version, Some(user.id))
+            .await
+            .map_err(conditional_write_error)?,
+        Some(EntryWrite::Update {
+            entry,
+            expected_version,
+        }) => state
+            .entry_repo
+            .update_entry_if_version_as(&entry, expected_version, Some(user.id))
+            .await
+            .map_err(conditional_write_error)?,
+        Some(EntryWrite::Delete(_)) | None => {
+            if let Some(stored) = stored.as_ref().filter(|_| overrides_changed) {
+                state
+                    .entry_repo
+                    .update_series_if_version_as(
+                        &stored.entry,
+                        &overrides,
+                        stored.entry.version,
+                        Some(user.id),
+                    )
+                    .await
+                    .map_err(conditional_write_error)?;
+            }
+        }
+    }
+
+    let resource = find_resource(state, calendar_id, entry_id)
+        .await?
+        .ok_or_else(resource_not_found)?;
+
+    tracing::info!(
+        calendar_id = %calendar_id,
+        entry_id = %entry_id,
+        created,
+        "Stored CalDAV resource"
+    );
+
+    let status = if created {
+        StatusCode::CREATED
+    } else {
+        StatusCode::NO_CONTENT
+    };
+    Ok((status, [(header::ETAG, resource.etag)]).into_response())
+}
+
+/// Restores an entry of the calendar from the trash, if it is there.
+async fn restore_trashed(
+    state: &AppState,
+    user: &User,
+    calendar_id: Uuid,
+    entry_id: Uuid,
+) -> Result<Option<StoredResource>, (StatusCode, String)> {
+    let trashed = state
+        .entry_repo
+        .get_trashed_entries(&[calendar_id])
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if !trashed.iter().any(|t| t.entry.id == entry_id) {
+        return Ok(None);
+    }
+
+    let entry = state
+        .entry_repo
+        .restore_entry_as(entry_id, Some(user.id))
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Some(load_resource(state, entry).await?))
+}
+
+async fn delete_resource_impl(
+    state: &AppState,
+    user: &User,
+    calendar_id: Uuid,
+    entry_id: Uuid,
+    headers: &HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let resource = find_resource(state, calendar_id, entry_id)
+        .await?
+        .ok_or_else(resource_not_found)?;
+    check_preconditions(headers, Some(&resource.etag))?;
+
+    // Repository calls handle cache invalidation and event publishing; the
+    // entry is only trashed while it is still the version checked above
+    state
+        .entry_repo
+        .delete_entry_if_version_as(entry_id, resource.entry.version, Some(user.id))
+        .await
+        .map_err(conditional_write_error)?;
+
+    tracing::info!(calendar_id = %calendar_id, entry_id = %entry_id, "Deleted CalDAV resource");
+
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_basic_password() {
+        let encoded = STANDARD.encode("ada@example.com:session-token");
+        assert_eq!(
+            basic_password(&format!("Basic {encoded}")).as_deref(),
+            Some("session-token")
+        );
+
+        let no_password = STANDARD.encode("ada@example.com:");
+        assert_eq!(basic_password(&format!("Basic {no_password}")), None);
+        assert_eq!(basic_password("Basic not-base64!"), None);
+        assert_eq!(basic_password("Bearer session-token"), None);
+    }
+
+    #[test]
+    fn test_check_preconditions() {
+        let headers = |name, value: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(name, HeaderValue::from_static(value));
+            headers
+        };
+
+        assert!(check_preconditions(&HeaderMap::new(), None).is_ok());
+        assert!(check_preconditions(&headers(header::IF_NONE_MATCH, "*"), None).is_ok());
+        assert!(check_preconditions(&headers(header::IF_NONE_MATCH, "*"), Some("\"1\"")).is_err());
+        assert!(check_preconditions(&headers(header::IF_MATCH, "\"1\""), Some("\"1\"")).is_ok());
+        assert!(check_preconditions(&headers(header::IF_MATCH, "\"1\""), Some("\"2\"")).is_err());
+        assert!(check_preconditions(&headers(header::IF_MATCH, "*"), None).is_err());
+    }
+}
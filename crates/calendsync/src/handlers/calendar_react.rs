@@ -2,7 +2,6 @@
 //!
 //! Uses the SSR worker pool from `calendsync_ssr` to render the React calendar.
 
-use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use calendsync_ssr::SsrPool;
@@ -20,24 +19,29 @@ use axum::response::Redirect;
 use super::flash::{redirect_with_flash, FlashMessage};
 
 use crate::context::RequestContext;
-use chrono::Local;
+use chrono::{Local, Utc};
+use chrono_tz::Tz;
 use uuid::Uuid;
 
-use calendsync_core::calendar::{CalendarEntry, CalendarSettings, User};
+use calendsync_core::calendar::{
+    localize_entries, localize_entry, CalendarEntry, CalendarSettings, User,
+};
 use calendsync_core::storage::DateRange;
 use calendsync_ssr::{sanitize_error, SsrConfig, SsrError};
 
 /// User info subset for SSR props (serialized to frontend).
 ///
 /// This is intentionally a **security projection** of `User` - it only includes
-/// fields safe to expose to the client (name, email). Sensitive fields like
-/// `id`, `provider`, `provider_subject`, and timestamps are excluded.
+/// fields safe to expose to the client (name, email, display time zone). Sensitive
+/// fields like `id`, `provider`, `provider_subject`, and timestamps are excluded.
 ///
 /// Matches the TypeScript `UserInfo` interface in `frontend/src/calendsync/types.ts`.
 #[derive(serde::Serialize)]
 struct SsrUserInfo {
     name: String,
     email: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timezone: Option<Tz>,
 }
 
 impl From<&User> for SsrUserInfo {
@@ -45,11 +49,12 @@ impl From<&User> for SsrUserInfo {
         Self {
             name: user.name.clone(),
             email: user.email.clone(),
+            timezone: user.timezone,
         }
     }
 }
 
-use super::entries::entry_to_server_entry;
+use super::entries::{entries_to_server_days, entry_to_server_entry};
 use crate::state::AppState;
 
 /// Check if dev mode with auto-refresh is enabled.
@@ -79,22 +84,26 @@ struct SsrPreparedData {
     ssr_pool: Arc<SsrPool>,
     highlighted_day: String,
     days: Vec<serde_json::Value>,
+    /// Calendar's time zone, for localizing entries outside the prepared days.
+    calendar_tz: Option<Tz>,
 }
 
 /// Prepare common SSR data: fetch calendar, validate, get entries.
 ///
 /// This extracts the common setup logic shared between `calendar_react_ssr_impl`
-/// and `calendar_react_ssr_entry_impl` to avoid code duplication.
+/// and `calendar_react_ssr_entry_impl` to avoid code duplication. Timed entries
+/// are converted into `viewer_tz` when given.
 async fn prepare_ssr_data(
     state: &AppState,
     calendar_id: Uuid,
+    viewer_tz: Option<Tz>,
 ) -> Result<SsrPreparedData, Response> {
     let urls = get_bundle_urls();
     let dev_mode = is_dev_mode();
 
     // Validate calendar exists
-    match state.calendar_repo.get_calendar(calendar_id).await {
-        Ok(Some(_)) => {} // Calendar exists, continue
+    let calendar_tz = match state.calendar_repo.get_calendar(calendar_id).await {
+        Ok(Some(calendar)) => calendar.timezone,
         Ok(None) => {
             tracing::warn!(calendar_id = %calendar_id, "Calendar not found");
             return Err(Html(error_html(
@@ -137,16 +146,23 @@ async fn prepare_ssr_data(
         }
     };
 
-    // Get today's date as the highlighted day
-    let today = Local::now().date_naive();
+    // Get today's date as the highlighted day (in the viewer's zone when known)
+    let today = match viewer_tz {
+        Some(tz) => Utc::now().with_timezone(&tz).date_naive(),
+        None => Local::now().date_naive(),
+    };
     let highlighted_day = today.to_string();
 
     // Calculate date range (before=365, after=365)
     let start = today - chrono::Duration::days(365);
     let end = today + chrono::Duration::days(365);
 
-    // Fetch entries for the date range
-    let date_range = match DateRange::new(start, end) {
+    // Fetch entries for the date range, plus a day on each side for entries
+    // that move across midnight once converted into the viewer's zone
+    let date_range = match DateRange::new(
+        start - chrono::Duration::days(1),
+        end + chrono::Duration::days(1),
+    ) {
         Ok(range) => range,
         Err(e) => {
             tracing::error!(error = %e, "Invalid date range");
@@ -180,6 +196,7 @@ async fn prepare_ssr_data(
         }
     };
 
+    let entries = localize_entries(entries, calendar_tz, viewer_tz);
     let entry_refs: Vec<&CalendarEntry> = entries.iter().collect();
     let days = entries_to_server_days(&entry_refs, start, end);
 
@@ -189,6 +206,7 @@ async fn prepare_ssr_data(
         ssr_pool,
         highlighted_day,
         days,
+        calendar_tz,
     })
 }
 
@@ -234,45 +252,6 @@ fn get_manifest() -> serde_json::Value {
     serde_json::from_str(manifest_str).unwrap_or(serde_json::json!({}))
 }
 
-/// Group entries by date into ServerDay format for a date range.
-/// Creates entries for all dates in the range, even if they have no entries.
-fn entries_to_server_days(
-    entries: &[&CalendarEntry],
-    start: chrono::NaiveDate,
-    end: chrono::NaiveDate,
-) -> Vec<serde_json::Value> {
-    let mut days_map: BTreeMap<chrono::NaiveDate, Vec<serde_json::Value>> = BTreeMap::new();
-
-    // Initialize all dates in the range
-    let mut current = start;
-    while current <= end {
-        days_map.insert(current, Vec::new());
-        current += chrono::Duration::days(1);
-    }
-
-    // Add entries - use start_date for grouping
-    // (Frontend will expand multi-day entries)
-    for entry in entries {
-        if entry.start_date >= start && entry.start_date <= end {
-            let server_entry = entry_to_server_entry(entry);
-            days_map
-                .entry(entry.start_date)
-                .or_default()
-                .push(server_entry);
-        }
-    }
-
-    days_map
-        .into_iter()
-        .map(|(date, entries)| {
-            serde_json::json!({
-                "date": date.to_string(),
-                "entries": entries,
-            })
-        })
-        .collect()
-}
-
 /// Generate simple HTML page for service unavailable states (e.g., SSR initializing).
 /// Includes auto-refresh hint to encourage user to retry.
 fn initializing_html(title: &str, message: &str) -> String {
@@ -592,7 +571,8 @@ async fn calendar_react_ssr_impl(
     settings: CalendarSettings,
 ) -> Response {
     // Prepare common SSR data (calendar validation, SSR pool, entries)
-    let data = match prepare_ssr_data(&state, calendar_id).await {
+    let viewer_tz = user_info.as_ref().and_then(|user| user.timezone);
+    let data = match prepare_ssr_data(&state, calendar_id, viewer_tz).await {
         Ok(d) => d,
         Err(response) => return response,
     };
@@ -753,7 +733,8 @@ async fn calendar_react_ssr_entry_impl(
     settings: CalendarSettings,
 ) -> Response {
     // Prepare common SSR data (calendar validation, SSR pool, entries)
-    let data = match prepare_ssr_data(&state, calendar_id).await {
+    let viewer_tz = user_info.as_ref().and_then(|user| user.timezone);
+    let data = match prepare_ssr_data(&state, calendar_id, viewer_tz).await {
         Ok(d) => d,
         Err(response) => return response,
     };
@@ -765,7 +746,7 @@ async fn calendar_react_ssr_entry_impl(
             Ok(Some(entry)) => serde_json::json!({
                 "mode": "edit",
                 "entryId": entry_id.to_string(),
-                "entry": entry_to_server_entry(&entry),
+                "entry": entry_to_server_entry(&localize_entry(entry, data.calendar_tz, viewer_tz)),
             }),
             Ok(None) => {
                 // Entry not found - return 404
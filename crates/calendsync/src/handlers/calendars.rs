@@ -2,36 +2,40 @@
 
 use axum::{
     extract::{rejection::FormRejection, Path, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
     Form, Json,
 };
+use serde::Serialize;
 use uuid::Uuid;
 
-use calendsync_core::calendar::Calendar;
+use calendsync_core::calendar::{
+    duplicate_calendar as duplicate_calendar_metadata, normalize_subscription_url, validate_shift,
+    Calendar, DuplicateOptions, DuplicateSummary, RefreshReport,
+};
+use calendsync_core::storage::RepositoryError;
 
 #[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
 use calendsync_core::calendar::CalendarRole;
 
-#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
-use serde::Serialize;
-
 #[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
 use axum::response::Response;
 
 #[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
 use calendsync_core::calendar::CalendarMembership;
 
+use super::preconditions::{check_if_match, conditional_write_error, etag_header};
 use crate::{
-    models::{CreateCalendar, UpdateCalendar},
+    models::{CreateCalendar, DuplicateCalendar, UpdateCalendar},
     state::AppState,
+    subscriptions::{refresh_subscription, RefreshError},
 };
 
 #[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
 use calendsync_auth::CurrentUser;
 
 #[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
-use super::authz::{require_admin_access, require_read_access, require_write_access};
+use super::authz::{require_admin_access, require_calendar_edit_access, require_read_access};
 
 /// Error response with message (for form validation errors).
 fn error_response(status: StatusCode, message: impl Into<String>) -> (StatusCode, String) {
@@ -49,6 +53,14 @@ pub struct CalendarWithRole {
     pub role: CalendarRole,
 }
 
+/// A new copy of a calendar, with how much was copied into it.
+#[derive(Serialize)]
+pub struct DuplicatedCalendar {
+    pub calendar: Calendar,
+    #[serde(flatten)]
+    pub summary: DuplicateSummary,
+}
+
 // ============================================================================
 // List My Calendars
 // ============================================================================
@@ -115,10 +127,7 @@ pub async fn create_calendar(
     tracing::debug!(user_id = %user.id, payload = ?payload, "Received create calendar request");
 
     // Build calendar from payload
-    let mut calendar = Calendar::new(&payload.name, &payload.color);
-    if let Some(desc) = payload.description {
-        calendar = calendar.with_description(desc);
-    }
+    let calendar = calendar_from_payload(&state, payload).map_err(IntoResponse::into_response)?;
 
     // Create via repository
     state
@@ -148,6 +157,7 @@ pub async fn create_calendar(
         name = %calendar.name,
         "Created new calendar with owner membership"
     );
+    spawn_first_refresh(&state, &calendar);
 
     Ok((StatusCode::CREATED, Json(calendar)))
 }
@@ -167,10 +177,7 @@ pub async fn create_calendar(
 
     tracing::debug!(payload = ?payload, "Received create calendar request");
 
-    let mut calendar = Calendar::new(&payload.name, &payload.color);
-    if let Some(desc) = payload.description {
-        calendar = calendar.with_description(desc);
-    }
+    let calendar = calendar_from_payload(&state, payload)?;
 
     state
         .calendar_repo
@@ -179,10 +186,41 @@ pub async fn create_calendar(
         .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
     tracing::info!(calendar_id = %calendar.id, name = %calendar.name, "Created new calendar");
+    spawn_first_refresh(&state, &calendar);
 
     Ok((StatusCode::CREATED, Json(calendar)))
 }
 
+/// Builds a new calendar from a create request, checking its feed URL.
+fn calendar_from_payload(
+    state: &AppState,
+    payload: CreateCalendar,
+) -> Result<Calendar, (StatusCode, String)> {
+    let mut calendar = Calendar::new(&payload.name, &payload.color);
+    if let Some(desc) = payload.description {
+        calendar = calendar.with_description(desc);
+    }
+    if let Some(timezone) = payload.timezone {
+        calendar = calendar.with_timezone(timezone);
+    }
+    if let Some(url) = payload.subscription_url {
+        let url = normalize_subscription_url(&url, &state.subscription_allowed_hosts)
+            .map_err(|e| error_response(StatusCode::BAD_REQUEST, e.to_string()))?;
+        calendar = calendar.with_subscription(url);
+    }
+    Ok(calendar)
+}
+
+/// Fills a newly subscribed calendar from its feed without holding up the
+/// response.
+fn spawn_first_refresh(state: &AppState, calendar: &Calendar) {
+    if calendar.is_read_only() {
+        let (state, id) = (state.clone(), calendar.id);
+        // Failures are logged and recorded on the calendar
+        tokio::spawn(async move { refresh_subscription(&state, id).await });
+    }
+}
+
 // ============================================================================
 // Get Calendar
 // ============================================================================
@@ -216,7 +254,7 @@ pub async fn get_calendar(
 async fn get_calendar_impl(
     state: &AppState,
     id: Uuid,
-) -> Result<Json<Calendar>, (StatusCode, String)> {
+) -> Result<impl IntoResponse, (StatusCode, String)> {
     tracing::debug!(calendar_id = %id, "Received get calendar request");
 
     let calendar = state
@@ -228,7 +266,7 @@ async fn get_calendar_impl(
     match calendar {
         Some(cal) => {
             tracing::debug!(calendar_id = %id, "Found calendar");
-            Ok(Json(cal))
+            Ok((etag_header(cal.version), Json(cal)))
         }
         None => {
             tracing::debug!(calendar_id = %id, "Calendar not found");
@@ -247,10 +285,11 @@ pub async fn update_calendar(
     CurrentUser(user): CurrentUser,
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    headers: HeaderMap,
     form_result: Result<Form<UpdateCalendar>, FormRejection>,
 ) -> Result<impl IntoResponse, Response> {
     let auth = state.auth.as_ref().expect("Auth state required");
-    require_write_access(auth, id, user.id)
+    require_calendar_edit_access(auth, id, user.id)
         .await
         .map_err(IntoResponse::into_response)?;
 
@@ -262,7 +301,7 @@ pub async fn update_calendar(
         .into_response()
     })?;
 
-    update_calendar_impl(&state, id, payload)
+    update_calendar_impl(&state, id, &headers, payload)
         .await
         .map_err(IntoResponse::into_response)
 }
@@ -272,6 +311,7 @@ pub async fn update_calendar(
 pub async fn update_calendar(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    headers: HeaderMap,
     form_result: Result<Form<UpdateCalendar>, FormRejection>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
     let Form(payload) = form_result.map_err(|e| {
@@ -281,12 +321,16 @@ pub async fn update_calendar(
         )
     })?;
 
-    update_calendar_impl(&state, id, payload).await
+    update_calendar_impl(&state, id, &headers, payload).await
 }
 
+/// Applies the update while the calendar is still at the version read here,
+/// failing with 412 when `If-Match` names another version or another write
+/// gets in first.
 async fn update_calendar_impl(
     state: &AppState,
     id: Uuid,
+    headers: &HeaderMap,
     payload: UpdateCalendar,
 ) -> Result<Json<Calendar>, (StatusCode, String)> {
     tracing::debug!(calendar_id = %id, payload = ?payload, "Received update calendar request");
@@ -297,6 +341,7 @@ async fn update_calendar_impl(
         .await
         .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
         .ok_or_else(|| error_response(StatusCode::NOT_FOUND, "Calendar not found"))?;
+    check_if_match(headers, calendar.version)?;
 
     if let Some(name) = payload.name {
         calendar.name = name;
@@ -307,12 +352,16 @@ async fn update_calendar_impl(
     if let Some(description) = payload.description {
         calendar.description = Some(description);
     }
+    if let Some(timezone) = payload.timezone {
+        calendar.timezone = Some(timezone);
+    }
 
     state
         .calendar_repo
-        .update_calendar(&calendar)
+        .update_calendar_if_version(&calendar, calendar.version)
         .await
-        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(conditional_write_error)?;
+    calendar.version += 1;
 
     tracing::info!(calendar_id = %id, "Updated calendar");
 
@@ -329,13 +378,14 @@ pub async fn delete_calendar(
     CurrentUser(user): CurrentUser,
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    headers: HeaderMap,
 ) -> Result<impl IntoResponse, Response> {
     let auth = state.auth.as_ref().expect("Auth state required");
     require_admin_access(auth, id, user.id)
         .await
         .map_err(IntoResponse::into_response)?;
 
-    delete_calendar_impl(&state, id)
+    delete_calendar_impl(&state, id, &headers)
         .await
         .map_err(IntoResponse::into_response)
 }
@@ -345,26 +395,25 @@ pub async fn delete_calendar(
 pub async fn delete_calendar(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    headers: HeaderMap,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    delete_calendar_impl(&state, id).await
+    delete_calendar_impl(&state, id, &headers).await
 }
 
 async fn delete_calendar_impl(
     state: &AppState,
     id: Uuid,
+    headers: &HeaderMap,
 ) -> Result<StatusCode, (StatusCode, String)> {
     tracing::debug!(calendar_id = %id, "Received delete calendar request");
 
-    let exists = state
+    let calendar = state
         .calendar_repo
         .get_calendar(id)
         .await
         .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .is_some();
-
-    if !exists {
-        return Err(error_response(StatusCode::NOT_FOUND, "Calendar not found"));
-    }
+        .ok_or_else(|| error_response(StatusCode::NOT_FOUND, "Calendar not found"))?;
+    check_if_match(headers, calendar.version)?;
 
     state
         .calendar_repo
@@ -376,3 +425,158 @@ async fn delete_calendar_impl(
 
     Ok(StatusCode::OK)
 }
+
+// ============================================================================
+// Duplicate Calendar
+// ============================================================================
+
+/// Duplicate a calendar (POST /api/calendars/{id}/duplicate) - with auth.
+///
+/// Requires read access to the source, or admin access when its members are
+/// copied. The current user owns the copy.
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+pub async fn duplicate_calendar(
+    CurrentUser(user): CurrentUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    form_result: Result<Form<DuplicateCalendar>, FormRejection>,
+) -> Result<impl IntoResponse, Response> {
+    let Form(payload) = form_result.map_err(|e| {
+        error_response(
+            StatusCode::BAD_REQUEST,
+            format!("Failed to parse form: {e}"),
+        )
+        .into_response()
+    })?;
+
+    let auth = state.auth.as_ref().expect("Auth state required");
+    if payload.include_members.unwrap_or(false) {
+        require_admin_access(auth, id, user.id).await
+    } else {
+        require_read_access(auth, id, user.id).await
+    }
+    .map_err(IntoResponse::into_response)?;
+
+    duplicate_calendar_impl(&state, id, payload, Some(user.id))
+        .await
+        .map_err(IntoResponse::into_response)
+}
+
+/// Duplicate a calendar (POST /api/calendars/{id}/duplicate) - no auth.
+#[cfg(not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")))]
+pub async fn duplicate_calendar(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    form_result: Result<Form<DuplicateCalendar>, FormRejection>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let Form(payload) = form_result.map_err(|e| {
+        error_response(
+            StatusCode::BAD_REQUEST,
+            format!("Failed to parse form: {e}"),
+        )
+    })?;
+
+    duplicate_calendar_impl(&state, id, payload, None).await
+}
+
+/// Copies the calendar and, as asked, its entries and members in one
+/// repository call.
+async fn duplicate_calendar_impl(
+    state: &AppState,
+    id: Uuid,
+    payload: DuplicateCalendar,
+    owner: Option<Uuid>,
+) -> Result<(StatusCode, Json<DuplicatedCalendar>), (StatusCode, String)> {
+    tracing::debug!(calendar_id = %id, payload = ?payload, "Received duplicate calendar request");
+
+    let options = DuplicateOptions {
+        entries: payload.include_entries.unwrap_or(false),
+        shift_days: payload.shift_days.unwrap_or(0),
+        members: payload.include_members.unwrap_or(false),
+        owner,
+    };
+    validate_shift(options.shift_days)
+        .map_err(|e| error_response(StatusCode::BAD_REQUEST, e.to_string()))?;
+
+    let source = state
+        .calendar_repo
+        .get_calendar(id)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| error_response(StatusCode::NOT_FOUND, "Calendar not found"))?;
+    let calendar = duplicate_calendar_metadata(&source, payload.name);
+
+    let summary = state
+        .calendar_repo
+        .duplicate_calendar(id, &calendar, &options)
+        .await
+        .map_err(|e| match e {
+            RepositoryError::NotFound { .. } => {
+                error_response(StatusCode::NOT_FOUND, "Calendar not found")
+            }
+            e => error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        })?;
+
+    tracing::info!(
+        source_id = %id,
+        calendar_id = %calendar.id,
+        entries = summary.entries,
+        members = summary.members,
+        "Duplicated calendar"
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(DuplicatedCalendar { calendar, summary }),
+    ))
+}
+
+// ============================================================================
+// Refresh Calendar
+// ============================================================================
+
+/// Refresh a subscribed calendar from its feed now (POST /api/calendars/{id}/refresh) - with auth.
+///
+/// Requires write access to the calendar.
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+pub async fn refresh_calendar(
+    CurrentUser(user): CurrentUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<RefreshReport>, Response> {
+    let auth = state.auth.as_ref().expect("Auth state required");
+    require_calendar_edit_access(auth, id, user.id)
+        .await
+        .map_err(IntoResponse::into_response)?;
+
+    refresh_calendar_impl(&state, id)
+        .await
+        .map_err(IntoResponse::into_response)
+}
+
+/// Refresh a subscribed calendar from its feed now (POST /api/calendars/{id}/refresh) - no auth.
+#[cfg(not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")))]
+pub async fn refresh_calendar(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<RefreshReport>, (StatusCode, String)> {
+    refresh_calendar_impl(&state, id).await
+}
+
+async fn refresh_calendar_impl(
+    state: &AppState,
+    id: Uuid,
+) -> Result<Json<RefreshReport>, (StatusCode, String)> {
+    refresh_subscription(state, id)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            let status = match e {
+                RefreshError::NotFound => StatusCode::NOT_FOUND,
+                RefreshError::NotSubscribed => StatusCode::BAD_REQUEST,
+                RefreshError::Fetch(_) | RefreshError::Feed(_) => StatusCode::BAD_GATEWAY,
+                RefreshError::Storage(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            error_response(status, e.to_string())
+        })
+}
@@ -3,36 +3,58 @@
 //! These handlers use repository trait objects for database access.
 //! Event publishing is handled by the cached repository decorator.
 
-use std::collections::BTreeMap;
+use std::collections::{hash_map::Entry, BTreeMap, BTreeSet, HashMap};
 
 use axum::{
-    extract::{rejection::FormRejection, Path, Query, State},
-    http::StatusCode,
-    response::IntoResponse,
+    extract::{
+        rejection::{FormRejection, JsonRejection},
+        Path, Query, State,
+    },
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     Form, Json,
 };
-use chrono::NaiveDate;
+use chrono::{NaiveDate, Utc};
+use chrono_tz::Tz;
 use serde::Deserialize;
 use uuid::Uuid;
 
-use calendsync_core::calendar::{merge_entry, CalendarEntry, EntryKind, MergeResult};
+use calendsync_core::calendar::{
+    convert_entry, entry_timezone, find_conflicts, localize_entries, localize_entry, merge_entry,
+    occurrence_of, segment_entry, split_series, three_way_merge, toggle_checklist_item,
+    truncate_series, validate_batch, Attendee, BatchError, Calendar, CalendarEntry, ChecklistItem,
+    ConflictError, ConflictScope, EntryKind, EntryWrite, MergeError, MergeResult,
+    OccurrenceOverride, RecurrenceError, RecurrenceScope, Tag, MAX_BATCH_SIZE,
+};
+use calendsync_core::serde::{deserialize_optional_tags, deserialize_optional_timezone};
 use calendsync_core::storage::{DateRange, RepositoryError};
 
 #[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
-use axum::response::Response;
+use calendsync_auth::CurrentUser;
 
 #[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
-use calendsync_auth::CurrentUser;
+use super::authz::{
+    require_move_access, require_read_access, require_rsvp_access, require_write_access, AuthzError,
+};
 
 #[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
-use super::authz::{require_read_access, require_write_access};
+use calendsync_core::calendar::{respond, ResponseStatus, User};
 
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+use crate::models::RespondToEntry;
+
+use super::preconditions::{check_if_match, conditional_write_error, etag_header};
 use crate::{
     handlers::AppError,
-    models::{CreateEntry, UpdateEntry},
+    models::{BatchEntries, BatchOperation, CreateEntry, MoveEntry, UpdateEntry},
     state::AppState,
 };
 
+/// How many times an RSVP is saved before giving up, when other writes to the
+/// entry keep getting in first.
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+const RSVP_ATTEMPTS: usize = 5;
+
 /// Error response with message (for form validation errors).
 fn error_response(status: StatusCode, message: impl Into<String>) -> (StatusCode, String) {
     let msg = message.into();
@@ -53,6 +75,28 @@ pub struct ListEntriesQuery {
     /// Number of days after highlighted_day (default: 365)
     #[serde(default = "default_after")]
     pub after: i64,
+    /// Viewer's IANA time zone (default: the user's display zone)
+    #[serde(default, deserialize_with = "deserialize_optional_timezone")]
+    pub timezone: Option<Tz>,
+    /// Comma-separated tags; only entries with at least one of them are listed
+    #[serde(default, deserialize_with = "deserialize_optional_tags")]
+    pub tags: Option<BTreeSet<Tag>>,
+}
+
+/// Query parameters for deleting an entry.
+#[derive(Debug, Default, Deserialize)]
+pub struct DeleteEntryQuery {
+    /// Which occurrences of a recurring series to delete (default: all)
+    pub scope: Option<RecurrenceScope>,
+    /// Original date of the selected occurrence (required for `this` and `following`)
+    pub occurrence_date: Option<NaiveDate>,
+}
+
+/// Query parameters for toggling a task.
+#[derive(Debug, Default, Deserialize)]
+pub struct ToggleEntryQuery {
+    /// Zero-based index of the checklist item to toggle instead of the task itself
+    pub item: Option<usize>,
 }
 
 fn default_before() -> i64 {
@@ -79,7 +123,7 @@ pub async fn list_entries(
         .await
         .map_err(IntoResponse::into_response)?;
 
-    list_entries_impl(&state, query)
+    list_entries_impl(&state, query, user.timezone)
         .await
         .map_err(IntoResponse::into_response)
 }
@@ -90,26 +134,58 @@ pub async fn list_entries(
     State(state): State<AppState>,
     Query(query): Query<ListEntriesQuery>,
 ) -> Result<Json<Vec<serde_json::Value>>, AppError> {
-    list_entries_impl(&state, query).await
+    list_entries_impl(&state, query, None).await
 }
 
+/// Lists entries bucketed into the viewer's local days.
+///
+/// The viewer's zone comes from the `timezone` query parameter, falling back to
+/// the user's display zone. Without one, entries are returned as stored.
 async fn list_entries_impl(
     state: &AppState,
     query: ListEntriesQuery,
+    user_tz: Option<Tz>,
 ) -> Result<Json<Vec<serde_json::Value>>, AppError> {
-    let highlighted = query
-        .highlighted_day
-        .unwrap_or_else(|| chrono::Local::now().date_naive());
+    let viewer_tz = query.timezone.or(user_tz);
+    let highlighted = query.highlighted_day.unwrap_or_else(|| match viewer_tz {
+        Some(tz) => Utc::now().with_timezone(&tz).date_naive(),
+        None => chrono::Local::now().date_naive(),
+    });
 
     let start = highlighted - chrono::Duration::days(query.before);
     let end = highlighted + chrono::Duration::days(query.after);
 
-    let date_range = DateRange::new(start, end)?;
+    // Timed entries can move a day either way once converted, so fetch one
+    // extra day on each side; entries_to_server_days trims the result.
+    let date_range = DateRange::new(
+        start - chrono::Duration::days(1),
+        end + chrono::Duration::days(1),
+    )?;
+
+    let entries = match &query.tags {
+        Some(tags) if !tags.is_empty() => {
+            state
+                .entry_repo
+                .get_entries_by_tags(query.calendar_id, tags, date_range)
+                .await?
+        }
+        _ => {
+            state
+                .entry_repo
+                .get_entries_by_calendar(query.calendar_id, date_range)
+                .await?
+        }
+    };
 
-    let entries = state
-        .entry_repo
-        .get_entries_by_calendar(query.calendar_id, date_range)
-        .await?;
+    let calendar_tz = match viewer_tz {
+        Some(_) => state
+            .calendar_repo
+            .get_calendar(query.calendar_id)
+            .await?
+            .and_then(|calendar| calendar.timezone),
+        None => None,
+    };
+    let entries = localize_entries(entries, calendar_tz, viewer_tz);
 
     let entry_refs: Vec<&CalendarEntry> = entries.iter().collect();
     let days = entries_to_server_days(&entry_refs, start, end);
@@ -142,7 +218,7 @@ pub async fn create_entry(
         .await
         .map_err(IntoResponse::into_response)?;
 
-    create_entry_impl(&state, payload)
+    create_entry_impl(&state, payload, Some(user.id))
         .await
         .map_err(IntoResponse::into_response)
 }
@@ -160,15 +236,20 @@ pub async fn create_entry(
         )
     })?;
 
-    create_entry_impl(&state, payload).await
+    create_entry_impl(&state, payload, None).await
 }
 
 async fn create_entry_impl(
     state: &AppState,
-    payload: CreateEntry,
+    mut payload: CreateEntry,
+    acting_user: Option<Uuid>,
 ) -> Result<(StatusCode, Json<serde_json::Value>), (StatusCode, String)> {
     tracing::debug!(payload = ?payload, "Received create entry request");
 
+    if let Some(attendees) = payload.attendees.as_mut() {
+        link_attendee_users(state, attendees).await?;
+    }
+
     // Verify the calendar exists
     let calendar = state
         .calendar_repo
@@ -183,23 +264,32 @@ async fn create_entry_impl(
         ));
     }
 
+    let check = ConflictCheck::new(
+        payload.conflict_scope,
+        payload.reject_on_conflict,
+        acting_user,
+    );
     let entry = payload.into_entry().ok_or_else(|| {
         error_response(
             StatusCode::BAD_REQUEST,
             "Invalid entry data: missing required fields for entry type",
         )
     })?;
+    let conflicts = check_conflicts(state, &entry, &check).await?;
 
     // Create entry via repository (which handles cache invalidation and event publishing)
     state
         .entry_repo
-        .create_entry(&entry)
+        .create_entry_as(&entry, acting_user)
         .await
         .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
     tracing::info!(entry_id = %entry.id, title = %entry.title, "Created new entry");
 
-    Ok((StatusCode::CREATED, Json(entry_to_server_entry(&entry))))
+    Ok((
+        StatusCode::CREATED,
+        Json(with_conflicts(entry_to_server_entry(&entry), &conflicts)),
+    ))
 }
 
 // ============================================================================
@@ -212,7 +302,7 @@ pub async fn get_entry(
     CurrentUser(user): CurrentUser,
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> Result<Json<CalendarEntry>, Response> {
+) -> Result<impl IntoResponse, Response> {
     // First fetch the entry to get its calendar_id
     let entry = state
         .entry_repo
@@ -233,7 +323,7 @@ pub async fn get_entry(
         .await
         .map_err(IntoResponse::into_response)?;
 
-    Ok(Json(entry))
+    Ok((etag_header(entry.version), Json(entry)))
 }
 
 /// Get a single entry by ID (GET /api/entries/{id}) - no auth.
@@ -241,11 +331,11 @@ pub async fn get_entry(
 pub async fn get_entry(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> Result<Json<CalendarEntry>, AppError> {
+) -> Result<impl IntoResponse, AppError> {
     let entry = state.entry_repo.get_entry(id).await?;
 
     match entry {
-        Some(e) => Ok(Json(e)),
+        Some(e) => Ok((etag_header(e.version), Json(e))),
         None => Err(RepositoryError::NotFound {
             entity_type: "CalendarEntry",
             id: id.to_string(),
@@ -264,6 +354,7 @@ pub async fn update_entry(
     CurrentUser(user): CurrentUser,
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    headers: HeaderMap,
     form_result: Result<Form<UpdateEntry>, FormRejection>,
 ) -> Result<Json<serde_json::Value>, Response> {
     let Form(payload) = form_result.map_err(|e| {
@@ -291,8 +382,9 @@ pub async fn update_entry(
     require_write_access(auth, server_entry.calendar_id, user.id)
         .await
         .map_err(IntoResponse::into_response)?;
+    check_if_match(&headers, server_entry.version).map_err(IntoResponse::into_response)?;
 
-    update_entry_impl(&state, id, payload, server_entry)
+    update_entry_impl(&state, id, payload, server_entry, Some(user.id))
         .await
         .map_err(IntoResponse::into_response)
 }
@@ -302,6 +394,7 @@ pub async fn update_entry(
 pub async fn update_entry(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    headers: HeaderMap,
     form_result: Result<Form<UpdateEntry>, FormRejection>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
     let Form(payload) = form_result.map_err(|e| {
@@ -317,32 +410,85 @@ pub async fn update_entry(
         .await
         .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
         .ok_or_else(|| error_response(StatusCode::NOT_FOUND, format!("Entry {id} not found")))?;
+    check_if_match(&headers, server_entry.version)?;
 
-    update_entry_impl(&state, id, payload, server_entry).await
+    update_entry_impl(&state, id, payload, server_entry, None).await
 }
 
 /// Update an entry by ID (PUT /api/entries/{id}).
 ///
-/// Uses Last-Write-Wins (LWW) merge strategy when the client provides an `updated_at` timestamp.
-/// If the client's timestamp is newer than the server's, the update is applied.
+/// When the client provides the `base_revision` it edited from, its changes are merged
+/// field by field with everything saved since; edits to the same field conflict with 409.
+///
+/// Otherwise uses Last-Write-Wins (LWW) merge strategy when the client provides an `updated_at`
+/// timestamp. If the client's timestamp is newer than the server's, the update is applied.
 /// Otherwise, the server's current entry is returned without modification.
+///
+/// For recurring entries, `scope` selects whether the edit applies to the whole series
+/// (default), a single occurrence, or an occurrence and every later one.
+///
+/// The edited entry is checked for overlapping timed entries; any are listed
+/// under `conflicts`, or fail the update when `reject_on_conflict` is set.
+///
+/// The entry is only saved while it is still at the version read here, so an
+/// update that races another server's write fails with 412.
 async fn update_entry_impl(
     state: &AppState,
     id: Uuid,
-    payload: UpdateEntry,
+    mut payload: UpdateEntry,
     server_entry: CalendarEntry,
+    acting_user: Option<Uuid>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
     tracing::debug!(entry_id = %id, payload = ?payload, "Received update entry request");
 
-    // Extract client timestamp for LWW merge
+    if let Some(attendees) = payload.attendees.as_mut() {
+        link_attendee_users(state, attendees).await?;
+    }
+
+    // The calendar's zone is only needed when the entry has none of its own
+    let editor_tz = payload.timezone;
+    let calendar_tz = match (editor_tz, server_entry.timezone) {
+        (Some(_), None) => state
+            .calendar_repo
+            .get_calendar(server_entry.calendar_id)
+            .await
+            .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            .and_then(|calendar| calendar.timezone),
+        _ => None,
+    };
+
+    let check = ConflictCheck::new(
+        payload.conflict_scope,
+        payload.reject_on_conflict,
+        acting_user,
+    );
+    let scope = payload.scope.unwrap_or_default();
+    if scope != RecurrenceScope::All {
+        let date = payload
+            .occurrence_date
+            .ok_or(RecurrenceError::MissingOccurrenceDate(scope.as_str()))
+            .map_err(|e| error_response(StatusCode::BAD_REQUEST, e.to_string()))?;
+
+        return match scope {
+            RecurrenceScope::This => {
+                update_occurrence(state, payload, server_entry, date, calendar_tz, &check).await
+            }
+            _ => update_following(state, payload, server_entry, date, calendar_tz, &check).await,
+        };
+    }
+
+    // Extract the client's base revision and timestamp for merging
+    let base_revision = payload.base_revision;
     let client_updated_at = payload.updated_at;
 
     // Apply updates to create the proposed client entry
-    let mut proposed_entry = server_entry.clone();
-    payload.apply_to(&mut proposed_entry);
+    let proposed_entry = apply_update(payload, &server_entry, calendar_tz);
 
-    // Perform LWW merge if client provided a timestamp
-    let final_entry = if let Some(client_ts) = client_updated_at {
+    // Prefer a three-way merge against the revision the client edited, then
+    // fall back to LWW if the client only provided a timestamp
+    let final_entry = if let Some(base_revision) = base_revision {
+        merge_from_base(state, &server_entry, proposed_entry, base_revision).await?
+    } else if let Some(client_ts) = client_updated_at {
         // Create a temporary entry with the client's timestamp for comparison
         let client_entry = proposed_entry.clone().with_updated_at(client_ts);
 
@@ -365,6 +511,7 @@ async fn update_entry_impl(
                     "Server wins LWW merge"
                 );
                 // Return server's current entry without persisting
+                let server = localize_entry(server, calendar_tz, editor_tz);
                 return Ok(Json(entry_to_server_entry(&server)));
             }
         }
@@ -372,17 +519,355 @@ async fn update_entry_impl(
         // No client timestamp provided, apply update unconditionally (legacy behavior)
         proposed_entry
     };
+    let conflicts = check_conflicts(state, &final_entry, &check).await?;
 
     // Update via repository (which handles cache invalidation and event publishing)
     state
         .entry_repo
-        .update_entry(&final_entry)
+        .update_entry_if_version_as(&final_entry, server_entry.version, acting_user)
         .await
-        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(conditional_write_error)?;
 
     tracing::info!(entry_id = %id, "Updated entry");
 
-    Ok(Json(entry_to_server_entry(&final_entry)))
+    let mut final_entry = final_entry;
+    final_entry.version = server_entry.version + 1;
+
+    let final_entry = localize_entry(final_entry, calendar_tz, editor_tz);
+    Ok(Json(with_conflicts(
+        entry_to_server_entry(&final_entry),
+        &conflicts,
+    )))
+}
+
+/// Links attendees to the accounts their email addresses belong to, so those
+/// users can answer the invitation.
+pub(crate) async fn link_attendee_users(
+    state: &AppState,
+    attendees: &mut [Attendee],
+) -> Result<(), (StatusCode, String)> {
+    for attendee in attendees.iter_mut().filter(|a| a.user_id.is_none()) {
+        let user = state
+            .user_repo
+            .get_user_by_email(&attendee.email)
+            .await
+            .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        if let Some(user) = user {
+            attendee.link_user(&user);
+        }
+    }
+    Ok(())
+}
+
+/// How a created or edited entry is checked for overlapping timed entries.
+struct ConflictCheck {
+    scope: ConflictScope,
+    reject: bool,
+    /// User whose calendars are searched for `ConflictScope::All`, and
+    /// who is recorded as making the change.
+    acting_user: Option<Uuid>,
+}
+
+impl ConflictCheck {
+    fn new(scope: Option<ConflictScope>, reject: Option<bool>, acting_user: Option<Uuid>) -> Self {
+        Self {
+            scope: scope.unwrap_or_default(),
+            reject: reject.unwrap_or(false),
+            acting_user,
+        }
+    }
+}
+
+/// Finds the timed entries that overlap `entry`, or fails with 409 when the
+/// check rejects conflicts.
+///
+/// Only the entry's first occurrence is compared; other entries' recurring
+/// series are expanded over the days around it.
+async fn check_conflicts(
+    state: &AppState,
+    entry: &CalendarEntry,
+    check: &ConflictCheck,
+) -> Result<Vec<CalendarEntry>, (StatusCode, String)> {
+    if !entry.kind.is_timed() {
+        return Ok(Vec::new());
+    }
+
+    let calendars = conflict_calendars(state, entry.calendar_id, check).await?;
+    let calendar_timezones: HashMap<Uuid, Tz> = calendars
+        .iter()
+        .filter_map(|calendar| calendar.timezone.map(|tz| (calendar.id, tz)))
+        .collect();
+
+    // Zones can shift an entry by up to a day either way
+    let range = DateRange::new(
+        entry.start_date - chrono::Duration::days(1),
+        entry.end_date + chrono::Duration::days(1),
+    )
+    .map_err(|e| error_response(StatusCode::BAD_REQUEST, e.to_string()))?;
+    let mut others = Vec::new();
+    for calendar in &calendars {
+        others.extend(
+            state
+                .entry_repo
+                .get_entries_by_calendar(calendar.id, range)
+                .await
+                .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+        );
+    }
+
+    let conflicts: Vec<CalendarEntry> = find_conflicts(entry, &others, &calendar_timezones)
+        .into_iter()
+        .cloned()
+        .collect();
+    if check.reject && !conflicts.is_empty() {
+        let titles = conflicts.iter().map(|c| c.title.clone()).collect();
+        return Err(error_response(
+            StatusCode::CONFLICT,
+            ConflictError::Overlaps(titles).to_string(),
+        ));
+    }
+    Ok(conflicts)
+}
+
+/// Returns the calendars an entry in `calendar_id` is checked against.
+///
+/// `ConflictScope::All` needs an acting user; without one (no auth) only the
+/// entry's own calendar is checked.
+async fn conflict_calendars(
+    state: &AppState,
+    calendar_id: Uuid,
+    check: &ConflictCheck,
+) -> Result<Vec<Calendar>, (StatusCode, String)> {
+    if let (ConflictScope::All, Some(user_id)) = (check.scope, check.acting_user) {
+        return state
+            .membership_repo
+            .get_calendars_for_user(user_id)
+            .await
+            .map(|calendars| calendars.into_iter().map(|(c, _)| c).collect())
+            .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()));
+    }
+    let calendar = state
+        .calendar_repo
+        .get_calendar(calendar_id)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(calendar.into_iter().collect())
+}
+
+/// Adds the entries that overlap a saved entry to its response, if there are any.
+fn with_conflicts(mut json: serde_json::Value, conflicts: &[CalendarEntry]) -> serde_json::Value {
+    if !conflicts.is_empty() {
+        json["conflicts"] = conflicts.iter().map(entry_to_server_entry).collect();
+    }
+    json
+}
+
+/// Applies an update whose dates and times are expressed in the payload's zone.
+///
+/// When that differs from the entry's zone, the entry is re-expressed in the
+/// payload's zone, updated, and converted back so it keeps its own zone.
+fn apply_update(
+    payload: UpdateEntry,
+    entry: &CalendarEntry,
+    calendar_tz: Option<Tz>,
+) -> CalendarEntry {
+    match (entry_timezone(entry, calendar_tz), payload.timezone) {
+        (Some(source), Some(editor)) if source != editor => {
+            let mut local = convert_entry(entry, source, editor);
+            payload.apply_to(&mut local);
+            let mut updated = convert_entry(&local, editor, source);
+            updated.timezone = entry.timezone;
+            updated
+        }
+        _ => {
+            let mut updated = entry.clone();
+            payload.apply_to(&mut updated);
+            updated
+        }
+    }
+}
+
+/// Edits a single occurrence of a recurring series by storing an override.
+///
+/// Re-editing an occurrence starts from its existing replacement; editing a
+/// cancelled occurrence is a 404.
+async fn update_occurrence(
+    state: &AppState,
+    payload: UpdateEntry,
+    series: CalendarEntry,
+    date: NaiveDate,
+    calendar_tz: Option<Tz>,
+    check: &ConflictCheck,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let id = series.id;
+    let editor_tz = payload.timezone;
+    let existing = state
+        .entry_repo
+        .get_occurrence_overrides(id)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .into_iter()
+        .find(|o| o.original_date == date);
+
+    let occurrence = match existing {
+        Some(OccurrenceOverride {
+            replacement: Some(replacement),
+            ..
+        }) => replacement,
+        Some(_) => {
+            return Err(error_response(
+                StatusCode::NOT_FOUND,
+                format!("Occurrence {date} of entry {id} was deleted"),
+            ))
+        }
+        None => occurrence_of(&series, date)
+            .map_err(|e| error_response(StatusCode::BAD_REQUEST, e.to_string()))?,
+    };
+    let mut occurrence = apply_update(payload, &occurrence, calendar_tz);
+    let conflicts = check_conflicts(state, &occurrence, check).await?;
+
+    // Saved as an edit of the series, so it is recorded in its history and
+    // fails with 412 if the series changed since it was read
+    state
+        .entry_repo
+        .upsert_occurrence_override_if_version_as(
+            &OccurrenceOverride::replaced(id, date, occurrence.clone()),
+            series.version,
+            check.acting_user,
+        )
+        .await
+        .map_err(conditional_write_error)?;
+
+    tracing::info!(entry_id = %id, occurrence_date = %date, "Updated occurrence");
+
+    occurrence.occurrence_date = Some(date);
+    occurrence.recurrence = series.recurrence;
+    let occurrence = localize_entry(occurrence, calendar_tz, editor_tz);
+    Ok(Json(with_conflicts(
+        entry_to_server_entry(&occurrence),
+        &conflicts,
+    )))
+}
+
+/// Three-way merges a client's edit with the changes made since `base_revision`.
+///
+/// Fails with 409 when both sides changed the same field differently, so
+/// neither edit is lost.
+async fn merge_from_base(
+    state: &AppState,
+    server_entry: &CalendarEntry,
+    proposed_entry: CalendarEntry,
+    base_revision: u32,
+) -> Result<CalendarEntry, (StatusCode, String)> {
+    let id = server_entry.id;
+    let base = state
+        .entry_repo
+        .get_entry_revisions(id)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .into_iter()
+        .find(|r| r.revision == base_revision)
+        .ok_or_else(|| {
+            error_response(
+                StatusCode::BAD_REQUEST,
+                MergeError::UnknownBase(base_revision).to_string(),
+            )
+        })?;
+
+    let merged = three_way_merge(&base.snapshot, server_entry, &proposed_entry);
+    if !merged.is_clean() {
+        let fields = merged.conflicts.iter().map(|f| f.to_string()).collect();
+        return Err(error_response(
+            StatusCode::CONFLICT,
+            MergeError::Conflicts(fields).to_string(),
+        ));
+    }
+
+    tracing::debug!(entry_id = %id, base_revision, "Merged edit with base revision");
+    let mut entry = merged.entry;
+    entry.updated_at = proposed_entry.updated_at;
+    Ok(entry)
+}
+
+/// Edits an occurrence and every later one by splitting the series in two.
+///
+/// The original series is truncated before `date` and the edit is applied to a
+/// new series starting on `date`. Editing from the first occurrence updates the
+/// whole series in place.
+async fn update_following(
+    state: &AppState,
+    payload: UpdateEntry,
+    series: CalendarEntry,
+    date: NaiveDate,
+    calendar_tz: Option<Tz>,
+    check: &ConflictCheck,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let id = series.id;
+    let editor_tz = payload.timezone;
+    let (head, tail) = split_series(&series, date)
+        .map_err(|e| error_response(StatusCode::BAD_REQUEST, e.to_string()))?;
+
+    let Some(head) = head else {
+        let updated = apply_update(payload, &series, calendar_tz);
+        let conflicts = check_conflicts(state, &updated, check).await?;
+        state
+            .entry_repo
+            .update_entry_if_version_as(&updated, series.version, check.acting_user)
+            .await
+            .map_err(conditional_write_error)?;
+
+        tracing::info!(entry_id = %id, "Updated entry");
+        let updated = localize_entry(updated, calendar_tz, editor_tz);
+        return Ok(Json(with_conflicts(
+            entry_to_server_entry(&updated),
+            &conflicts,
+        )));
+    };
+
+    let tail = apply_update(payload, &tail, calendar_tz);
+    let conflicts = check_conflicts(state, &tail, check).await?;
+
+    // Truncating the series first fails the split with 412 if it changed
+    // since it was read, before anything else is written
+    truncate_series_at(state, &head, date, series.version, check.acting_user)
+        .await
+        .map_err(conditional_write_error)?;
+    state
+        .entry_repo
+        .create_entry_as(&tail, check.acting_user)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    tracing::info!(entry_id = %id, new_entry_id = %tail.id, occurrence_date = %date, "Split series");
+
+    let tail = localize_entry(tail, calendar_tz, editor_tz);
+    Ok(Json(with_conflicts(
+        entry_to_server_entry(&tail),
+        &conflicts,
+    )))
+}
+
+/// Stores `head`, a series truncated before `date`, dropping the overrides of
+/// the occurrences it no longer has, if the series is still at
+/// `expected_version`.
+async fn truncate_series_at(
+    state: &AppState,
+    head: &CalendarEntry,
+    date: NaiveDate,
+    expected_version: u64,
+    acting_user: Option<Uuid>,
+) -> Result<(), RepositoryError> {
+    let kept: Vec<_> = state
+        .entry_repo
+        .get_occurrence_overrides(head.id)
+        .await?
+        .into_iter()
+        .filter(|o| o.original_date < date)
+        .collect();
+    state
+        .entry_repo
+        .update_series_if_version_as(head, &kept, expected_version, acting_user)
+        .await
 }
 
 // ============================================================================
@@ -395,6 +880,8 @@ pub async fn delete_entry(
     CurrentUser(user): CurrentUser,
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    Query(query): Query<DeleteEntryQuery>,
+    headers: HeaderMap,
 ) -> Result<StatusCode, Response> {
     tracing::debug!(entry_id = %id, "Received delete entry request");
 
@@ -417,17 +904,11 @@ pub async fn delete_entry(
     require_write_access(auth, entry.calendar_id, user.id)
         .await
         .map_err(IntoResponse::into_response)?;
+    check_if_match(&headers, entry.version).map_err(IntoResponse::into_response)?;
 
-    // Delete via repository (which handles cache invalidation and event publishing)
-    state
-        .entry_repo
-        .delete_entry(id)
+    delete_entry_impl(&state, entry, query, Some(user.id))
         .await
-        .map_err(|e| AppError::from(e).into_response())?;
-
-    tracing::info!(entry_id = %id, "Deleted entry");
-
-    Ok(StatusCode::OK)
+        .map_err(IntoResponse::into_response)
 }
 
 /// Delete an entry by ID (DELETE /api/entries/{id}) - no auth.
@@ -435,13 +916,82 @@ pub async fn delete_entry(
 pub async fn delete_entry(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> Result<StatusCode, AppError> {
+    Query(query): Query<DeleteEntryQuery>,
+    headers: HeaderMap,
+) -> Result<StatusCode, Response> {
     tracing::debug!(entry_id = %id, "Received delete entry request");
 
-    // Delete via repository (which handles cache invalidation and event publishing)
-    state.entry_repo.delete_entry(id).await?;
+    let entry = state
+        .entry_repo
+        .get_entry(id)
+        .await
+        .map_err(|e| AppError::from(e).into_response())?
+        .ok_or_else(|| {
+            AppError::from(RepositoryError::NotFound {
+                entity_type: "CalendarEntry",
+                id: id.to_string(),
+            })
+            .into_response()
+        })?;
+    check_if_match(&headers, entry.version).map_err(IntoResponse::into_response)?;
+
+    delete_entry_impl(&state, entry, query, None)
+        .await
+        .map_err(IntoResponse::into_response)
+}
 
-    tracing::info!(entry_id = %id, "Deleted entry");
+/// Delete an entry, or some occurrences of a recurring entry.
+///
+/// `scope=this` cancels a single occurrence and `scope=following` ends the
+/// series before the selected occurrence; both require `occurrence_date`.
+///
+/// The entry is only changed while it is still at the version read here, so
+/// a delete that races another server's write fails with 412.
+async fn delete_entry_impl(
+    state: &AppState,
+    entry: CalendarEntry,
+    query: DeleteEntryQuery,
+    acting_user: Option<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let id = entry.id;
+    let scope = query.scope.unwrap_or_default();
+
+    // Repository calls handle cache invalidation and event publishing
+    match (scope, query.occurrence_date) {
+        (RecurrenceScope::All, _) => {
+            state
+                .entry_repo
+                .delete_entry_if_version_as(id, entry.version, acting_user)
+                .await?;
+            tracing::info!(entry_id = %id, "Deleted entry");
+        }
+        (_, None) => return Err(RecurrenceError::MissingOccurrenceDate(scope.as_str()).into()),
+        (RecurrenceScope::This, Some(date)) => {
+            occurrence_of(&entry, date)?;
+            state
+                .entry_repo
+                .upsert_occurrence_override_if_version_as(
+                    &OccurrenceOverride::cancelled(id, date),
+                    entry.version,
+                    acting_user,
+                )
+                .await?;
+            tracing::info!(entry_id = %id, occurrence_date = %date, "Deleted occurrence");
+        }
+        (RecurrenceScope::Following, Some(date)) => match truncate_series(&entry, date)? {
+            Some(head) => {
+                truncate_series_at(state, &head, date, entry.version, acting_user).await?;
+                tracing::info!(entry_id = %id, occurrence_date = %date, "Truncated series");
+            }
+            None => {
+                state
+                    .entry_repo
+                    .delete_entry_if_version_as(id, entry.version, acting_user)
+                    .await?;
+                tracing::info!(entry_id = %id, "Deleted entry");
+            }
+        },
+    }
 
     Ok(StatusCode::OK)
 }
@@ -451,11 +1001,14 @@ pub async fn delete_entry(
 // ============================================================================
 
 /// Toggle a task's completion status (PATCH /api/entries/{id}/toggle) - with auth.
+/// With `?item=N`, toggles the task's Nth checklist item instead.
 #[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
 pub async fn toggle_entry(
     CurrentUser(user): CurrentUser,
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    Query(query): Query<ToggleEntryQuery>,
+    headers: HeaderMap,
 ) -> Result<Json<serde_json::Value>, Response> {
     tracing::debug!(entry_id = %id, "Received toggle entry request");
 
@@ -476,17 +1029,21 @@ pub async fn toggle_entry(
     require_write_access(auth, existing.calendar_id, user.id)
         .await
         .map_err(IntoResponse::into_response)?;
+    check_if_match(&headers, existing.version).map_err(IntoResponse::into_response)?;
 
-    toggle_entry_impl(&state, id, existing)
+    toggle_entry_impl(&state, id, existing, query, Some(user.id))
         .await
         .map_err(IntoResponse::into_response)
 }
 
 /// Toggle a task's completion status (PATCH /api/entries/{id}/toggle) - no auth.
+/// With `?item=N`, toggles the task's Nth checklist item instead.
 #[cfg(not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")))]
 pub async fn toggle_entry(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    Query(query): Query<ToggleEntryQuery>,
+    headers: HeaderMap,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
     tracing::debug!(entry_id = %id, "Received toggle entry request");
 
@@ -496,19 +1053,33 @@ pub async fn toggle_entry(
         .await
         .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
         .ok_or_else(|| error_response(StatusCode::NOT_FOUND, format!("Entry {id} not found")))?;
+    check_if_match(&headers, existing.version)?;
 
-    toggle_entry_impl(&state, id, existing).await
+    toggle_entry_impl(&state, id, existing, query, None).await
 }
 
+/// Toggles a task or one of its checklist items.
+///
+/// The entry is only saved while it is still at the version read here, so a
+/// toggle that races another server's write fails with 412.
 async fn toggle_entry_impl(
     state: &AppState,
     id: Uuid,
     existing: CalendarEntry,
+    query: ToggleEntryQuery,
+    acting_user: Option<Uuid>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let version = existing.version;
+
     // Toggle if it's a task
     let mut updated_entry = existing;
-    match &mut updated_entry.kind {
-        EntryKind::Task { completed } => {
+    match (&mut updated_entry.kind, query.item) {
+        (kind, Some(index)) => {
+            let completed = toggle_checklist_item(kind, index)
+                .map_err(|e| error_response(StatusCode::BAD_REQUEST, e.to_string()))?;
+            tracing::info!(entry_id = %id, item = index, completed = %completed, "Toggled checklist item");
+        }
+        (EntryKind::Task { completed, .. }, None) => {
             *completed = !*completed;
             tracing::info!(entry_id = %id, completed = %completed, "Toggled task");
         }
@@ -523,13 +1094,576 @@ async fn toggle_entry_impl(
     // Update via repository (which handles cache invalidation and event publishing)
     state
         .entry_repo
-        .update_entry(&updated_entry)
+        .update_entry_if_version_as(&updated_entry, version, acting_user)
         .await
-        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(conditional_write_error)?;
+    updated_entry.version = version + 1;
 
     Ok(Json(entry_to_server_entry(&updated_entry)))
 }
 
+// ============================================================================
+// RSVP
+// ============================================================================
+
+/// Answer an invitation to an entry as the current user (PUT /api/entries/{id}/rsvp).
+///
+/// The response applies to every occurrence of a recurring entry. The update
+/// is published as an `entry_updated` event like any other edit.
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+pub async fn rsvp_entry(
+    CurrentUser(user): CurrentUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    form_result: Result<Form<RespondToEntry>, FormRejection>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let Form(payload) = form_result.map_err(|e| {
+        error_response(
+            StatusCode::BAD_REQUEST,
+            format!("Failed to parse form: {e}"),
+        )
+        .into_response()
+    })?;
+
+    let entry = state
+        .entry_repo
+        .get_entry(id)
+        .await
+        .map_err(|e| {
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        })?
+        .ok_or_else(|| {
+            error_response(StatusCode::NOT_FOUND, format!("Entry {id} not found")).into_response()
+        })?;
+
+    // Invited readers may answer even though they cannot edit the entry
+    let auth = state.auth.as_ref().expect("Auth state required");
+    require_rsvp_access(auth, &entry, &user)
+        .await
+        .map_err(IntoResponse::into_response)?;
+
+    let entry = save_rsvp(&state, entry, &user, payload.status)
+        .await
+        .map_err(IntoResponse::into_response)?;
+
+    tracing::info!(entry_id = %id, user_id = %user.id, status = %payload.status, "Recorded RSVP");
+
+    Ok(Json(entry_to_server_entry(&entry)))
+}
+
+/// Records `user`'s response on `entry`, saving it only while the entry is
+/// still the version the response was applied to.
+///
+/// An RSVP only touches the caller's own attendee, so when another write got
+/// in first the entry is read again from storage, past a cache that may still
+/// hold the old version, and the response applied to that instead.
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+async fn save_rsvp(
+    state: &AppState,
+    mut entry: CalendarEntry,
+    user: &User,
+    status: ResponseStatus,
+) -> Result<CalendarEntry, (StatusCode, String)> {
+    let mut attempt = 1;
+    loop {
+        let version = entry.version;
+        respond(&mut entry.attendees, user, status)
+            .map_err(|e| error_response(StatusCode::FORBIDDEN, e.to_string()))?;
+        entry.updated_at = Utc::now();
+
+        // Update via repository (which handles cache invalidation and event publishing)
+        match state
+            .entry_repo
+            .update_entry_if_version_as(&entry, version, Some(user.id))
+            .await
+        {
+            Ok(()) => {
+                entry.version = version + 1;
+                return Ok(entry);
+            }
+            Err(RepositoryError::VersionMismatch { .. }) if attempt < RSVP_ATTEMPTS => {
+                attempt += 1;
+                entry = state
+                    .entry_storage
+                    .get_entry(entry.id)
+                    .await
+                    .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+                    .ok_or_else(|| {
+                        error_response(
+                            StatusCode::NOT_FOUND,
+                            format!("Entry {} not found", entry.id),
+                        )
+                    })?;
+            }
+            Err(err) => return Err(conditional_write_error(err)),
+        }
+    }
+}
+
+// ============================================================================
+// Move Entry
+// ============================================================================
+
+/// Move an entry to another calendar (POST /api/entries/{id}/move) - with auth.
+///
+/// Requires write access to both the entry's calendar and the target calendar.
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+pub async fn move_entry(
+    CurrentUser(user): CurrentUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    form_result: Result<Form<MoveEntry>, FormRejection>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let Form(payload) = form_result.map_err(|e| {
+        error_response(
+            StatusCode::BAD_REQUEST,
+            format!("Failed to parse form: {e}"),
+        )
+        .into_response()
+    })?;
+
+    let entry = state
+        .entry_repo
+        .get_entry(id)
+        .await
+        .map_err(|e| {
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        })?
+        .ok_or_else(|| {
+            error_response(StatusCode::NOT_FOUND, format!("Entry {id} not found")).into_response()
+        })?;
+
+    let auth = state.auth.as_ref().expect("Auth state required");
+    require_move_access(auth, entry.calendar_id, payload.calendar_id, user.id)
+        .await
+        .map_err(IntoResponse::into_response)?;
+
+    move_entry_impl(&state, entry, &headers, payload.calendar_id, Some(user.id))
+        .await
+        .map_err(IntoResponse::into_response)
+}
+
+/// Move an entry to another calendar (POST /api/entries/{id}/move) - no auth.
+#[cfg(not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")))]
+pub async fn move_entry(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    form_result: Result<Form<MoveEntry>, FormRejection>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let Form(payload) = form_result.map_err(|e| {
+        error_response(
+            StatusCode::BAD_REQUEST,
+            format!("Failed to parse form: {e}"),
+        )
+    })?;
+
+    let entry = state
+        .entry_repo
+        .get_entry(id)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| error_response(StatusCode::NOT_FOUND, format!("Entry {id} not found")))?;
+
+    move_entry_impl(&state, entry, &headers, payload.calendar_id, None).await
+}
+
+/// Moves an entry to `calendar_id`, keeping its ID, history and overrides.
+///
+/// The source calendar's clients get an `entry_deleted` event and the target's
+/// an `entry_added` event.
+async fn move_entry_impl(
+    state: &AppState,
+    entry: CalendarEntry,
+    headers: &HeaderMap,
+    calendar_id: Uuid,
+    acting_user: Option<Uuid>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    check_if_match(headers, entry.version)?;
+
+    if entry.calendar_id == calendar_id {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            format!("Entry is already in calendar {calendar_id}"),
+        ));
+    }
+    state
+        .calendar_repo
+        .get_calendar(calendar_id)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| error_response(StatusCode::NOT_FOUND, "Calendar not found"))?;
+
+    // Move via repository (which handles cache invalidation and event publishing)
+    let moved = state
+        .entry_repo
+        .move_entry_as(entry.id, calendar_id, entry.version, acting_user)
+        .await
+        .map_err(conditional_write_error)?;
+
+    tracing::info!(
+        entry_id = %entry.id,
+        from_calendar_id = %entry.calendar_id,
+        to_calendar_id = %calendar_id,
+        "Moved entry"
+    );
+
+    Ok(Json(entry_to_server_entry(&moved)))
+}
+
+// ============================================================================
+// Batch Entries
+// ============================================================================
+
+/// Apply several creates, updates and deletes at once (POST /api/entries/batch) - with auth.
+///
+/// Requires write access to every calendar the batch touches.
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+pub async fn batch_entries(
+    CurrentUser(user): CurrentUser,
+    State(state): State<AppState>,
+    json_result: Result<Json<BatchEntries>, JsonRejection>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let Json(payload) = json_result.map_err(|e| {
+        error_response(
+            StatusCode::BAD_REQUEST,
+            format!("Failed to parse batch: {e}"),
+        )
+        .into_response()
+    })?;
+
+    let operations = resolve_batch(&state, payload.operations)
+        .await
+        .map_err(IntoResponse::into_response)?;
+
+    // Access is checked before anything else about the entries is looked at,
+    // and an entry the caller may not write to is reported as missing
+    let auth = state.auth.as_ref().expect("Auth state required");
+    let mut writable = BTreeSet::new();
+    for (index, operation) in operations.iter().enumerate() {
+        let calendar_id = operation.calendar_id();
+        if writable.contains(&calendar_id) {
+            continue;
+        }
+        match (
+            require_write_access(auth, calendar_id, user.id).await,
+            operation.stored(),
+        ) {
+            (Ok(_), _) => {
+                writable.insert(calendar_id);
+            }
+            (Err(err @ AuthzError::LookupFailed { .. }), _) | (Err(err), None) => {
+                return Err(err.into_response())
+            }
+            (Err(_), Some(stored)) => {
+                return Err(batch_entry_not_found(index, stored.id).into_response())
+            }
+        }
+    }
+
+    let batch = prepare_batch(&state, operations)
+        .await
+        .map_err(IntoResponse::into_response)?;
+    batch_entries_impl(&state, batch, Some(user.id))
+        .await
+        .map_err(IntoResponse::into_response)
+}
+
+/// Apply several creates, updates and deletes at once (POST /api/entries/batch) - no auth.
+#[cfg(not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")))]
+pub async fn batch_entries(
+    State(state): State<AppState>,
+    json_result: Result<Json<BatchEntries>, JsonRejection>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let Json(payload) = json_result.map_err(|e| {
+        error_response(
+            StatusCode::BAD_REQUEST,
+            format!("Failed to parse batch: {e}"),
+        )
+    })?;
+
+    let operations = resolve_batch(&state, payload.operations).await?;
+    let batch = prepare_batch(&state, operations).await?;
+    batch_entries_impl(&state, batch, None).await
+}
+
+/// A batch operation with the stored entry it applies to.
+enum ResolvedOperation {
+    Create(CreateEntry),
+    Update {
+        stored: CalendarEntry,
+        version: Option<u64>,
+        changes: Box<UpdateEntry>,
+    },
+    Delete {
+        stored: CalendarEntry,
+        version: Option<u64>,
+    },
+}
+
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+impl ResolvedOperation {
+    /// The entry the operation applies to, unless it creates one.
+    fn stored(&self) -> Option<&CalendarEntry> {
+        match self {
+            Self::Create(_) => None,
+            Self::Update { stored, .. } | Self::Delete { stored, .. } => Some(stored),
+        }
+    }
+
+    /// The calendar the operation writes to.
+    fn calendar_id(&self) -> Uuid {
+        match self {
+            Self::Create(payload) => payload.calendar_id,
+            Self::Update { stored, .. } | Self::Delete { stored, .. } => stored.calendar_id,
+        }
+    }
+}
+
+/// A batch turned into storage writes, with the calendars it touches.
+struct PreparedBatch {
+    writes: Vec<EntryWrite>,
+    calendar_ids: BTreeSet<Uuid>,
+}
+
+/// Fetches the live entry each update and delete applies to.
+///
+/// Only whether the entry exists is checked here, so callers can decide
+/// access on the resulting calendars before anything else is revealed.
+async fn resolve_batch(
+    state: &AppState,
+    operations: Vec<BatchOperation>,
+) -> Result<Vec<ResolvedOperation>, (StatusCode, String)> {
+    if operations.len() > MAX_BATCH_SIZE {
+        return Err(batch_error(BatchError::TooLarge(MAX_BATCH_SIZE)));
+    }
+
+    let mut resolved = Vec::with_capacity(operations.len());
+    for (index, operation) in operations.into_iter().enumerate() {
+        resolved.push(match operation {
+            BatchOperation::Create(payload) => ResolvedOperation::Create(payload),
+            BatchOperation::Update {
+                id,
+                version,
+                changes,
+            } => ResolvedOperation::Update {
+                stored: batch_entry(state, index, id).await?,
+                version,
+                changes: Box::new(changes),
+            },
+            BatchOperation::Delete { id, version } => ResolvedOperation::Delete {
+                stored: batch_entry(state, index, id).await?,
+                version,
+            },
+        });
+    }
+    Ok(resolved)
+}
+
+/// Turns resolved operations into storage writes.
+///
+/// Updates apply to the whole entry; recurrence scopes, merges and conflict
+/// checks are left to the single-entry endpoints. Errors name the index of
+/// the operation that caused them.
+async fn prepare_batch(
+    state: &AppState,
+    operations: Vec<ResolvedOperation>,
+) -> Result<PreparedBatch, (StatusCode, String)> {
+    let mut calendars: HashMap<Uuid, Option<Calendar>> = HashMap::new();
+    let mut writes = Vec::with_capacity(operations.len());
+    let mut calendar_ids = BTreeSet::new();
+
+    for (index, operation) in operations.into_iter().enumerate() {
+        let write = match operation {
+            ResolvedOperation::Create(mut payload) => {
+                if let Some(attendees) = payload.attendees.as_mut() {
+                    link_attendee_users(state, attendees).await?;
+                }
+                if batch_calendar(state, &mut calendars, payload.calendar_id)
+                    .await?
+                    .is_none()
+                {
+                    return Err(error_response(
+                        StatusCode::BAD_REQUEST,
+                        format!(
+                            "Operation {index}: calendar {} not found",
+                            payload.calendar_id
+                        ),
+                    ));
+                }
+                let entry = payload.into_entry().ok_or_else(|| {
+                    error_response(
+                        StatusCode::BAD_REQUEST,
+                        format!("Operation {index}: missing required fields for entry type"),
+                    )
+                })?;
+                EntryWrite::Create(entry)
+            }
+            ResolvedOperation::Update {
+                stored,
+                version,
+                mut changes,
+            } => {
+                if changes
+                    .scope
+                    .is_some_and(|scope| scope != RecurrenceScope::All)
+                {
+                    return Err(error_response(
+                        StatusCode::BAD_REQUEST,
+                        format!("Operation {index}: batches only update whole entries"),
+                    ));
+                }
+                check_batch_version(index, &stored, version)?;
+                if let Some(attendees) = changes.attendees.as_mut() {
+                    link_attendee_users(state, attendees).await?;
+                }
+                let calendar_tz = batch_calendar(state, &mut calendars, stored.calendar_id)
+                    .await?
+                    .as_ref()
+                    .and_then(|calendar| calendar.timezone);
+                EntryWrite::Update {
+                    entry: apply_update(*changes, &stored, calendar_tz),
+                    expected_version: stored.version,
+                }
+            }
+            ResolvedOperation::Delete { stored, version } => {
+                check_batch_version(index, &stored, version)?;
+                calendar_ids.insert(stored.calendar_id);
+                EntryWrite::Delete(stored.id)
+            }
+        };
+        if let EntryWrite::Create(entry) | EntryWrite::Update { entry, .. } = &write {
+            calendar_ids.insert(entry.calendar_id);
+        }
+        writes.push(write);
+    }
+
+    validate_batch(&writes).map_err(batch_error)?;
+    Ok(PreparedBatch {
+        writes,
+        calendar_ids,
+    })
+}
+
+/// Fetches the live entry an operation applies to.
+async fn batch_entry(
+    state: &AppState,
+    index: usize,
+    id: Uuid,
+) -> Result<CalendarEntry, (StatusCode, String)> {
+    state
+        .entry_repo
+        .get_entry(id)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| batch_entry_not_found(index, id))
+}
+
+fn batch_entry_not_found(index: usize, id: Uuid) -> (StatusCode, String) {
+    error_response(
+        StatusCode::NOT_FOUND,
+        format!("Operation {index}: entry {id} not found"),
+    )
+}
+
+/// Checks an entry's version when the operation gives one.
+fn check_batch_version(
+    index: usize,
+    entry: &CalendarEntry,
+    version: Option<u64>,
+) -> Result<(), (StatusCode, String)> {
+    match version {
+        Some(version) if version != entry.version => Err(error_response(
+            StatusCode::PRECONDITION_FAILED,
+            format!(
+                "Operation {index}: entry {} has changed; current version is {}",
+                entry.id, entry.version
+            ),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Looks up a calendar once per batch.
+async fn batch_calendar<'a>(
+    state: &AppState,
+    calendars: &'a mut HashMap<Uuid, Option<Calendar>>,
+    calendar_id: Uuid,
+) -> Result<&'a Option<Calendar>, (StatusCode, String)> {
+    let calendar = match calendars.entry(calendar_id) {
+        Entry::Occupied(cached) => cached.into_mut(),
+        Entry::Vacant(slot) => {
+            let calendar = state
+                .calendar_repo
+                .get_calendar(calendar_id)
+                .await
+                .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            slot.insert(calendar)
+        }
+    };
+    Ok(calendar)
+}
+
+fn batch_error(err: BatchError) -> (StatusCode, String) {
+    error_response(StatusCode::BAD_REQUEST, err.to_string())
+}
+
+/// Applies a prepared batch in one repository call.
+///
+/// Storage applies it atomically where it can, and each calendar's clients
+/// get a single `entry_batch` event. An update that races another write
+/// fails the batch with 412.
+async fn batch_entries_impl(
+    state: &AppState,
+    batch: PreparedBatch,
+    acting_user: Option<Uuid>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let PreparedBatch {
+        writes,
+        calendar_ids,
+    } = batch;
+
+    // Apply via repository (which handles cache invalidation and event publishing)
+    state
+        .entry_repo
+        .apply_entry_batch_as(&writes, acting_user)
+        .await
+        .map_err(conditional_write_error)?;
+
+    tracing::info!(
+        operations = writes.len(),
+        calendars = calendar_ids.len(),
+        "Applied entry batch"
+    );
+
+    let mut created = Vec::new();
+    let mut updated = Vec::new();
+    let mut deleted = Vec::new();
+    for write in writes {
+        match write {
+            EntryWrite::Create(entry) => created.push(entry_to_server_entry(&entry)),
+            EntryWrite::Update {
+                entry,
+                expected_version,
+            } => {
+                let entry = CalendarEntry {
+                    version: expected_version + 1,
+                    ..entry
+                };
+                updated.push(entry_to_server_entry(&entry));
+            }
+            EntryWrite::Delete(id) => deleted.push(id),
+        }
+    }
+
+    Ok(Json(serde_json::json!({
+        "created": created,
+        "updated": updated,
+        "deleted": deleted,
+    })))
+}
+
 // ============================================================================
 // Helper functions for ServerDay[] format
 // ============================================================================
@@ -539,7 +1673,7 @@ pub fn entry_to_server_entry(entry: &CalendarEntry) -> serde_json::Value {
     let (kind, completed, is_multi_day, is_all_day, is_timed, is_task) = match &entry.kind {
         EntryKind::AllDay => ("all-day", false, false, true, false, false),
         EntryKind::Timed { .. } => ("timed", false, false, false, true, false),
-        EntryKind::Task { completed } => ("task", *completed, false, false, false, true),
+        EntryKind::Task { completed, .. } => ("task", *completed, false, false, false, true),
         EntryKind::MultiDay => ("multi-day", false, true, false, false, false),
     };
 
@@ -549,6 +1683,18 @@ pub fn entry_to_server_entry(entry: &CalendarEntry) -> serde_json::Value {
         .map(|t| t.format("%H:%M").to_string());
     let end_time = entry.kind.end_time().map(|t| t.format("%H:%M").to_string());
 
+    // Per-day segment of a timed entry crossing midnight: the hours it covers
+    // on the day it is shown on, from or until midnight where it continues.
+    let segment = entry.segment.filter(|_| entry.kind.is_timed());
+    let segment_start_time = segment.map(|s| match s.continued {
+        true => "00:00".to_string(),
+        false => start_time.clone().unwrap_or_default(),
+    });
+    let segment_end_time = segment.map(|s| match s.continues {
+        true => "24:00".to_string(),
+        false => end_time.clone().unwrap_or_default(),
+    });
+
     serde_json::json!({
         "id": entry.id.to_string(),
         "calendarId": entry.calendar_id.to_string(),
@@ -566,6 +1712,40 @@ pub fn entry_to_server_entry(entry: &CalendarEntry) -> serde_json::Value {
         "endDate": entry.end_date.to_string(),
         "startTime": start_time,
         "endTime": end_time,
+        "recurrence": entry.recurrence.as_ref().map(ToString::to_string),
+        "timezone": entry.timezone.map(|tz| tz.name()),
+        "reminders": entry.reminders.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        "attendees": entry.attendees.iter().map(attendee_to_server_attendee).collect::<Vec<_>>(),
+        "tags": entry.tags.iter().map(Tag::as_str).collect::<Vec<_>>(),
+        "priority": entry.kind.priority().map(|p| p.as_str()),
+        "dueTime": entry.kind.due_time().map(|t| t.format("%H:%M").to_string()),
+        "checklist": entry.kind.checklist().iter().map(checklist_item_to_server_item).collect::<Vec<_>>(),
+        "rollOver": entry.kind.rolls_over(),
+        "occurrenceDate": entry.occurrence_date.map(|d| d.to_string()),
+        "segmentDate": segment.map(|s| s.date.to_string()),
+        "segmentStartTime": segment_start_time,
+        "segmentEndTime": segment_end_time,
+        "continued": segment.is_some_and(|s| s.continued),
+        "continues": segment.is_some_and(|s| s.continues),
+        "version": entry.version,
+    })
+}
+
+/// Convert an Attendee to the ServerAttendee format expected by the frontend.
+fn attendee_to_server_attendee(attendee: &Attendee) -> serde_json::Value {
+    serde_json::json!({
+        "email": attendee.email,
+        "userId": attendee.user_id.map(|id| id.to_string()),
+        "name": attendee.name,
+        "status": attendee.status.as_str(),
+    })
+}
+
+/// Convert a ChecklistItem to the ServerChecklistItem format expected by the frontend.
+fn checklist_item_to_server_item(item: &ChecklistItem) -> serde_json::Value {
+    serde_json::json!({
+        "text": item.text,
+        "completed": item.completed,
     })
 }
 
@@ -586,14 +1766,21 @@ pub fn entries_to_server_days(
     }
 
     // Add entries - use start_date for grouping
-    // (Frontend will expand multi-day entries)
+    // (Frontend will expand multi-day entries; timed entries crossing
+    // midnight are split here so each day gets its own segment)
     for entry in entries {
-        if entry.start_date >= start && entry.start_date <= end {
-            let server_entry = entry_to_server_entry(entry);
-            days_map
-                .entry(entry.start_date)
-                .or_default()
-                .push(server_entry);
+        let pieces = match entry.kind {
+            EntryKind::Timed { .. } => segment_entry((*entry).clone()),
+            _ => vec![(*entry).clone()],
+        };
+        for piece in pieces {
+            let date = piece.display_date();
+            if date >= start && date <= end {
+                days_map
+                    .entry(date)
+                    .or_default()
+                    .push(entry_to_server_entry(&piece));
+            }
         }
     }
 
@@ -607,3 +1794,49 @@ pub fn entries_to_server_days(
         })
         .collect()
 }
+
+#[cfg(all(
+    test,
+    feature = "inmemory",
+    feature = "memory",
+    any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")
+))]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[tokio::test]
+    async fn test_save_rsvp_retries_on_a_newer_version() {
+        let state = AppState::new(&Config::default()).await.unwrap();
+        let user = User::new("Ada", "ada@example.com");
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let mut entry = CalendarEntry::all_day(Uuid::new_v4(), "Standup", date);
+        entry.attendees = vec![Attendee::new("ada@example.com")];
+        state.entry_repo.create_entry(&entry).await.unwrap();
+
+        // The handler read version 1 through the cache, then another writer
+        // renamed the entry behind it
+        let read = state.entry_repo.get_entry(entry.id).await.unwrap().unwrap();
+        let renamed = CalendarEntry {
+            title: "Daily standup".to_string(),
+            ..entry.clone()
+        };
+        state.entry_storage.update_entry(&renamed).await.unwrap();
+
+        let saved = save_rsvp(&state, read, &user, ResponseStatus::Accepted)
+            .await
+            .unwrap();
+        assert_eq!(saved.version, 3);
+
+        // Neither the rename nor the answer is lost
+        let stored = state
+            .entry_storage
+            .get_entry(entry.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.version, 3);
+        assert_eq!(stored.title, "Daily standup");
+        assert_eq!(stored.attendees[0].status, ResponseStatus::Accepted);
+    }
+}
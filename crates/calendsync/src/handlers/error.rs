@@ -2,6 +2,7 @@ use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
 };
+use calendsync_core::calendar::RecurrenceError;
 use calendsync_core::storage::{repository_error_to_status_code, RepositoryError};
 
 pub struct AppError(pub anyhow::Error);
@@ -11,6 +12,8 @@ impl IntoResponse for AppError {
         let status_code = if let Some(repo_error) = self.0.downcast_ref::<RepositoryError>() {
             let code = repository_error_to_status_code(repo_error);
             StatusCode::from_u16(code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+        } else if self.0.is::<RecurrenceError>() {
+            StatusCode::BAD_REQUEST
         } else {
             StatusCode::INTERNAL_SERVER_ERROR
         };
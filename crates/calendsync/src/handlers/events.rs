@@ -24,7 +24,8 @@ use crate::state::{AppState, CalendarEvent};
 /// Serialize a CalendarEvent to JSON, transforming entries to ServerEntry format.
 fn serialize_event(event: &CalendarEvent) -> String {
     match event {
-        CalendarEvent::EntryAdded { entry, date } => {
+        CalendarEvent::EntryAdded { entry, date }
+        | CalendarEvent::EntryRestored { entry, date } => {
             let server_entry = entry_to_server_entry(entry);
             serde_json::json!({
                 "entry": server_entry,
@@ -32,19 +33,60 @@ fn serialize_event(event: &CalendarEvent) -> String {
             })
             .to_string()
         }
-        CalendarEvent::EntryUpdated { entry, date } => {
+        CalendarEvent::EntryUpdated {
+            entry,
+            date,
+            occurrence_date,
+        } => {
             let server_entry = entry_to_server_entry(entry);
             serde_json::json!({
                 "entry": server_entry,
                 "date": date,
+                "occurrence_date": occurrence_date,
             })
             .to_string()
         }
-        CalendarEvent::EntryDeleted { entry_id, date } => serde_json::json!({
+        CalendarEvent::EntryDeleted {
+            entry_id,
+            date,
+            occurrence_date,
+        } => serde_json::json!({
             "entry_id": entry_id,
             "date": date,
+            "occurrence_date": occurrence_date,
         })
         .to_string(),
+        CalendarEvent::ReminderDue {
+            entry,
+            date,
+            reminder,
+            fire_at,
+        } => {
+            let server_entry = entry_to_server_entry(entry);
+            serde_json::json!({
+                "entry": server_entry,
+                "date": date,
+                "reminder": reminder.to_string(),
+                "fire_at": fire_at,
+            })
+            .to_string()
+        }
+        CalendarEvent::EntryBatch {
+            added,
+            updated,
+            deleted,
+            date,
+        } => {
+            let added: Vec<_> = added.iter().map(entry_to_server_entry).collect();
+            let updated: Vec<_> = updated.iter().map(entry_to_server_entry).collect();
+            serde_json::json!({
+                "added": added,
+                "updated": updated,
+                "deleted": deleted,
+                "date": date,
+            })
+            .to_string()
+        }
     }
 }
 
@@ -69,7 +111,7 @@ pub async fn events_sse(
         .await
         .map_err(IntoResponse::into_response)?;
 
-    Ok(events_sse_impl(state, query))
+    Ok(events_sse_impl(state, query).await)
 }
 
 /// SSE endpoint for calendar events - no auth.
@@ -78,17 +120,17 @@ pub async fn events_sse(
     State(state): State<AppState>,
     Query(query): Query<EventsQuery>,
 ) -> Sse<impl tokio_stream::Stream<Item = Result<Event, Infallible>>> {
-    events_sse_impl(state, query)
+    events_sse_impl(state, query).await
 }
 
-fn events_sse_impl(
+async fn events_sse_impl(
     state: AppState,
     query: EventsQuery,
 ) -> Sse<impl tokio_stream::Stream<Item = Result<Event, Infallible>>> {
     let calendar_id = query.calendar_id;
     let last_event_id = query.last_event_id.unwrap_or(0);
 
-    state.ensure_event_listener(calendar_id);
+    state.ensure_event_listener(calendar_id).await;
 
     let mut shutdown_rx = state.subscribe_shutdown();
     let oldest_event_id = state.oldest_event_id();
@@ -113,6 +155,9 @@ fn events_sse_impl(
                 CalendarEvent::EntryAdded { .. } => "entry_added",
                 CalendarEvent::EntryUpdated { .. } => "entry_updated",
                 CalendarEvent::EntryDeleted { .. } => "entry_deleted",
+                CalendarEvent::EntryRestored { .. } => "entry_restored",
+                CalendarEvent::ReminderDue { .. } => "reminder_due",
+                CalendarEvent::EntryBatch { .. } => "entry_batch",
             };
 
             yield Ok(Event::default()
@@ -139,6 +184,9 @@ fn events_sse_impl(
                     CalendarEvent::EntryAdded { .. } => "entry_added",
                     CalendarEvent::EntryUpdated { .. } => "entry_updated",
                     CalendarEvent::EntryDeleted { .. } => "entry_deleted",
+                    CalendarEvent::EntryRestored { .. } => "entry_restored",
+                    CalendarEvent::ReminderDue { .. } => "reminder_due",
+                    CalendarEvent::EntryBatch { .. } => "entry_batch",
                 };
 
                 yield Ok(Event::default()
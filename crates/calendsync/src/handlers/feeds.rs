@@ -0,0 +1,223 @@
+//! iCalendar subscription feed handlers.
+//!
+//! Members manage a secret feed token per calendar, and calendar apps fetch
+//! the calendar at `/feeds/<token>.ics` without a session. The token stands in
+//! for its owner: a feed is only served while the owner can read the calendar.
+
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use calendsync_auth::CurrentUser;
+use calendsync_core::calendar::{
+    parse_feed_file, FeedSubscription, FeedToken, User, ICS_CONTENT_TYPE,
+};
+use calendsync_core::storage::RepositoryError;
+use uuid::Uuid;
+
+use super::authz::{require_read_access, AuthzError};
+use super::ics::render_calendar_ics;
+use crate::state::AppState;
+
+/// Error response with message.
+fn error_response(status: StatusCode, message: impl Into<String>) -> (StatusCode, String) {
+    let msg = message.into();
+    tracing::warn!(status = %status, message = %msg, "API error");
+    (status, msg)
+}
+
+/// Describes a token with the absolute URLs it is served at.
+fn subscription(
+    state: &AppState,
+    token: FeedToken,
+) -> Result<FeedSubscription, (StatusCode, String)> {
+    let auth = state.auth.as_ref().expect("Auth state required");
+    let url = auth
+        .config
+        .base_url
+        .join(&token.path())
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(FeedSubscription::new(token, url.to_string()))
+}
+
+// ============================================================================
+// Manage Feed Tokens
+// ============================================================================
+
+/// Get the current user's feed for a calendar (GET /api/calendars/{id}/feed).
+///
+/// Requires read access to the calendar. Returns 404 if the user has no token.
+pub async fn get_feed(
+    CurrentUser(user): CurrentUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<FeedSubscription>, Response> {
+    let auth = state.auth.as_ref().expect("Auth state required");
+    require_read_access(auth, id, user.id)
+        .await
+        .map_err(IntoResponse::into_response)?;
+
+    get_feed_impl(&state, id, &user)
+        .await
+        .map_err(IntoResponse::into_response)
+}
+
+async fn get_feed_impl(
+    state: &AppState,
+    calendar_id: Uuid,
+    user: &User,
+) -> Result<Json<FeedSubscription>, (StatusCode, String)> {
+    let token = state
+        .feed_token_repo
+        .get_feed_token_for_user(calendar_id, user.id)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| error_response(StatusCode::NOT_FOUND, "Feed not found"))?;
+
+    Ok(Json(subscription(state, token)?))
+}
+
+/// Create or rotate the current user's feed for a calendar (POST /api/calendars/{id}/feed).
+///
+/// Requires read access to the calendar. Rotating replaces the previous token,
+/// so subscriptions using the old URL stop working.
+pub async fn create_feed(
+    CurrentUser(user): CurrentUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<(StatusCode, Json<FeedSubscription>), Response> {
+    let auth = state.auth.as_ref().expect("Auth state required");
+    require_read_access(auth, id, user.id)
+        .await
+        .map_err(IntoResponse::into_response)?;
+
+    create_feed_impl(&state, id, &user)
+        .await
+        .map_err(IntoResponse::into_response)
+}
+
+async fn create_feed_impl(
+    state: &AppState,
+    calendar_id: Uuid,
+    user: &User,
+) -> Result<(StatusCode, Json<FeedSubscription>), (StatusCode, String)> {
+    let token = FeedToken::new(calendar_id, user.id);
+    state
+        .feed_token_repo
+        .put_feed_token(&token)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    tracing::info!(calendar_id = %calendar_id, user_id = %user.id, "Issued feed token");
+
+    Ok((StatusCode::CREATED, Json(subscription(state, token)?)))
+}
+
+/// Revoke the current user's feed for a calendar (DELETE /api/calendars/{id}/feed).
+///
+/// Requires read access to the calendar. Returns 404 if the user has no token.
+pub async fn revoke_feed(
+    CurrentUser(user): CurrentUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, Response> {
+    let auth = state.auth.as_ref().expect("Auth state required");
+    require_read_access(auth, id, user.id)
+        .await
+        .map_err(IntoResponse::into_response)?;
+
+    revoke_feed_impl(&state, id, &user)
+        .await
+        .map_err(IntoResponse::into_response)
+}
+
+async fn revoke_feed_impl(
+    state: &AppState,
+    calendar_id: Uuid,
+    user: &User,
+) -> Result<StatusCode, (StatusCode, String)> {
+    match state
+        .feed_token_repo
+        .delete_feed_token(calendar_id, user.id)
+        .await
+    {
+        Ok(()) => {
+            tracing::info!(calendar_id = %calendar_id, user_id = %user.id, "Revoked feed token");
+            Ok(StatusCode::NO_CONTENT)
+        }
+        Err(RepositoryError::NotFound { .. }) => {
+            Err(error_response(StatusCode::NOT_FOUND, "Feed not found"))
+        }
+        Err(e) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            e.to_string(),
+        )),
+    }
+}
+
+// ============================================================================
+// Serve Feeds
+// ============================================================================
+
+/// Serve a calendar's subscription feed (GET /feeds/{token}.ics).
+///
+/// Takes no session: the token identifies its owner. Unknown tokens and
+/// tokens whose owner can no longer read the calendar both return 404, so the
+/// response never reveals whether a token once existed.
+pub async fn serve_feed(
+    State(state): State<AppState>,
+    Path(file): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let not_found = || (StatusCode::NOT_FOUND, "Feed not found".to_string());
+    let token = parse_feed_file(&file).ok_or_else(not_found)?;
+
+    let token = state
+        .feed_token_repo
+        .get_feed_token(token)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(not_found)?;
+
+    let auth = state.auth.as_ref().expect("Auth state required");
+    match require_read_access(auth, token.calendar_id, token.user_id).await {
+        Ok(_) => {}
+        Err(AuthzError::LookupFailed { error, .. }) => {
+            return Err(error_response(StatusCode::INTERNAL_SERVER_ERROR, error));
+        }
+        Err(_) => {
+            tracing::debug!(
+                calendar_id = %token.calendar_id,
+                user_id = %token.user_id,
+                "Feed owner can no longer read the calendar"
+            );
+            return Err(not_found());
+        }
+    }
+
+    let calendar = state
+        .calendar_repo
+        .get_calendar(token.calendar_id)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(not_found)?;
+
+    state.ensure_event_listener(calendar.id).await;
+    let ics = match state.cached_feed(calendar.id, calendar.version) {
+        Some(ics) => {
+            tracing::trace!(calendar_id = %calendar.id, "Serving cached feed");
+            ics
+        }
+        None => {
+            // An entry change while rendering bumps the generation, and the
+            // possibly stale render is then served but not cached
+            let generation = state.feed_generation(calendar.id);
+            let ics = render_calendar_ics(&state, &calendar).await?;
+            state.cache_feed(calendar.id, generation, calendar.version, ics.clone());
+            ics
+        }
+    };
+
+    Ok(([(header::CONTENT_TYPE, ICS_CONTENT_TYPE)], ics))
+}
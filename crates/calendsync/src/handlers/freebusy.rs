@@ -0,0 +1,154 @@
+//! Free/busy handlers.
+//!
+//! Reports when a user is busy across all of their calendars, for scheduling.
+//! Entry details are only included for calendars the requester can read.
+
+use std::collections::{HashMap, HashSet};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use calendsync_auth::CurrentUser;
+use calendsync_core::calendar::{
+    format_vfreebusy, free_busy, freebusy_search_range, validate_freebusy_range, User,
+};
+use calendsync_core::serde::deserialize_optional_timezone;
+use calendsync_core::storage::DateRange;
+use chrono::{NaiveDate, Utc};
+use chrono_tz::Tz;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::state::AppState;
+
+/// Error response with message (for query validation errors).
+fn error_response(status: StatusCode, message: impl Into<String>) -> (StatusCode, String) {
+    let msg = message.into();
+    tracing::warn!(status = %status, message = %msg, "API error");
+    (status, msg)
+}
+
+/// Output format for free/busy information.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FreeBusyFormat {
+    #[default]
+    Json,
+    /// An iCalendar object with a VFREEBUSY component
+    Ics,
+}
+
+/// Query parameters for free/busy information.
+#[derive(Debug, Deserialize)]
+pub struct FreeBusyQuery {
+    /// First day of the range (inclusive)
+    pub start: NaiveDate,
+    /// Last day of the range (inclusive)
+    pub end: NaiveDate,
+    /// Zone the range's days are in (defaults to the requester's, then UTC)
+    #[serde(default, deserialize_with = "deserialize_optional_timezone")]
+    pub timezone: Option<Tz>,
+    #[serde(default)]
+    pub format: FreeBusyFormat,
+}
+
+// ============================================================================
+// Free/Busy
+// ============================================================================
+
+/// Get when a user is busy (GET /api/users/{id}/freebusy).
+///
+/// Busy intervals cover all of the user's calendars. Titles are only listed
+/// for entries in calendars the requester is a member of.
+pub async fn get_freebusy(
+    CurrentUser(user): CurrentUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<FreeBusyQuery>,
+) -> Result<Response, Response> {
+    get_freebusy_impl(&state, &user, id, query)
+        .await
+        .map_err(IntoResponse::into_response)
+}
+
+async fn get_freebusy_impl(
+    state: &AppState,
+    requester: &User,
+    user_id: Uuid,
+    query: FreeBusyQuery,
+) -> Result<Response, (StatusCode, String)> {
+    let range = DateRange::new(query.start, query.end)
+        .map_err(|e| error_response(StatusCode::BAD_REQUEST, e.to_string()))?;
+    validate_freebusy_range(&range)
+        .map_err(|e| error_response(StatusCode::BAD_REQUEST, e.to_string()))?;
+    let tz = query.timezone.or(requester.timezone).unwrap_or(Tz::UTC);
+
+    let target = state
+        .user_repo
+        .get_user(user_id)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| error_response(StatusCode::NOT_FOUND, "User not found"))?;
+
+    let auth = state
+        .auth
+        .as_ref()
+        .expect("Auth state required when auth feature enabled");
+    let calendars = auth
+        .memberships
+        .get_calendars_for_user(target.id)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let readable: HashSet<Uuid> = auth
+        .memberships
+        .get_calendars_for_user(requester.id)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .into_iter()
+        .map(|(calendar, _)| calendar.id)
+        .collect();
+
+    let search_range = freebusy_search_range(&range);
+    let mut calendar_timezones = HashMap::new();
+    let mut entries = Vec::new();
+    for (calendar, _) in &calendars {
+        if let Some(calendar_tz) = calendar.timezone {
+            calendar_timezones.insert(calendar.id, calendar_tz);
+        }
+        entries.extend(
+            state
+                .entry_repo
+                .get_entries_by_calendar(calendar.id, search_range)
+                .await
+                .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+        );
+    }
+
+    let result = free_busy(
+        target.id,
+        &entries,
+        &calendar_timezones,
+        &readable,
+        &range,
+        tz,
+    );
+
+    tracing::debug!(
+        requester_id = %requester.id,
+        user_id = %target.id,
+        busy_count = result.busy.len(),
+        "Computed free/busy"
+    );
+
+    Ok(match query.format {
+        FreeBusyFormat::Json => Json(result).into_response(),
+        FreeBusyFormat::Ics => (
+            [(header::CONTENT_TYPE, "text/calendar; charset=utf-8")],
+            format_vfreebusy(&result, Some(&target.email), Utc::now()),
+        )
+            .into_response(),
+    })
+}
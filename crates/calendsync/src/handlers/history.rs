@@ -0,0 +1,187 @@
+//! Entry history handlers.
+//!
+//! Lists the revisions recorded for an entry and reverts a live entry to one
+//! of them. A revert is saved like any other edit, so it shows up as a new
+//! revision and is published as an `entry_updated` event. Reverting a
+//! recurring series also puts back its occurrence overrides.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::Utc;
+use uuid::Uuid;
+
+use calendsync_core::calendar::{revert_to, CalendarEntry, EntryRevision};
+
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+use axum::response::{IntoResponse, Response};
+
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+use calendsync_auth::CurrentUser;
+
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+use super::authz::{require_read_access, require_write_access};
+
+use super::entries::entry_to_server_entry;
+use super::preconditions::conditional_write_error;
+use crate::state::AppState;
+
+/// Error response with message.
+fn error_response(status: StatusCode, message: impl Into<String>) -> (StatusCode, String) {
+    let msg = message.into();
+    tracing::warn!(status = %status, message = %msg, "API error");
+    (status, msg)
+}
+
+// ============================================================================
+// Entry History
+// ============================================================================
+
+/// List an entry's revisions, oldest first (GET /api/entries/{id}/history) - with auth.
+///
+/// History stays readable while the entry is in the trash; access is checked
+/// against the calendar the entry was last in.
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+pub async fn get_entry_history(
+    CurrentUser(user): CurrentUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<EntryRevision>>, Response> {
+    let history = get_entry_history_impl(&state, id)
+        .await
+        .map_err(IntoResponse::into_response)?;
+
+    let calendar_id = history[history.len() - 1].snapshot.calendar_id;
+    let auth = state.auth.as_ref().expect("Auth state required");
+    require_read_access(auth, calendar_id, user.id)
+        .await
+        .map_err(IntoResponse::into_response)?;
+
+    Ok(Json(history))
+}
+
+/// List an entry's revisions, oldest first (GET /api/entries/{id}/history) - no auth.
+#[cfg(not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")))]
+pub async fn get_entry_history(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<EntryRevision>>, (StatusCode, String)> {
+    get_entry_history_impl(&state, id).await.map(Json)
+}
+
+/// Returns the entry's history, or 404 when none was recorded.
+async fn get_entry_history_impl(
+    state: &AppState,
+    id: Uuid,
+) -> Result<Vec<EntryRevision>, (StatusCode, String)> {
+    let history = state
+        .entry_repo
+        .get_entry_revisions(id)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if history.is_empty() {
+        return Err(error_response(
+            StatusCode::NOT_FOUND,
+            format!("No history for entry {id}"),
+        ));
+    }
+
+    Ok(history)
+}
+
+// ============================================================================
+// Revert Entry
+// ============================================================================
+
+/// Revert an entry to an earlier revision
+/// (POST /api/entries/{id}/history/{revision}/revert) - with auth.
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+pub async fn revert_entry(
+    CurrentUser(user): CurrentUser,
+    State(state): State<AppState>,
+    Path((id, revision)): Path<(Uuid, u32)>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let current = live_entry(&state, id)
+        .await
+        .map_err(IntoResponse::into_response)?;
+
+    let auth = state.auth.as_ref().expect("Auth state required");
+    require_write_access(auth, current.calendar_id, user.id)
+        .await
+        .map_err(IntoResponse::into_response)?;
+
+    revert_entry_impl(&state, current, revision, Some(user.id))
+        .await
+        .map_err(IntoResponse::into_response)
+}
+
+/// Revert an entry to an earlier revision
+/// (POST /api/entries/{id}/history/{revision}/revert) - no auth.
+#[cfg(not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")))]
+pub async fn revert_entry(
+    State(state): State<AppState>,
+    Path((id, revision)): Path<(Uuid, u32)>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let current = live_entry(&state, id).await?;
+    revert_entry_impl(&state, current, revision, None).await
+}
+
+/// Fetches an entry that is not in the trash.
+async fn live_entry(state: &AppState, id: Uuid) -> Result<CalendarEntry, (StatusCode, String)> {
+    state
+        .entry_repo
+        .get_entry(id)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| error_response(StatusCode::NOT_FOUND, format!("Entry {id} not found")))
+}
+
+async fn revert_entry_impl(
+    state: &AppState,
+    current: CalendarEntry,
+    revision: u32,
+    acting_user: Option<Uuid>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let id = current.id;
+    let target = state
+        .entry_repo
+        .get_entry_revisions(id)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .into_iter()
+        .find(|r| r.revision == revision)
+        .ok_or_else(|| {
+            error_response(
+                StatusCode::NOT_FOUND,
+                format!("Entry {id} has no revision {revision}"),
+            )
+        })?;
+
+    let mut reverted = revert_to(&current, &target, Utc::now());
+
+    // Update via repository (which records the revision, invalidates caches and publishes).
+    // A series' overrides are put back too when the revision recorded them.
+    match &target.overrides {
+        Some(overrides) => {
+            state
+                .entry_repo
+                .update_series_if_version_as(&reverted, overrides, current.version, acting_user)
+                .await
+        }
+        None => {
+            state
+                .entry_repo
+                .update_entry_if_version_as(&reverted, current.version, acting_user)
+                .await
+        }
+    }
+    .map_err(conditional_write_error)?;
+    reverted.version = current.version + 1;
+
+    tracing::info!(entry_id = %id, revision, "Reverted entry");
+
+    Ok(Json(entry_to_server_entry(&reverted)))
+}
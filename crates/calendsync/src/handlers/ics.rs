@@ -0,0 +1,379 @@
+//! iCalendar handlers.
+//!
+//! Exports a calendar's entries as an .ics file other calendar tools can
+//! import, and imports .ics files from them.
+
+use std::collections::{HashMap, HashSet};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use calendsync_core::calendar::{
+    changed_fields, format_ics, ics_filename, import_entry_id, parse_ics, reimport_entry, Calendar,
+    CalendarEntry, EntryWrite, ImportAction, ImportReport, ImportedItem, OccurrenceOverride,
+    SkippedComponent, ICS_CONTENT_TYPE, MAX_BATCH_SIZE,
+};
+
+use super::entries::link_attendee_users;
+use super::preconditions::conditional_write_error;
+use crate::state::AppState;
+
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+use axum::response::Response;
+
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+use calendsync_auth::CurrentUser;
+
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+use super::authz::{require_read_access, require_write_access};
+
+/// Error response with message.
+fn error_response(status: StatusCode, message: impl Into<String>) -> (StatusCode, String) {
+    let msg = message.into();
+    tracing::warn!(status = %status, message = %msg, "API error");
+    (status, msg)
+}
+
+// ============================================================================
+// Export
+// ============================================================================
+
+/// Export a calendar as iCalendar (GET /api/calendars/{id}/export.ics) - with auth.
+///
+/// Requires read access to the calendar.
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+pub async fn export_calendar(
+    CurrentUser(user): CurrentUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, Response> {
+    let auth = state.auth.as_ref().expect("Auth state required");
+    require_read_access(auth, id, user.id)
+        .await
+        .map_err(IntoResponse::into_response)?;
+
+    export_calendar_impl(&state, id)
+        .await
+        .map_err(IntoResponse::into_response)
+}
+
+/// Export a calendar as iCalendar (GET /api/calendars/{id}/export.ics) - no auth.
+#[cfg(not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")))]
+pub async fn export_calendar(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    export_calendar_impl(&state, id).await
+}
+
+async fn export_calendar_impl(
+    state: &AppState,
+    id: Uuid,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    tracing::debug!(calendar_id = %id, "Received export calendar request");
+
+    let calendar = state
+        .calendar_repo
+        .get_calendar(id)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| error_response(StatusCode::NOT_FOUND, "Calendar not found"))?;
+    let ics = render_calendar_ics(state, &calendar).await?;
+
+    let disposition = format!("attachment; filename=\"{}\"", ics_filename(&calendar));
+    Ok((
+        [
+            (header::CONTENT_TYPE, ICS_CONTENT_TYPE.to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        ics,
+    ))
+}
+
+/// Renders a calendar's stored entries and their occurrence overrides as an
+/// iCalendar object.
+pub(crate) async fn render_calendar_ics(
+    state: &AppState,
+    calendar: &Calendar,
+) -> Result<String, (StatusCode, String)> {
+    let entries = state
+        .entry_repo
+        .get_stored_entries_by_calendar(calendar.id)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let mut overrides = Vec::new();
+    for series in entries.iter().filter(|e| e.is_recurring()) {
+        overrides.extend(
+            state
+                .entry_repo
+                .get_occurrence_overrides(series.id)
+                .await
+                .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+        );
+    }
+
+    tracing::debug!(
+        calendar_id = %calendar.id,
+        entry_count = entries.len(),
+        override_count = overrides.len(),
+        "Rendering calendar as iCalendar"
+    );
+
+    Ok(format_ics(calendar, &entries, &overrides, Utc::now()))
+}
+
+// ============================================================================
+// Import
+// ============================================================================
+
+/// Query parameters for importing an iCalendar file.
+#[derive(Debug, Default, Deserialize)]
+pub struct ImportQuery {
+    /// Report what the import would do without writing anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Import an iCalendar file into a calendar (POST /api/calendars/{id}/import) - with auth.
+///
+/// Requires write access to the calendar.
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+pub async fn import_calendar(
+    CurrentUser(user): CurrentUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<ImportQuery>,
+    body: String,
+) -> Result<Json<ImportReport>, Response> {
+    let auth = state.auth.as_ref().expect("Auth state required");
+    require_write_access(auth, id, user.id)
+        .await
+        .map_err(IntoResponse::into_response)?;
+
+    import_calendar_impl(&state, id, &body, query.dry_run, Some(user.id))
+        .await
+        .map_err(IntoResponse::into_response)
+}
+
+/// Import an iCalendar file into a calendar (POST /api/calendars/{id}/import) - no auth.
+#[cfg(not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")))]
+pub async fn import_calendar(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<ImportQuery>,
+    body: String,
+) -> Result<Json<ImportReport>, (StatusCode, String)> {
+    import_calendar_impl(&state, id, &body, query.dry_run, None).await
+}
+
+/// Imports the VEVENTs and VTODOs of `body` into calendar `id`.
+///
+/// Components whose UID was imported before update the entry they created,
+/// or are left alone when nothing changed. Writes go through the batch API,
+/// `MAX_BATCH_SIZE` entries at a time, and occurrence overrides are written
+/// once their series exist.
+async fn import_calendar_impl(
+    state: &AppState,
+    id: Uuid,
+    body: &str,
+    dry_run: bool,
+    acting_user: Option<Uuid>,
+) -> Result<Json<ImportReport>, (StatusCode, String)> {
+    tracing::debug!(calendar_id = %id, dry_run, "Received import calendar request");
+
+    let calendar = state
+        .calendar_repo
+        .get_calendar(id)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| error_response(StatusCode::NOT_FOUND, "Calendar not found"))?;
+    let import = parse_ics(body, calendar.id)
+        .map_err(|e| error_response(StatusCode::BAD_REQUEST, e.to_string()))?;
+
+    let trashed: HashSet<Uuid> = state
+        .entry_repo
+        .get_trashed_entries(&[calendar.id])
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .into_iter()
+        .map(|trashed| trashed.entry.id)
+        .collect();
+
+    let mut report = ImportReport {
+        dry_run,
+        skipped: import.skipped,
+        unsupported: import.unsupported,
+        ..ImportReport::default()
+    };
+    let skip = |report: &mut ImportReport, uid: &str, reason: &str| {
+        report.skipped.push(SkippedComponent {
+            uid: Some(uid.to_string()),
+            reason: reason.to_string(),
+        });
+    };
+
+    let mut writes = Vec::new();
+    let mut overrides = Vec::new();
+    let mut series_ids: HashMap<String, Uuid> = HashMap::new();
+    let (series, occurrences): (Vec<_>, Vec<_>) = import
+        .entries
+        .into_iter()
+        .partition(|imported| imported.recurrence_id.is_none());
+
+    for imported in series {
+        if series_ids.contains_key(&imported.uid) {
+            skip(&mut report, &imported.uid, "duplicate UID");
+            continue;
+        }
+        let (entry_id, stored) = find_imported_entry(state, &calendar, &imported.uid).await?;
+        if trashed.contains(&entry_id) {
+            skip(&mut report, &imported.uid, "entry is in the trash");
+            continue;
+        }
+        if stored
+            .as_ref()
+            .is_some_and(|s| s.calendar_id != calendar.id)
+        {
+            skip(
+                &mut report,
+                &imported.uid,
+                "entry was moved to another calendar",
+            );
+            continue;
+        }
+        series_ids.insert(imported.uid.clone(), entry_id);
+
+        let mut entry = imported.to_entry(entry_id);
+        link_attendee_users(state, &mut entry.attendees).await?;
+        overrides.extend(
+            imported
+                .exdates
+                .iter()
+                .map(|date| OccurrenceOverride::cancelled(entry_id, *date)),
+        );
+
+        let action = match stored {
+            None => ImportAction::Create,
+            Some(stored) => {
+                entry = reimport_entry(entry, &stored);
+                if changed_fields(&stored, &entry).is_empty() {
+                    ImportAction::Unchanged
+                } else {
+                    ImportAction::Update
+                }
+            }
+        };
+        report.push(ImportedItem {
+            uid: imported.uid,
+            id: entry_id,
+            title: entry.title.clone(),
+            start_date: entry.start_date,
+            action,
+        });
+        match action {
+            ImportAction::Create => writes.push(EntryWrite::Create(entry)),
+            ImportAction::Update => writes.push(EntryWrite::Update {
+                expected_version: entry.version,
+                entry,
+            }),
+            ImportAction::Unchanged => {}
+        }
+    }
+
+    for imported in occurrences {
+        let Some(&series_id) = series_ids.get(&imported.uid) else {
+            skip(
+                &mut report,
+                &imported.uid,
+                "no recurring entry with this UID",
+            );
+            continue;
+        };
+        let original_date = imported
+            .recurrence_id
+            .expect("partitioned on recurrence_id");
+        let mut replacement = imported.to_entry(series_id);
+        link_attendee_users(state, &mut replacement.attendees).await?;
+        overrides.push(OccurrenceOverride::replaced(
+            series_id,
+            original_date,
+            replacement,
+        ));
+    }
+    report.overrides = overrides.len();
+
+    if !dry_run {
+        apply_import(state, &writes, &overrides, acting_user).await?;
+        tracing::info!(
+            calendar_id = %calendar.id,
+            created = report.created,
+            updated = report.updated,
+            unchanged = report.unchanged,
+            skipped = report.skipped.len(),
+            "Imported iCalendar file"
+        );
+    }
+
+    Ok(Json(report))
+}
+
+/// Finds the entry a UID was imported as, returning its ID either way.
+///
+/// UIDs of entries exported from this calendar are their IDs; anything else
+/// maps to [`import_entry_id`].
+async fn find_imported_entry(
+    state: &AppState,
+    calendar: &Calendar,
+    uid: &str,
+) -> Result<(Uuid, Option<CalendarEntry>), (StatusCode, String)> {
+    let get_entry = |id| async move {
+        state
+            .entry_repo
+            .get_entry(id)
+            .await
+            .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+    };
+
+    if let Ok(exported_id) = uid.parse::<Uuid>() {
+        if let Some(entry) = get_entry(exported_id).await? {
+            if entry.calendar_id == calendar.id {
+                return Ok((exported_id, Some(entry)));
+            }
+        }
+    }
+    let id = import_entry_id(calendar.id, uid);
+    Ok((id, get_entry(id).await?))
+}
+
+/// Writes an import's entries, then the overrides of its recurring ones.
+async fn apply_import(
+    state: &AppState,
+    writes: &[EntryWrite],
+    overrides: &[OccurrenceOverride],
+    acting_user: Option<Uuid>,
+) -> Result<(), (StatusCode, String)> {
+    // Repository calls handle cache invalidation and event publishing
+    for chunk in writes.chunks(MAX_BATCH_SIZE) {
+        state
+            .entry_repo
+            .apply_entry_batch_as(chunk, acting_user)
+            .await
+            .map_err(conditional_write_error)?;
+    }
+    for occurrence in overrides {
+        state
+            .entry_repo
+            .upsert_occurrence_override(occurrence)
+            .await
+            .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    }
+    Ok(())
+}
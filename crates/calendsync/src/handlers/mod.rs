@@ -1,5 +1,9 @@
 #[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+pub mod admin;
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
 pub mod authz;
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+pub mod caldav;
 pub mod calendar_react;
 pub mod calendars;
 #[cfg(debug_assertions)]
@@ -8,13 +12,26 @@ pub mod entries;
 pub mod error;
 pub mod events;
 #[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+pub mod feeds;
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
 pub mod flash;
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+pub mod freebusy;
 pub mod health;
+pub mod history;
+pub mod ics;
 #[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
 pub mod login;
+pub mod preconditions;
 #[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
 pub mod root;
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+pub mod search;
 pub mod settings;
 pub mod static_files;
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+pub mod trash;
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+pub mod users;
 
 pub use error::AppError;
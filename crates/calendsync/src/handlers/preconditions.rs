@@ -0,0 +1,42 @@
+//! Conditional request helpers.
+//!
+//! Calendars and entries are read with their version as the `ETag`. Updates
+//! and deletes that send it back in `If-Match` fail with 412 once someone else
+//! has changed the resource.
+
+use axum::http::{header, HeaderMap, HeaderName, StatusCode};
+use calendsync_core::calendar::{format_etag, if_match_satisfied};
+use calendsync_core::storage::{repository_error_to_status_code, RepositoryError};
+
+/// `ETag` header for a resource at `version`.
+pub fn etag_header(version: u64) -> [(HeaderName, String); 1] {
+    [(header::ETAG, format_etag(version))]
+}
+
+/// Checks the request's `If-Match` header, if any, against the stored version.
+pub fn check_if_match(headers: &HeaderMap, version: u64) -> Result<(), (StatusCode, String)> {
+    let Some(value) = headers.get(header::IF_MATCH) else {
+        return Ok(());
+    };
+
+    let satisfied = value
+        .to_str()
+        .is_ok_and(|value| if_match_satisfied(value, version));
+    if satisfied {
+        return Ok(());
+    }
+
+    let msg = format!("Resource has changed; current version is {version}");
+    tracing::warn!(status = %StatusCode::PRECONDITION_FAILED, message = %msg, "API error");
+    Err((StatusCode::PRECONDITION_FAILED, msg))
+}
+
+/// Maps a failed conditional write, reporting a lost race as 412 and a
+/// resource deleted since it was read as 404.
+pub fn conditional_write_error(err: RepositoryError) -> (StatusCode, String) {
+    let status = StatusCode::from_u16(repository_error_to_status_code(&err))
+        .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    let msg = err.to_string();
+    tracing::warn!(status = %status, message = %msg, "API error");
+    (status, msg)
+}
@@ -0,0 +1,105 @@
+//! Search handlers.
+//!
+//! Searches entry titles, descriptions and locations across every calendar
+//! the current user is a member of.
+
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use calendsync_auth::CurrentUser;
+use calendsync_core::calendar::{SearchHit, SearchQuery, DEFAULT_SEARCH_LIMIT, MAX_SEARCH_LIMIT};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::state::AppState;
+
+/// Error response with message (for query validation errors).
+fn error_response(status: StatusCode, message: impl Into<String>) -> (StatusCode, String) {
+    let msg = message.into();
+    tracing::warn!(status = %status, message = %msg, "API error");
+    (status, msg)
+}
+
+/// Query parameters for searching entries.
+#[derive(Debug, Deserialize)]
+pub struct SearchEntriesQuery {
+    /// Words to look for; each must start a word of the entry's text
+    pub q: String,
+    /// Maximum number of results (default 20, at most 100)
+    pub limit: Option<usize>,
+}
+
+// ============================================================================
+// Search Entries
+// ============================================================================
+
+/// Search entries in every calendar the current user can read (GET /api/search?q=).
+///
+/// Results are ranked best first and include the calendar, the entry and a
+/// highlighted snippet for each matching field.
+pub async fn search_entries(
+    CurrentUser(user): CurrentUser,
+    State(state): State<AppState>,
+    Query(query): Query<SearchEntriesQuery>,
+) -> Result<Json<Vec<SearchHit>>, Response> {
+    search_entries_impl(&state, user.id, query)
+        .await
+        .map_err(IntoResponse::into_response)
+}
+
+async fn search_entries_impl(
+    state: &AppState,
+    user_id: Uuid,
+    params: SearchEntriesQuery,
+) -> Result<Json<Vec<SearchHit>>, (StatusCode, String)> {
+    let query = params
+        .q
+        .parse::<SearchQuery>()
+        .map_err(|e| error_response(StatusCode::BAD_REQUEST, e.to_string()))?;
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        .min(MAX_SEARCH_LIMIT);
+
+    let auth = state
+        .auth
+        .as_ref()
+        .expect("Auth state required when auth feature enabled");
+    let calendars: HashMap<Uuid, _> = auth
+        .memberships
+        .get_calendars_for_user(user_id)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .into_iter()
+        .map(|(calendar, _)| (calendar.id, calendar))
+        .collect();
+    let calendar_ids: Vec<Uuid> = calendars.keys().copied().collect();
+
+    let entries = state
+        .entry_repo
+        .search_entries(&calendar_ids, &query, limit)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let hits: Vec<SearchHit> = entries
+        .into_iter()
+        .filter_map(|entry| {
+            let calendar = calendars.get(&entry.calendar_id)?.clone();
+            Some(SearchHit::new(&query, calendar, entry))
+        })
+        .collect();
+
+    tracing::debug!(
+        user_id = %user_id,
+        query = %query,
+        hit_count = hits.len(),
+        "Searched entries"
+    );
+
+    Ok(Json(hits))
+}
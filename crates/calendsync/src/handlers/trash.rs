@@ -0,0 +1,196 @@
+//! Trash handlers.
+//!
+//! Lists the calendars and entries the current user deleted recently and
+//! restores them. Entries can be restored by anyone who can write to their
+//! calendar; calendars only by their owner.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use calendsync_auth::CurrentUser;
+use calendsync_core::calendar::{Calendar, CalendarEntry, Trash};
+use calendsync_core::storage::RepositoryError;
+use uuid::Uuid;
+
+use crate::handlers::authz::require_admin_access;
+use crate::state::AppState;
+
+/// Error response with message (for lookup and restore errors).
+fn error_response(status: StatusCode, message: impl Into<String>) -> (StatusCode, String) {
+    let msg = message.into();
+    tracing::warn!(status = %status, message = %msg, "API error");
+    (status, msg)
+}
+
+/// Maps a restore error, reporting items that are not in the trash as 404.
+fn restore_error(err: RepositoryError, not_found: &str) -> (StatusCode, String) {
+    match err {
+        RepositoryError::NotFound { .. } => error_response(StatusCode::NOT_FOUND, not_found),
+        err => error_response(StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+    }
+}
+
+// ============================================================================
+// List Trash
+// ============================================================================
+
+/// List the current user's trash (GET /api/trash).
+///
+/// Includes calendars the user owns and entries deleted from calendars they
+/// can write to, most recently deleted first.
+pub async fn list_trash(
+    CurrentUser(user): CurrentUser,
+    State(state): State<AppState>,
+) -> Result<Json<Trash>, Response> {
+    list_trash_impl(&state, user.id)
+        .await
+        .map_err(IntoResponse::into_response)
+}
+
+async fn list_trash_impl(
+    state: &AppState,
+    user_id: Uuid,
+) -> Result<Json<Trash>, (StatusCode, String)> {
+    let auth = state
+        .auth
+        .as_ref()
+        .expect("Auth state required when auth feature enabled");
+
+    let calendars = auth
+        .memberships
+        .get_trashed_calendars_for_user(user_id)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .into_iter()
+        .filter(|(_, role)| role.can_administer())
+        .map(|(trashed, _)| trashed)
+        .collect();
+
+    let writable = writable_calendar_ids(state, user_id).await?;
+    let entries = state
+        .entry_repo
+        .get_trashed_entries(&writable)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let trash = Trash::new(calendars, entries, state.trash_retention_days);
+
+    tracing::debug!(
+        user_id = %user_id,
+        calendar_count = trash.calendars.len(),
+        entry_count = trash.entries.len(),
+        "Listed trash"
+    );
+
+    Ok(Json(trash))
+}
+
+/// Returns the live calendars the user can write entries to.
+///
+/// Read-only calendars are left out: a refresh trashes the entries their feed
+/// drops, and only the next refresh may bring them back.
+async fn writable_calendar_ids(
+    state: &AppState,
+    user_id: Uuid,
+) -> Result<Vec<Uuid>, (StatusCode, String)> {
+    let auth = state
+        .auth
+        .as_ref()
+        .expect("Auth state required when auth feature enabled");
+    Ok(auth
+        .memberships
+        .get_calendars_for_user(user_id)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .into_iter()
+        .filter(|(calendar, role)| role.can_write() && !calendar.is_read_only())
+        .map(|(calendar, _)| calendar.id)
+        .collect())
+}
+
+// ============================================================================
+// Restore Entry
+// ============================================================================
+
+/// Restore a deleted entry (POST /api/trash/entries/{id}/restore).
+///
+/// Open clients are told through an `entry_restored` event.
+pub async fn restore_entry(
+    CurrentUser(user): CurrentUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<CalendarEntry>, Response> {
+    restore_entry_impl(&state, user.id, id)
+        .await
+        .map_err(IntoResponse::into_response)
+}
+
+async fn restore_entry_impl(
+    state: &AppState,
+    user_id: Uuid,
+    id: Uuid,
+) -> Result<Json<CalendarEntry>, (StatusCode, String)> {
+    // Only entries the user could have deleted can be restored
+    let writable = writable_calendar_ids(state, user_id).await?;
+    let in_trash = state
+        .entry_repo
+        .get_trashed_entries(&writable)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .iter()
+        .any(|trashed| trashed.entry.id == id);
+    if !in_trash {
+        return Err(error_response(StatusCode::NOT_FOUND, "Entry not in trash"));
+    }
+
+    let entry = state
+        .entry_repo
+        .restore_entry_as(id, Some(user_id))
+        .await
+        .map_err(|e| restore_error(e, "Entry not in trash"))?;
+
+    tracing::info!(entry_id = %id, calendar_id = %entry.calendar_id, "Restored entry");
+
+    Ok(Json(entry))
+}
+
+// ============================================================================
+// Restore Calendar
+// ============================================================================
+
+/// Restore a deleted calendar and its entries (POST /api/trash/calendars/{id}/restore).
+pub async fn restore_calendar(
+    CurrentUser(user): CurrentUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Calendar>, Response> {
+    let auth = state
+        .auth
+        .as_ref()
+        .expect("Auth state required when auth feature enabled");
+    require_admin_access(auth, id, user.id)
+        .await
+        .map_err(IntoResponse::into_response)?;
+
+    restore_calendar_impl(&state, id)
+        .await
+        .map_err(IntoResponse::into_response)
+}
+
+async fn restore_calendar_impl(
+    state: &AppState,
+    id: Uuid,
+) -> Result<Json<Calendar>, (StatusCode, String)> {
+    let calendar = state
+        .calendar_repo
+        .restore_calendar(id)
+        .await
+        .map_err(|e| restore_error(e, "Calendar not in trash"))?;
+
+    tracing::info!(calendar_id = %id, "Restored calendar");
+
+    Ok(Json(calendar))
+}
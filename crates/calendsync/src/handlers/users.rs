@@ -0,0 +1,65 @@
+//! Current user handlers.
+//!
+//! Handles per-user preferences that are not tied to a calendar.
+
+use axum::{
+    extract::{rejection::FormRejection, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Form,
+};
+use calendsync_auth::CurrentUser;
+use calendsync_core::calendar::User;
+
+use crate::{models::UpdateUserTimezone, state::AppState};
+
+/// Error response with message (for form validation errors).
+fn error_response(status: StatusCode, message: impl Into<String>) -> (StatusCode, String) {
+    let msg = message.into();
+    tracing::warn!(status = %status, message = %msg, "API error");
+    (status, msg)
+}
+
+// ============================================================================
+// Update Display Time Zone
+// ============================================================================
+
+/// Set the current user's display time zone (PUT /api/users/me/timezone).
+///
+/// An empty `timezone` clears it, so entries are shown as stored.
+pub async fn update_my_timezone(
+    CurrentUser(user): CurrentUser,
+    State(state): State<AppState>,
+    form_result: Result<Form<UpdateUserTimezone>, FormRejection>,
+) -> Result<StatusCode, Response> {
+    let Form(payload) = form_result.map_err(|e| {
+        error_response(
+            StatusCode::BAD_REQUEST,
+            format!("Failed to parse form: {e}"),
+        )
+        .into_response()
+    })?;
+
+    update_my_timezone_impl(&state, user, payload)
+        .await
+        .map_err(IntoResponse::into_response)
+}
+
+async fn update_my_timezone_impl(
+    state: &AppState,
+    mut user: User,
+    payload: UpdateUserTimezone,
+) -> Result<StatusCode, (StatusCode, String)> {
+    user.timezone = payload.timezone;
+    user.touch();
+
+    state
+        .user_repo
+        .update_user(&user)
+        .await
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    tracing::info!(user_id = %user.id, timezone = ?user.timezone, "Updated display time zone");
+
+    Ok(StatusCode::OK)
+}
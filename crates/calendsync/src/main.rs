@@ -1,4 +1,5 @@
 mod app;
+mod backup;
 mod cache;
 mod config;
 mod context;
@@ -6,14 +7,17 @@ mod context;
 mod dev;
 mod handlers;
 mod models;
+mod reminders;
 mod state;
 mod storage;
+mod subscriptions;
+mod trash;
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 use calendsync_ssr::{SsrPool, SsrPoolConfig};
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use listenfd::ListenFd;
 use tokio::{net::TcpListener, signal};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
@@ -41,6 +45,45 @@ struct Cli {
     /// Port to listen on
     #[arg(long, short, default_value = "3000", env = "PORT")]
     port: u16,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Maintenance commands, run against the configured storage instead of serving.
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Write a backup of all users, calendars, memberships, settings and entries to FILE
+    Backup {
+        /// Path of the JSON Lines file to write
+        file: PathBuf,
+    },
+    /// Count the live entries of every calendar without a backup and write the total to FILE, as JSON
+    Count {
+        /// Path of the JSON file to write
+        file: PathBuf,
+    },
+    /// Restore a backup from FILE, keeping its IDs, then verify the result
+    Restore {
+        /// Path of a JSON Lines file written by `backup`
+        file: PathBuf,
+
+        /// Number of records written at once
+        #[arg(long, default_value_t = 25)]
+        batch_size: usize,
+
+        /// Milliseconds to wait between batches, to stay under the storage's write capacity
+        #[arg(long, default_value_t = 0)]
+        pause_ms: u64,
+
+        /// File tracking progress; an interrupted restore given the same file resumes from it
+        #[arg(long)]
+        checkpoint: Option<PathBuf>,
+
+        /// File to write the restore report to, as JSON
+        #[arg(long)]
+        report: Option<PathBuf>,
+    },
 }
 
 #[tokio::main]
@@ -62,6 +105,10 @@ async fn main() -> Result<()> {
     // Create application state WITHOUT SSR pool (starts as None, initialized in background)
     let state = AppState::new(&config).await?;
 
+    if let Some(command) = cli.command {
+        return run_command(&state, command).await;
+    }
+
     // Initialize dev annotation store when in dev mode
     #[cfg(feature = "dev-annotations")]
     let state = {
@@ -113,6 +160,15 @@ async fn main() -> Result<()> {
         }
     };
 
+    // Deliver due reminders in the background until shutdown
+    reminders::spawn_reminder_scheduler(state.clone(), &config);
+
+    // Purge trash past its retention period in the background until shutdown
+    trash::spawn_trash_purger(state.clone(), &config);
+
+    // Refresh calendars subscribed to iCalendar feeds in the background until shutdown
+    subscriptions::spawn_subscription_refresher(state.clone(), &config);
+
     // Build the application router
     let app = create_app(state.clone(), &config);
 
@@ -156,6 +212,92 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Runs a maintenance command to completion.
+async fn run_command(state: &AppState, command: Command) -> Result<()> {
+    let store = backup::BackupStore::from_state(state);
+    match command {
+        Command::Backup { file } => {
+            let dump = backup::dump(&store).await?;
+            std::fs::write(&file, dump.to_jsonl()?)?;
+            tracing::info!(path = %file.display(), counts = ?dump.counts(), "Backup written");
+        }
+        Command::Count { file } => {
+            let entries = backup::count_entries(&store).await?;
+            write_json(&file, &entries)?;
+            tracing::info!(path = %file.display(), entries, "Entries counted");
+        }
+        Command::Restore {
+            file,
+            batch_size,
+            pause_ms,
+            checkpoint,
+            report: report_file,
+        } => {
+            let contents = std::fs::read_to_string(&file)?;
+            let dump = calendsync_core::backup::Backup::from_jsonl(&contents)?;
+            let options = backup::RestoreOptions {
+                batch_size,
+                pause: std::time::Duration::from_millis(pause_ms),
+            };
+            let resume_from = match &checkpoint {
+                Some(path) => read_checkpoint(path, &dump)?,
+                None => None,
+            };
+            let report = backup::resume(&store, &dump, &options, resume_from, |progress| {
+                if let Some(path) = &checkpoint {
+                    if let Err(e) = write_json(path, progress) {
+                        tracing::warn!(error = %e, path = %path.display(), "Failed to save checkpoint");
+                    }
+                }
+            })
+            .await?;
+            if let Some(path) = &report_file {
+                write_json(path, &report)?;
+            }
+            tracing::info!(
+                path = %file.display(),
+                restored = ?report.restored,
+                skipped = ?report.skipped,
+                conflicts = ?report.conflicts,
+                "Backup restored"
+            );
+            for mismatch in &report.mismatches {
+                tracing::error!(%mismatch, "Record did not verify");
+            }
+            if !report.is_verified() {
+                anyhow::bail!("{} records did not verify", report.mismatches.len());
+            }
+            tracing::info!("All records verified");
+        }
+    }
+    Ok(())
+}
+
+/// Reads a restore checkpoint, if there is one for `dump`.
+fn read_checkpoint(
+    path: &Path,
+    dump: &calendsync_core::backup::Backup,
+) -> Result<Option<backup::Checkpoint>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let checkpoint: backup::Checkpoint = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    if !checkpoint.is_for(dump) {
+        tracing::warn!(path = %path.display(), "Checkpoint is for another backup, starting over");
+        return Ok(None);
+    }
+    tracing::info!(done = checkpoint.done, "Resuming restore");
+    Ok(Some(checkpoint))
+}
+
+/// Writes `value` to `path` as JSON, replacing the file in one step.
+fn write_json(path: &Path, value: &impl serde::Serialize) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, serde_json::to_vec_pretty(value)?)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
 /// Initialize the SSR worker pool.
 ///
 /// Reads the server bundle path from the manifest and creates a pool
@@ -1,4 +1,5 @@
-use calendsync_core::serde::deserialize_optional_string;
+use calendsync_core::serde::{deserialize_optional_string, deserialize_optional_timezone};
+use chrono_tz::Tz;
 use serde::Deserialize;
 
 /// Request payload for creating a new calendar.
@@ -8,6 +9,13 @@ pub struct CreateCalendar {
     pub color: String,
     #[serde(default, deserialize_with = "deserialize_optional_string")]
     pub description: Option<String>,
+    /// IANA time zone for the calendar's timed entries (e.g. `America/Montevideo`).
+    #[serde(default, deserialize_with = "deserialize_optional_timezone")]
+    pub timezone: Option<Tz>,
+    /// iCalendar feed (`http`, `https` or `webcal`) the calendar mirrors,
+    /// making it read-only.
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
+    pub subscription_url: Option<String>,
 }
 
 /// Request payload for updating a calendar.
@@ -19,4 +27,23 @@ pub struct UpdateCalendar {
     pub color: Option<String>,
     #[serde(default, deserialize_with = "deserialize_optional_string")]
     pub description: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_optional_timezone")]
+    pub timezone: Option<Tz>,
+}
+
+/// Request payload for duplicating a calendar.
+#[derive(Debug, Deserialize)]
+pub struct DuplicateCalendar {
+    /// Name of the copy; defaults to "<name> (copy)".
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
+    pub name: Option<String>,
+    /// When true, the calendar's entries are copied too.
+    #[serde(default)]
+    pub include_entries: Option<bool>,
+    /// Days to move copied entries by (negative moves them earlier).
+    #[serde(default)]
+    pub shift_days: Option<i64>,
+    /// When true, the calendar's members are copied with their roles.
+    #[serde(default)]
+    pub include_members: Option<bool>,
 }
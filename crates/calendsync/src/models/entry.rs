@@ -1,11 +1,19 @@
+use std::collections::BTreeSet;
+
 use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use chrono_tz::Tz;
 use serde::Deserialize;
 use uuid::Uuid;
 
-use calendsync_core::calendar::{CalendarEntry, EntryKind, EntryType};
+use calendsync_core::calendar::{
+    merge_attendees, Attendee, CalendarEntry, ChecklistItem, ConflictScope, EntryKind, EntryType,
+    RecurrenceRule, RecurrenceScope, Reminder, Tag, TaskPriority, INITIAL_VERSION,
+};
 use calendsync_core::serde::{
-    deserialize_optional_date, deserialize_optional_datetime, deserialize_optional_string,
-    deserialize_optional_time,
+    deserialize_optional_attendees, deserialize_optional_checklist, deserialize_optional_date,
+    deserialize_optional_datetime, deserialize_optional_priority, deserialize_optional_recurrence,
+    deserialize_optional_reminders, deserialize_optional_string, deserialize_optional_tags,
+    deserialize_optional_time, deserialize_optional_timezone,
 };
 
 /// Server-side entry type with custom deserialization.
@@ -54,6 +62,39 @@ pub struct CreateEntry {
     pub end_date: Option<NaiveDate>,
     #[serde(default, deserialize_with = "deserialize_optional_string")]
     pub color: Option<String>,
+    /// RRULE value (e.g. `FREQ=WEEKLY;BYDAY=MO`).
+    #[serde(default, deserialize_with = "deserialize_optional_recurrence")]
+    pub recurrence: Option<RecurrenceRule>,
+    /// IANA time zone of the dates and times; defaults to the calendar's zone.
+    #[serde(default, deserialize_with = "deserialize_optional_timezone")]
+    pub timezone: Option<Tz>,
+    /// Comma-separated reminders (e.g. `10m,1d@09:00`).
+    #[serde(default, deserialize_with = "deserialize_optional_reminders")]
+    pub reminders: Option<Vec<Reminder>>,
+    /// Comma-separated attendee email addresses.
+    #[serde(default, deserialize_with = "deserialize_optional_attendees")]
+    pub attendees: Option<Vec<Attendee>>,
+    /// Comma-separated tags (e.g. `oncall,travel`).
+    #[serde(default, deserialize_with = "deserialize_optional_tags")]
+    pub tags: Option<BTreeSet<Tag>>,
+    /// Time of day a task is due.
+    #[serde(default, deserialize_with = "deserialize_optional_time")]
+    pub due_time: Option<NaiveTime>,
+    /// Task priority (`low`, `normal`, `high` or `urgent`).
+    #[serde(default, deserialize_with = "deserialize_optional_priority")]
+    pub priority: Option<TaskPriority>,
+    /// Task checklist, one item per line (e.g. `[x] Book flight`).
+    #[serde(default, deserialize_with = "deserialize_optional_checklist")]
+    pub checklist: Option<Vec<ChecklistItem>>,
+    /// When true, the task is shown on today until it is completed.
+    #[serde(default)]
+    pub roll_over: Option<bool>,
+    /// Which calendars to check for overlapping timed entries (`calendar` or `all`).
+    #[serde(default)]
+    pub conflict_scope: Option<ConflictScope>,
+    /// When true, the request fails with 409 instead of saving an entry with conflicts.
+    #[serde(default)]
+    pub reject_on_conflict: Option<bool>,
 }
 
 impl CreateEntry {
@@ -62,6 +103,8 @@ impl CreateEntry {
     pub fn into_entry(self) -> Option<CalendarEntry> {
         let end_date = match self.entry_type {
             ServerEntryType::MultiDay => self.end_date?,
+            // Timed entries may end on a later day (e.g. overnight)
+            ServerEntryType::Timed => self.end_date.unwrap_or(self.start_date),
             _ => self.start_date,
         };
 
@@ -72,7 +115,13 @@ impl CreateEntry {
                 let end = self.end_time?;
                 EntryKind::Timed { start, end }
             }
-            ServerEntryType::Task => EntryKind::Task { completed: false },
+            ServerEntryType::Task => EntryKind::Task {
+                completed: false,
+                due: self.due_time,
+                priority: self.priority.unwrap_or_default(),
+                checklist: self.checklist.unwrap_or_default(),
+                roll_over: self.roll_over.unwrap_or(false),
+            },
             ServerEntryType::MultiDay => EntryKind::MultiDay,
         };
 
@@ -87,6 +136,14 @@ impl CreateEntry {
             start_date: self.start_date,
             end_date,
             color: self.color,
+            timezone: self.timezone,
+            recurrence: self.recurrence,
+            reminders: self.reminders.unwrap_or_default(),
+            attendees: self.attendees.unwrap_or_default(),
+            tags: self.tags.unwrap_or_default(),
+            occurrence_date: None,
+            segment: None,
+            version: INITIAL_VERSION,
             created_at: now,
             updated_at: now,
         })
@@ -119,11 +176,73 @@ pub struct UpdateEntry {
     pub color: Option<String>,
     #[serde(default)]
     pub completed: Option<bool>,
+    /// New RRULE value for the series.
+    #[serde(default, deserialize_with = "deserialize_optional_recurrence")]
+    pub recurrence: Option<RecurrenceRule>,
+    /// When true, the entry stops repeating.
+    #[serde(default)]
+    pub clear_recurrence: Option<bool>,
+    /// Replaces the entry's reminders.
+    #[serde(default, deserialize_with = "deserialize_optional_reminders")]
+    pub reminders: Option<Vec<Reminder>>,
+    /// When true, all reminders are removed.
+    #[serde(default)]
+    pub clear_reminders: Option<bool>,
+    /// Replaces the attendees; people who stay invited keep their response.
+    #[serde(default, deserialize_with = "deserialize_optional_attendees")]
+    pub attendees: Option<Vec<Attendee>>,
+    /// When true, all attendees are removed.
+    #[serde(default)]
+    pub clear_attendees: Option<bool>,
+    /// Replaces the entry's tags.
+    #[serde(default, deserialize_with = "deserialize_optional_tags")]
+    pub tags: Option<BTreeSet<Tag>>,
+    /// When true, all tags are removed.
+    #[serde(default)]
+    pub clear_tags: Option<bool>,
+    /// New time of day a task is due.
+    #[serde(default, deserialize_with = "deserialize_optional_time")]
+    pub due_time: Option<NaiveTime>,
+    /// When true, the task's due time is removed.
+    #[serde(default)]
+    pub clear_due_time: Option<bool>,
+    /// New task priority.
+    #[serde(default, deserialize_with = "deserialize_optional_priority")]
+    pub priority: Option<TaskPriority>,
+    /// Replaces the task's checklist, one item per line.
+    #[serde(default, deserialize_with = "deserialize_optional_checklist")]
+    pub checklist: Option<Vec<ChecklistItem>>,
+    /// When true, all checklist items are removed.
+    #[serde(default)]
+    pub clear_checklist: Option<bool>,
+    /// Whether the task rolls over to today until it is completed.
+    #[serde(default)]
+    pub roll_over: Option<bool>,
+    /// Which occurrences of a recurring series to update (defaults to `all`).
+    #[serde(default)]
+    pub scope: Option<RecurrenceScope>,
+    /// Original date of the occurrence being edited, required for `this` and `following`.
+    #[serde(default, deserialize_with = "deserialize_optional_date")]
+    pub occurrence_date: Option<NaiveDate>,
+    /// IANA time zone the dates and times in this update are expressed in.
+    #[serde(default, deserialize_with = "deserialize_optional_timezone")]
+    pub timezone: Option<Tz>,
     /// Client's timestamp for LWW merge conflict resolution.
     /// If provided, the server will compare this with its own `updated_at`
     /// and only apply the update if the client's timestamp is newer.
     #[serde(default, deserialize_with = "deserialize_optional_datetime")]
     pub updated_at: Option<DateTime<Utc>>,
+    /// Revision the client's edit started from. When provided, the edit is
+    /// merged field by field with changes made since that revision, and
+    /// `updated_at` is ignored.
+    #[serde(default)]
+    pub base_revision: Option<u32>,
+    /// Which calendars to check for overlapping timed entries (`calendar` or `all`).
+    #[serde(default)]
+    pub conflict_scope: Option<ConflictScope>,
+    /// When true, the request fails with 409 instead of saving an entry with conflicts.
+    #[serde(default)]
+    pub reject_on_conflict: Option<bool>,
 }
 
 impl UpdateEntry {
@@ -142,11 +261,16 @@ impl UpdateEntry {
             entry.location = Some(location);
         }
         if let Some(start_date) = self.start_date {
-            entry.start_date = start_date;
-            // For non-multi-day entries, keep end_date in sync
-            if !matches!(entry.kind, EntryKind::MultiDay) {
-                entry.end_date = start_date;
+            // Timed entries keep their length in days; other single-day
+            // entries keep end_date in sync
+            match entry.kind {
+                EntryKind::Timed { .. } => {
+                    entry.end_date = start_date + (entry.end_date - entry.start_date);
+                }
+                EntryKind::MultiDay => {}
+                _ => entry.end_date = start_date,
             }
+            entry.start_date = start_date;
         }
         if let Some(end_date) = self.end_date {
             entry.end_date = end_date;
@@ -154,6 +278,26 @@ impl UpdateEntry {
         if let Some(color) = self.color {
             entry.color = Some(color);
         }
+        if self.clear_recurrence == Some(true) {
+            entry.recurrence = None;
+        } else if let Some(recurrence) = self.recurrence {
+            entry.recurrence = Some(recurrence);
+        }
+        if self.clear_reminders == Some(true) {
+            entry.reminders.clear();
+        } else if let Some(reminders) = self.reminders {
+            entry.reminders = reminders;
+        }
+        if self.clear_attendees == Some(true) {
+            entry.attendees.clear();
+        } else if let Some(attendees) = self.attendees {
+            entry.attendees = merge_attendees(&entry.attendees, attendees);
+        }
+        if self.clear_tags == Some(true) {
+            entry.tags.clear();
+        } else if let Some(tags) = self.tags {
+            entry.tags = tags;
+        }
 
         // Handle entry type changes
         if let Some(entry_type) = self.entry_type {
@@ -164,8 +308,8 @@ impl UpdateEntry {
                     EntryKind::AllDay
                 }
                 ServerEntryType::Timed => {
-                    // When changing to timed, sync end_date to start_date
-                    entry.end_date = entry.start_date;
+                    // When changing to timed, end on the start date unless told otherwise
+                    entry.end_date = self.end_date.unwrap_or(entry.start_date);
                     let start = self.start_time.unwrap_or_else(|| {
                         entry
                             .kind
@@ -183,8 +327,16 @@ impl UpdateEntry {
                 ServerEntryType::Task => {
                     // When changing to task, sync end_date to start_date
                     entry.end_date = entry.start_date;
-                    let completed = self.completed.unwrap_or(false);
-                    EntryKind::Task { completed }
+                    // An existing task keeps its details; see below for updates
+                    let mut kind = if entry.kind.is_task() {
+                        entry.kind.clone()
+                    } else {
+                        EntryKind::task(false)
+                    };
+                    if let EntryKind::Task { completed, .. } = &mut kind {
+                        *completed = self.completed.unwrap_or(false);
+                    }
+                    kind
                 }
                 ServerEntryType::MultiDay => {
                     // When changing to multi-day, use provided end_date or keep existing
@@ -205,7 +357,7 @@ impl UpdateEntry {
                         *end = new_end;
                     }
                 }
-                EntryKind::Task { completed } => {
+                EntryKind::Task { completed, .. } => {
                     if let Some(new_completed) = self.completed {
                         *completed = new_completed;
                     }
@@ -213,5 +365,76 @@ impl UpdateEntry {
                 _ => {}
             }
         }
+
+        if let EntryKind::Task {
+            due,
+            priority,
+            checklist,
+            roll_over,
+            ..
+        } = &mut entry.kind
+        {
+            if self.clear_due_time == Some(true) {
+                *due = None;
+            } else if let Some(due_time) = self.due_time {
+                *due = Some(due_time);
+            }
+            if let Some(new_priority) = self.priority {
+                *priority = new_priority;
+            }
+            if self.clear_checklist == Some(true) {
+                checklist.clear();
+            } else if let Some(items) = self.checklist {
+                *checklist = items;
+            }
+            if let Some(new_roll_over) = self.roll_over {
+                *roll_over = new_roll_over;
+            }
+        }
     }
 }
+
+/// Request payload for moving an entry to another calendar.
+#[derive(Debug, Deserialize)]
+pub struct MoveEntry {
+    /// The calendar the entry moves to.
+    pub calendar_id: Uuid,
+}
+
+/// Request payload for applying several entry writes at once.
+#[derive(Debug, Deserialize)]
+pub struct BatchEntries {
+    pub operations: Vec<BatchOperation>,
+}
+
+/// One write in a batch, tagged by `op`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BatchOperation {
+    /// Creates an entry from the same fields as `POST /api/entries`.
+    Create(CreateEntry),
+    /// Updates an entry with the same fields as `PUT /api/entries/{id}`.
+    Update {
+        id: Uuid,
+        /// When set, the update only applies while the entry is at this version.
+        #[serde(default)]
+        version: Option<u64>,
+        #[serde(flatten)]
+        changes: UpdateEntry,
+    },
+    /// Moves an entry to the trash.
+    Delete {
+        id: Uuid,
+        /// When set, the delete only applies while the entry is at this version.
+        #[serde(default)]
+        version: Option<u64>,
+    },
+}
+
+/// Request payload for answering an invitation to an entry.
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+#[derive(Debug, Deserialize)]
+pub struct RespondToEntry {
+    /// The current user's response (`accepted`, `declined`, `tentative` or `needs-action`).
+    pub status: calendsync_core::calendar::ResponseStatus,
+}
@@ -1,5 +1,11 @@
 mod calendar;
 mod entry;
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+mod user;
 
-pub use calendar::{CreateCalendar, UpdateCalendar};
-pub use entry::{CreateEntry, UpdateEntry};
+pub use calendar::{CreateCalendar, DuplicateCalendar, UpdateCalendar};
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+pub use entry::RespondToEntry;
+pub use entry::{BatchEntries, BatchOperation, CreateEntry, MoveEntry, UpdateEntry};
+#[cfg(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock"))]
+pub use user::UpdateUserTimezone;
@@ -0,0 +1,11 @@
+use calendsync_core::serde::deserialize_optional_timezone;
+use chrono_tz::Tz;
+use serde::Deserialize;
+
+/// Request payload for setting the current user's display time zone.
+#[derive(Debug, Deserialize)]
+pub struct UpdateUserTimezone {
+    /// IANA time zone name; empty clears the zone.
+    #[serde(default, deserialize_with = "deserialize_optional_timezone")]
+    pub timezone: Option<Tz>,
+}
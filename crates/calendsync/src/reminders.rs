@@ -0,0 +1,168 @@
+//! Background scheduler that delivers due reminders.
+//!
+//! Every tick covers the time since the previous one: reminders that fell due
+//! in between are claimed in storage and published as `reminder_due` events
+//! through `CachePubSub`, which takes them to SSE clients on every instance.
+//! After a restart, the first tick looks back over `REMINDER_LOOKBACK_SECONDS`
+//! so reminders missed while the server was down are still delivered. Claims
+//! are stored, so a reminder is delivered once even when several instances
+//! run the scheduler.
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tokio::time::MissedTickBehavior;
+use uuid::Uuid;
+
+use calendsync_core::calendar::{due_reminders, reminder_search_range, CalendarEvent, DueReminder};
+use calendsync_core::storage::Result;
+
+use crate::config::Config;
+use crate::state::AppState;
+
+/// Spawns the reminder scheduler. It runs until the server shuts down.
+pub fn spawn_reminder_scheduler(state: AppState, config: &Config) {
+    let interval = config.reminder_poll_interval();
+    let lookback = config.reminder_lookback();
+    tokio::spawn(run_scheduler(state, interval, lookback));
+}
+
+async fn run_scheduler(state: AppState, interval: Duration, lookback: Duration) {
+    let mut shutdown_rx = state.subscribe_shutdown();
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    let lookback = chrono::Duration::from_std(lookback).unwrap_or(chrono::Duration::zero());
+    let mut last_tick = Utc::now() - lookback;
+
+    tracing::info!(
+        interval_secs = interval.as_secs(),
+        lookback_secs = lookback.num_seconds(),
+        "Reminder scheduler started"
+    );
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                let now = Utc::now();
+                match deliver_due_reminders(&state, last_tick, now).await {
+                    Ok(delivered) => {
+                        if delivered > 0 {
+                            tracing::debug!(delivered, "Delivered due reminders");
+                        }
+                        last_tick = now;
+                    }
+                    // Keep the window open so the next tick retries it
+                    Err(err) => tracing::warn!(error = %err, "Failed to check due reminders"),
+                }
+            }
+            _ = shutdown_rx.recv() => {
+                tracing::debug!("Reminder scheduler shutting down");
+                break;
+            }
+        }
+    }
+}
+
+/// Delivers the reminders that fell due after `after` and no later than `until`.
+///
+/// Returns how many reminders this instance delivered. Reminders already
+/// claimed (by an earlier tick or another instance) are skipped.
+pub async fn deliver_due_reminders(
+    state: &AppState,
+    after: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> Result<usize> {
+    let entries = state
+        .entry_repo
+        .get_entries_with_reminders(reminder_search_range(after, until))
+        .await?;
+
+    // Entries without a zone of their own use their calendar's zone
+    let calendar_ids: HashSet<Uuid> = entries
+        .iter()
+        .filter(|entry| entry.timezone.is_none())
+        .map(|entry| entry.calendar_id)
+        .collect();
+    let mut calendar_timezones = HashMap::new();
+    for calendar_id in calendar_ids {
+        let timezone = state
+            .calendar_repo
+            .get_calendar(calendar_id)
+            .await?
+            .and_then(|calendar| calendar.timezone);
+        if let Some(timezone) = timezone {
+            calendar_timezones.insert(calendar_id, timezone);
+        }
+    }
+
+    let mut delivered = 0;
+    for due in due_reminders(&entries, &calendar_timezones, after, until) {
+        if !state
+            .entry_repo
+            .claim_reminder(due.entry.id, due.fire_at)
+            .await?
+        {
+            continue;
+        }
+        publish_reminder(state, due).await;
+        delivered += 1;
+    }
+
+    Ok(delivered)
+}
+
+async fn publish_reminder(state: &AppState, due: DueReminder) {
+    let calendar_id = due.entry.calendar_id;
+    let entry_id = due.entry.id;
+    let event = CalendarEvent::reminder_due(due);
+
+    if let Err(err) = state.cache_pubsub.publish(calendar_id, &event).await {
+        tracing::warn!(
+            %calendar_id,
+            %entry_id,
+            error = %err,
+            "Failed to publish reminder"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calendsync_core::calendar::{CalendarEntry, Reminder};
+
+    #[tokio::test]
+    async fn test_deliver_due_reminders_once() {
+        let state = AppState::default();
+        let now = Utc::now();
+        let starts_at = now + chrono::Duration::minutes(5);
+        let entry = CalendarEntry::timed(
+            Uuid::new_v4(),
+            "Standup",
+            starts_at.date_naive(),
+            starts_at.time(),
+            starts_at.time(),
+        )
+        .with_reminder(Reminder::minutes_before(10))
+        .with_created_at(now - chrono::Duration::hours(1));
+        state.entry_repo.create_entry(&entry).await.unwrap();
+
+        let mut receiver = state
+            .cache_pubsub
+            .subscribe(entry.calendar_id)
+            .await
+            .unwrap();
+        let after = now - chrono::Duration::minutes(30);
+
+        assert_eq!(deliver_due_reminders(&state, after, now).await.unwrap(), 1);
+        let event = receiver.try_recv().unwrap();
+        assert!(matches!(event, CalendarEvent::ReminderDue { .. }));
+        assert_eq!(event.entry().map(|e| e.id), Some(entry.id));
+
+        // Running over the same window again (e.g. another instance) delivers nothing
+        assert_eq!(deliver_due_reminders(&state, after, now).await.unwrap(), 0);
+        assert!(receiver.try_recv().is_err());
+    }
+}
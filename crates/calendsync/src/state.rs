@@ -5,7 +5,7 @@
 //! and supports different backend combinations via feature flags.
 
 use std::{
-    collections::{HashSet, VecDeque},
+    collections::{HashMap, HashSet, VecDeque},
     sync::{
         atomic::{AtomicU64, Ordering},
         Arc, RwLock,
@@ -14,9 +14,10 @@ use std::{
 use tokio::sync::{broadcast, RwLock as TokioRwLock};
 use uuid::Uuid;
 
-use calendsync_core::cache::CachePubSub;
+use calendsync_core::cache::{Cache, CachePubSub};
 use calendsync_core::storage::{
-    CalendarRepository, EntryRepository, MembershipRepository, SettingsRepository, UserRepository,
+    CalendarRepository, EntryRepository, FeedTokenRepository, MembershipRepository,
+    SettingsRepository, UserRepository,
 };
 use calendsync_ssr::SsrPool;
 
@@ -77,6 +78,22 @@ pub struct StoredEvent {
     pub event: CalendarEvent,
 }
 
+/// A rendered subscription feed and the calendar version it was rendered at.
+#[derive(Clone, Debug)]
+struct CachedFeed {
+    calendar_version: u64,
+    ics: String,
+}
+
+/// A calendar's slot in the feed cache.
+#[derive(Clone, Debug, Default)]
+struct FeedSlot {
+    /// Bumped by every invalidation, so a feed rendered before one is never
+    /// cached after it.
+    generation: u64,
+    feed: Option<CachedFeed>,
+}
+
 /// Shared application state.
 ///
 /// This is cloned for each request handler and contains shared resources
@@ -87,6 +104,13 @@ pub struct AppState {
     pub entry_repo: Arc<dyn EntryRepository>,
     /// Calendar repository (cached, wraps underlying storage).
     pub calendar_repo: Arc<dyn CalendarRepository>,
+    /// Entry repository without the cache, for bulk writes such as restores
+    /// that record no revisions and notify once per calendar.
+    pub entry_storage: Arc<dyn EntryRepository>,
+    /// Calendar repository without the cache.
+    pub calendar_storage: Arc<dyn CalendarRepository>,
+    /// Cache in front of `entry_storage` and `calendar_storage`.
+    pub cache: Arc<dyn Cache>,
     /// User repository (uncached, auth lookups should hit source of truth).
     /// Note: Only used when auth features are enabled.
     #[allow(dead_code)]
@@ -102,6 +126,13 @@ pub struct AppState {
         allow(dead_code)
     )]
     pub settings_repo: Arc<dyn SettingsRepository>,
+    /// Feed token repository for iCalendar subscription feeds.
+    /// Note: Only used when auth features are enabled (feeds belong to users).
+    #[cfg_attr(
+        not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")),
+        allow(dead_code)
+    )]
+    pub feed_token_repo: Arc<dyn FeedTokenRepository>,
     /// Cache pub/sub for cross-instance event propagation.
     pub cache_pubsub: Arc<dyn CachePubSub>,
 
@@ -111,8 +142,27 @@ pub struct AppState {
     pub event_history: Arc<RwLock<VecDeque<StoredEvent>>>,
     /// Maximum events to keep in history.
     event_history_max_size: usize,
+    /// Days deleted calendars and entries stay in the trash.
+    /// Note: Only read when auth features are enabled (the trash is per user).
+    #[cfg_attr(
+        not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")),
+        allow(dead_code)
+    )]
+    pub trash_retention_days: u32,
+    /// Lowercased emails of the users who may back up and restore the server.
+    /// Note: Only read when auth features are enabled (admins are users).
+    #[cfg_attr(
+        not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")),
+        allow(dead_code)
+    )]
+    pub admin_emails: Vec<String>,
+    /// Lowercased feed hosts that may be private or loopback addresses.
+    pub subscription_allowed_hosts: Vec<String>,
     /// Calendars with active event listeners.
     active_listeners: Arc<RwLock<HashSet<Uuid>>>,
+    /// Rendered subscription feeds by calendar.
+    /// Dropped whenever the calendar's event listener sees an event.
+    feed_cache: Arc<RwLock<HashMap<Uuid, FeedSlot>>>,
 
     /// Shutdown signal sender for SSE connections.
     pub shutdown_tx: broadcast::Sender<()>,
@@ -144,12 +194,17 @@ pub struct AppState {
 
 impl AppState {
     /// Creates a new AppState with the given repositories and configuration.
+    #[allow(clippy::too_many_arguments)]
     fn build(
         entry_repo: Arc<dyn EntryRepository>,
         calendar_repo: Arc<dyn CalendarRepository>,
+        entry_storage: Arc<dyn EntryRepository>,
+        calendar_storage: Arc<dyn CalendarRepository>,
         user_repo: Arc<dyn UserRepository>,
         membership_repo: Arc<dyn MembershipRepository>,
         settings_repo: Arc<dyn SettingsRepository>,
+        feed_token_repo: Arc<dyn FeedTokenRepository>,
+        cache: Arc<dyn Cache>,
         cache_pubsub: Arc<dyn CachePubSub>,
         config: &Config,
     ) -> Self {
@@ -163,14 +218,22 @@ impl AppState {
         Self {
             entry_repo,
             calendar_repo,
+            entry_storage,
+            calendar_storage,
             user_repo,
             membership_repo,
             settings_repo,
+            feed_token_repo,
+            cache,
             cache_pubsub,
             event_counter: Arc::new(AtomicU64::new(1)),
             event_history: Arc::new(RwLock::new(VecDeque::new())),
             event_history_max_size: config.event_history_max_size,
+            trash_retention_days: config.trash_retention_days,
+            admin_emails: config.admin_emails.clone(),
+            subscription_allowed_hosts: config.subscription_allowed_hosts.clone(),
             active_listeners: Arc::new(RwLock::new(HashSet::new())),
+            feed_cache: Arc::new(RwLock::new(HashMap::new())),
             shutdown_tx,
             ssr_pool: Arc::new(TokioRwLock::new(None)),
             dev_reload_tx,
@@ -268,6 +331,8 @@ impl AppState {
 
         tracing::trace!(event_id = id, %calendar_id, "Storing event in history");
 
+        self.invalidate_feed(calendar_id);
+
         if let Ok(mut history) = self.event_history.write() {
             history.push_back(stored);
 
@@ -278,12 +343,84 @@ impl AppState {
         }
     }
 
+    /// Get a calendar's cached subscription feed.
+    ///
+    /// Returns `None` if no feed is cached or it was rendered at an older
+    /// calendar version (calendar edits don't emit events).
+    #[cfg_attr(
+        not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")),
+        allow(dead_code)
+    )]
+    pub fn cached_feed(&self, calendar_id: Uuid, calendar_version: u64) -> Option<String> {
+        self.feed_cache
+            .read()
+            .ok()?
+            .get(&calendar_id)?
+            .feed
+            .as_ref()
+            .filter(|feed| feed.calendar_version == calendar_version)
+            .map(|feed| feed.ics.clone())
+    }
+
+    /// Get a calendar's feed generation, to read before rendering its feed
+    /// and pass to `cache_feed` afterwards.
+    #[cfg_attr(
+        not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")),
+        allow(dead_code)
+    )]
+    pub fn feed_generation(&self, calendar_id: Uuid) -> u64 {
+        self.feed_cache
+            .read()
+            .ok()
+            .and_then(|cache| cache.get(&calendar_id).map(|slot| slot.generation))
+            .unwrap_or_default()
+    }
+
+    /// Cache a calendar's rendered subscription feed.
+    ///
+    /// `generation` is the calendar's `feed_generation` from before the feed
+    /// was rendered; if the feed was invalidated since, the render may have
+    /// missed a change and is not cached. Callers must await
+    /// `ensure_event_listener` for the calendar first, so entry changes drop
+    /// the cached feed.
+    #[cfg_attr(
+        not(any(feature = "auth-sqlite", feature = "auth-redis", feature = "auth-mock")),
+        allow(dead_code)
+    )]
+    pub fn cache_feed(
+        &self,
+        calendar_id: Uuid,
+        generation: u64,
+        calendar_version: u64,
+        ics: String,
+    ) {
+        if let Ok(mut cache) = self.feed_cache.write() {
+            let slot = cache.entry(calendar_id).or_default();
+            if slot.generation == generation {
+                slot.feed = Some(CachedFeed {
+                    calendar_version,
+                    ics,
+                });
+            }
+        }
+    }
+
+    /// Drop a calendar's cached subscription feed, and any being rendered.
+    fn invalidate_feed(&self, calendar_id: Uuid) {
+        if let Ok(mut cache) = self.feed_cache.write() {
+            let slot = cache.entry(calendar_id).or_default();
+            slot.generation += 1;
+            slot.feed = None;
+        }
+    }
+
     /// Ensures an event listener is running for the given calendar.
     ///
     /// If a listener is already running, this is a no-op.
-    /// Otherwise, spawns a background task that subscribes to CachePubSub
-    /// and populates the local event_history.
-    pub fn ensure_event_listener(&self, calendar_id: Uuid) {
+    /// Otherwise, subscribes to CachePubSub before returning, so events
+    /// published after this call are seen, and spawns a background task that
+    /// populates the local event_history.
+    pub async fn ensure_event_listener(&self, calendar_id: Uuid) {
         // Check if listener already exists
         {
             let listeners = self.active_listeners.read().expect("Lock poisoned");
@@ -302,27 +439,30 @@ impl AppState {
             listeners.insert(calendar_id);
         }
 
+        let mut receiver = match self.cache_pubsub.subscribe(calendar_id).await {
+            Ok(r) => r,
+            Err(err) => {
+                tracing::error!(%calendar_id, error = %err, "Failed to subscribe to calendar events");
+                // Remove from active listeners
+                if let Ok(mut listeners) = self.active_listeners.write() {
+                    listeners.remove(&calendar_id);
+                }
+                return;
+            }
+        };
+
+        tracing::debug!(%calendar_id, "Event listener started");
+
+        // A feed cached or being rendered before the subscription was live
+        // may have missed events, including one started by a concurrent
+        // caller that found this listener registered but not yet subscribed
+        self.invalidate_feed(calendar_id);
+
         // Spawn the listener task
         let state = self.clone();
-        let cache_pubsub = self.cache_pubsub.clone();
         let mut shutdown_rx = self.shutdown_tx.subscribe();
 
         tokio::spawn(async move {
-            let receiver_result = cache_pubsub.subscribe(calendar_id).await;
-            let mut receiver = match receiver_result {
-                Ok(r) => r,
-                Err(err) => {
-                    tracing::error!(%calendar_id, error = %err, "Failed to subscribe to calendar events");
-                    // Remove from active listeners
-                    if let Ok(mut listeners) = state.active_listeners.write() {
-                        listeners.remove(&calendar_id);
-                    }
-                    return;
-                }
-            };
-
-            tracing::debug!(%calendar_id, "Event listener started");
-
             loop {
                 tokio::select! {
                     result = receiver.recv() => {
@@ -332,6 +472,7 @@ impl AppState {
                             }
                             Err(broadcast::error::RecvError::Lagged(n)) => {
                                 tracing::warn!(%calendar_id, lagged = n, "Event listener lagged");
+                                state.invalidate_feed(calendar_id);
                             }
                             Err(broadcast::error::RecvError::Closed) => {
                                 tracing::info!(%calendar_id, "Event channel closed");
@@ -346,10 +487,11 @@ impl AppState {
                 }
             }
 
-            // Remove from active listeners
+            // Remove from active listeners; nothing invalidates the feed anymore
             if let Ok(mut listeners) = state.active_listeners.write() {
                 listeners.remove(&calendar_id);
             }
+            state.invalidate_feed(calendar_id);
         });
     }
 
@@ -443,7 +585,7 @@ mod sqlite_memory {
 
             let cached_calendar_repo = Arc::new(CachedCalendarRepository::new(
                 sqlite_repo.clone(),
-                memory_cache,
+                memory_cache.clone(),
                 config.cache_ttl(),
             ));
 
@@ -452,7 +594,11 @@ mod sqlite_memory {
                 cached_calendar_repo,
                 sqlite_repo.clone(),
                 sqlite_repo.clone(),
+                sqlite_repo.clone(),
+                sqlite_repo.clone(),
+                sqlite_repo.clone(),
                 sqlite_repo,
+                memory_cache,
                 memory_pubsub,
                 config,
             ))
@@ -483,7 +629,7 @@ mod sqlite_redis {
 
             let cached_calendar_repo = Arc::new(CachedCalendarRepository::new(
                 sqlite_repo.clone(),
-                redis_cache,
+                redis_cache.clone(),
                 config.cache_ttl(),
             ));
 
@@ -492,7 +638,10 @@ mod sqlite_redis {
                 cached_calendar_repo,
                 sqlite_repo.clone(),
                 sqlite_repo.clone(),
+                sqlite_repo.clone(),
+                sqlite_repo.clone(),
                 sqlite_repo,
+                redis_cache,
                 redis_pubsub,
                 config,
             ))
@@ -524,7 +673,7 @@ mod inmemory_memory {
 
             let cached_calendar_repo = Arc::new(CachedCalendarRepository::new(
                 inmemory_repo.clone(),
-                memory_cache,
+                memory_cache.clone(),
                 config.cache_ttl(),
             ));
 
@@ -533,7 +682,11 @@ mod inmemory_memory {
                 cached_calendar_repo,
                 inmemory_repo.clone(),
                 inmemory_repo.clone(),
+                inmemory_repo.clone(),
+                inmemory_repo.clone(),
+                inmemory_repo.clone(),
                 inmemory_repo,
+                memory_cache,
                 memory_pubsub,
                 config,
             ))
@@ -570,7 +723,7 @@ mod dynamodb_memory {
 
             let cached_calendar_repo = Arc::new(CachedCalendarRepository::new(
                 dynamodb_repo.clone(),
-                memory_cache,
+                memory_cache.clone(),
                 config.cache_ttl(),
             ));
 
@@ -579,7 +732,11 @@ mod dynamodb_memory {
                 cached_calendar_repo,
                 dynamodb_repo.clone(),
                 dynamodb_repo.clone(),
+                dynamodb_repo.clone(),
+                dynamodb_repo.clone(),
+                dynamodb_repo.clone(),
                 dynamodb_repo,
+                memory_cache,
                 memory_pubsub,
                 config,
             ))
@@ -616,7 +773,7 @@ mod dynamodb_redis {
 
             let cached_calendar_repo = Arc::new(CachedCalendarRepository::new(
                 dynamodb_repo.clone(),
-                redis_cache,
+                redis_cache.clone(),
                 config.cache_ttl(),
             ));
 
@@ -625,7 +782,10 @@ mod dynamodb_redis {
                 cached_calendar_repo,
                 dynamodb_repo.clone(),
                 dynamodb_repo.clone(),
+                dynamodb_repo.clone(),
+                dynamodb_repo.clone(),
                 dynamodb_repo,
+                redis_cache,
                 redis_pubsub,
                 config,
             ))
@@ -640,19 +800,23 @@ mod dynamodb_redis {
 #[cfg(test)]
 mod test_support {
     use super::*;
-    use crate::cache::memory::MemoryPubSub;
+    use crate::cache::memory::{MemoryCache, MemoryPubSub};
 
-    use std::collections::HashMap;
+    use std::collections::{BTreeSet, HashMap, HashSet};
 
     use async_trait::async_trait;
+    use chrono::{DateTime, NaiveDate, Utc};
     use tokio::sync::RwLock;
 
     use calendsync_core::calendar::{
-        Calendar, CalendarEntry, CalendarMembership, CalendarRole, CalendarSettings, User,
+        duplicate_contents, expand_recurring_entries, matches_any_tag, rank_entries, Calendar,
+        CalendarEntry, CalendarMembership, CalendarRole, CalendarSettings, CalendarSubscription,
+        DuplicateOptions, DuplicateSummary, EntryRevision, FeedToken, OccurrenceOverride,
+        SearchQuery, Tag, TrashedCalendar, TrashedEntry, User,
     };
     use calendsync_core::storage::{
-        CalendarRepository, DateRange, EntryRepository, MembershipRepository, Result,
-        SettingsRepository, UserRepository,
+        CalendarRepository, DateRange, EntryRepository, FeedTokenRepository, MembershipRepository,
+        RepositoryError, Result, SettingsRepository, UserRepository,
     };
 
     /// Minimal in-memory repository for tests.
@@ -660,10 +824,16 @@ mod test_support {
     #[derive(Debug, Default)]
     struct TestRepository {
         entries: RwLock<HashMap<Uuid, CalendarEntry>>,
+        overrides: RwLock<HashMap<(Uuid, NaiveDate), OccurrenceOverride>>,
         calendars: RwLock<HashMap<Uuid, Calendar>>,
         users: RwLock<HashMap<Uuid, User>>,
         memberships: RwLock<HashMap<(Uuid, Uuid), CalendarMembership>>,
         settings: RwLock<HashMap<(Uuid, Uuid), CalendarSettings>>,
+        reminder_claims: RwLock<HashSet<(Uuid, DateTime<Utc>)>>,
+        trashed_entries: RwLock<HashMap<Uuid, TrashedEntry>>,
+        trashed_calendars: RwLock<HashMap<Uuid, TrashedCalendar>>,
+        revisions: RwLock<HashMap<Uuid, Vec<EntryRevision>>>,
+        feed_tokens: RwLock<HashMap<(Uuid, Uuid), FeedToken>>,
     }
 
     #[async_trait]
@@ -683,12 +853,57 @@ mod test_support {
                 .values()
                 .filter(|entry| {
                     entry.calendar_id == calendar_id
-                        && entry.start_date <= date_range.end
-                        && entry.end_date >= date_range.start
+                        && (entry.is_recurring()
+                            || (entry.start_date <= date_range.end
+                                && entry.end_date >= date_range.start))
                 })
                 .cloned()
                 .collect();
-            Ok(filtered)
+            let overrides: Vec<OccurrenceOverride> =
+                self.overrides.read().await.values().cloned().collect();
+            Ok(expand_recurring_entries(filtered, &overrides, &date_range))
+        }
+
+        async fn get_entries_by_tags(
+            &self,
+            calendar_id: Uuid,
+            tags: &BTreeSet<Tag>,
+            date_range: DateRange,
+        ) -> Result<Vec<CalendarEntry>> {
+            let mut entries = self
+                .get_entries_by_calendar(calendar_id, date_range)
+                .await?;
+            entries.retain(|entry| matches_any_tag(&entry.tags, tags));
+            Ok(entries)
+        }
+
+        async fn get_stored_entries_by_calendar(
+            &self,
+            calendar_id: Uuid,
+        ) -> Result<Vec<CalendarEntry>> {
+            let entries = self.entries.read().await;
+            let mut result: Vec<CalendarEntry> = entries
+                .values()
+                .filter(|entry| entry.calendar_id == calendar_id)
+                .cloned()
+                .collect();
+            result.sort_by_key(|entry| entry.start_date);
+            Ok(result)
+        }
+
+        async fn search_entries(
+            &self,
+            calendar_ids: &[Uuid],
+            query: &SearchQuery,
+            limit: usize,
+        ) -> Result<Vec<CalendarEntry>> {
+            let entries = self.entries.read().await;
+            let candidates: Vec<CalendarEntry> = entries
+                .values()
+                .filter(|entry| calendar_ids.contains(&entry.calendar_id))
+                .cloned()
+                .collect();
+            Ok(rank_entries(query, candidates, limit))
         }
 
         async fn create_entry(&self, entry: &CalendarEntry) -> Result<()> {
@@ -699,15 +914,214 @@ mod test_support {
 
         async fn update_entry(&self, entry: &CalendarEntry) -> Result<()> {
             let mut entries = self.entries.write().await;
-            entries.insert(entry.id, entry.clone());
+            let version = entries
+                .get(&entry.id)
+                .map_or(entry.version, |e| e.version + 1);
+            entries.insert(
+                entry.id,
+                CalendarEntry {
+                    version,
+                    ..entry.clone()
+                },
+            );
+            Ok(())
+        }
+
+        async fn update_entry_if_version(
+            &self,
+            entry: &CalendarEntry,
+            expected_version: u64,
+        ) -> Result<()> {
+            let mut entries = self.entries.write().await;
+            if entries.get(&entry.id).map(|e| e.version) != Some(expected_version) {
+                return Err(RepositoryError::VersionMismatch {
+                    entity_type: "CalendarEntry",
+                    id: entry.id.to_string(),
+                    expected: expected_version,
+                });
+            }
+            entries.insert(
+                entry.id,
+                CalendarEntry {
+                    version: expected_version + 1,
+                    ..entry.clone()
+                },
+            );
             Ok(())
         }
 
+        async fn move_entry(
+            &self,
+            id: Uuid,
+            calendar_id: Uuid,
+            expected_version: u64,
+        ) -> Result<CalendarEntry> {
+            let mut entries = self.entries.write().await;
+            let Some(entry) = entries.get_mut(&id) else {
+                return Err(RepositoryError::NotFound {
+                    entity_type: "CalendarEntry",
+                    id: id.to_string(),
+                });
+            };
+            if entry.version != expected_version {
+                return Err(RepositoryError::VersionMismatch {
+                    entity_type: "CalendarEntry",
+                    id: id.to_string(),
+                    expected: expected_version,
+                });
+            }
+            entry.calendar_id = calendar_id;
+            entry.version += 1;
+            Ok(entry.clone())
+        }
+
         async fn delete_entry(&self, id: Uuid) -> Result<()> {
             let mut entries = self.entries.write().await;
-            entries.remove(&id);
+            if let Some(entry) = entries.remove(&id) {
+                let deleted_at = Utc::now();
+                let trashed = TrashedEntry { entry, deleted_at };
+                self.trashed_entries.write().await.insert(id, trashed);
+            }
+            Ok(())
+        }
+
+        async fn delete_entry_if_version(&self, id: Uuid, expected_version: u64) -> Result<()> {
+            let mut entries = self.entries.write().await;
+            let entry = entries
+                .remove(&id)
+                .ok_or_else(|| RepositoryError::NotFound {
+                    entity_type: "CalendarEntry",
+                    id: id.to_string(),
+                })?;
+            if entry.version != expected_version {
+                entries.insert(id, entry);
+                return Err(RepositoryError::VersionMismatch {
+                    entity_type: "CalendarEntry",
+                    id: id.to_string(),
+                    expected: expected_version,
+                });
+            }
+            let deleted_at = Utc::now();
+            let trashed = TrashedEntry { entry, deleted_at };
+            self.trashed_entries.write().await.insert(id, trashed);
+            Ok(())
+        }
+
+        async fn get_trashed_entries(&self, calendar_ids: &[Uuid]) -> Result<Vec<TrashedEntry>> {
+            let trashed = self.trashed_entries.read().await;
+            Ok(trashed
+                .values()
+                .filter(|t| calendar_ids.contains(&t.entry.calendar_id))
+                .cloned()
+                .collect())
+        }
+
+        async fn restore_entry(&self, id: Uuid) -> Result<CalendarEntry> {
+            let trashed = self
+                .trashed_entries
+                .write()
+                .await
+                .remove(&id)
+                .ok_or_else(|| RepositoryError::NotFound {
+                    entity_type: "TrashedEntry",
+                    id: id.to_string(),
+                })?;
+            let mut entries = self.entries.write().await;
+            entries.insert(id, trashed.entry.clone());
+            Ok(trashed.entry)
+        }
+
+        async fn purge_entries(&self, deleted_before: DateTime<Utc>) -> Result<usize> {
+            let mut trashed = self.trashed_entries.write().await;
+            let purged: Vec<Uuid> = trashed
+                .values()
+                .filter(|t| t.deleted_at < deleted_before)
+                .map(|t| t.entry.id)
+                .collect();
+            trashed.retain(|id, _| !purged.contains(id));
+            self.overrides
+                .write()
+                .await
+                .retain(|(series_id, _), _| !purged.contains(series_id));
+            self.revisions
+                .write()
+                .await
+                .retain(|entry_id, _| !purged.contains(entry_id));
+            Ok(purged.len())
+        }
+
+        async fn append_entry_revision(&self, revision: &EntryRevision) -> Result<()> {
+            let mut revisions = self.revisions.write().await;
+            revisions
+                .entry(revision.entry_id)
+                .or_default()
+                .push(revision.clone());
+            Ok(())
+        }
+
+        async fn get_entry_revisions(&self, entry_id: Uuid) -> Result<Vec<EntryRevision>> {
+            let revisions = self.revisions.read().await;
+            Ok(revisions.get(&entry_id).cloned().unwrap_or_default())
+        }
+
+        async fn get_occurrence_overrides(
+            &self,
+            series_id: Uuid,
+        ) -> Result<Vec<OccurrenceOverride>> {
+            let overrides = self.overrides.read().await;
+            Ok(overrides
+                .values()
+                .filter(|o| o.series_id == series_id)
+                .cloned()
+                .collect())
+        }
+
+        async fn upsert_occurrence_override(&self, occurrence: &OccurrenceOverride) -> Result<()> {
+            let mut overrides = self.overrides.write().await;
+            overrides.insert(
+                (occurrence.series_id, occurrence.original_date),
+                occurrence.clone(),
+            );
             Ok(())
         }
+
+        async fn delete_occurrence_overrides(
+            &self,
+            series_id: Uuid,
+            from: NaiveDate,
+        ) -> Result<()> {
+            let mut overrides = self.overrides.write().await;
+            overrides.retain(|(id, date), _| *id != series_id || *date < from);
+            Ok(())
+        }
+
+        async fn get_entries_with_reminders(
+            &self,
+            date_range: DateRange,
+        ) -> Result<Vec<CalendarEntry>> {
+            let entries = self.entries.read().await;
+            let filtered: Vec<CalendarEntry> = entries
+                .values()
+                .filter(|entry| {
+                    entry.has_reminders()
+                        && (entry.is_recurring()
+                            || (entry.start_date <= date_range.end
+                                && entry.end_date >= date_range.start))
+                })
+                .cloned()
+                .collect();
+            let overrides: Vec<OccurrenceOverride> =
+                self.overrides.read().await.values().cloned().collect();
+            Ok(expand_recurring_entries(filtered, &overrides, &date_range))
+        }
+
+        async fn claim_reminder(&self, entry_id: Uuid, fire_at: DateTime<Utc>) -> Result<bool> {
+            Ok(self
+                .reminder_claims
+                .write()
+                .await
+                .insert((entry_id, fire_at)))
+        }
     }
 
     #[async_trait]
@@ -725,15 +1139,198 @@ mod test_support {
 
         async fn update_calendar(&self, calendar: &Calendar) -> Result<()> {
             let mut calendars = self.calendars.write().await;
-            calendars.insert(calendar.id, calendar.clone());
+            let version = calendars
+                .get(&calendar.id)
+                .map_or(calendar.version, |c| c.version + 1);
+            calendars.insert(
+                calendar.id,
+                Calendar {
+                    version,
+                    ..calendar.clone()
+                },
+            );
+            Ok(())
+        }
+
+        async fn update_calendar_if_version(
+            &self,
+            calendar: &Calendar,
+            expected_version: u64,
+        ) -> Result<()> {
+            let mut calendars = self.calendars.write().await;
+            if calendars.get(&calendar.id).map(|c| c.version) != Some(expected_version) {
+                return Err(RepositoryError::VersionMismatch {
+                    entity_type: "Calendar",
+                    id: calendar.id.to_string(),
+                    expected: expected_version,
+                });
+            }
+            calendars.insert(
+                calendar.id,
+                Calendar {
+                    version: expected_version + 1,
+                    ..calendar.clone()
+                },
+            );
             Ok(())
         }
 
+        async fn record_subscription_refresh(
+            &self,
+            id: Uuid,
+            subscription: &CalendarSubscription,
+            expected_version: u64,
+        ) -> Result<()> {
+            let mut calendars = self.calendars.write().await;
+            match calendars.get_mut(&id) {
+                Some(calendar) if calendar.version == expected_version => {
+                    calendar.subscription = Some(subscription.clone());
+                    Ok(())
+                }
+                _ => Err(RepositoryError::VersionMismatch {
+                    entity_type: "Calendar",
+                    id: id.to_string(),
+                    expected: expected_version,
+                }),
+            }
+        }
+
         async fn delete_calendar(&self, id: Uuid) -> Result<()> {
             let mut calendars = self.calendars.write().await;
-            calendars.remove(&id);
+            let Some(calendar) = calendars.remove(&id) else {
+                return Ok(());
+            };
+            let deleted_at = Utc::now();
+            let mut entries = self.entries.write().await;
+            let mut trashed = self.trashed_entries.write().await;
+            let entry_ids: Vec<Uuid> = entries
+                .values()
+                .filter(|e| e.calendar_id == id)
+                .map(|e| e.id)
+                .collect();
+            for entry_id in entry_ids {
+                if let Some(entry) = entries.remove(&entry_id) {
+                    trashed.insert(entry_id, TrashedEntry { entry, deleted_at });
+                }
+            }
+            self.trashed_calendars.write().await.insert(
+                id,
+                TrashedCalendar {
+                    calendar,
+                    deleted_at,
+                },
+            );
             Ok(())
         }
+
+        async fn restore_calendar(&self, id: Uuid) -> Result<Calendar> {
+            let trashed_calendar = self
+                .trashed_calendars
+                .write()
+                .await
+                .remove(&id)
+                .ok_or_else(|| RepositoryError::NotFound {
+                    entity_type: "TrashedCalendar",
+                    id: id.to_string(),
+                })?;
+            let mut entries = self.entries.write().await;
+            self.trashed_entries.write().await.retain(|entry_id, t| {
+                let restore =
+                    t.entry.calendar_id == id && t.deleted_at == trashed_calendar.deleted_at;
+                if restore {
+                    entries.insert(*entry_id, t.entry.clone());
+                }
+                !restore
+            });
+            let mut calendars = self.calendars.write().await;
+            calendars.insert(id, trashed_calendar.calendar.clone());
+            Ok(trashed_calendar.calendar)
+        }
+
+        async fn purge_calendars(&self, deleted_before: DateTime<Utc>) -> Result<usize> {
+            let mut trashed = self.trashed_calendars.write().await;
+            let purged: Vec<Uuid> = trashed
+                .values()
+                .filter(|t| t.deleted_at < deleted_before)
+                .map(|t| t.calendar.id)
+                .collect();
+            trashed.retain(|id, _| !purged.contains(id));
+            self.trashed_entries
+                .write()
+                .await
+                .retain(|_, t| !purged.contains(&t.entry.calendar_id));
+            self.memberships
+                .write()
+                .await
+                .retain(|(calendar_id, _), _| !purged.contains(calendar_id));
+            Ok(purged.len())
+        }
+
+        async fn get_subscribed_calendars(&self) -> Result<Vec<Calendar>> {
+            Ok(self
+                .calendars
+                .read()
+                .await
+                .values()
+                .filter(|c| c.subscription.is_some())
+                .cloned()
+                .collect())
+        }
+
+        async fn list_calendars(&self) -> Result<Vec<Calendar>> {
+            Ok(self.calendars.read().await.values().cloned().collect())
+        }
+
+        async fn duplicate_calendar(
+            &self,
+            source_id: Uuid,
+            copy: &Calendar,
+            options: &DuplicateOptions,
+        ) -> Result<DuplicateSummary> {
+            if !self.calendars.read().await.contains_key(&source_id) {
+                return Err(RepositoryError::NotFound {
+                    entity_type: "Calendar",
+                    id: source_id.to_string(),
+                });
+            }
+            let entries: Vec<CalendarEntry> = self
+                .entries
+                .read()
+                .await
+                .values()
+                .filter(|e| e.calendar_id == source_id)
+                .cloned()
+                .collect();
+            let overrides: Vec<OccurrenceOverride> =
+                self.overrides.read().await.values().cloned().collect();
+            let memberships: Vec<CalendarMembership> = self
+                .memberships
+                .read()
+                .await
+                .values()
+                .filter(|m| m.calendar_id == source_id)
+                .cloned()
+                .collect();
+            let contents = duplicate_contents(copy.id, &entries, &overrides, &memberships, options);
+
+            self.calendars.write().await.insert(copy.id, copy.clone());
+            let mut stored = self.entries.write().await;
+            for entry in &contents.entries {
+                stored.insert(entry.id, entry.clone());
+            }
+            let mut stored = self.overrides.write().await;
+            for over in &contents.overrides {
+                stored.insert((over.series_id, over.original_date), over.clone());
+            }
+            let mut stored = self.memberships.write().await;
+            for membership in &contents.memberships {
+                stored.insert(
+                    (membership.calendar_id, membership.user_id),
+                    membership.clone(),
+                );
+            }
+            Ok(contents.summary())
+        }
     }
 
     #[async_trait]
@@ -774,6 +1371,10 @@ mod test_support {
             users.insert(user.id, user.clone());
             Ok(())
         }
+
+        async fn list_users(&self) -> Result<Vec<User>> {
+            Ok(self.users.read().await.values().cloned().collect())
+        }
     }
 
     #[async_trait]
@@ -801,6 +1402,19 @@ mod test_support {
             Ok(result)
         }
 
+        async fn get_trashed_calendars_for_user(
+            &self,
+            user_id: Uuid,
+        ) -> Result<Vec<(TrashedCalendar, CalendarRole)>> {
+            let memberships = self.memberships.read().await;
+            let trashed = self.trashed_calendars.read().await;
+            Ok(memberships
+                .iter()
+                .filter(|((_, uid), _)| *uid == user_id)
+                .filter_map(|((cal_id, _), m)| trashed.get(cal_id).cloned().map(|t| (t, m.role)))
+                .collect())
+        }
+
         async fn get_users_for_calendar(
             &self,
             calendar_id: Uuid,
@@ -854,6 +1468,40 @@ mod test_support {
         }
     }
 
+    #[async_trait]
+    impl FeedTokenRepository for TestRepository {
+        async fn get_feed_token(&self, token: &str) -> Result<Option<FeedToken>> {
+            let feed_tokens = self.feed_tokens.read().await;
+            Ok(feed_tokens.values().find(|t| t.token == token).cloned())
+        }
+
+        async fn get_feed_token_for_user(
+            &self,
+            calendar_id: Uuid,
+            user_id: Uuid,
+        ) -> Result<Option<FeedToken>> {
+            let feed_tokens = self.feed_tokens.read().await;
+            Ok(feed_tokens.get(&(calendar_id, user_id)).cloned())
+        }
+
+        async fn put_feed_token(&self, token: &FeedToken) -> Result<()> {
+            let mut feed_tokens = self.feed_tokens.write().await;
+            feed_tokens.insert((token.calendar_id, token.user_id), token.clone());
+            Ok(())
+        }
+
+        async fn delete_feed_token(&self, calendar_id: Uuid, user_id: Uuid) -> Result<()> {
+            let mut feed_tokens = self.feed_tokens.write().await;
+            if feed_tokens.remove(&(calendar_id, user_id)).is_none() {
+                return Err(RepositoryError::NotFound {
+                    entity_type: "FeedToken",
+                    id: format!("{calendar_id}:{user_id}"),
+                });
+            }
+            Ok(())
+        }
+    }
+
     impl Default for AppState {
         /// Creates an AppState with in-memory storage for testing.
         ///
@@ -866,14 +1514,89 @@ mod test_support {
 
             // For tests, we use the test repository without caching
             Self::build(
+                test_repo.clone(),
+                test_repo.clone(),
+                test_repo.clone(),
                 test_repo.clone(),
                 test_repo.clone(),
                 test_repo.clone(),
                 test_repo.clone(),
                 test_repo,
+                Arc::new(MemoryCache::new(1_000)),
                 memory_pubsub,
                 &config,
             )
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calendsync_core::calendar::CalendarEntry;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_feed_cache_is_invalidated_by_events() {
+        let state = AppState::default();
+        let calendar_id = Uuid::new_v4();
+
+        let generation = state.feed_generation(calendar_id);
+        state.cache_feed(calendar_id, generation, 1, "BEGIN:VCALENDAR".to_string());
+        assert!(state.cached_feed(calendar_id, 1).is_some());
+        // A calendar edit bumps its version
+        assert!(state.cached_feed(calendar_id, 2).is_none());
+
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let entry = CalendarEntry::all_day(calendar_id, "Standup", date);
+        state.store_event(calendar_id, CalendarEvent::entry_added(entry));
+        assert!(state.cached_feed(calendar_id, 1).is_none());
+    }
+
+    #[test]
+    fn test_feed_rendered_across_an_event_is_not_cached() {
+        let state = AppState::default();
+        let calendar_id = Uuid::new_v4();
+
+        // The render starts, then an entry changes before it is cached
+        let generation = state.feed_generation(calendar_id);
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let entry = CalendarEntry::all_day(calendar_id, "Standup", date);
+        state.store_event(calendar_id, CalendarEvent::entry_added(entry));
+        state.cache_feed(calendar_id, generation, 1, "stale".to_string());
+        assert!(state.cached_feed(calendar_id, 1).is_none());
+
+        // The next render starts after the change and is cached
+        let generation = state.feed_generation(calendar_id);
+        state.cache_feed(calendar_id, generation, 1, "fresh".to_string());
+        assert_eq!(state.cached_feed(calendar_id, 1).as_deref(), Some("fresh"));
+    }
+
+    #[tokio::test]
+    async fn test_event_listener_is_live_once_ensured() {
+        let state = AppState::default();
+        let calendar_id = Uuid::new_v4();
+
+        state.ensure_event_listener(calendar_id).await;
+        let generation = state.feed_generation(calendar_id);
+        state.cache_feed(calendar_id, generation, 1, "BEGIN:VCALENDAR".to_string());
+
+        // Published right away, before the listener task has had a chance to run
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let entry = CalendarEntry::all_day(calendar_id, "Standup", date);
+        state
+            .cache_pubsub
+            .publish(calendar_id, &CalendarEvent::entry_added(entry))
+            .await
+            .unwrap();
+
+        for _ in 0..100 {
+            if state.cached_feed(calendar_id, 1).is_none() {
+                break;
+            }
+            tokio::task::yield_now().await;
+        }
+        assert!(state.cached_feed(calendar_id, 1).is_none());
+        assert_eq!(state.get_events_since(calendar_id, 0).len(), 1);
+    }
+}
@@ -6,12 +6,15 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
 use calendsync_core::cache::{
     calendar_entries_pattern, calendar_key, deserialize_calendar, serialize_calendar, Cache,
 };
-use calendsync_core::calendar::Calendar;
+use calendsync_core::calendar::{
+    Calendar, CalendarSubscription, DuplicateOptions, DuplicateSummary,
+};
 use calendsync_core::storage::{CalendarRepository, Result};
 
 /// Cached calendar repository decorator.
@@ -56,6 +59,14 @@ where
             ttl,
         }
     }
+
+    /// Drops a calendar from the cache; it is repopulated on the next read.
+    async fn invalidate_calendar(&self, id: Uuid) {
+        let cache_key = calendar_key(id);
+        if let Err(err) = self.cache.delete(&cache_key).await {
+            tracing::warn!(calendar_id = %id, error = %err, "Failed to invalidate calendar cache");
+        }
+    }
 }
 
 #[async_trait]
@@ -118,28 +129,50 @@ where
         self.repository.update_calendar(calendar).await?;
 
         // 2. Invalidate cache (will be repopulated on next read)
-        let cache_key = calendar_key(calendar.id);
-        if let Err(err) = self.cache.delete(&cache_key).await {
-            tracing::warn!(
-                calendar_id = %calendar.id,
-                error = %err,
-                "Failed to invalidate calendar cache"
-            );
-        }
+        self.invalidate_calendar(calendar.id).await;
 
         tracing::debug!(calendar_id = %calendar.id, name = %calendar.name, "Calendar updated");
         Ok(())
     }
 
+    async fn update_calendar_if_version(
+        &self,
+        calendar: &Calendar,
+        expected_version: u64,
+    ) -> Result<()> {
+        // 1. Persist to storage, unless another writer got there first
+        self.repository
+            .update_calendar_if_version(calendar, expected_version)
+            .await?;
+
+        // 2. Invalidate cache (will be repopulated on next read)
+        self.invalidate_calendar(calendar.id).await;
+
+        tracing::debug!(calendar_id = %calendar.id, name = %calendar.name, "Calendar updated");
+        Ok(())
+    }
+
+    async fn record_subscription_refresh(
+        &self,
+        id: Uuid,
+        subscription: &CalendarSubscription,
+        expected_version: u64,
+    ) -> Result<()> {
+        self.repository
+            .record_subscription_refresh(id, subscription, expected_version)
+            .await?;
+
+        // The cached copy carries the old status under the same version
+        self.invalidate_calendar(id).await;
+        Ok(())
+    }
+
     async fn delete_calendar(&self, id: Uuid) -> Result<()> {
         // 1. Persist deletion to storage
         self.repository.delete_calendar(id).await?;
 
         // 2. Invalidate calendar cache
-        let cache_key = calendar_key(id);
-        if let Err(err) = self.cache.delete(&cache_key).await {
-            tracing::warn!(calendar_id = %id, error = %err, "Failed to invalidate calendar cache");
-        }
+        self.invalidate_calendar(id).await;
 
         // 3. Invalidate all cached entries for this calendar
         // This uses the calendar metadata key deletion behavior which cleans up
@@ -156,6 +189,77 @@ where
         tracing::debug!(calendar_id = %id, "Calendar deleted");
         Ok(())
     }
+
+    async fn restore_calendar(&self, id: Uuid) -> Result<Calendar> {
+        // 1. Persist to storage
+        let calendar = self.repository.restore_calendar(id).await?;
+
+        // 2. Invalidate entry listings cached while the calendar was in the trash
+        let pattern = calendar_entries_pattern(id);
+        if let Err(err) = self.cache.delete_pattern(&pattern).await {
+            tracing::warn!(
+                calendar_id = %id,
+                error = %err,
+                "Failed to invalidate calendar entries cache"
+            );
+        }
+
+        tracing::debug!(calendar_id = %id, name = %calendar.name, "Calendar restored");
+        Ok(calendar)
+    }
+
+    async fn purge_calendars(&self, deleted_before: DateTime<Utc>) -> Result<usize> {
+        // Trashed calendars were dropped from the cache when they were deleted.
+        self.repository.purge_calendars(deleted_before).await
+    }
+
+    async fn get_subscribed_calendars(&self) -> Result<Vec<Calendar>> {
+        // Refreshes run rarely and need every subscribed calendar, so this
+        // reads through to storage.
+        self.repository.get_subscribed_calendars().await
+    }
+
+    async fn list_calendars(&self) -> Result<Vec<Calendar>> {
+        // Only backups list every calendar, so this reads through to storage.
+        self.repository.list_calendars().await
+    }
+
+    async fn duplicate_calendar(
+        &self,
+        source_id: Uuid,
+        copy: &Calendar,
+        options: &DuplicateOptions,
+    ) -> Result<DuplicateSummary> {
+        // 1. Persist to storage. The copy is a new calendar, so no cached
+        // entry listings can be stale.
+        let summary = self
+            .repository
+            .duplicate_calendar(source_id, copy, options)
+            .await?;
+
+        // 2. Populate cache immediately, as for a created calendar
+        if let Ok(bytes) = serialize_calendar(copy) {
+            if let Err(err) = self
+                .cache
+                .set(&calendar_key(copy.id), &bytes, Some(self.ttl))
+                .await
+            {
+                tracing::warn!(
+                    calendar_id = %copy.id,
+                    error = %err,
+                    "Failed to cache duplicated calendar"
+                );
+            }
+        }
+
+        tracing::debug!(
+            source_id = %source_id,
+            calendar_id = %copy.id,
+            entries = summary.entries,
+            "Calendar duplicated"
+        );
+        Ok(summary)
+    }
 }
 
 #[cfg(test)]
@@ -170,6 +274,7 @@ mod tests {
     // Mock repository that tracks calls
     struct MockCalendarRepository {
         calendars: RwLock<HashMap<Uuid, Calendar>>,
+        trashed: RwLock<HashMap<Uuid, (Calendar, DateTime<Utc>)>>,
         get_calls: AtomicUsize,
     }
 
@@ -177,6 +282,7 @@ mod tests {
         fn new() -> Self {
             Self {
                 calendars: RwLock::new(HashMap::new()),
+                trashed: RwLock::new(HashMap::new()),
                 get_calls: AtomicUsize::new(0),
             }
         }
@@ -209,10 +315,91 @@ mod tests {
             Ok(())
         }
 
+        async fn update_calendar_if_version(
+            &self,
+            calendar: &Calendar,
+            expected_version: u64,
+        ) -> Result<()> {
+            let mut calendars = self.calendars.write().await;
+            if calendars.get(&calendar.id).map(|c| c.version) != Some(expected_version) {
+                return Err(calendsync_core::storage::RepositoryError::VersionMismatch {
+                    entity_type: "Calendar",
+                    id: calendar.id.to_string(),
+                    expected: expected_version,
+                });
+            }
+            let next = Calendar {
+                version: expected_version + 1,
+                ..calendar.clone()
+            };
+            calendars.insert(calendar.id, next);
+            Ok(())
+        }
+
+        async fn record_subscription_refresh(
+            &self,
+            id: Uuid,
+            subscription: &CalendarSubscription,
+            _expected_version: u64,
+        ) -> Result<()> {
+            if let Some(calendar) = self.calendars.write().await.get_mut(&id) {
+                calendar.subscription = Some(subscription.clone());
+            }
+            Ok(())
+        }
+
         async fn delete_calendar(&self, id: Uuid) -> Result<()> {
-            self.calendars.write().await.remove(&id);
+            if let Some(calendar) = self.calendars.write().await.remove(&id) {
+                self.trashed
+                    .write()
+                    .await
+                    .insert(id, (calendar, Utc::now()));
+            }
             Ok(())
         }
+
+        async fn restore_calendar(&self, id: Uuid) -> Result<Calendar> {
+            let (calendar, _) = self.trashed.write().await.remove(&id).ok_or_else(|| {
+                calendsync_core::storage::RepositoryError::NotFound {
+                    entity_type: "TrashedCalendar",
+                    id: id.to_string(),
+                }
+            })?;
+            self.insert(calendar.clone()).await;
+            Ok(calendar)
+        }
+
+        async fn purge_calendars(&self, deleted_before: DateTime<Utc>) -> Result<usize> {
+            let mut trashed = self.trashed.write().await;
+            let before = trashed.len();
+            trashed.retain(|_, (_, deleted_at)| *deleted_at >= deleted_before);
+            Ok(before - trashed.len())
+        }
+
+        async fn get_subscribed_calendars(&self) -> Result<Vec<Calendar>> {
+            Ok(self
+                .calendars
+                .read()
+                .await
+                .values()
+                .filter(|c| c.subscription.is_some())
+                .cloned()
+                .collect())
+        }
+
+        async fn list_calendars(&self) -> Result<Vec<Calendar>> {
+            Ok(self.calendars.read().await.values().cloned().collect())
+        }
+
+        async fn duplicate_calendar(
+            &self,
+            _source_id: Uuid,
+            copy: &Calendar,
+            _options: &DuplicateOptions,
+        ) -> Result<DuplicateSummary> {
+            self.insert(copy.clone()).await;
+            Ok(DuplicateSummary::default())
+        }
     }
 
     // Mock cache
@@ -377,4 +564,34 @@ mod tests {
         // Cache should be invalidated
         assert!(!cache.store.read().await.contains_key(&cache_key));
     }
+
+    #[tokio::test]
+    async fn test_restore_calendar_invalidates_entries() {
+        let calendar = create_test_calendar();
+
+        let repo = Arc::new(MockCalendarRepository::new());
+        repo.insert(calendar.clone()).await;
+
+        let cache = Arc::new(MockCache::new());
+
+        let cached =
+            CachedCalendarRepository::new(repo.clone(), cache.clone(), Duration::from_secs(300));
+
+        cached.delete_calendar(calendar.id).await.unwrap();
+        assert!(cached.get_calendar(calendar.id).await.unwrap().is_none());
+
+        // An (empty) listing cached while the calendar was in the trash
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 6, 15).unwrap();
+        let entries_cache_key =
+            calendsync_core::cache::calendar_entries_key(calendar.id, date, date);
+        cache.set(&entries_cache_key, b"[]", None).await.unwrap();
+
+        let restored = cached.restore_calendar(calendar.id).await.unwrap();
+        assert_eq!(restored, calendar);
+        assert!(!cache.store.read().await.contains_key(&entries_cache_key));
+        assert_eq!(
+            cached.get_calendar(calendar.id).await.unwrap(),
+            Some(calendar)
+        );
+    }
 }
@@ -3,18 +3,35 @@
 //! Wraps an `EntryRepository` implementation with cache-aside pattern and
 //! event publishing for real-time updates.
 
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::sync::Arc;
 use std::time::Duration;
 
 use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
 use uuid::Uuid;
 
 use calendsync_core::cache::{
     calendar_entries_key, calendar_entries_pattern, deserialize_entries, deserialize_entry,
     entry_key, serialize_entries, serialize_entry, Cache, CachePubSub,
 };
-use calendsync_core::calendar::{CalendarEntry, CalendarEvent};
-use calendsync_core::storage::{DateRange, EntryRepository, Result};
+use calendsync_core::calendar::{
+    next_revision_number, CalendarEntry, CalendarEvent, EntryRevision, EntryWrite,
+    OccurrenceOverride, RevisionAction, SearchQuery, Tag, TrashedEntry,
+};
+use calendsync_core::storage::{DateRange, EntryRepository, RepositoryError, Result};
+
+/// How many times a revision is appended before giving up, when concurrent
+/// writes to the same entry keep taking its number.
+const REVISION_APPEND_ATTEMPTS: usize = 5;
+
+/// What a batch changed in one calendar, published as a single event.
+#[derive(Default)]
+struct BatchChanges {
+    added: Vec<CalendarEntry>,
+    updated: Vec<CalendarEntry>,
+    deleted: Vec<(Uuid, NaiveDate)>,
+}
 
 /// Cached entry repository decorator.
 ///
@@ -61,6 +78,223 @@ where
             ttl,
         }
     }
+
+    /// Invalidates every cached date range of a calendar's entries.
+    async fn invalidate_calendar_entries(&self, calendar_id: Uuid) {
+        let pattern = calendar_entries_pattern(calendar_id);
+        if let Err(err) = self.cache.delete_pattern(&pattern).await {
+            tracing::warn!(
+                %calendar_id,
+                error = %err,
+                "Failed to invalidate calendar entries cache"
+            );
+        }
+    }
+
+    /// Appends a revision of an entry after a write.
+    ///
+    /// `before` is the entry as it was, for updates. A recurring series'
+    /// revision also holds its overrides, compared against the previous
+    /// revision's to tell whether they changed. The append only succeeds
+    /// while no other revision has the same number, so when a concurrent
+    /// write to the entry takes it the history is read again and the append
+    /// retried. The write has already committed, so a failure here is only
+    /// logged.
+    async fn record_revision(
+        &self,
+        action: RevisionAction,
+        snapshot: &CalendarEntry,
+        before: Option<&CalendarEntry>,
+        acting_user: Option<Uuid>,
+    ) {
+        let result = async {
+            let mut attempt = 1;
+            loop {
+                let history = self.repository.get_entry_revisions(snapshot.id).await?;
+                let mut revision = EntryRevision::new(
+                    next_revision_number(&history),
+                    action,
+                    snapshot.clone(),
+                    acting_user,
+                    Utc::now(),
+                );
+                if let Some(before) = before {
+                    revision = revision.with_changes_from(before);
+                }
+                if snapshot.recurrence.is_some() {
+                    let overrides = self
+                        .repository
+                        .get_occurrence_overrides(snapshot.id)
+                        .await?;
+                    let previous = history
+                        .last()
+                        .and_then(|last| last.overrides.clone())
+                        .unwrap_or_default();
+                    let previous = before.map(|_| previous.as_slice());
+                    revision = revision.with_overrides(overrides, previous);
+                }
+                match self.repository.append_entry_revision(&revision).await {
+                    Err(RepositoryError::AlreadyExists { .. })
+                        if attempt < REVISION_APPEND_ATTEMPTS =>
+                    {
+                        attempt += 1;
+                    }
+                    result => return result,
+                }
+            }
+        }
+        .await;
+
+        if let Err(err) = result {
+            tracing::warn!(
+                entry_id = %snapshot.id,
+                action = %action,
+                error = %err,
+                "Failed to record entry revision"
+            );
+        }
+    }
+
+    /// Moves an entry to the trash, provided it is still at `expected_version`
+    /// when one is given, then records the revision, invalidates caches and
+    /// publishes the event.
+    async fn trash_entry(
+        &self,
+        id: Uuid,
+        expected_version: Option<u64>,
+        acting_user: Option<Uuid>,
+    ) -> Result<()> {
+        // Get entry first for event data (need calendar_id and date)
+        let entry = self.repository.get_entry(id).await?;
+
+        // 1. Persist deletion to storage and record the revision
+        match expected_version {
+            Some(expected) => {
+                self.repository
+                    .delete_entry_if_version(id, expected)
+                    .await?
+            }
+            None => self.repository.delete_entry(id).await?,
+        }
+        if let Some(ref e) = entry {
+            self.record_revision(RevisionAction::Deleted, e, None, acting_user)
+                .await;
+        }
+
+        // 2. Invalidate specific entry cache
+        let entry_cache_key = entry_key(id);
+        if let Err(err) = self.cache.delete(&entry_cache_key).await {
+            tracing::warn!(entry_id = %id, error = %err, "Failed to invalidate entry cache");
+        }
+
+        // 3. Invalidate calendar entries cache and publish event (if we had entry data)
+        if let Some(e) = entry {
+            let pattern = calendar_entries_pattern(e.calendar_id);
+            if let Err(err) = self.cache.delete_pattern(&pattern).await {
+                tracing::warn!(
+                    calendar_id = %e.calendar_id,
+                    error = %err,
+                    "Failed to invalidate calendar entries cache"
+                );
+            }
+
+            // 4. Publish event for cross-instance propagation
+            let event = CalendarEvent::entry_deleted(e.id, e.start_date);
+            if let Err(err) = self.pubsub.publish(e.calendar_id, &event).await {
+                tracing::warn!(
+                    calendar_id = %e.calendar_id,
+                    entry_id = %id,
+                    error = %err,
+                    "Failed to publish entry_deleted event"
+                );
+            }
+
+            tracing::debug!(entry_id = %id, calendar_id = %e.calendar_id, "Entry deleted");
+        } else {
+            tracing::debug!(entry_id = %id, "Entry deleted (no calendar context for event)");
+        }
+
+        Ok(())
+    }
+
+    /// Records the revision, invalidates caches and publishes the event for an
+    /// entry that was just stored at `version`.
+    async fn after_update(
+        &self,
+        entry: &CalendarEntry,
+        version: u64,
+        before: Option<&CalendarEntry>,
+        acting_user: Option<Uuid>,
+    ) {
+        let entry = &CalendarEntry {
+            version,
+            ..entry.clone()
+        };
+
+        // 2. Record the revision
+        self.record_revision(RevisionAction::Updated, entry, before, acting_user)
+            .await;
+
+        // 3. Invalidate specific entry cache
+        let entry_cache_key = entry_key(entry.id);
+        if let Err(err) = self.cache.delete(&entry_cache_key).await {
+            tracing::warn!(
+                entry_id = %entry.id,
+                error = %err,
+                "Failed to invalidate entry cache"
+            );
+        }
+
+        // 4. Invalidate calendar entries cache (all date ranges)
+        let pattern = calendar_entries_pattern(entry.calendar_id);
+        if let Err(err) = self.cache.delete_pattern(&pattern).await {
+            tracing::warn!(
+                calendar_id = %entry.calendar_id,
+                error = %err,
+                "Failed to invalidate calendar entries cache"
+            );
+        }
+
+        // 5. Publish event for cross-instance propagation
+        let event = CalendarEvent::entry_updated(entry.clone());
+        if let Err(err) = self.pubsub.publish(entry.calendar_id, &event).await {
+            tracing::warn!(
+                calendar_id = %entry.calendar_id,
+                entry_id = %entry.id,
+                error = %err,
+                "Failed to publish entry_updated event"
+            );
+        }
+
+        tracing::debug!(entry_id = %entry.id, calendar_id = %entry.calendar_id, "Entry updated");
+    }
+
+    /// Publishes an event scoped to the single occurrence an override changed.
+    async fn publish_occurrence(&self, series: &CalendarEntry, occurrence: &OccurrenceOverride) {
+        let event = match &occurrence.replacement {
+            Some(replacement) => {
+                let mut updated = replacement.clone();
+                updated.recurrence = series.recurrence.clone();
+                CalendarEvent::occurrence_updated(updated)
+            }
+            None => CalendarEvent::occurrence_deleted(series.id, occurrence.original_date),
+        };
+        if let Err(err) = self.pubsub.publish(series.calendar_id, &event).await {
+            tracing::warn!(
+                calendar_id = %series.calendar_id,
+                entry_id = %series.id,
+                error = %err,
+                "Failed to publish occurrence event"
+            );
+        }
+
+        tracing::debug!(
+            entry_id = %series.id,
+            original_date = %occurrence.original_date,
+            cancelled = occurrence.is_cancelled(),
+            "Occurrence override saved"
+        );
+    }
 }
 
 #[async_trait]
@@ -144,9 +378,58 @@ where
         Ok(entries)
     }
 
+    async fn get_entries_by_tags(
+        &self,
+        calendar_id: Uuid,
+        tags: &BTreeSet<Tag>,
+        date_range: DateRange,
+    ) -> Result<Vec<CalendarEntry>> {
+        // Tag-filtered lists are not cached; caching them would multiply the
+        // keys to invalidate on every write.
+        self.repository
+            .get_entries_by_tags(calendar_id, tags, date_range)
+            .await
+    }
+
+    async fn get_stored_entries_by_calendar(
+        &self,
+        calendar_id: Uuid,
+    ) -> Result<Vec<CalendarEntry>> {
+        // Only read whole for exports, so not worth a key to invalidate.
+        self.repository
+            .get_stored_entries_by_calendar(calendar_id)
+            .await
+    }
+
+    async fn count_entries_by_calendar(&self, calendar_id: Uuid) -> Result<usize> {
+        self.repository.count_entries_by_calendar(calendar_id).await
+    }
+
+    async fn search_entries(
+        &self,
+        calendar_ids: &[Uuid],
+        query: &SearchQuery,
+        limit: usize,
+    ) -> Result<Vec<CalendarEntry>> {
+        // Search results are not cached, for the same reason as tag filters.
+        self.repository
+            .search_entries(calendar_ids, query, limit)
+            .await
+    }
+
     async fn create_entry(&self, entry: &CalendarEntry) -> Result<()> {
-        // 1. Persist to storage
+        self.create_entry_as(entry, None).await
+    }
+
+    async fn create_entry_as(
+        &self,
+        entry: &CalendarEntry,
+        acting_user: Option<Uuid>,
+    ) -> Result<()> {
+        // 1. Persist to storage and record the revision
         self.repository.create_entry(entry).await?;
+        self.record_revision(RevisionAction::Created, entry, None, acting_user)
+            .await;
 
         // 2. Invalidate calendar entries cache (all date ranges for this calendar)
         let pattern = calendar_entries_pattern(entry.calendar_id);
@@ -174,137 +457,719 @@ where
     }
 
     async fn update_entry(&self, entry: &CalendarEntry) -> Result<()> {
+        self.update_entry_as(entry, None).await
+    }
+
+    async fn update_entry_as(
+        &self,
+        entry: &CalendarEntry,
+        acting_user: Option<Uuid>,
+    ) -> Result<()> {
+        // Get the stored entry first to diff the revision against
+        let before = self.repository.get_entry(entry.id).await?;
+
         // 1. Persist to storage
         self.repository.update_entry(entry).await?;
 
-        // 2. Invalidate specific entry cache
-        let entry_cache_key = entry_key(entry.id);
-        if let Err(err) = self.cache.delete(&entry_cache_key).await {
+        let version = before.as_ref().map_or(entry.version, |b| b.version + 1);
+        self.after_update(entry, version, before.as_ref(), acting_user)
+            .await;
+        Ok(())
+    }
+
+    async fn update_entry_if_version(
+        &self,
+        entry: &CalendarEntry,
+        expected_version: u64,
+    ) -> Result<()> {
+        self.update_entry_if_version_as(entry, expected_version, None)
+            .await
+    }
+
+    async fn update_entry_if_version_as(
+        &self,
+        entry: &CalendarEntry,
+        expected_version: u64,
+        acting_user: Option<Uuid>,
+    ) -> Result<()> {
+        let before = self.repository.get_entry(entry.id).await?;
+
+        // 1. Persist to storage, unless another writer got there first
+        self.repository
+            .update_entry_if_version(entry, expected_version)
+            .await?;
+
+        self.after_update(entry, expected_version + 1, before.as_ref(), acting_user)
+            .await;
+        Ok(())
+    }
+
+    async fn move_entry(
+        &self,
+        id: Uuid,
+        calendar_id: Uuid,
+        expected_version: u64,
+    ) -> Result<CalendarEntry> {
+        self.move_entry_as(id, calendar_id, expected_version, None)
+            .await
+    }
+
+    async fn move_entry_as(
+        &self,
+        id: Uuid,
+        calendar_id: Uuid,
+        expected_version: u64,
+        acting_user: Option<Uuid>,
+    ) -> Result<CalendarEntry> {
+        // Get the stored entry first for its source calendar
+        let before =
+            self.repository
+                .get_entry(id)
+                .await?
+                .ok_or_else(|| RepositoryError::NotFound {
+                    entity_type: "CalendarEntry",
+                    id: id.to_string(),
+                })?;
+
+        // 1. Persist to storage and record the revision
+        let moved = self
+            .repository
+            .move_entry(id, calendar_id, expected_version)
+            .await?;
+        self.record_revision(RevisionAction::Updated, &moved, Some(&before), acting_user)
+            .await;
+
+        // 2. Invalidate the entry and both calendars' entry caches
+        if let Err(err) = self.cache.delete(&entry_key(id)).await {
+            tracing::warn!(entry_id = %id, error = %err, "Failed to invalidate entry cache");
+        }
+        self.invalidate_calendar_entries(before.calendar_id).await;
+        self.invalidate_calendar_entries(calendar_id).await;
+
+        // 3. Publish a deletion to the source calendar and an addition to the
+        //    target, so each calendar's clients see only their own change
+        let removed = CalendarEvent::entry_deleted(id, before.start_date);
+        if let Err(err) = self.pubsub.publish(before.calendar_id, &removed).await {
             tracing::warn!(
-                entry_id = %entry.id,
+                calendar_id = %before.calendar_id,
+                entry_id = %id,
                 error = %err,
-                "Failed to invalidate entry cache"
+                "Failed to publish entry_deleted event"
             );
         }
-
-        // 3. Invalidate calendar entries cache (all date ranges)
-        let pattern = calendar_entries_pattern(entry.calendar_id);
-        if let Err(err) = self.cache.delete_pattern(&pattern).await {
+        let added = CalendarEvent::entry_added(moved.clone());
+        if let Err(err) = self.pubsub.publish(calendar_id, &added).await {
             tracing::warn!(
-                calendar_id = %entry.calendar_id,
+                calendar_id = %calendar_id,
+                entry_id = %id,
                 error = %err,
-                "Failed to invalidate calendar entries cache"
+                "Failed to publish entry_added event"
             );
         }
 
-        // 4. Publish event for cross-instance propagation
-        let event = CalendarEvent::entry_updated(entry.clone());
+        tracing::debug!(
+            entry_id = %id,
+            from_calendar_id = %before.calendar_id,
+            to_calendar_id = %calendar_id,
+            "Entry moved"
+        );
+        Ok(moved)
+    }
+
+    async fn delete_entry(&self, id: Uuid) -> Result<()> {
+        self.delete_entry_as(id, None).await
+    }
+
+    async fn delete_entry_as(&self, id: Uuid, acting_user: Option<Uuid>) -> Result<()> {
+        self.trash_entry(id, None, acting_user).await
+    }
+
+    async fn delete_entry_if_version(&self, id: Uuid, expected_version: u64) -> Result<()> {
+        self.delete_entry_if_version_as(id, expected_version, None)
+            .await
+    }
+
+    async fn delete_entry_if_version_as(
+        &self,
+        id: Uuid,
+        expected_version: u64,
+        acting_user: Option<Uuid>,
+    ) -> Result<()> {
+        self.trash_entry(id, Some(expected_version), acting_user)
+            .await
+    }
+
+    async fn apply_entry_batch(&self, writes: &[EntryWrite]) -> Result<()> {
+        self.apply_entry_batch_as(writes, None).await
+    }
+
+    async fn apply_entry_batch_as(
+        &self,
+        writes: &[EntryWrite],
+        acting_user: Option<Uuid>,
+    ) -> Result<()> {
+        // Get the stored entries first for revisions and event data
+        let mut before = HashMap::new();
+        for write in writes {
+            if matches!(write, EntryWrite::Create(_)) {
+                continue;
+            }
+            if let Some(entry) = self.repository.get_entry(write.entry_id()).await? {
+                before.insert(entry.id, entry);
+            }
+        }
+
+        // 1. Persist the whole batch to storage
+        self.repository.apply_entry_batch(writes).await?;
+
+        // 2. Record the revisions and group the changes by calendar
+        let mut changes: BTreeMap<Uuid, BatchChanges> = BTreeMap::new();
+        for write in writes {
+            match write {
+                EntryWrite::Create(entry) => {
+                    self.record_revision(RevisionAction::Created, entry, None, acting_user)
+                        .await;
+                    changes
+                        .entry(entry.calendar_id)
+                        .or_default()
+                        .added
+                        .push(entry.clone());
+                }
+                EntryWrite::Update {
+                    entry,
+                    expected_version,
+                } => {
+                    let stored = CalendarEntry {
+                        version: expected_version + 1,
+                        ..entry.clone()
+                    };
+                    let previous = before.get(&entry.id);
+                    self.record_revision(RevisionAction::Updated, &stored, previous, acting_user)
+                        .await;
+                    // An update that changed calendars leaves the old one stale too
+                    if let Some(previous) = previous {
+                        changes.entry(previous.calendar_id).or_default();
+                    }
+                    changes
+                        .entry(entry.calendar_id)
+                        .or_default()
+                        .updated
+                        .push(stored);
+                }
+                EntryWrite::Delete(id) => {
+                    if let Some(previous) = before.get(id) {
+                        self.record_revision(RevisionAction::Deleted, previous, None, acting_user)
+                            .await;
+                        changes
+                            .entry(previous.calendar_id)
+                            .or_default()
+                            .deleted
+                            .push((*id, previous.start_date));
+                    }
+                }
+            }
+        }
+
+        // 3. Invalidate the cached entries, then each calendar's entries once
+        for id in before.keys() {
+            if let Err(err) = self.cache.delete(&entry_key(*id)).await {
+                tracing::warn!(entry_id = %id, error = %err, "Failed to invalidate entry cache");
+            }
+        }
+        for calendar_id in changes.keys() {
+            self.invalidate_calendar_entries(*calendar_id).await;
+        }
+
+        // 4. Publish one event per calendar instead of one per entry
+        for (calendar_id, batch) in changes {
+            if batch.added.is_empty() && batch.updated.is_empty() && batch.deleted.is_empty() {
+                continue;
+            }
+            let event = CalendarEvent::entry_batch(batch.added, batch.updated, batch.deleted);
+            if let Err(err) = self.pubsub.publish(calendar_id, &event).await {
+                tracing::warn!(
+                    calendar_id = %calendar_id,
+                    error = %err,
+                    "Failed to publish entry_batch event"
+                );
+            }
+        }
+
+        tracing::debug!(writes = writes.len(), "Entry batch applied");
+        Ok(())
+    }
+
+    async fn get_trashed_entries(&self, calendar_ids: &[Uuid]) -> Result<Vec<TrashedEntry>> {
+        // Only read when listing the trash; not cached.
+        self.repository.get_trashed_entries(calendar_ids).await
+    }
+
+    async fn restore_entry(&self, id: Uuid) -> Result<CalendarEntry> {
+        self.restore_entry_as(id, None).await
+    }
+
+    async fn restore_entry_as(&self, id: Uuid, acting_user: Option<Uuid>) -> Result<CalendarEntry> {
+        // 1. Persist to storage and record the revision
+        let entry = self.repository.restore_entry(id).await?;
+        self.record_revision(RevisionAction::Restored, &entry, None, acting_user)
+            .await;
+
+        // 2. Invalidate calendar entries cache (all date ranges)
+        self.invalidate_calendar_entries(entry.calendar_id).await;
+
+        // 3. Publish event for cross-instance propagation
+        let event = CalendarEvent::entry_restored(entry.clone());
         if let Err(err) = self.pubsub.publish(entry.calendar_id, &event).await {
             tracing::warn!(
                 calendar_id = %entry.calendar_id,
-                entry_id = %entry.id,
+                entry_id = %id,
                 error = %err,
-                "Failed to publish entry_updated event"
+                "Failed to publish entry_restored event"
             );
         }
 
-        tracing::debug!(entry_id = %entry.id, calendar_id = %entry.calendar_id, "Entry updated");
+        tracing::debug!(entry_id = %id, calendar_id = %entry.calendar_id, "Entry restored");
+        Ok(entry)
+    }
+
+    async fn purge_entries(&self, deleted_before: DateTime<Utc>) -> Result<usize> {
+        // Trashed entries were dropped from the cache when they were deleted.
+        self.repository.purge_entries(deleted_before).await
+    }
+
+    async fn append_entry_revision(&self, revision: &EntryRevision) -> Result<()> {
+        self.repository.append_entry_revision(revision).await
+    }
+
+    async fn get_entry_revisions(&self, entry_id: Uuid) -> Result<Vec<EntryRevision>> {
+        // History is only read on request; not cached.
+        self.repository.get_entry_revisions(entry_id).await
+    }
+
+    async fn get_occurrence_overrides(&self, series_id: Uuid) -> Result<Vec<OccurrenceOverride>> {
+        // Overrides are only read while editing a series; cached ranges already include them.
+        self.repository.get_occurrence_overrides(series_id).await
+    }
+
+    async fn upsert_occurrence_override(&self, occurrence: &OccurrenceOverride) -> Result<()> {
+        // Get the series first for event data (need calendar_id and rule)
+        let series = self.repository.get_entry(occurrence.series_id).await?;
+
+        // 1. Persist to storage
+        self.repository
+            .upsert_occurrence_override(occurrence)
+            .await?;
+
+        let Some(series) = series else {
+            return Ok(());
+        };
+
+        // 2. Invalidate calendar entries cache (expanded ranges include overrides)
+        self.invalidate_calendar_entries(series.calendar_id).await;
+
+        // 3. Publish an event scoped to the single occurrence
+        self.publish_occurrence(&series, occurrence).await;
         Ok(())
     }
 
-    async fn delete_entry(&self, id: Uuid) -> Result<()> {
-        // Get entry first for event data (need calendar_id and date)
-        let entry = self.repository.get_entry(id).await?;
+    async fn upsert_occurrence_override_if_version(
+        &self,
+        occurrence: &OccurrenceOverride,
+        expected_version: u64,
+    ) -> Result<()> {
+        self.upsert_occurrence_override_if_version_as(occurrence, expected_version, None)
+            .await
+    }
 
-        // 1. Persist deletion to storage
-        self.repository.delete_entry(id).await?;
+    async fn upsert_occurrence_override_if_version_as(
+        &self,
+        occurrence: &OccurrenceOverride,
+        expected_version: u64,
+        acting_user: Option<Uuid>,
+    ) -> Result<()> {
+        let series_id = occurrence.series_id;
+        let before = self.repository.get_entry(series_id).await?.ok_or_else(|| {
+            RepositoryError::NotFound {
+                entity_type: "CalendarEntry",
+                id: series_id.to_string(),
+            }
+        })?;
+
+        // 1. Persist to storage, bumping the series version first so the
+        //    override is only written while the series is still as it was read
+        let series = CalendarEntry {
+            updated_at: Utc::now(),
+            ..before.clone()
+        };
+        self.repository
+            .update_entry_if_version(&series, expected_version)
+            .await?;
+        self.repository
+            .upsert_occurrence_override(occurrence)
+            .await?;
 
-        // 2. Invalidate specific entry cache
-        let entry_cache_key = entry_key(id);
-        if let Err(err) = self.cache.delete(&entry_cache_key).await {
-            tracing::warn!(entry_id = %id, error = %err, "Failed to invalidate entry cache");
+        // 2. Record the edit as a revision of the series
+        let series = CalendarEntry {
+            version: expected_version + 1,
+            ..series
+        };
+        self.record_revision(RevisionAction::Updated, &series, Some(&before), acting_user)
+            .await;
+
+        // 3. Invalidate the series and its calendar's entries
+        if let Err(err) = self.cache.delete(&entry_key(series_id)).await {
+            tracing::warn!(entry_id = %series_id, error = %err, "Failed to invalidate entry cache");
         }
+        self.invalidate_calendar_entries(series.calendar_id).await;
 
-        // 3. Invalidate calendar entries cache and publish event (if we had entry data)
-        if let Some(e) = entry {
-            let pattern = calendar_entries_pattern(e.calendar_id);
-            if let Err(err) = self.cache.delete_pattern(&pattern).await {
-                tracing::warn!(
-                    calendar_id = %e.calendar_id,
-                    error = %err,
-                    "Failed to invalidate calendar entries cache"
-                );
+        // 4. Publish an event scoped to the single occurrence
+        self.publish_occurrence(&series, occurrence).await;
+        Ok(())
+    }
+
+    async fn update_series_if_version(
+        &self,
+        entry: &CalendarEntry,
+        overrides: &[OccurrenceOverride],
+        expected_version: u64,
+    ) -> Result<()> {
+        self.update_series_if_version_as(entry, overrides, expected_version, None)
+            .await
+    }
+
+    async fn update_series_if_version_as(
+        &self,
+        entry: &CalendarEntry,
+        overrides: &[OccurrenceOverride],
+        expected_version: u64,
+        acting_user: Option<Uuid>,
+    ) -> Result<()> {
+        let before = self.repository.get_entry(entry.id).await?;
+
+        // 1. Persist the series and its overrides, unless another writer got there first
+        self.repository
+            .update_series_if_version(entry, overrides, expected_version)
+            .await?;
+
+        self.after_update(entry, expected_version + 1, before.as_ref(), acting_user)
+            .await;
+        Ok(())
+    }
+
+    async fn delete_occurrence_overrides(&self, series_id: Uuid, from: NaiveDate) -> Result<()> {
+        self.repository
+            .delete_occurrence_overrides(series_id, from)
+            .await?;
+
+        // No event here: callers pair this with a series update that publishes one.
+        if let Some(series) = self.repository.get_entry(series_id).await? {
+            self.invalidate_calendar_entries(series.calendar_id).await;
+        }
+
+        Ok(())
+    }
+
+    async fn get_entries_with_reminders(
+        &self,
+        date_range: DateRange,
+    ) -> Result<Vec<CalendarEntry>> {
+        // Polled by the reminder scheduler across all calendars; not cached.
+        self.repository.get_entries_with_reminders(date_range).await
+    }
+
+    async fn claim_reminder(&self, entry_id: Uuid, fire_at: DateTime<Utc>) -> Result<bool> {
+        self.repository.claim_reminder(entry_id, fire_at).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::sync::{broadcast, RwLock};
+
+    use calendsync_core::cache::Result as CacheResult;
+
+    // Mock repository that tracks calls
+    struct MockEntryRepository {
+        entries: RwLock<HashMap<Uuid, CalendarEntry>>,
+        trashed: RwLock<HashMap<Uuid, TrashedEntry>>,
+        overrides: RwLock<Vec<OccurrenceOverride>>,
+        revisions: RwLock<Vec<EntryRevision>>,
+        get_entry_calls: AtomicUsize,
+        get_entries_calls: AtomicUsize,
+    }
+
+    impl MockEntryRepository {
+        fn new() -> Self {
+            Self {
+                entries: RwLock::new(HashMap::new()),
+                trashed: RwLock::new(HashMap::new()),
+                overrides: RwLock::new(Vec::new()),
+                revisions: RwLock::new(Vec::new()),
+                get_entry_calls: AtomicUsize::new(0),
+                get_entries_calls: AtomicUsize::new(0),
             }
+        }
 
-            // 4. Publish event for cross-instance propagation
-            let event = CalendarEvent::entry_deleted(e.id, e.start_date);
-            if let Err(err) = self.pubsub.publish(e.calendar_id, &event).await {
-                tracing::warn!(
-                    calendar_id = %e.calendar_id,
-                    entry_id = %id,
-                    error = %err,
-                    "Failed to publish entry_deleted event"
-                );
+        async fn insert(&self, entry: CalendarEntry) {
+            self.entries.write().await.insert(entry.id, entry);
+        }
+    }
+
+    #[async_trait]
+    impl EntryRepository for MockEntryRepository {
+        async fn get_entry(&self, id: Uuid) -> Result<Option<CalendarEntry>> {
+            self.get_entry_calls.fetch_add(1, Ordering::SeqCst);
+            Ok(self.entries.read().await.get(&id).cloned())
+        }
+
+        async fn get_entries_by_calendar(
+            &self,
+            calendar_id: Uuid,
+            date_range: DateRange,
+        ) -> Result<Vec<CalendarEntry>> {
+            self.get_entries_calls.fetch_add(1, Ordering::SeqCst);
+            let entries = self.entries.read().await;
+            Ok(entries
+                .values()
+                .filter(|e| {
+                    e.calendar_id == calendar_id
+                        && e.start_date <= date_range.end
+                        && e.end_date >= date_range.start
+                })
+                .cloned()
+                .collect())
+        }
+
+        async fn get_entries_by_tags(
+            &self,
+            calendar_id: Uuid,
+            tags: &BTreeSet<Tag>,
+            date_range: DateRange,
+        ) -> Result<Vec<CalendarEntry>> {
+            let mut entries = self
+                .get_entries_by_calendar(calendar_id, date_range)
+                .await?;
+            entries.retain(|e| !e.tags.is_disjoint(tags));
+            Ok(entries)
+        }
+
+        async fn get_stored_entries_by_calendar(
+            &self,
+            calendar_id: Uuid,
+        ) -> Result<Vec<CalendarEntry>> {
+            let entries = self.entries.read().await;
+            Ok(entries
+                .values()
+                .filter(|e| e.calendar_id == calendar_id)
+                .cloned()
+                .collect())
+        }
+
+        async fn search_entries(
+            &self,
+            calendar_ids: &[Uuid],
+            query: &SearchQuery,
+            limit: usize,
+        ) -> Result<Vec<CalendarEntry>> {
+            let entries = self.entries.read().await;
+            let candidates = entries
+                .values()
+                .filter(|e| calendar_ids.contains(&e.calendar_id))
+                .cloned()
+                .collect();
+            Ok(calendsync_core::calendar::rank_entries(
+                query, candidates, limit,
+            ))
+        }
+
+        async fn create_entry(&self, entry: &CalendarEntry) -> Result<()> {
+            self.entries.write().await.insert(entry.id, entry.clone());
+            Ok(())
+        }
+
+        async fn update_entry(&self, entry: &CalendarEntry) -> Result<()> {
+            self.entries.write().await.insert(entry.id, entry.clone());
+            Ok(())
+        }
+
+        async fn update_entry_if_version(
+            &self,
+            entry: &CalendarEntry,
+            expected_version: u64,
+        ) -> Result<()> {
+            let mut entries = self.entries.write().await;
+            if entries.get(&entry.id).map(|e| e.version) != Some(expected_version) {
+                return Err(RepositoryError::VersionMismatch {
+                    entity_type: "CalendarEntry",
+                    id: entry.id.to_string(),
+                    expected: expected_version,
+                });
             }
+            let next = CalendarEntry {
+                version: expected_version + 1,
+                ..entry.clone()
+            };
+            entries.insert(entry.id, next);
+            Ok(())
+        }
+
+        async fn move_entry(
+            &self,
+            id: Uuid,
+            calendar_id: Uuid,
+            expected_version: u64,
+        ) -> Result<CalendarEntry> {
+            let mut entries = self.entries.write().await;
+            let entry = entries
+                .get_mut(&id)
+                .filter(|e| e.version == expected_version)
+                .ok_or(RepositoryError::VersionMismatch {
+                    entity_type: "CalendarEntry",
+                    id: id.to_string(),
+                    expected: expected_version,
+                })?;
+            entry.calendar_id = calendar_id;
+            entry.version += 1;
+            Ok(entry.clone())
+        }
+
+        async fn delete_entry(&self, id: Uuid) -> Result<()> {
+            if let Some(entry) = self.entries.write().await.remove(&id) {
+                let deleted_at = Utc::now();
+                let trashed = TrashedEntry { entry, deleted_at };
+                self.trashed.write().await.insert(id, trashed);
+            }
+            Ok(())
+        }
+
+        async fn delete_entry_if_version(&self, id: Uuid, expected_version: u64) -> Result<()> {
+            let mut entries = self.entries.write().await;
+            let entry = entries
+                .remove(&id)
+                .ok_or_else(|| RepositoryError::NotFound {
+                    entity_type: "CalendarEntry",
+                    id: id.to_string(),
+                })?;
+            if entry.version != expected_version {
+                entries.insert(id, entry);
+                return Err(RepositoryError::VersionMismatch {
+                    entity_type: "CalendarEntry",
+                    id: id.to_string(),
+                    expected: expected_version,
+                });
+            }
+            let deleted_at = Utc::now();
+            let trashed = TrashedEntry { entry, deleted_at };
+            self.trashed.write().await.insert(id, trashed);
+            Ok(())
+        }
 
-            tracing::debug!(entry_id = %id, calendar_id = %e.calendar_id, "Entry deleted");
-        } else {
-            tracing::debug!(entry_id = %id, "Entry deleted (no calendar context for event)");
+        async fn get_trashed_entries(&self, calendar_ids: &[Uuid]) -> Result<Vec<TrashedEntry>> {
+            Ok(self
+                .trashed
+                .read()
+                .await
+                .values()
+                .filter(|t| calendar_ids.contains(&t.entry.calendar_id))
+                .cloned()
+                .collect())
         }
 
-        Ok(())
-    }
-}
+        async fn restore_entry(&self, id: Uuid) -> Result<CalendarEntry> {
+            let trashed = self.trashed.write().await.remove(&id).ok_or_else(|| {
+                RepositoryError::NotFound {
+                    entity_type: "TrashedEntry",
+                    id: id.to_string(),
+                }
+            })?;
+            self.insert(trashed.entry.clone()).await;
+            Ok(trashed.entry)
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use chrono::NaiveDate;
-    use std::collections::HashMap;
-    use std::sync::atomic::{AtomicUsize, Ordering};
-    use tokio::sync::{broadcast, RwLock};
+        async fn purge_entries(&self, deleted_before: DateTime<Utc>) -> Result<usize> {
+            let mut trashed = self.trashed.write().await;
+            let before = trashed.len();
+            trashed.retain(|_, t| t.deleted_at >= deleted_before);
+            Ok(before - trashed.len())
+        }
 
-    use calendsync_core::cache::Result as CacheResult;
+        async fn append_entry_revision(&self, revision: &EntryRevision) -> Result<()> {
+            let mut revisions = self.revisions.write().await;
+            if revisions
+                .iter()
+                .any(|r| (r.entry_id, r.revision) == (revision.entry_id, revision.revision))
+            {
+                return Err(RepositoryError::AlreadyExists {
+                    entity_type: "EntryRevision",
+                    id: format!("{}@{}", revision.entry_id, revision.revision),
+                });
+            }
+            revisions.push(revision.clone());
+            Ok(())
+        }
 
-    // Mock repository that tracks calls
-    struct MockEntryRepository {
-        entries: RwLock<HashMap<Uuid, CalendarEntry>>,
-        get_entry_calls: AtomicUsize,
-        get_entries_calls: AtomicUsize,
-    }
+        async fn get_entry_revisions(&self, entry_id: Uuid) -> Result<Vec<EntryRevision>> {
+            let history = self
+                .revisions
+                .read()
+                .await
+                .iter()
+                .filter(|r| r.entry_id == entry_id)
+                .cloned()
+                .collect();
+            // Let concurrent writers read the same history before appending
+            tokio::task::yield_now().await;
+            Ok(history)
+        }
 
-    impl MockEntryRepository {
-        fn new() -> Self {
-            Self {
-                entries: RwLock::new(HashMap::new()),
-                get_entry_calls: AtomicUsize::new(0),
-                get_entries_calls: AtomicUsize::new(0),
-            }
+        async fn get_occurrence_overrides(
+            &self,
+            series_id: Uuid,
+        ) -> Result<Vec<OccurrenceOverride>> {
+            Ok(self
+                .overrides
+                .read()
+                .await
+                .iter()
+                .filter(|o| o.series_id == series_id)
+                .cloned()
+                .collect())
         }
 
-        async fn insert(&self, entry: CalendarEntry) {
-            self.entries.write().await.insert(entry.id, entry);
+        async fn upsert_occurrence_override(&self, occurrence: &OccurrenceOverride) -> Result<()> {
+            let mut overrides = self.overrides.write().await;
+            overrides.retain(|o| {
+                (o.series_id, o.original_date) != (occurrence.series_id, occurrence.original_date)
+            });
+            overrides.push(occurrence.clone());
+            Ok(())
         }
-    }
 
-    #[async_trait]
-    impl EntryRepository for MockEntryRepository {
-        async fn get_entry(&self, id: Uuid) -> Result<Option<CalendarEntry>> {
-            self.get_entry_calls.fetch_add(1, Ordering::SeqCst);
-            Ok(self.entries.read().await.get(&id).cloned())
+        async fn delete_occurrence_overrides(
+            &self,
+            series_id: Uuid,
+            from: NaiveDate,
+        ) -> Result<()> {
+            self.overrides
+                .write()
+                .await
+                .retain(|o| o.series_id != series_id || o.original_date < from);
+            Ok(())
         }
 
-        async fn get_entries_by_calendar(
+        async fn get_entries_with_reminders(
             &self,
-            calendar_id: Uuid,
             date_range: DateRange,
         ) -> Result<Vec<CalendarEntry>> {
-            self.get_entries_calls.fetch_add(1, Ordering::SeqCst);
             let entries = self.entries.read().await;
             Ok(entries
                 .values()
                 .filter(|e| {
-                    e.calendar_id == calendar_id
+                    e.has_reminders()
                         && e.start_date <= date_range.end
                         && e.end_date >= date_range.start
                 })
@@ -312,19 +1177,8 @@ mod tests {
                 .collect())
         }
 
-        async fn create_entry(&self, entry: &CalendarEntry) -> Result<()> {
-            self.entries.write().await.insert(entry.id, entry.clone());
-            Ok(())
-        }
-
-        async fn update_entry(&self, entry: &CalendarEntry) -> Result<()> {
-            self.entries.write().await.insert(entry.id, entry.clone());
-            Ok(())
-        }
-
-        async fn delete_entry(&self, id: Uuid) -> Result<()> {
-            self.entries.write().await.remove(&id);
-            Ok(())
+        async fn claim_reminder(&self, _entry_id: Uuid, _fire_at: DateTime<Utc>) -> Result<bool> {
+            Ok(true)
         }
     }
 
@@ -682,6 +1536,163 @@ mod tests {
         assert!(matches!(events[0].1, CalendarEvent::EntryUpdated { .. }));
     }
 
+    #[tokio::test]
+    async fn test_update_entry_if_version() {
+        let calendar_id = Uuid::new_v4();
+        let date = NaiveDate::from_ymd_opt(2024, 6, 15).unwrap();
+        let entry = create_test_entry(calendar_id, date);
+
+        let repo = Arc::new(MockEntryRepository::new());
+        repo.insert(entry.clone()).await;
+
+        let pubsub = Arc::new(MockPubSub::new());
+        let cached = CachedEntryRepository::new(
+            repo.clone(),
+            Arc::new(MockCache::new()),
+            pubsub.clone(),
+            Duration::from_secs(300),
+        );
+
+        cached.update_entry_if_version(&entry, 1).await.unwrap();
+
+        // The published entry and its revision carry the stored version
+        let events = pubsub.published_events().await;
+        assert!(matches!(
+            &events[0].1,
+            CalendarEvent::EntryUpdated { entry, .. } if entry.version == 2
+        ));
+        let history = cached.get_entry_revisions(entry.id).await.unwrap();
+        assert_eq!(history[0].snapshot.version, 2);
+
+        // A stale write is rejected without publishing
+        let result = cached.update_entry_if_version(&entry, 1).await;
+        assert!(matches!(
+            result,
+            Err(RepositoryError::VersionMismatch { .. })
+        ));
+        assert_eq!(pubsub.published_events().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_move_entry_notifies_both_calendars() {
+        let personal = Uuid::new_v4();
+        let family = Uuid::new_v4();
+        let date = NaiveDate::from_ymd_opt(2024, 6, 15).unwrap();
+        let entry = create_test_entry(personal, date);
+
+        let repo = Arc::new(MockEntryRepository::new());
+        repo.insert(entry.clone()).await;
+
+        let cache = Arc::new(MockCache::new());
+        let pubsub = Arc::new(MockPubSub::new());
+
+        let cached = CachedEntryRepository::new(
+            repo.clone(),
+            cache.clone(),
+            pubsub.clone(),
+            Duration::from_secs(300),
+        );
+
+        let range = DateRange::new(date, date).unwrap();
+        let source_key = calendar_entries_key(personal, range.start, range.end);
+        let target_key = calendar_entries_key(family, range.start, range.end);
+        for key in [&source_key, &target_key] {
+            cache.set(key, b"cached_entries", None).await.unwrap();
+        }
+
+        let moved = cached.move_entry(entry.id, family, 1).await.unwrap();
+        assert_eq!(moved.calendar_id, family);
+        assert_eq!(moved.version, 2);
+
+        assert!(!cache.store.read().await.contains_key(&source_key));
+        assert!(!cache.store.read().await.contains_key(&target_key));
+
+        let events = pubsub.published_events().await;
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].0, personal);
+        assert!(matches!(events[0].1, CalendarEvent::EntryDeleted { .. }));
+        assert_eq!(events[1].0, family);
+        assert!(matches!(events[1].1, CalendarEvent::EntryAdded { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_apply_entry_batch_coalesces_invalidation_and_events() {
+        let personal = Uuid::new_v4();
+        let family = Uuid::new_v4();
+        let date = NaiveDate::from_ymd_opt(2024, 9, 2).unwrap();
+        let updated = create_test_entry(personal, date);
+        let deleted = create_test_entry(family, date.succ_opt().unwrap());
+
+        let repo = Arc::new(MockEntryRepository::new());
+        repo.insert(updated.clone()).await;
+        repo.insert(deleted.clone()).await;
+
+        let cache = Arc::new(MockCache::new());
+        let pubsub = Arc::new(MockPubSub::new());
+
+        let cached = CachedEntryRepository::new(
+            repo.clone(),
+            cache.clone(),
+            pubsub.clone(),
+            Duration::from_secs(300),
+        );
+
+        let range = DateRange::new(date, date).unwrap();
+        let personal_key = calendar_entries_key(personal, range.start, range.end);
+        let family_key = calendar_entries_key(family, range.start, range.end);
+        let updated_key = entry_key(updated.id);
+        for key in [&personal_key, &family_key, &updated_key] {
+            cache.set(key, b"cached", None).await.unwrap();
+        }
+
+        let writes: Vec<_> = (0..3)
+            .map(|_| EntryWrite::Create(create_test_entry(personal, date)))
+            .chain([
+                EntryWrite::Update {
+                    entry: updated.clone(),
+                    expected_version: 1,
+                },
+                EntryWrite::Delete(deleted.id),
+            ])
+            .collect();
+        cached.apply_entry_batch(&writes).await.unwrap();
+
+        let store = cache.store.read().await;
+        assert!(!store.contains_key(&personal_key));
+        assert!(!store.contains_key(&family_key));
+        assert!(!store.contains_key(&updated_key));
+        drop(store);
+
+        // One event per calendar, not one per entry
+        let events = pubsub.published_events().await;
+        assert_eq!(events.len(), 2);
+        let by_calendar: HashMap<_, _> = events.into_iter().collect();
+        match &by_calendar[&personal] {
+            CalendarEvent::EntryBatch {
+                added,
+                updated: changed,
+                deleted: removed,
+                ..
+            } => {
+                assert_eq!(added.len(), 3);
+                assert_eq!(changed.len(), 1);
+                assert_eq!(changed[0].version, 2);
+                assert!(removed.is_empty());
+            }
+            other => panic!("Expected EntryBatch, got {other:?}"),
+        }
+        match &by_calendar[&family] {
+            CalendarEvent::EntryBatch {
+                deleted: removed, ..
+            } => {
+                assert_eq!(removed, &vec![deleted.id]);
+            }
+            other => panic!("Expected EntryBatch, got {other:?}"),
+        }
+
+        assert_eq!(repo.revisions.read().await.len(), 5);
+    }
+
     #[tokio::test]
     async fn test_delete_entry_invalidates_and_publishes() {
         let calendar_id = Uuid::new_v4();
@@ -719,4 +1730,254 @@ mod tests {
         assert_eq!(events.len(), 1);
         assert!(matches!(events[0].1, CalendarEvent::EntryDeleted { .. }));
     }
+
+    #[tokio::test]
+    async fn test_restore_entry_invalidates_and_publishes() {
+        let calendar_id = Uuid::new_v4();
+        let date = NaiveDate::from_ymd_opt(2024, 6, 15).unwrap();
+        let entry = create_test_entry(calendar_id, date);
+
+        let repo = Arc::new(MockEntryRepository::new());
+        repo.insert(entry.clone()).await;
+
+        let cache = Arc::new(MockCache::new());
+        let pubsub = Arc::new(MockPubSub::new());
+
+        let cached = CachedEntryRepository::new(
+            repo.clone(),
+            cache.clone(),
+            pubsub.clone(),
+            Duration::from_secs(300),
+        );
+
+        cached.delete_entry(entry.id).await.unwrap();
+
+        // A listing cached while the entry was in the trash
+        let entries_cache_key = calendar_entries_key(calendar_id, date, date);
+        cache
+            .set(&entries_cache_key, b"cached_entries", None)
+            .await
+            .unwrap();
+
+        let restored = cached.restore_entry(entry.id).await.unwrap();
+        assert_eq!(restored, entry);
+        assert!(!cache.store.read().await.contains_key(&entries_cache_key));
+
+        let events = pubsub.published_events().await;
+        assert_eq!(events.len(), 2);
+        assert!(matches!(events[0].1, CalendarEvent::EntryDeleted { .. }));
+        assert_eq!(events[1].1, CalendarEvent::entry_restored(entry));
+    }
+
+    #[tokio::test]
+    async fn test_writes_record_revisions() {
+        let calendar_id = Uuid::new_v4();
+        let date = NaiveDate::from_ymd_opt(2024, 6, 15).unwrap();
+        let entry = create_test_entry(calendar_id, date);
+        let actor = Some(Uuid::new_v4());
+
+        let repo = Arc::new(MockEntryRepository::new());
+        let cached = CachedEntryRepository::new(
+            repo.clone(),
+            Arc::new(MockCache::new()),
+            Arc::new(MockPubSub::new()),
+            Duration::from_secs(300),
+        );
+
+        cached.create_entry_as(&entry, actor).await.unwrap();
+        let mut renamed = entry.clone();
+        renamed.title = "Renamed".to_string();
+        cached.update_entry_as(&renamed, actor).await.unwrap();
+        cached.delete_entry(entry.id).await.unwrap();
+        cached.restore_entry_as(entry.id, actor).await.unwrap();
+
+        let history = cached.get_entry_revisions(entry.id).await.unwrap();
+        let actions: Vec<RevisionAction> = history.iter().map(|r| r.action).collect();
+        assert_eq!(
+            actions,
+            vec![
+                RevisionAction::Created,
+                RevisionAction::Updated,
+                RevisionAction::Deleted,
+                RevisionAction::Restored,
+            ]
+        );
+        let numbers: Vec<u32> = history.iter().map(|r| r.revision).collect();
+        assert_eq!(numbers, vec![1, 2, 3, 4]);
+        assert_eq!(history[1].changed_fields, vec!["title".to_string()]);
+        assert_eq!(history[1].snapshot.title, "Renamed");
+        assert_eq!(history[0].actor_id, actor);
+        assert_eq!(history[2].actor_id, None);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_writes_keep_every_revision() {
+        let calendar_id = Uuid::new_v4();
+        let date = NaiveDate::from_ymd_opt(2024, 6, 15).unwrap();
+        let entry = create_test_entry(calendar_id, date);
+
+        let repo = Arc::new(MockEntryRepository::new());
+        let cached = CachedEntryRepository::new(
+            repo.clone(),
+            Arc::new(MockCache::new()),
+            Arc::new(MockPubSub::new()),
+            Duration::from_secs(300),
+        );
+        cached.create_entry(&entry).await.unwrap();
+
+        let rename = |title: &str| CalendarEntry {
+            title: title.to_string(),
+            ..entry.clone()
+        };
+        let (a, b, c) = (rename("A"), rename("B"), rename("C"));
+        let (a, b, c) = tokio::join!(
+            cached.update_entry(&a),
+            cached.update_entry(&b),
+            cached.update_entry(&c),
+        );
+        a.unwrap();
+        b.unwrap();
+        c.unwrap();
+
+        let history = cached.get_entry_revisions(entry.id).await.unwrap();
+        let mut numbers: Vec<u32> = history.iter().map(|r| r.revision).collect();
+        numbers.sort_unstable();
+        assert_eq!(numbers, vec![1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_upsert_occurrence_override_invalidates_and_publishes() {
+        let calendar_id = Uuid::new_v4();
+        let date = NaiveDate::from_ymd_opt(2024, 6, 3).unwrap();
+        let series =
+            create_test_entry(calendar_id, date).with_recurrence("FREQ=WEEKLY".parse().unwrap());
+
+        let repo = Arc::new(MockEntryRepository::new());
+        repo.insert(series.clone()).await;
+
+        let cache = Arc::new(MockCache::new());
+        let pubsub = Arc::new(MockPubSub::new());
+
+        let cached = CachedEntryRepository::new(
+            repo.clone(),
+            cache.clone(),
+            pubsub.clone(),
+            Duration::from_secs(300),
+        );
+
+        let range = DateRange::new(date, NaiveDate::from_ymd_opt(2024, 6, 30).unwrap()).unwrap();
+        let entries_cache_key = calendar_entries_key(calendar_id, range.start, range.end);
+        cache
+            .set(&entries_cache_key, b"cached_entries", None)
+            .await
+            .unwrap();
+
+        let original_date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+        let mut moved = series.clone();
+        moved.start_date = NaiveDate::from_ymd_opt(2024, 6, 11).unwrap();
+        moved.end_date = moved.start_date;
+        cached
+            .upsert_occurrence_override(&OccurrenceOverride::replaced(
+                series.id,
+                original_date,
+                moved,
+            ))
+            .await
+            .unwrap();
+        cached
+            .upsert_occurrence_override(&OccurrenceOverride::cancelled(
+                series.id,
+                NaiveDate::from_ymd_opt(2024, 6, 17).unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        // Cached ranges include overrides, so they must be invalidated
+        assert!(!cache.store.read().await.contains_key(&entries_cache_key));
+
+        let events = pubsub.published_events().await;
+        assert_eq!(events.len(), 2);
+        match &events[0].1 {
+            CalendarEvent::EntryUpdated {
+                entry,
+                date,
+                occurrence_date,
+            } => {
+                assert_eq!(entry.id, series.id);
+                assert!(entry.is_recurring());
+                assert_eq!(date, "2024-06-11");
+                assert_eq!(*occurrence_date, Some(original_date));
+            }
+            other => panic!("Expected EntryUpdated, got {other:?}"),
+        }
+        assert_eq!(
+            events[1].1,
+            CalendarEvent::occurrence_deleted(
+                series.id,
+                NaiveDate::from_ymd_opt(2024, 6, 17).unwrap()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_override_edits_are_series_revisions() {
+        let calendar_id = Uuid::new_v4();
+        let date = NaiveDate::from_ymd_opt(2024, 6, 3).unwrap();
+        let series =
+            create_test_entry(calendar_id, date).with_recurrence("FREQ=WEEKLY".parse().unwrap());
+        let actor = Some(Uuid::new_v4());
+
+        let repo = Arc::new(MockEntryRepository::new());
+        let cache = Arc::new(MockCache::new());
+        let cached = CachedEntryRepository::new(
+            repo.clone(),
+            cache.clone(),
+            Arc::new(MockPubSub::new()),
+            Duration::from_secs(300),
+        );
+        cached.create_entry_as(&series, actor).await.unwrap();
+        cached.get_entry(series.id).await.unwrap();
+
+        let cancelled =
+            OccurrenceOverride::cancelled(series.id, NaiveDate::from_ymd_opt(2024, 6, 10).unwrap());
+        cached
+            .upsert_occurrence_override_if_version_as(&cancelled, 1, actor)
+            .await
+            .unwrap();
+
+        // The series moved on to version 2, so an edit based on 1 is refused
+        let stale =
+            OccurrenceOverride::cancelled(series.id, NaiveDate::from_ymd_opt(2024, 6, 17).unwrap());
+        let result = cached
+            .upsert_occurrence_override_if_version_as(&stale, 1, actor)
+            .await;
+        assert!(matches!(
+            result,
+            Err(RepositoryError::VersionMismatch { .. })
+        ));
+        assert_eq!(repo.overrides.read().await.clone(), vec![cancelled.clone()]);
+        assert!(!cache.store.read().await.contains_key(&entry_key(series.id)));
+        assert_eq!(
+            cached.get_entry(series.id).await.unwrap().unwrap().version,
+            2
+        );
+
+        // Putting the overrides back as they were is an edit of the series too
+        cached
+            .update_series_if_version_as(&series, &[], 2, None)
+            .await
+            .unwrap();
+        assert!(repo.overrides.read().await.is_empty());
+
+        let history = cached.get_entry_revisions(series.id).await.unwrap();
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[0].overrides, Some(Vec::new()));
+        assert_eq!(history[1].action, RevisionAction::Updated);
+        assert_eq!(history[1].actor_id, actor);
+        assert_eq!(history[1].changed_fields, vec!["overrides".to_string()]);
+        assert_eq!(history[1].overrides, Some(vec![cancelled]));
+        assert_eq!(history[1].snapshot.version, 2);
+        assert_eq!(history[2].changed_fields, vec!["overrides".to_string()]);
+        assert_eq!(history[2].overrides, Some(Vec::new()));
+    }
 }
@@ -7,10 +7,14 @@ use std::collections::HashMap;
 
 use aws_sdk_dynamodb::types::AttributeValue;
 use calendsync_core::calendar::{
-    Calendar, CalendarEntry, CalendarMembership, CalendarRole, CalendarSettings, EntryKind, User,
+    format_reminders, format_tags, parse_reminders, parse_tags, Calendar, CalendarEntry,
+    CalendarMembership, CalendarRole, CalendarSettings, CalendarSubscription, EntryKind,
+    EntryRevision, FeedToken, OccurrenceOverride, RecurrenceRule, RevisionAction, Tag,
+    TrashedCalendar, TrashedEntry, User, INITIAL_VERSION,
 };
 use calendsync_core::storage::RepositoryError;
 use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
 use uuid::Uuid;
 
 use super::keys;
@@ -24,6 +28,12 @@ pub const ENTITY_TYPE_CALENDAR: &str = "CALENDAR";
 pub const ENTITY_TYPE_ENTRY: &str = "ENTRY";
 pub const ENTITY_TYPE_MEMBERSHIP: &str = "MEMBERSHIP";
 pub const ENTITY_TYPE_SETTINGS: &str = "SETTINGS";
+pub const ENTITY_TYPE_FEED_TOKEN: &str = "FEED_TOKEN";
+pub const ENTITY_TYPE_OVERRIDE: &str = "OVERRIDE";
+pub const ENTITY_TYPE_REMINDER_DELIVERY: &str = "REMINDER_DELIVERY";
+pub const ENTITY_TYPE_ENTRY_TAG: &str = "ENTRY_TAG";
+pub const ENTITY_TYPE_ENTRY_TERM: &str = "ENTRY_TERM";
+pub const ENTITY_TYPE_ENTRY_REVISION: &str = "ENTRY_REVISION";
 
 // ============================================================================
 // User conversions
@@ -74,6 +84,12 @@ pub fn user_to_item(user: &User) -> HashMap<String, AttributeValue> {
             AttributeValue::S(provider_subject.clone()),
         );
     }
+    if let Some(tz) = user.timezone {
+        item.insert(
+            "timezone".to_string(),
+            AttributeValue::S(tz.name().to_string()),
+        );
+    }
 
     // GSI3 keys for provider lookup (only when both provider fields are set)
     if let (Some(provider), Some(provider_subject)) = (&user.provider, &user.provider_subject) {
@@ -98,6 +114,7 @@ pub fn item_to_user(item: &HashMap<String, AttributeValue>) -> Result<User, Repo
         email: get_string(item, "email")?,
         provider: get_optional_string(item, "provider"),
         provider_subject: get_optional_string(item, "providerSubject"),
+        timezone: get_optional_timezone(item, "timezone")?,
         created_at: get_datetime(item, "createdAt")?,
         updated_at: get_datetime(item, "updatedAt")?,
     })
@@ -141,6 +158,44 @@ pub fn calendar_to_item(calendar: &Calendar) -> HashMap<String, AttributeValue>
         "isDefault".to_string(),
         AttributeValue::Bool(calendar.is_default),
     );
+    if let Some(tz) = calendar.timezone {
+        item.insert(
+            "timezone".to_string(),
+            AttributeValue::S(tz.name().to_string()),
+        );
+    }
+    // Subscribed calendars are also indexed on GSI2 so the refresher can
+    // find them; trashing a calendar moves it to the trash partition instead
+    if let Some(subscription) = &calendar.subscription {
+        item.insert(
+            "subscriptionUrl".to_string(),
+            AttributeValue::S(subscription.url.clone()),
+        );
+        if let Some(refreshed_at) = subscription.refreshed_at {
+            item.insert(
+                "subscriptionRefreshedAt".to_string(),
+                AttributeValue::S(refreshed_at.to_rfc3339()),
+            );
+        }
+        if let Some(error) = &subscription.error {
+            item.insert(
+                "subscriptionError".to_string(),
+                AttributeValue::S(error.clone()),
+            );
+        }
+        item.insert(
+            "GSI2PK".to_string(),
+            AttributeValue::S(keys::SUBSCRIPTIONS_GSI2_PK.to_string()),
+        );
+        item.insert(
+            "GSI2SK".to_string(),
+            AttributeValue::S(keys::calendar_sk(calendar.id)),
+        );
+    }
+    item.insert(
+        "version".to_string(),
+        AttributeValue::N(calendar.version.to_string()),
+    );
     item.insert(
         "createdAt".to_string(),
         AttributeValue::S(calendar.created_at.to_rfc3339()),
@@ -163,11 +218,61 @@ pub fn item_to_calendar(
         color: get_string(item, "color")?,
         description: get_optional_string(item, "description"),
         is_default: get_optional_bool(item, "isDefault").unwrap_or(false),
+        timezone: get_optional_timezone(item, "timezone")?,
+        subscription: get_subscription(item)?,
+        version: get_version(item)?,
         created_at: get_datetime(item, "createdAt")?,
         updated_at: get_datetime(item, "updatedAt")?,
     })
 }
 
+/// Read a calendar's feed subscription, if it has one.
+fn get_subscription(
+    item: &HashMap<String, AttributeValue>,
+) -> Result<Option<CalendarSubscription>, RepositoryError> {
+    let Some(url) = get_optional_string(item, "subscriptionUrl") else {
+        return Ok(None);
+    };
+    let refreshed_at = if item.contains_key("subscriptionRefreshedAt") {
+        Some(get_datetime(item, "subscriptionRefreshedAt")?)
+    } else {
+        None
+    };
+    Ok(Some(CalendarSubscription {
+        url,
+        refreshed_at,
+        error: get_optional_string(item, "subscriptionError"),
+    }))
+}
+
+/// Convert a trashed Calendar to DynamoDB item.
+///
+/// The calendar keeps its keys and gains a `deletedAt` attribute plus GSI2
+/// keys in the trashed calendars partition, which the purge job queries.
+pub fn trashed_calendar_to_item(
+    calendar: &Calendar,
+    deleted_at: DateTime<Utc>,
+) -> HashMap<String, AttributeValue> {
+    let mut item = calendar_to_item(calendar);
+    insert_trash_attributes(
+        &mut item,
+        keys::TRASHED_CALENDARS_GSI2_PK,
+        calendar.id,
+        deleted_at,
+    );
+    item
+}
+
+/// Convert a DynamoDB item to TrashedCalendar.
+pub fn item_to_trashed_calendar(
+    item: &HashMap<String, AttributeValue>,
+) -> Result<TrashedCalendar, RepositoryError> {
+    Ok(TrashedCalendar {
+        calendar: item_to_calendar(item)?,
+        deleted_at: get_datetime(item, "deletedAt")?,
+    })
+}
+
 // ============================================================================
 // Entry conversions
 // ============================================================================
@@ -227,12 +332,57 @@ pub fn entry_to_item(
     if let Some(color) = &entry.color {
         item.insert("color".to_string(), AttributeValue::S(color.clone()));
     }
+    if let Some(tz) = entry.timezone {
+        item.insert(
+            "timezone".to_string(),
+            AttributeValue::S(tz.name().to_string()),
+        );
+    }
+    if let Some(rule) = &entry.recurrence {
+        item.insert(
+            "recurrence".to_string(),
+            AttributeValue::S(rule.to_string()),
+        );
+    }
+    // Entries with reminders are also indexed on GSI2 so the reminder
+    // scheduler can find them across calendars
+    if entry.has_reminders() {
+        item.insert(
+            "reminders".to_string(),
+            AttributeValue::S(format_reminders(&entry.reminders)),
+        );
+        item.insert(
+            "GSI2PK".to_string(),
+            AttributeValue::S(keys::REMINDERS_GSI2_PK.to_string()),
+        );
+        item.insert(
+            "GSI2SK".to_string(),
+            AttributeValue::S(keys::entry_gsi2_sk(entry.start_date, entry.id)),
+        );
+    }
+
+    if !entry.attendees.is_empty() {
+        let attendees_json = serde_json::to_string(&entry.attendees)
+            .map_err(|e| RepositoryError::Serialization(e.to_string()))?;
+        item.insert("attendees".to_string(), AttributeValue::S(attendees_json));
+    }
+
+    if !entry.tags.is_empty() {
+        item.insert(
+            "tags".to_string(),
+            AttributeValue::S(format_tags(&entry.tags)),
+        );
+    }
 
     // Entry kind as JSON
     let kind_json = serde_json::to_string(&entry.kind)
         .map_err(|e| RepositoryError::Serialization(e.to_string()))?;
     item.insert("kind".to_string(), AttributeValue::S(kind_json));
 
+    item.insert(
+        "version".to_string(),
+        AttributeValue::N(entry.version.to_string()),
+    );
     item.insert(
         "createdAt".to_string(),
         AttributeValue::S(entry.created_at.to_rfc3339()),
@@ -245,6 +395,65 @@ pub fn entry_to_item(
     Ok(item)
 }
 
+/// Convert a trashed CalendarEntry to DynamoDB item.
+///
+/// GSI1 moves to the calendar's `TRASH#` partition and GSI2 to the trashed
+/// entries partition, so calendar and reminder queries no longer see it.
+pub fn trashed_entry_to_item(
+    entry: &CalendarEntry,
+    deleted_at: DateTime<Utc>,
+) -> Result<HashMap<String, AttributeValue>, RepositoryError> {
+    let mut item = entry_to_item(entry)?;
+    item.insert(
+        "GSI1PK".to_string(),
+        AttributeValue::S(keys::trashed_entry_gsi1_pk(entry.calendar_id)),
+    );
+    item.insert(
+        "GSI1SK".to_string(),
+        AttributeValue::S(keys::trash_sk(deleted_at, entry.id)),
+    );
+    insert_trash_attributes(
+        &mut item,
+        keys::TRASHED_ENTRIES_GSI2_PK,
+        entry.id,
+        deleted_at,
+    );
+    Ok(item)
+}
+
+/// Convert a DynamoDB item to TrashedEntry.
+pub fn item_to_trashed_entry(
+    item: &HashMap<String, AttributeValue>,
+) -> Result<TrashedEntry, RepositoryError> {
+    Ok(TrashedEntry {
+        entry: item_to_entry(item)?,
+        deleted_at: get_datetime(item, "deletedAt")?,
+    })
+}
+
+/// Whether an item has been moved to the trash.
+pub fn is_trashed(item: &HashMap<String, AttributeValue>) -> bool {
+    item.contains_key("deletedAt")
+}
+
+/// Stamp an item with its deletion time and index it in a trash partition.
+fn insert_trash_attributes(
+    item: &mut HashMap<String, AttributeValue>,
+    gsi2_pk: &str,
+    id: Uuid,
+    deleted_at: DateTime<Utc>,
+) {
+    item.insert(
+        "deletedAt".to_string(),
+        AttributeValue::S(keys::trash_timestamp(deleted_at)),
+    );
+    item.insert("GSI2PK".to_string(), AttributeValue::S(gsi2_pk.to_string()));
+    item.insert(
+        "GSI2SK".to_string(),
+        AttributeValue::S(keys::trash_sk(deleted_at, id)),
+    );
+}
+
 /// Convert a DynamoDB item to CalendarEntry.
 pub fn item_to_entry(
     item: &HashMap<String, AttributeValue>,
@@ -255,6 +464,31 @@ pub fn item_to_entry(
 
     let start_date = get_date(item, "start_date")?;
     let end_date = get_date(item, "end_date")?;
+    let recurrence = get_optional_string(item, "recurrence")
+        .map(|rule| {
+            rule.parse::<RecurrenceRule>()
+                .map_err(|e| RepositoryError::InvalidData(format!("recurrence: {e}")))
+        })
+        .transpose()?;
+    let reminders = get_optional_string(item, "reminders")
+        .map(|reminders| {
+            parse_reminders(&reminders)
+                .map_err(|e| RepositoryError::InvalidData(format!("reminders: {e}")))
+        })
+        .transpose()?
+        .unwrap_or_default();
+    let attendees = get_optional_string(item, "attendees")
+        .map(|json| {
+            serde_json::from_str(&json).map_err(|e| RepositoryError::Serialization(e.to_string()))
+        })
+        .transpose()?
+        .unwrap_or_default();
+    let tags = get_optional_string(item, "tags")
+        .map(|tags| {
+            parse_tags(&tags).map_err(|e| RepositoryError::InvalidData(format!("tags: {e}")))
+        })
+        .transpose()?
+        .unwrap_or_default();
 
     Ok(CalendarEntry {
         id: get_uuid(item, "id")?,
@@ -266,11 +500,330 @@ pub fn item_to_entry(
         start_date,
         end_date,
         color: get_optional_string(item, "color"),
+        timezone: get_optional_timezone(item, "timezone")?,
+        recurrence,
+        reminders,
+        attendees,
+        tags,
+        occurrence_date: None,
+        segment: None,
+        version: get_version(item)?,
         created_at: get_datetime(item, "createdAt")?,
         updated_at: get_datetime(item, "updatedAt")?,
     })
 }
 
+// ============================================================================
+// Occurrence override conversions
+// ============================================================================
+
+/// Convert an OccurrenceOverride to DynamoDB item.
+///
+/// Overrides live in their series' partition and have no GSI keys, so they
+/// never show up in calendar range queries on their own.
+pub fn override_to_item(
+    occurrence: &OccurrenceOverride,
+) -> Result<HashMap<String, AttributeValue>, RepositoryError> {
+    let mut item = HashMap::new();
+
+    // Keys
+    item.insert(
+        "PK".to_string(),
+        AttributeValue::S(keys::override_pk(occurrence.series_id)),
+    );
+    item.insert(
+        "SK".to_string(),
+        AttributeValue::S(keys::override_sk(occurrence.original_date)),
+    );
+
+    // Entity type
+    item.insert(
+        "entityType".to_string(),
+        AttributeValue::S(ENTITY_TYPE_OVERRIDE.to_string()),
+    );
+
+    // Data
+    item.insert(
+        "seriesId".to_string(),
+        AttributeValue::S(occurrence.series_id.to_string()),
+    );
+    item.insert(
+        "original_date".to_string(),
+        AttributeValue::S(occurrence.original_date.to_string()),
+    );
+    if let Some(replacement) = &occurrence.replacement {
+        let replacement_json = serde_json::to_string(replacement)
+            .map_err(|e| RepositoryError::Serialization(e.to_string()))?;
+        item.insert(
+            "replacementJson".to_string(),
+            AttributeValue::S(replacement_json),
+        );
+    }
+    item.insert(
+        "updatedAt".to_string(),
+        AttributeValue::S(Utc::now().to_rfc3339()),
+    );
+
+    Ok(item)
+}
+
+/// Convert a DynamoDB item to OccurrenceOverride.
+pub fn item_to_override(
+    item: &HashMap<String, AttributeValue>,
+) -> Result<OccurrenceOverride, RepositoryError> {
+    let replacement = get_optional_string(item, "replacementJson")
+        .map(|json| {
+            serde_json::from_str::<CalendarEntry>(&json)
+                .map_err(|e| RepositoryError::Serialization(e.to_string()))
+        })
+        .transpose()?;
+
+    Ok(OccurrenceOverride {
+        series_id: get_uuid(item, "seriesId")?,
+        original_date: get_date(item, "original_date")?,
+        replacement,
+    })
+}
+
+// ============================================================================
+// Entry revision conversions
+// ============================================================================
+
+/// Convert an EntryRevision to DynamoDB item.
+///
+/// Revisions live in their entry's partition, numbered in the sort key, and
+/// keep the snapshot as JSON.
+pub fn entry_revision_to_item(
+    revision: &EntryRevision,
+) -> Result<HashMap<String, AttributeValue>, RepositoryError> {
+    let mut item = HashMap::new();
+
+    // Keys
+    item.insert(
+        "PK".to_string(),
+        AttributeValue::S(keys::entry_revision_pk(revision.entry_id)),
+    );
+    item.insert(
+        "SK".to_string(),
+        AttributeValue::S(keys::entry_revision_sk(revision.revision)),
+    );
+
+    // Entity type
+    item.insert(
+        "entityType".to_string(),
+        AttributeValue::S(ENTITY_TYPE_ENTRY_REVISION.to_string()),
+    );
+
+    // Data
+    item.insert(
+        "entryId".to_string(),
+        AttributeValue::S(revision.entry_id.to_string()),
+    );
+    item.insert(
+        "revision".to_string(),
+        AttributeValue::N(revision.revision.to_string()),
+    );
+    item.insert(
+        "action".to_string(),
+        AttributeValue::S(revision.action.as_str().to_string()),
+    );
+    if let Some(actor_id) = revision.actor_id {
+        item.insert(
+            "actorId".to_string(),
+            AttributeValue::S(actor_id.to_string()),
+        );
+    }
+    item.insert(
+        "recordedAt".to_string(),
+        AttributeValue::S(revision.recorded_at.to_rfc3339()),
+    );
+    if !revision.changed_fields.is_empty() {
+        item.insert(
+            "changedFields".to_string(),
+            AttributeValue::S(revision.changed_fields.join(",")),
+        );
+    }
+    let snapshot_json = serde_json::to_string(&revision.snapshot)
+        .map_err(|e| RepositoryError::Serialization(e.to_string()))?;
+    item.insert("snapshotJson".to_string(), AttributeValue::S(snapshot_json));
+    if let Some(overrides) = &revision.overrides {
+        let overrides_json = serde_json::to_string(overrides)
+            .map_err(|e| RepositoryError::Serialization(e.to_string()))?;
+        item.insert(
+            "overridesJson".to_string(),
+            AttributeValue::S(overrides_json),
+        );
+    }
+
+    Ok(item)
+}
+
+/// Convert a DynamoDB item to EntryRevision.
+pub fn item_to_entry_revision(
+    item: &HashMap<String, AttributeValue>,
+) -> Result<EntryRevision, RepositoryError> {
+    let action = get_string(item, "action")?
+        .parse::<RevisionAction>()
+        .map_err(|e| RepositoryError::InvalidData(e.to_string()))?;
+    let revision = item
+        .get("revision")
+        .and_then(|v| v.as_n().ok())
+        .and_then(|n| n.parse::<u32>().ok())
+        .ok_or_else(|| RepositoryError::InvalidData("Missing or invalid field: revision".into()))?;
+    let actor_id = get_optional_string(item, "actorId")
+        .map(|s| {
+            Uuid::parse_str(&s)
+                .map_err(|e| RepositoryError::InvalidData(format!("Invalid UUID actorId: {}", e)))
+        })
+        .transpose()?;
+    let changed_fields = get_optional_string(item, "changedFields")
+        .map(|s| s.split(',').map(String::from).collect())
+        .unwrap_or_default();
+    let snapshot = serde_json::from_str::<CalendarEntry>(&get_string(item, "snapshotJson")?)
+        .map_err(|e| RepositoryError::Serialization(e.to_string()))?;
+    let overrides = get_optional_string(item, "overridesJson")
+        .map(|json| serde_json::from_str::<Vec<OccurrenceOverride>>(&json))
+        .transpose()
+        .map_err(|e| RepositoryError::Serialization(e.to_string()))?;
+
+    Ok(EntryRevision {
+        entry_id: get_uuid(item, "entryId")?,
+        revision,
+        action,
+        actor_id,
+        recorded_at: get_datetime(item, "recordedAt")?,
+        changed_fields,
+        snapshot,
+        overrides,
+    })
+}
+
+// ============================================================================
+// Entry tag conversions
+// ============================================================================
+
+/// Build the index item that lists an entry under one of its tags.
+///
+/// Tag items live in the calendar partition so a tag query is a single
+/// `begins_with` on the sort key.
+pub fn entry_tag_to_item(entry: &CalendarEntry, tag: &Tag) -> HashMap<String, AttributeValue> {
+    let mut item = HashMap::new();
+
+    item.insert(
+        "PK".to_string(),
+        AttributeValue::S(keys::entry_tag_pk(entry.calendar_id)),
+    );
+    item.insert(
+        "SK".to_string(),
+        AttributeValue::S(keys::entry_tag_sk(tag.as_str(), entry.id)),
+    );
+    item.insert(
+        "entityType".to_string(),
+        AttributeValue::S(ENTITY_TYPE_ENTRY_TAG.to_string()),
+    );
+    item.insert(
+        "entryId".to_string(),
+        AttributeValue::S(entry.id.to_string()),
+    );
+    item.insert(
+        "calendarId".to_string(),
+        AttributeValue::S(entry.calendar_id.to_string()),
+    );
+    item.insert("tag".to_string(), AttributeValue::S(tag.to_string()));
+
+    item
+}
+
+/// Get the entry ID from an entry tag item.
+pub fn entry_tag_item_entry_id(
+    item: &HashMap<String, AttributeValue>,
+) -> Result<Uuid, RepositoryError> {
+    get_uuid(item, "entryId")
+}
+
+// ============================================================================
+// Entry search term conversions
+// ============================================================================
+
+/// Build the index item that lists an entry under one of its search words.
+///
+/// Like tag items, term items live in the calendar partition so finding the
+/// entries with a word starting with a prefix is a single `begins_with` query.
+pub fn entry_term_to_item(entry: &CalendarEntry, word: &str) -> HashMap<String, AttributeValue> {
+    let mut item = HashMap::new();
+
+    item.insert(
+        "PK".to_string(),
+        AttributeValue::S(keys::entry_term_pk(entry.calendar_id)),
+    );
+    item.insert(
+        "SK".to_string(),
+        AttributeValue::S(keys::entry_term_sk(word, entry.id)),
+    );
+    item.insert(
+        "entityType".to_string(),
+        AttributeValue::S(ENTITY_TYPE_ENTRY_TERM.to_string()),
+    );
+    item.insert(
+        "entryId".to_string(),
+        AttributeValue::S(entry.id.to_string()),
+    );
+    item.insert(
+        "calendarId".to_string(),
+        AttributeValue::S(entry.calendar_id.to_string()),
+    );
+    item.insert("term".to_string(), AttributeValue::S(word.to_string()));
+
+    item
+}
+
+/// Get the entry ID from an entry search term item.
+pub fn entry_term_item_entry_id(
+    item: &HashMap<String, AttributeValue>,
+) -> Result<Uuid, RepositoryError> {
+    get_uuid(item, "entryId")
+}
+
+// ============================================================================
+// Reminder delivery conversions
+// ============================================================================
+
+/// Build the item recording that an entry's reminder due at `fire_at` was delivered.
+pub fn reminder_delivery_to_item(
+    entry_id: Uuid,
+    fire_at: DateTime<Utc>,
+    delivered_at: DateTime<Utc>,
+) -> HashMap<String, AttributeValue> {
+    let mut item = HashMap::new();
+
+    item.insert(
+        "PK".to_string(),
+        AttributeValue::S(keys::reminder_delivery_pk(entry_id)),
+    );
+    item.insert(
+        "SK".to_string(),
+        AttributeValue::S(keys::reminder_delivery_sk(fire_at)),
+    );
+    item.insert(
+        "entityType".to_string(),
+        AttributeValue::S(ENTITY_TYPE_REMINDER_DELIVERY.to_string()),
+    );
+    item.insert(
+        "entryId".to_string(),
+        AttributeValue::S(entry_id.to_string()),
+    );
+    item.insert(
+        "fireAt".to_string(),
+        AttributeValue::S(fire_at.to_rfc3339()),
+    );
+    item.insert(
+        "deliveredAt".to_string(),
+        AttributeValue::S(delivered_at.to_rfc3339()),
+    );
+
+    item
+}
+
 // ============================================================================
 // Membership conversions
 // ============================================================================
@@ -394,6 +947,68 @@ pub fn item_to_settings(
     serde_json::from_str(&json_str).map_err(|e| RepositoryError::Serialization(e.to_string()))
 }
 
+// ============================================================================
+// Feed token conversions
+// ============================================================================
+
+/// Convert a FeedToken to a DynamoDB item.
+pub fn feed_token_to_item(token: &FeedToken) -> HashMap<String, AttributeValue> {
+    let mut item = HashMap::new();
+
+    // Keys
+    item.insert(
+        "PK".to_string(),
+        AttributeValue::S(keys::feed_token_pk(token.calendar_id)),
+    );
+    item.insert(
+        "SK".to_string(),
+        AttributeValue::S(keys::feed_token_sk(token.user_id)),
+    );
+    item.insert(
+        "GSI2PK".to_string(),
+        AttributeValue::S(keys::feed_token_gsi2_pk(&token.token)),
+    );
+    item.insert(
+        "GSI2SK".to_string(),
+        AttributeValue::S(keys::feed_token_gsi2_sk(token.calendar_id)),
+    );
+
+    // Entity type
+    item.insert(
+        "entityType".to_string(),
+        AttributeValue::S(ENTITY_TYPE_FEED_TOKEN.to_string()),
+    );
+
+    // Data
+    item.insert("token".to_string(), AttributeValue::S(token.token.clone()));
+    item.insert(
+        "calendarId".to_string(),
+        AttributeValue::S(token.calendar_id.to_string()),
+    );
+    item.insert(
+        "userId".to_string(),
+        AttributeValue::S(token.user_id.to_string()),
+    );
+    item.insert(
+        "createdAt".to_string(),
+        AttributeValue::S(token.created_at.to_rfc3339()),
+    );
+
+    item
+}
+
+/// Convert a DynamoDB item to a FeedToken.
+pub fn item_to_feed_token(
+    item: &HashMap<String, AttributeValue>,
+) -> Result<FeedToken, RepositoryError> {
+    Ok(FeedToken {
+        token: get_string(item, "token")?,
+        calendar_id: get_uuid(item, "calendarId")?,
+        user_id: get_uuid(item, "userId")?,
+        created_at: get_datetime(item, "createdAt")?,
+    })
+}
+
 // ============================================================================
 // Role conversions
 // ============================================================================
@@ -440,10 +1055,34 @@ fn get_optional_string(item: &HashMap<String, AttributeValue>, key: &str) -> Opt
 }
 
 /// Get an optional boolean attribute.
+fn get_optional_timezone(
+    item: &HashMap<String, AttributeValue>,
+    key: &str,
+) -> Result<Option<Tz>, RepositoryError> {
+    get_optional_string(item, key)
+        .map(|s| {
+            s.parse::<Tz>()
+                .map_err(|e| RepositoryError::InvalidData(format!("Invalid {}: {}", key, e)))
+        })
+        .transpose()
+}
+
 fn get_optional_bool(item: &HashMap<String, AttributeValue>, key: &str) -> Option<bool> {
     item.get(key).and_then(|v| v.as_bool().ok()).copied()
 }
 
+/// Get the `version` attribute, which items written before versions existed lack.
+fn get_version(item: &HashMap<String, AttributeValue>) -> Result<u64, RepositoryError> {
+    match item.get("version") {
+        None => Ok(INITIAL_VERSION),
+        Some(value) => value
+            .as_n()
+            .ok()
+            .and_then(|n| n.parse().ok())
+            .ok_or_else(|| RepositoryError::InvalidData("Invalid field: version".into())),
+    }
+}
+
 /// Get a required UUID attribute.
 fn get_uuid(item: &HashMap<String, AttributeValue>, key: &str) -> Result<Uuid, RepositoryError> {
     let s = get_string(item, key)?;
@@ -484,6 +1123,7 @@ mod tests {
             email: "john@example.com".to_string(),
             provider: None,
             provider_subject: None,
+            timezone: None,
             created_at: DateTime::parse_from_rfc3339("2024-01-15T10:30:00Z")
                 .unwrap()
                 .with_timezone(&Utc),
@@ -500,6 +1140,9 @@ mod tests {
             color: "#3B82F6".to_string(),
             description: Some("My personal calendar".to_string()),
             is_default: false,
+            timezone: None,
+            subscription: None,
+            version: 3,
             created_at: DateTime::parse_from_rfc3339("2024-01-15T10:30:00Z")
                 .unwrap()
                 .with_timezone(&Utc),
@@ -524,6 +1167,14 @@ mod tests {
             start_date: date,
             end_date: date,
             color: Some("#3B82F6".to_string()),
+            timezone: None,
+            recurrence: None,
+            reminders: Vec::new(),
+            attendees: Vec::new(),
+            tags: Default::default(),
+            occurrence_date: None,
+            segment: None,
+            version: 2,
             created_at: DateTime::parse_from_rfc3339("2024-01-15T08:00:00Z")
                 .unwrap()
                 .with_timezone(&Utc),
@@ -577,8 +1228,8 @@ mod tests {
         );
         assert_eq!(item.get("entityType").unwrap().as_s().unwrap(), "USER");
         // GSI3 keys should not be present when provider is not set
-        assert!(item.get("GSI3PK").is_none());
-        assert!(item.get("GSI3SK").is_none());
+        assert!(!item.contains_key("GSI3PK"));
+        assert!(!item.contains_key("GSI3SK"));
     }
 
     #[test]
@@ -608,6 +1259,31 @@ mod tests {
         assert_eq!(calendar.name, parsed.name);
         assert_eq!(calendar.color, parsed.color);
         assert_eq!(calendar.description, parsed.description);
+        assert_eq!(calendar.version, parsed.version);
+    }
+
+    #[test]
+    fn test_subscribed_calendar_round_trip() {
+        let mut calendar = sample_calendar().with_subscription("https://example.com/h.ics");
+        let item = calendar_to_item(&calendar);
+        assert_eq!(
+            item.get("GSI2PK").unwrap().as_s().unwrap(),
+            keys::SUBSCRIPTIONS_GSI2_PK
+        );
+        assert_eq!(item_to_calendar(&item).unwrap(), calendar);
+
+        let subscription = calendar.subscription.as_mut().unwrap();
+        subscription.refreshed(Utc::now());
+        subscription.failed("HTTP 500");
+        let parsed = item_to_calendar(&calendar_to_item(&calendar)).unwrap();
+        assert_eq!(parsed.subscription, calendar.subscription);
+
+        let trashed = trashed_calendar_to_item(&calendar, Utc::now());
+        assert_eq!(
+            trashed.get("GSI2PK").unwrap().as_s().unwrap(),
+            keys::TRASHED_CALENDARS_GSI2_PK
+        );
+        assert!(!calendar_to_item(&sample_calendar()).contains_key("GSI2PK"));
     }
 
     #[test]
@@ -622,6 +1298,176 @@ mod tests {
         assert_eq!(entry.start_date, parsed.start_date);
         assert_eq!(entry.end_date, parsed.end_date);
         assert_eq!(entry.kind, parsed.kind);
+        assert_eq!(entry.version, parsed.version);
+    }
+
+    #[test]
+    fn test_item_without_version_is_initial_version() {
+        let mut item = entry_to_item(&sample_entry()).unwrap();
+        item.remove("version");
+        assert_eq!(item_to_entry(&item).unwrap().version, INITIAL_VERSION);
+    }
+
+    #[test]
+    fn test_entry_round_trip_with_recurrence() {
+        let entry = sample_entry().with_recurrence("FREQ=WEEKLY;BYDAY=MO,TU".parse().unwrap());
+        let item = entry_to_item(&entry).unwrap();
+        assert_eq!(
+            item.get("recurrence").unwrap().as_s().unwrap(),
+            "FREQ=WEEKLY;BYDAY=MO,TU"
+        );
+
+        let parsed = item_to_entry(&item).unwrap();
+        assert_eq!(entry.recurrence, parsed.recurrence);
+
+        let without = entry_to_item(&sample_entry()).unwrap();
+        assert!(!without.contains_key("recurrence"));
+    }
+
+    #[test]
+    fn test_entry_round_trip_with_reminders() {
+        let entry = sample_entry()
+            .with_reminder("10m".parse().unwrap())
+            .with_reminder("1d@09:00".parse().unwrap());
+        let item = entry_to_item(&entry).unwrap();
+        assert_eq!(
+            item.get("reminders").unwrap().as_s().unwrap(),
+            "10m,1d@09:00"
+        );
+        assert_eq!(item.get("GSI2PK").unwrap().as_s().unwrap(), "REMINDERS");
+        assert_eq!(
+            item.get("GSI2SK").unwrap().as_s().unwrap(),
+            item.get("GSI1SK").unwrap().as_s().unwrap()
+        );
+        assert_eq!(item_to_entry(&item).unwrap().reminders, entry.reminders);
+
+        // Entries without reminders stay out of the sparse index
+        let without = entry_to_item(&sample_entry()).unwrap();
+        assert!(!without.contains_key("reminders"));
+        assert!(!without.contains_key("GSI2PK"));
+    }
+
+    #[test]
+    fn test_trashed_entry_round_trip() {
+        let entry = sample_entry().with_reminder("10m".parse().unwrap());
+        let deleted_at = DateTime::parse_from_rfc3339("2024-07-01T08:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let item = trashed_entry_to_item(&entry, deleted_at).unwrap();
+
+        assert!(is_trashed(&item));
+        assert!(!is_trashed(&entry_to_item(&entry).unwrap()));
+        assert_eq!(
+            item.get("GSI1PK").unwrap().as_s().unwrap(),
+            &keys::trashed_entry_gsi1_pk(entry.calendar_id)
+        );
+        // Trashed entries leave the reminders partition
+        assert_eq!(
+            item.get("GSI2PK").unwrap().as_s().unwrap(),
+            keys::TRASHED_ENTRIES_GSI2_PK
+        );
+
+        let trashed = item_to_trashed_entry(&item).unwrap();
+        assert_eq!(trashed.entry.id, entry.id);
+        assert_eq!(trashed.deleted_at, deleted_at);
+    }
+
+    #[test]
+    fn test_trashed_calendar_round_trip() {
+        let calendar = sample_calendar();
+        let deleted_at = DateTime::parse_from_rfc3339("2024-07-01T08:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let item = trashed_calendar_to_item(&calendar, deleted_at);
+
+        assert!(is_trashed(&item));
+        assert_eq!(
+            item.get("GSI2PK").unwrap().as_s().unwrap(),
+            keys::TRASHED_CALENDARS_GSI2_PK
+        );
+        let trashed = item_to_trashed_calendar(&item).unwrap();
+        assert_eq!(trashed.calendar.id, calendar.id);
+        assert_eq!(trashed.deleted_at, deleted_at);
+    }
+
+    #[test]
+    fn test_entry_round_trip_with_attendees() {
+        use calendsync_core::calendar::{Attendee, ResponseStatus};
+
+        let entry = sample_entry()
+            .with_attendee(Attendee::new("ana@example.com").with_status(ResponseStatus::Tentative));
+        let item = entry_to_item(&entry).unwrap();
+        assert!(item.get("attendees").unwrap().as_s().is_ok());
+        assert_eq!(item_to_entry(&item).unwrap().attendees, entry.attendees);
+
+        assert!(!entry_to_item(&sample_entry())
+            .unwrap()
+            .contains_key("attendees"));
+    }
+
+    #[test]
+    fn test_entry_round_trip_with_tags() {
+        let entry = sample_entry()
+            .with_tag("travel".parse().unwrap())
+            .with_tag("oncall".parse().unwrap());
+        let item = entry_to_item(&entry).unwrap();
+        assert_eq!(item.get("tags").unwrap().as_s().unwrap(), "oncall,travel");
+        assert_eq!(item_to_entry(&item).unwrap().tags, entry.tags);
+        assert!(!entry_to_item(&sample_entry()).unwrap().contains_key("tags"));
+
+        let tag_item = entry_tag_to_item(&entry, &"travel".parse().unwrap());
+        assert_eq!(
+            tag_item.get("PK").unwrap().as_s().unwrap(),
+            &keys::entry_tag_pk(entry.calendar_id)
+        );
+        assert_eq!(
+            tag_item.get("SK").unwrap().as_s().unwrap(),
+            &keys::entry_tag_sk("travel", entry.id)
+        );
+        assert_eq!(entry_tag_item_entry_id(&tag_item).unwrap(), entry.id);
+    }
+
+    #[test]
+    fn test_entry_term_item() {
+        let entry = sample_entry();
+        let item = entry_term_to_item(&entry, "dentist");
+        assert_eq!(
+            item.get("PK").unwrap().as_s().unwrap(),
+            &keys::entry_term_pk(entry.calendar_id)
+        );
+        assert_eq!(
+            item.get("SK").unwrap().as_s().unwrap(),
+            &keys::entry_term_sk("dentist", entry.id)
+        );
+        assert_eq!(
+            item.get("entityType").unwrap().as_s().unwrap(),
+            ENTITY_TYPE_ENTRY_TERM
+        );
+        assert_eq!(entry_term_item_entry_id(&item).unwrap(), entry.id);
+    }
+
+    #[test]
+    fn test_timezone_round_trip() {
+        let tz = chrono_tz::America::Montevideo;
+
+        let entry = sample_entry().with_timezone(tz);
+        let item = entry_to_item(&entry).unwrap();
+        assert_eq!(
+            item.get("timezone").unwrap().as_s().unwrap(),
+            "America/Montevideo"
+        );
+        assert_eq!(item_to_entry(&item).unwrap().timezone, Some(tz));
+
+        let calendar = sample_calendar().with_timezone(tz);
+        let parsed = item_to_calendar(&calendar_to_item(&calendar)).unwrap();
+        assert_eq!(parsed.timezone, Some(tz));
+
+        let user = sample_user().with_timezone(tz);
+        let parsed = item_to_user(&user_to_item(&user)).unwrap();
+        assert_eq!(parsed.timezone, Some(tz));
+
+        let without = entry_to_item(&sample_entry()).unwrap();
+        assert!(!without.contains_key("timezone"));
     }
 
     #[test]
@@ -641,6 +1487,71 @@ mod tests {
             .starts_with("ENTRY#2024-01-15#"));
     }
 
+    #[test]
+    fn test_override_round_trip() {
+        let entry = sample_entry();
+        let original_date = entry.start_date;
+        let mut moved = entry.clone();
+        moved.start_date = original_date.succ_opt().unwrap();
+        moved.end_date = moved.start_date;
+
+        let replaced = OccurrenceOverride::replaced(entry.id, original_date, moved);
+        let item = override_to_item(&replaced).unwrap();
+        assert_eq!(
+            item.get("PK").unwrap().as_s().unwrap(),
+            "ENTRY#550e8400-e29b-41d4-a716-446655440003"
+        );
+        assert_eq!(
+            item.get("SK").unwrap().as_s().unwrap(),
+            "OVERRIDE#2024-01-15"
+        );
+        assert!(!item.contains_key("GSI1PK"));
+        assert_eq!(item_to_override(&item).unwrap(), replaced);
+
+        let cancelled = OccurrenceOverride::cancelled(entry.id, original_date);
+        let item = override_to_item(&cancelled).unwrap();
+        assert!(!item.contains_key("replacementJson"));
+        assert_eq!(item_to_override(&item).unwrap(), cancelled);
+    }
+
+    #[test]
+    fn test_entry_revision_round_trip() {
+        let entry = sample_entry();
+        let created = EntryRevision::new(
+            1,
+            RevisionAction::Created,
+            entry.clone(),
+            None,
+            entry.created_at,
+        );
+        let item = entry_revision_to_item(&created).unwrap();
+        assert_eq!(
+            item.get("PK").unwrap().as_s().unwrap(),
+            "ENTRY#550e8400-e29b-41d4-a716-446655440003"
+        );
+        assert_eq!(item.get("SK").unwrap().as_s().unwrap(), "REV#0000000001");
+        assert!(!item.contains_key("actorId"));
+        assert_eq!(item_to_entry_revision(&item).unwrap(), created);
+
+        let mut renamed = entry.clone();
+        renamed.title = "Renamed".to_string();
+        let updated = EntryRevision::new(
+            2,
+            RevisionAction::Updated,
+            renamed,
+            Some(sample_user().id),
+            entry.updated_at,
+        )
+        .with_changes_from(&entry)
+        .with_overrides(
+            vec![OccurrenceOverride::cancelled(entry.id, entry.start_date)],
+            Some(&[]),
+        );
+        let item = entry_revision_to_item(&updated).unwrap();
+        assert!(item.contains_key("overridesJson"));
+        assert_eq!(item_to_entry_revision(&item).unwrap(), updated);
+    }
+
     #[test]
     fn test_membership_round_trip() {
         let membership = sample_membership();
@@ -693,6 +1604,24 @@ mod tests {
         assert_eq!(item.get("entityType").unwrap().as_s().unwrap(), "SETTINGS");
     }
 
+    #[test]
+    fn test_feed_token_round_trip() {
+        let calendar_id = Uuid::parse_str("550e8400-e29b-41d4-a716-446655440002").unwrap();
+        let user_id = Uuid::parse_str("550e8400-e29b-41d4-a716-446655440001").unwrap();
+        let token = FeedToken::new(calendar_id, user_id);
+        let item = feed_token_to_item(&token);
+
+        assert_eq!(
+            item.get("SK").unwrap().as_s().unwrap(),
+            "FEED#550e8400-e29b-41d4-a716-446655440001"
+        );
+        assert_eq!(
+            item.get("GSI2PK").unwrap().as_s().unwrap(),
+            &format!("FEED#{}", token.token)
+        );
+        assert_eq!(item_to_feed_token(&item).unwrap(), token);
+    }
+
     #[test]
     fn test_get_string_missing_field() {
         let item = HashMap::new();
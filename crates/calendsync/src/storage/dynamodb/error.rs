@@ -9,6 +9,8 @@ use aws_sdk_dynamodb::operation::delete_item::DeleteItemError;
 use aws_sdk_dynamodb::operation::get_item::GetItemError;
 use aws_sdk_dynamodb::operation::put_item::PutItemError;
 use aws_sdk_dynamodb::operation::query::QueryError;
+use aws_sdk_dynamodb::operation::scan::ScanError;
+use aws_sdk_dynamodb::operation::transact_write_items::TransactWriteItemsError;
 use aws_sdk_dynamodb::operation::update_item::UpdateItemError;
 use calendsync_core::storage::RepositoryError;
 
@@ -58,6 +60,27 @@ pub fn map_query_error<R: Debug + Send + Sync + 'static>(
     }
 }
 
+/// Map a Scan SDK error to RepositoryError.
+pub fn map_scan_error<R: Debug + Send + Sync + 'static>(
+    err: SdkError<ScanError, R>,
+) -> RepositoryError {
+    match err.into_service_error() {
+        ScanError::ResourceNotFoundException(_) => {
+            RepositoryError::QueryFailed("Table not found".to_string())
+        }
+        ScanError::ProvisionedThroughputExceededException(_) => {
+            RepositoryError::QueryFailed("Throughput exceeded, please retry".to_string())
+        }
+        ScanError::RequestLimitExceeded(_) => {
+            RepositoryError::QueryFailed("Request limit exceeded, please retry".to_string())
+        }
+        ScanError::InternalServerError(_) => {
+            RepositoryError::QueryFailed("DynamoDB internal server error".to_string())
+        }
+        err => RepositoryError::QueryFailed(format!("Scan failed: {:?}", err)),
+    }
+}
+
 /// Map a PutItem SDK error to RepositoryError.
 pub fn map_put_item_error<R: Debug + Send + Sync + 'static>(
     err: SdkError<PutItemError, R>,
@@ -92,6 +115,71 @@ pub fn map_put_item_error<R: Debug + Send + Sync + 'static>(
     }
 }
 
+/// Map the SDK error of a PutItem conditioned on the stored version being
+/// `expected` to RepositoryError.
+///
+/// A failed condition means another writer got there first, or the item is
+/// gone; callers tell the two apart.
+pub fn map_versioned_put_item_error<R: Debug + Send + Sync + 'static>(
+    err: SdkError<PutItemError, R>,
+    entity_type: &'static str,
+    id: impl Into<String>,
+    expected: u64,
+) -> RepositoryError {
+    let id_str = id.into();
+    match err.as_service_error() {
+        Some(PutItemError::ConditionalCheckFailedException(_)) => {
+            RepositoryError::VersionMismatch {
+                entity_type,
+                id: id_str,
+                expected,
+            }
+        }
+        _ => map_put_item_error(err, entity_type, id_str),
+    }
+}
+
+/// Map a TransactWriteItems SDK error to RepositoryError.
+///
+/// When the transaction was cancelled because a condition failed,
+/// `condition_failed` maps the index of the first failing item.
+pub fn map_transact_write_error<R: Debug + Send + Sync + 'static>(
+    err: SdkError<TransactWriteItemsError, R>,
+    condition_failed: impl FnOnce(usize) -> RepositoryError,
+) -> RepositoryError {
+    match err.into_service_error() {
+        TransactWriteItemsError::TransactionCanceledException(cancelled) => {
+            let failed = cancelled
+                .cancellation_reasons()
+                .iter()
+                .position(|reason| reason.code() == Some("ConditionalCheckFailed"));
+            match failed {
+                Some(index) => condition_failed(index),
+                None => RepositoryError::QueryFailed(format!(
+                    "Transaction cancelled: {}",
+                    cancelled.message().unwrap_or("no reason given")
+                )),
+            }
+        }
+        TransactWriteItemsError::TransactionInProgressException(_) => {
+            RepositoryError::QueryFailed("Transaction conflict, please retry".to_string())
+        }
+        TransactWriteItemsError::ResourceNotFoundException(_) => {
+            RepositoryError::QueryFailed("Table not found".to_string())
+        }
+        TransactWriteItemsError::ProvisionedThroughputExceededException(_) => {
+            RepositoryError::QueryFailed("Throughput exceeded, please retry".to_string())
+        }
+        TransactWriteItemsError::RequestLimitExceeded(_) => {
+            RepositoryError::QueryFailed("Request limit exceeded, please retry".to_string())
+        }
+        TransactWriteItemsError::InternalServerError(_) => {
+            RepositoryError::QueryFailed("DynamoDB internal server error".to_string())
+        }
+        err => RepositoryError::QueryFailed(format!("TransactWriteItems failed: {:?}", err)),
+    }
+}
+
 /// Map an UpdateItem SDK error to RepositoryError.
 pub fn map_update_item_error<R: Debug + Send + Sync + 'static>(
     err: SdkError<UpdateItemError, R>,
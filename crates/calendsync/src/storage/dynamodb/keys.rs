@@ -3,7 +3,7 @@
 //! Pure functions for generating partition and sort keys following the single-table design.
 //! All functions are sync and have no side effects.
 
-use chrono::NaiveDate;
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
 use uuid::Uuid;
 
 // ============================================================================
@@ -15,8 +15,28 @@ pub const CALENDAR_PREFIX: &str = "CAL#";
 pub const ENTRY_PREFIX: &str = "ENTRY#";
 pub const MEMBER_PREFIX: &str = "MEMBER#";
 pub const SETTINGS_PREFIX: &str = "SETTINGS#";
+pub const FEED_PREFIX: &str = "FEED#";
+pub const OVERRIDE_PREFIX: &str = "OVERRIDE#";
 pub const EMAIL_PREFIX: &str = "EMAIL#";
 pub const PROVIDER_PREFIX: &str = "PROV#";
+pub const REMINDER_PREFIX: &str = "REMINDER#";
+pub const FIRED_PREFIX: &str = "FIRED#";
+pub const TAG_PREFIX: &str = "TAG#";
+pub const TERM_PREFIX: &str = "TERM#";
+pub const TRASH_PREFIX: &str = "TRASH#";
+pub const REVISION_PREFIX: &str = "REV#";
+
+/// GSI2 partition holding every entry that has reminders.
+pub const REMINDERS_GSI2_PK: &str = "REMINDERS";
+
+/// GSI2 partition holding every trashed entry, oldest deletion first.
+pub const TRASHED_ENTRIES_GSI2_PK: &str = "TRASHED_ENTRIES";
+
+/// GSI2 partition holding every trashed calendar, oldest deletion first.
+pub const TRASHED_CALENDARS_GSI2_PK: &str = "TRASHED_CALENDARS";
+
+/// GSI2 partition holding every calendar that mirrors an iCalendar feed.
+pub const SUBSCRIPTIONS_GSI2_PK: &str = "SUBSCRIPTIONS";
 
 // ============================================================================
 // User keys
@@ -143,6 +163,181 @@ pub fn entry_gsi1_sk_max(date: NaiveDate) -> String {
     format!("{ENTRY_PREFIX}{}#~", date.format("%Y-%m-%d"))
 }
 
+/// Generate GSI2 sort key for an Entry with reminders.
+///
+/// Pattern: `ENTRY#<start_date>#<entry_id>` (same as GSI1SK)
+///
+/// Only entries with reminders carry GSI2 keys, so the index stays sparse.
+pub fn entry_gsi2_sk(start_date: NaiveDate, entry_id: Uuid) -> String {
+    entry_gsi1_sk(start_date, entry_id)
+}
+
+// ============================================================================
+// Trash keys
+// ============================================================================
+
+/// Format a deletion time for trash sort keys.
+///
+/// Fixed-width RFC 3339 (UTC, microseconds), so keys sort chronologically.
+pub fn trash_timestamp(deleted_at: DateTime<Utc>) -> String {
+    deleted_at.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+/// Generate GSI1 partition key for a trashed Entry (calendar lookup).
+///
+/// Pattern: `TRASH#<calendar_id>`
+///
+/// Trashed entries leave the `CAL#` partition so calendar queries skip them.
+pub fn trashed_entry_gsi1_pk(calendar_id: Uuid) -> String {
+    format!("{TRASH_PREFIX}{calendar_id}")
+}
+
+/// Generate the GSI1/GSI2 sort key for a trashed item.
+///
+/// Pattern: `<deleted_at>#<id>`
+pub fn trash_sk(deleted_at: DateTime<Utc>, id: Uuid) -> String {
+    format!("{}#{id}", trash_timestamp(deleted_at))
+}
+
+/// Generate the exclusive upper bound for items trashed before `cutoff`.
+///
+/// Pattern: `<cutoff>`; an item deleted exactly at the cutoff sorts after it.
+pub fn trash_sk_before(cutoff: DateTime<Utc>) -> String {
+    trash_timestamp(cutoff)
+}
+
+// ============================================================================
+// Entry tag keys
+// ============================================================================
+
+/// Generate primary key for an entry tag.
+///
+/// Pattern: `CAL#<calendar_id>` (shares partition with the calendar)
+pub fn entry_tag_pk(calendar_id: Uuid) -> String {
+    format!("{CALENDAR_PREFIX}{calendar_id}")
+}
+
+/// Generate sort key for an entry tag.
+///
+/// Pattern: `TAG#<tag>#ENTRY#<entry_id>`
+///
+/// Tags never contain `#`, so the prefix for one tag never matches another.
+pub fn entry_tag_sk(tag: &str, entry_id: Uuid) -> String {
+    format!("{TAG_PREFIX}{tag}#{ENTRY_PREFIX}{entry_id}")
+}
+
+/// Generate the sort key prefix for querying all entries with a tag.
+///
+/// Pattern: `TAG#<tag>#`
+pub fn entry_tag_sk_prefix(tag: &str) -> String {
+    format!("{TAG_PREFIX}{tag}#")
+}
+
+// ============================================================================
+// Entry search term keys
+// ============================================================================
+
+/// Generate primary key for an entry search term.
+///
+/// Pattern: `CAL#<calendar_id>` (shares partition with the calendar)
+pub fn entry_term_pk(calendar_id: Uuid) -> String {
+    format!("{CALENDAR_PREFIX}{calendar_id}")
+}
+
+/// Generate sort key for an entry search term.
+///
+/// Pattern: `TERM#<word>#ENTRY#<entry_id>`
+pub fn entry_term_sk(word: &str, entry_id: Uuid) -> String {
+    format!("{TERM_PREFIX}{word}#{ENTRY_PREFIX}{entry_id}")
+}
+
+/// Generate the sort key prefix for querying entries with a word starting with `prefix`.
+///
+/// Pattern: `TERM#<prefix>`
+///
+/// Unlike tag prefixes there is no trailing `#`, so "dent" matches "dentist".
+pub fn entry_term_sk_prefix(prefix: &str) -> String {
+    format!("{TERM_PREFIX}{prefix}")
+}
+
+// ============================================================================
+// Reminder delivery keys
+// ============================================================================
+
+/// Generate primary key for a reminder delivery.
+///
+/// Pattern: `REMINDER#<entry_id>`
+pub fn reminder_delivery_pk(entry_id: Uuid) -> String {
+    format!("{REMINDER_PREFIX}{entry_id}")
+}
+
+/// Generate sort key for a reminder delivery.
+///
+/// Pattern: `FIRED#<fire_at>` (RFC 3339, UTC, whole seconds)
+pub fn reminder_delivery_sk(fire_at: DateTime<Utc>) -> String {
+    format!(
+        "{FIRED_PREFIX}{}",
+        fire_at.to_rfc3339_opts(SecondsFormat::Secs, true)
+    )
+}
+
+// ============================================================================
+// Occurrence override keys
+// ============================================================================
+
+/// Generate primary key for an OccurrenceOverride.
+///
+/// Pattern: `ENTRY#<series_id>` (shares partition with its series)
+pub fn override_pk(series_id: Uuid) -> String {
+    format!("{ENTRY_PREFIX}{series_id}")
+}
+
+/// Generate sort key for an OccurrenceOverride.
+///
+/// Pattern: `OVERRIDE#<original_date>`
+pub fn override_sk(original_date: NaiveDate) -> String {
+    format!("{OVERRIDE_PREFIX}{}", original_date.format("%Y-%m-%d"))
+}
+
+/// Generate the sort key prefix for querying all overrides of a series.
+///
+/// Pattern: `OVERRIDE#`
+pub fn override_sk_prefix() -> &'static str {
+    OVERRIDE_PREFIX
+}
+
+/// Generate the maximum sort key for override range queries.
+///
+/// Pattern: `OVERRIDE#~`
+pub fn override_sk_max() -> String {
+    format!("{OVERRIDE_PREFIX}~")
+}
+
+// ============================================================================
+// Entry revision keys
+// ============================================================================
+
+/// Generate primary key for an EntryRevision.
+///
+/// Pattern: `ENTRY#<entry_id>` (shares partition with its entry)
+pub fn entry_revision_pk(entry_id: Uuid) -> String {
+    format!("{ENTRY_PREFIX}{entry_id}")
+}
+
+/// Generate sort key for an EntryRevision.
+///
+/// Pattern: `REV#<revision>` (zero-padded so revisions sort numerically)
+pub fn entry_revision_sk(revision: u32) -> String {
+    format!("{REVISION_PREFIX}{revision:010}")
+}
+
+/// Generate the sort key prefix for querying all revisions of an entry.
+///
+/// Pattern: `REV#`
+pub fn entry_revision_sk_prefix() -> &'static str {
+    REVISION_PREFIX
+}
+
 // ============================================================================
 // Membership keys
 // ============================================================================
@@ -207,6 +402,39 @@ pub fn settings_sk(user_id: Uuid) -> String {
     format!("{SETTINGS_PREFIX}{user_id}")
 }
 
+// ============================================================================
+// Feed token keys
+// ============================================================================
+
+/// Generate primary key for a FeedToken.
+///
+/// Pattern: `CAL#<calendar_id>` (shares partition with the calendar, so purging
+/// the calendar deletes its tokens)
+pub fn feed_token_pk(calendar_id: Uuid) -> String {
+    format!("{CALENDAR_PREFIX}{calendar_id}")
+}
+
+/// Generate sort key for a FeedToken.
+///
+/// Pattern: `FEED#<user_id>` (one token per user and calendar)
+pub fn feed_token_sk(user_id: Uuid) -> String {
+    format!("{FEED_PREFIX}{user_id}")
+}
+
+/// Generate GSI2 partition key for FeedToken lookup by secret.
+///
+/// Pattern: `FEED#<token>`
+pub fn feed_token_gsi2_pk(token: &str) -> String {
+    format!("{FEED_PREFIX}{token}")
+}
+
+/// Generate GSI2 sort key for a FeedToken.
+///
+/// Pattern: `CAL#<calendar_id>`
+pub fn feed_token_gsi2_sk(calendar_id: Uuid) -> String {
+    format!("{CALENDAR_PREFIX}{calendar_id}")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -323,9 +551,106 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_feed_token_keys() {
+        let calendar_id = Uuid::parse_str("550e8400-e29b-41d4-a716-446655440002").unwrap();
+        let user_id = Uuid::parse_str("550e8400-e29b-41d4-a716-446655440001").unwrap();
+        assert_eq!(
+            feed_token_pk(calendar_id),
+            "CAL#550e8400-e29b-41d4-a716-446655440002"
+        );
+        assert_eq!(
+            feed_token_sk(user_id),
+            "FEED#550e8400-e29b-41d4-a716-446655440001"
+        );
+        assert_eq!(feed_token_gsi2_pk("abc123"), "FEED#abc123");
+    }
+
     #[test]
     fn test_prefixes() {
         assert_eq!(membership_sk_prefix(), "MEMBER#");
         assert_eq!(calendar_gsi1_sk_prefix(), "CAL#");
     }
+
+    #[test]
+    fn test_override_keys() {
+        let id = Uuid::parse_str("550e8400-e29b-41d4-a716-446655440003").unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+        assert_eq!(override_pk(id), entry_pk(id));
+        assert_eq!(override_sk(date), "OVERRIDE#2024-06-10");
+        assert!(override_sk(date).starts_with(override_sk_prefix()));
+        assert!(override_sk(date) < override_sk_max());
+    }
+
+    #[test]
+    fn test_entry_revision_keys() {
+        let id = Uuid::parse_str("550e8400-e29b-41d4-a716-446655440003").unwrap();
+        assert_eq!(entry_revision_pk(id), entry_pk(id));
+        assert_eq!(entry_revision_sk(7), "REV#0000000007");
+        assert!(entry_revision_sk(9) < entry_revision_sk(10));
+        assert!(entry_revision_sk(1).starts_with(entry_revision_sk_prefix()));
+    }
+
+    #[test]
+    fn test_entry_tag_keys() {
+        let calendar_id = Uuid::parse_str("550e8400-e29b-41d4-a716-446655440002").unwrap();
+        let entry_id = Uuid::parse_str("550e8400-e29b-41d4-a716-446655440003").unwrap();
+        assert_eq!(entry_tag_pk(calendar_id), calendar_pk(calendar_id));
+        assert_eq!(
+            entry_tag_sk("travel", entry_id),
+            "TAG#travel#ENTRY#550e8400-e29b-41d4-a716-446655440003"
+        );
+        assert!(entry_tag_sk("travel", entry_id).starts_with(&entry_tag_sk_prefix("travel")));
+        // A tag's prefix does not match longer tags that start the same way
+        assert!(!entry_tag_sk("travel-work", entry_id).starts_with(&entry_tag_sk_prefix("travel")));
+    }
+
+    #[test]
+    fn test_entry_term_keys() {
+        let calendar_id = Uuid::parse_str("550e8400-e29b-41d4-a716-446655440001").unwrap();
+        let entry_id = Uuid::parse_str("550e8400-e29b-41d4-a716-446655440003").unwrap();
+
+        assert_eq!(entry_term_pk(calendar_id), calendar_pk(calendar_id));
+        assert_eq!(
+            entry_term_sk("dentist", entry_id),
+            "TERM#dentist#ENTRY#550e8400-e29b-41d4-a716-446655440003"
+        );
+        assert!(entry_term_sk("dentist", entry_id).starts_with(&entry_term_sk_prefix("dent")));
+        assert!(!entry_term_sk("dentist", entry_id).starts_with(&entry_term_sk_prefix("ortho")));
+    }
+
+    #[test]
+    fn test_reminder_delivery_keys() {
+        use chrono::TimeZone;
+
+        let id = Uuid::parse_str("550e8400-e29b-41d4-a716-446655440004").unwrap();
+        let fire_at = Utc.with_ymd_and_hms(2024, 6, 10, 8, 50, 0).unwrap();
+        assert_eq!(
+            reminder_delivery_pk(id),
+            "REMINDER#550e8400-e29b-41d4-a716-446655440004"
+        );
+        assert_eq!(reminder_delivery_sk(fire_at), "FIRED#2024-06-10T08:50:00Z");
+    }
+
+    #[test]
+    fn test_trash_keys() {
+        use chrono::TimeZone;
+
+        let calendar_id = Uuid::parse_str("550e8400-e29b-41d4-a716-446655440002").unwrap();
+        let id = Uuid::parse_str("550e8400-e29b-41d4-a716-446655440003").unwrap();
+        let deleted_at = Utc.with_ymd_and_hms(2024, 6, 10, 8, 50, 0).unwrap();
+
+        assert_eq!(
+            trashed_entry_gsi1_pk(calendar_id),
+            "TRASH#550e8400-e29b-41d4-a716-446655440002"
+        );
+        assert_eq!(
+            trash_sk(deleted_at, id),
+            "2024-06-10T08:50:00.000000Z#550e8400-e29b-41d4-a716-446655440003"
+        );
+        // Only items deleted strictly before the cutoff sort below it
+        assert!(trash_sk(deleted_at, id) > trash_sk_before(deleted_at));
+        let later = deleted_at + chrono::Duration::microseconds(1);
+        assert!(trash_sk(deleted_at, id) < trash_sk_before(later));
+    }
 }
@@ -2,28 +2,84 @@
 //!
 //! Implements the repository traits from `calendsync_core::storage` using DynamoDB.
 
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::future::Future;
+
 use async_trait::async_trait;
-use aws_sdk_dynamodb::types::AttributeValue;
+use aws_config::retry::RetryConfig;
+use aws_sdk_dynamodb::types::{AttributeValue, Put, ReturnValue, Select, TransactWriteItem};
 use aws_sdk_dynamodb::Client;
+use chrono::{DateTime, NaiveDate, Utc};
 use uuid::Uuid;
 
 use calendsync_core::calendar::{
-    Calendar, CalendarEntry, CalendarMembership, CalendarRole, CalendarSettings, User,
+    duplicate_contents, entry_search_terms, expand_recurring_entries, matches_any_tag,
+    rank_entries, Calendar, CalendarEntry, CalendarMembership, CalendarRole, CalendarSettings,
+    CalendarSubscription, DuplicateOptions, DuplicateSummary, EntryRevision, EntryWrite, FeedToken,
+    OccurrenceOverride, SearchQuery, Tag, TrashedCalendar, TrashedEntry, User, INITIAL_VERSION,
 };
 use calendsync_core::storage::{
-    CalendarRepository, DateRange, EntryRepository, MembershipRepository, Result,
-    SettingsRepository, UserRepository,
+    CalendarRepository, DateRange, EntryRepository, FeedTokenRepository, MembershipRepository,
+    RepositoryError, Result, SettingsRepository, UserRepository,
 };
 
 use super::conversions::{
-    calendar_to_item, entry_to_item, item_to_calendar, item_to_entry, item_to_membership,
-    item_to_settings, item_to_user, membership_to_item, settings_to_item, user_to_item,
+    calendar_to_item, entry_revision_to_item, entry_tag_item_entry_id, entry_tag_to_item,
+    entry_term_item_entry_id, entry_term_to_item, entry_to_item, feed_token_to_item, is_trashed,
+    item_to_calendar, item_to_entry, item_to_entry_revision, item_to_feed_token,
+    item_to_membership, item_to_override, item_to_settings, item_to_trashed_calendar,
+    item_to_trashed_entry, item_to_user, membership_to_item, override_to_item,
+    reminder_delivery_to_item, settings_to_item, trashed_calendar_to_item, trashed_entry_to_item,
+    user_to_item, ENTITY_TYPE_CALENDAR, ENTITY_TYPE_USER,
 };
 use super::error::{
-    map_delete_item_error, map_get_item_error, map_put_item_error, map_query_error,
+    map_delete_item_error, map_get_item_error, map_put_item_error, map_query_error, map_scan_error,
+    map_transact_write_error, map_versioned_put_item_error,
 };
 use super::keys;
 
+/// Condition for overwriting a live item whose stored version is `:expected`.
+///
+/// Items written before versions existed have no `version` attribute and
+/// count as the initial version.
+fn version_condition(expected: u64) -> &'static str {
+    if expected == INITIAL_VERSION {
+        "attribute_not_exists(deletedAt) AND (#version = :expected OR (attribute_exists(PK) AND attribute_not_exists(#version)))"
+    } else {
+        "attribute_not_exists(deletedAt) AND #version = :expected"
+    }
+}
+
+/// A DynamoDB item as the SDK returns it.
+type Item = HashMap<String, AttributeValue>;
+
+/// Collects every item of a paginated query or scan.
+///
+/// `fetch` reads the page starting after the given key and returns its items
+/// along with the key to resume from, which is `None` on the last page.
+async fn collect_pages<F, Fut>(mut fetch: F) -> Result<Vec<Item>>
+where
+    F: FnMut(Option<Item>) -> Fut,
+    Fut: Future<Output = Result<(Vec<Item>, Option<Item>)>>,
+{
+    let mut items = Vec::new();
+    let mut start_key = None;
+    loop {
+        let (page, last_key) = fetch(start_key).await?;
+        items.extend(page);
+        start_key = last_key;
+        if start_key.is_none() {
+            return Ok(items);
+        }
+    }
+}
+
+/// Most items DynamoDB accepts in a single `TransactWriteItems` call.
+const TRANSACT_CHUNK_SIZE: usize = 100;
+
+/// Attempts per request unless `DYNAMODB_MAX_ATTEMPTS` says otherwise.
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
 /// DynamoDB-based repository implementation.
 ///
 /// Provides async access to DynamoDB storage for all entity types.
@@ -45,8 +101,17 @@ impl DynamoDbRepository {
     ///
     /// Uses AWS SDK default credential chain and reads table name from
     /// `DYNAMODB_TABLE_NAME` environment variable (defaults to "calendsync").
+    /// Throttled requests are retried with adaptive backoff, up to
+    /// `DYNAMODB_MAX_ATTEMPTS` attempts (defaults to 3).
     pub async fn from_env() -> Result<Self> {
-        let config = aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await;
+        let max_attempts = std::env::var("DYNAMODB_MAX_ATTEMPTS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_MAX_ATTEMPTS);
+        let config = aws_config::defaults(aws_config::BehaviorVersion::latest())
+            .retry_config(RetryConfig::adaptive().with_max_attempts(max_attempts))
+            .load()
+            .await;
         let client = Client::new(&config);
         let table_name =
             std::env::var("DYNAMODB_TABLE_NAME").unwrap_or_else(|_| "calendsync".to_string());
@@ -58,6 +123,593 @@ impl DynamoDbRepository {
     pub fn table_name(&self) -> &str {
         &self.table_name
     }
+
+    /// Deletes a series' override items whose sort key is at or after `from_sk`.
+    async fn delete_overrides_from_sk(&self, series_id: Uuid, from_sk: String) -> Result<()> {
+        let result = self
+            .client
+            .query()
+            .table_name(&self.table_name)
+            .key_condition_expression("PK = :pk AND SK BETWEEN :from_sk AND :max_sk")
+            .expression_attribute_values(":pk", AttributeValue::S(keys::override_pk(series_id)))
+            .expression_attribute_values(":from_sk", AttributeValue::S(from_sk))
+            .expression_attribute_values(":max_sk", AttributeValue::S(keys::override_sk_max()))
+            .projection_expression("PK, SK")
+            .send()
+            .await
+            .map_err(map_query_error)?;
+
+        for item in result.items.unwrap_or_default() {
+            let (Some(pk), Some(sk)) = (item.get("PK"), item.get("SK")) else {
+                continue;
+            };
+            self.client
+                .delete_item()
+                .table_name(&self.table_name)
+                .key("PK", pk.clone())
+                .key("SK", sk.clone())
+                .send()
+                .await
+                .map_err(|e| {
+                    map_delete_item_error(e, "OccurrenceOverride", series_id.to_string())
+                })?;
+        }
+
+        Ok(())
+    }
+
+    /// Deletes every revision item of an entry.
+    async fn delete_entry_revisions(&self, entry_id: Uuid) -> Result<()> {
+        let result = self
+            .client
+            .query()
+            .table_name(&self.table_name)
+            .key_condition_expression("PK = :pk AND begins_with(SK, :sk_prefix)")
+            .expression_attribute_values(
+                ":pk",
+                AttributeValue::S(keys::entry_revision_pk(entry_id)),
+            )
+            .expression_attribute_values(
+                ":sk_prefix",
+                AttributeValue::S(keys::entry_revision_sk_prefix().to_string()),
+            )
+            .projection_expression("PK, SK")
+            .send()
+            .await
+            .map_err(map_query_error)?;
+
+        for item in result.items.unwrap_or_default() {
+            let (Some(pk), Some(sk)) = (item.get("PK"), item.get("SK")) else {
+                continue;
+            };
+            self.client
+                .delete_item()
+                .table_name(&self.table_name)
+                .key("PK", pk.clone())
+                .key("SK", sk.clone())
+                .send()
+                .await
+                .map_err(|e| map_delete_item_error(e, "EntryRevision", entry_id.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Brings an entry's tag index items in line with its current tags.
+    ///
+    /// `previous` is the entry as stored before the write, if any. Tag items
+    /// the entry no longer needs are deleted; current ones are (re)written.
+    /// Pass `None` for `current` when the entry was deleted.
+    async fn sync_entry_tags(
+        &self,
+        current: Option<&CalendarEntry>,
+        previous: Option<&CalendarEntry>,
+    ) -> Result<()> {
+        if let Some(previous) = previous {
+            let stale = previous.tags.iter().filter(|tag| match current {
+                Some(current) => {
+                    current.calendar_id != previous.calendar_id || !current.tags.contains(*tag)
+                }
+                None => true,
+            });
+            for tag in stale {
+                self.client
+                    .delete_item()
+                    .table_name(&self.table_name)
+                    .key(
+                        "PK",
+                        AttributeValue::S(keys::entry_tag_pk(previous.calendar_id)),
+                    )
+                    .key(
+                        "SK",
+                        AttributeValue::S(keys::entry_tag_sk(tag.as_str(), previous.id)),
+                    )
+                    .send()
+                    .await
+                    .map_err(|e| map_delete_item_error(e, "EntryTag", previous.id.to_string()))?;
+            }
+        }
+
+        if let Some(entry) = current {
+            for tag in &entry.tags {
+                self.client
+                    .put_item()
+                    .table_name(&self.table_name)
+                    .set_item(Some(entry_tag_to_item(entry, tag)))
+                    .send()
+                    .await
+                    .map_err(|e| map_put_item_error(e, "EntryTag", entry.id.to_string()))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Brings an entry's search term items in line with its current text.
+    ///
+    /// Works like [`Self::sync_entry_tags`], with one item per distinct word
+    /// of the entry's title, description and location.
+    async fn sync_entry_terms(
+        &self,
+        current: Option<&CalendarEntry>,
+        previous: Option<&CalendarEntry>,
+    ) -> Result<()> {
+        let current_terms = current.map(entry_search_terms).unwrap_or_default();
+
+        if let Some(previous) = previous {
+            let moved = current.is_some_and(|c| c.calendar_id != previous.calendar_id);
+            let stale = entry_search_terms(previous)
+                .into_iter()
+                .filter(|term| moved || !current_terms.contains(term));
+            for term in stale {
+                self.client
+                    .delete_item()
+                    .table_name(&self.table_name)
+                    .key(
+                        "PK",
+                        AttributeValue::S(keys::entry_term_pk(previous.calendar_id)),
+                    )
+                    .key(
+                        "SK",
+                        AttributeValue::S(keys::entry_term_sk(&term, previous.id)),
+                    )
+                    .send()
+                    .await
+                    .map_err(|e| map_delete_item_error(e, "EntryTerm", previous.id.to_string()))?;
+            }
+        }
+
+        if let Some(entry) = current {
+            for term in &current_terms {
+                self.client
+                    .put_item()
+                    .table_name(&self.table_name)
+                    .set_item(Some(entry_term_to_item(entry, term)))
+                    .send()
+                    .await
+                    .map_err(|e| map_put_item_error(e, "EntryTerm", entry.id.to_string()))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the IDs of entries in a calendar with a word starting with `prefix`.
+    async fn get_entry_ids_by_term_prefix(
+        &self,
+        calendar_id: Uuid,
+        prefix: &str,
+    ) -> Result<Vec<Uuid>> {
+        let result = self
+            .client
+            .query()
+            .table_name(&self.table_name)
+            .key_condition_expression("PK = :pk AND begins_with(SK, :sk_prefix)")
+            .expression_attribute_values(":pk", AttributeValue::S(keys::entry_term_pk(calendar_id)))
+            .expression_attribute_values(
+                ":sk_prefix",
+                AttributeValue::S(keys::entry_term_sk_prefix(prefix)),
+            )
+            .send()
+            .await
+            .map_err(map_query_error)?;
+
+        result
+            .items
+            .unwrap_or_default()
+            .iter()
+            .map(entry_term_item_entry_id)
+            .collect()
+    }
+
+    /// Returns the IDs of entries in a calendar carrying `tag`.
+    async fn get_entry_ids_by_tag(&self, calendar_id: Uuid, tag: &Tag) -> Result<Vec<Uuid>> {
+        let result = self
+            .client
+            .query()
+            .table_name(&self.table_name)
+            .key_condition_expression("PK = :pk AND begins_with(SK, :sk_prefix)")
+            .expression_attribute_values(":pk", AttributeValue::S(keys::entry_tag_pk(calendar_id)))
+            .expression_attribute_values(
+                ":sk_prefix",
+                AttributeValue::S(keys::entry_tag_sk_prefix(tag.as_str())),
+            )
+            .send()
+            .await
+            .map_err(map_query_error)?;
+
+        result
+            .items
+            .unwrap_or_default()
+            .iter()
+            .map(entry_tag_item_entry_id)
+            .collect()
+    }
+}
+
+impl DynamoDbRepository {
+    /// Gets an item by its primary key, trashed or not.
+    async fn get_item(
+        &self,
+        pk: String,
+        sk: String,
+        entity_type: &'static str,
+        id: Uuid,
+    ) -> Result<Option<HashMap<String, AttributeValue>>> {
+        let result = self
+            .client
+            .get_item()
+            .table_name(&self.table_name)
+            .key("PK", AttributeValue::S(pk))
+            .key("SK", AttributeValue::S(sk))
+            .send()
+            .await
+            .map_err(|e| map_get_item_error(e, entity_type, id.to_string()))?;
+
+        Ok(result.item)
+    }
+
+    /// Returns every GSI1 item in a partition, following query pages to the end.
+    async fn query_gsi1(&self, pk: String) -> Result<Vec<Item>> {
+        let pk = &pk;
+        collect_pages(|start_key| async move {
+            let result = self
+                .client
+                .query()
+                .table_name(&self.table_name)
+                .index_name("GSI1")
+                .key_condition_expression("GSI1PK = :pk")
+                .expression_attribute_values(":pk", AttributeValue::S(pk.clone()))
+                .set_exclusive_start_key(start_key)
+                .send()
+                .await
+                .map_err(map_query_error)?;
+
+            Ok((result.items.unwrap_or_default(), result.last_evaluated_key))
+        })
+        .await
+    }
+
+    /// Returns every item of an entity type, following scan pages to the end.
+    ///
+    /// Scans read the whole table, so only backups use this.
+    async fn scan_entities(&self, entity_type: &str) -> Result<Vec<Item>> {
+        collect_pages(|start_key| async move {
+            let result = self
+                .client
+                .scan()
+                .table_name(&self.table_name)
+                .filter_expression("entityType = :type")
+                .expression_attribute_values(":type", AttributeValue::S(entity_type.to_string()))
+                .set_exclusive_start_key(start_key)
+                .send()
+                .await
+                .map_err(map_scan_error)?;
+
+            Ok((result.items.unwrap_or_default(), result.last_evaluated_key))
+        })
+        .await
+    }
+
+    /// Returns the items of a GSI2 trash partition deleted before `cutoff`,
+    /// following query pages to the end.
+    async fn query_trash_before(&self, gsi2_pk: &str, cutoff: DateTime<Utc>) -> Result<Vec<Item>> {
+        let cutoff = &keys::trash_sk_before(cutoff);
+        collect_pages(|start_key| async move {
+            let result = self
+                .client
+                .query()
+                .table_name(&self.table_name)
+                .index_name("GSI2")
+                .key_condition_expression("GSI2PK = :pk AND GSI2SK < :cutoff")
+                .expression_attribute_values(":pk", AttributeValue::S(gsi2_pk.to_string()))
+                .expression_attribute_values(":cutoff", AttributeValue::S(cutoff.clone()))
+                .set_exclusive_start_key(start_key)
+                .send()
+                .await
+                .map_err(map_query_error)?;
+
+            Ok((result.items.unwrap_or_default(), result.last_evaluated_key))
+        })
+        .await
+    }
+
+    /// Stores `entry` as the version after `expected`, provided the live entry
+    /// is still at `expected`, and re-indexes its tags and words.
+    async fn put_entry_if_version(&self, entry: &CalendarEntry, expected: u64) -> Result<()> {
+        let next = CalendarEntry {
+            version: expected + 1,
+            ..entry.clone()
+        };
+
+        let result = self
+            .client
+            .put_item()
+            .table_name(&self.table_name)
+            .set_item(Some(entry_to_item(&next)?))
+            .condition_expression(version_condition(expected))
+            .expression_attribute_names("#version", "version")
+            .expression_attribute_values(":expected", AttributeValue::N(expected.to_string()))
+            .return_values(ReturnValue::AllOld)
+            .send()
+            .await
+            .map_err(|e| {
+                map_versioned_put_item_error(e, "CalendarEntry", entry.id.to_string(), expected)
+            });
+        let output = match result {
+            Err(RepositoryError::VersionMismatch { .. })
+                if self.get_entry(entry.id).await?.is_none() =>
+            {
+                return Err(RepositoryError::NotFound {
+                    entity_type: "CalendarEntry",
+                    id: entry.id.to_string(),
+                });
+            }
+            result => result?,
+        };
+
+        let previous = output.attributes.as_ref().map(item_to_entry).transpose()?;
+        self.sync_entry_tags(Some(&next), previous.as_ref()).await?;
+        self.sync_entry_terms(Some(&next), previous.as_ref()).await
+    }
+
+    /// Stores `calendar` as the version after `expected`, provided the live
+    /// calendar is still at `expected`.
+    async fn put_calendar_if_version(&self, calendar: &Calendar, expected: u64) -> Result<()> {
+        let next = Calendar {
+            version: expected + 1,
+            ..calendar.clone()
+        };
+        self.put_calendar_at_version(&next, expected).await
+    }
+
+    /// Stores `calendar` with the version it carries, provided the live
+    /// calendar is still at `expected`.
+    async fn put_calendar_at_version(&self, calendar: &Calendar, expected: u64) -> Result<()> {
+        let result = self
+            .client
+            .put_item()
+            .table_name(&self.table_name)
+            .set_item(Some(calendar_to_item(calendar)))
+            .condition_expression(version_condition(expected))
+            .expression_attribute_names("#version", "version")
+            .expression_attribute_values(":expected", AttributeValue::N(expected.to_string()))
+            .send()
+            .await
+            .map_err(|e| {
+                map_versioned_put_item_error(e, "Calendar", calendar.id.to_string(), expected)
+            });
+        match result {
+            Err(RepositoryError::VersionMismatch { .. })
+                if self.get_calendar(calendar.id).await?.is_none() =>
+            {
+                Err(RepositoryError::NotFound {
+                    entity_type: "Calendar",
+                    id: calendar.id.to_string(),
+                })
+            }
+            result => result.map(|_| ()),
+        }
+    }
+
+    /// Returns every membership item of a calendar.
+    async fn query_memberships(&self, calendar_id: Uuid) -> Result<Vec<CalendarMembership>> {
+        let result = self
+            .client
+            .query()
+            .table_name(&self.table_name)
+            .key_condition_expression("PK = :pk AND begins_with(SK, :sk_prefix)")
+            .expression_attribute_values(":pk", AttributeValue::S(keys::membership_pk(calendar_id)))
+            .expression_attribute_values(
+                ":sk_prefix",
+                AttributeValue::S(keys::membership_sk_prefix().to_string()),
+            )
+            .send()
+            .await
+            .map_err(map_query_error)?;
+
+        result
+            .items
+            .unwrap_or_default()
+            .iter()
+            .map(item_to_membership)
+            .collect()
+    }
+
+    /// Builds a put that only succeeds if the item doesn't exist yet.
+    fn new_item_put(&self, item: HashMap<String, AttributeValue>) -> Result<TransactWriteItem> {
+        let put = Put::builder()
+            .table_name(&self.table_name)
+            .set_item(Some(item))
+            .condition_expression("attribute_not_exists(PK)")
+            .build()
+            .map_err(|e| RepositoryError::QueryFailed(e.to_string()))?;
+
+        Ok(TransactWriteItem::builder().put(put).build())
+    }
+
+    /// Builds the conditional put that applies one batched write.
+    ///
+    /// `stored` is the live entry a delete moves to the trash.
+    fn batch_write_item(
+        &self,
+        write: &EntryWrite,
+        stored: Option<&CalendarEntry>,
+        deleted_at: DateTime<Utc>,
+    ) -> Result<TransactWriteItem> {
+        let put = Put::builder().table_name(&self.table_name);
+        let put = match write {
+            EntryWrite::Create(entry) => put
+                .set_item(Some(entry_to_item(entry)?))
+                .condition_expression("attribute_not_exists(PK)"),
+            EntryWrite::Update {
+                entry,
+                expected_version,
+            } => {
+                let next = CalendarEntry {
+                    version: expected_version + 1,
+                    ..entry.clone()
+                };
+                put.set_item(Some(entry_to_item(&next)?))
+                    .condition_expression(version_condition(*expected_version))
+                    .expression_attribute_names("#version", "version")
+                    .expression_attribute_values(
+                        ":expected",
+                        AttributeValue::N(expected_version.to_string()),
+                    )
+            }
+            EntryWrite::Delete(id) => {
+                let stored = stored.ok_or_else(|| RepositoryError::NotFound {
+                    entity_type: "CalendarEntry",
+                    id: id.to_string(),
+                })?;
+                put.set_item(Some(trashed_entry_to_item(stored, deleted_at)?))
+                    .condition_expression(
+                        "attribute_exists(PK) AND attribute_not_exists(deletedAt)",
+                    )
+            }
+        };
+        let put = put
+            .build()
+            .map_err(|e| RepositoryError::QueryFailed(e.to_string()))?;
+
+        Ok(TransactWriteItem::builder().put(put).build())
+    }
+
+    /// Moves a live entry to the trash and drops it from the tag and search indexes.
+    async fn trash_entry(&self, entry: &CalendarEntry, deleted_at: DateTime<Utc>) -> Result<()> {
+        self.client
+            .put_item()
+            .table_name(&self.table_name)
+            .set_item(Some(trashed_entry_to_item(entry, deleted_at)?))
+            .condition_expression("attribute_exists(PK) AND attribute_not_exists(deletedAt)")
+            .send()
+            .await
+            .map_err(|e| map_put_item_error(e, "CalendarEntry", entry.id.to_string()))?;
+
+        self.sync_entry_tags(None, Some(entry)).await?;
+        self.sync_entry_terms(None, Some(entry)).await
+    }
+
+    /// Moves a live entry to the trash, provided it is still at `expected`.
+    async fn trash_entry_if_version(&self, entry: &CalendarEntry, expected: u64) -> Result<()> {
+        let result = self
+            .client
+            .put_item()
+            .table_name(&self.table_name)
+            .set_item(Some(trashed_entry_to_item(entry, Utc::now())?))
+            .condition_expression(version_condition(expected))
+            .expression_attribute_names("#version", "version")
+            .expression_attribute_values(":expected", AttributeValue::N(expected.to_string()))
+            .send()
+            .await
+            .map_err(|e| {
+                map_versioned_put_item_error(e, "CalendarEntry", entry.id.to_string(), expected)
+            });
+        match result {
+            Err(RepositoryError::VersionMismatch { .. })
+                if self.get_entry(entry.id).await?.is_none() =>
+            {
+                return Err(RepositoryError::NotFound {
+                    entity_type: "CalendarEntry",
+                    id: entry.id.to_string(),
+                });
+            }
+            result => result?,
+        };
+
+        self.sync_entry_tags(None, Some(entry)).await?;
+        self.sync_entry_terms(None, Some(entry)).await
+    }
+
+    /// Brings a trashed entry back and re-indexes its tags and words.
+    async fn untrash_entry(&self, entry: &CalendarEntry) -> Result<()> {
+        self.client
+            .put_item()
+            .table_name(&self.table_name)
+            .set_item(Some(entry_to_item(entry)?))
+            .condition_expression("attribute_exists(deletedAt)")
+            .send()
+            .await
+            .map_err(|e| map_put_item_error(e, "TrashedEntry", entry.id.to_string()))?;
+
+        self.sync_entry_tags(Some(entry), None).await?;
+        self.sync_entry_terms(Some(entry), None).await
+    }
+
+    /// Permanently deletes a trashed entry, its overrides and its revision
+    /// history.
+    ///
+    /// Its tag and search term items went when it was trashed.
+    async fn purge_entry(&self, id: Uuid) -> Result<()> {
+        self.client
+            .delete_item()
+            .table_name(&self.table_name)
+            .key("PK", AttributeValue::S(keys::entry_pk(id)))
+            .key("SK", AttributeValue::S(keys::entry_sk(id)))
+            .send()
+            .await
+            .map_err(|e| map_delete_item_error(e, "CalendarEntry", id.to_string()))?;
+
+        self.delete_overrides_from_sk(id, keys::override_sk_prefix().to_string())
+            .await?;
+        self.delete_entry_revisions(id).await
+    }
+
+    /// Permanently deletes a trashed calendar, its entries and everything in
+    /// its partition (memberships, settings and feed tokens).
+    async fn purge_calendar(&self, id: Uuid) -> Result<()> {
+        for item in self.query_gsi1(keys::trashed_entry_gsi1_pk(id)).await? {
+            self.purge_entry(item_to_entry(&item)?.id).await?;
+        }
+
+        let result = self
+            .client
+            .query()
+            .table_name(&self.table_name)
+            .key_condition_expression("PK = :pk")
+            .expression_attribute_values(":pk", AttributeValue::S(keys::calendar_pk(id)))
+            .projection_expression("PK, SK")
+            .send()
+            .await
+            .map_err(map_query_error)?;
+
+        for item in result.items.unwrap_or_default() {
+            let (Some(pk), Some(sk)) = (item.get("PK"), item.get("SK")) else {
+                continue;
+            };
+            self.client
+                .delete_item()
+                .table_name(&self.table_name)
+                .key("PK", pk.clone())
+                .key("SK", sk.clone())
+                .send()
+                .await
+                .map_err(|e| map_delete_item_error(e, "Calendar", id.to_string()))?;
+        }
+
+        Ok(())
+    }
 }
 
 // ============================================================================
@@ -67,39 +719,495 @@ impl DynamoDbRepository {
 #[async_trait]
 impl EntryRepository for DynamoDbRepository {
     async fn get_entry(&self, id: Uuid) -> Result<Option<CalendarEntry>> {
+        let item = self
+            .get_item(keys::entry_pk(id), keys::entry_sk(id), "CalendarEntry", id)
+            .await?;
+
+        match item {
+            Some(item) if !is_trashed(&item) => Ok(Some(item_to_entry(&item)?)),
+            _ => Ok(None),
+        }
+    }
+
+    async fn get_entries_by_calendar(
+        &self,
+        calendar_id: Uuid,
+        date_range: DateRange,
+    ) -> Result<Vec<CalendarEntry>> {
+        // Overlap query strategy:
+        // 1. Key condition: entries starting on or before query end (GSI1SK <= max)
+        // 2. Filter: entries ending on or after query start (end_date >= query_start)
+        //    or recurring series, whose later occurrences may still overlap
+        // This captures all entries that overlap with the query range.
         let result = self
             .client
-            .get_item()
+            .query()
+            .table_name(&self.table_name)
+            .index_name("GSI1")
+            .key_condition_expression("GSI1PK = :pk AND GSI1SK <= :max_sk")
+            .filter_expression("end_date >= :query_start OR attribute_exists(recurrence)")
+            .expression_attribute_values(":pk", AttributeValue::S(keys::entry_gsi1_pk(calendar_id)))
+            .expression_attribute_values(
+                ":max_sk",
+                AttributeValue::S(keys::entry_gsi1_sk_max(date_range.end)),
+            )
+            .expression_attribute_values(
+                ":query_start",
+                AttributeValue::S(date_range.start.to_string()),
+            )
+            .send()
+            .await
+            .map_err(map_query_error)?;
+
+        let items = result.items.unwrap_or_default();
+        let entries = items
+            .iter()
+            .map(item_to_entry)
+            .collect::<Result<Vec<_>>>()?;
+
+        // Overrides live in each series' own partition: one query per recurring series.
+        let mut overrides = Vec::new();
+        for series in entries.iter().filter(|e| e.is_recurring()) {
+            overrides.extend(self.get_occurrence_overrides(series.id).await?);
+        }
+
+        Ok(expand_recurring_entries(entries, &overrides, &date_range))
+    }
+
+    async fn get_entries_by_tags(
+        &self,
+        calendar_id: Uuid,
+        tags: &BTreeSet<Tag>,
+        date_range: DateRange,
+    ) -> Result<Vec<CalendarEntry>> {
+        if tags.is_empty() {
+            return self.get_entries_by_calendar(calendar_id, date_range).await;
+        }
+
+        // One query per tag in the calendar partition, then load each matching entry once.
+        let mut seen = HashSet::new();
+        let mut entries = Vec::new();
+        for tag in tags {
+            for id in self.get_entry_ids_by_tag(calendar_id, tag).await? {
+                if !seen.insert(id) {
+                    continue;
+                }
+                let Some(entry) = self.get_entry(id).await? else {
+                    continue;
+                };
+                if entry.is_recurring()
+                    || (entry.start_date <= date_range.end && entry.end_date >= date_range.start)
+                {
+                    entries.push(entry);
+                }
+            }
+        }
+
+        let mut overrides = Vec::new();
+        for series in entries.iter().filter(|e| e.is_recurring()) {
+            overrides.extend(self.get_occurrence_overrides(series.id).await?);
+        }
+
+        // Overrides can replace an occurrence's tags, so filter after expansion
+        let mut expanded = expand_recurring_entries(entries, &overrides, &date_range);
+        expanded.retain(|e| matches_any_tag(&e.tags, tags));
+        Ok(expanded)
+    }
+
+    async fn get_stored_entries_by_calendar(
+        &self,
+        calendar_id: Uuid,
+    ) -> Result<Vec<CalendarEntry>> {
+        // The calendar's GSI1 partition holds its live entries, sorted by start date
+        self.query_gsi1(keys::entry_gsi1_pk(calendar_id))
+            .await?
+            .iter()
+            .map(item_to_entry)
+            .collect()
+    }
+
+    async fn count_entries_by_calendar(&self, calendar_id: Uuid) -> Result<usize> {
+        // Counted page by page on the server, without reading the items back
+        let pk = keys::entry_gsi1_pk(calendar_id);
+        let mut count = 0;
+        let mut start_key = None;
+        loop {
+            let result = self
+                .client
+                .query()
+                .table_name(&self.table_name)
+                .index_name("GSI1")
+                .key_condition_expression("GSI1PK = :pk")
+                .expression_attribute_values(":pk", AttributeValue::S(pk.clone()))
+                .select(Select::Count)
+                .set_exclusive_start_key(start_key)
+                .send()
+                .await
+                .map_err(map_query_error)?;
+
+            count += result.count as usize;
+            start_key = result.last_evaluated_key;
+            if start_key.is_none() {
+                return Ok(count);
+            }
+        }
+    }
+
+    async fn search_entries(
+        &self,
+        calendar_ids: &[Uuid],
+        query: &SearchQuery,
+        limit: usize,
+    ) -> Result<Vec<CalendarEntry>> {
+        // The longest query word is usually the most selective: look it up in
+        // each calendar partition, then load each candidate once and let
+        // `rank_entries` check the remaining words.
+        let Some(longest) = query.terms().iter().max_by_key(|term| term.len()) else {
+            return Ok(Vec::new());
+        };
+
+        let mut seen = HashSet::new();
+        let mut candidates = Vec::new();
+        for &calendar_id in calendar_ids {
+            for id in self
+                .get_entry_ids_by_term_prefix(calendar_id, longest)
+                .await?
+            {
+                if !seen.insert(id) {
+                    continue;
+                }
+                if let Some(entry) = self.get_entry(id).await? {
+                    candidates.push(entry);
+                }
+            }
+        }
+
+        Ok(rank_entries(query, candidates, limit))
+    }
+
+    async fn create_entry(&self, entry: &CalendarEntry) -> Result<()> {
+        let item = entry_to_item(entry)?;
+
+        self.client
+            .put_item()
+            .table_name(&self.table_name)
+            .set_item(Some(item))
+            .condition_expression("attribute_not_exists(PK)")
+            .send()
+            .await
+            .map_err(|e| map_put_item_error(e, "CalendarEntry", entry.id.to_string()))?;
+
+        self.sync_entry_tags(Some(entry), None).await?;
+        self.sync_entry_terms(Some(entry), None).await
+    }
+
+    async fn update_entry(&self, entry: &CalendarEntry) -> Result<()> {
+        // Write on top of the stored version, retrying if another writer
+        // gets in between, so every write gets a version of its own
+        loop {
+            let stored =
+                self.get_entry(entry.id)
+                    .await?
+                    .ok_or_else(|| RepositoryError::NotFound {
+                        entity_type: "CalendarEntry",
+                        id: entry.id.to_string(),
+                    })?;
+            match self.put_entry_if_version(entry, stored.version).await {
+                Err(RepositoryError::VersionMismatch { .. }) => continue,
+                result => return result,
+            }
+        }
+    }
+
+    async fn update_entry_if_version(
+        &self,
+        entry: &CalendarEntry,
+        expected_version: u64,
+    ) -> Result<()> {
+        self.put_entry_if_version(entry, expected_version).await
+    }
+
+    async fn move_entry(
+        &self,
+        id: Uuid,
+        calendar_id: Uuid,
+        expected_version: u64,
+    ) -> Result<CalendarEntry> {
+        let entry = self
+            .get_entry(id)
+            .await?
+            .ok_or_else(|| RepositoryError::NotFound {
+                entity_type: "CalendarEntry",
+                id: id.to_string(),
+            })?;
+
+        // The entry moves to the new calendar's GSI1 partition, and its tag
+        // and term items are re-indexed there
+        let moved = CalendarEntry {
+            calendar_id,
+            updated_at: Utc::now(),
+            ..entry
+        };
+        self.put_entry_if_version(&moved, expected_version).await?;
+
+        Ok(CalendarEntry {
+            version: expected_version + 1,
+            ..moved
+        })
+    }
+
+    async fn delete_entry(&self, id: Uuid) -> Result<()> {
+        let entry = self
+            .get_entry(id)
+            .await?
+            .ok_or_else(|| RepositoryError::NotFound {
+                entity_type: "CalendarEntry",
+                id: id.to_string(),
+            })?;
+
+        self.trash_entry(&entry, Utc::now()).await
+    }
+
+    async fn delete_entry_if_version(&self, id: Uuid, expected_version: u64) -> Result<()> {
+        let entry = self
+            .get_entry(id)
+            .await?
+            .ok_or_else(|| RepositoryError::NotFound {
+                entity_type: "CalendarEntry",
+                id: id.to_string(),
+            })?;
+
+        self.trash_entry_if_version(&entry, expected_version).await
+    }
+
+    async fn get_trashed_entries(&self, calendar_ids: &[Uuid]) -> Result<Vec<TrashedEntry>> {
+        let mut trashed = Vec::new();
+        for &calendar_id in calendar_ids {
+            for item in self
+                .query_gsi1(keys::trashed_entry_gsi1_pk(calendar_id))
+                .await?
+            {
+                trashed.push(item_to_trashed_entry(&item)?);
+            }
+        }
+        Ok(trashed)
+    }
+
+    async fn restore_entry(&self, id: Uuid) -> Result<CalendarEntry> {
+        let item = self
+            .get_item(keys::entry_pk(id), keys::entry_sk(id), "CalendarEntry", id)
+            .await?
+            .filter(is_trashed)
+            .ok_or_else(|| RepositoryError::NotFound {
+                entity_type: "TrashedEntry",
+                id: id.to_string(),
+            })?;
+
+        let entry = item_to_entry(&item)?;
+        self.untrash_entry(&entry).await?;
+        Ok(entry)
+    }
+
+    async fn purge_entries(&self, deleted_before: DateTime<Utc>) -> Result<usize> {
+        let items = self
+            .query_trash_before(keys::TRASHED_ENTRIES_GSI2_PK, deleted_before)
+            .await?;
+        for item in &items {
+            self.purge_entry(item_to_entry(item)?.id).await?;
+        }
+        Ok(items.len())
+    }
+
+    async fn append_entry_revision(&self, revision: &EntryRevision) -> Result<()> {
+        let item = entry_revision_to_item(revision)?;
+
+        self.client
+            .put_item()
+            .table_name(&self.table_name)
+            .set_item(Some(item))
+            .condition_expression("attribute_not_exists(PK)")
+            .send()
+            .await
+            .map_err(|e| {
+                map_put_item_error(
+                    e,
+                    "EntryRevision",
+                    format!("{}@{}", revision.entry_id, revision.revision),
+                )
+            })?;
+
+        Ok(())
+    }
+
+    async fn get_entry_revisions(&self, entry_id: Uuid) -> Result<Vec<EntryRevision>> {
+        // Zero-padded sort keys come back in revision order
+        let result = self
+            .client
+            .query()
+            .table_name(&self.table_name)
+            .key_condition_expression("PK = :pk AND begins_with(SK, :sk_prefix)")
+            .expression_attribute_values(
+                ":pk",
+                AttributeValue::S(keys::entry_revision_pk(entry_id)),
+            )
+            .expression_attribute_values(
+                ":sk_prefix",
+                AttributeValue::S(keys::entry_revision_sk_prefix().to_string()),
+            )
+            .send()
+            .await
+            .map_err(map_query_error)?;
+
+        result
+            .items
+            .unwrap_or_default()
+            .iter()
+            .map(item_to_entry_revision)
+            .collect()
+    }
+
+    async fn apply_entry_batch(&self, writes: &[EntryWrite]) -> Result<()> {
+        // Updates and deletes need the stored entry, to trash it and to
+        // re-index its tags and words afterwards
+        let mut stored = HashMap::new();
+        for write in writes {
+            if matches!(write, EntryWrite::Create(_)) {
+                continue;
+            }
+            let id = write.entry_id();
+            let entry = self
+                .get_entry(id)
+                .await?
+                .ok_or_else(|| RepositoryError::NotFound {
+                    entity_type: "CalendarEntry",
+                    id: id.to_string(),
+                })?;
+            stored.insert(id, entry);
+        }
+        let deleted_at = Utc::now();
+
+        // Each chunk is one transaction, so its writes apply together or not
+        // at all. A failing chunk leaves the chunks before it applied.
+        for chunk in writes.chunks(TRANSACT_CHUNK_SIZE) {
+            let items = chunk
+                .iter()
+                .map(|write| {
+                    self.batch_write_item(write, stored.get(&write.entry_id()), deleted_at)
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            self.client
+                .transact_write_items()
+                .set_transact_items(Some(items))
+                .send()
+                .await
+                .map_err(|e| {
+                    map_transact_write_error(e, |index| {
+                        let write = &chunk[index];
+                        let id = write.entry_id().to_string();
+                        match write {
+                            EntryWrite::Create(_) => RepositoryError::AlreadyExists {
+                                entity_type: "CalendarEntry",
+                                id,
+                            },
+                            EntryWrite::Update {
+                                expected_version, ..
+                            } => RepositoryError::VersionMismatch {
+                                entity_type: "CalendarEntry",
+                                id,
+                                expected: *expected_version,
+                            },
+                            EntryWrite::Delete(_) => RepositoryError::NotFound {
+                                entity_type: "CalendarEntry",
+                                id,
+                            },
+                        }
+                    })
+                })?;
+
+            for write in chunk {
+                let (current, previous) = match write {
+                    EntryWrite::Create(entry) => (Some(entry), None),
+                    EntryWrite::Update { entry, .. } => (Some(entry), stored.get(&entry.id)),
+                    EntryWrite::Delete(id) => (None, stored.get(id)),
+                };
+                self.sync_entry_tags(current, previous).await?;
+                self.sync_entry_terms(current, previous).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn get_occurrence_overrides(&self, series_id: Uuid) -> Result<Vec<OccurrenceOverride>> {
+        let result = self
+            .client
+            .query()
+            .table_name(&self.table_name)
+            .key_condition_expression("PK = :pk AND begins_with(SK, :sk_prefix)")
+            .expression_attribute_values(":pk", AttributeValue::S(keys::override_pk(series_id)))
+            .expression_attribute_values(
+                ":sk_prefix",
+                AttributeValue::S(keys::override_sk_prefix().to_string()),
+            )
+            .send()
+            .await
+            .map_err(map_query_error)?;
+
+        result
+            .items
+            .unwrap_or_default()
+            .iter()
+            .map(item_to_override)
+            .collect()
+    }
+
+    async fn upsert_occurrence_override(&self, occurrence: &OccurrenceOverride) -> Result<()> {
+        // Overrides only make sense for an existing series
+        if self.get_entry(occurrence.series_id).await?.is_none() {
+            return Err(RepositoryError::NotFound {
+                entity_type: "CalendarEntry",
+                id: occurrence.series_id.to_string(),
+            });
+        }
+
+        let item = override_to_item(occurrence)?;
+
+        self.client
+            .put_item()
             .table_name(&self.table_name)
-            .key("PK", AttributeValue::S(keys::entry_pk(id)))
-            .key("SK", AttributeValue::S(keys::entry_sk(id)))
+            .set_item(Some(item))
             .send()
             .await
-            .map_err(|e| map_get_item_error(e, "CalendarEntry", id.to_string()))?;
+            .map_err(|e| {
+                map_put_item_error(e, "OccurrenceOverride", occurrence.series_id.to_string())
+            })?;
 
-        match result.item {
-            Some(item) => Ok(Some(item_to_entry(&item)?)),
-            None => Ok(None),
-        }
+        Ok(())
     }
 
-    async fn get_entries_by_calendar(
+    async fn delete_occurrence_overrides(&self, series_id: Uuid, from: NaiveDate) -> Result<()> {
+        self.delete_overrides_from_sk(series_id, keys::override_sk(from))
+            .await
+    }
+
+    async fn get_entries_with_reminders(
         &self,
-        calendar_id: Uuid,
         date_range: DateRange,
     ) -> Result<Vec<CalendarEntry>> {
-        // Overlap query strategy:
-        // 1. Key condition: entries starting on or before query end (GSI1SK <= max)
-        // 2. Filter: entries ending on or after query start (end_date >= query_start)
-        // This captures all entries that overlap with the query range.
+        // Same overlap strategy as `get_entries_by_calendar`, on the sparse
+        // GSI2 partition that holds every entry with reminders
         let result = self
             .client
             .query()
             .table_name(&self.table_name)
-            .index_name("GSI1")
-            .key_condition_expression("GSI1PK = :pk AND GSI1SK <= :max_sk")
-            .filter_expression("end_date >= :query_start")
-            .expression_attribute_values(":pk", AttributeValue::S(keys::entry_gsi1_pk(calendar_id)))
+            .index_name("GSI2")
+            .key_condition_expression("GSI2PK = :pk AND GSI2SK <= :max_sk")
+            .filter_expression("end_date >= :query_start OR attribute_exists(recurrence)")
+            .expression_attribute_values(
+                ":pk",
+                AttributeValue::S(keys::REMINDERS_GSI2_PK.to_string()),
+            )
             .expression_attribute_values(
                 ":max_sk",
                 AttributeValue::S(keys::entry_gsi1_sk_max(date_range.end)),
@@ -113,51 +1221,41 @@ impl EntryRepository for DynamoDbRepository {
             .map_err(map_query_error)?;
 
         let items = result.items.unwrap_or_default();
-        items.iter().map(item_to_entry).collect()
-    }
-
-    async fn create_entry(&self, entry: &CalendarEntry) -> Result<()> {
-        let item = entry_to_item(entry)?;
+        let entries = items
+            .iter()
+            .map(item_to_entry)
+            .collect::<Result<Vec<_>>>()?;
 
-        self.client
-            .put_item()
-            .table_name(&self.table_name)
-            .set_item(Some(item))
-            .condition_expression("attribute_not_exists(PK)")
-            .send()
-            .await
-            .map_err(|e| map_put_item_error(e, "CalendarEntry", entry.id.to_string()))?;
+        let mut overrides = Vec::new();
+        for series in entries.iter().filter(|e| e.is_recurring()) {
+            overrides.extend(self.get_occurrence_overrides(series.id).await?);
+        }
 
-        Ok(())
+        let mut entries = expand_recurring_entries(entries, &overrides, &date_range);
+        // An override may have removed an occurrence's reminders
+        entries.retain(CalendarEntry::has_reminders);
+        Ok(entries)
     }
 
-    async fn update_entry(&self, entry: &CalendarEntry) -> Result<()> {
-        let item = entry_to_item(entry)?;
+    async fn claim_reminder(&self, entry_id: Uuid, fire_at: DateTime<Utc>) -> Result<bool> {
+        let item = reminder_delivery_to_item(entry_id, fire_at, Utc::now());
 
-        self.client
+        // The conditional put fails for every instance but the first to claim it
+        let result = self
+            .client
             .put_item()
             .table_name(&self.table_name)
             .set_item(Some(item))
-            .condition_expression("attribute_exists(PK)")
-            .send()
-            .await
-            .map_err(|e| map_put_item_error(e, "CalendarEntry", entry.id.to_string()))?;
-
-        Ok(())
-    }
-
-    async fn delete_entry(&self, id: Uuid) -> Result<()> {
-        self.client
-            .delete_item()
-            .table_name(&self.table_name)
-            .key("PK", AttributeValue::S(keys::entry_pk(id)))
-            .key("SK", AttributeValue::S(keys::entry_sk(id)))
-            .condition_expression("attribute_exists(PK)")
+            .condition_expression("attribute_not_exists(PK)")
             .send()
             .await
-            .map_err(|e| map_delete_item_error(e, "CalendarEntry", id.to_string()))?;
+            .map_err(|e| map_put_item_error(e, "ReminderDelivery", entry_id.to_string()));
 
-        Ok(())
+        match result {
+            Ok(_) => Ok(true),
+            Err(RepositoryError::AlreadyExists { .. }) => Ok(false),
+            Err(e) => Err(e),
+        }
     }
 }
 
@@ -168,19 +1266,13 @@ impl EntryRepository for DynamoDbRepository {
 #[async_trait]
 impl CalendarRepository for DynamoDbRepository {
     async fn get_calendar(&self, id: Uuid) -> Result<Option<Calendar>> {
-        let result = self
-            .client
-            .get_item()
-            .table_name(&self.table_name)
-            .key("PK", AttributeValue::S(keys::calendar_pk(id)))
-            .key("SK", AttributeValue::S(keys::calendar_sk(id)))
-            .send()
-            .await
-            .map_err(|e| map_get_item_error(e, "Calendar", id.to_string()))?;
+        let item = self
+            .get_item(keys::calendar_pk(id), keys::calendar_sk(id), "Calendar", id)
+            .await?;
 
-        match result.item {
-            Some(item) => Ok(Some(item_to_calendar(&item)?)),
-            None => Ok(None),
+        match item {
+            Some(item) if !is_trashed(&item) => Ok(Some(item_to_calendar(&item)?)),
+            _ => Ok(None),
         }
     }
 
@@ -200,32 +1292,219 @@ impl CalendarRepository for DynamoDbRepository {
     }
 
     async fn update_calendar(&self, calendar: &Calendar) -> Result<()> {
-        let item = calendar_to_item(calendar);
+        // See `update_entry`
+        loop {
+            let stored =
+                self.get_calendar(calendar.id)
+                    .await?
+                    .ok_or_else(|| RepositoryError::NotFound {
+                        entity_type: "Calendar",
+                        id: calendar.id.to_string(),
+                    })?;
+            match self.put_calendar_if_version(calendar, stored.version).await {
+                Err(RepositoryError::VersionMismatch { .. }) => continue,
+                result => return result,
+            }
+        }
+    }
+
+    async fn update_calendar_if_version(
+        &self,
+        calendar: &Calendar,
+        expected_version: u64,
+    ) -> Result<()> {
+        self.put_calendar_if_version(calendar, expected_version)
+            .await
+    }
+
+    async fn record_subscription_refresh(
+        &self,
+        id: Uuid,
+        subscription: &CalendarSubscription,
+        expected_version: u64,
+    ) -> Result<()> {
+        let stored = self
+            .get_calendar(id)
+            .await?
+            .ok_or_else(|| RepositoryError::NotFound {
+                entity_type: "Calendar",
+                id: id.to_string(),
+            })?;
+        // Written back whole at the same version; the condition still fails
+        // if the calendar changed since `expected_version`
+        let calendar = Calendar {
+            subscription: Some(subscription.clone()),
+            version: expected_version,
+            ..stored
+        };
+        self.put_calendar_at_version(&calendar, expected_version)
+            .await
+    }
+
+    async fn delete_calendar(&self, id: Uuid) -> Result<()> {
+        let calendar = self
+            .get_calendar(id)
+            .await?
+            .ok_or_else(|| RepositoryError::NotFound {
+                entity_type: "Calendar",
+                id: id.to_string(),
+            })?;
+        let deleted_at = Utc::now();
 
         self.client
             .put_item()
             .table_name(&self.table_name)
-            .set_item(Some(item))
-            .condition_expression("attribute_exists(PK)")
+            .set_item(Some(trashed_calendar_to_item(&calendar, deleted_at)))
+            .condition_expression("attribute_exists(PK) AND attribute_not_exists(deletedAt)")
             .send()
             .await
-            .map_err(|e| map_put_item_error(e, "Calendar", calendar.id.to_string()))?;
+            .map_err(|e| map_put_item_error(e, "Calendar", id.to_string()))?;
+
+        // The entries share the calendar's deletion time, which is how a
+        // restore tells them from entries deleted on their own
+        for item in self.query_gsi1(keys::entry_gsi1_pk(id)).await? {
+            self.trash_entry(&item_to_entry(&item)?, deleted_at).await?;
+        }
 
         Ok(())
     }
 
-    async fn delete_calendar(&self, id: Uuid) -> Result<()> {
+    async fn restore_calendar(&self, id: Uuid) -> Result<Calendar> {
+        let item = self
+            .get_item(keys::calendar_pk(id), keys::calendar_sk(id), "Calendar", id)
+            .await?
+            .filter(is_trashed)
+            .ok_or_else(|| RepositoryError::NotFound {
+                entity_type: "TrashedCalendar",
+                id: id.to_string(),
+            })?;
+        let trashed = item_to_trashed_calendar(&item)?;
+
         self.client
-            .delete_item()
+            .put_item()
             .table_name(&self.table_name)
-            .key("PK", AttributeValue::S(keys::calendar_pk(id)))
-            .key("SK", AttributeValue::S(keys::calendar_sk(id)))
-            .condition_expression("attribute_exists(PK)")
+            .set_item(Some(calendar_to_item(&trashed.calendar)))
+            .condition_expression("attribute_exists(deletedAt)")
             .send()
             .await
-            .map_err(|e| map_delete_item_error(e, "Calendar", id.to_string()))?;
+            .map_err(|e| map_put_item_error(e, "TrashedCalendar", id.to_string()))?;
 
-        Ok(())
+        for item in self.query_gsi1(keys::trashed_entry_gsi1_pk(id)).await? {
+            let entry = item_to_trashed_entry(&item)?;
+            if entry.deleted_at == trashed.deleted_at {
+                self.untrash_entry(&entry.entry).await?;
+            }
+        }
+
+        Ok(trashed.calendar)
+    }
+
+    async fn purge_calendars(&self, deleted_before: DateTime<Utc>) -> Result<usize> {
+        let items = self
+            .query_trash_before(keys::TRASHED_CALENDARS_GSI2_PK, deleted_before)
+            .await?;
+        for item in &items {
+            self.purge_calendar(item_to_calendar(item)?.id).await?;
+        }
+        Ok(items.len())
+    }
+
+    async fn get_subscribed_calendars(&self) -> Result<Vec<Calendar>> {
+        let result = self
+            .client
+            .query()
+            .table_name(&self.table_name)
+            .index_name("GSI2")
+            .key_condition_expression("GSI2PK = :pk")
+            .expression_attribute_values(
+                ":pk",
+                AttributeValue::S(keys::SUBSCRIPTIONS_GSI2_PK.to_string()),
+            )
+            .send()
+            .await
+            .map_err(map_query_error)?;
+
+        result
+            .items
+            .unwrap_or_default()
+            .iter()
+            .map(item_to_calendar)
+            .collect()
+    }
+
+    async fn list_calendars(&self) -> Result<Vec<Calendar>> {
+        self.scan_entities(ENTITY_TYPE_CALENDAR)
+            .await?
+            .iter()
+            .filter(|item| !is_trashed(item))
+            .map(item_to_calendar)
+            .collect()
+    }
+
+    async fn duplicate_calendar(
+        &self,
+        source_id: Uuid,
+        copy: &Calendar,
+        options: &DuplicateOptions,
+    ) -> Result<DuplicateSummary> {
+        if self.get_calendar(source_id).await?.is_none() {
+            return Err(RepositoryError::NotFound {
+                entity_type: "Calendar",
+                id: source_id.to_string(),
+            });
+        }
+
+        let mut entries = Vec::new();
+        let mut overrides = Vec::new();
+        if options.entries {
+            for item in self.query_gsi1(keys::entry_gsi1_pk(source_id)).await? {
+                entries.push(item_to_entry(&item)?);
+            }
+            for series in entries.iter().filter(|e| e.is_recurring()) {
+                overrides.extend(self.get_occurrence_overrides(series.id).await?);
+            }
+        }
+        let memberships = if options.members {
+            self.query_memberships(source_id).await?
+        } else {
+            Vec::new()
+        };
+        let contents = duplicate_contents(copy.id, &entries, &overrides, &memberships, options);
+
+        let mut items = vec![self.new_item_put(calendar_to_item(copy))?];
+        for entry in &contents.entries {
+            items.push(self.new_item_put(entry_to_item(entry)?)?);
+        }
+        for over in &contents.overrides {
+            items.push(self.new_item_put(override_to_item(over)?)?);
+        }
+        for membership in &contents.memberships {
+            items.push(self.new_item_put(membership_to_item(membership))?);
+        }
+
+        // The calendar goes in the first chunk, so a failed first chunk
+        // leaves nothing behind. Like batches, a later failing chunk leaves
+        // the chunks before it applied.
+        for chunk in items.chunks(TRANSACT_CHUNK_SIZE) {
+            self.client
+                .transact_write_items()
+                .set_transact_items(Some(chunk.to_vec()))
+                .send()
+                .await
+                .map_err(|e| {
+                    map_transact_write_error(e, |_| RepositoryError::AlreadyExists {
+                        entity_type: "Calendar",
+                        id: copy.id.to_string(),
+                    })
+                })?;
+        }
+
+        for entry in &contents.entries {
+            self.sync_entry_tags(Some(entry), None).await?;
+            self.sync_entry_terms(Some(entry), None).await?;
+        }
+
+        Ok(contents.summary())
     }
 }
 
@@ -326,6 +1605,14 @@ impl UserRepository for DynamoDbRepository {
 
         Ok(())
     }
+
+    async fn list_users(&self) -> Result<Vec<User>> {
+        self.scan_entities(ENTITY_TYPE_USER)
+            .await?
+            .iter()
+            .map(item_to_user)
+            .collect()
+    }
 }
 
 // ============================================================================
@@ -403,6 +1690,49 @@ impl MembershipRepository for DynamoDbRepository {
         Ok(results)
     }
 
+    async fn get_trashed_calendars_for_user(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<(TrashedCalendar, CalendarRole)>> {
+        let membership_result = self
+            .client
+            .query()
+            .table_name(&self.table_name)
+            .index_name("GSI1")
+            .key_condition_expression("GSI1PK = :pk AND begins_with(GSI1SK, :sk_prefix)")
+            .expression_attribute_values(
+                ":pk",
+                AttributeValue::S(keys::membership_gsi1_pk(user_id)),
+            )
+            .expression_attribute_values(
+                ":sk_prefix",
+                AttributeValue::S(keys::calendar_gsi1_sk_prefix().to_string()),
+            )
+            .send()
+            .await
+            .map_err(map_query_error)?;
+
+        let memberships: Vec<CalendarMembership> = membership_result
+            .items
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|item| item_to_membership(item).ok())
+            .collect();
+
+        let mut results = Vec::new();
+        for membership in memberships {
+            let id = membership.calendar_id;
+            let item = self
+                .get_item(keys::calendar_pk(id), keys::calendar_sk(id), "Calendar", id)
+                .await?;
+            if let Some(item) = item.filter(is_trashed) {
+                results.push((item_to_trashed_calendar(&item)?, membership.role));
+            }
+        }
+
+        Ok(results)
+    }
+
     async fn get_users_for_calendar(&self, calendar_id: Uuid) -> Result<Vec<(User, CalendarRole)>> {
         // Get all memberships for the calendar
         let membership_result = self
@@ -541,3 +1871,136 @@ impl SettingsRepository for DynamoDbRepository {
         Ok(())
     }
 }
+
+// ============================================================================
+// FeedTokenRepository implementation
+// ============================================================================
+
+#[async_trait]
+impl FeedTokenRepository for DynamoDbRepository {
+    async fn get_feed_token(&self, token: &str) -> Result<Option<FeedToken>> {
+        let result = self
+            .client
+            .query()
+            .table_name(&self.table_name)
+            .index_name("GSI2")
+            .key_condition_expression("GSI2PK = :pk")
+            .expression_attribute_values(":pk", AttributeValue::S(keys::feed_token_gsi2_pk(token)))
+            .send()
+            .await
+            .map_err(map_query_error)?;
+
+        let items = result.items.unwrap_or_default();
+        match items.first() {
+            Some(item) => Ok(Some(item_to_feed_token(item)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn get_feed_token_for_user(
+        &self,
+        calendar_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<FeedToken>> {
+        let result = self
+            .client
+            .get_item()
+            .table_name(&self.table_name)
+            .key("PK", AttributeValue::S(keys::feed_token_pk(calendar_id)))
+            .key("SK", AttributeValue::S(keys::feed_token_sk(user_id)))
+            .send()
+            .await
+            .map_err(|e| {
+                map_get_item_error(e, "FeedToken", format!("{}:{}", calendar_id, user_id))
+            })?;
+
+        match result.item {
+            Some(item) => Ok(Some(item_to_feed_token(&item)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn put_feed_token(&self, token: &FeedToken) -> Result<()> {
+        // Same keys as the user's previous token, so this replaces it
+        self.client
+            .put_item()
+            .table_name(&self.table_name)
+            .set_item(Some(feed_token_to_item(token)))
+            .send()
+            .await
+            .map_err(|e| {
+                map_put_item_error(
+                    e,
+                    "FeedToken",
+                    format!("{}:{}", token.calendar_id, token.user_id),
+                )
+            })?;
+
+        Ok(())
+    }
+
+    async fn delete_feed_token(&self, calendar_id: Uuid, user_id: Uuid) -> Result<()> {
+        self.client
+            .delete_item()
+            .table_name(&self.table_name)
+            .key("PK", AttributeValue::S(keys::feed_token_pk(calendar_id)))
+            .key("SK", AttributeValue::S(keys::feed_token_sk(user_id)))
+            .condition_expression("attribute_exists(PK)")
+            .send()
+            .await
+            .map_err(|e| {
+                map_delete_item_error(e, "FeedToken", format!("{}:{}", calendar_id, user_id))
+            })?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(n: usize) -> Item {
+        HashMap::from([("PK".to_string(), AttributeValue::S(format!("ITEM#{n}")))])
+    }
+
+    #[tokio::test]
+    async fn test_collect_pages_follows_last_evaluated_key() {
+        let pages = [
+            vec![item(0), item(1)],
+            vec![item(2)],
+            vec![item(3), item(4)],
+        ];
+        let mut start_keys = Vec::new();
+
+        let items = collect_pages(|start_key: Option<Item>| {
+            start_keys.push(start_key.clone());
+            let index = start_keys.len() - 1;
+            let page = pages[index].clone();
+            let last_key = (index + 1 < pages.len()).then(|| page.last().unwrap().clone());
+            async move { Ok((page, last_key)) }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(items, (0..5).map(item).collect::<Vec<_>>());
+        assert_eq!(start_keys, vec![None, Some(item(1)), Some(item(2))]);
+    }
+
+    #[tokio::test]
+    async fn test_collect_pages_stops_at_an_error() {
+        let mut calls = 0;
+        let result = collect_pages(|_| {
+            calls += 1;
+            let page = match calls {
+                1 => Ok((vec![item(0)], Some(item(0)))),
+                _ => Err(RepositoryError::QueryFailed("throttled".to_string())),
+            };
+            async move { page }
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls, 2);
+    }
+}
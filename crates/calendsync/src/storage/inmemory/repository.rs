@@ -1,20 +1,59 @@
 //! In-memory repository implementation.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
 use calendsync_core::calendar::{
-    Calendar, CalendarEntry, CalendarMembership, CalendarRole, CalendarSettings, User,
+    duplicate_contents, entry_search_terms, expand_recurring_entries, matches_any_tag,
+    rank_entries, Calendar, CalendarEntry, CalendarMembership, CalendarRole, CalendarSettings,
+    CalendarSubscription, DuplicateOptions, DuplicateSummary, EntryRevision, EntryWrite, FeedToken,
+    OccurrenceOverride, SearchQuery, Tag, TrashedCalendar, TrashedEntry, User,
 };
 use calendsync_core::storage::{
-    CalendarRepository, DateRange, EntryRepository, MembershipRepository, RepositoryError, Result,
-    SettingsRepository, UserRepository,
+    CalendarRepository, DateRange, EntryRepository, FeedTokenRepository, MembershipRepository,
+    RepositoryError, Result, SettingsRepository, UserRepository,
 };
 
+/// Delivered reminders, keyed by entry ID and fire time.
+type ReminderClaims = HashSet<(Uuid, DateTime<Utc>)>;
+
+/// Entry IDs keyed by the words of their title, description and location.
+/// Kept sorted so the words starting with a prefix are adjacent.
+type SearchIndex = BTreeMap<String, HashSet<Uuid>>;
+
+/// Adds an entry's words to the search index.
+fn index_entry(index: &mut SearchIndex, entry: &CalendarEntry) {
+    for term in entry_search_terms(entry) {
+        index.entry(term).or_default().insert(entry.id);
+    }
+}
+
+/// Removes an entry's words from the search index.
+fn unindex_entry(index: &mut SearchIndex, entry: &CalendarEntry) {
+    for term in entry_search_terms(entry) {
+        if let Some(ids) = index.get_mut(&term) {
+            ids.remove(&entry.id);
+            if ids.is_empty() {
+                index.remove(&term);
+            }
+        }
+    }
+}
+
+/// Returns the IDs of entries with a word starting with `prefix`.
+fn prefix_matches(index: &SearchIndex, prefix: &str) -> HashSet<Uuid> {
+    index
+        .range(prefix.to_string()..)
+        .take_while(|(word, _)| word.starts_with(prefix))
+        .flat_map(|(_, ids)| ids.iter().copied())
+        .collect()
+}
+
 /// In-memory storage backend for testing.
 ///
 /// Uses HashMaps wrapped in `Arc<RwLock<_>>` for thread-safe access.
@@ -22,10 +61,19 @@ use calendsync_core::storage::{
 #[derive(Debug, Clone)]
 pub struct InMemoryRepository {
     entries: Arc<RwLock<HashMap<Uuid, CalendarEntry>>>,
+    overrides: Arc<RwLock<HashMap<(Uuid, NaiveDate), OccurrenceOverride>>>,
     calendars: Arc<RwLock<HashMap<Uuid, Calendar>>>,
     users: Arc<RwLock<HashMap<Uuid, User>>>,
     memberships: Arc<RwLock<HashMap<(Uuid, Uuid), CalendarMembership>>>,
     settings: Arc<RwLock<HashMap<(Uuid, Uuid), CalendarSettings>>>,
+    feed_tokens: Arc<RwLock<HashMap<(Uuid, Uuid), FeedToken>>>,
+    reminder_claims: Arc<RwLock<ReminderClaims>>,
+    search_index: Arc<RwLock<SearchIndex>>,
+    /// Deleted entries and calendars, kept out of the maps above so reads skip them.
+    trashed_entries: Arc<RwLock<HashMap<Uuid, TrashedEntry>>>,
+    trashed_calendars: Arc<RwLock<HashMap<Uuid, TrashedCalendar>>>,
+    /// Revision history of each entry, oldest first.
+    revisions: Arc<RwLock<HashMap<Uuid, Vec<EntryRevision>>>>,
 }
 
 impl Default for InMemoryRepository {
@@ -39,11 +87,137 @@ impl InMemoryRepository {
     pub fn new() -> Self {
         Self {
             entries: Arc::new(RwLock::new(HashMap::new())),
+            overrides: Arc::new(RwLock::new(HashMap::new())),
             calendars: Arc::new(RwLock::new(HashMap::new())),
             users: Arc::new(RwLock::new(HashMap::new())),
             memberships: Arc::new(RwLock::new(HashMap::new())),
             settings: Arc::new(RwLock::new(HashMap::new())),
+            feed_tokens: Arc::new(RwLock::new(HashMap::new())),
+            reminder_claims: Arc::new(RwLock::new(HashSet::new())),
+            search_index: Arc::new(RwLock::new(BTreeMap::new())),
+            trashed_entries: Arc::new(RwLock::new(HashMap::new())),
+            trashed_calendars: Arc::new(RwLock::new(HashMap::new())),
+            revisions: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Returns the entries matching `filter` within a date range, with
+    /// recurring series expanded.
+    async fn expand_entries(
+        &self,
+        filter: impl Fn(&CalendarEntry) -> bool,
+        date_range: DateRange,
+    ) -> Vec<CalendarEntry> {
+        let entries = self.entries.read().await;
+        // Recurring series are kept regardless of dates: later occurrences, or
+        // occurrences moved by an override, may still land in the range.
+        let matching: Vec<CalendarEntry> = entries
+            .values()
+            .filter(|e| filter(e))
+            .filter(|e| {
+                e.is_recurring()
+                    || (e.start_date <= date_range.end && e.end_date >= date_range.start)
+            })
+            .cloned()
+            .collect();
+
+        let overrides = self.overrides.read().await;
+        let series_overrides: Vec<OccurrenceOverride> = overrides
+            .values()
+            .filter(|o| matching.iter().any(|e| e.id == o.series_id))
+            .cloned()
+            .collect();
+
+        expand_recurring_entries(matching, &series_overrides, &date_range)
+    }
+
+    /// Replaces a stored entry with the next version of `entry`, provided the
+    /// stored version is `expected_version` when one is given.
+    async fn store_entry(
+        &self,
+        entry: &CalendarEntry,
+        expected_version: Option<u64>,
+    ) -> Result<()> {
+        let mut entries = self.entries.write().await;
+        let Some(stored) = entries.get_mut(&entry.id) else {
+            return Err(RepositoryError::NotFound {
+                entity_type: "CalendarEntry",
+                id: entry.id.to_string(),
+            });
+        };
+        if let Some(expected) = expected_version.filter(|v| *v != stored.version) {
+            return Err(RepositoryError::VersionMismatch {
+                entity_type: "CalendarEntry",
+                id: entry.id.to_string(),
+                expected,
+            });
+        }
+        let next = CalendarEntry {
+            version: stored.version + 1,
+            ..entry.clone()
+        };
+        let previous = std::mem::replace(stored, next);
+        let mut index = self.search_index.write().await;
+        unindex_entry(&mut index, &previous);
+        index_entry(&mut index, entry);
+        Ok(())
+    }
+
+    /// Moves a live entry to the trash, provided its stored version is
+    /// `expected_version` when one is given.
+    async fn trash_entry(&self, id: Uuid, expected_version: Option<u64>) -> Result<()> {
+        let mut entries = self.entries.write().await;
+        let Some(entry) = entries.remove(&id) else {
+            return Err(RepositoryError::NotFound {
+                entity_type: "CalendarEntry",
+                id: id.to_string(),
+            });
+        };
+        if let Some(expected) = expected_version.filter(|v| *v != entry.version) {
+            entries.insert(id, entry);
+            return Err(RepositoryError::VersionMismatch {
+                entity_type: "CalendarEntry",
+                id: id.to_string(),
+                expected,
+            });
+        }
+        unindex_entry(&mut *self.search_index.write().await, &entry);
+        self.trashed_entries.write().await.insert(
+            id,
+            TrashedEntry {
+                entry,
+                deleted_at: Utc::now(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Replaces a stored calendar with the next version of `calendar`,
+    /// provided the stored version is `expected_version` when one is given.
+    async fn store_calendar(
+        &self,
+        calendar: &Calendar,
+        expected_version: Option<u64>,
+    ) -> Result<()> {
+        let mut calendars = self.calendars.write().await;
+        let Some(stored) = calendars.get_mut(&calendar.id) else {
+            return Err(RepositoryError::NotFound {
+                entity_type: "Calendar",
+                id: calendar.id.to_string(),
+            });
+        };
+        if let Some(expected) = expected_version.filter(|v| *v != stored.version) {
+            return Err(RepositoryError::VersionMismatch {
+                entity_type: "Calendar",
+                id: calendar.id.to_string(),
+                expected,
+            });
         }
+        *stored = Calendar {
+            version: stored.version + 1,
+            ..calendar.clone()
+        };
+        Ok(())
     }
 }
 
@@ -58,14 +232,68 @@ impl EntryRepository for InMemoryRepository {
         &self,
         calendar_id: Uuid,
         date_range: DateRange,
+    ) -> Result<Vec<CalendarEntry>> {
+        Ok(self
+            .expand_entries(|e| e.calendar_id == calendar_id, date_range)
+            .await)
+    }
+
+    async fn get_entries_by_tags(
+        &self,
+        calendar_id: Uuid,
+        tags: &BTreeSet<Tag>,
+        date_range: DateRange,
+    ) -> Result<Vec<CalendarEntry>> {
+        let mut entries = self
+            .expand_entries(|e| e.calendar_id == calendar_id, date_range)
+            .await;
+        // Overrides may retag single occurrences, so filter after expanding
+        entries.retain(|e| matches_any_tag(&e.tags, tags));
+        Ok(entries)
+    }
+
+    async fn get_stored_entries_by_calendar(
+        &self,
+        calendar_id: Uuid,
     ) -> Result<Vec<CalendarEntry>> {
         let entries = self.entries.read().await;
-        Ok(entries
+        let mut result: Vec<CalendarEntry> = entries
             .values()
             .filter(|e| e.calendar_id == calendar_id)
-            .filter(|e| e.start_date <= date_range.end && e.end_date >= date_range.start)
             .cloned()
-            .collect())
+            .collect();
+        result.sort_by_key(|e| e.start_date);
+        Ok(result)
+    }
+
+    async fn search_entries(
+        &self,
+        calendar_ids: &[Uuid],
+        query: &SearchQuery,
+        limit: usize,
+    ) -> Result<Vec<CalendarEntry>> {
+        // Lock order matches the writers: entries, then the index
+        let entries = self.entries.read().await;
+        let index = self.search_index.read().await;
+
+        // Entries with a word starting with every query term
+        let mut terms = query.terms().iter();
+        let mut ids = terms
+            .next()
+            .map(|term| prefix_matches(&index, term))
+            .unwrap_or_default();
+        for term in terms {
+            let matching = prefix_matches(&index, term);
+            ids.retain(|id| matching.contains(id));
+        }
+
+        let candidates = ids
+            .iter()
+            .filter_map(|id| entries.get(id))
+            .filter(|e| calendar_ids.contains(&e.calendar_id))
+            .cloned()
+            .collect();
+        Ok(rank_entries(query, candidates, limit))
     }
 
     async fn create_entry(&self, entry: &CalendarEntry) -> Result<()> {
@@ -77,29 +305,241 @@ impl EntryRepository for InMemoryRepository {
             });
         }
         entries.insert(entry.id, entry.clone());
+        index_entry(&mut *self.search_index.write().await, entry);
         Ok(())
     }
 
     async fn update_entry(&self, entry: &CalendarEntry) -> Result<()> {
+        self.store_entry(entry, None).await
+    }
+
+    async fn update_entry_if_version(
+        &self,
+        entry: &CalendarEntry,
+        expected_version: u64,
+    ) -> Result<()> {
+        self.store_entry(entry, Some(expected_version)).await
+    }
+
+    async fn move_entry(
+        &self,
+        id: Uuid,
+        calendar_id: Uuid,
+        expected_version: u64,
+    ) -> Result<CalendarEntry> {
+        let entry = self
+            .get_entry(id)
+            .await?
+            .ok_or_else(|| RepositoryError::NotFound {
+                entity_type: "CalendarEntry",
+                id: id.to_string(),
+            })?;
+
+        let moved = CalendarEntry {
+            calendar_id,
+            updated_at: Utc::now(),
+            ..entry
+        };
+        self.store_entry(&moved, Some(expected_version)).await?;
+
+        Ok(CalendarEntry {
+            version: expected_version + 1,
+            ..moved
+        })
+    }
+
+    async fn delete_entry(&self, id: Uuid) -> Result<()> {
+        self.trash_entry(id, None).await
+    }
+
+    async fn delete_entry_if_version(&self, id: Uuid, expected_version: u64) -> Result<()> {
+        self.trash_entry(id, Some(expected_version)).await
+    }
+
+    async fn get_trashed_entries(&self, calendar_ids: &[Uuid]) -> Result<Vec<TrashedEntry>> {
+        let trashed = self.trashed_entries.read().await;
+        Ok(trashed
+            .values()
+            .filter(|t| calendar_ids.contains(&t.entry.calendar_id))
+            .cloned()
+            .collect())
+    }
+
+    async fn restore_entry(&self, id: Uuid) -> Result<CalendarEntry> {
         let mut entries = self.entries.write().await;
-        if !entries.contains_key(&entry.id) {
+        let Some(trashed) = self.trashed_entries.write().await.remove(&id) else {
+            return Err(RepositoryError::NotFound {
+                entity_type: "TrashedEntry",
+                id: id.to_string(),
+            });
+        };
+        index_entry(&mut *self.search_index.write().await, &trashed.entry);
+        entries.insert(id, trashed.entry.clone());
+        Ok(trashed.entry)
+    }
+
+    async fn purge_entries(&self, deleted_before: DateTime<Utc>) -> Result<usize> {
+        let mut trashed = self.trashed_entries.write().await;
+        let purged: HashSet<Uuid> = trashed
+            .values()
+            .filter(|t| t.deleted_at < deleted_before)
+            .map(|t| t.entry.id)
+            .collect();
+        trashed.retain(|id, _| !purged.contains(id));
+        self.overrides
+            .write()
+            .await
+            .retain(|(series_id, _), _| !purged.contains(series_id));
+        self.revisions
+            .write()
+            .await
+            .retain(|entry_id, _| !purged.contains(entry_id));
+        Ok(purged.len())
+    }
+
+    async fn get_occurrence_overrides(&self, series_id: Uuid) -> Result<Vec<OccurrenceOverride>> {
+        let overrides = self.overrides.read().await;
+        let mut result: Vec<OccurrenceOverride> = overrides
+            .values()
+            .filter(|o| o.series_id == series_id)
+            .cloned()
+            .collect();
+        result.sort_by_key(|o| o.original_date);
+        Ok(result)
+    }
+
+    async fn upsert_occurrence_override(&self, occurrence: &OccurrenceOverride) -> Result<()> {
+        if !self
+            .entries
+            .read()
+            .await
+            .contains_key(&occurrence.series_id)
+        {
             return Err(RepositoryError::NotFound {
                 entity_type: "CalendarEntry",
-                id: entry.id.to_string(),
+                id: occurrence.series_id.to_string(),
             });
         }
-        entries.insert(entry.id, entry.clone());
+        self.overrides.write().await.insert(
+            (occurrence.series_id, occurrence.original_date),
+            occurrence.clone(),
+        );
         Ok(())
     }
 
-    async fn delete_entry(&self, id: Uuid) -> Result<()> {
-        let mut entries = self.entries.write().await;
-        if entries.remove(&id).is_none() {
-            return Err(RepositoryError::NotFound {
-                entity_type: "CalendarEntry",
-                id: id.to_string(),
+    async fn delete_occurrence_overrides(&self, series_id: Uuid, from: NaiveDate) -> Result<()> {
+        self.overrides
+            .write()
+            .await
+            .retain(|(id, date), _| *id != series_id || *date < from);
+        Ok(())
+    }
+
+    async fn get_entries_with_reminders(
+        &self,
+        date_range: DateRange,
+    ) -> Result<Vec<CalendarEntry>> {
+        let mut entries = self
+            .expand_entries(CalendarEntry::has_reminders, date_range)
+            .await;
+        // An override may have removed an occurrence's reminders
+        entries.retain(CalendarEntry::has_reminders);
+        Ok(entries)
+    }
+
+    async fn claim_reminder(&self, entry_id: Uuid, fire_at: DateTime<Utc>) -> Result<bool> {
+        Ok(self
+            .reminder_claims
+            .write()
+            .await
+            .insert((entry_id, fire_at)))
+    }
+
+    async fn append_entry_revision(&self, revision: &EntryRevision) -> Result<()> {
+        let mut revisions = self.revisions.write().await;
+        let history = revisions.entry(revision.entry_id).or_default();
+        if history.iter().any(|r| r.revision == revision.revision) {
+            return Err(RepositoryError::AlreadyExists {
+                entity_type: "EntryRevision",
+                id: format!("{}@{}", revision.entry_id, revision.revision),
             });
         }
+        history.push(revision.clone());
+        history.sort_by_key(|r| r.revision);
+        Ok(())
+    }
+
+    async fn get_entry_revisions(&self, entry_id: Uuid) -> Result<Vec<EntryRevision>> {
+        let revisions = self.revisions.read().await;
+        Ok(revisions.get(&entry_id).cloned().unwrap_or_default())
+    }
+
+    async fn apply_entry_batch(&self, writes: &[EntryWrite]) -> Result<()> {
+        let mut entries = self.entries.write().await;
+
+        // Check every write before applying any, so a failing batch leaves
+        // nothing behind
+        for write in writes {
+            let id = write.entry_id();
+            match (write, entries.get(&id)) {
+                (EntryWrite::Create(_), Some(_)) => {
+                    return Err(RepositoryError::AlreadyExists {
+                        entity_type: "CalendarEntry",
+                        id: id.to_string(),
+                    });
+                }
+                (EntryWrite::Update { .. } | EntryWrite::Delete(_), None) => {
+                    return Err(RepositoryError::NotFound {
+                        entity_type: "CalendarEntry",
+                        id: id.to_string(),
+                    });
+                }
+                (
+                    EntryWrite::Update {
+                        expected_version, ..
+                    },
+                    Some(stored),
+                ) if stored.version != *expected_version => {
+                    return Err(RepositoryError::VersionMismatch {
+                        entity_type: "CalendarEntry",
+                        id: id.to_string(),
+                        expected: *expected_version,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        let mut index = self.search_index.write().await;
+        let mut trashed = self.trashed_entries.write().await;
+        let deleted_at = Utc::now();
+        for write in writes {
+            match write {
+                EntryWrite::Create(entry) => {
+                    entries.insert(entry.id, entry.clone());
+                    index_entry(&mut index, entry);
+                }
+                EntryWrite::Update {
+                    entry,
+                    expected_version,
+                } => {
+                    let next = CalendarEntry {
+                        version: expected_version + 1,
+                        ..entry.clone()
+                    };
+                    if let Some(previous) = entries.insert(entry.id, next) {
+                        unindex_entry(&mut index, &previous);
+                    }
+                    index_entry(&mut index, entry);
+                }
+                EntryWrite::Delete(id) => {
+                    if let Some(entry) = entries.remove(id) {
+                        unindex_entry(&mut index, &entry);
+                        trashed.insert(*id, TrashedEntry { entry, deleted_at });
+                    }
+                }
+            }
+        }
         Ok(())
     }
 }
@@ -124,27 +564,227 @@ impl CalendarRepository for InMemoryRepository {
     }
 
     async fn update_calendar(&self, calendar: &Calendar) -> Result<()> {
+        self.store_calendar(calendar, None).await
+    }
+
+    async fn update_calendar_if_version(
+        &self,
+        calendar: &Calendar,
+        expected_version: u64,
+    ) -> Result<()> {
+        self.store_calendar(calendar, Some(expected_version)).await
+    }
+
+    async fn record_subscription_refresh(
+        &self,
+        id: Uuid,
+        subscription: &CalendarSubscription,
+        expected_version: u64,
+    ) -> Result<()> {
         let mut calendars = self.calendars.write().await;
-        if !calendars.contains_key(&calendar.id) {
+        let Some(stored) = calendars.get_mut(&id) else {
             return Err(RepositoryError::NotFound {
                 entity_type: "Calendar",
-                id: calendar.id.to_string(),
+                id: id.to_string(),
+            });
+        };
+        if stored.version != expected_version {
+            return Err(RepositoryError::VersionMismatch {
+                entity_type: "Calendar",
+                id: id.to_string(),
+                expected: expected_version,
             });
         }
-        calendars.insert(calendar.id, calendar.clone());
+        stored.subscription = Some(subscription.clone());
         Ok(())
     }
 
     async fn delete_calendar(&self, id: Uuid) -> Result<()> {
         let mut calendars = self.calendars.write().await;
-        if calendars.remove(&id).is_none() {
+        let Some(calendar) = calendars.remove(&id) else {
             return Err(RepositoryError::NotFound {
                 entity_type: "Calendar",
                 id: id.to_string(),
             });
+        };
+        let deleted_at = Utc::now();
+
+        // The calendar's entries share its deletion time, which is how a
+        // restore tells them from entries deleted on their own
+        let mut entries = self.entries.write().await;
+        let mut trashed = self.trashed_entries.write().await;
+        let mut index = self.search_index.write().await;
+        let entry_ids: Vec<Uuid> = entries
+            .values()
+            .filter(|e| e.calendar_id == id)
+            .map(|e| e.id)
+            .collect();
+        for entry_id in entry_ids {
+            if let Some(entry) = entries.remove(&entry_id) {
+                unindex_entry(&mut index, &entry);
+                trashed.insert(entry_id, TrashedEntry { entry, deleted_at });
+            }
         }
+
+        self.trashed_calendars.write().await.insert(
+            id,
+            TrashedCalendar {
+                calendar,
+                deleted_at,
+            },
+        );
         Ok(())
     }
+
+    async fn restore_calendar(&self, id: Uuid) -> Result<Calendar> {
+        let mut calendars = self.calendars.write().await;
+        let Some(trashed_calendar) = self.trashed_calendars.write().await.remove(&id) else {
+            return Err(RepositoryError::NotFound {
+                entity_type: "TrashedCalendar",
+                id: id.to_string(),
+            });
+        };
+
+        let mut entries = self.entries.write().await;
+        let mut trashed = self.trashed_entries.write().await;
+        let mut index = self.search_index.write().await;
+        let entry_ids: Vec<Uuid> = trashed
+            .values()
+            .filter(|t| t.entry.calendar_id == id && t.deleted_at == trashed_calendar.deleted_at)
+            .map(|t| t.entry.id)
+            .collect();
+        for entry_id in entry_ids {
+            if let Some(TrashedEntry { entry, .. }) = trashed.remove(&entry_id) {
+                index_entry(&mut index, &entry);
+                entries.insert(entry_id, entry);
+            }
+        }
+
+        calendars.insert(id, trashed_calendar.calendar.clone());
+        Ok(trashed_calendar.calendar)
+    }
+
+    async fn purge_calendars(&self, deleted_before: DateTime<Utc>) -> Result<usize> {
+        let mut trashed_calendars = self.trashed_calendars.write().await;
+        let purged: HashSet<Uuid> = trashed_calendars
+            .values()
+            .filter(|t| t.deleted_at < deleted_before)
+            .map(|t| t.calendar.id)
+            .collect();
+        if purged.is_empty() {
+            return Ok(0);
+        }
+        trashed_calendars.retain(|id, _| !purged.contains(id));
+
+        let mut trashed = self.trashed_entries.write().await;
+        let entry_ids: HashSet<Uuid> = trashed
+            .values()
+            .filter(|t| purged.contains(&t.entry.calendar_id))
+            .map(|t| t.entry.id)
+            .collect();
+        trashed.retain(|id, _| !entry_ids.contains(id));
+        self.overrides
+            .write()
+            .await
+            .retain(|(series_id, _), _| !entry_ids.contains(series_id));
+        self.revisions
+            .write()
+            .await
+            .retain(|entry_id, _| !entry_ids.contains(entry_id));
+        self.memberships
+            .write()
+            .await
+            .retain(|(calendar_id, _), _| !purged.contains(calendar_id));
+        self.settings
+            .write()
+            .await
+            .retain(|(calendar_id, _), _| !purged.contains(calendar_id));
+        self.feed_tokens
+            .write()
+            .await
+            .retain(|(calendar_id, _), _| !purged.contains(calendar_id));
+        Ok(purged.len())
+    }
+
+    async fn get_subscribed_calendars(&self) -> Result<Vec<Calendar>> {
+        Ok(self
+            .calendars
+            .read()
+            .await
+            .values()
+            .filter(|c| c.subscription.is_some())
+            .cloned()
+            .collect())
+    }
+
+    async fn list_calendars(&self) -> Result<Vec<Calendar>> {
+        Ok(self.calendars.read().await.values().cloned().collect())
+    }
+
+    async fn duplicate_calendar(
+        &self,
+        source_id: Uuid,
+        copy: &Calendar,
+        options: &DuplicateOptions,
+    ) -> Result<DuplicateSummary> {
+        // Memberships are locked before calendars, like get_calendars_for_user
+        let mut memberships = self.memberships.write().await;
+        let mut calendars = self.calendars.write().await;
+        if !calendars.contains_key(&source_id) {
+            return Err(RepositoryError::NotFound {
+                entity_type: "Calendar",
+                id: source_id.to_string(),
+            });
+        }
+        if calendars.contains_key(&copy.id) {
+            return Err(RepositoryError::AlreadyExists {
+                entity_type: "Calendar",
+                id: copy.id.to_string(),
+            });
+        }
+
+        let mut entries = self.entries.write().await;
+        let mut overrides = self.overrides.write().await;
+        let source_entries: Vec<CalendarEntry> = entries
+            .values()
+            .filter(|e| e.calendar_id == source_id)
+            .cloned()
+            .collect();
+        let source_overrides: Vec<OccurrenceOverride> = overrides
+            .values()
+            .filter(|o| source_entries.iter().any(|e| e.id == o.series_id))
+            .cloned()
+            .collect();
+        let source_memberships: Vec<CalendarMembership> = memberships
+            .values()
+            .filter(|m| m.calendar_id == source_id)
+            .cloned()
+            .collect();
+        let contents = duplicate_contents(
+            copy.id,
+            &source_entries,
+            &source_overrides,
+            &source_memberships,
+            options,
+        );
+
+        calendars.insert(copy.id, copy.clone());
+        let mut index = self.search_index.write().await;
+        for entry in &contents.entries {
+            index_entry(&mut index, entry);
+            entries.insert(entry.id, entry.clone());
+        }
+        for over in &contents.overrides {
+            overrides.insert((over.series_id, over.original_date), over.clone());
+        }
+        for membership in &contents.memberships {
+            memberships.insert(
+                (membership.calendar_id, membership.user_id),
+                membership.clone(),
+            );
+        }
+        Ok(contents.summary())
+    }
 }
 
 #[async_trait]
@@ -197,6 +837,10 @@ impl UserRepository for InMemoryRepository {
         users.insert(user.id, user.clone());
         Ok(())
     }
+
+    async fn list_users(&self) -> Result<Vec<User>> {
+        Ok(self.users.read().await.values().cloned().collect())
+    }
 }
 
 #[async_trait]
@@ -223,6 +867,20 @@ impl MembershipRepository for InMemoryRepository {
         Ok(result)
     }
 
+    async fn get_trashed_calendars_for_user(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<(TrashedCalendar, CalendarRole)>> {
+        let memberships = self.memberships.read().await;
+        let trashed = self.trashed_calendars.read().await;
+
+        Ok(memberships
+            .values()
+            .filter(|m| m.user_id == user_id)
+            .filter_map(|m| trashed.get(&m.calendar_id).map(|t| (t.clone(), m.role)))
+            .collect())
+    }
+
     async fn get_users_for_calendar(&self, calendar_id: Uuid) -> Result<Vec<(User, CalendarRole)>> {
         let memberships = self.memberships.read().await;
         let users = self.users.read().await;
@@ -285,10 +943,44 @@ impl SettingsRepository for InMemoryRepository {
     }
 }
 
+#[async_trait]
+impl FeedTokenRepository for InMemoryRepository {
+    async fn get_feed_token(&self, token: &str) -> Result<Option<FeedToken>> {
+        let feed_tokens = self.feed_tokens.read().await;
+        Ok(feed_tokens.values().find(|t| t.token == token).cloned())
+    }
+
+    async fn get_feed_token_for_user(
+        &self,
+        calendar_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<FeedToken>> {
+        let feed_tokens = self.feed_tokens.read().await;
+        Ok(feed_tokens.get(&(calendar_id, user_id)).cloned())
+    }
+
+    async fn put_feed_token(&self, token: &FeedToken) -> Result<()> {
+        let mut feed_tokens = self.feed_tokens.write().await;
+        feed_tokens.insert((token.calendar_id, token.user_id), token.clone());
+        Ok(())
+    }
+
+    async fn delete_feed_token(&self, calendar_id: Uuid, user_id: Uuid) -> Result<()> {
+        let mut feed_tokens = self.feed_tokens.write().await;
+        if feed_tokens.remove(&(calendar_id, user_id)).is_none() {
+            return Err(RepositoryError::NotFound {
+                entity_type: "FeedToken",
+                id: format!("{calendar_id}:{user_id}"),
+            });
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use calendsync_core::calendar::{EntryStyle, ViewMode};
+    use calendsync_core::calendar::{EntryStyle, RevisionAction, ViewMode};
     use chrono::NaiveDate;
 
     // Helper to create test dates
@@ -332,6 +1024,34 @@ mod tests {
         assert_eq!(retrieved.title, "Updated Title");
     }
 
+    #[tokio::test]
+    async fn test_entry_update_if_version() {
+        let repo = InMemoryRepository::new();
+        let calendar_id = Uuid::new_v4();
+        let mut entry = CalendarEntry::all_day(calendar_id, "Original Title", date(2024, 6, 15));
+        repo.create_entry(&entry).await.unwrap();
+
+        entry.title = "First".to_string();
+        repo.update_entry_if_version(&entry, 1).await.unwrap();
+        let retrieved = repo.get_entry(entry.id).await.unwrap().unwrap();
+        assert_eq!(retrieved.version, 2);
+
+        // A writer that read version 1 must not overwrite version 2
+        entry.title = "Second".to_string();
+        let result = repo.update_entry_if_version(&entry, 1).await;
+        assert!(matches!(
+            result,
+            Err(RepositoryError::VersionMismatch { expected: 1, .. })
+        ));
+        let retrieved = repo.get_entry(entry.id).await.unwrap().unwrap();
+        assert_eq!(retrieved.title, "First");
+
+        // Unconditional updates still bump the version
+        repo.update_entry(&entry).await.unwrap();
+        let retrieved = repo.get_entry(entry.id).await.unwrap().unwrap();
+        assert_eq!(retrieved.version, 3);
+    }
+
     #[tokio::test]
     async fn test_entry_update_nonexistent() {
         let repo = InMemoryRepository::new();
@@ -355,6 +1075,123 @@ mod tests {
         assert!(retrieved.is_none());
     }
 
+    #[tokio::test]
+    async fn test_entry_delete_if_version() {
+        let repo = InMemoryRepository::new();
+        let calendar_id = Uuid::new_v4();
+        let mut entry = CalendarEntry::all_day(calendar_id, "Test Event", date(2024, 6, 15));
+        repo.create_entry(&entry).await.unwrap();
+        entry.title = "Renamed".to_string();
+        repo.update_entry(&entry).await.unwrap();
+
+        // A writer that read version 1 must not delete version 2
+        let result = repo.delete_entry_if_version(entry.id, 1).await;
+        assert!(matches!(
+            result,
+            Err(RepositoryError::VersionMismatch { expected: 1, .. })
+        ));
+        assert!(repo.get_entry(entry.id).await.unwrap().is_some());
+
+        repo.delete_entry_if_version(entry.id, 2).await.unwrap();
+        assert!(repo.get_entry(entry.id).await.unwrap().is_none());
+        let result = repo.delete_entry_if_version(entry.id, 2).await;
+        assert!(matches!(result, Err(RepositoryError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_entry_trash_and_restore() {
+        let repo = InMemoryRepository::new();
+        let calendar_id = Uuid::new_v4();
+        let entry = CalendarEntry::all_day(calendar_id, "Dentist", date(2024, 6, 15));
+        let date_range = DateRange::new(date(2024, 6, 1), date(2024, 6, 30)).unwrap();
+
+        repo.create_entry(&entry).await.unwrap();
+        repo.delete_entry(entry.id).await.unwrap();
+
+        let query: SearchQuery = "dentist".parse().unwrap();
+        assert!(repo
+            .get_entries_by_calendar(calendar_id, date_range)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(repo
+            .search_entries(&[calendar_id], &query, 10)
+            .await
+            .unwrap()
+            .is_empty());
+        let trashed = repo.get_trashed_entries(&[calendar_id]).await.unwrap();
+        assert_eq!(trashed.len(), 1);
+        assert_eq!(trashed[0].entry, entry);
+
+        let restored = repo.restore_entry(entry.id).await.unwrap();
+        assert_eq!(restored, entry);
+        assert_eq!(repo.get_entry(entry.id).await.unwrap(), Some(entry.clone()));
+        assert_eq!(
+            repo.search_entries(&[calendar_id], &query, 10)
+                .await
+                .unwrap(),
+            vec![entry.clone()]
+        );
+        assert!(repo
+            .get_trashed_entries(&[calendar_id])
+            .await
+            .unwrap()
+            .is_empty());
+
+        let result = repo.restore_entry(entry.id).await;
+        assert!(matches!(result, Err(RepositoryError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_entry_purge() {
+        let repo = InMemoryRepository::new();
+        let entry = CalendarEntry::all_day(Uuid::new_v4(), "Dentist", date(2024, 6, 15));
+
+        repo.create_entry(&entry).await.unwrap();
+        repo.delete_entry(entry.id).await.unwrap();
+
+        // Nothing was deleted before an hour ago
+        let an_hour_ago = Utc::now() - chrono::Duration::hours(1);
+        assert_eq!(repo.purge_entries(an_hour_ago).await.unwrap(), 0);
+
+        let later = Utc::now() + chrono::Duration::seconds(1);
+        assert_eq!(repo.purge_entries(later).await.unwrap(), 1);
+        let result = repo.restore_entry(entry.id).await;
+        assert!(matches!(result, Err(RepositoryError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_entry_revisions() {
+        let repo = InMemoryRepository::new();
+        let entry = CalendarEntry::all_day(Uuid::new_v4(), "Dentist", date(2024, 6, 15));
+        let created =
+            EntryRevision::new(1, RevisionAction::Created, entry.clone(), None, Utc::now());
+        let updated = EntryRevision::new(
+            2,
+            RevisionAction::Updated,
+            entry.clone().with_location("Clinic"),
+            Some(Uuid::new_v4()),
+            Utc::now(),
+        );
+
+        repo.append_entry_revision(&updated).await.unwrap();
+        repo.append_entry_revision(&created).await.unwrap();
+        let history = repo.get_entry_revisions(entry.id).await.unwrap();
+        assert_eq!(history, vec![created.clone(), updated]);
+
+        // Revisions are immutable
+        let result = repo.append_entry_revision(&created).await;
+        assert!(matches!(result, Err(RepositoryError::AlreadyExists { .. })));
+
+        // History goes with the purged entry
+        repo.create_entry(&entry).await.unwrap();
+        repo.delete_entry(entry.id).await.unwrap();
+        repo.purge_entries(Utc::now() + chrono::Duration::seconds(1))
+            .await
+            .unwrap();
+        assert!(repo.get_entry_revisions(entry.id).await.unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn test_entry_delete_nonexistent() {
         let repo = InMemoryRepository::new();
@@ -396,6 +1233,216 @@ mod tests {
         assert!(titles.contains(&"Entry 3"));
     }
 
+    #[tokio::test]
+    async fn test_get_entries_by_calendar_expands_recurring_entries() {
+        let repo = InMemoryRepository::new();
+        let calendar_id = Uuid::new_v4();
+
+        // Weekly on Mondays, starting well before the queried range.
+        let series = CalendarEntry::all_day(calendar_id, "Standup", date(2024, 1, 1))
+            .with_recurrence("FREQ=WEEKLY;BYDAY=MO".parse().unwrap());
+        repo.create_entry(&series).await.unwrap();
+
+        let date_range = DateRange::new(date(2024, 6, 1), date(2024, 6, 30)).unwrap();
+        let mut entries = repo
+            .get_entries_by_calendar(calendar_id, date_range)
+            .await
+            .unwrap();
+        entries.sort_by_key(|e| e.start_date);
+
+        let dates: Vec<NaiveDate> = entries.iter().map(|e| e.start_date).collect();
+        assert_eq!(
+            dates,
+            vec![
+                date(2024, 6, 3),
+                date(2024, 6, 10),
+                date(2024, 6, 17),
+                date(2024, 6, 24)
+            ]
+        );
+        assert!(entries.iter().all(|e| e.id == series.id));
+        assert_eq!(entries[0].occurrence_date, Some(date(2024, 6, 3)));
+
+        // The stored series itself is unchanged.
+        let stored = repo.get_entry(series.id).await.unwrap().unwrap();
+        assert_eq!(stored.start_date, date(2024, 1, 1));
+        assert_eq!(stored.occurrence_date, None);
+    }
+
+    #[tokio::test]
+    async fn test_occurrence_overrides() {
+        let repo = InMemoryRepository::new();
+        let calendar_id = Uuid::new_v4();
+        let series = CalendarEntry::all_day(calendar_id, "Standup", date(2024, 6, 3))
+            .with_recurrence("FREQ=WEEKLY".parse().unwrap());
+        repo.create_entry(&series).await.unwrap();
+
+        let mut moved = series.clone();
+        moved.start_date = date(2024, 6, 11);
+        moved.end_date = date(2024, 6, 11);
+        repo.upsert_occurrence_override(&OccurrenceOverride::cancelled(
+            series.id,
+            date(2024, 6, 3),
+        ))
+        .await
+        .unwrap();
+        repo.upsert_occurrence_override(&OccurrenceOverride::replaced(
+            series.id,
+            date(2024, 6, 10),
+            moved,
+        ))
+        .await
+        .unwrap();
+        repo.upsert_occurrence_override(&OccurrenceOverride::cancelled(
+            series.id,
+            date(2024, 6, 17),
+        ))
+        .await
+        .unwrap();
+
+        let date_range = DateRange::new(date(2024, 6, 1), date(2024, 6, 23)).unwrap();
+        let entries = repo
+            .get_entries_by_calendar(calendar_id, date_range)
+            .await
+            .unwrap();
+        let dates: Vec<NaiveDate> = entries.iter().map(|e| e.start_date).collect();
+        assert_eq!(dates, vec![date(2024, 6, 11)]);
+
+        repo.delete_occurrence_overrides(series.id, date(2024, 6, 10))
+            .await
+            .unwrap();
+        let remaining = repo.get_occurrence_overrides(series.id).await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].original_date, date(2024, 6, 3));
+
+        // Overrides stay with a trashed series and go away when it is purged.
+        repo.delete_entry(series.id).await.unwrap();
+        let kept = repo.get_occurrence_overrides(series.id).await.unwrap();
+        assert_eq!(kept.len(), 1);
+        repo.purge_entries(Utc::now() + chrono::Duration::seconds(1))
+            .await
+            .unwrap();
+        assert!(repo
+            .get_occurrence_overrides(series.id)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // A series must exist to be overridden.
+        let result = repo
+            .upsert_occurrence_override(&OccurrenceOverride::cancelled(series.id, date(2024, 6, 3)))
+            .await;
+        assert!(matches!(result, Err(RepositoryError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_get_stored_entries_by_calendar() {
+        let repo = InMemoryRepository::new();
+        let calendar_id = Uuid::new_v4();
+        let series = CalendarEntry::all_day(calendar_id, "Standup", date(2024, 6, 3))
+            .with_recurrence("FREQ=DAILY".parse().unwrap());
+        let kickoff = CalendarEntry::all_day(calendar_id, "Kickoff", date(2024, 6, 1));
+        let deleted = CalendarEntry::all_day(calendar_id, "Deleted", date(2024, 6, 2));
+        let elsewhere = CalendarEntry::all_day(Uuid::new_v4(), "Other", date(2024, 6, 1));
+        for entry in [&series, &kickoff, &deleted, &elsewhere] {
+            repo.create_entry(entry).await.unwrap();
+        }
+        repo.delete_entry(deleted.id).await.unwrap();
+
+        let entries = repo
+            .get_stored_entries_by_calendar(calendar_id)
+            .await
+            .unwrap();
+        let ids: Vec<Uuid> = entries.iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![kickoff.id, series.id]);
+        assert!(entries[1].occurrence_date.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_entries_by_tags() {
+        let repo = InMemoryRepository::new();
+        let calendar_id = Uuid::new_v4();
+        let oncall: Tag = "oncall".parse().unwrap();
+        let travel: Tag = "travel".parse().unwrap();
+
+        let pager = CalendarEntry::all_day(calendar_id, "Pager", date(2024, 6, 10))
+            .with_tag(oncall.clone());
+        let flight = CalendarEntry::all_day(calendar_id, "Flight", date(2024, 6, 12))
+            .with_tag(travel.clone());
+        let elsewhere = CalendarEntry::all_day(Uuid::new_v4(), "Other", date(2024, 6, 10))
+            .with_tag(oncall.clone());
+        for entry in [&pager, &flight, &elsewhere] {
+            repo.create_entry(entry).await.unwrap();
+        }
+
+        let date_range = DateRange::new(date(2024, 6, 1), date(2024, 6, 30)).unwrap();
+        let entries = repo
+            .get_entries_by_tags(calendar_id, &BTreeSet::from([oncall]), date_range)
+            .await
+            .unwrap();
+        assert_eq!(entries, vec![pager]);
+    }
+
+    #[tokio::test]
+    async fn test_search_entries() {
+        let repo = InMemoryRepository::new();
+        let calendar_id = Uuid::new_v4();
+        let dentist = CalendarEntry::all_day(calendar_id, "Dentist", date(2024, 6, 10))
+            .with_location("Main St. clinic");
+        let checkup = CalendarEntry::all_day(calendar_id, "Checkup", date(2024, 6, 12))
+            .with_description("Ask the dentist about X-rays");
+        let elsewhere = CalendarEntry::all_day(Uuid::new_v4(), "Dentist", date(2024, 6, 10));
+        for entry in [&dentist, &checkup, &elsewhere] {
+            repo.create_entry(entry).await.unwrap();
+        }
+
+        let query: SearchQuery = "dent".parse().unwrap();
+        let found = repo
+            .search_entries(&[calendar_id], &query, 10)
+            .await
+            .unwrap();
+        assert_eq!(found, vec![dentist.clone(), checkup.clone()]);
+
+        let query: SearchQuery = "dentist clinic".parse().unwrap();
+        let found = repo
+            .search_entries(&[calendar_id], &query, 10)
+            .await
+            .unwrap();
+        assert_eq!(found, vec![dentist.clone()]);
+
+        // Updates and deletes keep the index in step
+        let mut renamed = dentist.clone();
+        renamed.title = "Orthodontist".to_string();
+        repo.update_entry(&renamed).await.unwrap();
+        repo.delete_entry(checkup.id).await.unwrap();
+        let query: SearchQuery = "dent".parse().unwrap();
+        let found = repo
+            .search_entries(&[calendar_id], &query, 10)
+            .await
+            .unwrap();
+        assert!(found.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_entries_with_reminders_and_claims() {
+        use calendsync_core::calendar::Reminder;
+
+        let repo = InMemoryRepository::new();
+        let reminded = CalendarEntry::all_day(Uuid::new_v4(), "Dentist", date(2024, 6, 15))
+            .with_reminder(Reminder::minutes_before(60));
+        let silent = CalendarEntry::all_day(Uuid::new_v4(), "Nothing", date(2024, 6, 15));
+        repo.create_entry(&reminded).await.unwrap();
+        repo.create_entry(&silent).await.unwrap();
+
+        let date_range = DateRange::new(date(2024, 6, 1), date(2024, 6, 30)).unwrap();
+        let entries = repo.get_entries_with_reminders(date_range).await.unwrap();
+        assert_eq!(entries, vec![reminded.clone()]);
+
+        let fire_at = Utc::now();
+        assert!(repo.claim_reminder(reminded.id, fire_at).await.unwrap());
+        assert!(!repo.claim_reminder(reminded.id, fire_at).await.unwrap());
+    }
+
     // ==================== Calendar CRUD Tests ====================
 
     #[tokio::test]
@@ -430,6 +1477,24 @@ mod tests {
         assert_eq!(retrieved.name, "Personal");
     }
 
+    #[tokio::test]
+    async fn test_calendar_update_if_version() {
+        let repo = InMemoryRepository::new();
+        let mut calendar = Calendar::new("Work", "#3B82F6");
+        repo.create_calendar(&calendar).await.unwrap();
+
+        calendar.name = "Personal".to_string();
+        repo.update_calendar_if_version(&calendar, 1).await.unwrap();
+
+        let result = repo.update_calendar_if_version(&calendar, 1).await;
+        assert!(matches!(
+            result,
+            Err(RepositoryError::VersionMismatch { .. })
+        ));
+        let retrieved = repo.get_calendar(calendar.id).await.unwrap().unwrap();
+        assert_eq!(retrieved.version, 2);
+    }
+
     #[tokio::test]
     async fn test_calendar_update_nonexistent() {
         let repo = InMemoryRepository::new();
@@ -451,6 +1516,125 @@ mod tests {
         assert!(retrieved.is_none());
     }
 
+    #[tokio::test]
+    async fn test_calendar_trash_and_restore() {
+        let repo = InMemoryRepository::new();
+        let calendar = Calendar::new("Work", "#3B82F6");
+        let user = User::new("Alice", "alice@example.com");
+        let kept = CalendarEntry::all_day(calendar.id, "Standup", date(2024, 6, 15));
+        let deleted_first = CalendarEntry::all_day(calendar.id, "Retro", date(2024, 6, 16));
+
+        repo.create_calendar(&calendar).await.unwrap();
+        repo.create_user(&user).await.unwrap();
+        repo.create_membership(&CalendarMembership::owner(calendar.id, user.id))
+            .await
+            .unwrap();
+        repo.create_entry(&kept).await.unwrap();
+        repo.create_entry(&deleted_first).await.unwrap();
+        repo.delete_entry(deleted_first.id).await.unwrap();
+
+        repo.delete_calendar(calendar.id).await.unwrap();
+        assert!(repo.get_calendar(calendar.id).await.unwrap().is_none());
+        assert!(repo.get_entry(kept.id).await.unwrap().is_none());
+        assert!(repo
+            .get_calendars_for_user(user.id)
+            .await
+            .unwrap()
+            .is_empty());
+        let trashed = repo.get_trashed_calendars_for_user(user.id).await.unwrap();
+        assert_eq!(trashed.len(), 1);
+        assert_eq!(trashed[0].0.calendar, calendar);
+
+        // Only the entries trashed with the calendar come back
+        let restored = repo.restore_calendar(calendar.id).await.unwrap();
+        assert_eq!(restored, calendar);
+        assert_eq!(repo.get_entry(kept.id).await.unwrap(), Some(kept.clone()));
+        assert!(repo.get_entry(deleted_first.id).await.unwrap().is_none());
+        assert_eq!(repo.get_calendars_for_user(user.id).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_calendar_purge() {
+        let repo = InMemoryRepository::new();
+        let calendar = Calendar::new("Work", "#3B82F6");
+        let entry = CalendarEntry::all_day(calendar.id, "Standup", date(2024, 6, 15));
+
+        repo.create_calendar(&calendar).await.unwrap();
+        repo.create_entry(&entry).await.unwrap();
+        repo.delete_calendar(calendar.id).await.unwrap();
+
+        let later = Utc::now() + chrono::Duration::seconds(1);
+        assert_eq!(repo.purge_calendars(later).await.unwrap(), 1);
+        assert!(repo
+            .get_trashed_entries(&[calendar.id])
+            .await
+            .unwrap()
+            .is_empty());
+        let result = repo.restore_calendar(calendar.id).await;
+        assert!(matches!(result, Err(RepositoryError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_calendar_duplicate() {
+        let repo = InMemoryRepository::new();
+        let source = Calendar::new("Q1", "#3B82F6");
+        let user = User::new("Alice", "alice@example.com");
+        let entry = CalendarEntry::all_day(source.id, "Kickoff", date(2024, 1, 8));
+        let series = CalendarEntry::all_day(source.id, "Standup", date(2024, 1, 1))
+            .with_recurrence("FREQ=WEEKLY".parse().unwrap());
+        let cancelled = OccurrenceOverride::cancelled(series.id, date(2024, 1, 15));
+
+        repo.create_calendar(&source).await.unwrap();
+        repo.create_user(&user).await.unwrap();
+        repo.create_membership(&CalendarMembership::writer(source.id, user.id))
+            .await
+            .unwrap();
+        repo.create_entry(&entry).await.unwrap();
+        repo.create_entry(&series).await.unwrap();
+        repo.upsert_occurrence_override(&cancelled).await.unwrap();
+
+        let copy = Calendar::new("Q2", "#3B82F6");
+        let options = DuplicateOptions {
+            entries: true,
+            shift_days: 91,
+            members: true,
+            owner: None,
+        };
+        let summary = repo
+            .duplicate_calendar(source.id, &copy, &options)
+            .await
+            .unwrap();
+        assert_eq!(
+            summary,
+            DuplicateSummary {
+                entries: 2,
+                members: 1
+            }
+        );
+
+        assert_eq!(
+            repo.get_calendar(copy.id).await.unwrap(),
+            Some(copy.clone())
+        );
+        let copied = repo
+            .get_entries_by_calendar(copy.id, DateRange::month(2024, 4))
+            .await
+            .unwrap();
+        assert!(copied
+            .iter()
+            .any(|e| e.title == "Kickoff" && e.start_date == date(2024, 4, 8)));
+        // The cancelled occurrence moved along with the series
+        let standups: Vec<_> = copied.iter().filter(|e| e.title == "Standup").collect();
+        assert!(standups.iter().all(|e| e.start_date != date(2024, 4, 15)));
+        assert_eq!(standups.len(), 4);
+        assert_eq!(repo.get_calendars_for_user(user.id).await.unwrap().len(), 2);
+
+        let result = repo
+            .duplicate_calendar(Uuid::new_v4(), &Calendar::new("Q3", "#3B82F6"), &options)
+            .await;
+        assert!(matches!(result, Err(RepositoryError::NotFound { .. })));
+    }
+
     #[tokio::test]
     async fn test_calendar_delete_nonexistent() {
         let repo = InMemoryRepository::new();
@@ -458,6 +1642,18 @@ mod tests {
         assert!(matches!(result, Err(RepositoryError::NotFound { .. })));
     }
 
+    #[tokio::test]
+    async fn test_list_calendars_skips_trash() {
+        let repo = InMemoryRepository::new();
+        let kept = Calendar::new("Work", "#3B82F6");
+        let trashed = Calendar::new("Old", "#EF4444");
+        repo.create_calendar(&kept).await.unwrap();
+        repo.create_calendar(&trashed).await.unwrap();
+        repo.delete_calendar(trashed.id).await.unwrap();
+
+        assert_eq!(repo.list_calendars().await.unwrap(), vec![kept]);
+    }
+
     // ==================== User CRUD Tests ====================
 
     #[tokio::test]
@@ -522,6 +1718,21 @@ mod tests {
         assert!(matches!(result, Err(RepositoryError::NotFound { .. })));
     }
 
+    #[tokio::test]
+    async fn test_list_users() {
+        let repo = InMemoryRepository::new();
+        assert!(repo.list_users().await.unwrap().is_empty());
+
+        let alice = User::new("Alice", "alice@example.com");
+        let bob = User::new("Bob", "bob@example.com");
+        repo.create_user(&alice).await.unwrap();
+        repo.create_user(&bob).await.unwrap();
+
+        let mut users = repo.list_users().await.unwrap();
+        users.sort_by_key(|u| u.name.clone());
+        assert_eq!(users, vec![alice, bob]);
+    }
+
     // ==================== Membership CRUD Tests ====================
 
     #[tokio::test]
@@ -655,6 +1866,60 @@ mod tests {
 
     // ==================== Settings CRUD Tests ====================
 
+    #[tokio::test]
+    async fn test_apply_entry_batch_is_all_or_nothing() {
+        let repo = InMemoryRepository::new();
+        let calendar_id = Uuid::new_v4();
+        let physics = CalendarEntry::all_day(calendar_id, "Physics", date(2024, 9, 2));
+        let chemistry = CalendarEntry::all_day(calendar_id, "Chemistry", date(2024, 9, 3));
+        repo.create_entry(&physics).await.unwrap();
+        repo.create_entry(&chemistry).await.unwrap();
+
+        let biology = CalendarEntry::all_day(calendar_id, "Biology", date(2024, 9, 4));
+        let missing = Uuid::new_v4();
+        let result = repo
+            .apply_entry_batch(&[
+                EntryWrite::Create(biology.clone()),
+                EntryWrite::Delete(missing),
+            ])
+            .await;
+        assert!(matches!(result, Err(RepositoryError::NotFound { .. })));
+        assert!(repo.get_entry(biology.id).await.unwrap().is_none());
+
+        let mut renamed = physics.clone();
+        renamed.title = "Physics I".to_string();
+        repo.apply_entry_batch(&[
+            EntryWrite::Create(biology.clone()),
+            EntryWrite::Update {
+                entry: renamed,
+                expected_version: 1,
+            },
+            EntryWrite::Delete(chemistry.id),
+        ])
+        .await
+        .unwrap();
+
+        assert!(repo.get_entry(biology.id).await.unwrap().is_some());
+        let updated = repo.get_entry(physics.id).await.unwrap().unwrap();
+        assert_eq!(updated.title, "Physics I");
+        assert_eq!(updated.version, 2);
+        assert!(repo.get_entry(chemistry.id).await.unwrap().is_none());
+        assert_eq!(
+            repo.get_trashed_entries(&[calendar_id])
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let query: SearchQuery = "physics".parse().unwrap();
+        let found = repo
+            .search_entries(&[calendar_id], &query, 10)
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 1);
+    }
+
     #[tokio::test]
     async fn test_settings_get_nonexistent() {
         let repo = InMemoryRepository::new();
@@ -741,4 +2006,36 @@ mod tests {
         assert_eq!(retrieved1, settings1);
         assert_eq!(retrieved2, settings2);
     }
+
+    #[tokio::test]
+    async fn test_feed_token_put_rotate_and_delete() {
+        let repo = InMemoryRepository::new();
+        let calendar_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+
+        let token = FeedToken::new(calendar_id, user_id);
+        repo.put_feed_token(&token).await.unwrap();
+        assert_eq!(
+            repo.get_feed_token(&token.token).await.unwrap(),
+            Some(token.clone())
+        );
+
+        // Rotating replaces the old token
+        let rotated = FeedToken::new(calendar_id, user_id);
+        repo.put_feed_token(&rotated).await.unwrap();
+        assert!(repo.get_feed_token(&token.token).await.unwrap().is_none());
+        assert_eq!(
+            repo.get_feed_token_for_user(calendar_id, user_id)
+                .await
+                .unwrap(),
+            Some(rotated.clone())
+        );
+
+        repo.delete_feed_token(calendar_id, user_id).await.unwrap();
+        assert!(repo.get_feed_token(&rotated.token).await.unwrap().is_none());
+        assert!(matches!(
+            repo.delete_feed_token(calendar_id, user_id).await,
+            Err(RepositoryError::NotFound { .. })
+        ));
+    }
 }
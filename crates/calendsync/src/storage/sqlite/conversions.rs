@@ -3,11 +3,17 @@
 //! Pure functions for converting between SQLite rows and domain types.
 //! These are testable in isolation without database access.
 
+use std::collections::BTreeSet;
+
 use calendsync_core::calendar::{
-    Calendar, CalendarEntry, CalendarMembership, CalendarRole, EntryKind, User,
+    format_reminders, parse_reminders, parse_tags, Attendee, Calendar, CalendarEntry,
+    CalendarMembership, CalendarRole, CalendarSubscription, EntryKind, EntryRevision, FeedToken,
+    OccurrenceOverride, RecurrenceRule, Reminder, RevisionAction, SearchQuery, Tag,
+    TrashedCalendar, TrashedEntry, User,
 };
 use calendsync_core::storage::RepositoryError;
 use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
 use rusqlite::Row;
 use uuid::Uuid;
 
@@ -17,7 +23,7 @@ use uuid::Uuid;
 
 /// Convert a SQLite row to a User.
 ///
-/// Expected columns: id, name, email, provider, provider_subject, created_at, updated_at
+/// Expected columns: id, name, email, provider, provider_subject, created_at, updated_at, timezone
 pub fn row_to_user(row: &Row) -> rusqlite::Result<User> {
     let id: String = row.get(0)?;
     let name: String = row.get(1)?;
@@ -26,6 +32,7 @@ pub fn row_to_user(row: &Row) -> rusqlite::Result<User> {
     let provider_subject: Option<String> = row.get(4)?;
     let created_at: String = row.get(5)?;
     let updated_at: String = row.get(6)?;
+    let timezone: Option<String> = row.get(7)?;
 
     Ok(User {
         id: parse_uuid(&id)?,
@@ -33,6 +40,7 @@ pub fn row_to_user(row: &Row) -> rusqlite::Result<User> {
         email,
         provider,
         provider_subject,
+        timezone: timezone.as_deref().map(parse_timezone).transpose()?,
         created_at: parse_datetime(&created_at)?,
         updated_at: parse_datetime(&updated_at)?,
     })
@@ -44,7 +52,7 @@ pub fn row_to_user(row: &Row) -> rusqlite::Result<User> {
 
 /// Convert a SQLite row to a Calendar.
 ///
-/// Expected columns: id, name, color, description, created_at, updated_at
+/// Expected columns: id, name, color, description, created_at, updated_at, timezone, version, subscription, is_default
 pub fn row_to_calendar(row: &Row) -> rusqlite::Result<Calendar> {
     let id: String = row.get(0)?;
     let name: String = row.get(1)?;
@@ -52,13 +60,23 @@ pub fn row_to_calendar(row: &Row) -> rusqlite::Result<Calendar> {
     let description: Option<String> = row.get(3)?;
     let created_at: String = row.get(4)?;
     let updated_at: String = row.get(5)?;
+    let timezone: Option<String> = row.get(6)?;
+    let version: i64 = row.get(7)?;
+    let subscription: Option<String> = row.get(8)?;
+    let is_default: bool = row.get(9)?;
 
     Ok(Calendar {
         id: parse_uuid(&id)?,
         name,
         color,
         description,
-        is_default: false,
+        is_default,
+        timezone: timezone.as_deref().map(parse_timezone).transpose()?,
+        subscription: subscription
+            .as_deref()
+            .map(json_to_subscription)
+            .transpose()?,
+        version: parse_version(version)?,
         created_at: parse_datetime(&created_at)?,
         updated_at: parse_datetime(&updated_at)?,
     })
@@ -66,37 +84,42 @@ pub fn row_to_calendar(row: &Row) -> rusqlite::Result<Calendar> {
 
 /// Convert a row to Calendar with role (from JOIN query).
 ///
-/// Expected columns: id, name, color, description, created_at, updated_at, role
+/// Expected columns: id, name, color, description, created_at, updated_at, timezone, version, subscription, is_default, role
 pub fn row_to_calendar_with_role(row: &Row) -> rusqlite::Result<(Calendar, CalendarRole)> {
-    let id: String = row.get(0)?;
-    let name: String = row.get(1)?;
-    let color: String = row.get(2)?;
-    let description: Option<String> = row.get(3)?;
-    let created_at: String = row.get(4)?;
-    let updated_at: String = row.get(5)?;
-    let role_str: String = row.get(6)?;
-
-    let calendar = Calendar {
-        id: parse_uuid(&id)?,
-        name,
-        color,
-        description,
-        is_default: false,
-        created_at: parse_datetime(&created_at)?,
-        updated_at: parse_datetime(&updated_at)?,
-    };
+    let calendar = row_to_calendar(row)?;
+    let role_str: String = row.get(10)?;
     let role = parse_role(&role_str)?;
 
     Ok((calendar, role))
 }
 
+/// Convert a row to a trashed Calendar with role (from JOIN query).
+///
+/// Expected columns: id, name, color, description, created_at, updated_at, timezone, version, subscription, is_default, role, deleted_at
+pub fn row_to_trashed_calendar_with_role(
+    row: &Row,
+) -> rusqlite::Result<(TrashedCalendar, CalendarRole)> {
+    let (calendar, role) = row_to_calendar_with_role(row)?;
+    let deleted_at: String = row.get(11)?;
+
+    Ok((
+        TrashedCalendar {
+            calendar,
+            deleted_at: parse_datetime(&deleted_at)?,
+        },
+        role,
+    ))
+}
+
 // ============================================================================
 // Entry conversions
 // ============================================================================
 
 /// Convert a SQLite row to a CalendarEntry.
 ///
-/// Expected columns: id, calendar_id, title, description, location, kind, start_date, end_date, color, created_at, updated_at
+/// Expected columns: id, calendar_id, title, description, location, kind, start_date, end_date, color, created_at, updated_at, recurrence, timezone, reminders, attendees, version, tags
+///
+/// `tags` is the comma-separated `group_concat` of the entry's `entry_tags` rows.
 pub fn row_to_entry(row: &Row) -> rusqlite::Result<CalendarEntry> {
     let id: String = row.get(0)?;
     let calendar_id: String = row.get(1)?;
@@ -109,6 +132,12 @@ pub fn row_to_entry(row: &Row) -> rusqlite::Result<CalendarEntry> {
     let color: Option<String> = row.get(8)?;
     let created_at: String = row.get(9)?;
     let updated_at: String = row.get(10)?;
+    let recurrence: Option<String> = row.get(11)?;
+    let timezone: Option<String> = row.get(12)?;
+    let reminders: Option<String> = row.get(13)?;
+    let attendees: Option<String> = row.get(14)?;
+    let version: i64 = row.get(15)?;
+    let tags: Option<String> = row.get(16)?;
 
     Ok(CalendarEntry {
         id: parse_uuid(&id)?,
@@ -120,11 +149,147 @@ pub fn row_to_entry(row: &Row) -> rusqlite::Result<CalendarEntry> {
         start_date: parse_date(&start_date)?,
         end_date: parse_date(&end_date)?,
         color,
+        timezone: timezone.as_deref().map(parse_timezone).transpose()?,
+        recurrence: recurrence.as_deref().map(parse_recurrence).transpose()?,
+        reminders: reminders
+            .as_deref()
+            .map(parse_reminder_list)
+            .transpose()?
+            .unwrap_or_default(),
+        attendees: attendees
+            .as_deref()
+            .map(json_to_attendees)
+            .transpose()?
+            .unwrap_or_default(),
+        tags: tags
+            .as_deref()
+            .map(parse_tag_list)
+            .transpose()?
+            .unwrap_or_default(),
+        occurrence_date: None,
+        segment: None,
+        version: parse_version(version)?,
         created_at: parse_datetime(&created_at)?,
         updated_at: parse_datetime(&updated_at)?,
     })
 }
 
+/// Convert a SQLite row to a TrashedEntry.
+///
+/// Expected columns: the `row_to_entry` columns followed by deleted_at
+pub fn row_to_trashed_entry(row: &Row) -> rusqlite::Result<TrashedEntry> {
+    let entry = row_to_entry(row)?;
+    let deleted_at: String = row.get(17)?;
+
+    Ok(TrashedEntry {
+        entry,
+        deleted_at: parse_datetime(&deleted_at)?,
+    })
+}
+
+/// Convert a SQLite row to an OccurrenceOverride.
+///
+/// Expected columns: series_id, original_date, replacement_json
+pub fn row_to_occurrence_override(row: &Row) -> rusqlite::Result<OccurrenceOverride> {
+    let series_id: String = row.get(0)?;
+    let original_date: String = row.get(1)?;
+    let replacement_json: Option<String> = row.get(2)?;
+
+    let replacement = replacement_json
+        .map(|json| {
+            serde_json::from_str::<CalendarEntry>(&json).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    2,
+                    rusqlite::types::Type::Text,
+                    Box::new(e),
+                )
+            })
+        })
+        .transpose()?;
+
+    Ok(OccurrenceOverride {
+        series_id: parse_uuid(&series_id)?,
+        original_date: parse_date(&original_date)?,
+        replacement,
+    })
+}
+
+/// Convert a SQLite row to an EntryRevision.
+///
+/// Expected columns: entry_id, revision, action, actor_id, recorded_at,
+/// changed_fields, snapshot_json, overrides
+pub fn row_to_entry_revision(row: &Row) -> rusqlite::Result<EntryRevision> {
+    let entry_id: String = row.get(0)?;
+    let revision: u32 = row.get(1)?;
+    let action: String = row.get(2)?;
+    let actor_id: Option<String> = row.get(3)?;
+    let recorded_at: String = row.get(4)?;
+    let changed_fields: Option<String> = row.get(5)?;
+    let snapshot_json: String = row.get(6)?;
+    let overrides: Option<String> = row.get(7)?;
+
+    let action = action.parse::<RevisionAction>().map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, Box::new(e))
+    })?;
+    let snapshot = serde_json::from_str::<CalendarEntry>(&snapshot_json).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(6, rusqlite::types::Type::Text, Box::new(e))
+    })?;
+    let overrides = overrides
+        .map(|json| serde_json::from_str::<Vec<OccurrenceOverride>>(&json))
+        .transpose()
+        .map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(7, rusqlite::types::Type::Text, Box::new(e))
+        })?;
+
+    Ok(EntryRevision {
+        entry_id: parse_uuid(&entry_id)?,
+        revision,
+        action,
+        actor_id: actor_id.as_deref().map(parse_uuid).transpose()?,
+        recorded_at: parse_datetime(&recorded_at)?,
+        changed_fields: changed_fields
+            .map(|fields| fields.split(',').map(String::from).collect())
+            .unwrap_or_default(),
+        snapshot,
+        overrides,
+    })
+}
+
+/// Serialize an override's replacement occurrence to JSON (`None` if cancelled).
+pub fn occurrence_replacement_to_json(
+    occurrence: &OccurrenceOverride,
+) -> Result<Option<String>, RepositoryError> {
+    occurrence
+        .replacement
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|e| RepositoryError::Serialization(e.to_string()))
+}
+
+/// Serialize reminders as a comma-separated list (`None` if there are none).
+pub fn reminders_to_string(reminders: &[Reminder]) -> Option<String> {
+    (!reminders.is_empty()).then(|| format_reminders(reminders))
+}
+
+/// Serialize attendees to JSON (`None` if there are none).
+pub fn attendees_to_json(attendees: &[Attendee]) -> Result<Option<String>, RepositoryError> {
+    (!attendees.is_empty())
+        .then(|| serde_json::to_string(attendees))
+        .transpose()
+        .map_err(|e| RepositoryError::Serialization(e.to_string()))
+}
+
+/// Serialize a calendar's feed subscription to JSON (`None` if it has none).
+pub fn subscription_to_json(calendar: &Calendar) -> Result<Option<String>, RepositoryError> {
+    calendar
+        .subscription
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|e| RepositoryError::Serialization(e.to_string()))
+}
+
 /// Serialize EntryKind to JSON string.
 pub fn entry_kind_to_json(kind: &EntryKind) -> Result<String, RepositoryError> {
     serde_json::to_string(kind).map_err(|e| RepositoryError::Serialization(e.to_string()))
@@ -142,6 +307,41 @@ fn json_to_entry_kind_internal(json: &str) -> rusqlite::Result<EntryKind> {
     })
 }
 
+/// Deserialize attendees from their JSON column.
+fn json_to_attendees(json: &str) -> rusqlite::Result<Vec<Attendee>> {
+    serde_json::from_str(json).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(14, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
+/// Deserialize a calendar's feed subscription from its JSON column.
+fn json_to_subscription(json: &str) -> rusqlite::Result<CalendarSubscription> {
+    serde_json::from_str(json).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(8, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
+// ============================================================================
+// Feed token conversions
+// ============================================================================
+
+/// Convert a SQLite row to a FeedToken.
+///
+/// Expected columns: token, calendar_id, user_id, created_at
+pub fn row_to_feed_token(row: &Row) -> rusqlite::Result<FeedToken> {
+    let token: String = row.get(0)?;
+    let calendar_id: String = row.get(1)?;
+    let user_id: String = row.get(2)?;
+    let created_at: String = row.get(3)?;
+
+    Ok(FeedToken {
+        token,
+        calendar_id: parse_uuid(&calendar_id)?,
+        user_id: parse_uuid(&user_id)?,
+        created_at: parse_datetime(&created_at)?,
+    })
+}
+
 // ============================================================================
 // Membership conversions
 // ============================================================================
@@ -167,7 +367,7 @@ pub fn row_to_membership(row: &Row) -> rusqlite::Result<CalendarMembership> {
 
 /// Convert a row to User with role (from JOIN query).
 ///
-/// Expected columns: id, name, email, provider, provider_subject, created_at, updated_at, role
+/// Expected columns: id, name, email, provider, provider_subject, created_at, updated_at, timezone, role
 pub fn row_to_user_with_role(row: &Row) -> rusqlite::Result<(User, CalendarRole)> {
     let id: String = row.get(0)?;
     let name: String = row.get(1)?;
@@ -176,7 +376,8 @@ pub fn row_to_user_with_role(row: &Row) -> rusqlite::Result<(User, CalendarRole)
     let provider_subject: Option<String> = row.get(4)?;
     let created_at: String = row.get(5)?;
     let updated_at: String = row.get(6)?;
-    let role_str: String = row.get(7)?;
+    let timezone: Option<String> = row.get(7)?;
+    let role_str: String = row.get(8)?;
 
     let user = User {
         id: parse_uuid(&id)?,
@@ -184,6 +385,7 @@ pub fn row_to_user_with_role(row: &Row) -> rusqlite::Result<(User, CalendarRole)
         email,
         provider,
         provider_subject,
+        timezone: timezone.as_deref().map(parse_timezone).transpose()?,
         created_at: parse_datetime(&created_at)?,
         updated_at: parse_datetime(&updated_at)?,
     };
@@ -219,6 +421,13 @@ fn parse_date(s: &str) -> rusqlite::Result<NaiveDate> {
     })
 }
 
+/// Parse a stored version, which SQLite keeps as a signed integer.
+fn parse_version(version: i64) -> rusqlite::Result<u64> {
+    u64::try_from(version).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Integer, Box::new(e))
+    })
+}
+
 /// Parse a datetime from RFC 3339 string.
 fn parse_datetime(s: &str) -> rusqlite::Result<DateTime<Utc>> {
     DateTime::parse_from_rfc3339(s)
@@ -228,6 +437,35 @@ fn parse_datetime(s: &str) -> rusqlite::Result<DateTime<Utc>> {
         })
 }
 
+/// Parse a recurrence rule from its RRULE string.
+fn parse_recurrence(s: &str) -> rusqlite::Result<RecurrenceRule> {
+    s.parse()
+        .map_err(|e: calendsync_core::calendar::RecurrenceError| {
+            rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+        })
+}
+
+/// Parse a comma-separated list of reminders.
+fn parse_reminder_list(s: &str) -> rusqlite::Result<Vec<Reminder>> {
+    parse_reminders(s).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
+/// Parse a comma-separated list of tags.
+fn parse_tag_list(s: &str) -> rusqlite::Result<BTreeSet<Tag>> {
+    parse_tags(s).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(15, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
+/// Parse an IANA time zone name.
+fn parse_timezone(s: &str) -> rusqlite::Result<Tz> {
+    s.parse().map_err(|e: chrono_tz::ParseError| {
+        rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
 /// Parse CalendarRole from string.
 fn parse_role(s: &str) -> rusqlite::Result<CalendarRole> {
     match s.to_lowercase().as_str() {
@@ -255,9 +493,24 @@ pub fn format_date(date: &NaiveDate) -> String {
     date.format("%Y-%m-%d").to_string()
 }
 
+/// Build the FTS5 match expression for a search query: every word must
+/// start some indexed word, e.g. `"dent"* "main"*`.
+///
+/// Query words contain only letters and digits, so quoting them is enough
+/// to keep FTS5 from reading them as operators.
+pub fn search_match_expression(query: &SearchQuery) -> String {
+    query
+        .terms()
+        .iter()
+        .map(|term| format!("\"{term}\"*"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use calendsync_core::calendar::{ChecklistItem, TaskPriority};
     use chrono::NaiveTime;
 
     #[test]
@@ -281,7 +534,7 @@ mod tests {
 
     #[test]
     fn test_entry_kind_to_json_task() {
-        let kind = EntryKind::Task { completed: true };
+        let kind = EntryKind::task(true);
         let json = entry_kind_to_json(&kind).unwrap();
         assert!(json.contains("Task"));
         assert!(json.contains("true"));
@@ -302,7 +555,14 @@ mod tests {
                 start: NaiveTime::from_hms_opt(14, 30, 0).unwrap(),
                 end: NaiveTime::from_hms_opt(15, 0, 0).unwrap(),
             },
-            EntryKind::Task { completed: false },
+            EntryKind::task(false),
+            EntryKind::Task {
+                completed: false,
+                due: NaiveTime::from_hms_opt(17, 0, 0),
+                priority: TaskPriority::High,
+                checklist: vec![ChecklistItem::new("Pack").completed()],
+                roll_over: true,
+            },
             EntryKind::MultiDay,
         ];
 
@@ -328,6 +588,15 @@ mod tests {
         assert!(parse_role("reader").is_ok());
     }
 
+    #[test]
+    fn test_parse_timezone() {
+        assert_eq!(
+            parse_timezone("America/Montevideo").unwrap(),
+            chrono_tz::America::Montevideo
+        );
+        assert!(parse_timezone("Mars/Olympus").is_err());
+    }
+
     #[test]
     fn test_parse_role_invalid() {
         assert!(parse_role("admin").is_err());
@@ -380,6 +649,30 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_parse_recurrence() {
+        let rule = parse_recurrence("FREQ=WEEKLY;BYDAY=MO,FR").unwrap();
+        assert_eq!(rule.to_string(), "FREQ=WEEKLY;BYDAY=MO,FR");
+        assert!(parse_recurrence("FREQ=SOMETIMES").is_err());
+    }
+
+    #[test]
+    fn test_parse_tag_list() {
+        let tags = parse_tag_list("oncall,travel").unwrap();
+        assert_eq!(tags.len(), 2);
+        assert!(tags.contains(&"travel".parse().unwrap()));
+        assert!(parse_tag_list("not ok").is_err());
+    }
+
+    #[test]
+    fn test_search_match_expression() {
+        let query: SearchQuery = "Dentist main-st".parse().unwrap();
+        assert_eq!(
+            search_match_expression(&query),
+            r#""dentist"* "main"* "st"*"#
+        );
+    }
+
     #[test]
     fn test_parse_datetime_valid() {
         let result = parse_datetime("2024-06-15T10:30:00Z");
@@ -392,6 +685,121 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_occurrence_override_row_round_trip() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(
+            "CREATE TABLE entry_overrides (
+                series_id TEXT NOT NULL,
+                original_date TEXT NOT NULL,
+                replacement_json TEXT
+            )",
+            [],
+        )
+        .unwrap();
+
+        let series_id = Uuid::new_v4();
+        let original_date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+        let moved = CalendarEntry::all_day(
+            Uuid::new_v4(),
+            "Moved",
+            NaiveDate::from_ymd_opt(2024, 6, 11).unwrap(),
+        );
+        let overrides = [
+            OccurrenceOverride::replaced(series_id, original_date, moved),
+            OccurrenceOverride::cancelled(series_id, original_date.succ_opt().unwrap()),
+        ];
+        for o in &overrides {
+            conn.execute(
+                "INSERT INTO entry_overrides VALUES (?1, ?2, ?3)",
+                rusqlite::params![
+                    o.series_id.to_string(),
+                    format_date(&o.original_date),
+                    occurrence_replacement_to_json(o).unwrap()
+                ],
+            )
+            .unwrap();
+        }
+
+        let mut stmt = conn
+            .prepare("SELECT series_id, original_date, replacement_json FROM entry_overrides ORDER BY original_date")
+            .unwrap();
+        let parsed: Vec<OccurrenceOverride> = stmt
+            .query_map([], row_to_occurrence_override)
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+
+        assert_eq!(parsed, overrides);
+    }
+
+    #[test]
+    fn test_entry_revision_row_round_trip() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(
+            "CREATE TABLE entry_revisions (
+                entry_id TEXT NOT NULL,
+                revision INTEGER NOT NULL,
+                action TEXT NOT NULL,
+                actor_id TEXT,
+                recorded_at TEXT NOT NULL,
+                changed_fields TEXT,
+                snapshot_json TEXT NOT NULL,
+                overrides TEXT
+            )",
+            [],
+        )
+        .unwrap();
+
+        let entry = CalendarEntry::all_day(
+            Uuid::new_v4(),
+            "Dentist",
+            NaiveDate::from_ymd_opt(2024, 6, 10).unwrap(),
+        )
+        .with_recurrence(RecurrenceRule::weekly());
+        let before = entry.clone();
+        let after = entry.with_location("Clinic");
+        let overrides = vec![OccurrenceOverride::cancelled(
+            after.id,
+            NaiveDate::from_ymd_opt(2024, 6, 17).unwrap(),
+        )];
+        let revision = EntryRevision::new(
+            2,
+            RevisionAction::Updated,
+            after,
+            Some(Uuid::new_v4()),
+            DateTime::parse_from_rfc3339("2024-06-01T10:00:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+        )
+        .with_changes_from(&before)
+        .with_overrides(overrides, Some(&[]));
+
+        conn.execute(
+            "INSERT INTO entry_revisions VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            rusqlite::params![
+                revision.entry_id.to_string(),
+                revision.revision,
+                revision.action.to_string(),
+                revision.actor_id.map(|id| id.to_string()),
+                format_datetime(&revision.recorded_at),
+                revision.changed_fields.join(","),
+                serde_json::to_string(&revision.snapshot).unwrap(),
+                serde_json::to_string(&revision.overrides).unwrap()
+            ],
+        )
+        .unwrap();
+
+        let parsed = conn
+            .query_row(
+                "SELECT entry_id, revision, action, actor_id, recorded_at, changed_fields, snapshot_json, overrides FROM entry_revisions",
+                [],
+                row_to_entry_revision,
+            )
+            .unwrap();
+        assert_eq!(parsed, revision);
+    }
+
     // Tests for row_to_user with provider fields require an actual rusqlite connection
     // These are integration-level tests using in-memory SQLite
 
@@ -406,7 +814,8 @@ mod tests {
                 provider TEXT,
                 provider_subject TEXT,
                 created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
+                updated_at TEXT NOT NULL,
+                timezone TEXT
             )",
             [],
         )
@@ -428,7 +837,7 @@ mod tests {
         .unwrap();
 
         let mut stmt = conn
-            .prepare("SELECT id, name, email, provider, provider_subject, created_at, updated_at FROM users")
+            .prepare("SELECT id, name, email, provider, provider_subject, created_at, updated_at, timezone FROM users")
             .unwrap();
         let user = stmt.query_row([], row_to_user).unwrap();
 
@@ -449,7 +858,8 @@ mod tests {
                 provider TEXT,
                 provider_subject TEXT,
                 created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
+                updated_at TEXT NOT NULL,
+                timezone TEXT
             )",
             [],
         )
@@ -469,7 +879,7 @@ mod tests {
         .unwrap();
 
         let mut stmt = conn
-            .prepare("SELECT id, name, email, provider, provider_subject, created_at, updated_at FROM users")
+            .prepare("SELECT id, name, email, provider, provider_subject, created_at, updated_at, timezone FROM users")
             .unwrap();
         let user = stmt.query_row([], row_to_user).unwrap();
 
@@ -490,7 +900,8 @@ mod tests {
                 provider TEXT,
                 provider_subject TEXT,
                 created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
+                updated_at TEXT NOT NULL,
+                timezone TEXT
             )",
             [],
         )
@@ -535,7 +946,7 @@ mod tests {
 
         let mut stmt = conn
             .prepare(
-                "SELECT u.id, u.name, u.email, u.provider, u.provider_subject, u.created_at, u.updated_at, m.role
+                "SELECT u.id, u.name, u.email, u.provider, u.provider_subject, u.created_at, u.updated_at, u.timezone, m.role
                  FROM users u
                  INNER JOIN memberships m ON u.id = m.user_id",
             )
@@ -548,4 +959,26 @@ mod tests {
         assert_eq!(user.provider_subject, Some("apple-subject-123".to_string()));
         assert_eq!(role, CalendarRole::Owner);
     }
+
+    #[test]
+    fn test_row_to_trashed_calendar_with_role() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT '660e8400-e29b-41d4-a716-446655440000', 'Work', '#3B82F6', NULL,
+                    '2024-06-15T10:30:00Z', '2024-06-15T10:30:00Z', NULL, 1, NULL, 0, 'writer',
+                    '2024-07-01T08:00:00Z'",
+            )
+            .unwrap();
+        let (trashed, role) = stmt
+            .query_row([], row_to_trashed_calendar_with_role)
+            .unwrap();
+
+        assert_eq!(trashed.calendar.name, "Work");
+        assert_eq!(
+            trashed.deleted_at,
+            parse_datetime("2024-07-01T08:00:00Z").unwrap()
+        );
+        assert_eq!(role, CalendarRole::Writer);
+    }
 }
@@ -2,21 +2,31 @@
 //!
 //! Implements the repository traits from `calendsync_core::storage` using SQLite.
 
+use std::collections::BTreeSet;
+
 use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
 use tokio_rusqlite::Connection;
 use uuid::Uuid;
 
 use calendsync_core::calendar::{
-    Calendar, CalendarEntry, CalendarMembership, CalendarRole, CalendarSettings, User,
+    duplicate_contents, expand_recurring_entries, matches_any_tag, rank_entries, Calendar,
+    CalendarEntry, CalendarMembership, CalendarRole, CalendarSettings, CalendarSubscription,
+    DuplicateOptions, DuplicateSummary, EntryRevision, EntryWrite, FeedToken, OccurrenceOverride,
+    SearchQuery, Tag, TrashedCalendar, TrashedEntry, User,
 };
 use calendsync_core::storage::{
-    CalendarRepository, DateRange, EntryRepository, MembershipRepository, RepositoryError, Result,
-    SettingsRepository, UserRepository,
+    CalendarRepository, DateRange, EntryRepository, FeedTokenRepository, MembershipRepository,
+    RepositoryError, Result, SettingsRepository, UserRepository,
 };
 
 use super::conversions::{
-    entry_kind_to_json, format_date, format_datetime, role_to_string, row_to_calendar,
-    row_to_calendar_with_role, row_to_entry, row_to_membership, row_to_user, row_to_user_with_role,
+    attendees_to_json, entry_kind_to_json, format_date, format_datetime,
+    occurrence_replacement_to_json, reminders_to_string, role_to_string, row_to_calendar,
+    row_to_calendar_with_role, row_to_entry, row_to_entry_revision, row_to_feed_token,
+    row_to_membership, row_to_occurrence_override, row_to_trashed_calendar_with_role,
+    row_to_trashed_entry, row_to_user, row_to_user_with_role, search_match_expression,
+    subscription_to_json,
 };
 use super::error::map_tokio_rusqlite_error_with_id;
 use super::schema;
@@ -26,6 +36,196 @@ fn wrap_err(e: rusqlite::Error) -> tokio_rusqlite::Error {
     tokio_rusqlite::Error::Rusqlite(e)
 }
 
+/// Permanently deletes an entry and everything stored alongside it.
+fn purge_entry(tx: &rusqlite::Transaction, id: &str) -> rusqlite::Result<()> {
+    tx.execute(schema::DELETE_OVERRIDES_BY_SERIES, [id])?;
+    tx.execute(schema::DELETE_REMINDER_DELIVERIES_BY_ENTRY, [id])?;
+    tx.execute(schema::DELETE_ENTRY_TAGS, [id])?;
+    tx.execute(schema::DELETE_ENTRY_REVISIONS, [id])?;
+    tx.execute(schema::DELETE_ENTRY, [id])?;
+    Ok(())
+}
+
+/// Adds the columns in `schema::ADDED_COLUMNS` that existing tables lack.
+///
+/// Tables that don't exist yet are skipped; `CREATE_TABLES` creates them whole.
+fn add_missing_columns(conn: &rusqlite::Connection) -> rusqlite::Result<()> {
+    for (table, column, definition) in schema::ADDED_COLUMNS {
+        let columns = conn
+            .prepare(&format!("PRAGMA table_info({table})"))?
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        if !columns.is_empty() && !columns.iter().any(|name| name == column) {
+            conn.execute_batch(&format!(
+                "ALTER TABLE {table} ADD COLUMN {column} {definition}"
+            ))?;
+        }
+    }
+    Ok(())
+}
+
+/// Carries a conversion error out of a connection call.
+fn wrap_conversion_err(e: RepositoryError) -> tokio_rusqlite::Error {
+    wrap_err(rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+}
+
+/// Collects every row a single-parameter query returns.
+fn query_all<T>(
+    tx: &rusqlite::Transaction,
+    sql: &str,
+    param: &str,
+    map: impl FnMut(&rusqlite::Row) -> rusqlite::Result<T>,
+) -> rusqlite::Result<Vec<T>> {
+    let mut stmt = tx.prepare(sql)?;
+    let rows = stmt.query_map([param], map)?;
+    rows.collect()
+}
+
+/// Collects the single text column of every row a query returns.
+fn query_ids(tx: &rusqlite::Transaction, sql: &str, param: &str) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = tx.prepare(sql)?;
+    let rows = stmt.query_map([param], |row| row.get(0))?;
+    rows.collect()
+}
+
+/// An entry's column values, owned so they can move into a connection call.
+struct EntryRow {
+    id: String,
+    calendar_id: String,
+    title: String,
+    description: Option<String>,
+    location: Option<String>,
+    kind_json: String,
+    start_date: String,
+    end_date: String,
+    color: Option<String>,
+    created_at: String,
+    updated_at: String,
+    recurrence: Option<String>,
+    timezone: Option<String>,
+    reminders: Option<String>,
+    attendees: Option<String>,
+    version: i64,
+    tags: Vec<String>,
+}
+
+impl EntryRow {
+    fn new(entry: &CalendarEntry) -> Result<Self> {
+        Ok(Self {
+            id: entry.id.to_string(),
+            calendar_id: entry.calendar_id.to_string(),
+            title: entry.title.clone(),
+            description: entry.description.clone(),
+            location: entry.location.clone(),
+            kind_json: entry_kind_to_json(&entry.kind)?,
+            start_date: format_date(&entry.start_date),
+            end_date: format_date(&entry.end_date),
+            color: entry.color.clone(),
+            created_at: format_datetime(&entry.created_at),
+            updated_at: format_datetime(&entry.updated_at),
+            recurrence: entry.recurrence.as_ref().map(ToString::to_string),
+            timezone: entry.timezone.map(|tz| tz.name().to_string()),
+            reminders: reminders_to_string(&entry.reminders),
+            attendees: attendees_to_json(&entry.attendees)?,
+            version: i64::try_from(entry.version).unwrap_or(i64::MAX),
+            tags: entry.tags.iter().map(ToString::to_string).collect(),
+        })
+    }
+
+    /// Inserts the entry and its tags.
+    fn insert(&self, tx: &rusqlite::Transaction) -> rusqlite::Result<()> {
+        tx.execute(
+            schema::INSERT_ENTRY,
+            rusqlite::params![
+                self.id,
+                self.calendar_id,
+                self.title,
+                self.description,
+                self.location,
+                self.kind_json,
+                self.start_date,
+                self.end_date,
+                self.color,
+                self.created_at,
+                self.updated_at,
+                self.recurrence,
+                self.timezone,
+                self.reminders,
+                self.attendees,
+                self.version
+            ],
+        )?;
+        for tag in &self.tags {
+            tx.execute(schema::INSERT_ENTRY_TAG, [&self.id, tag])?;
+        }
+        Ok(())
+    }
+
+    /// Updates the live entry and its tags, bumping the stored version.
+    ///
+    /// With `expected`, returns `false` without writing if the stored version
+    /// no longer matches it. Fails with no rows if the entry is gone.
+    fn update(&self, tx: &rusqlite::Transaction, expected: Option<i64>) -> rusqlite::Result<bool> {
+        let mut params: Vec<&dyn rusqlite::types::ToSql> = vec![
+            &self.id,
+            &self.title,
+            &self.description,
+            &self.location,
+            &self.kind_json,
+            &self.start_date,
+            &self.end_date,
+            &self.color,
+            &self.updated_at,
+            &self.recurrence,
+            &self.timezone,
+            &self.reminders,
+            &self.attendees,
+        ];
+        let sql = match &expected {
+            Some(version) => {
+                params.push(version);
+                schema::UPDATE_ENTRY_IF_VERSION
+            }
+            None => schema::UPDATE_ENTRY,
+        };
+        let rows = tx.execute(sql, params.as_slice())?;
+        if rows == 0 {
+            // Fails with no rows if the entry is gone; otherwise another
+            // writer changed it first
+            tx.query_row(schema::SELECT_ENTRY_VERSION, [&self.id], |row| {
+                row.get::<_, i64>(0)
+            })?;
+            return Ok(false);
+        }
+        tx.execute(schema::DELETE_ENTRY_TAGS, [&self.id])?;
+        for tag in &self.tags {
+            tx.execute(schema::INSERT_ENTRY_TAG, [&self.id, tag])?;
+        }
+        Ok(true)
+    }
+}
+
+/// A batched write, converted to column values up front.
+enum BatchRow {
+    Create(EntryRow),
+    Update(EntryRow, i64),
+    Delete(String),
+}
+
+impl BatchRow {
+    /// Applies the write; returns `false` if an update lost a version race.
+    fn apply(&self, tx: &rusqlite::Transaction, deleted_at: &str) -> rusqlite::Result<bool> {
+        match self {
+            Self::Create(row) => row.insert(tx).map(|()| true),
+            Self::Update(row, expected) => row.update(tx, Some(*expected)),
+            Self::Delete(id) => match tx.execute(schema::TRASH_ENTRY, [id.as_str(), deleted_at])? {
+                0 => Err(rusqlite::Error::QueryReturnedNoRows),
+                _ => Ok(true),
+            },
+        }
+    }
+}
+
 /// SQLite-based repository implementation.
 ///
 /// Provides async access to SQLite storage for all entity types.
@@ -64,6 +264,7 @@ impl SqliteRepository {
     /// Initialize the database schema.
     async fn init_schema(conn: &Connection) -> Result<()> {
         conn.call(|conn| {
+            add_missing_columns(conn).map_err(wrap_err)?;
             conn.execute_batch(schema::CREATE_TABLES)
                 .map_err(wrap_err)?;
             Ok(())
@@ -71,6 +272,103 @@ impl SqliteRepository {
         .await
         .map_err(|e| RepositoryError::QueryFailed(e.to_string()))
     }
+
+    /// Updates an entry and its tags, bumping the stored version.
+    ///
+    /// With `expected_version`, the update only applies while the stored
+    /// version still matches it.
+    async fn store_entry(
+        &self,
+        entry: &CalendarEntry,
+        expected_version: Option<u64>,
+    ) -> Result<()> {
+        let row = EntryRow::new(entry)?;
+        let expected = expected_version.map(|v| i64::try_from(v).unwrap_or(i64::MAX));
+        let entry_id = entry.id.to_string();
+
+        let applied = self
+            .conn
+            .call(move |conn| {
+                let tx = conn.transaction().map_err(wrap_err)?;
+                if !row.update(&tx, expected).map_err(wrap_err)? {
+                    return Ok(false);
+                }
+                tx.commit().map_err(wrap_err)?;
+                Ok(true)
+            })
+            .await
+            .map_err(|e| map_tokio_rusqlite_error_with_id(e, "CalendarEntry", entry_id))?;
+
+        match expected_version {
+            Some(expected) if !applied => Err(RepositoryError::VersionMismatch {
+                entity_type: "CalendarEntry",
+                id: entry.id.to_string(),
+                expected,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Updates a calendar, bumping the stored version.
+    ///
+    /// With `expected_version`, the update only applies while the stored
+    /// version still matches it.
+    async fn store_calendar(
+        &self,
+        calendar: &Calendar,
+        expected_version: Option<u64>,
+    ) -> Result<()> {
+        let id = calendar.id.to_string();
+        let name = calendar.name.clone();
+        let color = calendar.color.clone();
+        let description = calendar.description.clone();
+        let updated_at = format_datetime(&calendar.updated_at);
+        let timezone = calendar.timezone.map(|tz| tz.name().to_string());
+        let subscription = subscription_to_json(calendar)?;
+        let expected = expected_version.map(|v| i64::try_from(v).unwrap_or(i64::MAX));
+        let calendar_id = calendar.id.to_string();
+
+        let applied = self
+            .conn
+            .call(move |conn| {
+                let mut params: Vec<&dyn rusqlite::types::ToSql> = vec![
+                    &id,
+                    &name,
+                    &color,
+                    &description,
+                    &updated_at,
+                    &timezone,
+                    &subscription,
+                ];
+                let sql = match &expected {
+                    Some(version) => {
+                        params.push(version);
+                        schema::UPDATE_CALENDAR_IF_VERSION
+                    }
+                    None => schema::UPDATE_CALENDAR,
+                };
+                let rows = conn.execute(sql, params.as_slice()).map_err(wrap_err)?;
+                if rows == 0 {
+                    conn.query_row(schema::SELECT_CALENDAR_VERSION, [&id], |row| {
+                        row.get::<_, i64>(0)
+                    })
+                    .map_err(wrap_err)?;
+                    return Ok(false);
+                }
+                Ok(true)
+            })
+            .await
+            .map_err(|e| map_tokio_rusqlite_error_with_id(e, "Calendar", calendar_id))?;
+
+        match expected_version {
+            Some(expected) if !applied => Err(RepositoryError::VersionMismatch {
+                entity_type: "Calendar",
+                id: calendar.id.to_string(),
+                expected,
+            }),
+            _ => Ok(()),
+        }
+    }
 }
 
 // ============================================================================
@@ -117,191 +415,241 @@ impl EntryRepository for SqliteRepository {
                 for row_result in rows {
                     entries.push(row_result.map_err(wrap_err)?);
                 }
-                Ok(entries)
+
+                let mut stmt = conn
+                    .prepare(schema::SELECT_RECURRING_OVERRIDES_BY_CALENDAR)
+                    .map_err(wrap_err)?;
+                let rows = stmt
+                    .query_map([&calendar_id_str], row_to_occurrence_override)
+                    .map_err(wrap_err)?;
+
+                let mut overrides = Vec::new();
+                for row_result in rows {
+                    overrides.push(row_result.map_err(wrap_err)?);
+                }
+                Ok((entries, overrides))
             })
             .await
+            .map(|(entries, overrides)| expand_recurring_entries(entries, &overrides, &date_range))
             .map_err(|e| RepositoryError::QueryFailed(e.to_string()))
     }
 
-    async fn create_entry(&self, entry: &CalendarEntry) -> Result<()> {
-        let id = entry.id.to_string();
-        let calendar_id = entry.calendar_id.to_string();
-        let title = entry.title.clone();
-        let description = entry.description.clone();
-        let location = entry.location.clone();
-        let kind_json = entry_kind_to_json(&entry.kind)?;
-        let start_date = format_date(&entry.start_date);
-        let end_date = format_date(&entry.end_date);
-        let color = entry.color.clone();
-        let created_at = format_datetime(&entry.created_at);
-        let updated_at = format_datetime(&entry.updated_at);
-        let entry_id = entry.id.to_string();
+    async fn get_entries_by_tags(
+        &self,
+        calendar_id: Uuid,
+        tags: &BTreeSet<Tag>,
+        date_range: DateRange,
+    ) -> Result<Vec<CalendarEntry>> {
+        if tags.is_empty() {
+            return self.get_entries_by_calendar(calendar_id, date_range).await;
+        }
 
-        self.conn
+        let calendar_id_str = calendar_id.to_string();
+        let start_str = format_date(&date_range.start);
+        let end_str = format_date(&date_range.end);
+        let tags_json = serde_json::to_string(tags)
+            .map_err(|e| RepositoryError::Serialization(e.to_string()))?;
+
+        let (entries, overrides) = self
+            .conn
             .call(move |conn| {
-                conn.execute(
-                    schema::INSERT_ENTRY,
-                    rusqlite::params![
-                        id,
-                        calendar_id,
-                        title,
-                        description,
-                        location,
-                        kind_json,
-                        start_date,
-                        end_date,
-                        color,
-                        created_at,
-                        updated_at
-                    ],
-                )
-                .map_err(wrap_err)?;
-                Ok(())
+                let mut stmt = conn
+                    .prepare(schema::SELECT_ENTRIES_BY_CALENDAR_AND_TAGS)
+                    .map_err(wrap_err)?;
+                let rows = stmt
+                    .query_map(
+                        [&calendar_id_str, &start_str, &end_str, &tags_json],
+                        row_to_entry,
+                    )
+                    .map_err(wrap_err)?;
+
+                let mut entries = Vec::new();
+                for row_result in rows {
+                    entries.push(row_result.map_err(wrap_err)?);
+                }
+
+                let mut stmt = conn
+                    .prepare(schema::SELECT_RECURRING_OVERRIDES_BY_CALENDAR)
+                    .map_err(wrap_err)?;
+                let rows = stmt
+                    .query_map([&calendar_id_str], row_to_occurrence_override)
+                    .map_err(wrap_err)?;
+
+                let mut overrides = Vec::new();
+                for row_result in rows {
+                    overrides.push(row_result.map_err(wrap_err)?);
+                }
+                Ok((entries, overrides))
             })
             .await
-            .map_err(|e| map_tokio_rusqlite_error_with_id(e, "CalendarEntry", entry_id))
+            .map_err(|e| RepositoryError::QueryFailed(e.to_string()))?;
+
+        // Overrides can replace an occurrence's tags, so filter after expansion
+        let mut expanded = expand_recurring_entries(entries, &overrides, &date_range);
+        expanded.retain(|e| matches_any_tag(&e.tags, tags));
+        Ok(expanded)
     }
 
-    async fn update_entry(&self, entry: &CalendarEntry) -> Result<()> {
-        let id = entry.id.to_string();
-        let title = entry.title.clone();
-        let description = entry.description.clone();
-        let location = entry.location.clone();
-        let kind_json = entry_kind_to_json(&entry.kind)?;
-        let start_date = format_date(&entry.start_date);
-        let end_date = format_date(&entry.end_date);
-        let color = entry.color.clone();
-        let updated_at = format_datetime(&entry.updated_at);
-        let entry_id = entry.id.to_string();
+    async fn get_stored_entries_by_calendar(
+        &self,
+        calendar_id: Uuid,
+    ) -> Result<Vec<CalendarEntry>> {
+        let calendar_id_str = calendar_id.to_string();
 
         self.conn
             .call(move |conn| {
-                let rows = conn
-                    .execute(
-                        schema::UPDATE_ENTRY,
-                        rusqlite::params![
-                            id,
-                            title,
-                            description,
-                            location,
-                            kind_json,
-                            start_date,
-                            end_date,
-                            color,
-                            updated_at
-                        ],
-                    )
+                let mut stmt = conn
+                    .prepare(schema::SELECT_ENTRIES_BY_CALENDAR)
                     .map_err(wrap_err)?;
-                if rows == 0 {
-                    Err(wrap_err(rusqlite::Error::QueryReturnedNoRows))
-                } else {
-                    Ok(())
+                let rows = stmt
+                    .query_map([&calendar_id_str], row_to_entry)
+                    .map_err(wrap_err)?;
+
+                let mut entries = Vec::new();
+                for row_result in rows {
+                    entries.push(row_result.map_err(wrap_err)?);
                 }
+                Ok(entries)
             })
             .await
-            .map_err(|e| map_tokio_rusqlite_error_with_id(e, "CalendarEntry", entry_id))
+            .map_err(|e| RepositoryError::QueryFailed(e.to_string()))
     }
 
-    async fn delete_entry(&self, id: Uuid) -> Result<()> {
-        let id_str = id.to_string();
-        let entry_id = id.to_string();
+    async fn count_entries_by_calendar(&self, calendar_id: Uuid) -> Result<usize> {
+        let calendar_id_str = calendar_id.to_string();
 
         self.conn
             .call(move |conn| {
-                let rows = conn
-                    .execute(schema::DELETE_ENTRY, [&id_str])
-                    .map_err(wrap_err)?;
-                if rows == 0 {
-                    Err(wrap_err(rusqlite::Error::QueryReturnedNoRows))
-                } else {
-                    Ok(())
-                }
+                conn.query_row(
+                    schema::COUNT_ENTRIES_BY_CALENDAR,
+                    [&calendar_id_str],
+                    |row| row.get::<_, i64>(0),
+                )
+                .map_err(wrap_err)
             })
             .await
-            .map_err(|e| map_tokio_rusqlite_error_with_id(e, "CalendarEntry", entry_id))
+            .map(|count| count as usize)
+            .map_err(|e| RepositoryError::QueryFailed(e.to_string()))
     }
-}
 
-// ============================================================================
-// CalendarRepository implementation
-// ============================================================================
+    async fn search_entries(
+        &self,
+        calendar_ids: &[Uuid],
+        query: &SearchQuery,
+        limit: usize,
+    ) -> Result<Vec<CalendarEntry>> {
+        if calendar_ids.is_empty() || limit == 0 {
+            return Ok(Vec::new());
+        }
 
-#[async_trait]
-impl CalendarRepository for SqliteRepository {
-    async fn get_calendar(&self, id: Uuid) -> Result<Option<Calendar>> {
-        let id_str = id.to_string();
+        let match_expr = search_match_expression(query);
+        let calendar_ids_json = serde_json::to_string(calendar_ids)
+            .map_err(|e| RepositoryError::Serialization(e.to_string()))?;
+        let limit_sql = i64::try_from(limit).unwrap_or(i64::MAX);
 
-        self.conn
+        let entries = self
+            .conn
             .call(move |conn| {
-                let mut stmt = conn
-                    .prepare(schema::SELECT_CALENDAR_BY_ID)
+                let mut stmt = conn.prepare(schema::SEARCH_ENTRIES).map_err(wrap_err)?;
+                let rows = stmt
+                    .query_map(
+                        rusqlite::params![match_expr, calendar_ids_json, limit_sql],
+                        row_to_entry,
+                    )
                     .map_err(wrap_err)?;
-                match stmt.query_row([&id_str], row_to_calendar) {
-                    Ok(calendar) => Ok(Some(calendar)),
-                    Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-                    Err(e) => Err(wrap_err(e)),
+
+                let mut entries = Vec::new();
+                for row_result in rows {
+                    entries.push(row_result.map_err(wrap_err)?);
                 }
+                Ok(entries)
             })
             .await
-            .map_err(|e| map_tokio_rusqlite_error_with_id(e, "Calendar", id.to_string()))
+            .map_err(|e| RepositoryError::QueryFailed(e.to_string()))?;
+
+        // FTS5 picks the best matches; order them like the other backends do
+        Ok(rank_entries(query, entries, limit))
     }
 
-    async fn create_calendar(&self, calendar: &Calendar) -> Result<()> {
-        let id = calendar.id.to_string();
-        let name = calendar.name.clone();
-        let color = calendar.color.clone();
-        let description = calendar.description.clone();
-        let created_at = format_datetime(&calendar.created_at);
-        let updated_at = format_datetime(&calendar.updated_at);
-        let calendar_id = calendar.id.to_string();
+    async fn create_entry(&self, entry: &CalendarEntry) -> Result<()> {
+        let row = EntryRow::new(entry)?;
+        let entry_id = entry.id.to_string();
 
         self.conn
             .call(move |conn| {
-                conn.execute(
-                    schema::INSERT_CALENDAR,
-                    rusqlite::params![id, name, color, description, created_at, updated_at],
-                )
-                .map_err(wrap_err)?;
+                let tx = conn.transaction().map_err(wrap_err)?;
+                row.insert(&tx).map_err(wrap_err)?;
+                tx.commit().map_err(wrap_err)?;
                 Ok(())
             })
             .await
-            .map_err(|e| map_tokio_rusqlite_error_with_id(e, "Calendar", calendar_id))
+            .map_err(|e| map_tokio_rusqlite_error_with_id(e, "CalendarEntry", entry_id))
     }
 
-    async fn update_calendar(&self, calendar: &Calendar) -> Result<()> {
-        let id = calendar.id.to_string();
-        let name = calendar.name.clone();
-        let color = calendar.color.clone();
-        let description = calendar.description.clone();
-        let updated_at = format_datetime(&calendar.updated_at);
-        let calendar_id = calendar.id.to_string();
+    async fn update_entry(&self, entry: &CalendarEntry) -> Result<()> {
+        self.store_entry(entry, None).await
+    }
 
-        self.conn
+    async fn update_entry_if_version(
+        &self,
+        entry: &CalendarEntry,
+        expected_version: u64,
+    ) -> Result<()> {
+        self.store_entry(entry, Some(expected_version)).await
+    }
+
+    async fn move_entry(
+        &self,
+        id: Uuid,
+        calendar_id: Uuid,
+        expected_version: u64,
+    ) -> Result<CalendarEntry> {
+        let id_str = id.to_string();
+        let calendar_id_str = calendar_id.to_string();
+        let updated_at = format_datetime(&Utc::now());
+        let expected = i64::try_from(expected_version).unwrap_or(i64::MAX);
+
+        let moved = self
+            .conn
             .call(move |conn| {
                 let rows = conn
                     .execute(
-                        schema::UPDATE_CALENDAR,
-                        rusqlite::params![name, color, description, updated_at, id],
+                        schema::MOVE_ENTRY,
+                        rusqlite::params![id_str, calendar_id_str, updated_at, expected],
                     )
                     .map_err(wrap_err)?;
                 if rows == 0 {
-                    Err(wrap_err(rusqlite::Error::QueryReturnedNoRows))
-                } else {
-                    Ok(())
+                    // Fails with no rows if the entry is gone; otherwise
+                    // another writer changed it first
+                    conn.query_row(schema::SELECT_ENTRY_VERSION, [&id_str], |row| {
+                        row.get::<_, i64>(0)
+                    })
+                    .map_err(wrap_err)?;
+                    return Ok(None);
                 }
+                conn.query_row(schema::SELECT_ENTRY_BY_ID, [&id_str], row_to_entry)
+                    .map(Some)
+                    .map_err(wrap_err)
             })
             .await
-            .map_err(|e| map_tokio_rusqlite_error_with_id(e, "Calendar", calendar_id))
+            .map_err(|e| map_tokio_rusqlite_error_with_id(e, "CalendarEntry", id.to_string()))?;
+
+        moved.ok_or_else(|| RepositoryError::VersionMismatch {
+            entity_type: "CalendarEntry",
+            id: id.to_string(),
+            expected: expected_version,
+        })
     }
 
-    async fn delete_calendar(&self, id: Uuid) -> Result<()> {
+    async fn delete_entry(&self, id: Uuid) -> Result<()> {
         let id_str = id.to_string();
-        let calendar_id = id.to_string();
+        let deleted_at = format_datetime(&Utc::now());
+        let entry_id = id.to_string();
 
         self.conn
             .call(move |conn| {
                 let rows = conn
-                    .execute(schema::DELETE_CALENDAR, [&id_str])
+                    .execute(schema::TRASH_ENTRY, [&id_str, &deleted_at])
                     .map_err(wrap_err)?;
                 if rows == 0 {
                     Err(wrap_err(rusqlite::Error::QueryReturnedNoRows))
@@ -310,46 +658,752 @@ impl CalendarRepository for SqliteRepository {
                 }
             })
             .await
-            .map_err(|e| map_tokio_rusqlite_error_with_id(e, "Calendar", calendar_id))
+            .map_err(|e| map_tokio_rusqlite_error_with_id(e, "CalendarEntry", entry_id))
     }
-}
-
-// ============================================================================
-// UserRepository implementation
-// ============================================================================
 
-#[async_trait]
-impl UserRepository for SqliteRepository {
-    async fn get_user(&self, id: Uuid) -> Result<Option<User>> {
+    async fn delete_entry_if_version(&self, id: Uuid, expected_version: u64) -> Result<()> {
         let id_str = id.to_string();
+        let deleted_at = format_datetime(&Utc::now());
+        let expected = i64::try_from(expected_version).unwrap_or(i64::MAX);
 
-        self.conn
+        let applied = self
+            .conn
             .call(move |conn| {
-                let mut stmt = conn.prepare(schema::SELECT_USER_BY_ID).map_err(wrap_err)?;
-                match stmt.query_row([&id_str], row_to_user) {
-                    Ok(user) => Ok(Some(user)),
-                    Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-                    Err(e) => Err(wrap_err(e)),
+                let rows = conn
+                    .execute(
+                        schema::TRASH_ENTRY_IF_VERSION,
+                        rusqlite::params![id_str, deleted_at, expected],
+                    )
+                    .map_err(wrap_err)?;
+                if rows == 0 {
+                    // Fails with no rows if the entry is gone; otherwise
+                    // another writer changed it first
+                    conn.query_row(schema::SELECT_ENTRY_VERSION, [&id_str], |row| {
+                        row.get::<_, i64>(0)
+                    })
+                    .map_err(wrap_err)?;
+                    return Ok(false);
                 }
+                Ok(true)
             })
             .await
-            .map_err(|e| map_tokio_rusqlite_error_with_id(e, "User", id.to_string()))
+            .map_err(|e| map_tokio_rusqlite_error_with_id(e, "CalendarEntry", id.to_string()))?;
+
+        if !applied {
+            return Err(RepositoryError::VersionMismatch {
+                entity_type: "CalendarEntry",
+                id: id.to_string(),
+                expected: expected_version,
+            });
+        }
+        Ok(())
     }
 
-    async fn get_user_by_email(&self, email: &str) -> Result<Option<User>> {
-        let email = email.to_string();
+    async fn get_trashed_entries(&self, calendar_ids: &[Uuid]) -> Result<Vec<TrashedEntry>> {
+        if calendar_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let calendar_ids_json = serde_json::to_string(calendar_ids)
+            .map_err(|e| RepositoryError::Serialization(e.to_string()))?;
 
         self.conn
             .call(move |conn| {
                 let mut stmt = conn
-                    .prepare(schema::SELECT_USER_BY_EMAIL)
+                    .prepare(schema::SELECT_TRASHED_ENTRIES_BY_CALENDARS)
                     .map_err(wrap_err)?;
-                match stmt.query_row([&email], row_to_user) {
-                    Ok(user) => Ok(Some(user)),
-                    Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-                    Err(e) => Err(wrap_err(e)),
-                }
-            })
+                let rows = stmt
+                    .query_map([&calendar_ids_json], row_to_trashed_entry)
+                    .map_err(wrap_err)?;
+
+                let mut entries = Vec::new();
+                for row_result in rows {
+                    entries.push(row_result.map_err(wrap_err)?);
+                }
+                Ok(entries)
+            })
+            .await
+            .map_err(|e| RepositoryError::QueryFailed(e.to_string()))
+    }
+
+    async fn restore_entry(&self, id: Uuid) -> Result<CalendarEntry> {
+        let id_str = id.to_string();
+        let entry_id = id.to_string();
+
+        self.conn
+            .call(move |conn| {
+                let tx = conn.transaction().map_err(wrap_err)?;
+                let rows = tx
+                    .execute(schema::RESTORE_ENTRY, [&id_str])
+                    .map_err(wrap_err)?;
+                if rows == 0 {
+                    return Err(wrap_err(rusqlite::Error::QueryReturnedNoRows));
+                }
+                let entry = tx
+                    .query_row(schema::SELECT_ENTRY_BY_ID, [&id_str], row_to_entry)
+                    .map_err(wrap_err)?;
+                tx.commit().map_err(wrap_err)?;
+                Ok(entry)
+            })
+            .await
+            .map_err(|e| map_tokio_rusqlite_error_with_id(e, "TrashedEntry", entry_id))
+    }
+
+    async fn purge_entries(&self, deleted_before: DateTime<Utc>) -> Result<usize> {
+        let cutoff = format_datetime(&deleted_before);
+
+        self.conn
+            .call(move |conn| {
+                let tx = conn.transaction().map_err(wrap_err)?;
+                let ids = query_ids(&tx, schema::SELECT_PURGEABLE_ENTRY_IDS, &cutoff)
+                    .map_err(wrap_err)?;
+                for id in &ids {
+                    purge_entry(&tx, id).map_err(wrap_err)?;
+                }
+                tx.commit().map_err(wrap_err)?;
+                Ok(ids.len())
+            })
+            .await
+            .map_err(|e| RepositoryError::QueryFailed(e.to_string()))
+    }
+
+    async fn get_occurrence_overrides(&self, series_id: Uuid) -> Result<Vec<OccurrenceOverride>> {
+        let series_id_str = series_id.to_string();
+
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn
+                    .prepare(schema::SELECT_OVERRIDES_BY_SERIES)
+                    .map_err(wrap_err)?;
+                let rows = stmt
+                    .query_map([&series_id_str], row_to_occurrence_override)
+                    .map_err(wrap_err)?;
+
+                let mut overrides = Vec::new();
+                for row_result in rows {
+                    overrides.push(row_result.map_err(wrap_err)?);
+                }
+                Ok(overrides)
+            })
+            .await
+            .map_err(|e| RepositoryError::QueryFailed(e.to_string()))
+    }
+
+    async fn upsert_occurrence_override(&self, occurrence: &OccurrenceOverride) -> Result<()> {
+        let series_id = occurrence.series_id.to_string();
+        let original_date = format_date(&occurrence.original_date);
+        let replacement_json = occurrence_replacement_to_json(occurrence)?;
+        let updated_at = format_datetime(&chrono::Utc::now());
+        let entry_id = occurrence.series_id.to_string();
+
+        self.conn
+            .call(move |conn| {
+                // Inserts nothing when the series does not exist
+                let rows = conn
+                    .execute(
+                        schema::UPSERT_OVERRIDE,
+                        rusqlite::params![series_id, original_date, replacement_json, updated_at],
+                    )
+                    .map_err(wrap_err)?;
+                if rows == 0 {
+                    Err(wrap_err(rusqlite::Error::QueryReturnedNoRows))
+                } else {
+                    Ok(())
+                }
+            })
+            .await
+            .map_err(|e| map_tokio_rusqlite_error_with_id(e, "CalendarEntry", entry_id))
+    }
+
+    async fn delete_occurrence_overrides(&self, series_id: Uuid, from: NaiveDate) -> Result<()> {
+        let series_id_str = series_id.to_string();
+        let from_str = format_date(&from);
+
+        self.conn
+            .call(move |conn| {
+                conn.execute(
+                    schema::DELETE_OVERRIDES_FROM,
+                    rusqlite::params![series_id_str, from_str],
+                )
+                .map_err(wrap_err)?;
+                Ok(())
+            })
+            .await
+            .map_err(|e| RepositoryError::QueryFailed(e.to_string()))
+    }
+
+    async fn get_entries_with_reminders(
+        &self,
+        date_range: DateRange,
+    ) -> Result<Vec<CalendarEntry>> {
+        let start_str = format_date(&date_range.start);
+        let end_str = format_date(&date_range.end);
+
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn
+                    .prepare(schema::SELECT_ENTRIES_WITH_REMINDERS_BY_DATE_RANGE)
+                    .map_err(wrap_err)?;
+                let rows = stmt
+                    .query_map([&start_str, &end_str], row_to_entry)
+                    .map_err(wrap_err)?;
+
+                let mut entries = Vec::new();
+                for row_result in rows {
+                    entries.push(row_result.map_err(wrap_err)?);
+                }
+
+                let mut stmt = conn
+                    .prepare(schema::SELECT_RECURRING_OVERRIDES_WITH_REMINDERS)
+                    .map_err(wrap_err)?;
+                let rows = stmt
+                    .query_map([], row_to_occurrence_override)
+                    .map_err(wrap_err)?;
+
+                let mut overrides = Vec::new();
+                for row_result in rows {
+                    overrides.push(row_result.map_err(wrap_err)?);
+                }
+                Ok((entries, overrides))
+            })
+            .await
+            .map(|(entries, overrides)| {
+                let mut entries = expand_recurring_entries(entries, &overrides, &date_range);
+                // An override may have removed an occurrence's reminders
+                entries.retain(CalendarEntry::has_reminders);
+                entries
+            })
+            .map_err(|e| RepositoryError::QueryFailed(e.to_string()))
+    }
+
+    async fn claim_reminder(&self, entry_id: Uuid, fire_at: DateTime<Utc>) -> Result<bool> {
+        let entry_id = entry_id.to_string();
+        let fire_at = format_datetime(&fire_at);
+        let delivered_at = format_datetime(&Utc::now());
+
+        self.conn
+            .call(move |conn| {
+                // Inserts nothing when the reminder was already claimed
+                let rows = conn
+                    .execute(
+                        schema::INSERT_REMINDER_DELIVERY,
+                        rusqlite::params![entry_id, fire_at, delivered_at],
+                    )
+                    .map_err(wrap_err)?;
+                Ok(rows == 1)
+            })
+            .await
+            .map_err(|e| RepositoryError::QueryFailed(e.to_string()))
+    }
+
+    async fn append_entry_revision(&self, revision: &EntryRevision) -> Result<()> {
+        let entry_id = revision.entry_id.to_string();
+        let number = revision.revision;
+        let action = revision.action.to_string();
+        let actor_id = revision.actor_id.map(|id| id.to_string());
+        let recorded_at = format_datetime(&revision.recorded_at);
+        let changed_fields =
+            (!revision.changed_fields.is_empty()).then(|| revision.changed_fields.join(","));
+        let snapshot_json = serde_json::to_string(&revision.snapshot)
+            .map_err(|e| RepositoryError::Serialization(e.to_string()))?;
+        let overrides = revision
+            .overrides
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| RepositoryError::Serialization(e.to_string()))?;
+        let revision_id = format!("{entry_id}@{number}");
+
+        self.conn
+            .call(move |conn| {
+                conn.execute(
+                    schema::INSERT_ENTRY_REVISION,
+                    rusqlite::params![
+                        entry_id,
+                        number,
+                        action,
+                        actor_id,
+                        recorded_at,
+                        changed_fields,
+                        snapshot_json,
+                        overrides
+                    ],
+                )
+                .map_err(wrap_err)?;
+                Ok(())
+            })
+            .await
+            .map_err(|e| map_tokio_rusqlite_error_with_id(e, "EntryRevision", revision_id))
+    }
+
+    async fn get_entry_revisions(&self, entry_id: Uuid) -> Result<Vec<EntryRevision>> {
+        let entry_id_str = entry_id.to_string();
+
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn
+                    .prepare(schema::SELECT_ENTRY_REVISIONS)
+                    .map_err(wrap_err)?;
+                let rows = stmt
+                    .query_map([&entry_id_str], row_to_entry_revision)
+                    .map_err(wrap_err)?;
+
+                let mut revisions = Vec::new();
+                for row_result in rows {
+                    revisions.push(row_result.map_err(wrap_err)?);
+                }
+                Ok(revisions)
+            })
+            .await
+            .map_err(|e| RepositoryError::QueryFailed(e.to_string()))
+    }
+
+    async fn apply_entry_batch(&self, writes: &[EntryWrite]) -> Result<()> {
+        let rows = writes
+            .iter()
+            .map(|write| {
+                Ok(match write {
+                    EntryWrite::Create(entry) => BatchRow::Create(EntryRow::new(entry)?),
+                    EntryWrite::Update {
+                        entry,
+                        expected_version,
+                    } => BatchRow::Update(
+                        EntryRow::new(entry)?,
+                        i64::try_from(*expected_version).unwrap_or(i64::MAX),
+                    ),
+                    EntryWrite::Delete(id) => BatchRow::Delete(id.to_string()),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let deleted_at = format_datetime(&Utc::now());
+
+        // The whole batch runs in one transaction; on the first failure it is
+        // dropped uncommitted, rolling back every earlier write
+        let failure = self
+            .conn
+            .call(move |conn| {
+                let tx = conn.transaction().map_err(wrap_err)?;
+                for (index, row) in rows.iter().enumerate() {
+                    match row.apply(&tx, &deleted_at) {
+                        Ok(true) => {}
+                        Ok(false) => return Ok(Some((index, None))),
+                        Err(e) => return Ok(Some((index, Some(e)))),
+                    }
+                }
+                tx.commit().map_err(wrap_err)?;
+                Ok(None)
+            })
+            .await
+            .map_err(|e| RepositoryError::QueryFailed(e.to_string()))?;
+
+        let Some((index, error)) = failure else {
+            return Ok(());
+        };
+        let write = &writes[index];
+        let id = write.entry_id().to_string();
+        if let Some(e) = error {
+            return Err(map_tokio_rusqlite_error_with_id(
+                wrap_err(e),
+                "CalendarEntry",
+                id,
+            ));
+        }
+        let EntryWrite::Update {
+            expected_version, ..
+        } = write
+        else {
+            unreachable!("only updates can lose a version race");
+        };
+        Err(RepositoryError::VersionMismatch {
+            entity_type: "CalendarEntry",
+            id,
+            expected: *expected_version,
+        })
+    }
+}
+
+// ============================================================================
+// CalendarRepository implementation
+// ============================================================================
+
+#[async_trait]
+impl CalendarRepository for SqliteRepository {
+    async fn get_calendar(&self, id: Uuid) -> Result<Option<Calendar>> {
+        let id_str = id.to_string();
+
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn
+                    .prepare(schema::SELECT_CALENDAR_BY_ID)
+                    .map_err(wrap_err)?;
+                match stmt.query_row([&id_str], row_to_calendar) {
+                    Ok(calendar) => Ok(Some(calendar)),
+                    Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                    Err(e) => Err(wrap_err(e)),
+                }
+            })
+            .await
+            .map_err(|e| map_tokio_rusqlite_error_with_id(e, "Calendar", id.to_string()))
+    }
+
+    async fn create_calendar(&self, calendar: &Calendar) -> Result<()> {
+        let id = calendar.id.to_string();
+        let name = calendar.name.clone();
+        let color = calendar.color.clone();
+        let description = calendar.description.clone();
+        let created_at = format_datetime(&calendar.created_at);
+        let updated_at = format_datetime(&calendar.updated_at);
+        let timezone = calendar.timezone.map(|tz| tz.name().to_string());
+        let version = i64::try_from(calendar.version).unwrap_or(i64::MAX);
+        let subscription = subscription_to_json(calendar)?;
+        let is_default = calendar.is_default;
+        let calendar_id = calendar.id.to_string();
+
+        self.conn
+            .call(move |conn| {
+                conn.execute(
+                    schema::INSERT_CALENDAR,
+                    rusqlite::params![
+                        id,
+                        name,
+                        color,
+                        description,
+                        created_at,
+                        updated_at,
+                        timezone,
+                        version,
+                        subscription,
+                        is_default
+                    ],
+                )
+                .map_err(wrap_err)?;
+                Ok(())
+            })
+            .await
+            .map_err(|e| map_tokio_rusqlite_error_with_id(e, "Calendar", calendar_id))
+    }
+
+    async fn update_calendar(&self, calendar: &Calendar) -> Result<()> {
+        self.store_calendar(calendar, None).await
+    }
+
+    async fn update_calendar_if_version(
+        &self,
+        calendar: &Calendar,
+        expected_version: u64,
+    ) -> Result<()> {
+        self.store_calendar(calendar, Some(expected_version)).await
+    }
+
+    async fn record_subscription_refresh(
+        &self,
+        id: Uuid,
+        subscription: &CalendarSubscription,
+        expected_version: u64,
+    ) -> Result<()> {
+        let id_str = id.to_string();
+        let json = serde_json::to_string(subscription)
+            .map_err(|e| RepositoryError::Serialization(e.to_string()))?;
+        let expected = i64::try_from(expected_version).unwrap_or(i64::MAX);
+
+        let applied = self
+            .conn
+            .call(move |conn| {
+                let rows = conn
+                    .execute(
+                        schema::UPDATE_CALENDAR_SUBSCRIPTION_IF_VERSION,
+                        rusqlite::params![&id_str, &json, expected],
+                    )
+                    .map_err(wrap_err)?;
+                if rows == 0 {
+                    conn.query_row(schema::SELECT_CALENDAR_VERSION, [&id_str], |row| {
+                        row.get::<_, i64>(0)
+                    })
+                    .map_err(wrap_err)?;
+                    return Ok(false);
+                }
+                Ok(true)
+            })
+            .await
+            .map_err(|e| map_tokio_rusqlite_error_with_id(e, "Calendar", id.to_string()))?;
+
+        if !applied {
+            return Err(RepositoryError::VersionMismatch {
+                entity_type: "Calendar",
+                id: id.to_string(),
+                expected: expected_version,
+            });
+        }
+        Ok(())
+    }
+
+    async fn delete_calendar(&self, id: Uuid) -> Result<()> {
+        let id_str = id.to_string();
+        let deleted_at = format_datetime(&Utc::now());
+        let calendar_id = id.to_string();
+
+        self.conn
+            .call(move |conn| {
+                let tx = conn.transaction().map_err(wrap_err)?;
+                let rows = tx
+                    .execute(schema::TRASH_CALENDAR, [&id_str, &deleted_at])
+                    .map_err(wrap_err)?;
+                if rows == 0 {
+                    return Err(wrap_err(rusqlite::Error::QueryReturnedNoRows));
+                }
+                // Sharing the calendar's deletion time marks the entries to
+                // bring back when the calendar is restored
+                tx.execute(schema::TRASH_CALENDAR_ENTRIES, [&id_str, &deleted_at])
+                    .map_err(wrap_err)?;
+                tx.commit().map_err(wrap_err)?;
+                Ok(())
+            })
+            .await
+            .map_err(|e| map_tokio_rusqlite_error_with_id(e, "Calendar", calendar_id))
+    }
+
+    async fn restore_calendar(&self, id: Uuid) -> Result<Calendar> {
+        let id_str = id.to_string();
+        let calendar_id = id.to_string();
+
+        self.conn
+            .call(move |conn| {
+                let tx = conn.transaction().map_err(wrap_err)?;
+                tx.execute(schema::RESTORE_CALENDAR_ENTRIES, [&id_str])
+                    .map_err(wrap_err)?;
+                let rows = tx
+                    .execute(schema::RESTORE_CALENDAR, [&id_str])
+                    .map_err(wrap_err)?;
+                if rows == 0 {
+                    return Err(wrap_err(rusqlite::Error::QueryReturnedNoRows));
+                }
+                let calendar = tx
+                    .query_row(schema::SELECT_CALENDAR_BY_ID, [&id_str], row_to_calendar)
+                    .map_err(wrap_err)?;
+                tx.commit().map_err(wrap_err)?;
+                Ok(calendar)
+            })
+            .await
+            .map_err(|e| map_tokio_rusqlite_error_with_id(e, "TrashedCalendar", calendar_id))
+    }
+
+    async fn purge_calendars(&self, deleted_before: DateTime<Utc>) -> Result<usize> {
+        let cutoff = format_datetime(&deleted_before);
+
+        self.conn
+            .call(move |conn| {
+                let tx = conn.transaction().map_err(wrap_err)?;
+                let ids = query_ids(&tx, schema::SELECT_PURGEABLE_CALENDAR_IDS, &cutoff)
+                    .map_err(wrap_err)?;
+                for id in &ids {
+                    let entry_ids = query_ids(&tx, schema::SELECT_ENTRY_IDS_BY_CALENDAR, id)
+                        .map_err(wrap_err)?;
+                    for entry_id in &entry_ids {
+                        purge_entry(&tx, entry_id).map_err(wrap_err)?;
+                    }
+                    tx.execute(schema::DELETE_MEMBERSHIPS_BY_CALENDAR, [id])
+                        .map_err(wrap_err)?;
+                    tx.execute(schema::DELETE_SETTINGS_BY_CALENDAR, [id])
+                        .map_err(wrap_err)?;
+                    tx.execute(schema::DELETE_FEED_TOKENS_BY_CALENDAR, [id])
+                        .map_err(wrap_err)?;
+                    tx.execute(schema::DELETE_CALENDAR, [id])
+                        .map_err(wrap_err)?;
+                }
+                tx.commit().map_err(wrap_err)?;
+                Ok(ids.len())
+            })
+            .await
+            .map_err(|e| RepositoryError::QueryFailed(e.to_string()))
+    }
+
+    async fn get_subscribed_calendars(&self) -> Result<Vec<Calendar>> {
+        self.conn
+            .call(|conn| {
+                let mut stmt = conn
+                    .prepare(schema::SELECT_SUBSCRIBED_CALENDARS)
+                    .map_err(wrap_err)?;
+                let rows = stmt.query_map([], row_to_calendar).map_err(wrap_err)?;
+
+                let mut results = Vec::new();
+                for row_result in rows {
+                    results.push(row_result.map_err(wrap_err)?);
+                }
+                Ok(results)
+            })
+            .await
+            .map_err(|e| RepositoryError::QueryFailed(e.to_string()))
+    }
+
+    async fn list_calendars(&self) -> Result<Vec<Calendar>> {
+        self.conn
+            .call(|conn| {
+                let mut stmt = conn
+                    .prepare(schema::SELECT_LIVE_CALENDARS)
+                    .map_err(wrap_err)?;
+                let rows = stmt.query_map([], row_to_calendar).map_err(wrap_err)?;
+
+                let mut results = Vec::new();
+                for row_result in rows {
+                    results.push(row_result.map_err(wrap_err)?);
+                }
+                Ok(results)
+            })
+            .await
+            .map_err(|e| RepositoryError::QueryFailed(e.to_string()))
+    }
+
+    async fn duplicate_calendar(
+        &self,
+        source_id: Uuid,
+        copy: &Calendar,
+        options: &DuplicateOptions,
+    ) -> Result<DuplicateSummary> {
+        let source_id_str = source_id.to_string();
+        let copy_subscription = subscription_to_json(copy)?;
+        let copy = copy.clone();
+        let options = *options;
+        let calendar_id = source_id.to_string();
+
+        self.conn
+            .call(move |conn| {
+                // Reading the source in the same transaction copies a
+                // consistent snapshot of it
+                let tx = conn.transaction().map_err(wrap_err)?;
+                tx.query_row(
+                    schema::SELECT_CALENDAR_BY_ID,
+                    [&source_id_str],
+                    row_to_calendar,
+                )
+                .map_err(wrap_err)?;
+                let (entries, overrides) = if options.entries {
+                    (
+                        query_all(
+                            &tx,
+                            schema::SELECT_ENTRIES_BY_CALENDAR,
+                            &source_id_str,
+                            row_to_entry,
+                        )
+                        .map_err(wrap_err)?,
+                        query_all(
+                            &tx,
+                            schema::SELECT_OVERRIDES_BY_CALENDAR,
+                            &source_id_str,
+                            row_to_occurrence_override,
+                        )
+                        .map_err(wrap_err)?,
+                    )
+                } else {
+                    (Vec::new(), Vec::new())
+                };
+                let memberships = if options.members {
+                    query_all(
+                        &tx,
+                        schema::SELECT_MEMBERSHIPS_BY_CALENDAR,
+                        &source_id_str,
+                        row_to_membership,
+                    )
+                    .map_err(wrap_err)?
+                } else {
+                    Vec::new()
+                };
+                let contents =
+                    duplicate_contents(copy.id, &entries, &overrides, &memberships, &options);
+
+                tx.execute(
+                    schema::INSERT_CALENDAR,
+                    rusqlite::params![
+                        copy.id.to_string(),
+                        copy.name,
+                        copy.color,
+                        copy.description,
+                        format_datetime(&copy.created_at),
+                        format_datetime(&copy.updated_at),
+                        copy.timezone.map(|tz| tz.name().to_string()),
+                        i64::try_from(copy.version).unwrap_or(i64::MAX),
+                        copy_subscription,
+                        copy.is_default
+                    ],
+                )
+                .map_err(wrap_err)?;
+                for entry in &contents.entries {
+                    EntryRow::new(entry)
+                        .map_err(wrap_conversion_err)?
+                        .insert(&tx)
+                        .map_err(wrap_err)?;
+                }
+                let updated_at = format_datetime(&Utc::now());
+                for over in &contents.overrides {
+                    let replacement_json =
+                        occurrence_replacement_to_json(over).map_err(wrap_conversion_err)?;
+                    tx.execute(
+                        schema::UPSERT_OVERRIDE,
+                        rusqlite::params![
+                            over.series_id.to_string(),
+                            format_date(&over.original_date),
+                            replacement_json,
+                            updated_at
+                        ],
+                    )
+                    .map_err(wrap_err)?;
+                }
+                for membership in &contents.memberships {
+                    tx.execute(
+                        schema::INSERT_MEMBERSHIP,
+                        rusqlite::params![
+                            membership.calendar_id.to_string(),
+                            membership.user_id.to_string(),
+                            role_to_string(&membership.role),
+                            format_datetime(&membership.created_at),
+                            format_datetime(&membership.updated_at)
+                        ],
+                    )
+                    .map_err(wrap_err)?;
+                }
+                tx.commit().map_err(wrap_err)?;
+                Ok(contents.summary())
+            })
+            .await
+            .map_err(|e| map_tokio_rusqlite_error_with_id(e, "Calendar", calendar_id))
+    }
+}
+
+// ============================================================================
+// UserRepository implementation
+// ============================================================================
+
+#[async_trait]
+impl UserRepository for SqliteRepository {
+    async fn get_user(&self, id: Uuid) -> Result<Option<User>> {
+        let id_str = id.to_string();
+
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(schema::SELECT_USER_BY_ID).map_err(wrap_err)?;
+                match stmt.query_row([&id_str], row_to_user) {
+                    Ok(user) => Ok(Some(user)),
+                    Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                    Err(e) => Err(wrap_err(e)),
+                }
+            })
+            .await
+            .map_err(|e| map_tokio_rusqlite_error_with_id(e, "User", id.to_string()))
+    }
+
+    async fn get_user_by_email(&self, email: &str) -> Result<Option<User>> {
+        let email = email.to_string();
+
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn
+                    .prepare(schema::SELECT_USER_BY_EMAIL)
+                    .map_err(wrap_err)?;
+                match stmt.query_row([&email], row_to_user) {
+                    Ok(user) => Ok(Some(user)),
+                    Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                    Err(e) => Err(wrap_err(e)),
+                }
+            })
             .await
             .map_err(|e| RepositoryError::QueryFailed(e.to_string()))
     }
@@ -385,6 +1439,7 @@ impl UserRepository for SqliteRepository {
         let provider_subject = user.provider_subject.clone();
         let created_at = format_datetime(&user.created_at);
         let updated_at = format_datetime(&user.updated_at);
+        let timezone = user.timezone.map(|tz| tz.name().to_string());
         let user_id = user.id.to_string();
 
         self.conn
@@ -398,7 +1453,8 @@ impl UserRepository for SqliteRepository {
                         provider,
                         provider_subject,
                         created_at,
-                        updated_at
+                        updated_at,
+                        timezone
                     ],
                 )
                 .map_err(wrap_err)?;
@@ -415,6 +1471,7 @@ impl UserRepository for SqliteRepository {
         let provider = user.provider.clone();
         let provider_subject = user.provider_subject.clone();
         let updated_at = format_datetime(&user.updated_at);
+        let timezone = user.timezone.map(|tz| tz.name().to_string());
         let user_id = user.id.to_string();
 
         self.conn
@@ -422,7 +1479,15 @@ impl UserRepository for SqliteRepository {
                 let rows = conn
                     .execute(
                         schema::UPDATE_USER,
-                        rusqlite::params![id, name, email, provider, provider_subject, updated_at],
+                        rusqlite::params![
+                            id,
+                            name,
+                            email,
+                            provider,
+                            provider_subject,
+                            updated_at,
+                            timezone
+                        ],
                     )
                     .map_err(wrap_err)?;
                 if rows == 0 {
@@ -434,6 +1499,22 @@ impl UserRepository for SqliteRepository {
             .await
             .map_err(|e| map_tokio_rusqlite_error_with_id(e, "User", user_id))
     }
+
+    async fn list_users(&self) -> Result<Vec<User>> {
+        self.conn
+            .call(|conn| {
+                let mut stmt = conn.prepare(schema::SELECT_ALL_USERS).map_err(wrap_err)?;
+                let rows = stmt.query_map([], row_to_user).map_err(wrap_err)?;
+
+                let mut results = Vec::new();
+                for row_result in rows {
+                    results.push(row_result.map_err(wrap_err)?);
+                }
+                Ok(results)
+            })
+            .await
+            .map_err(|e| RepositoryError::QueryFailed(e.to_string()))
+    }
 }
 
 // ============================================================================
@@ -485,6 +1566,31 @@ impl MembershipRepository for SqliteRepository {
             .map_err(|e| RepositoryError::QueryFailed(e.to_string()))
     }
 
+    async fn get_trashed_calendars_for_user(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<(TrashedCalendar, CalendarRole)>> {
+        let user_id_str = user_id.to_string();
+
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn
+                    .prepare(schema::SELECT_TRASHED_CALENDARS_FOR_USER)
+                    .map_err(wrap_err)?;
+                let rows = stmt
+                    .query_map([&user_id_str], row_to_trashed_calendar_with_role)
+                    .map_err(wrap_err)?;
+
+                let mut results = Vec::new();
+                for row_result in rows {
+                    results.push(row_result.map_err(wrap_err)?);
+                }
+                Ok(results)
+            })
+            .await
+            .map_err(|e| RepositoryError::QueryFailed(e.to_string()))
+    }
+
     async fn get_users_for_calendar(&self, calendar_id: Uuid) -> Result<Vec<(User, CalendarRole)>> {
         let calendar_id_str = calendar_id.to_string();
 
@@ -593,38 +1699,124 @@ impl SettingsRepository for SqliteRepository {
             })
     }
 
-    async fn upsert_settings(
-        &self,
-        calendar_id: Uuid,
-        user_id: Uuid,
-        settings: &CalendarSettings,
-    ) -> Result<()> {
-        let calendar_id_str = calendar_id.to_string();
-        let user_id_str = user_id.to_string();
-        let settings_json = serde_json::to_string(settings)
-            .map_err(|e| RepositoryError::InvalidData(e.to_string()))?;
-        let updated_at = chrono::Utc::now().to_rfc3339();
-        let id_for_error = format!("{}:{}", calendar_id, user_id);
+    async fn upsert_settings(
+        &self,
+        calendar_id: Uuid,
+        user_id: Uuid,
+        settings: &CalendarSettings,
+    ) -> Result<()> {
+        let calendar_id_str = calendar_id.to_string();
+        let user_id_str = user_id.to_string();
+        let settings_json = serde_json::to_string(settings)
+            .map_err(|e| RepositoryError::InvalidData(e.to_string()))?;
+        let updated_at = chrono::Utc::now().to_rfc3339();
+        let id_for_error = format!("{}:{}", calendar_id, user_id);
+
+        self.conn
+            .call(move |conn| {
+                conn.execute(
+                    schema::UPSERT_SETTINGS,
+                    rusqlite::params![calendar_id_str, user_id_str, settings_json, updated_at],
+                )
+                .map_err(wrap_err)?;
+                Ok(())
+            })
+            .await
+            .map_err(|e| map_tokio_rusqlite_error_with_id(e, "CalendarSettings", id_for_error))
+    }
+}
+
+// ============================================================================
+// FeedTokenRepository implementation
+// ============================================================================
+
+#[async_trait]
+impl FeedTokenRepository for SqliteRepository {
+    async fn get_feed_token(&self, token: &str) -> Result<Option<FeedToken>> {
+        let token = token.to_string();
+
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(schema::SELECT_FEED_TOKEN).map_err(wrap_err)?;
+                match stmt.query_row([&token], row_to_feed_token) {
+                    Ok(feed_token) => Ok(Some(feed_token)),
+                    Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                    Err(e) => Err(wrap_err(e)),
+                }
+            })
+            .await
+            .map_err(|e| RepositoryError::QueryFailed(e.to_string()))
+    }
+
+    async fn get_feed_token_for_user(
+        &self,
+        calendar_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<FeedToken>> {
+        let calendar_id_str = calendar_id.to_string();
+        let user_id_str = user_id.to_string();
+
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn
+                    .prepare(schema::SELECT_FEED_TOKEN_FOR_USER)
+                    .map_err(wrap_err)?;
+                match stmt.query_row([&calendar_id_str, &user_id_str], row_to_feed_token) {
+                    Ok(feed_token) => Ok(Some(feed_token)),
+                    Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                    Err(e) => Err(wrap_err(e)),
+                }
+            })
+            .await
+            .map_err(|e| RepositoryError::QueryFailed(e.to_string()))
+    }
+
+    async fn put_feed_token(&self, token: &FeedToken) -> Result<()> {
+        let token_str = token.token.clone();
+        let calendar_id_str = token.calendar_id.to_string();
+        let user_id_str = token.user_id.to_string();
+        let created_at = format_datetime(&token.created_at);
+        let id_for_error = format!("{}:{}", token.calendar_id, token.user_id);
 
         self.conn
             .call(move |conn| {
                 conn.execute(
-                    schema::UPSERT_SETTINGS,
-                    rusqlite::params![calendar_id_str, user_id_str, settings_json, updated_at],
+                    schema::UPSERT_FEED_TOKEN,
+                    rusqlite::params![token_str, calendar_id_str, user_id_str, created_at],
                 )
                 .map_err(wrap_err)?;
                 Ok(())
             })
             .await
-            .map_err(|e| map_tokio_rusqlite_error_with_id(e, "CalendarSettings", id_for_error))
+            .map_err(|e| map_tokio_rusqlite_error_with_id(e, "FeedToken", id_for_error))
+    }
+
+    async fn delete_feed_token(&self, calendar_id: Uuid, user_id: Uuid) -> Result<()> {
+        let calendar_id_str = calendar_id.to_string();
+        let user_id_str = user_id.to_string();
+        let id_for_error = format!("{}:{}", calendar_id, user_id);
+
+        self.conn
+            .call(move |conn| {
+                let rows = conn
+                    .execute(schema::DELETE_FEED_TOKEN, [&calendar_id_str, &user_id_str])
+                    .map_err(wrap_err)?;
+                if rows == 0 {
+                    Err(wrap_err(rusqlite::Error::QueryReturnedNoRows))
+                } else {
+                    Ok(())
+                }
+            })
+            .await
+            .map_err(|e| map_tokio_rusqlite_error_with_id(e, "FeedToken", id_for_error))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use calendsync_core::calendar::{EntryStyle, ViewMode};
-    use calendsync_core::storage::{CalendarRepository, UserRepository};
+    use calendsync_core::calendar::{EntryStyle, RevisionAction, ViewMode};
+    use calendsync_core::storage::{CalendarRepository, MembershipRepository, UserRepository};
 
     async fn create_stub_calendar(repo: &SqliteRepository, id: Uuid) {
         repo.create_calendar(&Calendar {
@@ -633,6 +1825,9 @@ mod tests {
             color: "#000".to_string(),
             description: None,
             is_default: false,
+            timezone: None,
+            subscription: None,
+            version: 1,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
         })
@@ -647,6 +1842,7 @@ mod tests {
             email: format!("{}@test.com", id),
             provider: None,
             provider_subject: None,
+            timezone: None,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
         })
@@ -654,6 +1850,826 @@ mod tests {
         .unwrap();
     }
 
+    fn date(year: i32, month: u32, day: u32) -> chrono::NaiveDate {
+        chrono::NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    /// Tables as created before any columns were added to them.
+    const BASELINE_TABLES: &str = "CREATE TABLE entries (
+        id TEXT PRIMARY KEY,
+        calendar_id TEXT NOT NULL,
+        title TEXT NOT NULL,
+        description TEXT,
+        location TEXT,
+        kind TEXT NOT NULL,
+        start_date TEXT NOT NULL,
+        end_date TEXT NOT NULL,
+        color TEXT,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    );
+    CREATE TABLE users (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL,
+        email TEXT NOT NULL UNIQUE,
+        provider TEXT,
+        provider_subject TEXT,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    );
+    CREATE TABLE calendars (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL,
+        color TEXT NOT NULL,
+        description TEXT,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    );
+    CREATE TABLE entry_revisions (
+        entry_id TEXT NOT NULL,
+        revision INTEGER NOT NULL,
+        action TEXT NOT NULL,
+        actor_id TEXT,
+        recorded_at TEXT NOT NULL,
+        changed_fields TEXT,
+        snapshot_json TEXT NOT NULL,
+        PRIMARY KEY (entry_id, revision)
+    );";
+
+    async fn open_baseline_database() -> Connection {
+        let conn = Connection::open_in_memory().await.unwrap();
+        conn.call(|conn| {
+            conn.execute_batch(BASELINE_TABLES)?;
+            Ok(())
+        })
+        .await
+        .unwrap();
+        conn
+    }
+
+    #[tokio::test]
+    async fn test_add_missing_columns_upgrades_baseline_tables() {
+        let conn = open_baseline_database().await;
+
+        let missing = conn
+            .call(|conn| {
+                add_missing_columns(conn).map_err(wrap_err)?;
+                // Running it again must leave the migrated tables alone.
+                add_missing_columns(conn).map_err(wrap_err)?;
+                let mut missing = Vec::new();
+                for (table, column, _) in schema::ADDED_COLUMNS {
+                    let columns = conn
+                        .prepare(&format!("PRAGMA table_info({table})"))?
+                        .query_map([], |row| row.get::<_, String>(1))?
+                        .collect::<rusqlite::Result<Vec<_>>>()?;
+                    if !columns.iter().any(|name| name == column) {
+                        missing.push(format!("{table}.{column}"));
+                    }
+                }
+                Ok(missing)
+            })
+            .await
+            .unwrap();
+        assert!(missing.is_empty(), "missing columns: {missing:?}");
+    }
+
+    #[tokio::test]
+    async fn test_init_schema_upgrades_baseline_database() {
+        let conn = open_baseline_database().await;
+        SqliteRepository::init_schema(&conn).await.unwrap();
+        SqliteRepository::init_schema(&conn).await.unwrap();
+        let repo = SqliteRepository { conn };
+
+        let calendar_id = Uuid::new_v4();
+        create_stub_calendar(&repo, calendar_id).await;
+        let entry = CalendarEntry::all_day(calendar_id, "Migrated", date(2024, 1, 1));
+        repo.create_entry(&entry).await.unwrap();
+
+        let stored = repo.get_entry(entry.id).await.unwrap().unwrap();
+        assert_eq!(stored.title, "Migrated");
+    }
+
+    #[tokio::test]
+    async fn test_recurring_entry_roundtrip_and_expansion() {
+        let repo = SqliteRepository::new_in_memory().await.unwrap();
+        let calendar_id = Uuid::new_v4();
+        create_stub_calendar(&repo, calendar_id).await;
+
+        let series = CalendarEntry::all_day(calendar_id, "Rent", date(2024, 1, 1))
+            .with_recurrence("FREQ=MONTHLY;BYMONTHDAY=1".parse().unwrap());
+        let one_off = CalendarEntry::all_day(calendar_id, "Old", date(2024, 1, 2));
+        repo.create_entry(&series).await.unwrap();
+        repo.create_entry(&one_off).await.unwrap();
+
+        let stored = repo.get_entry(series.id).await.unwrap().unwrap();
+        assert_eq!(stored.recurrence, series.recurrence);
+
+        // Stored entries list the series once, unexpanded.
+        let all = repo
+            .get_stored_entries_by_calendar(calendar_id)
+            .await
+            .unwrap();
+        let ids: Vec<_> = all.iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![series.id, one_off.id]);
+
+        let range = DateRange::new(date(2024, 3, 1), date(2024, 4, 30)).unwrap();
+        let entries = repo
+            .get_entries_by_calendar(calendar_id, range)
+            .await
+            .unwrap();
+        let dates: Vec<_> = entries.iter().map(|e| e.start_date).collect();
+        assert_eq!(dates, vec![date(2024, 3, 1), date(2024, 4, 1)]);
+
+        // Clearing the rule stops the expansion.
+        let mut updated = stored;
+        updated.recurrence = None;
+        repo.update_entry(&updated).await.unwrap();
+        let entries = repo
+            .get_entries_by_calendar(calendar_id, range)
+            .await
+            .unwrap();
+        assert!(entries.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_timezone_roundtrip() {
+        use chrono_tz::{America::Montevideo, Europe::Madrid};
+
+        let repo = SqliteRepository::new_in_memory().await.unwrap();
+
+        let mut calendar = Calendar::new("Work", "#3B82F6").with_timezone(Montevideo);
+        repo.create_calendar(&calendar).await.unwrap();
+        let stored = repo.get_calendar(calendar.id).await.unwrap().unwrap();
+        assert_eq!(stored.timezone, Some(Montevideo));
+
+        calendar.timezone = Some(Madrid);
+        repo.update_calendar(&calendar).await.unwrap();
+        let stored = repo.get_calendar(calendar.id).await.unwrap().unwrap();
+        assert_eq!(stored.timezone, Some(Madrid));
+
+        let entry = CalendarEntry::timed(
+            calendar.id,
+            "Call",
+            date(2024, 1, 15),
+            chrono::NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            chrono::NaiveTime::from_hms_opt(10, 0, 0).unwrap(),
+        )
+        .with_timezone(Montevideo);
+        repo.create_entry(&entry).await.unwrap();
+        let stored = repo.get_entry(entry.id).await.unwrap().unwrap();
+        assert_eq!(stored.timezone, Some(Montevideo));
+
+        let user = User::new("Ana", "ana@example.com").with_timezone(Madrid);
+        repo.create_user(&user).await.unwrap();
+        let stored = repo.get_user(user.id).await.unwrap().unwrap();
+        assert_eq!(stored.timezone, Some(Madrid));
+    }
+
+    #[tokio::test]
+    async fn test_subscription_roundtrip() {
+        let repo = SqliteRepository::new_in_memory().await.unwrap();
+
+        let plain = Calendar::new("Work", "#3B82F6");
+        let mut holidays =
+            Calendar::new("Holidays", "#10B981").with_subscription("https://example.com/h.ics");
+        repo.create_calendar(&plain).await.unwrap();
+        repo.create_calendar(&holidays).await.unwrap();
+
+        let subscribed = repo.get_subscribed_calendars().await.unwrap();
+        assert_eq!(subscribed.len(), 1);
+        assert_eq!(subscribed[0].subscription, holidays.subscription);
+
+        let subscription = holidays.subscription.as_mut().unwrap();
+        subscription.refreshed(chrono::Utc::now());
+        subscription.failed("HTTP 500");
+        repo.update_calendar(&holidays).await.unwrap();
+        let stored = repo.get_calendar(holidays.id).await.unwrap().unwrap();
+        assert_eq!(stored.subscription, holidays.subscription);
+
+        // Recording a refresh keeps the version, and only applies to it
+        let mut subscription = stored.subscription.clone().unwrap();
+        subscription.refreshed(chrono::Utc::now());
+        repo.record_subscription_refresh(holidays.id, &subscription, stored.version)
+            .await
+            .unwrap();
+        let refreshed = repo.get_calendar(holidays.id).await.unwrap().unwrap();
+        assert_eq!(refreshed.version, stored.version);
+        assert_eq!(refreshed.subscription, Some(subscription.clone()));
+        assert!(matches!(
+            repo.record_subscription_refresh(holidays.id, &subscription, stored.version + 1)
+                .await,
+            Err(RepositoryError::VersionMismatch { .. })
+        ));
+        assert!(matches!(
+            repo.record_subscription_refresh(Uuid::new_v4(), &subscription, 1)
+                .await,
+            Err(RepositoryError::NotFound { .. })
+        ));
+
+        repo.delete_calendar(holidays.id).await.unwrap();
+        assert!(repo.get_subscribed_calendars().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_default_calendar_roundtrip() {
+        let repo = SqliteRepository::new_in_memory().await.unwrap();
+        let user_id = Uuid::new_v4();
+        create_stub_user(&repo, user_id).await;
+
+        let personal = Calendar::new("Personal", "#3B82F6").as_default();
+        let work = Calendar::new("Work", "#10B981");
+        for calendar in [&personal, &work] {
+            repo.create_calendar(calendar).await.unwrap();
+            repo.create_membership(&CalendarMembership::owner(calendar.id, user_id))
+                .await
+                .unwrap();
+        }
+
+        let stored = repo.get_calendar(personal.id).await.unwrap().unwrap();
+        assert!(stored.is_default);
+
+        let calendars = repo.get_calendars_for_user(user_id).await.unwrap();
+        let defaults: Vec<_> = calendars
+            .iter()
+            .filter(|(calendar, _)| calendar.is_default)
+            .map(|(calendar, _)| calendar.id)
+            .collect();
+        assert_eq!(defaults, vec![personal.id]);
+    }
+
+    #[tokio::test]
+    async fn test_reminders_roundtrip_and_claims() {
+        use calendsync_core::calendar::Reminder;
+
+        let repo = SqliteRepository::new_in_memory().await.unwrap();
+        let calendar_id = Uuid::new_v4();
+        create_stub_calendar(&repo, calendar_id).await;
+
+        let series = CalendarEntry::all_day(calendar_id, "Rent", date(2024, 1, 1))
+            .with_recurrence("FREQ=MONTHLY;BYMONTHDAY=1".parse().unwrap())
+            .with_reminder("1d@09:00".parse().unwrap())
+            .with_reminder(Reminder::minutes_before(30));
+        let silent = CalendarEntry::all_day(calendar_id, "Quiet", date(2024, 3, 2));
+        repo.create_entry(&series).await.unwrap();
+        repo.create_entry(&silent).await.unwrap();
+
+        let stored = repo.get_entry(series.id).await.unwrap().unwrap();
+        assert_eq!(stored.reminders, series.reminders);
+
+        let range = DateRange::new(date(2024, 3, 1), date(2024, 4, 30)).unwrap();
+        let entries = repo.get_entries_with_reminders(range).await.unwrap();
+        let dates: Vec<_> = entries.iter().map(|e| e.start_date).collect();
+        assert_eq!(dates, vec![date(2024, 3, 1), date(2024, 4, 1)]);
+
+        let fire_at = chrono::Utc::now();
+        assert!(repo.claim_reminder(series.id, fire_at).await.unwrap());
+        assert!(!repo.claim_reminder(series.id, fire_at).await.unwrap());
+
+        // Removing every reminder stores NULL and drops the entry from the query.
+        let mut updated = stored;
+        updated.reminders.clear();
+        repo.update_entry(&updated).await.unwrap();
+        assert!(repo
+            .get_entries_with_reminders(range)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_attendees_roundtrip() {
+        use calendsync_core::calendar::{Attendee, ResponseStatus};
+
+        let repo = SqliteRepository::new_in_memory().await.unwrap();
+        let calendar_id = Uuid::new_v4();
+        create_stub_calendar(&repo, calendar_id).await;
+
+        let user = User::new("Ana", "ana@example.com");
+        let entry = CalendarEntry::all_day(calendar_id, "Planning", date(2024, 5, 6))
+            .with_attendee(Attendee::for_user(&user).with_status(ResponseStatus::Accepted))
+            .with_attendee(Attendee::new("guest@example.org"));
+        repo.create_entry(&entry).await.unwrap();
+
+        let mut stored = repo.get_entry(entry.id).await.unwrap().unwrap();
+        assert_eq!(stored.attendees, entry.attendees);
+
+        stored.attendees[1].status = ResponseStatus::Declined;
+        repo.update_entry(&stored).await.unwrap();
+        let updated = repo.get_entry(entry.id).await.unwrap().unwrap();
+        assert_eq!(updated.attendees[1].status, ResponseStatus::Declined);
+    }
+
+    #[tokio::test]
+    async fn test_tags_roundtrip_and_filter() {
+        let repo = SqliteRepository::new_in_memory().await.unwrap();
+        let calendar_id = Uuid::new_v4();
+        create_stub_calendar(&repo, calendar_id).await;
+
+        let tag = |s: &str| s.parse::<Tag>().unwrap();
+        let trip = CalendarEntry::all_day(calendar_id, "Trip", date(2024, 5, 6))
+            .with_tag(tag("travel"))
+            .with_tag(tag("personal"));
+        let shift =
+            CalendarEntry::all_day(calendar_id, "Shift", date(2024, 5, 7)).with_tag(tag("oncall"));
+        let plain = CalendarEntry::all_day(calendar_id, "Plain", date(2024, 5, 8));
+        for entry in [&trip, &shift, &plain] {
+            repo.create_entry(entry).await.unwrap();
+        }
+
+        let stored = repo.get_entry(trip.id).await.unwrap().unwrap();
+        assert_eq!(stored.tags, trip.tags);
+        assert!(repo
+            .get_entry(plain.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .tags
+            .is_empty());
+
+        let range = DateRange::new(date(2024, 5, 1), date(2024, 5, 31)).unwrap();
+        let wanted = BTreeSet::from([tag("travel"), tag("oncall")]);
+        let titles =
+            |entries: Vec<CalendarEntry>| entries.into_iter().map(|e| e.title).collect::<Vec<_>>();
+        let found = repo
+            .get_entries_by_tags(calendar_id, &wanted, range)
+            .await
+            .unwrap();
+        assert_eq!(titles(found), vec!["Trip", "Shift"]);
+
+        // Updating replaces the stored tag set
+        let mut updated = stored;
+        updated.tags = BTreeSet::from([tag("personal")]);
+        repo.update_entry(&updated).await.unwrap();
+        let found = repo
+            .get_entries_by_tags(calendar_id, &wanted, range)
+            .await
+            .unwrap();
+        assert_eq!(titles(found), vec!["Shift"]);
+
+        repo.delete_entry(shift.id).await.unwrap();
+        assert!(repo
+            .get_entries_by_tags(calendar_id, &wanted, range)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_search_entries() {
+        let repo = SqliteRepository::new_in_memory().await.unwrap();
+        let calendar_id = Uuid::new_v4();
+        let other_id = Uuid::new_v4();
+        create_stub_calendar(&repo, calendar_id).await;
+        create_stub_calendar(&repo, other_id).await;
+
+        let dentist = CalendarEntry::all_day(calendar_id, "Dentist", date(2024, 6, 10))
+            .with_location("Main St. clinic");
+        let checkup = CalendarEntry::all_day(calendar_id, "Checkup", date(2024, 6, 12))
+            .with_description("Ask the dentist about X-rays");
+        let elsewhere = CalendarEntry::all_day(other_id, "Dentist", date(2024, 6, 10));
+        for entry in [&dentist, &checkup, &elsewhere] {
+            repo.create_entry(entry).await.unwrap();
+        }
+
+        let query: SearchQuery = "dent".parse().unwrap();
+        let found = repo
+            .search_entries(&[calendar_id], &query, 10)
+            .await
+            .unwrap();
+        let ids: Vec<Uuid> = found.iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![dentist.id, checkup.id]);
+
+        // The triggers keep the index in step with updates and deletes
+        let mut renamed = dentist.clone();
+        renamed.title = "Orthodontist".to_string();
+        repo.update_entry(&renamed).await.unwrap();
+        repo.delete_entry(checkup.id).await.unwrap();
+        assert!(repo
+            .search_entries(&[calendar_id], &query, 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_occurrence_overrides() {
+        let repo = SqliteRepository::new_in_memory().await.unwrap();
+        let calendar_id = Uuid::new_v4();
+        create_stub_calendar(&repo, calendar_id).await;
+
+        let series = CalendarEntry::all_day(calendar_id, "Standup", date(2024, 6, 3))
+            .with_recurrence("FREQ=WEEKLY".parse().unwrap());
+        repo.create_entry(&series).await.unwrap();
+
+        let mut moved = series.clone().with_description("Moved to Tuesday");
+        moved.start_date = date(2024, 6, 11);
+        moved.end_date = date(2024, 6, 11);
+        repo.upsert_occurrence_override(&OccurrenceOverride::cancelled(
+            series.id,
+            date(2024, 6, 3),
+        ))
+        .await
+        .unwrap();
+        repo.upsert_occurrence_override(&OccurrenceOverride::cancelled(
+            series.id,
+            date(2024, 6, 10),
+        ))
+        .await
+        .unwrap();
+        // Upserting the same occurrence again replaces the previous override.
+        repo.upsert_occurrence_override(&OccurrenceOverride::replaced(
+            series.id,
+            date(2024, 6, 10),
+            moved,
+        ))
+        .await
+        .unwrap();
+
+        let overrides = repo.get_occurrence_overrides(series.id).await.unwrap();
+        assert_eq!(overrides.len(), 2);
+        assert!(overrides[0].is_cancelled());
+        assert_eq!(
+            overrides[1].replacement.as_ref().unwrap().description,
+            Some("Moved to Tuesday".to_string())
+        );
+
+        let range = DateRange::new(date(2024, 6, 1), date(2024, 6, 16)).unwrap();
+        let entries = repo
+            .get_entries_by_calendar(calendar_id, range)
+            .await
+            .unwrap();
+        let dates: Vec<_> = entries
+            .iter()
+            .map(|e| (e.start_date, e.occurrence_date))
+            .collect();
+        assert_eq!(dates, vec![(date(2024, 6, 11), Some(date(2024, 6, 10)))]);
+
+        repo.delete_occurrence_overrides(series.id, date(2024, 6, 10))
+            .await
+            .unwrap();
+        assert_eq!(
+            repo.get_occurrence_overrides(series.id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Overrides are kept while the series is in the trash and removed
+        // when it is purged.
+        repo.delete_entry(series.id).await.unwrap();
+        assert_eq!(
+            repo.get_occurrence_overrides(series.id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        repo.purge_entries(Utc::now() + chrono::Duration::seconds(1))
+            .await
+            .unwrap();
+        assert!(repo
+            .get_occurrence_overrides(series.id)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let result = repo
+            .upsert_occurrence_override(&OccurrenceOverride::cancelled(series.id, date(2024, 6, 3)))
+            .await;
+        assert!(matches!(result, Err(RepositoryError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_update_if_version() {
+        let repo = SqliteRepository::new_in_memory().await.unwrap();
+        let calendar_id = Uuid::new_v4();
+        create_stub_calendar(&repo, calendar_id).await;
+
+        let mut entry = CalendarEntry::all_day(calendar_id, "Dentist", date(2024, 6, 15));
+        repo.create_entry(&entry).await.unwrap();
+
+        entry.title = "Dentist (moved)".to_string();
+        repo.update_entry_if_version(&entry, 1).await.unwrap();
+        let stored = repo.get_entry(entry.id).await.unwrap().unwrap();
+        assert_eq!(stored.version, 2);
+
+        let result = repo.update_entry_if_version(&entry, 1).await;
+        assert!(matches!(
+            result,
+            Err(RepositoryError::VersionMismatch { expected: 1, .. })
+        ));
+        let result = repo.update_entry_if_version(&stored, 2).await;
+        assert!(result.is_ok());
+
+        let result = repo.delete_entry_if_version(entry.id, 2).await;
+        assert!(matches!(
+            result,
+            Err(RepositoryError::VersionMismatch { expected: 2, .. })
+        ));
+        repo.delete_entry_if_version(entry.id, 3).await.unwrap();
+        let result = repo.update_entry_if_version(&stored, 3).await;
+        assert!(matches!(result, Err(RepositoryError::NotFound { .. })));
+        let result = repo.delete_entry_if_version(entry.id, 3).await;
+        assert!(matches!(result, Err(RepositoryError::NotFound { .. })));
+
+        let mut calendar = repo.get_calendar(calendar_id).await.unwrap().unwrap();
+        calendar.name = "Renamed".to_string();
+        repo.update_calendar(&calendar).await.unwrap();
+        let result = repo.update_calendar_if_version(&calendar, 1).await;
+        assert!(matches!(
+            result,
+            Err(RepositoryError::VersionMismatch { .. })
+        ));
+        repo.update_calendar_if_version(&calendar, 2).await.unwrap();
+        let stored = repo.get_calendar(calendar_id).await.unwrap().unwrap();
+        assert_eq!(stored.version, 3);
+    }
+
+    #[tokio::test]
+    async fn test_move_entry() {
+        let repo = SqliteRepository::new_in_memory().await.unwrap();
+        let personal = Uuid::new_v4();
+        let family = Uuid::new_v4();
+        create_stub_calendar(&repo, personal).await;
+        create_stub_calendar(&repo, family).await;
+
+        let entry = CalendarEntry::all_day(personal, "Dentist", date(2024, 6, 15))
+            .with_tag("health".parse().unwrap());
+        repo.create_entry(&entry).await.unwrap();
+
+        let moved = repo.move_entry(entry.id, family, 1).await.unwrap();
+        assert_eq!(moved.id, entry.id);
+        assert_eq!(moved.calendar_id, family);
+        assert_eq!(moved.version, 2);
+        assert_eq!(moved.tags, entry.tags);
+
+        let range = DateRange::new(date(2024, 6, 1), date(2024, 6, 30)).unwrap();
+        assert!(repo
+            .get_entries_by_calendar(personal, range)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            repo.get_entries_by_calendar(family, range)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let result = repo.move_entry(entry.id, personal, 1).await;
+        assert!(matches!(
+            result,
+            Err(RepositoryError::VersionMismatch { expected: 1, .. })
+        ));
+        let result = repo.move_entry(Uuid::new_v4(), personal, 1).await;
+        assert!(matches!(result, Err(RepositoryError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_apply_entry_batch_is_atomic() {
+        let repo = SqliteRepository::new_in_memory().await.unwrap();
+        let calendar_id = Uuid::new_v4();
+        create_stub_calendar(&repo, calendar_id).await;
+
+        let physics = CalendarEntry::all_day(calendar_id, "Physics", date(2024, 9, 2));
+        let chemistry = CalendarEntry::all_day(calendar_id, "Chemistry", date(2024, 9, 3));
+        repo.create_entry(&physics).await.unwrap();
+        repo.create_entry(&chemistry).await.unwrap();
+
+        // A stale update fails the batch and rolls back the create before it
+        let biology = CalendarEntry::all_day(calendar_id, "Biology", date(2024, 9, 4))
+            .with_tag("lab".parse().unwrap());
+        let mut renamed = physics.clone();
+        renamed.title = "Physics I".to_string();
+        let stale = vec![
+            EntryWrite::Create(biology.clone()),
+            EntryWrite::Update {
+                entry: renamed.clone(),
+                expected_version: 5,
+            },
+        ];
+        let result = repo.apply_entry_batch(&stale).await;
+        assert!(matches!(
+            result,
+            Err(RepositoryError::VersionMismatch { expected: 5, .. })
+        ));
+        assert!(repo.get_entry(biology.id).await.unwrap().is_none());
+
+        let writes = vec![
+            EntryWrite::Create(biology.clone()),
+            EntryWrite::Update {
+                entry: renamed,
+                expected_version: 1,
+            },
+            EntryWrite::Delete(chemistry.id),
+        ];
+        repo.apply_entry_batch(&writes).await.unwrap();
+
+        let created = repo.get_entry(biology.id).await.unwrap().unwrap();
+        assert_eq!(created.tags, biology.tags);
+        let updated = repo.get_entry(physics.id).await.unwrap().unwrap();
+        assert_eq!(updated.title, "Physics I");
+        assert_eq!(updated.version, 2);
+        assert!(repo.get_entry(chemistry.id).await.unwrap().is_none());
+
+        let result = repo
+            .apply_entry_batch(&[EntryWrite::Delete(Uuid::new_v4())])
+            .await;
+        assert!(matches!(result, Err(RepositoryError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_entry_trash_restore_and_purge() {
+        let repo = SqliteRepository::new_in_memory().await.unwrap();
+        let calendar_id = Uuid::new_v4();
+        create_stub_calendar(&repo, calendar_id).await;
+
+        let entry = CalendarEntry::all_day(calendar_id, "Dentist", date(2024, 6, 15))
+            .with_tag("health".parse().unwrap());
+        repo.create_entry(&entry).await.unwrap();
+        repo.delete_entry(entry.id).await.unwrap();
+
+        assert!(repo.get_entry(entry.id).await.unwrap().is_none());
+        let query: SearchQuery = "dentist".parse().unwrap();
+        assert!(repo
+            .search_entries(&[calendar_id], &query, 10)
+            .await
+            .unwrap()
+            .is_empty());
+        let trashed = repo.get_trashed_entries(&[calendar_id]).await.unwrap();
+        assert_eq!(trashed.len(), 1);
+        assert_eq!(trashed[0].entry.tags, entry.tags);
+
+        let restored = repo.restore_entry(entry.id).await.unwrap();
+        assert_eq!(restored.title, "Dentist");
+        assert!(repo.get_entry(entry.id).await.unwrap().is_some());
+
+        repo.delete_entry(entry.id).await.unwrap();
+        let later = Utc::now() + chrono::Duration::seconds(1);
+        assert_eq!(repo.purge_entries(later).await.unwrap(), 1);
+        let result = repo.restore_entry(entry.id).await;
+        assert!(matches!(result, Err(RepositoryError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_entry_revisions() {
+        let repo = SqliteRepository::new_in_memory().await.unwrap();
+        let calendar_id = Uuid::new_v4();
+        create_stub_calendar(&repo, calendar_id).await;
+
+        let entry = CalendarEntry::all_day(calendar_id, "Dentist", date(2024, 6, 15));
+        let created =
+            EntryRevision::new(1, RevisionAction::Created, entry.clone(), None, Utc::now());
+        let updated = EntryRevision::new(
+            2,
+            RevisionAction::Updated,
+            entry.clone().with_location("Clinic"),
+            Some(Uuid::new_v4()),
+            Utc::now(),
+        )
+        .with_changes_from(&entry);
+
+        repo.append_entry_revision(&updated).await.unwrap();
+        repo.append_entry_revision(&created).await.unwrap();
+        let history = repo.get_entry_revisions(entry.id).await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].action, RevisionAction::Created);
+        assert_eq!(history[1].changed_fields, vec!["location".to_string()]);
+        assert_eq!(history[1].actor_id, updated.actor_id);
+
+        // Revisions are immutable
+        let result = repo.append_entry_revision(&created).await;
+        assert!(matches!(result, Err(RepositoryError::AlreadyExists { .. })));
+
+        // History goes with the purged entry
+        repo.create_entry(&entry).await.unwrap();
+        repo.delete_entry(entry.id).await.unwrap();
+        repo.purge_entries(Utc::now() + chrono::Duration::seconds(1))
+            .await
+            .unwrap();
+        assert!(repo.get_entry_revisions(entry.id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_calendar_trash_restore_and_purge() {
+        let repo = SqliteRepository::new_in_memory().await.unwrap();
+        let calendar_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        create_stub_calendar(&repo, calendar_id).await;
+        create_stub_user(&repo, user_id).await;
+        repo.create_membership(&CalendarMembership::owner(calendar_id, user_id))
+            .await
+            .unwrap();
+
+        let kept = CalendarEntry::all_day(calendar_id, "Standup", date(2024, 6, 15));
+        let deleted_first = CalendarEntry::all_day(calendar_id, "Retro", date(2024, 6, 16));
+        repo.create_entry(&kept).await.unwrap();
+        repo.create_entry(&deleted_first).await.unwrap();
+        repo.delete_entry(deleted_first.id).await.unwrap();
+
+        repo.delete_calendar(calendar_id).await.unwrap();
+        assert!(repo.get_calendar(calendar_id).await.unwrap().is_none());
+        assert!(repo.get_entry(kept.id).await.unwrap().is_none());
+        assert!(repo
+            .get_calendars_for_user(user_id)
+            .await
+            .unwrap()
+            .is_empty());
+        let trashed = repo.get_trashed_calendars_for_user(user_id).await.unwrap();
+        assert_eq!(trashed.len(), 1);
+        assert_eq!(trashed[0].1, CalendarRole::Owner);
+
+        // Only the entries trashed with the calendar come back
+        repo.restore_calendar(calendar_id).await.unwrap();
+        assert!(repo.get_entry(kept.id).await.unwrap().is_some());
+        assert!(repo.get_entry(deleted_first.id).await.unwrap().is_none());
+
+        repo.delete_calendar(calendar_id).await.unwrap();
+        let later = Utc::now() + chrono::Duration::seconds(1);
+        assert_eq!(repo.purge_calendars(later).await.unwrap(), 1);
+        assert!(repo
+            .get_trashed_entries(&[calendar_id])
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(repo
+            .get_membership(calendar_id, user_id)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_calendar() {
+        let repo = SqliteRepository::new_in_memory().await.unwrap();
+        let source_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        create_stub_calendar(&repo, source_id).await;
+        create_stub_user(&repo, user_id).await;
+        repo.create_membership(&CalendarMembership::writer(source_id, user_id))
+            .await
+            .unwrap();
+
+        let tag: Tag = "launch".parse().unwrap();
+        let entry = CalendarEntry::all_day(source_id, "Kickoff", date(2024, 1, 8)).with_tag(tag);
+        let series = CalendarEntry::all_day(source_id, "Standup", date(2024, 1, 1))
+            .with_recurrence("FREQ=WEEKLY".parse().unwrap());
+        repo.create_entry(&entry).await.unwrap();
+        repo.create_entry(&series).await.unwrap();
+        repo.upsert_occurrence_override(&OccurrenceOverride::cancelled(
+            series.id,
+            date(2024, 1, 15),
+        ))
+        .await
+        .unwrap();
+
+        let copy = Calendar::new("Q2", "#3B82F6");
+        let options = DuplicateOptions {
+            entries: true,
+            shift_days: 91,
+            members: true,
+            owner: None,
+        };
+        let summary = repo
+            .duplicate_calendar(source_id, &copy, &options)
+            .await
+            .unwrap();
+        assert_eq!(
+            summary,
+            DuplicateSummary {
+                entries: 2,
+                members: 1
+            }
+        );
+
+        let copied = repo
+            .get_entries_by_calendar(copy.id, DateRange::month(2024, 4))
+            .await
+            .unwrap();
+        let kickoff = copied.iter().find(|e| e.title == "Kickoff").unwrap();
+        assert_ne!(kickoff.id, entry.id);
+        assert_eq!(kickoff.start_date, date(2024, 4, 8));
+        assert_eq!(kickoff.tags, entry.tags);
+        // The cancelled occurrence moved along with the series
+        let standups = copied.iter().filter(|e| e.title == "Standup").count();
+        assert_eq!(standups, 4);
+        assert_eq!(
+            repo.get_membership(copy.id, user_id)
+                .await
+                .unwrap()
+                .unwrap()
+                .role,
+            CalendarRole::Writer
+        );
+
+        let result = repo
+            .duplicate_calendar(Uuid::new_v4(), &Calendar::new("Q3", "#000"), &options)
+            .await;
+        assert!(matches!(result, Err(RepositoryError::NotFound { .. })));
+    }
+
     #[tokio::test]
     async fn test_settings_get_nonexistent() {
         let repo = SqliteRepository::new_in_memory().await.unwrap();
@@ -745,4 +2761,37 @@ mod tests {
         assert_eq!(retrieved1, settings1);
         assert_eq!(retrieved2, settings2);
     }
+
+    #[tokio::test]
+    async fn test_feed_token_put_rotate_and_delete() {
+        let repo = SqliteRepository::new_in_memory().await.unwrap();
+        let calendar_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        create_stub_calendar(&repo, calendar_id).await;
+        create_stub_user(&repo, user_id).await;
+
+        let token = FeedToken::new(calendar_id, user_id);
+        repo.put_feed_token(&token).await.unwrap();
+        let found = repo.get_feed_token(&token.token).await.unwrap().unwrap();
+        assert_eq!(found.calendar_id, calendar_id);
+        assert_eq!(found.user_id, user_id);
+
+        // Rotating replaces the old token
+        let rotated = FeedToken::new(calendar_id, user_id);
+        repo.put_feed_token(&rotated).await.unwrap();
+        assert_eq!(repo.get_feed_token(&token.token).await.unwrap(), None);
+        let found = repo
+            .get_feed_token_for_user(calendar_id, user_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.token, rotated.token);
+
+        repo.delete_feed_token(calendar_id, user_id).await.unwrap();
+        assert_eq!(repo.get_feed_token(&rotated.token).await.unwrap(), None);
+        assert!(matches!(
+            repo.delete_feed_token(calendar_id, user_id).await,
+            Err(RepositoryError::NotFound { .. })
+        ));
+    }
 }
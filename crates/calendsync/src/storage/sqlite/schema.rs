@@ -13,7 +13,8 @@ CREATE TABLE IF NOT EXISTS users (
     provider TEXT,
     provider_subject TEXT,
     created_at TEXT NOT NULL,
-    updated_at TEXT NOT NULL
+    updated_at TEXT NOT NULL,
+    timezone TEXT
 );
 
 -- Partial unique index for provider lookups (only for users with a provider)
@@ -27,7 +28,12 @@ CREATE TABLE IF NOT EXISTS calendars (
     color TEXT NOT NULL,
     description TEXT,
     created_at TEXT NOT NULL,
-    updated_at TEXT NOT NULL
+    updated_at TEXT NOT NULL,
+    timezone TEXT,
+    version INTEGER NOT NULL DEFAULT 1,
+    subscription TEXT,
+    is_default INTEGER NOT NULL DEFAULT 0,
+    deleted_at TEXT
 );
 
 -- Calendar entries table
@@ -43,9 +49,84 @@ CREATE TABLE IF NOT EXISTS entries (
     color TEXT,
     created_at TEXT NOT NULL,
     updated_at TEXT NOT NULL,
+    recurrence TEXT,
+    timezone TEXT,
+    reminders TEXT,
+    attendees TEXT,
+    version INTEGER NOT NULL DEFAULT 1,
+    deleted_at TEXT,
     FOREIGN KEY (calendar_id) REFERENCES calendars(id) ON DELETE CASCADE
 );
 
+-- Per-occurrence exceptions to recurring entries (NULL replacement = cancelled)
+CREATE TABLE IF NOT EXISTS entry_overrides (
+    series_id TEXT NOT NULL,
+    original_date TEXT NOT NULL,
+    replacement_json TEXT,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (series_id, original_date),
+    FOREIGN KEY (series_id) REFERENCES entries(id) ON DELETE CASCADE
+);
+
+-- Reminders already delivered, one row per entry and firing instant
+CREATE TABLE IF NOT EXISTS reminder_deliveries (
+    entry_id TEXT NOT NULL,
+    fire_at TEXT NOT NULL,
+    delivered_at TEXT NOT NULL,
+    PRIMARY KEY (entry_id, fire_at)
+);
+
+-- Entry revision history, one immutable row per change
+CREATE TABLE IF NOT EXISTS entry_revisions (
+    entry_id TEXT NOT NULL,
+    revision INTEGER NOT NULL,
+    action TEXT NOT NULL,
+    actor_id TEXT,
+    recorded_at TEXT NOT NULL,
+    changed_fields TEXT,
+    snapshot_json TEXT NOT NULL,
+    overrides TEXT,
+    PRIMARY KEY (entry_id, revision)
+);
+
+-- Entry tags, one row per entry and tag
+CREATE TABLE IF NOT EXISTS entry_tags (
+    entry_id TEXT NOT NULL,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (entry_id, tag),
+    FOREIGN KEY (entry_id) REFERENCES entries(id) ON DELETE CASCADE
+);
+
+-- Full-text index of entry titles, descriptions and locations, kept in step
+-- with the entries table by the triggers below
+CREATE VIRTUAL TABLE IF NOT EXISTS entry_search USING fts5(
+    entry_id UNINDEXED,
+    title,
+    description,
+    location,
+    tokenize = 'unicode61 remove_diacritics 0'
+);
+
+CREATE TRIGGER IF NOT EXISTS entries_search_insert AFTER INSERT ON entries BEGIN
+    INSERT INTO entry_search (entry_id, title, description, location)
+    VALUES (new.id, new.title, new.description, new.location);
+END;
+
+CREATE TRIGGER IF NOT EXISTS entries_search_delete AFTER DELETE ON entries BEGIN
+    DELETE FROM entry_search WHERE entry_id = old.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS entries_search_update AFTER UPDATE OF title, description, location ON entries BEGIN
+    DELETE FROM entry_search WHERE entry_id = old.id;
+    INSERT INTO entry_search (entry_id, title, description, location)
+    VALUES (new.id, new.title, new.description, new.location);
+END;
+
+-- Index entries created before the search table existed
+INSERT INTO entry_search (entry_id, title, description, location)
+SELECT id, title, description, location FROM entries
+WHERE id NOT IN (SELECT entry_id FROM entry_search);
+
 -- Calendar memberships table
 CREATE TABLE IF NOT EXISTS memberships (
     calendar_id TEXT NOT NULL,
@@ -61,6 +142,8 @@ CREATE TABLE IF NOT EXISTS memberships (
 -- Indexes for efficient queries
 CREATE INDEX IF NOT EXISTS idx_entries_calendar_id ON entries(calendar_id);
 CREATE INDEX IF NOT EXISTS idx_entries_calendar_range ON entries(calendar_id, start_date, end_date);
+CREATE INDEX IF NOT EXISTS idx_entries_deleted_at ON entries(deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_entry_tags_tag ON entry_tags(tag, entry_id);
 CREATE INDEX IF NOT EXISTS idx_memberships_user_id ON memberships(user_id);
 CREATE INDEX IF NOT EXISTS idx_users_email ON users(email);
 
@@ -74,54 +157,165 @@ CREATE TABLE IF NOT EXISTS calendar_settings (
     FOREIGN KEY (calendar_id) REFERENCES calendars(id) ON DELETE CASCADE,
     FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
 );
+
+-- Calendar feed tokens (per-user, per-calendar subscription secrets)
+CREATE TABLE IF NOT EXISTS feed_tokens (
+    token TEXT PRIMARY KEY,
+    calendar_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    UNIQUE (calendar_id, user_id),
+    FOREIGN KEY (calendar_id) REFERENCES calendars(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
 "#;
 
+/// Columns added to tables after their first release, as
+/// `(table, column, definition)`.
+///
+/// `CREATE TABLE IF NOT EXISTS` leaves existing tables as they are, so each
+/// column listed here is added with `ALTER TABLE ... ADD COLUMN` to databases
+/// that lack it before `CREATE_TABLES` runs.
+pub const ADDED_COLUMNS: &[(&str, &str, &str)] = &[
+    ("entries", "recurrence", "TEXT"),
+    ("users", "timezone", "TEXT"),
+    ("calendars", "timezone", "TEXT"),
+    ("entries", "timezone", "TEXT"),
+    ("entries", "reminders", "TEXT"),
+    ("entries", "attendees", "TEXT"),
+    ("calendars", "deleted_at", "TEXT"),
+    ("entries", "deleted_at", "TEXT"),
+    ("calendars", "version", "INTEGER NOT NULL DEFAULT 1"),
+    ("entries", "version", "INTEGER NOT NULL DEFAULT 1"),
+    ("calendars", "subscription", "TEXT"),
+    ("calendars", "is_default", "INTEGER NOT NULL DEFAULT 0"),
+    ("entry_revisions", "overrides", "TEXT"),
+];
+
 // User queries
 pub const INSERT_USER: &str = r#"
-INSERT INTO users (id, name, email, provider, provider_subject, created_at, updated_at)
-VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+INSERT INTO users (id, name, email, provider, provider_subject, created_at, updated_at, timezone)
+VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
 "#;
 
 pub const SELECT_USER_BY_ID: &str = r#"
-SELECT id, name, email, provider, provider_subject, created_at, updated_at
+SELECT id, name, email, provider, provider_subject, created_at, updated_at, timezone
 FROM users
 WHERE id = ?1
 "#;
 
 pub const SELECT_USER_BY_EMAIL: &str = r#"
-SELECT id, name, email, provider, provider_subject, created_at, updated_at
+SELECT id, name, email, provider, provider_subject, created_at, updated_at, timezone
 FROM users
 WHERE email = ?1
 "#;
 
+/// Every user, for backups.
+pub const SELECT_ALL_USERS: &str = r#"
+SELECT id, name, email, provider, provider_subject, created_at, updated_at, timezone
+FROM users
+ORDER BY id
+"#;
+
 pub const SELECT_USER_BY_PROVIDER: &str = r#"
-SELECT id, name, email, provider, provider_subject, created_at, updated_at
+SELECT id, name, email, provider, provider_subject, created_at, updated_at, timezone
 FROM users
 WHERE provider = ?1 AND provider_subject = ?2
 "#;
 
 pub const UPDATE_USER: &str = r#"
 UPDATE users
-SET name = ?2, email = ?3, provider = ?4, provider_subject = ?5, updated_at = ?6
+SET name = ?2, email = ?3, provider = ?4, provider_subject = ?5, updated_at = ?6, timezone = ?7
 WHERE id = ?1
 "#;
 
 // Calendar queries
 pub const INSERT_CALENDAR: &str = r#"
-INSERT INTO calendars (id, name, color, description, created_at, updated_at)
-VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+INSERT INTO calendars (id, name, color, description, created_at, updated_at, timezone, version, subscription, is_default)
+VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
 "#;
 
 pub const SELECT_CALENDAR_BY_ID: &str = r#"
-SELECT id, name, color, description, created_at, updated_at
+SELECT id, name, color, description, created_at, updated_at, timezone, version, subscription, is_default
 FROM calendars
-WHERE id = ?1
+WHERE id = ?1 AND deleted_at IS NULL
+"#;
+
+/// Live calendars that mirror an iCalendar feed.
+pub const SELECT_SUBSCRIBED_CALENDARS: &str = r#"
+SELECT id, name, color, description, created_at, updated_at, timezone, version, subscription, is_default
+FROM calendars
+WHERE subscription IS NOT NULL AND deleted_at IS NULL
+"#;
+
+/// Every live calendar, for backups.
+pub const SELECT_LIVE_CALENDARS: &str = r#"
+SELECT id, name, color, description, created_at, updated_at, timezone, version, subscription, is_default
+FROM calendars
+WHERE deleted_at IS NULL
+ORDER BY id
 "#;
 
 pub const UPDATE_CALENDAR: &str = r#"
 UPDATE calendars
-SET name = ?2, color = ?3, description = ?4, updated_at = ?5
-WHERE id = ?1
+SET name = ?2, color = ?3, description = ?4, updated_at = ?5, timezone = ?6, subscription = ?7, version = version + 1
+WHERE id = ?1 AND deleted_at IS NULL
+"#;
+
+/// Like `UPDATE_CALENDAR`, applied only while the stored version is `?8`.
+pub const UPDATE_CALENDAR_IF_VERSION: &str = r#"
+UPDATE calendars
+SET name = ?2, color = ?3, description = ?4, updated_at = ?5, timezone = ?6, subscription = ?7, version = version + 1
+WHERE id = ?1 AND deleted_at IS NULL AND version = ?8
+"#;
+
+/// Stores a calendar's subscription while its version is `?3`, keeping the version.
+pub const UPDATE_CALENDAR_SUBSCRIPTION_IF_VERSION: &str = r#"
+UPDATE calendars
+SET subscription = ?2
+WHERE id = ?1 AND deleted_at IS NULL AND version = ?3
+"#;
+
+pub const SELECT_CALENDAR_VERSION: &str = r#"
+SELECT version
+FROM calendars
+WHERE id = ?1 AND deleted_at IS NULL
+"#;
+
+/// Moves a calendar to the trash, stamping it with the time bound to `?2`.
+pub const TRASH_CALENDAR: &str = r#"
+UPDATE calendars
+SET deleted_at = ?2
+WHERE id = ?1 AND deleted_at IS NULL
+"#;
+
+/// Moves a calendar's live entries to the trash along with it.
+pub const TRASH_CALENDAR_ENTRIES: &str = r#"
+UPDATE entries
+SET deleted_at = ?2
+WHERE calendar_id = ?1 AND deleted_at IS NULL
+"#;
+
+/// Restores the entries trashed together with a calendar, i.e. those sharing
+/// its deletion time. Must run before `RESTORE_CALENDAR` clears that time.
+pub const RESTORE_CALENDAR_ENTRIES: &str = r#"
+UPDATE entries
+SET deleted_at = NULL
+WHERE calendar_id = ?1
+  AND deleted_at = (SELECT deleted_at FROM calendars WHERE id = ?1)
+"#;
+
+pub const RESTORE_CALENDAR: &str = r#"
+UPDATE calendars
+SET deleted_at = NULL
+WHERE id = ?1 AND deleted_at IS NOT NULL
+"#;
+
+/// Calendars trashed before the time bound to `?1`.
+pub const SELECT_PURGEABLE_CALENDAR_IDS: &str = r#"
+SELECT id
+FROM calendars
+WHERE deleted_at < ?1
 "#;
 
 pub const DELETE_CALENDAR: &str = r#"
@@ -131,29 +325,141 @@ WHERE id = ?1
 
 // Entry queries
 pub const INSERT_ENTRY: &str = r#"
-INSERT INTO entries (id, calendar_id, title, description, location, kind, start_date, end_date, color, created_at, updated_at)
-VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+INSERT INTO entries (id, calendar_id, title, description, location, kind, start_date, end_date, color, created_at, updated_at, recurrence, timezone, reminders, attendees, version)
+VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)
 "#;
 
 pub const SELECT_ENTRY_BY_ID: &str = r#"
-SELECT id, calendar_id, title, description, location, kind, start_date, end_date, color, created_at, updated_at
+SELECT id, calendar_id, title, description, location, kind, start_date, end_date, color, created_at, updated_at, recurrence, timezone, reminders, attendees, version,
+    (SELECT group_concat(tag, ',') FROM entry_tags WHERE entry_id = entries.id) AS tags
 FROM entries
-WHERE id = ?1
+WHERE id = ?1 AND deleted_at IS NULL
+"#;
+
+pub const SELECT_ENTRIES_BY_CALENDAR: &str = r#"
+SELECT id, calendar_id, title, description, location, kind, start_date, end_date, color, created_at, updated_at, recurrence, timezone, reminders, attendees, version,
+    (SELECT group_concat(tag, ',') FROM entry_tags WHERE entry_id = entries.id) AS tags
+FROM entries
+WHERE calendar_id = ?1 AND deleted_at IS NULL
+ORDER BY start_date
+"#;
+
+pub const COUNT_ENTRIES_BY_CALENDAR: &str = r#"
+SELECT COUNT(*) FROM entries WHERE calendar_id = ?1 AND deleted_at IS NULL
 "#;
 
 pub const SELECT_ENTRIES_BY_CALENDAR_AND_DATE_RANGE: &str = r#"
-SELECT id, calendar_id, title, description, location, kind, start_date, end_date, color, created_at, updated_at
+SELECT id, calendar_id, title, description, location, kind, start_date, end_date, color, created_at, updated_at, recurrence, timezone, reminders, attendees, version,
+    (SELECT group_concat(tag, ',') FROM entry_tags WHERE entry_id = entries.id) AS tags
 FROM entries
-WHERE calendar_id = ?1
-  AND start_date <= ?3
-  AND end_date >= ?2
+WHERE calendar_id = ?1 AND deleted_at IS NULL
+  AND ((start_date <= ?3 AND end_date >= ?2) OR recurrence IS NOT NULL)
 ORDER BY start_date ASC, end_date ASC
 "#;
 
+/// Like `SELECT_ENTRIES_BY_CALENDAR_AND_DATE_RANGE`, restricted to entries
+/// carrying any of the tags in the JSON array bound to `?4`.
+pub const SELECT_ENTRIES_BY_CALENDAR_AND_TAGS: &str = r#"
+SELECT id, calendar_id, title, description, location, kind, start_date, end_date, color, created_at, updated_at, recurrence, timezone, reminders, attendees, version,
+    (SELECT group_concat(tag, ',') FROM entry_tags WHERE entry_id = entries.id) AS tags
+FROM entries
+WHERE calendar_id = ?1 AND deleted_at IS NULL
+  AND ((start_date <= ?3 AND end_date >= ?2) OR recurrence IS NOT NULL)
+  AND id IN (SELECT entry_id FROM entry_tags WHERE tag IN (SELECT value FROM json_each(?4)))
+ORDER BY start_date ASC, end_date ASC
+"#;
+
+pub const SELECT_ENTRIES_WITH_REMINDERS_BY_DATE_RANGE: &str = r#"
+SELECT id, calendar_id, title, description, location, kind, start_date, end_date, color, created_at, updated_at, recurrence, timezone, reminders, attendees, version,
+    (SELECT group_concat(tag, ',') FROM entry_tags WHERE entry_id = entries.id) AS tags
+FROM entries
+WHERE reminders IS NOT NULL AND deleted_at IS NULL
+  AND ((start_date <= ?2 AND end_date >= ?1) OR recurrence IS NOT NULL)
+ORDER BY start_date ASC, end_date ASC
+"#;
+
+/// Entries in any calendar of the JSON array bound to `?2` whose text matches
+/// the FTS5 expression bound to `?1`, best first. Title matches weigh most,
+/// then location, then description.
+pub const SEARCH_ENTRIES: &str = r#"
+SELECT entries.id, entries.calendar_id, entries.title, entries.description, entries.location, entries.kind, entries.start_date, entries.end_date, entries.color, entries.created_at, entries.updated_at, entries.recurrence, entries.timezone, entries.reminders, entries.attendees, entries.version,
+    (SELECT group_concat(tag, ',') FROM entry_tags WHERE entry_tags.entry_id = entries.id) AS tags
+FROM entry_search
+JOIN entries ON entries.id = entry_search.entry_id
+WHERE entry_search MATCH ?1
+  AND entries.calendar_id IN (SELECT value FROM json_each(?2))
+  AND entries.deleted_at IS NULL
+ORDER BY bm25(entry_search, 0.0, 4.0, 1.0, 2.0), entries.start_date DESC
+LIMIT ?3
+"#;
+
 pub const UPDATE_ENTRY: &str = r#"
 UPDATE entries
-SET title = ?2, description = ?3, location = ?4, kind = ?5, start_date = ?6, end_date = ?7, color = ?8, updated_at = ?9
-WHERE id = ?1
+SET title = ?2, description = ?3, location = ?4, kind = ?5, start_date = ?6, end_date = ?7, color = ?8, updated_at = ?9, recurrence = ?10, timezone = ?11, reminders = ?12, attendees = ?13, version = version + 1
+WHERE id = ?1 AND deleted_at IS NULL
+"#;
+
+/// Like `UPDATE_ENTRY`, applied only while the stored version is `?14`.
+pub const UPDATE_ENTRY_IF_VERSION: &str = r#"
+UPDATE entries
+SET title = ?2, description = ?3, location = ?4, kind = ?5, start_date = ?6, end_date = ?7, color = ?8, updated_at = ?9, recurrence = ?10, timezone = ?11, reminders = ?12, attendees = ?13, version = version + 1
+WHERE id = ?1 AND deleted_at IS NULL AND version = ?14
+"#;
+
+/// Moves an entry to calendar `?2`, applied only while the stored version is `?4`.
+pub const MOVE_ENTRY: &str = r#"
+UPDATE entries
+SET calendar_id = ?2, updated_at = ?3, version = version + 1
+WHERE id = ?1 AND deleted_at IS NULL AND version = ?4
+"#;
+
+pub const SELECT_ENTRY_VERSION: &str = r#"
+SELECT version
+FROM entries
+WHERE id = ?1 AND deleted_at IS NULL
+"#;
+
+/// Moves an entry to the trash, stamping it with the time bound to `?2`.
+pub const TRASH_ENTRY: &str = r#"
+UPDATE entries
+SET deleted_at = ?2
+WHERE id = ?1 AND deleted_at IS NULL
+"#;
+
+/// Like `TRASH_ENTRY`, applied only while the stored version is `?3`.
+pub const TRASH_ENTRY_IF_VERSION: &str = r#"
+UPDATE entries
+SET deleted_at = ?2
+WHERE id = ?1 AND deleted_at IS NULL AND version = ?3
+"#;
+
+pub const RESTORE_ENTRY: &str = r#"
+UPDATE entries
+SET deleted_at = NULL
+WHERE id = ?1 AND deleted_at IS NOT NULL
+"#;
+
+/// Trashed entries in any calendar of the JSON array bound to `?1`.
+pub const SELECT_TRASHED_ENTRIES_BY_CALENDARS: &str = r#"
+SELECT id, calendar_id, title, description, location, kind, start_date, end_date, color, created_at, updated_at, recurrence, timezone, reminders, attendees, version,
+    (SELECT group_concat(tag, ',') FROM entry_tags WHERE entry_id = entries.id) AS tags,
+    deleted_at
+FROM entries
+WHERE calendar_id IN (SELECT value FROM json_each(?1))
+  AND deleted_at IS NOT NULL
+"#;
+
+/// Entries trashed before the time bound to `?1`.
+pub const SELECT_PURGEABLE_ENTRY_IDS: &str = r#"
+SELECT id
+FROM entries
+WHERE deleted_at < ?1
+"#;
+
+pub const SELECT_ENTRY_IDS_BY_CALENDAR: &str = r#"
+SELECT id
+FROM entries
+WHERE calendar_id = ?1
 "#;
 
 pub const DELETE_ENTRY: &str = r#"
@@ -161,6 +467,93 @@ DELETE FROM entries
 WHERE id = ?1
 "#;
 
+// Entry tag queries
+pub const INSERT_ENTRY_TAG: &str = r#"
+INSERT OR IGNORE INTO entry_tags (entry_id, tag)
+VALUES (?1, ?2)
+"#;
+
+pub const DELETE_ENTRY_TAGS: &str = r#"
+DELETE FROM entry_tags
+WHERE entry_id = ?1
+"#;
+
+// Occurrence override queries
+pub const SELECT_OVERRIDES_BY_SERIES: &str = r#"
+SELECT series_id, original_date, replacement_json
+FROM entry_overrides
+WHERE series_id = ?1
+ORDER BY original_date ASC
+"#;
+
+pub const SELECT_RECURRING_OVERRIDES_BY_CALENDAR: &str = r#"
+SELECT o.series_id, o.original_date, o.replacement_json
+FROM entry_overrides o
+INNER JOIN entries e ON e.id = o.series_id
+WHERE e.calendar_id = ?1 AND e.recurrence IS NOT NULL AND e.deleted_at IS NULL
+"#;
+
+pub const SELECT_OVERRIDES_BY_CALENDAR: &str = r#"
+SELECT o.series_id, o.original_date, o.replacement_json
+FROM entry_overrides o
+INNER JOIN entries e ON e.id = o.series_id
+WHERE e.calendar_id = ?1 AND e.deleted_at IS NULL
+"#;
+
+pub const SELECT_RECURRING_OVERRIDES_WITH_REMINDERS: &str = r#"
+SELECT o.series_id, o.original_date, o.replacement_json
+FROM entry_overrides o
+INNER JOIN entries e ON e.id = o.series_id
+WHERE e.reminders IS NOT NULL AND e.recurrence IS NOT NULL
+"#;
+
+pub const UPSERT_OVERRIDE: &str = r#"
+INSERT INTO entry_overrides (series_id, original_date, replacement_json, updated_at)
+SELECT ?1, ?2, ?3, ?4
+WHERE EXISTS (SELECT 1 FROM entries WHERE id = ?1 AND deleted_at IS NULL)
+ON CONFLICT (series_id, original_date)
+DO UPDATE SET replacement_json = excluded.replacement_json, updated_at = excluded.updated_at
+"#;
+
+pub const DELETE_OVERRIDES_FROM: &str = r#"
+DELETE FROM entry_overrides
+WHERE series_id = ?1 AND original_date >= ?2
+"#;
+
+pub const DELETE_OVERRIDES_BY_SERIES: &str = r#"
+DELETE FROM entry_overrides
+WHERE series_id = ?1
+"#;
+
+// Reminder delivery queries
+pub const INSERT_REMINDER_DELIVERY: &str = r#"
+INSERT OR IGNORE INTO reminder_deliveries (entry_id, fire_at, delivered_at)
+VALUES (?1, ?2, ?3)
+"#;
+
+pub const DELETE_REMINDER_DELIVERIES_BY_ENTRY: &str = r#"
+DELETE FROM reminder_deliveries
+WHERE entry_id = ?1
+"#;
+
+// Entry revision queries
+pub const INSERT_ENTRY_REVISION: &str = r#"
+INSERT INTO entry_revisions (entry_id, revision, action, actor_id, recorded_at, changed_fields, snapshot_json, overrides)
+VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+"#;
+
+pub const SELECT_ENTRY_REVISIONS: &str = r#"
+SELECT entry_id, revision, action, actor_id, recorded_at, changed_fields, snapshot_json, overrides
+FROM entry_revisions
+WHERE entry_id = ?1
+ORDER BY revision ASC
+"#;
+
+pub const DELETE_ENTRY_REVISIONS: &str = r#"
+DELETE FROM entry_revisions
+WHERE entry_id = ?1
+"#;
+
 // Membership queries
 pub const INSERT_MEMBERSHIP: &str = r#"
 INSERT INTO memberships (calendar_id, user_id, role, created_at, updated_at)
@@ -173,15 +566,28 @@ FROM memberships
 WHERE calendar_id = ?1 AND user_id = ?2
 "#;
 
+pub const SELECT_MEMBERSHIPS_BY_CALENDAR: &str = r#"
+SELECT calendar_id, user_id, role, created_at, updated_at
+FROM memberships
+WHERE calendar_id = ?1
+"#;
+
 pub const SELECT_CALENDARS_FOR_USER: &str = r#"
-SELECT c.id, c.name, c.color, c.description, c.created_at, c.updated_at, m.role
+SELECT c.id, c.name, c.color, c.description, c.created_at, c.updated_at, c.timezone, c.version, c.subscription, c.is_default, m.role
+FROM calendars c
+INNER JOIN memberships m ON c.id = m.calendar_id
+WHERE m.user_id = ?1 AND c.deleted_at IS NULL
+"#;
+
+pub const SELECT_TRASHED_CALENDARS_FOR_USER: &str = r#"
+SELECT c.id, c.name, c.color, c.description, c.created_at, c.updated_at, c.timezone, c.version, c.subscription, c.is_default, m.role, c.deleted_at
 FROM calendars c
 INNER JOIN memberships m ON c.id = m.calendar_id
-WHERE m.user_id = ?1
+WHERE m.user_id = ?1 AND c.deleted_at IS NOT NULL
 "#;
 
 pub const SELECT_USERS_FOR_CALENDAR: &str = r#"
-SELECT u.id, u.name, u.email, u.provider, u.provider_subject, u.created_at, u.updated_at, m.role
+SELECT u.id, u.name, u.email, u.provider, u.provider_subject, u.created_at, u.updated_at, u.timezone, m.role
 FROM users u
 INNER JOIN memberships m ON u.id = m.user_id
 WHERE m.calendar_id = ?1
@@ -192,6 +598,11 @@ DELETE FROM memberships
 WHERE calendar_id = ?1 AND user_id = ?2
 "#;
 
+pub const DELETE_MEMBERSHIPS_BY_CALENDAR: &str = r#"
+DELETE FROM memberships
+WHERE calendar_id = ?1
+"#;
+
 // Settings queries
 pub const SELECT_SETTINGS: &str = r#"
 SELECT settings_json
@@ -206,6 +617,41 @@ ON CONFLICT (calendar_id, user_id)
 DO UPDATE SET settings_json = excluded.settings_json, updated_at = excluded.updated_at
 "#;
 
+pub const DELETE_SETTINGS_BY_CALENDAR: &str = r#"
+DELETE FROM calendar_settings
+WHERE calendar_id = ?1
+"#;
+
+// Feed token queries
+pub const SELECT_FEED_TOKEN: &str = r#"
+SELECT token, calendar_id, user_id, created_at
+FROM feed_tokens
+WHERE token = ?1
+"#;
+
+pub const SELECT_FEED_TOKEN_FOR_USER: &str = r#"
+SELECT token, calendar_id, user_id, created_at
+FROM feed_tokens
+WHERE calendar_id = ?1 AND user_id = ?2
+"#;
+
+pub const UPSERT_FEED_TOKEN: &str = r#"
+INSERT INTO feed_tokens (token, calendar_id, user_id, created_at)
+VALUES (?1, ?2, ?3, ?4)
+ON CONFLICT (calendar_id, user_id)
+DO UPDATE SET token = excluded.token, created_at = excluded.created_at
+"#;
+
+pub const DELETE_FEED_TOKEN: &str = r#"
+DELETE FROM feed_tokens
+WHERE calendar_id = ?1 AND user_id = ?2
+"#;
+
+pub const DELETE_FEED_TOKENS_BY_CALENDAR: &str = r#"
+DELETE FROM feed_tokens
+WHERE calendar_id = ?1
+"#;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -216,10 +662,29 @@ mod tests {
         assert!(CREATE_TABLES.contains("CREATE TABLE IF NOT EXISTS users"));
         assert!(CREATE_TABLES.contains("CREATE TABLE IF NOT EXISTS calendars"));
         assert!(CREATE_TABLES.contains("CREATE TABLE IF NOT EXISTS entries"));
+        assert!(CREATE_TABLES.contains("CREATE TABLE IF NOT EXISTS entry_overrides"));
+        assert!(CREATE_TABLES.contains("CREATE TABLE IF NOT EXISTS reminder_deliveries"));
+        assert!(CREATE_TABLES.contains("CREATE TABLE IF NOT EXISTS entry_revisions"));
+        assert!(CREATE_TABLES.contains("CREATE TABLE IF NOT EXISTS entry_tags"));
+        assert!(CREATE_TABLES.contains("CREATE VIRTUAL TABLE IF NOT EXISTS entry_search"));
+        assert!(CREATE_TABLES.contains("CREATE TRIGGER IF NOT EXISTS entries_search_update"));
         assert!(CREATE_TABLES.contains("CREATE TABLE IF NOT EXISTS memberships"));
         assert!(CREATE_TABLES.contains("CREATE TABLE IF NOT EXISTS calendar_settings"));
     }
 
+    #[test]
+    fn test_added_columns_are_in_create_tables() {
+        for (table, column, definition) in ADDED_COLUMNS {
+            let create = format!("CREATE TABLE IF NOT EXISTS {table} (");
+            let start = CREATE_TABLES.find(&create).unwrap();
+            let body = &CREATE_TABLES[start..start + CREATE_TABLES[start..].find(");").unwrap()];
+            assert!(
+                body.contains(&format!("    {column} {definition}")),
+                "{table}.{column} missing from CREATE_TABLES"
+            );
+        }
+    }
+
     #[test]
     fn test_queries_contain_expected_keywords() {
         // User queries
@@ -230,6 +695,7 @@ mod tests {
         assert!(SELECT_USER_BY_ID.contains("provider"));
         assert!(SELECT_USER_BY_EMAIL.contains("email"));
         assert!(SELECT_USER_BY_EMAIL.contains("provider"));
+        assert!(SELECT_ALL_USERS.contains("FROM users"));
         assert!(SELECT_USER_BY_PROVIDER.contains("provider = ?1"));
         assert!(SELECT_USER_BY_PROVIDER.contains("provider_subject = ?2"));
         assert!(UPDATE_USER.contains("UPDATE"));
@@ -239,20 +705,69 @@ mod tests {
         assert!(INSERT_CALENDAR.contains("INSERT"));
         assert!(SELECT_CALENDAR_BY_ID.contains("SELECT"));
         assert!(UPDATE_CALENDAR.contains("UPDATE"));
+        assert!(UPDATE_CALENDAR.contains("version = version + 1"));
+        assert!(UPDATE_CALENDAR_IF_VERSION.contains("AND version = ?8"));
         assert!(DELETE_CALENDAR.contains("DELETE"));
+        assert!(SELECT_CALENDAR_BY_ID.contains("deleted_at IS NULL"));
+        assert!(SELECT_LIVE_CALENDARS.contains("deleted_at IS NULL"));
+        assert!(TRASH_CALENDAR.contains("SET deleted_at = ?2"));
+        assert!(TRASH_CALENDAR_ENTRIES.contains("calendar_id = ?1"));
+        assert!(RESTORE_CALENDAR_ENTRIES.contains("SELECT deleted_at FROM calendars"));
+        assert!(RESTORE_CALENDAR.contains("SET deleted_at = NULL"));
+        assert!(SELECT_PURGEABLE_CALENDAR_IDS.contains("deleted_at < ?1"));
 
         // Entry queries
         assert!(INSERT_ENTRY.contains("INSERT"));
         assert!(SELECT_ENTRY_BY_ID.contains("SELECT"));
+        assert!(COUNT_ENTRIES_BY_CALENDAR.contains("deleted_at IS NULL"));
         assert!(SELECT_ENTRIES_BY_CALENDAR_AND_DATE_RANGE.contains("start_date <="));
         assert!(SELECT_ENTRIES_BY_CALENDAR_AND_DATE_RANGE.contains("end_date >="));
+        assert!(SELECT_ENTRIES_BY_CALENDAR_AND_DATE_RANGE.contains("recurrence IS NOT NULL"));
         assert!(UPDATE_ENTRY.contains("UPDATE"));
+        assert!(UPDATE_ENTRY.contains("version = version + 1"));
+        assert!(UPDATE_ENTRY_IF_VERSION.contains("AND version = ?14"));
+        assert!(MOVE_ENTRY.contains("SET calendar_id = ?2"));
+        assert!(MOVE_ENTRY.contains("AND version = ?4"));
         assert!(DELETE_ENTRY.contains("DELETE"));
+        assert!(SELECT_ENTRY_BY_ID.contains("deleted_at IS NULL"));
+        assert!(SEARCH_ENTRIES.contains("entries.deleted_at IS NULL"));
+        assert!(TRASH_ENTRY.contains("SET deleted_at = ?2"));
+        assert!(RESTORE_ENTRY.contains("SET deleted_at = NULL"));
+        assert!(SELECT_TRASHED_ENTRIES_BY_CALENDARS.contains("deleted_at IS NOT NULL"));
+        assert!(SELECT_PURGEABLE_ENTRY_IDS.contains("deleted_at < ?1"));
+        assert!(SELECT_ENTRY_IDS_BY_CALENDAR.contains("calendar_id = ?1"));
+        assert!(SELECT_ENTRY_BY_ID.contains("AS tags"));
+        assert!(SELECT_ENTRIES_BY_CALENDAR_AND_TAGS.contains("json_each(?4)"));
+        assert!(SEARCH_ENTRIES.contains("MATCH ?1"));
+        assert!(SEARCH_ENTRIES.contains("bm25(entry_search"));
+
+        // Entry tag queries
+        assert!(INSERT_ENTRY_TAG.contains("INSERT OR IGNORE"));
+        assert!(DELETE_ENTRY_TAGS.contains("entry_id = ?1"));
+
+        // Occurrence override queries
+        assert!(SELECT_OVERRIDES_BY_SERIES.contains("series_id = ?1"));
+        assert!(SELECT_RECURRING_OVERRIDES_BY_CALENDAR.contains("JOIN"));
+        assert!(UPSERT_OVERRIDE.contains("ON CONFLICT"));
+        assert!(DELETE_OVERRIDES_FROM.contains("original_date >= ?2"));
+        assert!(DELETE_OVERRIDES_BY_SERIES.contains("DELETE"));
+        assert!(INSERT_ENTRY_REVISION.contains("INSERT INTO entry_revisions"));
+        assert!(SELECT_ENTRY_REVISIONS.contains("ORDER BY revision"));
+        assert!(DELETE_ENTRY_REVISIONS.contains("DELETE"));
+
+        // Reminder queries
+        assert!(SELECT_ENTRIES_WITH_REMINDERS_BY_DATE_RANGE.contains("reminders IS NOT NULL"));
+        assert!(SELECT_RECURRING_OVERRIDES_WITH_REMINDERS.contains("JOIN"));
+        assert!(INSERT_REMINDER_DELIVERY.contains("INSERT OR IGNORE"));
+        assert!(DELETE_REMINDER_DELIVERIES_BY_ENTRY.contains("DELETE"));
 
         // Membership queries
         assert!(INSERT_MEMBERSHIP.contains("INSERT"));
         assert!(SELECT_MEMBERSHIP.contains("SELECT"));
         assert!(SELECT_CALENDARS_FOR_USER.contains("JOIN"));
+        assert!(SELECT_CALENDARS_FOR_USER.contains("c.deleted_at IS NULL"));
+        assert!(SELECT_TRASHED_CALENDARS_FOR_USER.contains("c.deleted_at IS NOT NULL"));
+        assert!(DELETE_MEMBERSHIPS_BY_CALENDAR.contains("DELETE"));
         assert!(SELECT_USERS_FOR_CALENDAR.contains("JOIN"));
         assert!(DELETE_MEMBERSHIP.contains("DELETE"));
 
@@ -261,5 +776,13 @@ mod tests {
         assert!(SELECT_SETTINGS.contains("calendar_settings"));
         assert!(UPSERT_SETTINGS.contains("INSERT"));
         assert!(UPSERT_SETTINGS.contains("ON CONFLICT"));
+        assert!(DELETE_SETTINGS_BY_CALENDAR.contains("DELETE"));
+
+        // Feed token queries
+        assert!(SELECT_FEED_TOKEN.contains("feed_tokens"));
+        assert!(SELECT_FEED_TOKEN_FOR_USER.contains("user_id = ?2"));
+        assert!(UPSERT_FEED_TOKEN.contains("ON CONFLICT (calendar_id, user_id)"));
+        assert!(DELETE_FEED_TOKEN.contains("DELETE"));
+        assert!(DELETE_FEED_TOKENS_BY_CALENDAR.contains("DELETE"));
     }
 }
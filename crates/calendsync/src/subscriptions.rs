@@ -0,0 +1,574 @@
+//! Background task that refreshes calendars subscribed to iCalendar feeds.
+//!
+//! Every tick fetches the feed of each subscribed calendar and brings the
+//! calendar's entries in line with it. Writes go through the entry
+//! repository, so open clients see `entry_added`, `entry_updated` and
+//! `entry_deleted` events only for entries that actually changed. The outcome
+//! of each refresh is recorded on the calendar's subscription.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{NaiveDate, Utc};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::redirect::Policy;
+use thiserror::Error;
+use tokio::time::MissedTickBehavior;
+use uuid::Uuid;
+
+use calendsync_core::calendar::{
+    check_subscription_host, is_public_address, parse_ics, plan_subscription_sync,
+    subscription_entry_ids, EntryWrite, IcsError, RefreshReport, SubscriptionError,
+};
+use calendsync_core::storage::RepositoryError;
+
+use crate::config::Config;
+use crate::state::AppState;
+
+/// How long fetching a feed may take.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Largest feed that is accepted, in bytes.
+const MAX_FEED_BYTES: usize = 10 * 1024 * 1024;
+
+/// Most redirects followed while fetching a feed.
+const MAX_REDIRECTS: usize = 10;
+
+/// Errors that can occur while refreshing a subscribed calendar.
+#[derive(Debug, Error)]
+pub enum RefreshError {
+    #[error("Calendar not found")]
+    NotFound,
+
+    #[error("Calendar is not subscribed to a feed")]
+    NotSubscribed,
+
+    #[error("Failed to fetch feed: {0}")]
+    Fetch(String),
+
+    #[error("Invalid feed: {0}")]
+    Feed(#[from] IcsError),
+
+    #[error(transparent)]
+    Storage(#[from] RepositoryError),
+}
+
+/// Spawns the subscription refresher. It runs until the server shuts down.
+pub fn spawn_subscription_refresher(state: AppState, config: &Config) {
+    let interval = config.subscription_refresh_interval();
+    tokio::spawn(run_refresher(state, interval));
+}
+
+async fn run_refresher(state: AppState, interval: Duration) {
+    let mut shutdown_rx = state.subscribe_shutdown();
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    tracing::info!(
+        interval_secs = interval.as_secs(),
+        "Subscription refresher started"
+    );
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => refresh_all(&state).await,
+            _ = shutdown_rx.recv() => {
+                tracing::debug!("Subscription refresher shutting down");
+                break;
+            }
+        }
+    }
+}
+
+/// Refreshes every subscribed calendar, one at a time.
+async fn refresh_all(state: &AppState) {
+    let calendars = match state.calendar_repo.get_subscribed_calendars().await {
+        Ok(calendars) => calendars,
+        Err(err) => {
+            tracing::warn!(error = %err, "Failed to list subscribed calendars");
+            return;
+        }
+    };
+
+    for calendar in calendars {
+        // Failures are logged and recorded on the calendar by refresh_subscription
+        let _ = refresh_subscription(state, calendar.id).await;
+    }
+}
+
+/// Fetches a subscribed calendar's feed and applies it.
+///
+/// The outcome is recorded on the calendar's subscription: its refresh time
+/// on success, or the error otherwise.
+pub async fn refresh_subscription(
+    state: &AppState,
+    calendar_id: Uuid,
+) -> Result<RefreshReport, RefreshError> {
+    let calendar = state
+        .calendar_repo
+        .get_calendar(calendar_id)
+        .await?
+        .ok_or(RefreshError::NotFound)?;
+    let url = calendar
+        .subscription
+        .as_ref()
+        .ok_or(RefreshError::NotSubscribed)?
+        .url
+        .clone();
+
+    let result = match fetch_feed(&url, &state.subscription_allowed_hosts).await {
+        Ok(body) => apply_feed(state, calendar_id, &body).await,
+        Err(err) => Err(err),
+    };
+
+    match &result {
+        Ok(report) => tracing::info!(
+            calendar_id = %calendar_id,
+            created = report.created,
+            updated = report.updated,
+            deleted = report.deleted,
+            unchanged = report.unchanged,
+            skipped = report.skipped.len(),
+            "Refreshed subscribed calendar"
+        ),
+        Err(err) => tracing::warn!(
+            calendar_id = %calendar_id,
+            url = %url,
+            error = %err,
+            "Failed to refresh subscribed calendar"
+        ),
+    }
+    if let Err(err) = record_outcome(state, calendar_id, &result).await {
+        tracing::warn!(calendar_id = %calendar_id, error = %err, "Failed to record refresh");
+    }
+
+    result
+}
+
+/// Resolves feed hosts to their public addresses only, so a public name
+/// can't point the fetch at the server's own network. Hosts in
+/// `allowed_hosts` keep all their addresses.
+struct PublicResolver {
+    allowed_hosts: Vec<String>,
+}
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_ascii_lowercase();
+        let allowed = self.allowed_hosts.contains(&host);
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| allowed || is_public_address(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{host} resolves only to private addresses").into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Describes a failed fetch along with its causes, which reqwest leaves out
+/// of its own message.
+fn fetch_error(err: reqwest::Error) -> RefreshError {
+    let mut message = err.to_string();
+    let mut source = std::error::Error::source(&err);
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    RefreshError::Fetch(message)
+}
+
+/// Downloads a feed as text.
+///
+/// The feed's host and every redirect must be public addresses unless they
+/// are in `allowed_hosts`, and the body is read in chunks so a feed over
+/// `MAX_FEED_BYTES` is dropped without being buffered whole.
+async fn fetch_feed(url: &str, allowed_hosts: &[String]) -> Result<String, RefreshError> {
+    let host_error = |e: SubscriptionError| RefreshError::Fetch(e.to_string());
+
+    let parsed = reqwest::Url::parse(url).map_err(|e| RefreshError::Fetch(e.to_string()))?;
+    check_subscription_host(parsed.host_str().unwrap_or_default(), allowed_hosts)
+        .map_err(host_error)?;
+
+    let redirect_hosts = allowed_hosts.to_vec();
+    let client = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .user_agent(concat!("calendsync/", env!("CARGO_PKG_VERSION")))
+        .dns_resolver(Arc::new(PublicResolver {
+            allowed_hosts: allowed_hosts.to_vec(),
+        }))
+        .redirect(Policy::custom(move |attempt| {
+            if attempt.previous().len() >= MAX_REDIRECTS {
+                return attempt.error("too many redirects");
+            }
+            let host = attempt.url().host_str().unwrap_or_default().to_string();
+            match check_subscription_host(&host, &redirect_hosts) {
+                Ok(()) => attempt.follow(),
+                Err(err) => attempt.error(err),
+            }
+        }))
+        .build()
+        .map_err(fetch_error)?;
+
+    let mut response = client
+        .get(parsed)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(fetch_error)?;
+    if response
+        .content_length()
+        .is_some_and(|len| len > MAX_FEED_BYTES as u64)
+    {
+        return Err(RefreshError::Fetch("feed is too large".to_string()));
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(fetch_error)? {
+        if body.len() + chunk.len() > MAX_FEED_BYTES {
+            return Err(RefreshError::Fetch("feed is too large".to_string()));
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+/// Brings a calendar's entries in line with a feed.
+async fn apply_feed(
+    state: &AppState,
+    calendar_id: Uuid,
+    body: &str,
+) -> Result<RefreshReport, RefreshError> {
+    let import = parse_ics(body, calendar_id)?;
+
+    // Entries dropped from the feed went to the trash; bring back the ones
+    // it lists again so they keep their IDs
+    let listed = subscription_entry_ids(calendar_id, &import);
+    for trashed in state.entry_repo.get_trashed_entries(&[calendar_id]).await? {
+        if listed.contains(&trashed.entry.id) {
+            state
+                .entry_repo
+                .restore_entry_as(trashed.entry.id, None)
+                .await?;
+        }
+    }
+
+    let stored = state
+        .entry_repo
+        .get_stored_entries_by_calendar(calendar_id)
+        .await?;
+    let mut stored_overrides = HashMap::new();
+    for series in stored.iter().filter(|e| e.is_recurring()) {
+        let overrides = state.entry_repo.get_occurrence_overrides(series.id).await?;
+        stored_overrides.insert(series.id, overrides);
+    }
+
+    let sync = plan_subscription_sync(calendar_id, import, &stored, &stored_overrides);
+
+    // Writes go one by one rather than as a batch so clients get an event
+    // per entry; repository calls handle cache invalidation and publishing
+    for write in &sync.writes {
+        match write {
+            EntryWrite::Create(entry) => state.entry_repo.create_entry_as(entry, None).await?,
+            EntryWrite::Update {
+                entry,
+                expected_version,
+            } => {
+                state
+                    .entry_repo
+                    .update_entry_if_version_as(entry, *expected_version, None)
+                    .await?
+            }
+            EntryWrite::Delete(id) => state.entry_repo.delete_entry_as(*id, None).await?,
+        }
+    }
+    for (series_id, overrides) in &sync.overrides {
+        state
+            .entry_repo
+            .delete_occurrence_overrides(*series_id, NaiveDate::MIN)
+            .await?;
+        for occurrence in overrides {
+            state
+                .entry_repo
+                .upsert_occurrence_override(occurrence)
+                .await?;
+        }
+    }
+
+    Ok(sync.report)
+}
+
+/// Records how a refresh went on the calendar's subscription.
+///
+/// The calendar is read again so an edit made during the refresh is kept.
+/// Its version stays the same: the feed only changes the calendar's entries,
+/// which carry versions of their own.
+async fn record_outcome(
+    state: &AppState,
+    calendar_id: Uuid,
+    result: &Result<RefreshReport, RefreshError>,
+) -> Result<(), RepositoryError> {
+    let Some(calendar) = state.calendar_repo.get_calendar(calendar_id).await? else {
+        return Ok(());
+    };
+    let Some(mut subscription) = calendar.subscription else {
+        return Ok(());
+    };
+    match result {
+        Ok(_) => subscription.refreshed(Utc::now()),
+        Err(err) => subscription.failed(err.to_string()),
+    }
+    state
+        .calendar_repo
+        .record_subscription_refresh(calendar_id, &subscription, calendar.version)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, RwLock};
+
+    use axum::{extract::State, routing::get, Router};
+    use calendsync_core::calendar::{Calendar, CalendarEntry};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    /// Holidays feed: two one-off days and a weekly series.
+    const FEED_V1: &str = include_str!("../tests/fixtures/subscriptions/holidays-v1.ics");
+
+    /// The same feed later: one day renamed, one dropped, one added, and an
+    /// occurrence of the series cancelled.
+    const FEED_V2: &str = include_str!("../tests/fixtures/subscriptions/holidays-v2.ics");
+
+    /// Serves whatever `feed` holds at `/holidays.ics` on a local port,
+    /// standing in for a remote calendar server.
+    async fn serve_feed(feed: Arc<RwLock<String>>) -> String {
+        let app = Router::new()
+            .route(
+                "/holidays.ics",
+                get(|State(feed): State<Arc<RwLock<String>>>| async move {
+                    feed.read().unwrap().clone()
+                }),
+            )
+            .with_state(feed);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        format!("http://{addr}/holidays.ics")
+    }
+
+    /// State that may fetch feeds from the local stand-in server.
+    fn feed_state() -> AppState {
+        let mut state = AppState::default();
+        state.subscription_allowed_hosts = vec!["127.0.0.1".to_string()];
+        state
+    }
+
+    async fn subscribed_calendar(state: &AppState, url: &str) -> Calendar {
+        let calendar = Calendar::new("Holidays", "#10B981").with_subscription(url);
+        state
+            .calendar_repo
+            .create_calendar(&calendar)
+            .await
+            .unwrap();
+        calendar
+    }
+
+    async fn stored_entries(state: &AppState, calendar_id: Uuid) -> Vec<CalendarEntry> {
+        let mut entries = state
+            .entry_repo
+            .get_stored_entries_by_calendar(calendar_id)
+            .await
+            .unwrap();
+        entries.sort_by(|a, b| a.title.cmp(&b.title));
+        entries
+    }
+
+    #[tokio::test]
+    async fn test_refresh_applies_only_changes() {
+        let state = feed_state();
+        let feed = Arc::new(RwLock::new(FEED_V1.to_string()));
+        let url = serve_feed(feed.clone()).await;
+        let calendar = subscribed_calendar(&state, &url).await;
+
+        let report = refresh_subscription(&state, calendar.id).await.unwrap();
+        assert_eq!((report.created, report.updated, report.deleted), (3, 0, 0));
+        let before = stored_entries(&state, calendar.id).await;
+
+        // Nothing changed upstream: nothing is written
+        let report = refresh_subscription(&state, calendar.id).await.unwrap();
+        assert_eq!((report.created, report.updated, report.deleted), (0, 0, 0));
+        assert_eq!(report.unchanged, 3);
+        assert_eq!(stored_entries(&state, calendar.id).await, before);
+
+        *feed.write().unwrap() = FEED_V2.to_string();
+        let report = refresh_subscription(&state, calendar.id).await.unwrap();
+        assert_eq!((report.created, report.updated, report.deleted), (1, 1, 1));
+        assert_eq!(report.overrides, 1);
+
+        let entries = stored_entries(&state, calendar.id).await;
+        let titles: Vec<&str> = entries.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, ["Book Club", "Carnival", "Labour Day"]);
+        let series = entries.iter().find(|e| e.is_recurring()).unwrap();
+        let overrides = state
+            .entry_repo
+            .get_occurrence_overrides(series.id)
+            .await
+            .unwrap();
+        assert_eq!(overrides.len(), 1);
+        assert_eq!(
+            overrides[0].original_date,
+            NaiveDate::from_ymd_opt(2024, 1, 10).unwrap()
+        );
+        assert!(overrides[0].replacement.is_none());
+
+        let stored = state
+            .calendar_repo
+            .get_calendar(calendar.id)
+            .await
+            .unwrap()
+            .unwrap();
+        let subscription = stored.subscription.unwrap();
+        assert!(subscription.refreshed_at.is_some());
+        assert_eq!(subscription.error, None);
+        // Refreshes change entries, not the calendar itself
+        assert_eq!(stored.version, calendar.version);
+    }
+
+    #[cfg(all(feature = "inmemory", feature = "memory"))]
+    #[tokio::test]
+    async fn test_refresh_publishes_only_changes() {
+        use calendsync_core::calendar::CalendarEvent;
+
+        // Events are published by the cached repositories
+        let config = Config {
+            subscription_allowed_hosts: vec!["127.0.0.1".to_string()],
+            ..Config::default()
+        };
+        let state = AppState::new(&config).await.unwrap();
+        let feed = Arc::new(RwLock::new(FEED_V1.to_string()));
+        let url = serve_feed(feed.clone()).await;
+        let calendar = subscribed_calendar(&state, &url).await;
+        let mut receiver = state.cache_pubsub.subscribe(calendar.id).await.unwrap();
+        let mut drain = || std::iter::from_fn(|| receiver.try_recv().ok()).collect::<Vec<_>>();
+
+        refresh_subscription(&state, calendar.id).await.unwrap();
+        let events = drain();
+        assert_eq!(events.len(), 3);
+        assert!(events
+            .iter()
+            .all(|e| matches!(e, CalendarEvent::EntryAdded { .. })));
+
+        refresh_subscription(&state, calendar.id).await.unwrap();
+        assert!(drain().is_empty());
+
+        *feed.write().unwrap() = FEED_V2.to_string();
+        refresh_subscription(&state, calendar.id).await.unwrap();
+        let events = drain();
+        let count = |f: fn(&CalendarEvent) -> bool| events.iter().filter(|e| f(e)).count();
+        assert_eq!(count(|e| matches!(e, CalendarEvent::EntryAdded { .. })), 1);
+        assert_eq!(
+            count(|e| matches!(e, CalendarEvent::EntryUpdated { .. })),
+            1
+        );
+        // The dropped day and the cancelled occurrence of the series
+        assert_eq!(
+            count(|e| matches!(e, CalendarEvent::EntryDeleted { .. })),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refresh_restores_entries_added_back() {
+        let state = feed_state();
+        let feed = Arc::new(RwLock::new(FEED_V1.to_string()));
+        let url = serve_feed(feed.clone()).await;
+        let calendar = subscribed_calendar(&state, &url).await;
+
+        refresh_subscription(&state, calendar.id).await.unwrap();
+        *feed.write().unwrap() = FEED_V2.to_string();
+        refresh_subscription(&state, calendar.id).await.unwrap();
+        *feed.write().unwrap() = FEED_V1.to_string();
+        let report = refresh_subscription(&state, calendar.id).await.unwrap();
+
+        // The dropped day comes back from the trash under the same ID
+        assert_eq!(report.created, 0);
+        assert_eq!(report.deleted, 1);
+        assert!(state
+            .entry_repo
+            .get_trashed_entries(&[calendar.id])
+            .await
+            .unwrap()
+            .iter()
+            .all(|t| t.entry.title == "Carnival"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_refresh_is_recorded() {
+        let state = feed_state();
+        let feed = Arc::new(RwLock::new("not a calendar".to_string()));
+        let url = serve_feed(feed).await;
+        let calendar = subscribed_calendar(&state, &url).await;
+
+        let err = refresh_subscription(&state, calendar.id).await.unwrap_err();
+        assert!(matches!(err, RefreshError::Feed(_)));
+
+        let stored = state
+            .calendar_repo
+            .get_calendar(calendar.id)
+            .await
+            .unwrap()
+            .unwrap();
+        let subscription = stored.subscription.unwrap();
+        assert_eq!(subscription.refreshed_at, None);
+        assert!(subscription.error.unwrap().starts_with("Invalid feed"));
+
+        let plain = Calendar::new("Work", "#3B82F6");
+        state.calendar_repo.create_calendar(&plain).await.unwrap();
+        assert!(matches!(
+            refresh_subscription(&state, plain.id).await,
+            Err(RefreshError::NotSubscribed)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_refuses_private_hosts() {
+        let feed = Arc::new(RwLock::new(FEED_V1.to_string()));
+        let url = serve_feed(feed).await;
+        let state = AppState::default();
+        let calendar = subscribed_calendar(&state, &url).await;
+
+        let err = refresh_subscription(&state, calendar.id).await.unwrap_err();
+        assert!(err.to_string().contains("private"), "{err}");
+        assert!(stored_entries(&state, calendar.id).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resolver_drops_private_addresses() {
+        let resolver = PublicResolver {
+            allowed_hosts: Vec::new(),
+        };
+        let err = resolver
+            .resolve("localhost".parse().unwrap())
+            .await
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("private"), "{err}");
+
+        let resolver = PublicResolver {
+            allowed_hosts: vec!["localhost".to_string()],
+        };
+        let addrs = resolver
+            .resolve("localhost".parse().unwrap())
+            .await
+            .unwrap();
+        assert!(addrs.into_iter().all(|addr| addr.ip().is_loopback()));
+    }
+}
@@ -0,0 +1,107 @@
+//! Background task that empties expired trash.
+//!
+//! Deleted calendars and entries stay restorable for `TRASH_RETENTION_DAYS`.
+//! Every tick purges whatever was deleted before that window, along with the
+//! data that belongs to it. Purging is idempotent, so several instances can
+//! run it at once.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tokio::time::MissedTickBehavior;
+
+use calendsync_core::calendar::purge_cutoff;
+use calendsync_core::storage::Result;
+
+use crate::config::Config;
+use crate::state::AppState;
+
+/// Spawns the trash purger. It runs until the server shuts down.
+pub fn spawn_trash_purger(state: AppState, config: &Config) {
+    let interval = config.trash_purge_interval();
+    let retention_days = config.trash_retention_days;
+    tokio::spawn(run_purger(state, interval, retention_days));
+}
+
+async fn run_purger(state: AppState, interval: Duration, retention_days: u32) {
+    let mut shutdown_rx = state.subscribe_shutdown();
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    tracing::info!(
+        interval_secs = interval.as_secs(),
+        retention_days,
+        "Trash purger started"
+    );
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                match purge_expired(&state, Utc::now(), retention_days).await {
+                    Ok(purged) => {
+                        if purged > 0 {
+                            tracing::debug!(purged, "Purged expired trash");
+                        }
+                    }
+                    Err(err) => tracing::warn!(error = %err, "Failed to purge expired trash"),
+                }
+            }
+            _ = shutdown_rx.recv() => {
+                tracing::debug!("Trash purger shutting down");
+                break;
+            }
+        }
+    }
+}
+
+/// Purges calendars and entries that have been in the trash for longer than
+/// `retention_days` at `now`.
+///
+/// Returns how many calendars and entries were purged. Entries trashed with
+/// their calendar are purged with it and not counted separately.
+pub async fn purge_expired(
+    state: &AppState,
+    now: DateTime<Utc>,
+    retention_days: u32,
+) -> Result<usize> {
+    let cutoff = purge_cutoff(now, retention_days);
+    let calendars = state.calendar_repo.purge_calendars(cutoff).await?;
+    let entries = state.entry_repo.purge_entries(cutoff).await?;
+    Ok(calendars + entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calendsync_core::calendar::CalendarEntry;
+    use chrono::NaiveDate;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_purge_expired_keeps_recent_trash() {
+        let state = AppState::default();
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let entry = CalendarEntry::all_day(Uuid::new_v4(), "Standup", date);
+        state.entry_repo.create_entry(&entry).await.unwrap();
+        state.entry_repo.delete_entry(entry.id).await.unwrap();
+
+        // Still within the retention period
+        assert_eq!(purge_expired(&state, Utc::now(), 30).await.unwrap(), 0);
+        let trashed = state
+            .entry_repo
+            .get_trashed_entries(&[entry.calendar_id])
+            .await
+            .unwrap();
+        assert_eq!(trashed.len(), 1);
+
+        // Past it
+        let later = Utc::now() + chrono::Duration::days(31);
+        assert_eq!(purge_expired(&state, later, 30).await.unwrap(), 1);
+        let trashed = state
+            .entry_repo
+            .get_trashed_entries(&[entry.calendar_id])
+            .await
+            .unwrap();
+        assert!(trashed.is_empty());
+    }
+}
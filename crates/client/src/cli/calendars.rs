@@ -1,5 +1,8 @@
 //! Calendar CLI commands.
 
+use std::path::PathBuf;
+
+use chrono_tz::Tz;
 use clap::{Parser, Subcommand};
 use uuid::Uuid;
 
@@ -26,6 +29,13 @@ pub enum CalendarsAction {
         /// Calendar description.
         #[arg(long)]
         description: Option<String>,
+        /// IANA time zone for timed entries (e.g. "America/Montevideo").
+        #[arg(long)]
+        timezone: Option<Tz>,
+        /// Mirror the iCalendar feed at this URL (http, https or webcal).
+        /// The calendar's entries become read-only.
+        #[arg(long, value_name = "URL")]
+        subscribe: Option<String>,
     },
     /// Get calendar by ID.
     Get {
@@ -45,10 +55,75 @@ pub enum CalendarsAction {
         /// New description.
         #[arg(long)]
         description: Option<String>,
+        /// New IANA time zone.
+        #[arg(long)]
+        timezone: Option<Tz>,
+        /// Only apply while the calendar is still at this version (see its `ETag`).
+        #[arg(long)]
+        if_match: Option<u64>,
+    },
+    /// Export a calendar as iCalendar (.ics).
+    Export {
+        /// Calendar ID.
+        id: Uuid,
+        /// File to write to (defaults to standard output).
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Import an iCalendar (.ics) file into a calendar.
+    ///
+    /// Entries imported before are matched by UID and updated in place.
+    Import {
+        /// Calendar ID.
+        id: Uuid,
+        /// The .ics file to import.
+        file: PathBuf,
+        /// Report what would change without writing anything.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Fetch a subscribed calendar's feed now instead of waiting for the
+    /// next scheduled refresh.
+    Refresh {
+        /// Calendar ID.
+        id: Uuid,
+    },
+    /// Show your iCalendar subscription (webcal) feed for a calendar.
+    ///
+    /// Anyone with the feed URL can read the calendar while you can.
+    Feed {
+        /// Calendar ID.
+        id: Uuid,
+        /// Issue a new feed URL; the previous one stops working.
+        #[arg(long, conflicts_with = "revoke")]
+        rotate: bool,
+        /// Revoke the feed URL.
+        #[arg(long)]
+        revoke: bool,
+    },
+    /// Duplicate a calendar under new IDs.
+    Duplicate {
+        /// Calendar ID.
+        id: Uuid,
+        /// Name of the copy (defaults to "<name> (copy)").
+        #[arg(long)]
+        name: Option<String>,
+        /// Copy the calendar's entries.
+        #[arg(long)]
+        include_entries: bool,
+        /// Days to move copied entries by; negative moves them earlier.
+        #[arg(long, default_value_t = 0, allow_negative_numbers = true)]
+        shift_days: i64,
+        /// Copy the calendar's members (needs admin access).
+        #[arg(long)]
+        include_members: bool,
     },
     /// Delete calendar by ID.
     Delete {
         /// Calendar ID.
         id: Uuid,
+        /// Only apply while the calendar is still at this version (see its `ETag`).
+        #[arg(long)]
+        if_match: Option<u64>,
     },
 }
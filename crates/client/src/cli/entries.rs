@@ -1,11 +1,16 @@
 //! Entry CLI commands.
 
 use chrono::{NaiveDate, NaiveTime};
+use chrono_tz::Tz;
 use clap::{Parser, Subcommand, ValueEnum};
 use uuid::Uuid;
 
 // Re-export core EntryType for API usage
 pub use calendsync_core::calendar::EntryType as CoreEntryType;
+use calendsync_core::calendar::{
+    Attendee, ChecklistItem, ConflictScope, RecurrenceRule, RecurrenceScope, Reminder,
+    ResponseStatus, Tag, TaskPriority,
+};
 
 /// Entry management commands.
 #[derive(Debug, Parser)]
@@ -57,6 +62,12 @@ pub enum EntriesAction {
         /// Days after highlighted day.
         #[arg(long, default_value = "3")]
         after: u32,
+        /// IANA time zone to show timed entries in.
+        #[arg(long)]
+        timezone: Option<Tz>,
+        /// Only list entries with this tag; repeatable (matches any).
+        #[arg(long = "tag", value_delimiter = ',')]
+        tags: Vec<Tag>,
     },
     /// Create a new entry.
     Create {
@@ -90,6 +101,39 @@ pub enum EntriesAction {
         /// Optional accent color.
         #[arg(long)]
         color: Option<String>,
+        /// Recurrence rule (RRULE, e.g. "FREQ=WEEKLY;BYDAY=MO,WE").
+        #[arg(long)]
+        recurrence: Option<RecurrenceRule>,
+        /// IANA time zone of the start and end times (defaults to the calendar's).
+        #[arg(long)]
+        timezone: Option<Tz>,
+        /// Reminder before the start (e.g. "10m", "2h", "1d@09:00"); repeatable.
+        #[arg(long = "reminder", value_delimiter = ',')]
+        reminders: Vec<Reminder>,
+        /// Email address of a person to invite; repeatable.
+        #[arg(long = "attendee", value_delimiter = ',')]
+        attendees: Vec<Attendee>,
+        /// Tag to label the entry with (e.g. "oncall"); repeatable.
+        #[arg(long = "tag", value_delimiter = ',')]
+        tags: Vec<Tag>,
+        /// Time (HH:MM) a task is due.
+        #[arg(long)]
+        due_time: Option<NaiveTime>,
+        /// Task priority (low, normal, high, urgent).
+        #[arg(long)]
+        priority: Option<TaskPriority>,
+        /// Task checklist item (e.g. "Pack" or "[x] Book flight"); repeatable.
+        #[arg(long = "checklist-item")]
+        checklist: Vec<ChecklistItem>,
+        /// Show the task on today until it is completed.
+        #[arg(long)]
+        roll_over: bool,
+        /// Calendars to check for overlapping timed entries (calendar, all).
+        #[arg(long)]
+        conflict_scope: Option<ConflictScope>,
+        /// Fail instead of saving when the entry overlaps another.
+        #[arg(long)]
+        reject_on_conflict: bool,
     },
     /// Get entry by ID.
     Get {
@@ -130,15 +174,138 @@ pub enum EntriesAction {
         /// Mark task as completed.
         #[arg(long)]
         completed: Option<bool>,
+        /// New recurrence rule (RRULE).
+        #[arg(long, conflicts_with = "no_recurrence")]
+        recurrence: Option<RecurrenceRule>,
+        /// Stop the entry from repeating.
+        #[arg(long)]
+        no_recurrence: bool,
+        /// Occurrences of a recurring entry to update (this, following, all).
+        #[arg(long, requires = "occurrence_date")]
+        scope: Option<RecurrenceScope>,
+        /// Original date (YYYY-MM-DD) of the occurrence to update.
+        #[arg(long)]
+        occurrence_date: Option<NaiveDate>,
+        /// IANA time zone the new dates and times are expressed in.
+        #[arg(long)]
+        timezone: Option<Tz>,
+        /// Replace the reminders (e.g. "10m", "1d@09:00"); repeatable.
+        #[arg(
+            long = "reminder",
+            value_delimiter = ',',
+            conflicts_with = "no_reminders"
+        )]
+        reminders: Vec<Reminder>,
+        /// Remove all reminders.
+        #[arg(long)]
+        no_reminders: bool,
+        /// Replace the attendees (email addresses); repeatable.
+        #[arg(
+            long = "attendee",
+            value_delimiter = ',',
+            conflicts_with = "no_attendees"
+        )]
+        attendees: Vec<Attendee>,
+        /// Remove all attendees.
+        #[arg(long)]
+        no_attendees: bool,
+        /// Replace the tags; repeatable.
+        #[arg(long = "tag", value_delimiter = ',', conflicts_with = "no_tags")]
+        tags: Vec<Tag>,
+        /// Remove all tags.
+        #[arg(long)]
+        no_tags: bool,
+        /// New time (HH:MM) a task is due.
+        #[arg(long, conflicts_with = "no_due_time")]
+        due_time: Option<NaiveTime>,
+        /// Remove the task's due time.
+        #[arg(long)]
+        no_due_time: bool,
+        /// New task priority (low, normal, high, urgent).
+        #[arg(long)]
+        priority: Option<TaskPriority>,
+        /// Replace the task's checklist; repeatable.
+        #[arg(long = "checklist-item", conflicts_with = "no_checklist")]
+        checklist: Vec<ChecklistItem>,
+        /// Remove all checklist items.
+        #[arg(long)]
+        no_checklist: bool,
+        /// Whether the task rolls over to today until it is completed.
+        #[arg(long)]
+        roll_over: Option<bool>,
+        /// Calendars to check for overlapping timed entries (calendar, all).
+        #[arg(long)]
+        conflict_scope: Option<ConflictScope>,
+        /// Fail instead of saving when the entry overlaps another.
+        #[arg(long)]
+        reject_on_conflict: bool,
+        /// Revision the edit is based on (see `entries history`); changes made
+        /// since then are merged field by field.
+        #[arg(long)]
+        base_revision: Option<u32>,
+        /// Only apply while the entry is still at this version (see its `ETag`).
+        #[arg(long)]
+        if_match: Option<u64>,
     },
     /// Delete entry by ID.
     Delete {
         /// Entry ID.
         id: Uuid,
+        /// Occurrences of a recurring entry to delete (this, following, all).
+        #[arg(long, requires = "occurrence_date")]
+        scope: Option<RecurrenceScope>,
+        /// Original date (YYYY-MM-DD) of the occurrence to delete.
+        #[arg(long)]
+        occurrence_date: Option<NaiveDate>,
+        /// Only apply while the entry is still at this version (see its `ETag`).
+        #[arg(long)]
+        if_match: Option<u64>,
     },
     /// Toggle task completion status.
     Toggle {
         /// Entry ID.
         id: Uuid,
+        /// Toggle this checklist item (1-based) instead of the task.
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+        item: Option<u32>,
+    },
+    /// Move an entry to another calendar, keeping its ID.
+    Move {
+        /// Entry ID.
+        id: Uuid,
+        /// Calendar to move the entry to.
+        #[arg(long)]
+        calendar_id: Uuid,
+        /// Only apply while the entry is still at this version (see its `ETag`).
+        #[arg(long)]
+        if_match: Option<u64>,
+    },
+    /// Answer an invitation to an entry as the current user.
+    Rsvp {
+        /// Entry ID.
+        id: Uuid,
+        /// Response (accepted, declined, tentative, needs-action).
+        status: ResponseStatus,
+    },
+    /// Search titles, descriptions and locations across your calendars.
+    Search {
+        /// Words to look for (e.g. "dentist").
+        query: String,
+        /// Maximum number of results (server default 20, at most 100).
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Show an entry's revision history.
+    History {
+        /// Entry ID.
+        id: Uuid,
+    },
+    /// Revert an entry to an earlier revision.
+    Revert {
+        /// Entry ID.
+        id: Uuid,
+        /// Revision number to go back to (see `entries history`).
+        #[arg(long)]
+        revision: u32,
     },
 }
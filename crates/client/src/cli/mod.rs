@@ -4,6 +4,7 @@ pub mod calendars;
 pub mod entries;
 pub mod events;
 pub mod health;
+pub mod trash;
 pub mod users;
 
 use clap::{Parser, Subcommand, ValueEnum};
@@ -47,9 +48,11 @@ pub enum Commands {
     /// Calendar management.
     Calendars(calendars::CalendarsCommand),
     /// Calendar entry management.
-    Entries(entries::EntriesCommand),
+    Entries(Box<entries::EntriesCommand>),
     /// Watch real-time SSE events.
     Events(events::EventsCommand),
     /// Server health checks.
     Health(health::HealthCommand),
+    /// Restore deleted calendars and entries.
+    Trash(trash::TrashCommand),
 }
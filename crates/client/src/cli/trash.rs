@@ -0,0 +1,28 @@
+//! Trash CLI commands.
+
+use clap::{Parser, Subcommand};
+use uuid::Uuid;
+
+/// Trash commands.
+#[derive(Debug, Parser)]
+pub struct TrashCommand {
+    #[command(subcommand)]
+    pub action: TrashAction,
+}
+
+/// Available trash actions.
+#[derive(Debug, Subcommand)]
+pub enum TrashAction {
+    /// List deleted calendars and entries that can still be restored.
+    List,
+    /// Restore a deleted entry.
+    RestoreEntry {
+        /// Entry ID.
+        id: Uuid,
+    },
+    /// Restore a deleted calendar and its entries.
+    RestoreCalendar {
+        /// Calendar ID.
+        id: Uuid,
+    },
+}
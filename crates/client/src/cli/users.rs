@@ -1,5 +1,7 @@
 //! User CLI commands.
 
+use chrono::NaiveDate;
+use chrono_tz::Tz;
 use clap::{Parser, Subcommand};
 use uuid::Uuid;
 
@@ -34,4 +36,21 @@ pub enum UsersAction {
         /// User ID.
         id: Uuid,
     },
+    /// Show when a user is busy across their calendars.
+    Freebusy {
+        /// User ID.
+        id: Uuid,
+        /// First day (YYYY-MM-DD).
+        #[arg(long)]
+        start: NaiveDate,
+        /// Last day (YYYY-MM-DD), inclusive.
+        #[arg(long)]
+        end: NaiveDate,
+        /// Time zone of the days (IANA name, e.g. Europe/Madrid).
+        #[arg(long)]
+        timezone: Option<Tz>,
+        /// Print an iCalendar VFREEBUSY object instead.
+        #[arg(long)]
+        ics: bool,
+    },
 }
@@ -2,11 +2,24 @@
 
 use super::CalendsyncClient;
 use crate::error::Result;
-use calendsync_core::calendar::Calendar;
+use calendsync_core::calendar::{
+    Calendar, DuplicateSummary, FeedSubscription, ImportReport, RefreshReport, ICS_CONTENT_TYPE,
+};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 // Re-export from core for public API
-pub use calendsync_core::calendar::{CreateCalendarRequest, UpdateCalendarRequest};
+pub use calendsync_core::calendar::{
+    CreateCalendarRequest, DuplicateCalendarRequest, UpdateCalendarRequest,
+};
+
+/// A calendar created by duplication, with what was copied into it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DuplicatedCalendar {
+    pub calendar: Calendar,
+    #[serde(flatten)]
+    pub summary: DuplicateSummary,
+}
 
 impl CalendsyncClient {
     /// List all calendars.
@@ -36,22 +49,111 @@ impl CalendsyncClient {
         self.handle_response(response).await
     }
 
-    /// Update a calendar.
-    pub async fn update_calendar(&self, id: Uuid, req: UpdateCalendarRequest) -> Result<Calendar> {
-        let response = self
+    /// Update a calendar, optionally only while it is at `if_match` version.
+    pub async fn update_calendar(
+        &self,
+        id: Uuid,
+        req: UpdateCalendarRequest,
+        if_match: Option<u64>,
+    ) -> Result<Calendar> {
+        let request = self
             .client
             .put(self.url(&format!("/api/calendars/{}", id)))
+            .form(&req);
+        let response = Self::if_match(request, if_match).send().await?;
+        self.handle_response(response).await
+    }
+
+    /// Delete calendar by ID, optionally only while it is at `if_match` version.
+    pub async fn delete_calendar(&self, id: Uuid, if_match: Option<u64>) -> Result<()> {
+        let request = self
+            .client
+            .delete(self.url(&format!("/api/calendars/{}", id)));
+        let response = Self::if_match(request, if_match).send().await?;
+        self.handle_delete_response(response).await
+    }
+
+    /// Duplicate a calendar, optionally with its entries and members.
+    pub async fn duplicate_calendar(
+        &self,
+        id: Uuid,
+        req: DuplicateCalendarRequest,
+    ) -> Result<DuplicatedCalendar> {
+        let response = self
+            .client
+            .post(self.url(&format!("/api/calendars/{}/duplicate", id)))
             .form(&req)
             .send()
             .await?;
         self.handle_response(response).await
     }
 
-    /// Delete calendar by ID.
-    pub async fn delete_calendar(&self, id: Uuid) -> Result<()> {
+    /// Export a calendar as an iCalendar (.ics) object.
+    pub async fn export_calendar(&self, id: Uuid) -> Result<String> {
+        let response = self
+            .client
+            .get(self.url(&format!("/api/calendars/{}/export.ics", id)))
+            .send()
+            .await?;
+        self.handle_text_response(response).await
+    }
+
+    /// Import an iCalendar (.ics) object into a calendar.
+    ///
+    /// With `dry_run`, reports what would change without writing anything.
+    pub async fn import_calendar(
+        &self,
+        id: Uuid,
+        ics: String,
+        dry_run: bool,
+    ) -> Result<ImportReport> {
+        let response = self
+            .client
+            .post(self.url(&format!("/api/calendars/{}/import", id)))
+            .query(&[("dry_run", dry_run)])
+            .header(reqwest::header::CONTENT_TYPE, ICS_CONTENT_TYPE)
+            .body(ics)
+            .send()
+            .await?;
+        self.handle_response(response).await
+    }
+
+    /// Refresh a subscribed calendar from its iCalendar feed.
+    pub async fn refresh_calendar(&self, id: Uuid) -> Result<RefreshReport> {
+        let response = self
+            .client
+            .post(self.url(&format!("/api/calendars/{}/refresh", id)))
+            .send()
+            .await?;
+        self.handle_response(response).await
+    }
+
+    /// Get the current user's subscription feed for a calendar.
+    pub async fn get_feed(&self, id: Uuid) -> Result<FeedSubscription> {
+        let response = self
+            .client
+            .get(self.url(&format!("/api/calendars/{}/feed", id)))
+            .send()
+            .await?;
+        self.handle_response(response).await
+    }
+
+    /// Create the current user's subscription feed for a calendar, replacing
+    /// (and invalidating) any previous feed URL.
+    pub async fn rotate_feed(&self, id: Uuid) -> Result<FeedSubscription> {
+        let response = self
+            .client
+            .post(self.url(&format!("/api/calendars/{}/feed", id)))
+            .send()
+            .await?;
+        self.handle_response(response).await
+    }
+
+    /// Revoke the current user's subscription feed for a calendar.
+    pub async fn revoke_feed(&self, id: Uuid) -> Result<()> {
         let response = self
             .client
-            .delete(self.url(&format!("/api/calendars/{}", id)))
+            .delete(self.url(&format!("/api/calendars/{}/feed", id)))
             .send()
             .await?;
         self.handle_delete_response(response).await
@@ -2,7 +2,10 @@
 
 use super::CalendsyncClient;
 use crate::error::Result;
-use calendsync_core::calendar::CalendarEntry;
+use calendsync_core::calendar::{
+    CalendarEntry, EntryRevision, RecurrenceScope, ResponseStatus, SearchHit,
+};
+use chrono::NaiveDate;
 use uuid::Uuid;
 
 // Re-export from core for public API
@@ -43,34 +46,121 @@ impl CalendsyncClient {
         self.handle_response(response).await
     }
 
-    /// Update an entry.
-    pub async fn update_entry(&self, id: Uuid, req: UpdateEntryRequest) -> Result<CalendarEntry> {
-        let response = self
+    /// Update an entry, optionally only while it is at `if_match` version.
+    pub async fn update_entry(
+        &self,
+        id: Uuid,
+        req: UpdateEntryRequest,
+        if_match: Option<u64>,
+    ) -> Result<CalendarEntry> {
+        let request = self
             .client
             .put(self.url(&format!("/api/entries/{}", id)))
-            .form(&req)
-            .send()
-            .await?;
+            .form(&req);
+        let response = Self::if_match(request, if_match).send().await?;
         self.handle_response(response).await
     }
 
-    /// Delete entry by ID.
-    pub async fn delete_entry(&self, id: Uuid) -> Result<()> {
+    /// Delete entry by ID, optionally only while it is at `if_match` version.
+    pub async fn delete_entry(&self, id: Uuid, if_match: Option<u64>) -> Result<()> {
+        let request = self
+            .client
+            .delete(self.url(&format!("/api/entries/{}", id)));
+        let response = Self::if_match(request, if_match).send().await?;
+        self.handle_delete_response(response).await
+    }
+
+    /// Delete some occurrences of a recurring entry.
+    pub async fn delete_occurrences(
+        &self,
+        id: Uuid,
+        scope: RecurrenceScope,
+        occurrence_date: NaiveDate,
+    ) -> Result<()> {
         let response = self
             .client
             .delete(self.url(&format!("/api/entries/{}", id)))
+            .query(&[
+                ("scope", scope.to_string()),
+                ("occurrence_date", occurrence_date.to_string()),
+            ])
             .send()
             .await?;
         self.handle_delete_response(response).await
     }
 
-    /// Toggle task completion status.
-    pub async fn toggle_entry(&self, id: Uuid) -> Result<CalendarEntry> {
+    /// Toggle task completion status, or one of its checklist items (zero-based).
+    pub async fn toggle_entry(&self, id: Uuid, item: Option<usize>) -> Result<CalendarEntry> {
+        let mut request = self
+            .client
+            .patch(self.url(&format!("/api/entries/{}/toggle", id)));
+        if let Some(item) = item {
+            request = request.query(&[("item", item)]);
+        }
+        let response = request.send().await?;
+        self.handle_response(response).await
+    }
+
+    /// Answer an invitation to an entry as the current user.
+    pub async fn rsvp_entry(&self, id: Uuid, status: ResponseStatus) -> Result<CalendarEntry> {
         let response = self
             .client
-            .patch(self.url(&format!("/api/entries/{}/toggle", id)))
+            .put(self.url(&format!("/api/entries/{}/rsvp", id)))
+            .form(&[("status", status.as_str())])
             .send()
             .await?;
         self.handle_response(response).await
     }
+
+    /// Move an entry to another calendar, keeping its ID.
+    pub async fn move_entry(
+        &self,
+        id: Uuid,
+        calendar_id: Uuid,
+        if_match: Option<u64>,
+    ) -> Result<CalendarEntry> {
+        let request = self
+            .client
+            .post(self.url(&format!("/api/entries/{}/move", id)))
+            .form(&[("calendar_id", calendar_id.to_string())]);
+        let response = Self::if_match(request, if_match).send().await?;
+        self.handle_response(response).await
+    }
+
+    /// List an entry's revisions, oldest first.
+    pub async fn get_entry_history(&self, id: Uuid) -> Result<Vec<EntryRevision>> {
+        let response = self
+            .client
+            .get(self.url(&format!("/api/entries/{}/history", id)))
+            .send()
+            .await?;
+        self.handle_response(response).await
+    }
+
+    /// Revert an entry to an earlier revision.
+    pub async fn revert_entry(&self, id: Uuid, revision: u32) -> Result<CalendarEntry> {
+        let response = self
+            .client
+            .post(self.url(&format!("/api/entries/{}/history/{}/revert", id, revision)))
+            .send()
+            .await?;
+        self.handle_response(response).await
+    }
+
+    /// Search entries in every calendar the current user can read.
+    pub async fn search_entries(
+        &self,
+        query: &str,
+        limit: Option<usize>,
+    ) -> Result<Vec<SearchHit>> {
+        let mut request = self
+            .client
+            .get(self.url("/api/search"))
+            .query(&[("q", query)]);
+        if let Some(limit) = limit {
+            request = request.query(&[("limit", limit)]);
+        }
+        let response = request.send().await?;
+        self.handle_response(response).await
+    }
 }
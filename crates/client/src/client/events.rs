@@ -2,10 +2,19 @@
 
 use super::CalendsyncClient;
 use crate::error::{ClientError, Result};
+use serde::Serialize;
 use uuid::Uuid;
 
-// Re-export from core for public API
-pub use calendsync_core::calendar::CalendarEvent;
+/// An event received from the SSE stream.
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchEvent {
+    /// Event ID, usable as `last_event_id` when reconnecting.
+    pub id: Option<u64>,
+    /// Event name (e.g. `entry_added`, `reminder_due`).
+    pub event: String,
+    /// Event payload as sent by the server.
+    pub data: serde_json::Value,
+}
 
 impl CalendsyncClient {
     /// Watch SSE events for a calendar.
@@ -14,7 +23,7 @@ impl CalendsyncClient {
         &self,
         calendar_id: Uuid,
         last_event_id: Option<u64>,
-    ) -> Result<impl futures_core::Stream<Item = Result<CalendarEvent>>> {
+    ) -> Result<impl futures_core::Stream<Item = Result<WatchEvent>>> {
         let mut url = format!("{}/api/events?calendar_id={}", self.base_url, calendar_id);
         if let Some(id) = last_event_id {
             url.push_str(&format!("&last_event_id={}", id));
@@ -68,14 +77,23 @@ impl CalendsyncClient {
 }
 
 /// Parse an SSE event from a string.
-fn parse_sse_event(event_str: &str) -> Option<CalendarEvent> {
+///
+/// Keep-alive comments and events without a JSON payload are skipped.
+fn parse_sse_event(event_str: &str) -> Option<WatchEvent> {
+    let mut id = None;
+    let mut event = "message".to_string();
     let mut data = None;
 
     for line in event_str.lines() {
-        if let Some(value) = line.strip_prefix("data: ") {
-            data = Some(value);
+        if let Some(value) = line.strip_prefix("id:") {
+            id = value.trim().parse().ok();
+        } else if let Some(value) = line.strip_prefix("event:") {
+            event = value.trim().to_string();
+        } else if let Some(value) = line.strip_prefix("data:") {
+            data = Some(value.trim_start());
         }
     }
 
-    data.and_then(|d| serde_json::from_str(d).ok())
+    let data = serde_json::from_str(data?).ok()?;
+    Some(WatchEvent { id, event, data })
 }
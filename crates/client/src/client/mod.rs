@@ -4,9 +4,11 @@ pub mod calendars;
 pub mod entries;
 pub mod events;
 pub mod health;
+pub mod trash;
 pub mod users;
 
 use crate::error::{ClientError, Result};
+use calendsync_core::calendar::format_etag;
 
 /// HTTP client for the calendsync API.
 #[derive(Debug, Clone)]
@@ -41,6 +43,14 @@ impl CalendsyncClient {
         format!("{}{}", self.base_url, path)
     }
 
+    /// Send `If-Match` so a write only applies while the resource is at `version`.
+    fn if_match(request: reqwest::RequestBuilder, version: Option<u64>) -> reqwest::RequestBuilder {
+        match version {
+            Some(version) => request.header(reqwest::header::IF_MATCH, format_etag(version)),
+            None => request,
+        }
+    }
+
     /// Handle error responses.
     async fn handle_response<T: serde::de::DeserializeOwned>(
         &self,
@@ -65,6 +75,27 @@ impl CalendsyncClient {
         }
     }
 
+    /// Handle responses with a plain text body (e.g. iCalendar data).
+    async fn handle_text_response(&self, response: reqwest::Response) -> Result<String> {
+        let status = response.status();
+        if status.is_success() {
+            response.text().await.map_err(ClientError::from)
+        } else if status.as_u16() == 404 {
+            Err(ClientError::NotFound {
+                resource: "Resource".to_string(),
+            })
+        } else {
+            let message = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            Err(ClientError::ServerError {
+                status: status.as_u16(),
+                message,
+            })
+        }
+    }
+
     /// Handle delete responses (no body expected).
     async fn handle_delete_response(&self, response: reqwest::Response) -> Result<()> {
         let status = response.status();
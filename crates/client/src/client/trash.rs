@@ -0,0 +1,34 @@
+//! Trash API operations.
+
+use super::CalendsyncClient;
+use crate::error::Result;
+use calendsync_core::calendar::{Calendar, CalendarEntry, Trash};
+use uuid::Uuid;
+
+impl CalendsyncClient {
+    /// List the current user's deleted calendars and entries.
+    pub async fn list_trash(&self) -> Result<Trash> {
+        let response = self.client.get(self.url("/api/trash")).send().await?;
+        self.handle_response(response).await
+    }
+
+    /// Restore a deleted entry.
+    pub async fn restore_entry(&self, id: Uuid) -> Result<CalendarEntry> {
+        let response = self
+            .client
+            .post(self.url(&format!("/api/trash/entries/{}/restore", id)))
+            .send()
+            .await?;
+        self.handle_response(response).await
+    }
+
+    /// Restore a deleted calendar and its entries.
+    pub async fn restore_calendar(&self, id: Uuid) -> Result<Calendar> {
+        let response = self
+            .client
+            .post(self.url(&format!("/api/trash/calendars/{}/restore", id)))
+            .send()
+            .await?;
+        self.handle_response(response).await
+    }
+}
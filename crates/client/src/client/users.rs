@@ -2,7 +2,9 @@
 
 use super::CalendsyncClient;
 use crate::error::Result;
-use calendsync_core::calendar::User;
+use calendsync_core::calendar::{FreeBusy, User};
+use chrono::NaiveDate;
+use chrono_tz::Tz;
 use uuid::Uuid;
 
 impl CalendsyncClient {
@@ -33,6 +35,55 @@ impl CalendsyncClient {
         self.handle_response(response).await
     }
 
+    /// Get when a user is busy between `start` and `end` (inclusive), with
+    /// days in `timezone` (the server falls back to the current user's zone).
+    pub async fn get_freebusy(
+        &self,
+        id: Uuid,
+        start: NaiveDate,
+        end: NaiveDate,
+        timezone: Option<Tz>,
+    ) -> Result<FreeBusy> {
+        let response = self
+            .freebusy_request(id, start, end, timezone)
+            .send()
+            .await?;
+        self.handle_response(response).await
+    }
+
+    /// Like [`Self::get_freebusy`], as an iCalendar VFREEBUSY object.
+    pub async fn get_freebusy_ics(
+        &self,
+        id: Uuid,
+        start: NaiveDate,
+        end: NaiveDate,
+        timezone: Option<Tz>,
+    ) -> Result<String> {
+        let response = self
+            .freebusy_request(id, start, end, timezone)
+            .query(&[("format", "ics")])
+            .send()
+            .await?;
+        self.handle_text_response(response).await
+    }
+
+    fn freebusy_request(
+        &self,
+        id: Uuid,
+        start: NaiveDate,
+        end: NaiveDate,
+        timezone: Option<Tz>,
+    ) -> reqwest::RequestBuilder {
+        let mut request = self
+            .client
+            .get(self.url(&format!("/api/users/{}/freebusy", id)))
+            .query(&[("start", start.to_string()), ("end", end.to_string())]);
+        if let Some(tz) = timezone {
+            request = request.query(&[("timezone", tz.name())]);
+        }
+        request
+    }
+
     /// Delete user by ID.
     pub async fn delete_user(&self, id: Uuid) -> Result<()> {
         let response = self
@@ -1,7 +1,9 @@
 //! calendsync-client CLI entry point.
 
 use calendsync_client::cli::{Cli, Commands, OutputFormat};
-use calendsync_client::client::calendars::{CreateCalendarRequest, UpdateCalendarRequest};
+use calendsync_client::client::calendars::{
+    CreateCalendarRequest, DuplicateCalendarRequest, UpdateCalendarRequest,
+};
 use calendsync_client::client::entries::{
     CreateEntryRequest, ListEntriesQuery, UpdateEntryRequest,
 };
@@ -47,6 +49,30 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         println!("Deleted user {}", id);
                     }
                 }
+                UsersAction::Freebusy {
+                    id,
+                    start,
+                    end,
+                    timezone,
+                    ics,
+                } => {
+                    if ics {
+                        print!(
+                            "{}",
+                            client.get_freebusy_ics(id, start, end, timezone).await?
+                        );
+                    } else {
+                        let free_busy = client.get_freebusy(id, start, end, timezone).await?;
+                        match cli.format {
+                            OutputFormat::Json => {
+                                println!("{}", format_output(&free_busy, cli.format))
+                            }
+                            OutputFormat::Pretty => {
+                                println!("{}", pretty::format_freebusy(&free_busy))
+                            }
+                        }
+                    }
+                }
             }
         }
         Commands::Calendars(calendars_cmd) => {
@@ -65,12 +91,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     name,
                     color,
                     description,
+                    timezone,
+                    subscribe,
                 } => {
                     let calendar = client
                         .create_calendar(CreateCalendarRequest {
                             name,
                             color: Some(color),
                             description,
+                            timezone,
+                            subscription_url: subscribe,
                         })
                         .await?;
                     match cli.format {
@@ -92,6 +122,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     name,
                     color,
                     description,
+                    timezone,
+                    if_match,
                 } => {
                     let calendar = client
                         .update_calendar(
@@ -100,7 +132,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                                 name,
                                 color,
                                 description,
+                                timezone,
                             },
+                            if_match,
                         )
                         .await?;
                     match cli.format {
@@ -110,8 +144,87 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         }
                     }
                 }
-                CalendarsAction::Delete { id } => {
-                    client.delete_calendar(id).await?;
+                CalendarsAction::Export { id, output } => {
+                    let ics = client.export_calendar(id).await?;
+                    match output {
+                        Some(path) => {
+                            std::fs::write(&path, ics)?;
+                            if !cli.quiet {
+                                println!("Exported calendar {} to {}", id, path.display());
+                            }
+                        }
+                        None => print!("{}", ics),
+                    }
+                }
+                CalendarsAction::Import { id, file, dry_run } => {
+                    let ics = std::fs::read_to_string(&file)?;
+                    let report = client.import_calendar(id, ics, dry_run).await?;
+                    match cli.format {
+                        OutputFormat::Json => println!("{}", format_output(&report, cli.format)),
+                        OutputFormat::Pretty => {
+                            println!("{}", pretty::format_import_report(&report))
+                        }
+                    }
+                }
+                CalendarsAction::Refresh { id } => {
+                    let report = client.refresh_calendar(id).await?;
+                    match cli.format {
+                        OutputFormat::Json => println!("{}", format_output(&report, cli.format)),
+                        OutputFormat::Pretty => {
+                            println!("{}", pretty::format_refresh_report(&report))
+                        }
+                    }
+                }
+                CalendarsAction::Feed { id, rotate, revoke } => {
+                    if revoke {
+                        client.revoke_feed(id).await?;
+                        if !cli.quiet {
+                            println!("Revoked feed for calendar {}", id);
+                        }
+                    } else {
+                        let feed = if rotate {
+                            client.rotate_feed(id).await?
+                        } else {
+                            client.get_feed(id).await?
+                        };
+                        match cli.format {
+                            OutputFormat::Json => println!("{}", format_output(&feed, cli.format)),
+                            OutputFormat::Pretty => println!("{}", pretty::format_feed(&feed)),
+                        }
+                    }
+                }
+                CalendarsAction::Duplicate {
+                    id,
+                    name,
+                    include_entries,
+                    shift_days,
+                    include_members,
+                } => {
+                    let duplicated = client
+                        .duplicate_calendar(
+                            id,
+                            DuplicateCalendarRequest {
+                                name,
+                                include_entries: Some(include_entries),
+                                shift_days: Some(shift_days),
+                                include_members: Some(include_members),
+                            },
+                        )
+                        .await?;
+                    match cli.format {
+                        OutputFormat::Json => {
+                            println!("{}", format_output(&duplicated, cli.format))
+                        }
+                        OutputFormat::Pretty => println!(
+                            "Duplicated ({} entries, {} members):\n{}",
+                            duplicated.summary.entries,
+                            duplicated.summary.members,
+                            pretty::format_calendar(&duplicated.calendar)
+                        ),
+                    }
+                }
+                CalendarsAction::Delete { id, if_match } => {
+                    client.delete_calendar(id, if_match).await?;
                     if !cli.quiet {
                         println!("Deleted calendar {}", id);
                     }
@@ -128,6 +241,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     highlighted_day,
                     before,
                     after,
+                    timezone,
+                    tags,
                 } => {
                     let entries = client
                         .list_entries(ListEntriesQuery {
@@ -137,6 +252,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                             highlighted_day,
                             before: Some(before),
                             after: Some(after),
+                            timezone,
+                            tags: (!tags.is_empty()).then(|| tags.into_iter().collect()),
                         })
                         .await?;
                     match cli.format {
@@ -155,6 +272,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     end_time,
                     end_date,
                     color,
+                    recurrence,
+                    timezone,
+                    reminders,
+                    attendees,
+                    tags,
+                    due_time,
+                    priority,
+                    checklist,
+                    roll_over,
+                    conflict_scope,
+                    reject_on_conflict,
                 } => {
                     let entry = client
                         .create_entry(CreateEntryRequest {
@@ -168,6 +296,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                             end_time,
                             end_date,
                             color,
+                            recurrence,
+                            timezone,
+                            reminders: (!reminders.is_empty()).then_some(reminders),
+                            attendees: (!attendees.is_empty()).then_some(attendees),
+                            tags: (!tags.is_empty()).then(|| tags.into_iter().collect()),
+                            due_time,
+                            priority,
+                            checklist: (!checklist.is_empty()).then_some(checklist),
+                            roll_over: roll_over.then_some(true),
+                            conflict_scope,
+                            reject_on_conflict: reject_on_conflict.then_some(true),
                         })
                         .await?;
                     match cli.format {
@@ -196,6 +335,27 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     end_date,
                     color,
                     completed,
+                    recurrence,
+                    no_recurrence,
+                    scope,
+                    occurrence_date,
+                    timezone,
+                    reminders,
+                    no_reminders,
+                    attendees,
+                    no_attendees,
+                    tags,
+                    no_tags,
+                    due_time,
+                    no_due_time,
+                    priority,
+                    checklist,
+                    no_checklist,
+                    roll_over,
+                    conflict_scope,
+                    reject_on_conflict,
+                    base_revision,
+                    if_match,
                 } => {
                     let entry = client
                         .update_entry(
@@ -211,8 +371,29 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                                 end_date,
                                 color,
                                 completed,
+                                recurrence,
+                                clear_recurrence: no_recurrence.then_some(true),
+                                reminders: (!reminders.is_empty()).then_some(reminders),
+                                clear_reminders: no_reminders.then_some(true),
+                                attendees: (!attendees.is_empty()).then_some(attendees),
+                                clear_attendees: no_attendees.then_some(true),
+                                tags: (!tags.is_empty()).then(|| tags.into_iter().collect()),
+                                clear_tags: no_tags.then_some(true),
+                                due_time,
+                                clear_due_time: no_due_time.then_some(true),
+                                priority,
+                                checklist: (!checklist.is_empty()).then_some(checklist),
+                                clear_checklist: no_checklist.then_some(true),
+                                roll_over,
+                                scope,
+                                occurrence_date,
+                                timezone,
                                 updated_at: None, // CLI doesn't use LWW merge
+                                base_revision,
+                                conflict_scope,
+                                reject_on_conflict: reject_on_conflict.then_some(true),
                             },
+                            if_match,
                         )
                         .await?;
                     match cli.format {
@@ -222,14 +403,32 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         }
                     }
                 }
-                EntriesAction::Delete { id } => {
-                    client.delete_entry(id).await?;
-                    if !cli.quiet {
-                        println!("Deleted entry {}", id);
+                EntriesAction::Delete {
+                    id,
+                    scope,
+                    occurrence_date,
+                    if_match,
+                } => match (scope, occurrence_date) {
+                    (Some(scope), Some(date)) => {
+                        client.delete_occurrences(id, scope, date).await?;
+                        if !cli.quiet {
+                            println!(
+                                "Deleted entry {} (scope: {}, occurrence: {})",
+                                id, scope, date
+                            );
+                        }
                     }
-                }
-                EntriesAction::Toggle { id } => {
-                    let entry = client.toggle_entry(id).await?;
+                    _ => {
+                        client.delete_entry(id, if_match).await?;
+                        if !cli.quiet {
+                            println!("Deleted entry {}", id);
+                        }
+                    }
+                },
+                EntriesAction::Toggle { id, item } => {
+                    // Checklist items are numbered from 1 on the command line
+                    let item = item.map(|n| n as usize - 1);
+                    let entry = client.toggle_entry(id, item).await?;
                     match cli.format {
                         OutputFormat::Json => println!("{}", format_output(&entry, cli.format)),
                         OutputFormat::Pretty => {
@@ -237,6 +436,51 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         }
                     }
                 }
+                EntriesAction::Move {
+                    id,
+                    calendar_id,
+                    if_match,
+                } => {
+                    let entry = client.move_entry(id, calendar_id, if_match).await?;
+                    match cli.format {
+                        OutputFormat::Json => println!("{}", format_output(&entry, cli.format)),
+                        OutputFormat::Pretty => {
+                            println!("Moved:\n{}", pretty::format_entry(&entry))
+                        }
+                    }
+                }
+                EntriesAction::Rsvp { id, status } => {
+                    let entry = client.rsvp_entry(id, status).await?;
+                    match cli.format {
+                        OutputFormat::Json => println!("{}", format_output(&entry, cli.format)),
+                        OutputFormat::Pretty => {
+                            println!("Responded:\n{}", pretty::format_entry(&entry))
+                        }
+                    }
+                }
+                EntriesAction::Search { query, limit } => {
+                    let hits = client.search_entries(&query, limit).await?;
+                    match cli.format {
+                        OutputFormat::Json => println!("{}", format_output(&hits, cli.format)),
+                        OutputFormat::Pretty => println!("{}", pretty::format_search_hits(&hits)),
+                    }
+                }
+                EntriesAction::History { id } => {
+                    let history = client.get_entry_history(id).await?;
+                    match cli.format {
+                        OutputFormat::Json => println!("{}", format_output(&history, cli.format)),
+                        OutputFormat::Pretty => println!("{}", pretty::format_history(&history)),
+                    }
+                }
+                EntriesAction::Revert { id, revision } => {
+                    let entry = client.revert_entry(id, revision).await?;
+                    match cli.format {
+                        OutputFormat::Json => println!("{}", format_output(&entry, cli.format)),
+                        OutputFormat::Pretty => {
+                            println!("Reverted:\n{}", pretty::format_entry(&entry))
+                        }
+                    }
+                }
             }
         }
         Commands::Events(events_cmd) => {
@@ -258,7 +502,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                                 OutputFormat::Json => {
                                     println!("{}", serde_json::to_string(&event)?)
                                 }
-                                OutputFormat::Pretty => println!("{:?}", event),
+                                OutputFormat::Pretty => {
+                                    println!("{}", pretty::format_watch_event(&event))
+                                }
                             },
                             Err(e) => {
                                 eprintln!("Error: {}", e);
@@ -298,6 +544,36 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 }
             }
         }
+        Commands::Trash(trash_cmd) => {
+            use calendsync_client::cli::trash::TrashAction;
+            match trash_cmd.action {
+                TrashAction::List => {
+                    let trash = client.list_trash().await?;
+                    match cli.format {
+                        OutputFormat::Json => println!("{}", format_output(&trash, cli.format)),
+                        OutputFormat::Pretty => println!("{}", pretty::format_trash(&trash)),
+                    }
+                }
+                TrashAction::RestoreEntry { id } => {
+                    let entry = client.restore_entry(id).await?;
+                    match cli.format {
+                        OutputFormat::Json => println!("{}", format_output(&entry, cli.format)),
+                        OutputFormat::Pretty => {
+                            println!("Restored:\n{}", pretty::format_entry(&entry))
+                        }
+                    }
+                }
+                TrashAction::RestoreCalendar { id } => {
+                    let calendar = client.restore_calendar(id).await?;
+                    match cli.format {
+                        OutputFormat::Json => println!("{}", format_output(&calendar, cli.format)),
+                        OutputFormat::Pretty => {
+                            println!("Restored:\n{}", pretty::format_calendar(&calendar))
+                        }
+                    }
+                }
+            }
+        }
     }
 
     Ok(())
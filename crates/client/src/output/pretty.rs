@@ -1,6 +1,11 @@
 //! Pretty output formatting.
 
-use calendsync_core::calendar::{Calendar, CalendarEntry, User};
+use calendsync_core::calendar::{
+    format_tags, Calendar, CalendarEntry, EntryKind, EntryRevision, FeedSubscription, FreeBusy,
+    ImportAction, ImportReport, RefreshReport, SearchHit, Trash, User,
+};
+
+use crate::client::events::WatchEvent;
 
 /// Format a user for display.
 pub fn format_user(user: &User) -> String {
@@ -30,6 +35,24 @@ pub fn format_calendar(calendar: &Calendar) -> String {
     if let Some(desc) = &calendar.description {
         output.push_str(&format!("\n  Description: {}", desc));
     }
+    if let Some(tz) = &calendar.timezone {
+        output.push_str(&format!("\n  Time zone: {}", tz));
+    }
+    if let Some(subscription) = &calendar.subscription {
+        output.push_str(&format!(
+            "\n  Subscribed to: {} (read-only)",
+            subscription.url
+        ));
+        if let Some(at) = subscription.refreshed_at {
+            output.push_str(&format!(
+                "\n  Last refreshed: {}",
+                at.format("%Y-%m-%d %H:%M")
+            ));
+        }
+        if let Some(error) = &subscription.error {
+            output.push_str(&format!("\n  Refresh error: {}", error));
+        }
+    }
     output
 }
 
@@ -63,9 +86,106 @@ pub fn format_entry(entry: &CalendarEntry) -> String {
     if let Some(color) = &entry.color {
         output.push_str(&format!("\n  Color: {}", color));
     }
+    if let Some(tz) = &entry.timezone {
+        output.push_str(&format!("\n  Time zone: {}", tz));
+    }
+    if entry.has_reminders() {
+        let reminders: Vec<String> = entry.reminders.iter().map(ToString::to_string).collect();
+        output.push_str(&format!("\n  Reminders: {}", reminders.join(", ")));
+    }
+
+    if !entry.attendees.is_empty() {
+        let attendees: Vec<String> = entry
+            .attendees
+            .iter()
+            .map(|attendee| format!("{} ({})", attendee.email, attendee.status))
+            .collect();
+        output.push_str(&format!("\n  Attendees: {}", attendees.join(", ")));
+    }
+    if !entry.tags.is_empty() {
+        output.push_str(&format!("\n  Tags: {}", format_tags(&entry.tags)));
+    }
+    if let EntryKind::Task {
+        completed,
+        due,
+        priority,
+        checklist,
+        roll_over,
+    } = &entry.kind
+    {
+        output.push_str(&format!(
+            "\n  Status: {}",
+            if *completed { "done" } else { "open" }
+        ));
+        output.push_str(&format!("\n  Priority: {}", priority));
+        if let Some(due) = due {
+            output.push_str(&format!("\n  Due: {}", due.format("%H:%M")));
+        }
+        if *roll_over {
+            output.push_str("\n  Rolls over: yes");
+        }
+        for (number, item) in checklist.iter().enumerate() {
+            output.push_str(&format!("\n  {}. {}", number + 1, item));
+        }
+    }
     output
 }
 
+/// Format an SSE event for display.
+pub fn format_watch_event(event: &WatchEvent) -> String {
+    let field = |key: &str| event.data.get(key).and_then(|v| v.as_str()).unwrap_or("");
+    let entry_field = |key: &str| {
+        event
+            .data
+            .get("entry")
+            .and_then(|entry| entry.get(key))
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+    };
+
+    match event.event.as_str() {
+        "reminder_due" => {
+            let mut output = format!(
+                "REMINDER {} ({})\n  Entry: {}\n  Date: {}",
+                entry_field("title"),
+                field("reminder"),
+                entry_field("id"),
+                field("date")
+            );
+            let start_time = entry_field("startTime");
+            if !start_time.is_empty() {
+                output.push_str(&format!(" {}", start_time));
+            }
+            output
+        }
+        "entry_deleted" => format!("[entry_deleted] {} on {}", field("entry_id"), field("date")),
+        "entry_batch" => {
+            let count = |key: &str| {
+                event
+                    .data
+                    .get(key)
+                    .and_then(|v| v.as_array())
+                    .map_or(0, Vec::len)
+            };
+            format!(
+                "[entry_batch] {} added, {} updated, {} deleted from {}",
+                count("added"),
+                count("updated"),
+                count("deleted"),
+                field("date")
+            )
+        }
+        "entry_added" | "entry_updated" | "entry_restored" => format!(
+            "[{}] {} on {}\n  ID: {}",
+            event.event,
+            entry_field("title"),
+            field("date"),
+            entry_field("id")
+        ),
+        other => format!("[{}] {}", other, event.data),
+    }
+}
+
 /// Format entries for display.
 pub fn format_entries(entries: &[CalendarEntry]) -> String {
     if entries.is_empty() {
@@ -80,10 +200,216 @@ pub fn format_entries(entries: &[CalendarEntry]) -> String {
     output
 }
 
+/// Format free/busy information for display. Times are in UTC.
+pub fn format_freebusy(free_busy: &FreeBusy) -> String {
+    let format_time = |at: &chrono::DateTime<chrono::Utc>| at.format("%Y-%m-%d %H:%M").to_string();
+    let mut output = format!(
+        "BUSY ({}) {} to {} UTC\n",
+        free_busy.busy.len(),
+        format_time(&free_busy.start),
+        format_time(&free_busy.end)
+    );
+    output.push_str(&"-".repeat(40));
+    if free_busy.busy.is_empty() {
+        output.push_str("\nFree the whole time.");
+    }
+    for interval in &free_busy.busy {
+        output.push_str(&format!(
+            "\n  {} - {}",
+            format_time(&interval.start),
+            format_time(&interval.end)
+        ));
+    }
+    if !free_busy.entries.is_empty() {
+        output.push_str("\nEntries:");
+        for entry in &free_busy.entries {
+            output.push_str(&format!(
+                "\n  {} - {}  {}",
+                format_time(&entry.start),
+                format_time(&entry.end),
+                entry.title
+            ));
+        }
+    }
+    output
+}
+
+/// Format search results for display, with matches marked `[like this]`.
+pub fn format_search_hits(hits: &[SearchHit]) -> String {
+    if hits.is_empty() {
+        return "No matches found.".to_string();
+    }
+    let mut output = format!("MATCHES ({})\n", hits.len());
+    output.push_str(&"-".repeat(40));
+    for hit in hits {
+        output.push_str(&format!(
+            "\n{} ({})\n  ID: {}\n  Date: {}",
+            hit.entry.title, hit.calendar.name, hit.entry.id, hit.entry.start_date
+        ));
+        for snippet in &hit.snippets {
+            output.push_str(&format!(
+                "\n  {}: {}",
+                snippet.field,
+                snippet.marked("[", "]")
+            ));
+        }
+        output.push('\n');
+    }
+    output
+}
+
+/// Format the trash for display.
+pub fn format_trash(trash: &Trash) -> String {
+    if trash.calendars.is_empty() && trash.entries.is_empty() {
+        return "Trash is empty.".to_string();
+    }
+    let format_time = |at: &chrono::DateTime<chrono::Utc>| at.format("%Y-%m-%d %H:%M").to_string();
+    let mut output = format!(
+        "TRASH ({}) kept for {} days\n",
+        trash.calendars.len() + trash.entries.len(),
+        trash.retention_days
+    );
+    output.push_str(&"-".repeat(40));
+    for trashed in &trash.calendars {
+        output.push_str(&format!(
+            "\nCalendar: {}\n  ID: {}\n  Deleted: {}\n",
+            trashed.calendar.name,
+            trashed.calendar.id,
+            format_time(&trashed.deleted_at)
+        ));
+    }
+    for trashed in &trash.entries {
+        output.push_str(&format!(
+            "\nEntry: {}\n  ID: {}\n  Date: {}\n  Deleted: {}\n",
+            trashed.entry.title,
+            trashed.entry.id,
+            trashed.entry.start_date,
+            format_time(&trashed.deleted_at)
+        ));
+    }
+    output
+}
+
+/// Format an entry's revision history for display, oldest first.
+pub fn format_history(history: &[EntryRevision]) -> String {
+    if history.is_empty() {
+        return "No history recorded.".to_string();
+    }
+    let mut output = format!("HISTORY ({})\n", history.len());
+    output.push_str(&"-".repeat(40));
+    for revision in history {
+        output.push_str(&format!(
+            "\n#{} {} {}",
+            revision.revision,
+            revision.action,
+            revision.recorded_at.format("%Y-%m-%d %H:%M")
+        ));
+        if let Some(actor_id) = revision.actor_id {
+            output.push_str(&format!(" by {}", actor_id));
+        }
+        output.push_str(&format!("\n  Title: {}", revision.snapshot.title));
+        if !revision.changed_fields.is_empty() {
+            output.push_str(&format!(
+                "\n  Changed: {}",
+                revision.changed_fields.join(", ")
+            ));
+        }
+        output.push('\n');
+    }
+    output
+}
+
+/// Format an iCalendar import report for display.
+pub fn format_import_report(report: &ImportReport) -> String {
+    let mut output = format!(
+        "{}: {} created, {} updated, {} unchanged, {} skipped",
+        if report.dry_run {
+            "IMPORT (dry run)"
+        } else {
+            "IMPORT"
+        },
+        report.created,
+        report.updated,
+        report.unchanged,
+        report.skipped.len()
+    );
+    if report.overrides > 0 {
+        output.push_str(&format!("\n  Occurrence overrides: {}", report.overrides));
+    }
+    output.push('\n');
+    output.push_str(&"-".repeat(40));
+    for item in &report.entries {
+        let action = match item.action {
+            ImportAction::Create => "create",
+            ImportAction::Update => "update",
+            ImportAction::Unchanged => "unchanged",
+        };
+        output.push_str(&format!(
+            "\n[{}] {} ({})\n  ID: {}",
+            action, item.title, item.start_date, item.id
+        ));
+    }
+    for skipped in &report.skipped {
+        output.push_str(&format!(
+            "\n[skipped] {}: {}",
+            skipped.uid.as_deref().unwrap_or("(no UID)"),
+            skipped.reason
+        ));
+    }
+    if !report.unsupported.is_empty() {
+        let unsupported: Vec<String> = report
+            .unsupported
+            .iter()
+            .map(|(name, count)| format!("{} ({})", name, count))
+            .collect();
+        output.push_str(&format!("\nUnsupported: {}", unsupported.join(", ")));
+    }
+    output
+}
+
+/// Format a subscribed calendar's refresh report for display.
+pub fn format_refresh_report(report: &RefreshReport) -> String {
+    let mut output = format!(
+        "REFRESH: {} created, {} updated, {} deleted, {} unchanged",
+        report.created, report.updated, report.deleted, report.unchanged
+    );
+    if report.overrides > 0 {
+        output.push_str(&format!(
+            "\n  Recurring entries with changed occurrences: {}",
+            report.overrides
+        ));
+    }
+    for skipped in &report.skipped {
+        output.push_str(&format!(
+            "\n[skipped] {}: {}",
+            skipped.uid.as_deref().unwrap_or("(no UID)"),
+            skipped.reason
+        ));
+    }
+    output
+}
+
+/// Format a calendar subscription feed for display.
+pub fn format_feed(feed: &FeedSubscription) -> String {
+    format!(
+        "FEED for calendar {}
+  URL: {}
+  Subscribe: {}
+  Created: {}",
+        feed.token.calendar_id,
+        feed.url,
+        feed.webcal_url,
+        feed.token.created_at.format("%Y-%m-%d %H:%M")
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use calendsync_core::calendar::{Calendar, CalendarEntry, EntryKind};
+    use calendsync_core::calendar::{
+        Calendar, CalendarEntry, CalendarSubscription, EntryKind, FeedToken, ImportedItem,
+        RevisionAction, SkippedComponent, TrashedCalendar, TrashedEntry,
+    };
     use chrono::{NaiveDate, Utc};
     use uuid::Uuid;
 
@@ -95,6 +421,7 @@ mod tests {
             email: email.to_string(),
             provider: None,
             provider_subject: None,
+            timezone: None,
             created_at: now,
             updated_at: now,
         }
@@ -108,6 +435,9 @@ mod tests {
             color: color.to_string(),
             description: None,
             is_default: false,
+            timezone: None,
+            subscription: None,
+            version: 1,
             created_at: now,
             updated_at: now,
         }
@@ -124,7 +454,15 @@ mod tests {
             start_date: date,
             end_date: date,
             color: None,
+            timezone: None,
             kind: EntryKind::AllDay,
+            recurrence: None,
+            reminders: Vec::new(),
+            attendees: Vec::new(),
+            tags: Default::default(),
+            occurrence_date: None,
+            segment: None,
+            version: 1,
             created_at: now,
             updated_at: now,
         }
@@ -203,6 +541,35 @@ mod tests {
         assert!(output.contains("Location: Room 101"));
     }
 
+    #[test]
+    fn test_format_entry_with_tags() {
+        let entry = make_entry("Flight", NaiveDate::from_ymd_opt(2024, 6, 15).unwrap())
+            .with_tag("travel".parse().unwrap())
+            .with_tag("oncall".parse().unwrap());
+        let output = format_entry(&entry);
+
+        assert!(output.contains("Tags: oncall,travel"));
+    }
+
+    #[test]
+    fn test_format_task_details() {
+        use calendsync_core::calendar::{ChecklistItem, TaskPriority};
+        use chrono::NaiveTime;
+
+        let date = NaiveDate::from_ymd_opt(2024, 6, 15).unwrap();
+        let entry = CalendarEntry::task(Uuid::new_v4(), "Pack", date, false)
+            .with_priority(TaskPriority::Urgent)
+            .with_due_time(NaiveTime::from_hms_opt(17, 0, 0).unwrap())
+            .with_checklist_item(ChecklistItem::new("Passport").completed())
+            .with_checklist_item(ChecklistItem::new("Charger"));
+        let output = format_entry(&entry);
+
+        assert!(output.contains("Priority: urgent"));
+        assert!(output.contains("Due: 17:00"));
+        assert!(output.contains("1. [x] Passport"));
+        assert!(output.contains("2. [ ] Charger"));
+    }
+
     #[test]
     fn test_format_entries_empty() {
         let output = format_entries(&[]);
@@ -221,4 +588,191 @@ mod tests {
         assert!(output.contains("Meeting 1"));
         assert!(output.contains("Meeting 2"));
     }
+
+    #[test]
+    fn test_format_search_hits() {
+        let query = "dentist".parse().unwrap();
+        let mut entry = make_entry("Dentist", NaiveDate::from_ymd_opt(2024, 6, 15).unwrap());
+        entry.location = Some("Main St. clinic".to_string());
+        let hit = SearchHit::new(&query, make_calendar("Personal", "#10B981"), entry);
+        let output = format_search_hits(&[hit]);
+
+        assert!(output.contains("MATCHES (1)"));
+        assert!(output.contains("Dentist (Personal)"));
+        assert!(output.contains("title: [Dentist]"));
+        assert!(!output.contains("location:"));
+        assert_eq!(format_search_hits(&[]), "No matches found.");
+    }
+
+    #[test]
+    fn test_format_freebusy() {
+        use calendsync_core::calendar::{BusyEntry, BusyInterval};
+        use chrono::TimeZone;
+
+        let at = |h| Utc.with_ymd_and_hms(2024, 1, 15, h, 0, 0).unwrap();
+        let free_busy = FreeBusy {
+            user_id: Uuid::new_v4(),
+            start: at(0),
+            end: at(23),
+            busy: vec![BusyInterval {
+                start: at(9),
+                end: at(10),
+            }],
+            entries: vec![BusyEntry {
+                entry_id: Uuid::new_v4(),
+                calendar_id: Uuid::new_v4(),
+                title: "Standup".to_string(),
+                start: at(9),
+                end: at(10),
+            }],
+        };
+        let output = format_freebusy(&free_busy);
+
+        assert!(output.starts_with("BUSY (1) 2024-01-15 00:00 to 2024-01-15 23:00 UTC"));
+        assert!(output.contains("\n  2024-01-15 09:00 - 2024-01-15 10:00"));
+        assert!(output.contains("10:00  Standup"));
+    }
+
+    #[test]
+    fn test_format_trash() {
+        assert_eq!(
+            format_trash(&Trash::new(Vec::new(), Vec::new(), 30)),
+            "Trash is empty."
+        );
+
+        let deleted_at = chrono::DateTime::parse_from_rfc3339("2024-01-20T08:15:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let trash = Trash::new(
+            vec![TrashedCalendar {
+                calendar: make_calendar("Work", "#3B82F6"),
+                deleted_at,
+            }],
+            vec![TrashedEntry {
+                entry: make_entry("Dentist", NaiveDate::from_ymd_opt(2024, 1, 15).unwrap()),
+                deleted_at,
+            }],
+            30,
+        );
+        let output = format_trash(&trash);
+
+        assert!(output.starts_with("TRASH (2) kept for 30 days"));
+        assert!(output.contains("Calendar: Work"));
+        assert!(output.contains("Entry: Dentist"));
+        assert!(output.contains("Date: 2024-01-15"));
+        assert!(output.contains("Deleted: 2024-01-20 08:15"));
+    }
+
+    #[test]
+    fn test_format_import_report() {
+        let mut report = ImportReport {
+            dry_run: true,
+            skipped: vec![SkippedComponent {
+                uid: Some("party@example.com".to_string()),
+                reason: "event is cancelled".to_string(),
+            }],
+            unsupported: [("X-WR-ALARMUID".to_string(), 2)].into_iter().collect(),
+            ..ImportReport::default()
+        };
+        report.push(ImportedItem {
+            uid: "dentist@example.com".to_string(),
+            id: Uuid::nil(),
+            title: "Dentist".to_string(),
+            start_date: NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+            action: ImportAction::Create,
+        });
+        let output = format_import_report(&report);
+
+        assert!(
+            output.starts_with("IMPORT (dry run): 1 created, 0 updated, 0 unchanged, 1 skipped")
+        );
+        assert!(output.contains("[create] Dentist (2024-01-15)"));
+        assert!(output.contains("[skipped] party@example.com: event is cancelled"));
+        assert!(output.contains("Unsupported: X-WR-ALARMUID (2)"));
+    }
+
+    #[test]
+    fn test_format_subscribed_calendar() {
+        let mut calendar = make_calendar("Holidays", "#10B981");
+        calendar.subscription = Some(CalendarSubscription {
+            url: "https://example.com/holidays.ics".to_string(),
+            refreshed_at: None,
+            error: Some("HTTP 404 Not Found".to_string()),
+        });
+        let output = format_calendar(&calendar);
+
+        assert!(output.contains("Subscribed to: https://example.com/holidays.ics (read-only)"));
+        assert!(!output.contains("Last refreshed"));
+        assert!(output.contains("Refresh error: HTTP 404 Not Found"));
+
+        let report = RefreshReport {
+            created: 3,
+            deleted: 1,
+            ..RefreshReport::default()
+        };
+        assert_eq!(
+            format_refresh_report(&report),
+            "REFRESH: 3 created, 0 updated, 1 deleted, 0 unchanged"
+        );
+    }
+
+    #[test]
+    fn test_format_feed() {
+        let token = FeedToken::new(Uuid::nil(), Uuid::nil());
+        let url = format!("https://cal.example.com{}", token.path());
+        let output = format_feed(&FeedSubscription::new(token.clone(), url.clone()));
+
+        assert!(output.starts_with(&format!("FEED for calendar {}", Uuid::nil())));
+        assert!(output.contains(&format!("URL: {url}")));
+        assert!(output.contains(&format!(
+            "Subscribe: webcal://cal.example.com/feeds/{}.ics",
+            token.token
+        )));
+    }
+
+    #[test]
+    fn test_format_history() {
+        assert_eq!(format_history(&[]), "No history recorded.");
+
+        let recorded_at = chrono::DateTime::parse_from_rfc3339("2024-01-20T08:15:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let entry = make_entry("Dentist", NaiveDate::from_ymd_opt(2024, 1, 15).unwrap());
+        let moved = entry.clone().with_location("Clinic");
+        let history = vec![
+            EntryRevision::new(1, RevisionAction::Created, entry.clone(), None, recorded_at),
+            EntryRevision::new(
+                2,
+                RevisionAction::Updated,
+                moved,
+                Some(Uuid::nil()),
+                recorded_at,
+            )
+            .with_changes_from(&entry),
+        ];
+        let output = format_history(&history);
+
+        assert!(output.starts_with("HISTORY (2)"));
+        assert!(output.contains("#1 created 2024-01-20 08:15"));
+        assert!(output.contains(&format!("#2 updated 2024-01-20 08:15 by {}", Uuid::nil())));
+        assert!(output.contains("Changed: location"));
+    }
+
+    #[test]
+    fn test_format_reminder_due_event() {
+        let event = WatchEvent {
+            id: Some(7),
+            event: "reminder_due".to_string(),
+            data: serde_json::json!({
+                "entry": {"id": "abc", "title": "Standup", "startTime": "09:30"},
+                "date": "2024-01-15",
+                "reminder": "10m",
+                "fire_at": "2024-01-15T09:20:00Z",
+            }),
+        };
+        let output = format_watch_event(&event);
+        assert!(output.starts_with("REMINDER Standup (10m)"));
+        assert!(output.contains("Entry: abc"));
+        assert!(output.contains("Date: 2024-01-15 09:30"));
+    }
 }
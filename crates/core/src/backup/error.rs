@@ -0,0 +1,29 @@
+use thiserror::Error;
+
+use super::format::RecordKind;
+
+/// Errors that can occur when reading or writing a backup.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum BackupError {
+    #[error("Not a calendsync backup: {0}")]
+    NotABackup(String),
+
+    #[error("Backup format version {found} is not supported (expected 1 to {supported})")]
+    UnsupportedVersion { found: u32, supported: u32 },
+
+    #[error("Invalid record on line {line}: {message}")]
+    InvalidRecord { line: usize, message: String },
+
+    #[error("Duplicate {kind} record: {id}")]
+    DuplicateRecord { kind: RecordKind, id: String },
+
+    #[error("{kind} {id} refers to a missing {missing}")]
+    MissingReference {
+        kind: RecordKind,
+        id: String,
+        missing: RecordKind,
+    },
+
+    #[error("Serialization error: {0}")]
+    Serialization(String),
+}
@@ -0,0 +1,528 @@
+//! The backup document: its header, records and JSON Lines encoding.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::error::BackupError;
+use crate::calendar::{
+    Calendar, CalendarEntry, CalendarMembership, CalendarSettings, OccurrenceOverride, User,
+};
+
+/// Value of the header's `format` field.
+pub const BACKUP_FORMAT: &str = "calendsync-backup";
+
+/// Version of the backup format written by this build.
+///
+/// Backups of this version or older can be read.
+pub const BACKUP_VERSION: u32 = 1;
+
+/// The first line of a backup.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupHeader {
+    /// Always `BACKUP_FORMAT`.
+    pub format: String,
+    pub version: u32,
+    /// When the data was read.
+    pub created_at: DateTime<Utc>,
+}
+
+/// A member's display settings for a calendar.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsRecord {
+    pub calendar_id: Uuid,
+    pub user_id: Uuid,
+    pub settings: CalendarSettings,
+}
+
+/// The kinds of record, in the order they are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordKind {
+    User,
+    Calendar,
+    Membership,
+    Settings,
+    Entry,
+    Override,
+}
+
+impl RecordKind {
+    /// Every kind, in the order records are written.
+    pub const ALL: [RecordKind; 6] = [
+        Self::User,
+        Self::Calendar,
+        Self::Membership,
+        Self::Settings,
+        Self::Entry,
+        Self::Override,
+    ];
+
+    /// Returns the name used for this kind in the `type` field.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Calendar => "calendar",
+            Self::Membership => "membership",
+            Self::Settings => "settings",
+            Self::Entry => "entry",
+            Self::Override => "override",
+        }
+    }
+}
+
+impl fmt::Display for RecordKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One line of a backup after the header.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum BackupRecord {
+    User(User),
+    Calendar(Calendar),
+    Membership(CalendarMembership),
+    Settings(SettingsRecord),
+    /// An entry as stored; recurring series are not expanded.
+    Entry(CalendarEntry),
+    Override(OccurrenceOverride),
+}
+
+impl BackupRecord {
+    pub fn kind(&self) -> RecordKind {
+        match self {
+            Self::User(_) => RecordKind::User,
+            Self::Calendar(_) => RecordKind::Calendar,
+            Self::Membership(_) => RecordKind::Membership,
+            Self::Settings(_) => RecordKind::Settings,
+            Self::Entry(_) => RecordKind::Entry,
+            Self::Override(_) => RecordKind::Override,
+        }
+    }
+
+    /// Returns the key that identifies the record among those of its kind.
+    ///
+    /// Memberships and settings are keyed `<calendar_id>:<user_id>`, and
+    /// overrides `<series_id>:<original_date>`.
+    pub fn id(&self) -> String {
+        match self {
+            Self::User(user) => user.id.to_string(),
+            Self::Calendar(calendar) => calendar.id.to_string(),
+            Self::Membership(m) => format!("{}:{}", m.calendar_id, m.user_id),
+            Self::Settings(s) => format!("{}:{}", s.calendar_id, s.user_id),
+            Self::Entry(entry) => entry.id.to_string(),
+            Self::Override(o) => format!("{}:{}", o.series_id, o.original_date),
+        }
+    }
+}
+
+/// How many records of each kind a backup holds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupCounts {
+    pub users: usize,
+    pub calendars: usize,
+    pub memberships: usize,
+    pub settings: usize,
+    pub entries: usize,
+    pub overrides: usize,
+}
+
+impl BackupCounts {
+    /// Counts one more record of `kind`.
+    pub fn add(&mut self, kind: RecordKind) {
+        *self.get_mut(kind) += 1;
+    }
+
+    /// Returns the count for `kind`.
+    pub fn get(&self, kind: RecordKind) -> usize {
+        match kind {
+            RecordKind::User => self.users,
+            RecordKind::Calendar => self.calendars,
+            RecordKind::Membership => self.memberships,
+            RecordKind::Settings => self.settings,
+            RecordKind::Entry => self.entries,
+            RecordKind::Override => self.overrides,
+        }
+    }
+
+    /// Returns the count over every kind.
+    pub fn total(&self) -> usize {
+        self.users
+            + self.calendars
+            + self.memberships
+            + self.settings
+            + self.entries
+            + self.overrides
+    }
+
+    fn get_mut(&mut self, kind: RecordKind) -> &mut usize {
+        match kind {
+            RecordKind::User => &mut self.users,
+            RecordKind::Calendar => &mut self.calendars,
+            RecordKind::Membership => &mut self.memberships,
+            RecordKind::Settings => &mut self.settings,
+            RecordKind::Entry => &mut self.entries,
+            RecordKind::Override => &mut self.overrides,
+        }
+    }
+}
+
+/// Everything a backup holds, by kind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backup {
+    /// When the data was read.
+    pub created_at: DateTime<Utc>,
+    pub users: Vec<User>,
+    pub calendars: Vec<Calendar>,
+    pub memberships: Vec<CalendarMembership>,
+    pub settings: Vec<SettingsRecord>,
+    pub entries: Vec<CalendarEntry>,
+    pub overrides: Vec<OccurrenceOverride>,
+}
+
+impl Backup {
+    /// Creates an empty backup of data read at `created_at`.
+    pub fn new(created_at: DateTime<Utc>) -> Self {
+        Self {
+            created_at,
+            users: Vec::new(),
+            calendars: Vec::new(),
+            memberships: Vec::new(),
+            settings: Vec::new(),
+            entries: Vec::new(),
+            overrides: Vec::new(),
+        }
+    }
+
+    /// Adds a record.
+    pub fn push(&mut self, record: BackupRecord) {
+        match record {
+            BackupRecord::User(user) => self.users.push(user),
+            BackupRecord::Calendar(calendar) => self.calendars.push(calendar),
+            BackupRecord::Membership(membership) => self.memberships.push(membership),
+            BackupRecord::Settings(settings) => self.settings.push(settings),
+            BackupRecord::Entry(entry) => self.entries.push(entry),
+            BackupRecord::Override(occurrence) => self.overrides.push(occurrence),
+        }
+    }
+
+    /// Returns every record, in the order they are written and restored.
+    pub fn records(&self) -> Vec<BackupRecord> {
+        let users = self.users.iter().cloned().map(BackupRecord::User);
+        let calendars = self.calendars.iter().cloned().map(BackupRecord::Calendar);
+        let memberships = self
+            .memberships
+            .iter()
+            .cloned()
+            .map(BackupRecord::Membership);
+        let settings = self.settings.iter().cloned().map(BackupRecord::Settings);
+        let entries = self.entries.iter().cloned().map(BackupRecord::Entry);
+        let overrides = self.overrides.iter().cloned().map(BackupRecord::Override);
+        users
+            .chain(calendars)
+            .chain(memberships)
+            .chain(settings)
+            .chain(entries)
+            .chain(overrides)
+            .collect()
+    }
+
+    /// Counts the records of each kind.
+    pub fn counts(&self) -> BackupCounts {
+        BackupCounts {
+            users: self.users.len(),
+            calendars: self.calendars.len(),
+            memberships: self.memberships.len(),
+            settings: self.settings.len(),
+            entries: self.entries.len(),
+            overrides: self.overrides.len(),
+        }
+    }
+
+    /// Sorts the records of each kind by their IDs, so dumps of the same data
+    /// are identical whatever order the backend listed it in.
+    pub fn sort(&mut self) {
+        self.users.sort_by_key(|u| u.id);
+        self.calendars.sort_by_key(|c| c.id);
+        self.memberships.sort_by_key(|m| (m.calendar_id, m.user_id));
+        self.settings.sort_by_key(|s| (s.calendar_id, s.user_id));
+        self.entries.sort_by_key(|e| e.id);
+        self.overrides
+            .sort_by_key(|o| (o.series_id, o.original_date));
+    }
+
+    /// Checks that no record appears twice and that every record refers only
+    /// to records in the backup.
+    pub fn validate(&self) -> Result<(), BackupError> {
+        let mut seen = HashSet::new();
+        for record in self.records() {
+            if !seen.insert((record.kind(), record.id())) {
+                return Err(BackupError::DuplicateRecord {
+                    kind: record.kind(),
+                    id: record.id(),
+                });
+            }
+        }
+
+        let users: HashSet<Uuid> = self.users.iter().map(|u| u.id).collect();
+        let calendars: HashSet<Uuid> = self.calendars.iter().map(|c| c.id).collect();
+        let entries: HashSet<Uuid> = self.entries.iter().map(|e| e.id).collect();
+
+        for record in self.records() {
+            let missing = match &record {
+                BackupRecord::Membership(CalendarMembership {
+                    calendar_id,
+                    user_id,
+                    ..
+                })
+                | BackupRecord::Settings(SettingsRecord {
+                    calendar_id,
+                    user_id,
+                    ..
+                }) => {
+                    if !calendars.contains(calendar_id) {
+                        Some(RecordKind::Calendar)
+                    } else if !users.contains(user_id) {
+                        Some(RecordKind::User)
+                    } else {
+                        None
+                    }
+                }
+                BackupRecord::Entry(entry) => {
+                    (!calendars.contains(&entry.calendar_id)).then_some(RecordKind::Calendar)
+                }
+                BackupRecord::Override(o) => {
+                    (!entries.contains(&o.series_id)).then_some(RecordKind::Entry)
+                }
+                BackupRecord::User(_) | BackupRecord::Calendar(_) => None,
+            };
+            if let Some(missing) = missing {
+                return Err(BackupError::MissingReference {
+                    kind: record.kind(),
+                    id: record.id(),
+                    missing,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Encodes the backup as JSON Lines: the header, then one record per line.
+    pub fn to_jsonl(&self) -> Result<String, BackupError> {
+        let header = BackupHeader {
+            format: BACKUP_FORMAT.to_string(),
+            version: BACKUP_VERSION,
+            created_at: self.created_at,
+        };
+        let mut output = to_line(&header)?;
+        for record in self.records() {
+            output.push_str(&to_line(&record)?);
+        }
+        Ok(output)
+    }
+
+    /// Decodes and validates a backup written by `to_jsonl`.
+    ///
+    /// Blank lines are skipped. Fails on the first line that isn't a known
+    /// record, and on backups written by a newer format version.
+    pub fn from_jsonl(input: &str) -> Result<Self, BackupError> {
+        let mut lines = input
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty());
+
+        let (_, first) = lines
+            .next()
+            .ok_or_else(|| BackupError::NotABackup("the document is empty".to_string()))?;
+        let header: BackupHeader = serde_json::from_str(first)
+            .map_err(|e| BackupError::NotABackup(format!("invalid header: {e}")))?;
+        if header.format != BACKUP_FORMAT {
+            return Err(BackupError::NotABackup(format!(
+                "unknown format {:?}",
+                header.format
+            )));
+        }
+        if !(1..=BACKUP_VERSION).contains(&header.version) {
+            return Err(BackupError::UnsupportedVersion {
+                found: header.version,
+                supported: BACKUP_VERSION,
+            });
+        }
+
+        let mut backup = Self::new(header.created_at);
+        for (line, text) in lines {
+            let record: BackupRecord =
+                serde_json::from_str(text).map_err(|e| BackupError::InvalidRecord {
+                    line,
+                    message: e.to_string(),
+                })?;
+            backup.push(record);
+        }
+        backup.validate()?;
+        Ok(backup)
+    }
+}
+
+fn to_line(value: &impl Serialize) -> Result<String, BackupError> {
+    let mut line =
+        serde_json::to_string(value).map_err(|e| BackupError::Serialization(e.to_string()))?;
+    line.push('\n');
+    Ok(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::{CalendarEntry, RecurrenceRule};
+    use chrono::{NaiveDate, TimeZone};
+
+    /// A backup holding one record of each kind.
+    fn sample_backup() -> Backup {
+        let created_at = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let user = User::new("Alice", "alice@example.com").with_created_at(created_at);
+        let calendar = Calendar::new("Work", "#3B82F6").with_created_at(created_at);
+        let date = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        let mut series = CalendarEntry::all_day(calendar.id, "Standup", date)
+            .with_recurrence(RecurrenceRule::weekly());
+        series.version = 3;
+
+        let mut backup = Backup::new(created_at);
+        backup.push(BackupRecord::Membership(CalendarMembership::owner(
+            calendar.id,
+            user.id,
+        )));
+        backup.push(BackupRecord::Settings(SettingsRecord {
+            calendar_id: calendar.id,
+            user_id: user.id,
+            settings: CalendarSettings::default(),
+        }));
+        backup.push(BackupRecord::Override(OccurrenceOverride::cancelled(
+            series.id,
+            date + chrono::Duration::days(7),
+        )));
+        backup.push(BackupRecord::Entry(series));
+        backup.push(BackupRecord::Calendar(calendar));
+        backup.push(BackupRecord::User(user));
+        backup
+    }
+
+    #[test]
+    fn test_jsonl_roundtrip() {
+        let backup = sample_backup();
+        let jsonl = backup.to_jsonl().unwrap();
+
+        let lines: Vec<&str> = jsonl.lines().collect();
+        assert_eq!(lines.len(), 7);
+        assert!(lines[0].starts_with(r#"{"format":"calendsync-backup","version":1"#));
+        assert!(lines[1].starts_with(r#"{"type":"user","data":"#));
+        assert!(lines[6].starts_with(r#"{"type":"override","data":"#));
+
+        assert_eq!(Backup::from_jsonl(&jsonl).unwrap(), backup);
+        assert_eq!(backup.counts().total(), 6);
+        assert_eq!(backup.counts().get(RecordKind::Override), 1);
+    }
+
+    #[test]
+    fn test_from_jsonl_skips_blank_lines() {
+        let jsonl = sample_backup().to_jsonl().unwrap().replace('\n', "\n\n");
+        assert_eq!(Backup::from_jsonl(&jsonl).unwrap().counts().total(), 6);
+    }
+
+    #[test]
+    fn test_from_jsonl_rejects_other_documents() {
+        assert!(matches!(
+            Backup::from_jsonl(""),
+            Err(BackupError::NotABackup(_))
+        ));
+        assert!(matches!(
+            Backup::from_jsonl("BEGIN:VCALENDAR"),
+            Err(BackupError::NotABackup(_))
+        ));
+        assert!(matches!(
+            Backup::from_jsonl(
+                r#"{"format":"other","version":1,"createdAt":"2024-03-01T12:00:00Z"}"#
+            ),
+            Err(BackupError::NotABackup(_))
+        ));
+        assert_eq!(
+            Backup::from_jsonl(
+                r#"{"format":"calendsync-backup","version":2,"createdAt":"2024-03-01T12:00:00Z"}"#
+            ),
+            Err(BackupError::UnsupportedVersion {
+                found: 2,
+                supported: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_from_jsonl_reports_invalid_line() {
+        let mut jsonl = sample_backup().to_jsonl().unwrap();
+        jsonl.push_str("{\"type\":\"feed_token\",\"data\":{}}\n");
+
+        assert!(matches!(
+            Backup::from_jsonl(&jsonl),
+            Err(BackupError::InvalidRecord { line: 8, .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_rejects_duplicates() {
+        let mut backup = sample_backup();
+        backup.users.push(backup.users[0].clone());
+
+        assert_eq!(
+            backup.validate(),
+            Err(BackupError::DuplicateRecord {
+                kind: RecordKind::User,
+                id: backup.users[0].id.to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_missing_references() {
+        let mut backup = sample_backup();
+        backup.users.clear();
+        assert!(matches!(
+            backup.validate(),
+            Err(BackupError::MissingReference {
+                kind: RecordKind::Membership,
+                missing: RecordKind::User,
+                ..
+            })
+        ));
+
+        let mut backup = sample_backup();
+        backup.entries.clear();
+        assert!(matches!(
+            backup.validate(),
+            Err(BackupError::MissingReference {
+                kind: RecordKind::Override,
+                missing: RecordKind::Entry,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_sort_is_stable_across_listing_order() {
+        let mut a = sample_backup();
+        let other = User::new("Bob", "bob@example.com");
+        a.users.push(other.clone());
+        let mut b = a.clone();
+        b.users.reverse();
+
+        a.sort();
+        b.sort();
+        assert_eq!(a.to_jsonl().unwrap(), b.to_jsonl().unwrap());
+    }
+}
@@ -0,0 +1,26 @@
+//! Full backups of a server's data.
+//!
+//! A backup is a JSON Lines document. The first line is a header naming the
+//! format and its version; every other line is one record: a user, calendar,
+//! membership, member's calendar settings, stored entry or occurrence
+//! override. Records are written in that order, so each one only refers to
+//! records above it. They keep their IDs, versions and timestamps, so a
+//! restored store reads back exactly like the one that was dumped, whatever
+//! the backend.
+//!
+//! Only live data is included: the trash, revision history, feed tokens and
+//! delivered reminders are left out.
+
+mod error;
+mod format;
+mod verify;
+
+pub use error::BackupError;
+pub use format::{
+    Backup, BackupCounts, BackupHeader, BackupRecord, RecordKind, SettingsRecord, BACKUP_FORMAT,
+    BACKUP_VERSION,
+};
+pub use verify::{verify_backup, BackupMismatch, MismatchKind, RestoreReport};
+
+/// Media type of backup documents.
+pub const BACKUP_CONTENT_TYPE: &str = "application/x-ndjson";
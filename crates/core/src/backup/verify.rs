@@ -0,0 +1,150 @@
+//! Checking a restore against the backup it came from.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::format::{Backup, BackupCounts, BackupRecord, RecordKind};
+
+/// How a stored record fails to match the backup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MismatchKind {
+    /// The store has no such record.
+    Missing,
+    /// The store's record differs from the backup's.
+    Different,
+}
+
+/// A backup record that the store doesn't hold as it was backed up.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupMismatch {
+    pub kind: RecordKind,
+    /// The record's key, see `BackupRecord::id`.
+    pub id: String,
+    pub problem: MismatchKind,
+}
+
+impl fmt::Display for BackupMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let problem = match self.problem {
+            MismatchKind::Missing => "missing",
+            MismatchKind::Different => "different",
+        };
+        write!(f, "{} {}: {problem}", self.kind, self.id)
+    }
+}
+
+/// What a restore did, and what its verification pass found.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RestoreReport {
+    /// Records written.
+    pub restored: BackupCounts,
+    /// Records the store already held unchanged.
+    pub skipped: BackupCounts,
+    /// Records the store already held with other contents, left as they were.
+    pub conflicts: BackupCounts,
+    /// Backup records the store doesn't hold as they were backed up.
+    pub mismatches: Vec<BackupMismatch>,
+}
+
+impl RestoreReport {
+    /// Returns whether every record of the backup is in the store unchanged.
+    pub fn is_verified(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// Compares the records of `expected` with `actual`, a dump of the store
+/// they were restored into.
+///
+/// Records only in `actual` are not mismatches, since a backup may be
+/// restored next to other data.
+pub fn verify_backup(expected: &Backup, actual: &Backup) -> Vec<BackupMismatch> {
+    let stored: HashMap<(RecordKind, String), BackupRecord> = actual
+        .records()
+        .into_iter()
+        .map(|record| ((record.kind(), record.id()), record))
+        .collect();
+
+    expected
+        .records()
+        .into_iter()
+        .filter_map(|record| {
+            let problem = match stored.get(&(record.kind(), record.id())) {
+                None => MismatchKind::Missing,
+                Some(found) if *found == record => return None,
+                Some(_) => MismatchKind::Different,
+            };
+            Some(BackupMismatch {
+                kind: record.kind(),
+                id: record.id(),
+                problem,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::{Calendar, CalendarEntry, CalendarMembership, User};
+    use chrono::{NaiveDate, Utc};
+
+    fn backup() -> Backup {
+        let user = User::new("Alice", "alice@example.com");
+        let calendar = Calendar::new("Work", "#3B82F6");
+        let date = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        let mut backup = Backup::new(Utc::now());
+        backup.memberships = vec![CalendarMembership::owner(calendar.id, user.id)];
+        backup.entries = vec![CalendarEntry::all_day(calendar.id, "Standup", date)];
+        backup.users = vec![user];
+        backup.calendars = vec![calendar];
+        backup
+    }
+
+    #[test]
+    fn test_verify_identical() {
+        let backup = backup();
+        assert!(verify_backup(&backup, &backup.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_verify_reports_missing_and_different() {
+        let expected = backup();
+        let mut actual = expected.clone();
+        actual.memberships.clear();
+        actual.entries[0].version += 1;
+
+        let mismatches = verify_backup(&expected, &actual);
+        assert_eq!(
+            mismatches,
+            vec![
+                BackupMismatch {
+                    kind: RecordKind::Membership,
+                    id: format!("{}:{}", expected.calendars[0].id, expected.users[0].id),
+                    problem: MismatchKind::Missing,
+                },
+                BackupMismatch {
+                    kind: RecordKind::Entry,
+                    id: expected.entries[0].id.to_string(),
+                    problem: MismatchKind::Different,
+                },
+            ]
+        );
+        assert_eq!(
+            mismatches[1].to_string(),
+            format!("entry {}: different", expected.entries[0].id)
+        );
+    }
+
+    #[test]
+    fn test_verify_ignores_other_data() {
+        let expected = backup();
+        let mut actual = expected.clone();
+        actual.users.push(User::new("Bob", "bob@example.com"));
+
+        assert!(verify_backup(&expected, &actual).is_empty());
+    }
+}
@@ -0,0 +1,34 @@
+use thiserror::Error;
+
+use crate::calendar::IcsError;
+
+/// Errors that can occur when decoding CalDAV requests.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum CalDavError {
+    #[error("Malformed XML: {0}")]
+    InvalidXml(String),
+
+    #[error("Unexpected request body: expected <{0}>")]
+    UnexpectedElement(String),
+
+    #[error("Unsupported report: {0}")]
+    UnsupportedReport(String),
+
+    #[error("Invalid time-range value: {0}")]
+    InvalidTimeRange(String),
+
+    #[error("Invalid calendar data: {0}")]
+    InvalidCalendarData(String),
+
+    #[error("A calendar resource must hold exactly one event or task")]
+    ComponentCount,
+
+    #[error("All components of a calendar resource must share one UID")]
+    MixedUids,
+}
+
+impl From<IcsError> for CalDavError {
+    fn from(err: IcsError) -> Self {
+        Self::InvalidCalendarData(err.to_string())
+    }
+}
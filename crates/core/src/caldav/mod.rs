@@ -0,0 +1,48 @@
+//! CalDAV (RFC 4791) support for native calendar clients.
+//!
+//! Thunderbird, DAVx5 and macOS Calendar sync over CalDAV: they discover the
+//! user's principal and calendar home with PROPFIND, list what changed by
+//! comparing collection ctags and resource ETags, fetch entries with REPORT
+//! and write them back as one iCalendar resource per entry with PUT and
+//! DELETE. This module holds the protocol logic - XML parsing, request
+//! decoding, property values and multistatus rendering - while the HTTP
+//! handlers only load and store data.
+//!
+//! Only the subset those clients need is implemented: no locking, no
+//! scheduling (inbox/outbox) and no `sync-collection` report.
+
+mod error;
+mod multistatus;
+mod props;
+mod request;
+mod resource;
+mod xml;
+
+pub use error::CalDavError;
+pub use multistatus::{format_multistatus, DavResponse};
+pub use props::{property_value, resource_properties, resource_response, DavProperty, DavResource};
+pub use request::{parse_propfind, parse_report, Depth, PropfindRequest, ReportRequest, TimeRange};
+pub use resource::{
+    calendar_path, collection_ctag, entry_component, etag_matches, parse_resource_href,
+    plan_resource_put, principal_path, resource_entry_id, resource_etag, resource_object,
+    resource_path, ResourceObject, CALENDAR_HOME_PATH, DAV_ROOT_PATH,
+};
+pub use xml::{escape_xml, parse_xml, XmlElement};
+
+/// The WebDAV namespace.
+pub const DAV_NS: &str = "DAV:";
+
+/// The CalDAV namespace.
+pub const CALDAV_NS: &str = "urn:ietf:params:xml:ns:caldav";
+
+/// Calendar Server extensions, where `getctag` lives.
+pub const CALSERVER_NS: &str = "http://calendarserver.org/ns/";
+
+/// Apple iCal extensions, where `calendar-color` lives.
+pub const APPLE_ICAL_NS: &str = "http://apple.com/ns/ical/";
+
+/// Value of the `DAV` header advertising the supported compliance classes.
+pub const DAV_COMPLIANCE: &str = "1, calendar-access";
+
+/// Media type of WebDAV XML bodies.
+pub const DAV_XML_CONTENT_TYPE: &str = "application/xml; charset=utf-8";
@@ -0,0 +1,161 @@
+//! Rendering `207 Multi-Status` response bodies.
+
+use super::props::DavProperty;
+use super::xml::escape_xml;
+use super::{APPLE_ICAL_NS, CALDAV_NS, CALSERVER_NS, DAV_NS};
+
+/// Prefixes declared on the `multistatus` element.
+const PREFIXES: &[(&str, &str)] = &[
+    ("d", DAV_NS),
+    ("c", CALDAV_NS),
+    ("cs", CALSERVER_NS),
+    ("ical", APPLE_ICAL_NS),
+];
+
+/// The properties of one resource in a multistatus response.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DavResponse {
+    pub href: String,
+    /// Properties the resource has, with their rendered values.
+    pub found: Vec<(DavProperty, String)>,
+    /// Properties that were asked for but the resource doesn't have.
+    pub missing: Vec<DavProperty>,
+    /// Set when the resource itself doesn't exist.
+    pub not_found: bool,
+}
+
+impl DavResponse {
+    /// Starts a response for the resource at `href`.
+    pub fn new(href: impl Into<String>) -> Self {
+        Self {
+            href: href.into(),
+            ..Self::default()
+        }
+    }
+
+    /// A response reporting that nothing exists at `href`.
+    pub fn not_found(href: impl Into<String>) -> Self {
+        Self {
+            not_found: true,
+            ..Self::new(href)
+        }
+    }
+}
+
+/// Renders a `multistatus` document.
+pub fn format_multistatus(responses: &[DavResponse]) -> String {
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="utf-8"?>"#);
+    xml.push_str("<d:multistatus");
+    for (prefix, namespace) in PREFIXES {
+        xml.push_str(&format!(r#" xmlns:{prefix}="{namespace}""#));
+    }
+    xml.push('>');
+
+    for response in responses {
+        xml.push_str("<d:response>");
+        xml.push_str(&format!("<d:href>{}</d:href>", escape_xml(&response.href)));
+        if response.not_found {
+            xml.push_str("<d:status>HTTP/1.1 404 Not Found</d:status>");
+        }
+        if !response.found.is_empty() {
+            let props: String = response
+                .found
+                .iter()
+                .map(|(property, value)| property_element(property, value))
+                .collect();
+            xml.push_str(&propstat(&props, "200 OK"));
+        }
+        if !response.missing.is_empty() {
+            let props: String = response
+                .missing
+                .iter()
+                .map(|property| property_element(property, ""))
+                .collect();
+            xml.push_str(&propstat(&props, "404 Not Found"));
+        }
+        xml.push_str("</d:response>");
+    }
+
+    xml.push_str("</d:multistatus>");
+    xml
+}
+
+fn propstat(props: &str, status: &str) -> String {
+    format!(
+        "<d:propstat><d:prop>{props}</d:prop><d:status>HTTP/1.1 {status}</d:status></d:propstat>"
+    )
+}
+
+/// Renders a property element, declaring its namespace inline when it has
+/// no prefix.
+fn property_element(property: &DavProperty, value: &str) -> String {
+    let (namespace, name) = property.qualified_name();
+    let (tag, declaration) = match PREFIXES.iter().find(|(_, ns)| *ns == namespace) {
+        Some((prefix, _)) => (format!("{prefix}:{name}"), String::new()),
+        None => (
+            format!("x:{name}"),
+            format!(r#" xmlns:x="{}""#, escape_xml(namespace)),
+        ),
+    };
+    if value.is_empty() {
+        format!("<{tag}{declaration}/>")
+    } else {
+        format!("<{tag}{declaration}>{value}</{tag}>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::caldav::parse_xml;
+
+    #[test]
+    fn test_format_multistatus() {
+        let mut calendar = DavResponse::new("/dav/calendars/a/");
+        calendar
+            .found
+            .push((DavProperty::DisplayName, "Work".to_string()));
+        calendar
+            .found
+            .push((DavProperty::GetCtag, "abc".to_string()));
+        calendar.missing.push(DavProperty::Unknown {
+            namespace: "urn:x".to_string(),
+            name: "quota".to_string(),
+        });
+        let responses = [calendar, DavResponse::not_found("/dav/calendars/a/b.ics")];
+
+        let xml = format_multistatus(&responses);
+        let root = parse_xml(&xml).unwrap();
+        assert!(root.is(DAV_NS, "multistatus"));
+        assert_eq!(root.children.len(), 2);
+
+        let first = &root.children[0];
+        assert_eq!(
+            first.child(DAV_NS, "href").unwrap().text,
+            "/dav/calendars/a/"
+        );
+        let propstats: Vec<_> = first
+            .children
+            .iter()
+            .filter(|c| c.is(DAV_NS, "propstat"))
+            .collect();
+        assert_eq!(propstats.len(), 2);
+        let ok = propstats[0].child(DAV_NS, "prop").unwrap();
+        assert_eq!(ok.child(DAV_NS, "displayname").unwrap().text, "Work");
+        assert_eq!(ok.child(CALSERVER_NS, "getctag").unwrap().text, "abc");
+        assert!(propstats[1]
+            .child(DAV_NS, "prop")
+            .unwrap()
+            .child("urn:x", "quota")
+            .is_some());
+        assert!(propstats[1]
+            .child(DAV_NS, "status")
+            .unwrap()
+            .text
+            .contains("404"));
+
+        let second = &root.children[1];
+        assert!(second.child(DAV_NS, "status").unwrap().text.contains("404"));
+        assert!(second.child(DAV_NS, "propstat").is_none());
+    }
+}
@@ -0,0 +1,382 @@
+//! WebDAV properties and their values on each kind of resource.
+
+use super::multistatus::DavResponse;
+use super::request::PropfindRequest;
+use super::resource::{entry_component, principal_path, CALENDAR_HOME_PATH};
+use super::xml::escape_xml;
+use super::{APPLE_ICAL_NS, CALDAV_NS, CALSERVER_NS, DAV_NS};
+use crate::calendar::{Calendar, CalendarEntry, CalendarRole, User};
+
+/// A property a client can ask for.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DavProperty {
+    ResourceType,
+    DisplayName,
+    CurrentUserPrincipal,
+    PrincipalUrl,
+    Owner,
+    CurrentUserPrivilegeSet,
+    SupportedReportSet,
+    GetEtag,
+    GetContentType,
+    CalendarHomeSet,
+    CalendarUserAddressSet,
+    CalendarDescription,
+    SupportedCalendarComponentSet,
+    CalendarData,
+    GetCtag,
+    CalendarColor,
+    /// A property this server doesn't have, reported back as not found.
+    Unknown {
+        namespace: String,
+        name: String,
+    },
+}
+
+/// Known properties with their namespace and element name.
+const KNOWN: &[(DavProperty, &str, &str)] = &[
+    (DavProperty::ResourceType, DAV_NS, "resourcetype"),
+    (DavProperty::DisplayName, DAV_NS, "displayname"),
+    (
+        DavProperty::CurrentUserPrincipal,
+        DAV_NS,
+        "current-user-principal",
+    ),
+    (DavProperty::PrincipalUrl, DAV_NS, "principal-URL"),
+    (DavProperty::Owner, DAV_NS, "owner"),
+    (
+        DavProperty::CurrentUserPrivilegeSet,
+        DAV_NS,
+        "current-user-privilege-set",
+    ),
+    (
+        DavProperty::SupportedReportSet,
+        DAV_NS,
+        "supported-report-set",
+    ),
+    (DavProperty::GetEtag, DAV_NS, "getetag"),
+    (DavProperty::GetContentType, DAV_NS, "getcontenttype"),
+    (DavProperty::CalendarHomeSet, CALDAV_NS, "calendar-home-set"),
+    (
+        DavProperty::CalendarUserAddressSet,
+        CALDAV_NS,
+        "calendar-user-address-set",
+    ),
+    (
+        DavProperty::CalendarDescription,
+        CALDAV_NS,
+        "calendar-description",
+    ),
+    (
+        DavProperty::SupportedCalendarComponentSet,
+        CALDAV_NS,
+        "supported-calendar-component-set",
+    ),
+    (DavProperty::CalendarData, CALDAV_NS, "calendar-data"),
+    (DavProperty::GetCtag, CALSERVER_NS, "getctag"),
+    (DavProperty::CalendarColor, APPLE_ICAL_NS, "calendar-color"),
+];
+
+impl DavProperty {
+    /// Returns the property named `name` in `namespace`.
+    pub fn from_name(namespace: &str, name: &str) -> Self {
+        KNOWN
+            .iter()
+            .find(|(_, ns, n)| *ns == namespace && *n == name)
+            .map(|(property, _, _)| property.clone())
+            .unwrap_or_else(|| Self::Unknown {
+                namespace: namespace.to_string(),
+                name: name.to_string(),
+            })
+    }
+
+    /// Returns the property's namespace and element name.
+    pub fn qualified_name(&self) -> (&str, &str) {
+        if let Self::Unknown { namespace, name } = self {
+            return (namespace, name);
+        }
+        KNOWN
+            .iter()
+            .find(|(property, _, _)| property == self)
+            .map(|(_, ns, name)| (*ns, *name))
+            .expect("every known property is listed")
+    }
+}
+
+/// A resource whose properties are reported, with what its values are
+/// computed from.
+#[derive(Debug, Clone, Copy)]
+pub enum DavResource<'a> {
+    /// The `/dav/` entry point.
+    Root,
+    /// The requesting user's principal.
+    Principal,
+    /// The collection holding the user's calendars.
+    Home,
+    /// A calendar the user is a member of, with its ctag if it was asked for.
+    Calendar {
+        calendar: &'a Calendar,
+        role: CalendarRole,
+        ctag: Option<&'a str>,
+    },
+    /// An entry stored as an iCalendar resource, with its data if it was
+    /// asked for.
+    Entry {
+        entry: &'a CalendarEntry,
+        etag: &'a str,
+        data: Option<&'a str>,
+    },
+}
+
+/// Returns the properties a resource has, as listed for `allprop` and
+/// `propname` requests. `calendar-data` is left out as RFC 4791 requires.
+pub fn resource_properties(resource: &DavResource) -> Vec<DavProperty> {
+    use DavProperty::*;
+    let mut properties = vec![ResourceType, CurrentUserPrincipal];
+    match resource {
+        DavResource::Root => {}
+        DavResource::Principal => properties.extend([
+            DisplayName,
+            PrincipalUrl,
+            CalendarHomeSet,
+            CalendarUserAddressSet,
+        ]),
+        DavResource::Home => properties.extend([DisplayName, CalendarHomeSet]),
+        DavResource::Calendar { .. } => properties.extend([
+            DisplayName,
+            Owner,
+            CurrentUserPrivilegeSet,
+            SupportedReportSet,
+            CalendarDescription,
+            SupportedCalendarComponentSet,
+            GetCtag,
+            CalendarColor,
+        ]),
+        DavResource::Entry { .. } => properties.extend([GetEtag, GetContentType]),
+    }
+    properties
+}
+
+/// Renders the value of `property` on `resource` as viewed by `user`.
+///
+/// Returns `None` when the resource doesn't have the property.
+pub fn property_value(
+    resource: &DavResource,
+    user: &User,
+    property: &DavProperty,
+) -> Option<String> {
+    use DavProperty::*;
+    let principal = || href(&principal_path(user.id));
+    let value = match (property, resource) {
+        (CurrentUserPrincipal, _) => principal(),
+        (ResourceType, DavResource::Root | DavResource::Home) => "<d:collection/>".to_string(),
+        (ResourceType, DavResource::Principal) => "<d:principal/>".to_string(),
+        (ResourceType, DavResource::Calendar { .. }) => "<d:collection/><c:calendar/>".to_string(),
+        (ResourceType, DavResource::Entry { .. }) => String::new(),
+        (DisplayName, DavResource::Principal) => escape_xml(&user.name),
+        (DisplayName, DavResource::Home) => "Calendars".to_string(),
+        (DisplayName, DavResource::Calendar { calendar, .. }) => escape_xml(&calendar.name),
+        (PrincipalUrl, DavResource::Principal) => principal(),
+        (Owner, DavResource::Calendar { .. }) => principal(),
+        (CalendarHomeSet, DavResource::Principal | DavResource::Home) => href(CALENDAR_HOME_PATH),
+        (CalendarUserAddressSet, DavResource::Principal) => href(&format!("mailto:{}", user.email)),
+        (CurrentUserPrivilegeSet, DavResource::Calendar { calendar, role, .. }) => {
+            privilege_set(calendar, *role)
+        }
+        (SupportedReportSet, DavResource::Calendar { .. }) => [
+            "calendar-query",
+            "calendar-multiget",
+        ]
+        .iter()
+        .map(|report| {
+            format!("<d:supported-report><d:report><c:{report}/></d:report></d:supported-report>")
+        })
+        .collect(),
+        (CalendarDescription, DavResource::Calendar { calendar, .. }) => {
+            escape_xml(calendar.description.as_deref()?)
+        }
+        (SupportedCalendarComponentSet, DavResource::Calendar { .. }) => {
+            r#"<c:comp name="VEVENT"/><c:comp name="VTODO"/>"#.to_string()
+        }
+        (GetCtag, DavResource::Calendar { ctag, .. }) => escape_xml((*ctag)?),
+        (CalendarColor, DavResource::Calendar { calendar, .. }) => escape_xml(&calendar.color),
+        (GetEtag, DavResource::Entry { etag, .. }) => escape_xml(etag),
+        (GetContentType, DavResource::Entry { entry, .. }) => format!(
+            "text/calendar; charset=utf-8; component={}",
+            entry_component(entry)
+        ),
+        (CalendarData, DavResource::Entry { data, .. }) => escape_xml((*data)?),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Describes `resource` at `href` with the properties `request` asks for.
+pub fn resource_response(
+    href: impl Into<String>,
+    resource: &DavResource,
+    user: &User,
+    request: &PropfindRequest,
+) -> DavResponse {
+    let mut response = DavResponse::new(href);
+    match request {
+        PropfindRequest::PropName => {
+            response.found = resource_properties(resource)
+                .into_iter()
+                .map(|property| (property, String::new()))
+                .collect();
+        }
+        PropfindRequest::AllProp => {
+            response.found = resource_properties(resource)
+                .into_iter()
+                .filter_map(|property| {
+                    let value = property_value(resource, user, &property)?;
+                    Some((property, value))
+                })
+                .collect();
+        }
+        PropfindRequest::Prop(properties) => {
+            for property in properties {
+                match property_value(resource, user, property) {
+                    Some(value) => response.found.push((property.clone(), value)),
+                    None => response.missing.push(property.clone()),
+                }
+            }
+        }
+    }
+    response
+}
+
+fn href(path: &str) -> String {
+    format!("<d:href>{}</d:href>", escape_xml(path))
+}
+
+/// Lists what the user may do in a calendar, so clients show read-only
+/// calendars as such.
+fn privilege_set(calendar: &Calendar, role: CalendarRole) -> String {
+    let mut privileges = vec!["read"];
+    if role.can_write() && !calendar.is_read_only() {
+        privileges.extend(["write", "write-content", "bind", "unbind"]);
+    }
+    privileges
+        .iter()
+        .map(|p| format!("<d:privilege><d:{p}/></d:privilege>"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::CalendarSubscription;
+
+    #[test]
+    fn test_property_names_round_trip() {
+        for (property, namespace, name) in KNOWN {
+            assert_eq!(&DavProperty::from_name(namespace, name), property);
+            assert_eq!(property.qualified_name(), (*namespace, *name));
+        }
+        let unknown = DavProperty::from_name("urn:x", "quota");
+        assert_eq!(unknown.qualified_name(), ("urn:x", "quota"));
+    }
+
+    #[test]
+    fn test_calendar_properties() {
+        let user = User::new("Ada", "ada@example.com");
+        let calendar = Calendar::new("Work & Play", "#3B82F6");
+        let resource = DavResource::Calendar {
+            calendar: &calendar,
+            role: CalendarRole::Writer,
+            ctag: Some("abc"),
+        };
+
+        let value = |p| property_value(&resource, &user, &p);
+        assert_eq!(
+            value(DavProperty::ResourceType).unwrap(),
+            "<d:collection/><c:calendar/>"
+        );
+        assert_eq!(value(DavProperty::DisplayName).unwrap(), "Work &amp; Play");
+        assert_eq!(value(DavProperty::GetCtag).unwrap(), "abc");
+        assert_eq!(value(DavProperty::CalendarColor).unwrap(), "#3B82F6");
+        assert!(value(DavProperty::CurrentUserPrivilegeSet)
+            .unwrap()
+            .contains("<d:write/>"));
+        assert_eq!(value(DavProperty::CalendarDescription), None);
+        assert_eq!(value(DavProperty::CalendarData), None);
+        assert_eq!(
+            value(DavProperty::CurrentUserPrincipal).unwrap(),
+            format!("<d:href>/dav/principals/{}/</d:href>", user.id)
+        );
+    }
+
+    #[test]
+    fn test_read_only_calendars_grant_read_only() {
+        let user = User::new("Ada", "ada@example.com");
+        let mut calendar = Calendar::new("Holidays", "#3B82F6");
+        let reader = DavResource::Calendar {
+            calendar: &calendar,
+            role: CalendarRole::Reader,
+            ctag: None,
+        };
+        let privileges = property_value(&reader, &user, &DavProperty::CurrentUserPrivilegeSet);
+        assert_eq!(privileges.unwrap(), "<d:privilege><d:read/></d:privilege>");
+
+        calendar.subscription = Some(CalendarSubscription::new("https://example.com/h.ics"));
+        let subscribed = DavResource::Calendar {
+            calendar: &calendar,
+            role: CalendarRole::Owner,
+            ctag: None,
+        };
+        let privileges =
+            property_value(&subscribed, &user, &DavProperty::CurrentUserPrivilegeSet).unwrap();
+        assert!(!privileges.contains("write"));
+    }
+
+    #[test]
+    fn test_resource_response() {
+        let user = User::new("Ada", "ada@example.com");
+        let entry = CalendarEntry::all_day(
+            uuid::Uuid::new_v4(),
+            "Standup",
+            chrono::NaiveDate::from_ymd_opt(2025, 1, 6).unwrap(),
+        );
+        let resource = DavResource::Entry {
+            entry: &entry,
+            etag: "\"1\"",
+            data: None,
+        };
+
+        let request = PropfindRequest::Prop(vec![DavProperty::GetEtag, DavProperty::CalendarData]);
+        let response = resource_response("/dav/a.ics", &resource, &user, &request);
+        assert_eq!(
+            response.found,
+            [(DavProperty::GetEtag, "&quot;1&quot;".to_string())]
+        );
+        assert_eq!(response.missing, [DavProperty::CalendarData]);
+
+        let all = resource_response("/dav/a.ics", &resource, &user, &PropfindRequest::AllProp);
+        assert_eq!(all.found.len(), resource_properties(&resource).len());
+        assert!(all.missing.is_empty());
+
+        let names = resource_response("/dav/a.ics", &resource, &user, &PropfindRequest::PropName);
+        assert!(names.found.iter().all(|(_, value)| value.is_empty()));
+    }
+
+    #[test]
+    fn test_principal_properties() {
+        let user = User::new("Ada", "ada@example.com");
+        let value = |p| property_value(&DavResource::Principal, &user, &p);
+
+        assert_eq!(
+            value(DavProperty::CalendarHomeSet).unwrap(),
+            "<d:href>/dav/calendars/</d:href>"
+        );
+        assert_eq!(
+            value(DavProperty::CalendarUserAddressSet).unwrap(),
+            "<d:href>mailto:ada@example.com</d:href>"
+        );
+        assert_eq!(value(DavProperty::GetCtag), None);
+        assert!(resource_properties(&DavResource::Principal)
+            .iter()
+            .all(|p| value(p.clone()).is_some()));
+    }
+}
@@ -0,0 +1,333 @@
+//! Decoding PROPFIND and REPORT request bodies.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeDelta, Utc};
+
+use super::error::CalDavError;
+use super::props::DavProperty;
+use super::xml::{parse_xml, XmlElement};
+use super::{CALDAV_NS, DAV_NS};
+
+/// How deep a PROPFIND reaches below the requested resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Depth {
+    Zero,
+    One,
+    Infinity,
+}
+
+impl Depth {
+    /// Reads a `Depth` header; a missing or unknown value means infinity.
+    ///
+    /// Collections here are at most one level deep, so infinity reaches as
+    /// far as one.
+    pub fn from_header(value: Option<&str>) -> Self {
+        match value.map(str::trim) {
+            Some("0") => Self::Zero,
+            Some("1") => Self::One,
+            _ => Self::Infinity,
+        }
+    }
+
+    /// Returns whether the request covers the resource's members.
+    pub fn includes_members(self) -> bool {
+        self != Self::Zero
+    }
+}
+
+/// What a PROPFIND asks for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PropfindRequest {
+    /// Every property with its value.
+    AllProp,
+    /// The names of every property, without values.
+    PropName,
+    /// The listed properties.
+    Prop(Vec<DavProperty>),
+}
+
+impl PropfindRequest {
+    /// Returns whether the response should include `property`.
+    pub fn wants(&self, property: &DavProperty) -> bool {
+        match self {
+            Self::AllProp => *property != DavProperty::CalendarData,
+            Self::PropName => false,
+            Self::Prop(properties) => properties.contains(property),
+        }
+    }
+}
+
+/// A date-time window from a `time-range` filter. Either end may be open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeRange {
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+}
+
+impl TimeRange {
+    /// Returns the first and last date the window touches, or `None` when
+    /// either end is open.
+    pub fn dates(&self) -> Option<(NaiveDate, NaiveDate)> {
+        let (start, end) = (self.start?, self.end?);
+        // The end is exclusive
+        let last = (end - TimeDelta::seconds(1)).max(start);
+        Some((start.date_naive(), last.date_naive()))
+    }
+}
+
+/// A REPORT this server answers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReportRequest {
+    /// Entries of a calendar, optionally of one component type and in a window.
+    CalendarQuery {
+        properties: Vec<DavProperty>,
+        /// `VEVENT` or `VTODO` when the filter names one.
+        component: Option<String>,
+        time_range: Option<TimeRange>,
+    },
+    /// Entries at the listed hrefs.
+    CalendarMultiget {
+        properties: Vec<DavProperty>,
+        hrefs: Vec<String>,
+    },
+}
+
+impl ReportRequest {
+    /// Returns the properties to report on each resource.
+    pub fn properties(&self) -> &[DavProperty] {
+        match self {
+            Self::CalendarQuery { properties, .. } | Self::CalendarMultiget { properties, .. } => {
+                properties
+            }
+        }
+    }
+}
+
+/// Parses a PROPFIND body. An empty body asks for all properties.
+pub fn parse_propfind(body: &str) -> Result<PropfindRequest, CalDavError> {
+    if body.trim().is_empty() {
+        return Ok(PropfindRequest::AllProp);
+    }
+    let root = parse_xml(body)?;
+    if !root.is(DAV_NS, "propfind") {
+        return Err(CalDavError::UnexpectedElement("DAV:propfind".to_string()));
+    }
+    if let Some(prop) = root.child(DAV_NS, "prop") {
+        Ok(PropfindRequest::Prop(property_list(prop)))
+    } else if root.child(DAV_NS, "propname").is_some() {
+        Ok(PropfindRequest::PropName)
+    } else {
+        Ok(PropfindRequest::AllProp)
+    }
+}
+
+/// Parses a REPORT body.
+pub fn parse_report(body: &str) -> Result<ReportRequest, CalDavError> {
+    let root = parse_xml(body)?;
+    let properties = root
+        .child(DAV_NS, "prop")
+        .map(property_list)
+        .unwrap_or_else(|| vec![DavProperty::GetEtag]);
+
+    if root.is(CALDAV_NS, "calendar-multiget") {
+        let hrefs = root
+            .children
+            .iter()
+            .filter(|c| c.is(DAV_NS, "href"))
+            .map(|c| c.text.trim().to_string())
+            .collect();
+        return Ok(ReportRequest::CalendarMultiget { properties, hrefs });
+    }
+    if !root.is(CALDAV_NS, "calendar-query") {
+        return Err(CalDavError::UnsupportedReport(format!(
+            "{}{}",
+            root.namespace, root.name
+        )));
+    }
+
+    // filter > comp-filter VCALENDAR > comp-filter VEVENT|VTODO > time-range
+    let component_filter = root
+        .child(CALDAV_NS, "filter")
+        .and_then(|filter| filter.child(CALDAV_NS, "comp-filter"))
+        .and_then(|calendar| calendar.child(CALDAV_NS, "comp-filter"));
+    let component = component_filter
+        .and_then(|filter| filter.attribute("name"))
+        .map(str::to_ascii_uppercase);
+    let time_range = component_filter
+        .and_then(|filter| filter.child(CALDAV_NS, "time-range"))
+        .map(|range| {
+            Ok::<_, CalDavError>(TimeRange {
+                start: range.attribute("start").map(parse_utc).transpose()?,
+                end: range.attribute("end").map(parse_utc).transpose()?,
+            })
+        })
+        .transpose()?;
+
+    Ok(ReportRequest::CalendarQuery {
+        properties,
+        component,
+        time_range,
+    })
+}
+
+fn property_list(prop: &XmlElement) -> Vec<DavProperty> {
+    prop.children
+        .iter()
+        .map(|c| DavProperty::from_name(&c.namespace, &c.name))
+        .collect()
+}
+
+/// Parses a UTC date-time in iCalendar form, e.g. `20240101T000000Z`.
+fn parse_utc(value: &str) -> Result<DateTime<Utc>, CalDavError> {
+    NaiveDateTime::parse_from_str(value.trim(), "%Y%m%dT%H%M%SZ")
+        .map(|naive| naive.and_utc())
+        .map_err(|_| CalDavError::InvalidTimeRange(value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_propfind() {
+        assert_eq!(parse_propfind("").unwrap(), PropfindRequest::AllProp);
+        assert_eq!(
+            parse_propfind(r#"<propfind xmlns="DAV:"><allprop/></propfind>"#).unwrap(),
+            PropfindRequest::AllProp
+        );
+        assert_eq!(
+            parse_propfind(r#"<propfind xmlns="DAV:"><propname/></propfind>"#).unwrap(),
+            PropfindRequest::PropName
+        );
+
+        let request = parse_propfind(
+            r#"<d:propfind xmlns:d="DAV:" xmlns:cs="http://calendarserver.org/ns/">
+                 <d:prop><d:getetag/><cs:getctag/><d:quota-used-bytes/></d:prop>
+               </d:propfind>"#,
+        )
+        .unwrap();
+        assert!(request.wants(&DavProperty::GetCtag));
+        assert!(!request.wants(&DavProperty::DisplayName));
+        assert_eq!(
+            request,
+            PropfindRequest::Prop(vec![
+                DavProperty::GetEtag,
+                DavProperty::GetCtag,
+                DavProperty::Unknown {
+                    namespace: "DAV:".to_string(),
+                    name: "quota-used-bytes".to_string(),
+                },
+            ])
+        );
+
+        assert_eq!(
+            parse_propfind(r#"<prop xmlns="DAV:"/>"#),
+            Err(CalDavError::UnexpectedElement("DAV:propfind".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_calendar_query() {
+        let request = parse_report(
+            r#"<?xml version="1.0" encoding="utf-8" ?>
+            <C:calendar-query xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav">
+              <D:prop><D:getetag/><C:calendar-data/></D:prop>
+              <C:filter>
+                <C:comp-filter name="VCALENDAR">
+                  <C:comp-filter name="VEVENT">
+                    <C:time-range start="20240101T000000Z" end="20240201T000000Z"/>
+                  </C:comp-filter>
+                </C:comp-filter>
+              </C:filter>
+            </C:calendar-query>"#,
+        )
+        .unwrap();
+
+        let ReportRequest::CalendarQuery {
+            properties,
+            component,
+            time_range,
+        } = request
+        else {
+            panic!("expected a calendar-query");
+        };
+        assert_eq!(
+            properties,
+            [DavProperty::GetEtag, DavProperty::CalendarData]
+        );
+        assert_eq!(component.as_deref(), Some("VEVENT"));
+        let dates = time_range.unwrap().dates().unwrap();
+        assert_eq!(
+            dates,
+            (
+                NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2024, 1, 31).unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_calendar_query_open_range() {
+        let request = parse_report(
+            r#"<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+              <d:prop><d:getetag/></d:prop>
+              <c:filter><c:comp-filter name="VCALENDAR"><c:comp-filter name="VTODO">
+                <c:time-range start="20240301T120000Z"/>
+              </c:comp-filter></c:comp-filter></c:filter>
+            </c:calendar-query>"#,
+        )
+        .unwrap();
+        let ReportRequest::CalendarQuery { time_range, .. } = request else {
+            panic!("expected a calendar-query");
+        };
+        assert_eq!(time_range.unwrap().dates(), None);
+
+        let invalid = parse_report(
+            r#"<c:calendar-query xmlns:c="urn:ietf:params:xml:ns:caldav">
+              <c:filter><c:comp-filter name="VCALENDAR"><c:comp-filter name="VEVENT">
+                <c:time-range start="2024-03-01"/>
+              </c:comp-filter></c:comp-filter></c:filter>
+            </c:calendar-query>"#,
+        );
+        assert_eq!(
+            invalid,
+            Err(CalDavError::InvalidTimeRange("2024-03-01".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_calendar_multiget() {
+        let request = parse_report(
+            r#"<c:calendar-multiget xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+              <d:prop><d:getetag/><c:calendar-data/></d:prop>
+              <d:href>/dav/calendars/a/1.ics</d:href>
+              <d:href> /dav/calendars/a/2.ics </d:href>
+            </c:calendar-multiget>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            request,
+            ReportRequest::CalendarMultiget {
+                properties: vec![DavProperty::GetEtag, DavProperty::CalendarData],
+                hrefs: vec![
+                    "/dav/calendars/a/1.ics".to_string(),
+                    "/dav/calendars/a/2.ics".to_string()
+                ],
+            }
+        );
+
+        assert!(matches!(
+            parse_report(r#"<sync-collection xmlns="DAV:"/>"#),
+            Err(CalDavError::UnsupportedReport(_))
+        ));
+    }
+
+    #[test]
+    fn test_depth_from_header() {
+        assert_eq!(Depth::from_header(Some("0")), Depth::Zero);
+        assert_eq!(Depth::from_header(Some(" 1")), Depth::One);
+        assert_eq!(Depth::from_header(Some("infinity")), Depth::Infinity);
+        assert_eq!(Depth::from_header(None), Depth::Infinity);
+        assert!(!Depth::Zero.includes_members());
+        assert!(Depth::Infinity.includes_members());
+    }
+}
@@ -0,0 +1,388 @@
+//! CalDAV paths, entity tags and writes of calendar resources.
+//!
+//! Each entry is served as one iCalendar resource at
+//! `/dav/calendars/<calendar>/<entry>.ics`, holding the series and its
+//! replaced occurrences. Clients may name new resources however they like:
+//! a name that is a UUID becomes the entry's ID, anything else maps to a
+//! fixed ID the same way imported UIDs do.
+
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+use super::error::CalDavError;
+use crate::calendar::{
+    changed_fields, format_etag, import_entry_id, reimport_entry, Calendar, CalendarEntry,
+    EntryKind, EntryWrite, IcsImport, ImportedEntry, OccurrenceOverride,
+};
+
+/// Path of the CalDAV entry point.
+pub const DAV_ROOT_PATH: &str = "/dav/";
+
+/// Path of the collection holding the user's calendars.
+pub const CALENDAR_HOME_PATH: &str = "/dav/calendars/";
+
+/// Returns the path of a user's principal.
+pub fn principal_path(user_id: Uuid) -> String {
+    format!("/dav/principals/{user_id}/")
+}
+
+/// Returns the path of a calendar collection.
+pub fn calendar_path(calendar_id: Uuid) -> String {
+    format!("{CALENDAR_HOME_PATH}{calendar_id}/")
+}
+
+/// Returns the path an entry is served at.
+pub fn resource_path(calendar_id: Uuid, entry_id: Uuid) -> String {
+    format!("{CALENDAR_HOME_PATH}{calendar_id}/{entry_id}.ics")
+}
+
+/// Returns the ID of the entry stored at resource `name` of a calendar, or
+/// `None` if `name` isn't an `.ics` file name.
+pub fn resource_entry_id(calendar_id: Uuid, name: &str) -> Option<Uuid> {
+    let stem = name.strip_suffix(".ics")?;
+    if stem.is_empty() || stem.contains('/') {
+        return None;
+    }
+    Some(
+        stem.parse()
+            .unwrap_or_else(|_| import_entry_id(calendar_id, stem)),
+    )
+}
+
+/// Reads the calendar and entry IDs out of a resource href, which may be an
+/// absolute URL and may be percent-encoded.
+pub fn parse_resource_href(href: &str) -> Option<(Uuid, Uuid)> {
+    let path = match href.split_once("://") {
+        Some((_, rest)) => &rest[rest.find('/')?..],
+        None => href,
+    };
+    let path = percent_decode(path)?;
+    let (calendar, name) = path.strip_prefix(CALENDAR_HOME_PATH)?.split_once('/')?;
+    let calendar_id = calendar.parse().ok()?;
+    Some((calendar_id, resource_entry_id(calendar_id, name)?))
+}
+
+fn percent_decode(value: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        if b == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(b);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Returns the iCalendar component an entry is served as.
+pub fn entry_component(entry: &CalendarEntry) -> &'static str {
+    match entry.kind {
+        EntryKind::Task { .. } => "VTODO",
+        _ => "VEVENT",
+    }
+}
+
+/// Returns the `ETag` of an entry's resource.
+///
+/// Overrides are stored apart from the series and don't bump its version, so
+/// a recurring entry with overrides gets a tag that also covers them.
+pub fn resource_etag(entry: &CalendarEntry, overrides: &[OccurrenceOverride]) -> String {
+    if overrides.is_empty() {
+        return format_etag(entry.version);
+    }
+    let mut overrides: Vec<&OccurrenceOverride> = overrides.iter().collect();
+    overrides.sort_by_key(|o| o.original_date);
+    let hash = overrides.iter().fold(FNV_OFFSET, |hash, o| {
+        let replacement = serde_json::to_string(&o.replacement).unwrap_or_default();
+        let hash = fnv1a(hash, o.original_date.to_string().as_bytes());
+        fnv1a(hash, replacement.as_bytes())
+    });
+    format!("\"{}-{hash:016x}\"", entry.version)
+}
+
+/// Returns the ctag of a calendar collection: a tag that changes whenever
+/// the calendar or any of its resources does, so clients skip unchanged
+/// calendars without listing them.
+///
+/// `etags` are the calendar's resources with their `ETag`s, in any order.
+pub fn collection_ctag(calendar: &Calendar, etags: &[(Uuid, String)]) -> String {
+    let mut etags: Vec<&(Uuid, String)> = etags.iter().collect();
+    etags.sort();
+    let hash = etags.iter().fold(
+        fnv1a(FNV_OFFSET, &calendar.version.to_le_bytes()),
+        |hash, (id, etag)| fnv1a(fnv1a(hash, id.as_bytes()), etag.as_bytes()),
+    );
+    format!("{hash:016x}")
+}
+
+/// Returns whether an `If-Match` or `If-None-Match` header value names
+/// `etag`. `*` matches any tag and weak tags never match.
+pub fn etag_matches(header: &str, etag: &str) -> bool {
+    header
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag == etag)
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// The content of a calendar resource a client wrote: one event or task,
+/// with any occurrences of it the client replaced.
+#[derive(Debug, Clone)]
+pub struct ResourceObject {
+    pub series: ImportedEntry,
+    pub occurrences: Vec<ImportedEntry>,
+}
+
+impl ResourceObject {
+    /// Returns the overrides the resource gives the entry stored as
+    /// `entry_id`: its EXDATEs and replaced occurrences.
+    pub fn overrides(&self, entry_id: Uuid) -> Vec<OccurrenceOverride> {
+        let cancelled = self
+            .series
+            .exdates
+            .iter()
+            .map(|date| OccurrenceOverride::cancelled(entry_id, *date));
+        let replaced = self.occurrences.iter().map(|imported| {
+            let original_date: NaiveDate = imported
+                .recurrence_id
+                .expect("occurrences have a RECURRENCE-ID");
+            OccurrenceOverride::replaced(entry_id, original_date, imported.to_entry(entry_id))
+        });
+        cancelled.chain(replaced).collect()
+    }
+}
+
+/// Checks that a parsed resource holds exactly one event or task.
+pub fn resource_object(import: IcsImport) -> Result<ResourceObject, CalDavError> {
+    let (mut series, occurrences): (Vec<_>, Vec<_>) = import
+        .entries
+        .into_iter()
+        .partition(|imported| imported.recurrence_id.is_none());
+
+    if series.is_empty() {
+        return Err(match import.skipped.into_iter().next() {
+            Some(skipped) => CalDavError::InvalidCalendarData(skipped.reason),
+            None => CalDavError::ComponentCount,
+        });
+    }
+    if series.len() > 1 {
+        return Err(CalDavError::ComponentCount);
+    }
+    let series = series.remove(0);
+    if occurrences.iter().any(|o| o.uid != series.uid) {
+        return Err(CalDavError::MixedUids);
+    }
+    Ok(ResourceObject {
+        series,
+        occurrences,
+    })
+}
+
+/// Plans the write that stores `entry` over what is stored at its ID.
+///
+/// What iCalendar doesn't carry is kept from `stored`, and `None` is
+/// returned when nothing changed so re-uploading a resource writes nothing.
+pub fn plan_resource_put(
+    entry: CalendarEntry,
+    stored: Option<&CalendarEntry>,
+) -> Option<EntryWrite> {
+    let Some(stored) = stored else {
+        return Some(EntryWrite::Create(entry));
+    };
+    let entry = reimport_entry(entry, stored);
+    if changed_fields(stored, &entry).is_empty() {
+        return None;
+    }
+    Some(EntryWrite::Update {
+        expected_version: stored.version,
+        entry,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::parse_ics;
+
+    const SERIES: &str = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\nUID:standup\r\nDTSTART;VALUE=DATE:20250106\r\nRRULE:FREQ=WEEKLY\r\n\
+EXDATE;VALUE=DATE:20250113\r\nSUMMARY:Standup\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nUID:standup\r\nRECURRENCE-ID;VALUE=DATE:20250120\r\n\
+DTSTART;VALUE=DATE:20250121\r\nSUMMARY:Standup (moved)\r\nEND:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_resource_entry_id() {
+        let calendar_id = Uuid::new_v4();
+        let entry_id = Uuid::new_v4();
+
+        assert_eq!(
+            resource_entry_id(calendar_id, &format!("{entry_id}.ics")),
+            Some(entry_id)
+        );
+        assert_eq!(
+            resource_entry_id(calendar_id, "abc@example.com.ics"),
+            Some(import_entry_id(calendar_id, "abc@example.com"))
+        );
+        assert_eq!(resource_entry_id(calendar_id, "abc"), None);
+        assert_eq!(resource_entry_id(calendar_id, ".ics"), None);
+    }
+
+    #[test]
+    fn test_parse_resource_href() {
+        let (calendar_id, entry_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let path = resource_path(calendar_id, entry_id);
+
+        assert_eq!(parse_resource_href(&path), Some((calendar_id, entry_id)));
+        assert_eq!(
+            parse_resource_href(&format!("https://cal.example.com{path}")),
+            Some((calendar_id, entry_id))
+        );
+        assert_eq!(
+            parse_resource_href(&format!("/dav/calendars/{calendar_id}/a%40b.ics")),
+            Some((calendar_id, import_entry_id(calendar_id, "a@b")))
+        );
+        assert_eq!(parse_resource_href(&calendar_path(calendar_id)), None);
+        assert_eq!(parse_resource_href("/dav/calendars/nope/a.ics"), None);
+        assert_eq!(parse_resource_href("/api/entries/a.ics"), None);
+    }
+
+    #[test]
+    fn test_resource_etag_covers_overrides() {
+        let entry = CalendarEntry::all_day(Uuid::new_v4(), "Standup", date(2025, 1, 6));
+        assert_eq!(resource_etag(&entry, &[]), "\"1\"");
+
+        let cancelled = OccurrenceOverride::cancelled(entry.id, date(2025, 1, 13));
+        let moved = OccurrenceOverride::replaced(
+            entry.id,
+            date(2025, 1, 20),
+            entry.clone().with_location("Room 2"),
+        );
+        let etag = resource_etag(&entry, &[cancelled.clone(), moved.clone()]);
+        assert!(etag.starts_with("\"1-"));
+        assert_eq!(
+            etag,
+            resource_etag(&entry, &[moved.clone(), cancelled.clone()])
+        );
+        assert_ne!(etag, resource_etag(&entry, &[cancelled]));
+
+        let mut relocated = moved;
+        relocated.replacement = Some(entry.clone().with_location("Room 3"));
+        assert_ne!(etag, resource_etag(&entry, &[relocated]));
+    }
+
+    #[test]
+    fn test_collection_ctag() {
+        let mut calendar = Calendar::new("Work", "#3B82F6");
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let etags = vec![(a, "\"1\"".to_string()), (b, "\"2\"".to_string())];
+        let ctag = collection_ctag(&calendar, &etags);
+
+        let reversed: Vec<_> = etags.iter().rev().cloned().collect();
+        assert_eq!(ctag, collection_ctag(&calendar, &reversed));
+        assert_ne!(ctag, collection_ctag(&calendar, &etags[..1]));
+        let bumped = vec![(a, "\"1\"".to_string()), (b, "\"3\"".to_string())];
+        assert_ne!(ctag, collection_ctag(&calendar, &bumped));
+
+        calendar.version += 1;
+        assert_ne!(ctag, collection_ctag(&calendar, &etags));
+    }
+
+    #[test]
+    fn test_etag_matches() {
+        assert!(etag_matches("\"2-ab\"", "\"2-ab\""));
+        assert!(etag_matches("\"1\", \"2-ab\"", "\"2-ab\""));
+        assert!(etag_matches("*", "\"2-ab\""));
+        assert!(!etag_matches("W/\"2-ab\"", "\"2-ab\""));
+        assert!(!etag_matches("\"2\"", "\"2-ab\""));
+    }
+
+    #[test]
+    fn test_resource_object() {
+        let calendar_id = Uuid::new_v4();
+        let object = resource_object(parse_ics(SERIES, calendar_id).unwrap()).unwrap();
+        assert_eq!(object.series.uid, "standup");
+
+        let entry_id = Uuid::new_v4();
+        let overrides = object.overrides(entry_id);
+        assert_eq!(overrides.len(), 2);
+        assert_eq!(
+            overrides[0],
+            OccurrenceOverride::cancelled(entry_id, date(2025, 1, 13))
+        );
+        let moved = overrides[1].replacement.as_ref().unwrap();
+        assert_eq!(moved.id, entry_id);
+        assert_eq!(moved.start_date, date(2025, 1, 21));
+    }
+
+    #[test]
+    fn test_resource_object_rejects_other_shapes() {
+        let calendar_id = Uuid::new_v4();
+        let parse = |ics: &str| resource_object(parse_ics(ics, calendar_id).unwrap());
+
+        let two = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\nUID:a\r\nDTSTART;VALUE=DATE:20250101\r\nSUMMARY:A\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nUID:b\r\nDTSTART;VALUE=DATE:20250102\r\nSUMMARY:B\r\nEND:VEVENT\r\n\
+END:VCALENDAR\r\n";
+        assert_eq!(parse(two).unwrap_err(), CalDavError::ComponentCount);
+        assert_eq!(
+            parse("BEGIN:VCALENDAR\r\nEND:VCALENDAR\r\n").unwrap_err(),
+            CalDavError::ComponentCount
+        );
+
+        let mixed = SERIES.replace("UID:standup\r\nRECURRENCE-ID", "UID:other\r\nRECURRENCE-ID");
+        assert_eq!(parse(&mixed).unwrap_err(), CalDavError::MixedUids);
+
+        let untitled = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\nUID:a\r\nSUMMARY:No start\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+        assert!(matches!(
+            parse(untitled).unwrap_err(),
+            CalDavError::InvalidCalendarData(_)
+        ));
+    }
+
+    #[test]
+    fn test_plan_resource_put() {
+        let calendar_id = Uuid::new_v4();
+        let entry = CalendarEntry::all_day(calendar_id, "Standup", date(2025, 1, 6));
+        assert!(matches!(
+            plan_resource_put(entry.clone(), None),
+            Some(EntryWrite::Create(_))
+        ));
+
+        let mut stored = entry.clone().with_color("#F97316");
+        stored.version = 4;
+        assert!(plan_resource_put(entry.clone(), Some(&stored)).is_none());
+
+        let renamed = CalendarEntry {
+            title: "Daily standup".to_string(),
+            ..entry
+        };
+        let Some(EntryWrite::Update {
+            expected_version,
+            entry,
+        }) = plan_resource_put(renamed, Some(&stored))
+        else {
+            panic!("expected an update");
+        };
+        assert_eq!(expected_version, 4);
+        assert_eq!(entry.title, "Daily standup");
+        assert_eq!(entry.color.as_deref(), Some("#F97316"));
+    }
+}
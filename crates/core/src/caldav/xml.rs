@@ -0,0 +1,354 @@
+//! A small namespace-aware XML reader for WebDAV request bodies.
+//!
+//! Request bodies are short documents of nested elements, so they are read
+//! into a tree in one pass. Element names are resolved against `xmlns`
+//! declarations; processing instructions, comments and the DOCTYPE are
+//! skipped, and entity and character references are decoded.
+
+use super::error::CalDavError;
+
+/// An element with its namespace resolved.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct XmlElement {
+    /// Namespace URI, empty when the element has none.
+    pub namespace: String,
+    /// Local name, without the prefix.
+    pub name: String,
+    /// Attributes other than namespace declarations, by local name.
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<XmlElement>,
+    /// Character data directly inside the element, concatenated.
+    pub text: String,
+}
+
+impl XmlElement {
+    /// Returns whether this is element `name` in `namespace`.
+    pub fn is(&self, namespace: &str, name: &str) -> bool {
+        self.namespace == namespace && self.name == name
+    }
+
+    /// Returns the first child element `name` in `namespace`.
+    pub fn child(&self, namespace: &str, name: &str) -> Option<&XmlElement> {
+        self.children.iter().find(|c| c.is(namespace, name))
+    }
+
+    /// Returns the value of attribute `name`.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Parses an XML document into its root element.
+pub fn parse_xml(input: &str) -> Result<XmlElement, CalDavError> {
+    let mut reader = Reader { input, pos: 0 };
+    reader.skip_misc()?;
+    if !reader.rest().starts_with('<') {
+        return Err(invalid("expected a root element"));
+    }
+    let root = reader.element(&[])?;
+    reader.skip_misc()?;
+    if !reader.rest().is_empty() {
+        return Err(invalid("content after the root element"));
+    }
+    Ok(root)
+}
+
+/// Escapes text for use in element content or attribute values.
+pub fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Kept as a reference so iCalendar data keeps its CRLF line ends
+            '\r' => escaped.push_str("&#13;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn invalid(message: impl Into<String>) -> CalDavError {
+    CalDavError::InvalidXml(message.into())
+}
+
+/// Namespace prefixes in scope, innermost last. The default namespace has an
+/// empty prefix.
+type Scope = Vec<(String, String)>;
+
+struct Reader<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        let found = self.rest().starts_with(token);
+        if found {
+            self.pos += token.len();
+        }
+        found
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// Returns everything up to `end` and moves past it.
+    fn until(&mut self, end: &str) -> Result<&'a str, CalDavError> {
+        let rest = self.rest();
+        let at = rest
+            .find(end)
+            .ok_or_else(|| invalid(format!("missing {end}")))?;
+        self.pos += at + end.len();
+        Ok(&rest[..at])
+    }
+
+    /// Skips whitespace, processing instructions, comments and the DOCTYPE
+    /// around the root element.
+    fn skip_misc(&mut self) -> Result<(), CalDavError> {
+        loop {
+            self.skip_whitespace();
+            if self.eat("<?") {
+                self.until("?>")?;
+            } else if self.eat("<!--") {
+                self.until("-->")?;
+            } else if self.eat("<!DOCTYPE") {
+                self.until(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<&'a str, CalDavError> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '/' | '>' | '=' | '<'))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(invalid("expected a name"));
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    fn quoted(&mut self) -> Result<String, CalDavError> {
+        let quote = match self.rest().chars().next() {
+            Some(q @ ('"' | '\'')) => q,
+            _ => return Err(invalid("expected a quoted attribute value")),
+        };
+        self.pos += 1;
+        let raw = self.until(&quote.to_string())?;
+        decode_references(raw)
+    }
+
+    /// Reads an element starting at `<`.
+    fn element(&mut self, scope: &[(String, String)]) -> Result<XmlElement, CalDavError> {
+        self.eat("<");
+        let qname = self.name()?;
+
+        let mut raw_attributes = Vec::new();
+        let empty = loop {
+            self.skip_whitespace();
+            if self.eat("/>") {
+                break true;
+            }
+            if self.eat(">") {
+                break false;
+            }
+            let name = self.name()?;
+            self.skip_whitespace();
+            if !self.eat("=") {
+                return Err(invalid(format!("attribute {name} has no value")));
+            }
+            self.skip_whitespace();
+            raw_attributes.push((name, self.quoted()?));
+        };
+
+        let mut scope: Scope = scope.to_vec();
+        let mut attributes = Vec::new();
+        for (name, value) in raw_attributes {
+            if name == "xmlns" {
+                scope.push((String::new(), value));
+            } else if let Some(prefix) = name.strip_prefix("xmlns:") {
+                scope.push((prefix.to_string(), value));
+            } else {
+                let local = name.split_once(':').map_or(name, |(_, local)| local);
+                attributes.push((local.to_string(), value));
+            }
+        }
+
+        let (prefix, name) = qname.split_once(':').unwrap_or(("", qname));
+        let namespace = match scope.iter().rev().find(|(p, _)| p == prefix) {
+            Some((_, uri)) => uri.clone(),
+            None if prefix.is_empty() => String::new(),
+            None => return Err(invalid(format!("undeclared prefix {prefix}"))),
+        };
+        let mut element = XmlElement {
+            namespace,
+            name: name.to_string(),
+            attributes,
+            ..XmlElement::default()
+        };
+        if empty {
+            return Ok(element);
+        }
+
+        loop {
+            if self.eat("</") {
+                let closing = self.name()?;
+                if closing != qname {
+                    return Err(invalid(format!("</{closing}> closes <{qname}>")));
+                }
+                self.skip_whitespace();
+                if !self.eat(">") {
+                    return Err(invalid(format!("unterminated </{closing}")));
+                }
+                return Ok(element);
+            } else if self.eat("<!--") {
+                self.until("-->")?;
+            } else if self.eat("<![CDATA[") {
+                element.text.push_str(self.until("]]>")?);
+            } else if self.eat("<?") {
+                self.until("?>")?;
+            } else if self.rest().starts_with('<') {
+                element.children.push(self.element(&scope)?);
+            } else if self.rest().is_empty() {
+                return Err(invalid(format!("missing </{qname}>")));
+            } else {
+                let rest = self.rest();
+                let len = rest.find('<').unwrap_or(rest.len());
+                self.pos += len;
+                element.text.push_str(&decode_references(&rest[..len])?);
+            }
+        }
+    }
+}
+
+/// Decodes the predefined entities and character references.
+fn decode_references(raw: &str) -> Result<String, CalDavError> {
+    let mut text = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(at) = rest.find('&') {
+        text.push_str(&rest[..at]);
+        let end = rest[at..]
+            .find(';')
+            .ok_or_else(|| invalid("unterminated reference"))?;
+        let reference = &rest[at + 1..at + end];
+        let decoded = match reference {
+            "lt" => '<',
+            "gt" => '>',
+            "amp" => '&',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => reference
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| reference.strip_prefix('#').map(str::parse))
+                .and_then(Result::ok)
+                .and_then(char::from_u32)
+                .ok_or_else(|| invalid(format!("unknown reference &{reference};")))?,
+        };
+        text.push(decoded);
+        rest = &rest[at + end + 1..];
+    }
+    text.push_str(rest);
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_xml_resolves_namespaces() {
+        let root = parse_xml(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+            <!-- discovery -->
+            <d:propfind xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+              <d:prop>
+                <d:displayname/>
+                <c:calendar-home-set />
+                <color xmlns="http://apple.com/ns/ical/"></color>
+              </d:prop>
+            </d:propfind>"#,
+        )
+        .unwrap();
+
+        assert!(root.is("DAV:", "propfind"));
+        let prop = root.child("DAV:", "prop").unwrap();
+        let names: Vec<(&str, &str)> = prop
+            .children
+            .iter()
+            .map(|c| (c.namespace.as_str(), c.name.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("DAV:", "displayname"),
+                ("urn:ietf:params:xml:ns:caldav", "calendar-home-set"),
+                ("http://apple.com/ns/ical/", "color"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_xml_text_and_attributes() {
+        let root = parse_xml(
+            "<a xmlns='urn:x'><href> /dav/a&amp;b.ics </href>\
+             <t start=\"20240101T000000Z\" c:x='1' xmlns:c='urn:c'/>\
+             <data><![CDATA[BEGIN:<VCALENDAR>]]>&#x41;&#66;</data></a>",
+        )
+        .unwrap();
+
+        assert_eq!(root.namespace, "urn:x");
+        assert_eq!(root.children[0].text.trim(), "/dav/a&b.ics");
+        assert_eq!(
+            root.children[1].attribute("start"),
+            Some("20240101T000000Z")
+        );
+        assert_eq!(root.children[1].attribute("x"), Some("1"));
+        assert_eq!(root.children[2].text, "BEGIN:<VCALENDAR>AB");
+    }
+
+    #[test]
+    fn test_parse_xml_rejects_malformed() {
+        for input in [
+            "",
+            "plain text",
+            "<a><b></a>",
+            "<a>",
+            "<d:a/>",
+            "<a/><b/>",
+            "<a x=1/>",
+            "<a>&nbsp;</a>",
+        ] {
+            assert!(
+                matches!(parse_xml(input), Err(CalDavError::InvalidXml(_))),
+                "{input:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_escape_xml() {
+        assert_eq!(
+            escape_xml(r#"Tom & Jerry's <"show">"#),
+            "Tom &amp; Jerry&apos;s &lt;&quot;show&quot;&gt;"
+        );
+        let root = parse_xml(&format!("<a>{}</a>", escape_xml("x < y & z\r\n"))).unwrap();
+        assert_eq!(root.text, "x < y & z\r\n");
+    }
+}
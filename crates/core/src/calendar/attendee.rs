@@ -0,0 +1,305 @@
+//! Entry attendees and their responses to an invitation.
+//!
+//! Attendees are identified by email address. When the address belongs to a
+//! `User`, the attendee also carries the user's ID so they can answer from the
+//! app. Response statuses follow iCalendar's PARTSTAT values (`needs-action`,
+//! `accepted`, `declined`, `tentative`).
+
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::error::AttendeeError;
+use super::types::User;
+
+/// An attendee's answer to an invitation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ResponseStatus {
+    /// The attendee has not answered yet.
+    #[default]
+    NeedsAction,
+    Accepted,
+    Declined,
+    Tentative,
+}
+
+impl ResponseStatus {
+    /// Returns the status as its kebab-case name.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NeedsAction => "needs-action",
+            Self::Accepted => "accepted",
+            Self::Declined => "declined",
+            Self::Tentative => "tentative",
+        }
+    }
+}
+
+impl fmt::Display for ResponseStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ResponseStatus {
+    type Err = AttendeeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "needs-action" => Ok(Self::NeedsAction),
+            "accepted" => Ok(Self::Accepted),
+            "declined" => Ok(Self::Declined),
+            "tentative" => Ok(Self::Tentative),
+            _ => Err(AttendeeError::InvalidStatus(s.to_string())),
+        }
+    }
+}
+
+/// A person invited to an entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attendee {
+    /// Email address, stored lowercase.
+    pub email: String,
+    /// The user this address belongs to, if they have an account.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<Uuid>,
+    /// Display name, taken from the user when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub status: ResponseStatus,
+}
+
+impl Attendee {
+    /// Invites an email address. The attendee has not answered yet.
+    pub fn new(email: impl AsRef<str>) -> Self {
+        Self {
+            email: email.as_ref().trim().to_lowercase(),
+            user_id: None,
+            name: None,
+            status: ResponseStatus::NeedsAction,
+        }
+    }
+
+    /// Invites a user by their account.
+    pub fn for_user(user: &User) -> Self {
+        let mut attendee = Self::new(&user.email);
+        attendee.link_user(user);
+        attendee
+    }
+
+    /// Sets the attendee's response.
+    pub fn with_status(mut self, status: ResponseStatus) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Records that this attendee's address belongs to `user`.
+    pub fn link_user(&mut self, user: &User) {
+        self.user_id = Some(user.id);
+        self.name = Some(user.name.clone());
+    }
+
+    /// Returns true if this attendee is `user`, by ID or by email address.
+    pub fn is_user(&self, user: &User) -> bool {
+        match self.user_id {
+            Some(id) => id == user.id,
+            None => self.email.eq_ignore_ascii_case(user.email.trim()),
+        }
+    }
+
+    /// Checks the email address looks like `local@domain`.
+    pub fn validate(&self) -> Result<(), AttendeeError> {
+        let valid = match self.email.split_once('@') {
+            Some((local, domain)) => {
+                !local.is_empty()
+                    && !domain.is_empty()
+                    && !domain.contains('@')
+                    && !self.email.contains(char::is_whitespace)
+            }
+            None => false,
+        };
+        match valid {
+            true => Ok(()),
+            false => Err(AttendeeError::InvalidEmail(self.email.clone())),
+        }
+    }
+}
+
+impl FromStr for Attendee {
+    type Err = AttendeeError;
+
+    /// Parses a single email address into an attendee.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let attendee = Self::new(s);
+        attendee.validate()?;
+        Ok(attendee)
+    }
+}
+
+/// Checks every attendee has a valid address and nobody is invited twice.
+pub fn validate_attendees(attendees: &[Attendee]) -> Result<(), AttendeeError> {
+    let mut seen = HashSet::new();
+    for attendee in attendees {
+        attendee.validate()?;
+        if !seen.insert(attendee.email.as_str()) {
+            return Err(AttendeeError::Duplicate(attendee.email.clone()));
+        }
+    }
+    Ok(())
+}
+
+/// Parses a comma-separated list of email addresses into attendees.
+pub fn parse_attendees(s: &str) -> Result<Vec<Attendee>, AttendeeError> {
+    let attendees: Vec<Attendee> = s
+        .split(',')
+        .filter(|part| !part.trim().is_empty())
+        .map(Attendee::new)
+        .collect();
+    validate_attendees(&attendees)?;
+    Ok(attendees)
+}
+
+/// Formats attendees as a comma-separated list of email addresses.
+pub fn format_attendees(attendees: &[Attendee]) -> String {
+    attendees
+        .iter()
+        .map(|attendee| attendee.email.as_str())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Finds `user` among the attendees.
+pub fn find_attendee<'a>(attendees: &'a [Attendee], user: &User) -> Option<&'a Attendee> {
+    attendees.iter().find(|attendee| attendee.is_user(user))
+}
+
+/// Replaces an attendee list with `invited`.
+///
+/// People who stay invited keep their response and linked account; new
+/// attendees start out as `needs-action`.
+pub fn merge_attendees(existing: &[Attendee], invited: Vec<Attendee>) -> Vec<Attendee> {
+    invited
+        .into_iter()
+        .map(|attendee| {
+            match existing
+                .iter()
+                .find(|current| current.email == attendee.email)
+            {
+                Some(current) => Attendee {
+                    user_id: attendee.user_id.or(current.user_id),
+                    name: attendee.name.or_else(|| current.name.clone()),
+                    status: current.status,
+                    ..attendee
+                },
+                None => attendee.with_status(ResponseStatus::NeedsAction),
+            }
+        })
+        .collect()
+}
+
+/// Records `user`'s response, linking their account to the matching attendee.
+pub fn respond(
+    attendees: &mut [Attendee],
+    user: &User,
+    status: ResponseStatus,
+) -> Result<(), AttendeeError> {
+    let attendee = attendees
+        .iter_mut()
+        .find(|attendee| attendee.is_user(user))
+        .ok_or(AttendeeError::NotAnAttendee)?;
+    attendee.link_user(user);
+    attendee.status = status;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(email: &str) -> User {
+        User::new("Alice", email)
+    }
+
+    #[test]
+    fn test_response_status_round_trip() {
+        for status in [
+            ResponseStatus::NeedsAction,
+            ResponseStatus::Accepted,
+            ResponseStatus::Declined,
+            ResponseStatus::Tentative,
+        ] {
+            assert_eq!(status.to_string().parse::<ResponseStatus>(), Ok(status));
+            let json = serde_json::to_string(&status).unwrap();
+            assert_eq!(json, format!("\"{status}\""));
+        }
+        assert_eq!("NEEDS_ACTION".parse(), Ok(ResponseStatus::NeedsAction));
+        assert!("maybe".parse::<ResponseStatus>().is_err());
+    }
+
+    #[test]
+    fn test_parse_attendees() {
+        let attendees = parse_attendees(" Alice@Example.com, bob@example.com,").unwrap();
+        assert_eq!(attendees.len(), 2);
+        assert_eq!(attendees[0].email, "alice@example.com");
+        assert_eq!(attendees[0].status, ResponseStatus::NeedsAction);
+        assert_eq!(
+            "Bob@Example.com".parse(),
+            Ok(Attendee::new("bob@example.com"))
+        );
+        assert_eq!(
+            format_attendees(&attendees),
+            "alice@example.com,bob@example.com"
+        );
+
+        assert_eq!(
+            parse_attendees("alice"),
+            Err(AttendeeError::InvalidEmail("alice".to_string()))
+        );
+        assert_eq!(
+            parse_attendees("a@example.com,A@example.com"),
+            Err(AttendeeError::Duplicate("a@example.com".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_merge_attendees_keeps_responses() {
+        let alice = user("alice@example.com");
+        let existing = vec![
+            Attendee::for_user(&alice).with_status(ResponseStatus::Accepted),
+            Attendee::new("bob@example.com").with_status(ResponseStatus::Declined),
+        ];
+        let invited = vec![
+            Attendee::new("alice@example.com"),
+            Attendee::new("carol@example.com").with_status(ResponseStatus::Accepted),
+        ];
+
+        let merged = merge_attendees(&existing, invited);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].status, ResponseStatus::Accepted);
+        assert_eq!(merged[0].user_id, Some(alice.id));
+        // New attendees always start without an answer
+        assert_eq!(merged[1].status, ResponseStatus::NeedsAction);
+    }
+
+    #[test]
+    fn test_respond() {
+        let alice = user("Alice@example.com");
+        let mut attendees = vec![Attendee::new("alice@example.com")];
+
+        respond(&mut attendees, &alice, ResponseStatus::Tentative).unwrap();
+        assert_eq!(attendees[0].status, ResponseStatus::Tentative);
+        assert_eq!(attendees[0].user_id, Some(alice.id));
+        assert!(find_attendee(&attendees, &alice).is_some());
+
+        let bob = user("bob@example.com");
+        assert_eq!(
+            respond(&mut attendees, &bob, ResponseStatus::Accepted),
+            Err(AttendeeError::NotAnAttendee)
+        );
+    }
+}
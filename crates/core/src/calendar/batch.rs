@@ -0,0 +1,113 @@
+//! Batched entry writes.
+//!
+//! Bulk imports send many creates, updates and deletes at once. A batch is
+//! validated up front, then handed to storage in one call so backends that
+//! support transactions can apply it all-or-nothing, and so caches and
+//! subscribers see one change instead of hundreds.
+
+use std::collections::HashSet;
+
+use uuid::Uuid;
+
+use super::error::BatchError;
+use super::types::CalendarEntry;
+
+/// Most writes accepted in a single batch.
+pub const MAX_BATCH_SIZE: usize = 500;
+
+/// One write in a batch.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntryWrite {
+    /// Creates a new entry.
+    Create(CalendarEntry),
+    /// Replaces a live entry, applied only while its stored version is still
+    /// `expected_version`.
+    Update {
+        entry: CalendarEntry,
+        expected_version: u64,
+    },
+    /// Moves an entry to the trash.
+    Delete(Uuid),
+}
+
+impl EntryWrite {
+    /// Returns the ID of the entry this write touches.
+    pub fn entry_id(&self) -> Uuid {
+        match self {
+            Self::Create(entry) | Self::Update { entry, .. } => entry.id,
+            Self::Delete(id) => *id,
+        }
+    }
+}
+
+/// Checks that a batch is non-empty, within [`MAX_BATCH_SIZE`] and touches
+/// each entry at most once, so the order of writes never matters.
+pub fn validate_batch(writes: &[EntryWrite]) -> Result<(), BatchError> {
+    if writes.is_empty() {
+        return Err(BatchError::Empty);
+    }
+    if writes.len() > MAX_BATCH_SIZE {
+        return Err(BatchError::TooLarge(MAX_BATCH_SIZE));
+    }
+
+    let mut seen = HashSet::with_capacity(writes.len());
+    for write in writes {
+        let id = write.entry_id();
+        if !seen.insert(id) {
+            return Err(BatchError::DuplicateEntry(id));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn entry(title: &str) -> CalendarEntry {
+        CalendarEntry::all_day(
+            Uuid::new_v4(),
+            title,
+            NaiveDate::from_ymd_opt(2024, 9, 2).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_validate_batch_accepts_distinct_entries() {
+        let updated = entry("Physics");
+        let writes = vec![
+            EntryWrite::Create(entry("Chemistry")),
+            EntryWrite::Update {
+                entry: updated,
+                expected_version: 1,
+            },
+            EntryWrite::Delete(Uuid::new_v4()),
+        ];
+
+        assert_eq!(validate_batch(&writes), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_batch_rejects_empty_and_oversized() {
+        assert_eq!(validate_batch(&[]), Err(BatchError::Empty));
+
+        let writes: Vec<_> = (0..=MAX_BATCH_SIZE)
+            .map(|_| EntryWrite::Delete(Uuid::new_v4()))
+            .collect();
+        assert_eq!(
+            validate_batch(&writes),
+            Err(BatchError::TooLarge(MAX_BATCH_SIZE))
+        );
+    }
+
+    #[test]
+    fn test_validate_batch_rejects_duplicate_entry() {
+        let created = entry("Biology");
+        let id = created.id;
+        let writes = vec![EntryWrite::Create(created), EntryWrite::Delete(id)];
+
+        assert_eq!(validate_batch(&writes), Err(BatchError::DuplicateEntry(id)));
+    }
+}
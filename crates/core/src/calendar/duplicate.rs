@@ -0,0 +1,327 @@
+//! Copying a calendar into a new one.
+//!
+//! A copy takes the source calendar's metadata and, optionally, its entries
+//! (moved by a number of days) and memberships. Everything copied gets a new
+//! ID and starts again at the initial version. Attendees are invited afresh,
+//! so their answers to the original entries don't carry over.
+
+use std::collections::HashMap;
+
+use chrono::{Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::attendee::ResponseStatus;
+use super::error::DuplicateError;
+use super::occurrence::OccurrenceOverride;
+use super::types::{Calendar, CalendarEntry, CalendarMembership, CalendarRole};
+use super::version::INITIAL_VERSION;
+
+/// Most days copied entries can be moved by, in either direction.
+pub const MAX_SHIFT_DAYS: i64 = 3660;
+
+/// What to copy along with a calendar's metadata.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DuplicateOptions {
+    /// Copy the calendar's live entries and their occurrence overrides.
+    pub entries: bool,
+    /// Days to move copied entries by; negative moves them earlier.
+    pub shift_days: i64,
+    /// Copy the calendar's memberships.
+    pub members: bool,
+    /// User who owns the copy, whatever their role in the source.
+    pub owner: Option<Uuid>,
+}
+
+/// The rows a duplication writes besides the calendar itself.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CalendarCopy {
+    pub entries: Vec<CalendarEntry>,
+    pub overrides: Vec<OccurrenceOverride>,
+    pub memberships: Vec<CalendarMembership>,
+}
+
+impl CalendarCopy {
+    /// Counts what was copied.
+    pub fn summary(&self) -> DuplicateSummary {
+        DuplicateSummary {
+            entries: self.entries.len(),
+            members: self.memberships.len(),
+        }
+    }
+}
+
+/// How many entries and memberships a duplication copied.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DuplicateSummary {
+    pub entries: usize,
+    pub members: usize,
+}
+
+/// Checks that copied entries can be moved by `shift_days`.
+pub fn validate_shift(shift_days: i64) -> Result<(), DuplicateError> {
+    if shift_days.abs() > MAX_SHIFT_DAYS {
+        return Err(DuplicateError::ShiftTooLarge(MAX_SHIFT_DAYS));
+    }
+    Ok(())
+}
+
+/// Creates the new calendar for a copy of `source`.
+///
+/// Without a `name`, the copy is called "<source name> (copy)". The copy is
+/// never the user's default calendar, and never subscribed to a feed, so a
+/// copy of a subscribed calendar can be edited.
+pub fn duplicate_calendar(source: &Calendar, name: Option<String>) -> Calendar {
+    let name = name.unwrap_or_else(|| format!("{} (copy)", source.name));
+    Calendar {
+        description: source.description.clone(),
+        timezone: source.timezone,
+        ..Calendar::new(name, &source.color)
+    }
+}
+
+/// Copies a calendar's entries, overrides and memberships into calendar
+/// `calendar_id` as `options` asks.
+///
+/// `entries` are the source's stored entries (series, not expanded
+/// occurrences) and `overrides` their occurrence overrides. Overrides of
+/// entries not in `entries` are dropped.
+pub fn duplicate_contents(
+    calendar_id: Uuid,
+    entries: &[CalendarEntry],
+    overrides: &[OccurrenceOverride],
+    memberships: &[CalendarMembership],
+    options: &DuplicateOptions,
+) -> CalendarCopy {
+    let mut copy = CalendarCopy::default();
+
+    if options.entries {
+        let mut series_ids = HashMap::with_capacity(entries.len());
+        for entry in entries {
+            let duplicate = duplicate_entry(entry, calendar_id, Uuid::new_v4(), options.shift_days);
+            series_ids.insert(entry.id, duplicate.id);
+            copy.entries.push(duplicate);
+        }
+        copy.overrides = overrides
+            .iter()
+            .filter_map(|over| {
+                let series_id = *series_ids.get(&over.series_id)?;
+                Some(duplicate_override(
+                    over,
+                    calendar_id,
+                    series_id,
+                    options.shift_days,
+                ))
+            })
+            .collect();
+    }
+
+    if options.members {
+        copy.memberships = memberships
+            .iter()
+            .filter(|m| Some(m.user_id) != options.owner)
+            .map(|m| CalendarMembership::new(calendar_id, m.user_id, m.role))
+            .collect();
+    }
+    if let Some(owner) = options.owner {
+        copy.memberships.push(CalendarMembership::new(
+            calendar_id,
+            owner,
+            CalendarRole::Owner,
+        ));
+    }
+
+    copy
+}
+
+/// Copies one entry into another calendar under a new ID, moved by `shift_days`.
+fn duplicate_entry(
+    entry: &CalendarEntry,
+    calendar_id: Uuid,
+    id: Uuid,
+    shift_days: i64,
+) -> CalendarEntry {
+    let now = Utc::now();
+    let mut copy = CalendarEntry {
+        id,
+        calendar_id,
+        start_date: shift(entry.start_date, shift_days),
+        end_date: shift(entry.end_date, shift_days),
+        occurrence_date: None,
+        segment: None,
+        version: INITIAL_VERSION,
+        created_at: now,
+        updated_at: now,
+        ..entry.clone()
+    };
+    if let Some(until) = copy
+        .recurrence
+        .as_mut()
+        .and_then(|rule| rule.until.as_mut())
+    {
+        *until = shift(*until, shift_days);
+    }
+    for attendee in &mut copy.attendees {
+        attendee.status = ResponseStatus::NeedsAction;
+    }
+    copy
+}
+
+/// Copies an occurrence override onto the copied series `series_id`.
+fn duplicate_override(
+    over: &OccurrenceOverride,
+    calendar_id: Uuid,
+    series_id: Uuid,
+    shift_days: i64,
+) -> OccurrenceOverride {
+    let original_date = shift(over.original_date, shift_days);
+    match &over.replacement {
+        Some(replacement) => OccurrenceOverride::replaced(
+            series_id,
+            original_date,
+            duplicate_entry(replacement, calendar_id, series_id, shift_days),
+        ),
+        None => OccurrenceOverride::cancelled(series_id, original_date),
+    }
+}
+
+/// Moves a date by whole days, saturating at the ends of the calendar.
+fn shift(date: NaiveDate, days: i64) -> NaiveDate {
+    date.checked_add_signed(Duration::days(days))
+        .unwrap_or(if days < 0 {
+            NaiveDate::MIN
+        } else {
+            NaiveDate::MAX
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::{Attendee, Frequency, RecurrenceRule};
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_duplicate_calendar_names_copy() {
+        let source = Calendar::new("Q1 Project", "#3B82F6")
+            .with_description("Planning")
+            .with_subscription("https://example.com/q1.ics");
+
+        let copy = duplicate_calendar(&source, None);
+        assert_ne!(copy.id, source.id);
+        assert_eq!(copy.name, "Q1 Project (copy)");
+        assert_eq!(copy.description.as_deref(), Some("Planning"));
+        assert!(!copy.is_read_only());
+
+        let named = duplicate_calendar(&source, Some("Q2 Project".to_string()));
+        assert_eq!(named.name, "Q2 Project");
+    }
+
+    #[test]
+    fn test_duplicate_contents_shifts_entries_and_overrides() {
+        let source_id = Uuid::new_v4();
+        let target_id = Uuid::new_v4();
+        let mut rule = RecurrenceRule::new(Frequency::Weekly);
+        rule.until = Some(date(3, 25));
+        let series = CalendarEntry::all_day(source_id, "Standup", date(1, 1))
+            .with_recurrence(rule)
+            .with_attendee(Attendee {
+                status: ResponseStatus::Accepted,
+                ..Attendee::new("ada@example.com")
+            });
+        let overrides = vec![
+            OccurrenceOverride::cancelled(series.id, date(1, 8)),
+            OccurrenceOverride::replaced(
+                series.id,
+                date(1, 15),
+                CalendarEntry::all_day(source_id, "Planning", date(1, 16)),
+            ),
+            OccurrenceOverride::cancelled(Uuid::new_v4(), date(1, 8)),
+        ];
+        let options = DuplicateOptions {
+            entries: true,
+            shift_days: 91,
+            ..DuplicateOptions::default()
+        };
+
+        let copy = duplicate_contents(
+            target_id,
+            std::slice::from_ref(&series),
+            &overrides,
+            &[],
+            &options,
+        );
+
+        assert_eq!(copy.entries.len(), 1);
+        let entry = &copy.entries[0];
+        assert_ne!(entry.id, series.id);
+        assert_eq!(entry.calendar_id, target_id);
+        assert_eq!(entry.start_date, date(4, 1));
+        assert_eq!(entry.recurrence.as_ref().unwrap().until, Some(date(6, 24)));
+        assert_eq!(entry.attendees[0].status, ResponseStatus::NeedsAction);
+
+        assert_eq!(copy.overrides.len(), 2);
+        assert!(copy.overrides.iter().all(|o| o.series_id == entry.id));
+        assert_eq!(copy.overrides[0].original_date, date(4, 8));
+        let replacement = copy.overrides[1].replacement.as_ref().unwrap();
+        assert_eq!(replacement.id, entry.id);
+        assert_eq!(replacement.calendar_id, target_id);
+        assert_eq!(replacement.start_date, date(4, 16));
+    }
+
+    #[test]
+    fn test_duplicate_contents_makes_owner() {
+        let source_id = Uuid::new_v4();
+        let target_id = Uuid::new_v4();
+        let (owner, writer, duplicator) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let memberships = vec![
+            CalendarMembership::owner(source_id, owner),
+            CalendarMembership::writer(source_id, writer),
+            CalendarMembership::reader(source_id, duplicator),
+        ];
+        let entry = CalendarEntry::all_day(source_id, "Kickoff", date(1, 2));
+
+        let only_owner = DuplicateOptions {
+            owner: Some(duplicator),
+            ..DuplicateOptions::default()
+        };
+        let copy = duplicate_contents(target_id, &[entry], &[], &memberships, &only_owner);
+        assert!(copy.entries.is_empty());
+        assert_eq!(copy.memberships.len(), 1);
+
+        let with_members = DuplicateOptions {
+            members: true,
+            ..only_owner
+        };
+        let copy = duplicate_contents(target_id, &[], &[], &memberships, &with_members);
+        assert_eq!(
+            copy.summary(),
+            DuplicateSummary {
+                entries: 0,
+                members: 3
+            }
+        );
+        assert!(copy.memberships.iter().all(|m| m.calendar_id == target_id));
+        let role = |user| {
+            copy.memberships
+                .iter()
+                .find(|m| m.user_id == user)
+                .unwrap()
+                .role
+        };
+        assert_eq!(role(duplicator), CalendarRole::Owner);
+        assert_eq!(role(writer), CalendarRole::Writer);
+    }
+
+    #[test]
+    fn test_validate_shift() {
+        assert_eq!(validate_shift(-MAX_SHIFT_DAYS), Ok(()));
+        assert_eq!(
+            validate_shift(MAX_SHIFT_DAYS + 1),
+            Err(DuplicateError::ShiftTooLarge(MAX_SHIFT_DAYS))
+        );
+    }
+}
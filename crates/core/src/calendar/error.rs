@@ -1,4 +1,6 @@
+use chrono::NaiveDate;
 use thiserror::Error;
+use uuid::Uuid;
 
 /// Errors that can occur when validating or manipulating calendars.
 #[derive(Debug, Error, Clone, PartialEq, Eq)]
@@ -24,6 +26,189 @@ pub enum EntryError {
     InvalidTimeRange,
     #[error("Calendar ID is required")]
     MissingCalendarId,
+    #[error("Invalid recurrence rule: {0}")]
+    InvalidRecurrence(#[from] RecurrenceError),
+    #[error("Invalid reminder: {0}")]
+    InvalidReminder(#[from] ReminderError),
+    #[error("Invalid attendee: {0}")]
+    InvalidAttendee(#[from] AttendeeError),
+    #[error("Invalid tag: {0}")]
+    InvalidTag(#[from] TagError),
+    #[error("Invalid task: {0}")]
+    InvalidTask(#[from] TaskError),
+}
+
+/// Errors that can occur when parsing or validating a recurrence rule.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum RecurrenceError {
+    #[error("FREQ is required")]
+    MissingFrequency,
+    #[error("Unsupported frequency: {0}")]
+    InvalidFrequency(String),
+    #[error("Malformed rule part: {0}")]
+    InvalidPart(String),
+    #[error("INTERVAL must be a positive integer")]
+    InvalidInterval,
+    #[error("COUNT must be a positive integer")]
+    InvalidCount,
+    #[error("Invalid BYDAY value: {0}")]
+    InvalidByDay(String),
+    #[error("Invalid BYMONTHDAY value: {0}")]
+    InvalidByMonthDay(String),
+    #[error("Invalid UNTIL value: {0}")]
+    InvalidUntil(String),
+    #[error("COUNT and UNTIL cannot both be set")]
+    CountAndUntil,
+    #[error("UNTIL must not be before the first occurrence")]
+    UntilBeforeStart,
+    #[error("Unsupported rule part: {0}")]
+    UnsupportedPart(String),
+    #[error("Unsupported combination: {0}")]
+    Unsupported(&'static str),
+    #[error("Entry is not a recurring series")]
+    NotRecurring,
+    #[error("{0} is not an occurrence of this series")]
+    NotAnOccurrence(NaiveDate),
+    #[error("An occurrence date is required for scope '{0}'")]
+    MissingOccurrenceDate(&'static str),
+    #[error("Invalid scope: {0} (expected this, following or all)")]
+    InvalidScope(String),
+}
+
+/// Errors that can occur when parsing or validating a reminder.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ReminderError {
+    #[error("'{0}' is not a reminder (expected e.g. 10m, 2h, 1d, 1w or 1d@09:00)")]
+    Invalid(String),
+    #[error("Reminders cannot fire more than {0} days before an entry")]
+    TooEarly(u32),
+}
+
+/// Errors that can occur when inviting attendees or recording their responses.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum AttendeeError {
+    #[error("'{0}' is not a valid email address")]
+    InvalidEmail(String),
+    #[error("{0} is invited more than once")]
+    Duplicate(String),
+    #[error(
+        "Invalid response status: {0} (expected needs-action, accepted, declined or tentative)"
+    )]
+    InvalidStatus(String),
+    #[error("Not an attendee of this entry")]
+    NotAnAttendee,
+}
+
+/// Errors that can occur when parsing a tag.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum TagError {
+    #[error("Tags cannot be empty")]
+    Empty,
+    #[error("Tag '{0}' is too long (max 32 characters)")]
+    TooLong(String),
+    #[error("Invalid tag '{0}' (use letters, digits, '-', '_', '.' or '/')")]
+    Invalid(String),
+}
+
+/// Errors that can occur when parsing or editing task details.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum TaskError {
+    #[error("Invalid priority: {0} (expected low, normal, high or urgent)")]
+    InvalidPriority(String),
+    #[error("Checklist items cannot be empty")]
+    EmptyChecklistItem,
+    #[error("Entry is not a task")]
+    NotATask,
+    #[error("Task has no checklist item {0}")]
+    NoSuchChecklistItem(usize),
+}
+
+/// Errors that can occur when parsing a search query.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum SearchError {
+    #[error("Search query must contain at least one word")]
+    EmptyQuery,
+    #[error("Search query has too many words (max {0})")]
+    TooManyTerms(usize),
+}
+
+/// Errors that can occur when checking an entry for scheduling conflicts.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ConflictError {
+    #[error("Invalid conflict scope: {0} (expected calendar or all)")]
+    InvalidScope(String),
+    #[error("Entry overlaps {} other entries: {}", .0.len(), .0.join(", "))]
+    Overlaps(Vec<String>),
+}
+
+/// Errors that can occur when merging concurrent edits to an entry.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum MergeError {
+    #[error("Revision {0} not found for this entry")]
+    UnknownBase(u32),
+    #[error("Entry was changed by someone else: conflicting edits to {}", .0.join(", "))]
+    Conflicts(Vec<String>),
+}
+
+/// Errors that can occur when reading an entry's revision history.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum RevisionError {
+    #[error("Invalid revision action: {0} (expected created, updated, deleted or restored)")]
+    InvalidAction(String),
+}
+
+/// Errors that can occur when computing free/busy information.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum FreeBusyError {
+    #[error("Free/busy range is too long (max {0} days)")]
+    RangeTooLong(i64),
+}
+
+/// Errors that can occur when validating a batch of entry writes.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum BatchError {
+    #[error("Batch contains no operations")]
+    Empty,
+    #[error("Batch is too large (max {0} operations)")]
+    TooLarge(usize),
+    #[error("Entry {0} appears more than once in the batch")]
+    DuplicateEntry(Uuid),
+}
+
+/// Errors that can occur when duplicating a calendar.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum DuplicateError {
+    #[error("Date shift is too large (max {0} days)")]
+    ShiftTooLarge(i64),
+}
+
+/// Errors that can occur when reading an iCalendar file.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum IcsError {
+    #[error("Not an iCalendar file (expected BEGIN:VCALENDAR)")]
+    NotCalendar,
+
+    #[error("Missing END:{0}")]
+    Unterminated(String),
+
+    #[error("Invalid content line: {0}")]
+    InvalidLine(String),
+}
+
+/// Errors that can occur when subscribing a calendar to an iCalendar feed.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum SubscriptionError {
+    #[error("Invalid subscription URL: {0}")]
+    InvalidUrl(String),
+
+    #[error("Unsupported URL scheme: {0} (expected http, https or webcal)")]
+    UnsupportedScheme(String),
+
+    #[error("Subscription URL too long (max 2048 characters)")]
+    UrlTooLong,
+
+    #[error("Subscription URL points to a private or loopback address: {0}")]
+    PrivateAddress(String),
 }
 
 #[cfg(test)]
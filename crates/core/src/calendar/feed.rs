@@ -0,0 +1,160 @@
+//! iCalendar subscription feeds.
+//!
+//! A feed token is a secret in a URL that lets calendar apps which only speak
+//! iCalendar over HTTP (webcal) fetch one calendar without a session. Each
+//! user has at most one token per calendar: rotating replaces it and revoking
+//! deletes it. A feed serves its calendar only while the token's owner can
+//! still read it.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Length of a feed token: 64 lowercase hex digits.
+pub const FEED_TOKEN_LENGTH: usize = 64;
+
+/// A user's secret token for subscribing to a calendar.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedToken {
+    pub token: String,
+    pub calendar_id: Uuid,
+    pub user_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+impl FeedToken {
+    /// Creates a new random token for a user's feed of a calendar.
+    pub fn new(calendar_id: Uuid, user_id: Uuid) -> Self {
+        Self {
+            token: generate_feed_token(),
+            calendar_id,
+            user_id,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Returns the path the feed is served at.
+    pub fn path(&self) -> String {
+        feed_path(&self.token)
+    }
+}
+
+/// A feed token with the URLs it is served at, as returned to its owner.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedSubscription {
+    #[serde(flatten)]
+    pub token: FeedToken,
+    /// Absolute `http(s)` URL of the feed
+    pub url: String,
+    /// The same URL with the `webcal` scheme
+    pub webcal_url: String,
+}
+
+impl FeedSubscription {
+    /// Describes a token served at the given absolute URL.
+    pub fn new(token: FeedToken, url: String) -> Self {
+        let webcal_url = webcal_url(&url);
+        Self {
+            token,
+            url,
+            webcal_url,
+        }
+    }
+}
+
+/// Generates a feed token from two random UUIDs (244 random bits).
+pub fn generate_feed_token() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// Returns the path a feed token is served at: `/feeds/<token>.ics`.
+pub fn feed_path(token: &str) -> String {
+    format!("/feeds/{token}.ics")
+}
+
+/// Reads the token out of a feed file name (`<token>.ics`).
+///
+/// Returns `None` unless the name is exactly a well-formed token followed by
+/// `.ics`, so malformed requests never reach storage.
+pub fn parse_feed_file(file: &str) -> Option<&str> {
+    let token = file.strip_suffix(".ics")?;
+    let well_formed = token.len() == FEED_TOKEN_LENGTH
+        && token
+            .bytes()
+            .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b));
+    well_formed.then_some(token)
+}
+
+/// Rewrites an `http` or `https` URL with the `webcal` scheme calendar apps
+/// open as a subscription.
+pub fn webcal_url(url: &str) -> String {
+    match url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+    {
+        Some(rest) => format!("webcal://{rest}"),
+        None => url.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feed_token_new() {
+        let (calendar_id, user_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let token = FeedToken::new(calendar_id, user_id);
+        let rotated = FeedToken::new(calendar_id, user_id);
+
+        assert_eq!(token.token.len(), FEED_TOKEN_LENGTH);
+        assert_ne!(token.token, rotated.token);
+        assert_eq!(token.path(), format!("/feeds/{}.ics", token.token));
+    }
+
+    #[test]
+    fn test_parse_feed_file() {
+        let token = generate_feed_token();
+        assert_eq!(
+            parse_feed_file(&format!("{token}.ics")),
+            Some(token.as_str())
+        );
+
+        assert_eq!(parse_feed_file(&token), None);
+        assert_eq!(parse_feed_file("abc.ics"), None);
+        let upper = token.to_uppercase();
+        assert_eq!(parse_feed_file(&format!("{upper}.ics")), None);
+        let traversal = format!("{}/...ics", &token[..59]);
+        assert_eq!(parse_feed_file(&traversal), None);
+    }
+
+    #[test]
+    fn test_webcal_url() {
+        assert_eq!(
+            webcal_url("https://cal.example.com/feeds/abc.ics"),
+            "webcal://cal.example.com/feeds/abc.ics"
+        );
+        assert_eq!(
+            webcal_url("http://localhost:3000/feeds/abc.ics"),
+            "webcal://localhost:3000/feeds/abc.ics"
+        );
+        assert_eq!(webcal_url("webcal://x/a.ics"), "webcal://x/a.ics");
+    }
+
+    #[test]
+    fn test_feed_subscription_serializes_flat() {
+        let token = FeedToken::new(Uuid::new_v4(), Uuid::new_v4());
+        let url = format!("https://cal.example.com{}", token.path());
+        let subscription = FeedSubscription::new(token.clone(), url);
+
+        let json = serde_json::to_value(&subscription).unwrap();
+        assert_eq!(json["token"], token.token);
+        assert_eq!(json["calendarId"], token.calendar_id.to_string());
+        assert!(json["webcalUrl"].as_str().unwrap().starts_with("webcal://"));
+
+        let parsed: FeedSubscription = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, subscription);
+    }
+}
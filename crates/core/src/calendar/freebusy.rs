@@ -0,0 +1,361 @@
+//! Free/busy aggregation.
+//!
+//! Merges the entries of several calendars into the intervals during which a
+//! user is busy, so others can schedule around them without seeing what the
+//! entries are. Timed entries are busy from start to end; all-day and
+//! multi-day entries are busy for their whole days. Tasks never block time.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::error::FreeBusyError;
+use super::timezone::{entry_timezone, resolve_local};
+use super::types::{CalendarEntry, EntryKind};
+use crate::storage::DateRange;
+
+/// Longest date range a single free/busy request may cover, in days.
+pub const MAX_FREEBUSY_DAYS: i64 = 92;
+
+/// A span of time, from `start` (inclusive) to `end` (exclusive).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct BusyInterval {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl BusyInterval {
+    /// Returns the part of this interval inside `window`, if any.
+    pub fn clip(&self, window: &BusyInterval) -> Option<BusyInterval> {
+        let start = self.start.max(window.start);
+        let end = self.end.min(window.end);
+        (start < end).then_some(BusyInterval { start, end })
+    }
+}
+
+/// An entry behind a busy interval, shown only to users who can read its calendar.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BusyEntry {
+    pub entry_id: Uuid,
+    pub calendar_id: Uuid,
+    pub title: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+/// When a user is busy over a date range.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FreeBusy {
+    pub user_id: Uuid,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Merged busy intervals across all of the user's calendars.
+    pub busy: Vec<BusyInterval>,
+    /// Entries behind the intervals, from calendars the requester can read.
+    pub entries: Vec<BusyEntry>,
+}
+
+/// Checks that a free/busy range is no longer than [`MAX_FREEBUSY_DAYS`].
+pub fn validate_freebusy_range(range: &DateRange) -> Result<(), FreeBusyError> {
+    if (range.end - range.start).num_days() >= MAX_FREEBUSY_DAYS {
+        return Err(FreeBusyError::RangeTooLong(MAX_FREEBUSY_DAYS));
+    }
+    Ok(())
+}
+
+/// Returns the instants covered by the days of `range` in `tz`.
+pub fn range_window(range: &DateRange, tz: Tz) -> BusyInterval {
+    BusyInterval {
+        start: resolve_local(range.start, NaiveTime::MIN, tz).with_timezone(&Utc),
+        end: resolve_local(range.end + Duration::days(1), NaiveTime::MIN, tz).with_timezone(&Utc),
+    }
+}
+
+/// Returns the entry dates that can overlap the days of `range` in any zone.
+pub fn freebusy_search_range(range: &DateRange) -> DateRange {
+    DateRange {
+        start: range.start - Duration::days(1),
+        end: range.end + Duration::days(1),
+    }
+}
+
+/// Returns when an entry, whose dates and times are expressed in `tz`, is busy.
+///
+/// Tasks and entries that end before they start are never busy.
+pub fn entry_busy_interval(entry: &CalendarEntry, tz: Tz) -> Option<BusyInterval> {
+    let (start, end) = match entry.kind {
+        EntryKind::Timed { start, end } => (
+            resolve_local(entry.start_date, start, tz),
+            resolve_local(entry.end_date, end, tz),
+        ),
+        EntryKind::AllDay | EntryKind::MultiDay => (
+            resolve_local(entry.start_date, NaiveTime::MIN, tz),
+            resolve_local(entry.end_date + Duration::days(1), NaiveTime::MIN, tz),
+        ),
+        EntryKind::Task { .. } => return None,
+    };
+    let interval = BusyInterval {
+        start: start.with_timezone(&Utc),
+        end: end.with_timezone(&Utc),
+    };
+    (interval.start < interval.end).then_some(interval)
+}
+
+/// Clips intervals to `window` and merges those that overlap or touch.
+///
+/// The result is sorted and its intervals never overlap.
+pub fn merge_busy_intervals(
+    intervals: impl IntoIterator<Item = BusyInterval>,
+    window: &BusyInterval,
+) -> Vec<BusyInterval> {
+    let mut clipped: Vec<BusyInterval> = intervals
+        .into_iter()
+        .filter_map(|interval| interval.clip(window))
+        .collect();
+    clipped.sort();
+
+    let mut merged: Vec<BusyInterval> = Vec::with_capacity(clipped.len());
+    for interval in clipped {
+        match merged.last_mut() {
+            Some(last) if interval.start <= last.end => last.end = last.end.max(interval.end),
+            _ => merged.push(interval),
+        }
+    }
+    merged
+}
+
+/// Aggregates a user's entries into free/busy information over the days of
+/// `range` in `tz`.
+///
+/// Entries use their own zone, then their calendar's zone from
+/// `calendar_timezones`, and are read as UTC when they have neither. Only
+/// entries from calendars in `readable` are listed in [`FreeBusy::entries`].
+pub fn free_busy(
+    user_id: Uuid,
+    entries: &[CalendarEntry],
+    calendar_timezones: &HashMap<Uuid, Tz>,
+    readable: &HashSet<Uuid>,
+    range: &DateRange,
+    tz: Tz,
+) -> FreeBusy {
+    let window = range_window(range, tz);
+
+    let intervals: Vec<(&CalendarEntry, BusyInterval)> = entries
+        .iter()
+        .filter_map(|entry| {
+            let calendar_tz = calendar_timezones.get(&entry.calendar_id).copied();
+            let entry_tz = entry_timezone(entry, calendar_tz).unwrap_or(Tz::UTC);
+            let interval = entry_busy_interval(entry, entry_tz)?.clip(&window)?;
+            Some((entry, interval))
+        })
+        .collect();
+
+    let mut details: Vec<BusyEntry> = intervals
+        .iter()
+        .filter(|(entry, _)| readable.contains(&entry.calendar_id))
+        .map(|(entry, interval)| BusyEntry {
+            entry_id: entry.id,
+            calendar_id: entry.calendar_id,
+            title: entry.title.clone(),
+            start: interval.start,
+            end: interval.end,
+        })
+        .collect();
+    details.sort_by_key(|busy| (busy.start, busy.end));
+
+    FreeBusy {
+        user_id,
+        start: window.start,
+        end: window.end,
+        busy: merge_busy_intervals(intervals.into_iter().map(|(_, i)| i), &window),
+        entries: details,
+    }
+}
+
+/// Formats an iCalendar UTC date-time, e.g. `20240115T093000Z`.
+fn ics_datetime(at: DateTime<Utc>) -> String {
+    at.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Formats free/busy information as an iCalendar object holding one
+/// VFREEBUSY component (RFC 5545 section 3.6.4).
+///
+/// Only the busy intervals are included, never entry details. `email` names
+/// the user as the component's organizer when given.
+pub fn format_vfreebusy(
+    free_busy: &FreeBusy,
+    email: Option<&str>,
+    dtstamp: DateTime<Utc>,
+) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//CalendSync//Free-Busy//EN".to_string(),
+        "BEGIN:VFREEBUSY".to_string(),
+        format!(
+            "UID:freebusy-{}-{}@calendsync",
+            free_busy.user_id,
+            ics_datetime(free_busy.start)
+        ),
+        format!("DTSTAMP:{}", ics_datetime(dtstamp)),
+        format!("DTSTART:{}", ics_datetime(free_busy.start)),
+        format!("DTEND:{}", ics_datetime(free_busy.end)),
+    ];
+    if let Some(email) = email {
+        lines.push(format!("ORGANIZER:mailto:{email}"));
+    }
+    for interval in &free_busy.busy {
+        lines.push(format!(
+            "FREEBUSY;FBTYPE=BUSY:{}/{}",
+            ics_datetime(interval.start),
+            ics_datetime(interval.end)
+        ));
+    }
+    lines.push("END:VFREEBUSY".to_string());
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut output = lines.join("\r\n");
+    output.push_str("\r\n");
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, TimeZone};
+    use chrono_tz::Europe::Madrid;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    fn utc(day: u32, h: u32, m: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, day, h, m, 0).unwrap()
+    }
+
+    fn interval(start: DateTime<Utc>, end: DateTime<Utc>) -> BusyInterval {
+        BusyInterval { start, end }
+    }
+
+    #[test]
+    fn test_entry_busy_interval() {
+        let cal_id = Uuid::new_v4();
+        let meeting = CalendarEntry::timed(cal_id, "Meeting", date(15), time(9, 0), time(10, 30));
+        assert_eq!(
+            entry_busy_interval(&meeting, Madrid),
+            Some(interval(utc(15, 8, 0), utc(15, 9, 30)))
+        );
+
+        let holiday = CalendarEntry::all_day(cal_id, "Holiday", date(15));
+        assert_eq!(
+            entry_busy_interval(&holiday, Tz::UTC),
+            Some(interval(utc(15, 0, 0), utc(16, 0, 0)))
+        );
+
+        let trip = CalendarEntry::multi_day(cal_id, "Trip", date(15), date(17));
+        assert_eq!(
+            entry_busy_interval(&trip, Tz::UTC),
+            Some(interval(utc(15, 0, 0), utc(18, 0, 0)))
+        );
+
+        let task = CalendarEntry::task(cal_id, "Chore", date(15), false);
+        assert_eq!(entry_busy_interval(&task, Tz::UTC), None);
+    }
+
+    #[test]
+    fn test_merge_busy_intervals() {
+        let window = interval(utc(15, 0, 0), utc(16, 0, 0));
+        let merged = merge_busy_intervals(
+            [
+                interval(utc(15, 13, 0), utc(15, 14, 0)),
+                interval(utc(15, 9, 0), utc(15, 10, 0)),
+                interval(utc(15, 9, 30), utc(15, 11, 0)),
+                interval(utc(15, 11, 0), utc(15, 12, 0)),
+                interval(utc(14, 22, 0), utc(15, 1, 0)),
+                interval(utc(16, 8, 0), utc(16, 9, 0)),
+            ],
+            &window,
+        );
+
+        assert_eq!(
+            merged,
+            vec![
+                interval(utc(15, 0, 0), utc(15, 1, 0)),
+                interval(utc(15, 9, 0), utc(15, 12, 0)),
+                interval(utc(15, 13, 0), utc(15, 14, 0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_free_busy_hides_unreadable_entries() {
+        let work = Uuid::new_v4();
+        let private = Uuid::new_v4();
+        let entries = vec![
+            CalendarEntry::timed(work, "Standup", date(15), time(9, 0), time(9, 30)),
+            CalendarEntry::timed(private, "Therapy", date(15), time(9, 15), time(10, 0)),
+            CalendarEntry::task(work, "Report", date(15), false),
+        ];
+        let timezones = HashMap::from([(work, Madrid)]);
+        let readable = HashSet::from([work]);
+        let range = DateRange::new(date(15), date(15)).unwrap();
+        let user_id = Uuid::new_v4();
+
+        let result = free_busy(user_id, &entries, &timezones, &readable, &range, Tz::UTC);
+
+        assert_eq!(result.start, utc(15, 0, 0));
+        assert_eq!(result.end, utc(16, 0, 0));
+        // Standup is 08:00-08:30 UTC (Madrid); Therapy is read as UTC
+        assert_eq!(
+            result.busy,
+            vec![
+                interval(utc(15, 8, 0), utc(15, 8, 30)),
+                interval(utc(15, 9, 15), utc(15, 10, 0)),
+            ]
+        );
+        let titles: Vec<&str> = result.entries.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, vec!["Standup"]);
+    }
+
+    #[test]
+    fn test_validate_freebusy_range() {
+        let ok = DateRange::new(date(1), date(31)).unwrap();
+        assert!(validate_freebusy_range(&ok).is_ok());
+
+        let long = DateRange {
+            start: date(1),
+            end: date(1) + Duration::days(MAX_FREEBUSY_DAYS),
+        };
+        assert_eq!(
+            validate_freebusy_range(&long),
+            Err(FreeBusyError::RangeTooLong(MAX_FREEBUSY_DAYS))
+        );
+    }
+
+    #[test]
+    fn test_format_vfreebusy() {
+        let free_busy = FreeBusy {
+            user_id: Uuid::nil(),
+            start: utc(15, 0, 0),
+            end: utc(16, 0, 0),
+            busy: vec![interval(utc(15, 9, 0), utc(15, 10, 0))],
+            entries: Vec::new(),
+        };
+        let ics = format_vfreebusy(&free_busy, Some("alice@example.com"), utc(14, 12, 0));
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.contains("\r\nBEGIN:VFREEBUSY\r\n"));
+        assert!(ics.contains("\r\nDTSTAMP:20240114T120000Z\r\n"));
+        assert!(ics.contains("\r\nDTSTART:20240115T000000Z\r\nDTEND:20240116T000000Z\r\n"));
+        assert!(ics.contains("\r\nORGANIZER:mailto:alice@example.com\r\n"));
+        assert!(ics.contains("\r\nFREEBUSY;FBTYPE=BUSY:20240115T090000Z/20240115T100000Z\r\n"));
+        assert!(ics.ends_with("END:VFREEBUSY\r\nEND:VCALENDAR\r\n"));
+    }
+}
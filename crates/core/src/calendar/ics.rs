@@ -0,0 +1,537 @@
+//! iCalendar (RFC 5545) export.
+//!
+//! Serializes a calendar's stored entries into an iCalendar object other
+//! calendar tools can import. All-day and multi-day entries become VEVENTs
+//! with DATE values, timed entries VEVENTs with DATE-TIME values and tasks
+//! VTODOs with a STATUS. Recurring series keep their RRULE; cancelled
+//! occurrences are listed in EXDATE and replaced ones are written as separate
+//! components with a RECURRENCE-ID.
+//!
+//! Times in a zone are written with a `TZID` parameter naming the IANA zone,
+//! and floating times without one. No VTIMEZONE components are emitted.
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+use chrono_tz::Tz;
+
+use super::attendee::Attendee;
+use super::occurrence::OccurrenceOverride;
+use super::reminder::Reminder;
+use super::task::TaskPriority;
+use super::timezone::{entry_timezone, resolve_local};
+use super::types::{Calendar, CalendarEntry, EntryKind};
+
+/// Media type of iCalendar data.
+pub const ICS_CONTENT_TYPE: &str = "text/calendar; charset=utf-8";
+
+/// Longest content line, in octets, before it is folded.
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Serializes a calendar and its entries as an iCalendar object.
+///
+/// `entries` are the calendar's stored entries (series, not expanded
+/// occurrences) and `overrides` their occurrence overrides; overrides of
+/// entries not in `entries` are left out. `dtstamp` is when the object is
+/// created.
+pub fn format_ics(
+    calendar: &Calendar,
+    entries: &[CalendarEntry],
+    overrides: &[OccurrenceOverride],
+    dtstamp: DateTime<Utc>,
+) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//CalendSync//Calendar//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        format!("X-WR-CALNAME:{}", escape_text(&calendar.name)),
+    ];
+    if let Some(description) = &calendar.description {
+        lines.push(format!("X-WR-CALDESC:{}", escape_text(description)));
+    }
+    if let Some(tz) = calendar.timezone {
+        lines.push(format!("X-WR-TIMEZONE:{}", tz.name()));
+    }
+
+    for entry in entries {
+        let tz = entry_timezone(entry, calendar.timezone);
+        let series_overrides: Vec<&OccurrenceOverride> = overrides
+            .iter()
+            .filter(|o| o.series_id == entry.id)
+            .collect();
+        let cancelled: Vec<NaiveDate> = series_overrides
+            .iter()
+            .filter(|o| o.is_cancelled())
+            .map(|o| o.original_date)
+            .collect();
+        write_component(&mut lines, entry, tz, dtstamp, &cancelled, None);
+
+        for over in series_overrides {
+            if let Some(replacement) = &over.replacement {
+                let recurrence_id = (entry, over.original_date);
+                write_component(
+                    &mut lines,
+                    replacement,
+                    tz,
+                    dtstamp,
+                    &[],
+                    Some(recurrence_id),
+                );
+            }
+        }
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut output = String::new();
+    for line in &lines {
+        output.push_str(&fold_line(line));
+        output.push_str("\r\n");
+    }
+    output
+}
+
+/// Returns a file name for a calendar's export, e.g. `team-offsite.ics`.
+pub fn ics_filename(calendar: &Calendar) -> String {
+    let mut name = String::new();
+    for c in calendar.name.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            name.push(c.to_ascii_lowercase());
+        } else if !name.ends_with('-') {
+            name.push('-');
+        }
+    }
+    let name = name.trim_matches('-');
+    if name.is_empty() {
+        "calendar.ics".to_string()
+    } else {
+        format!("{name}.ics")
+    }
+}
+
+/// Escapes a TEXT value (RFC 5545 section 3.3.11).
+pub fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Folds a content line so no line is longer than 75 octets (RFC 5545
+/// section 3.1), without splitting a UTF-8 character.
+pub fn fold_line(line: &str) -> String {
+    if line.len() <= MAX_LINE_OCTETS {
+        return line.to_string();
+    }
+    let mut folded = String::with_capacity(line.len() + line.len() / MAX_LINE_OCTETS * 3);
+    // Continuation lines start with a space, which counts towards their length
+    let mut limit = MAX_LINE_OCTETS;
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > limit {
+            folded.push_str("\r\n ");
+            limit = MAX_LINE_OCTETS - 1;
+            octets = 0;
+        }
+        folded.push(c);
+        octets += c.len_utf8();
+    }
+    folded
+}
+
+/// Writes the VEVENT or VTODO for an entry.
+///
+/// `recurrence_id` is the series and original date the entry replaces an
+/// occurrence of.
+fn write_component(
+    lines: &mut Vec<String>,
+    entry: &CalendarEntry,
+    tz: Option<Tz>,
+    dtstamp: DateTime<Utc>,
+    exdates: &[NaiveDate],
+    recurrence_id: Option<(&CalendarEntry, NaiveDate)>,
+) {
+    let component = if entry.kind.is_task() {
+        "VTODO"
+    } else {
+        "VEVENT"
+    };
+    lines.push(format!("BEGIN:{component}"));
+    let uid = recurrence_id.map_or(entry.id, |(series, _)| series.id);
+    lines.push(format!("UID:{uid}"));
+    lines.push(format!("DTSTAMP:{}", format_utc(dtstamp)));
+    lines.push(format!("CREATED:{}", format_utc(entry.created_at)));
+    lines.push(format!("LAST-MODIFIED:{}", format_utc(entry.updated_at)));
+    lines.push(format!("SEQUENCE:{}", entry.version.saturating_sub(1)));
+    if let Some((series, original_date)) = recurrence_id {
+        lines.push(format!(
+            "RECURRENCE-ID{}:{}",
+            occurrence_params(series, tz),
+            occurrence_value(series, original_date)
+        ));
+    }
+    lines.push(format!("SUMMARY:{}", escape_text(&entry.title)));
+    if let Some(description) = &entry.description {
+        lines.push(format!("DESCRIPTION:{}", escape_text(description)));
+    }
+    if let Some(location) = &entry.location {
+        lines.push(format!("LOCATION:{}", escape_text(location)));
+    }
+
+    match &entry.kind {
+        EntryKind::AllDay | EntryKind::MultiDay => {
+            lines.push(format!(
+                "DTSTART;VALUE=DATE:{}",
+                format_date(entry.start_date)
+            ));
+            lines.push(format!(
+                "DTEND;VALUE=DATE:{}",
+                format_date(entry.end_date + Duration::days(1))
+            ));
+        }
+        EntryKind::Timed { start, end } => {
+            lines.push(format!(
+                "DTSTART{}",
+                datetime_value(entry.start_date, *start, tz)
+            ));
+            lines.push(format!("DTEND{}", datetime_value(entry.end_date, *end, tz)));
+        }
+        EntryKind::Task {
+            completed,
+            due,
+            priority,
+            ..
+        } => {
+            // Tasks are due on their date. An RRULE needs a DTSTART to
+            // repeat from, so recurring tasks also start when they're due.
+            let value = match due {
+                Some(due) => datetime_value(entry.start_date, *due, tz),
+                None => format!(";VALUE=DATE:{}", format_date(entry.start_date)),
+            };
+            if entry.recurrence.is_some() {
+                lines.push(format!("DTSTART{value}"));
+            }
+            lines.push(format!("DUE{value}"));
+            lines.push(format!("PRIORITY:{}", ics_priority(*priority)));
+            if *completed {
+                lines.push("STATUS:COMPLETED".to_string());
+                lines.push(format!("COMPLETED:{}", format_utc(entry.updated_at)));
+            } else {
+                lines.push("STATUS:NEEDS-ACTION".to_string());
+            }
+        }
+    }
+
+    if let Some(rule) = &entry.recurrence {
+        let mut rule = rule.clone();
+        let until = rule.until.take();
+        let mut rrule = format!("RRULE:{rule}");
+        if let Some(until) = until {
+            rrule.push_str(";UNTIL=");
+            rrule.push_str(&until_value(entry, until, tz));
+        }
+        lines.push(rrule);
+    }
+    if !exdates.is_empty() {
+        let dates: Vec<String> = exdates
+            .iter()
+            .map(|date| occurrence_value(entry, *date))
+            .collect();
+        lines.push(format!(
+            "EXDATE{}:{}",
+            occurrence_params(entry, tz),
+            dates.join(",")
+        ));
+    }
+
+    if !entry.tags.is_empty() {
+        let tags: Vec<String> = entry.tags.iter().map(|t| escape_text(t.as_str())).collect();
+        lines.push(format!("CATEGORIES:{}", tags.join(",")));
+    }
+    for attendee in &entry.attendees {
+        lines.push(attendee_line(attendee));
+    }
+    for reminder in &entry.reminders {
+        lines.push("BEGIN:VALARM".to_string());
+        lines.push("ACTION:DISPLAY".to_string());
+        lines.push(format!("DESCRIPTION:{}", escape_text(&entry.title)));
+        lines.push(format!(
+            "TRIGGER:{}",
+            format_duration(trigger_minutes(reminder, entry))
+        ));
+        lines.push("END:VALARM".to_string());
+    }
+
+    lines.push(format!("END:{component}"));
+}
+
+/// Parameters of a property naming occurrences of `series`, which take the
+/// value type of the series' start.
+fn occurrence_params(series: &CalendarEntry, tz: Option<Tz>) -> String {
+    match (series.kind.start_time(), tz) {
+        (None, _) => ";VALUE=DATE".to_string(),
+        (Some(_), Some(tz)) => format!(";TZID={}", tz.name()),
+        (Some(_), None) => String::new(),
+    }
+}
+
+/// Value naming the occurrence of `series` that starts on `date`.
+fn occurrence_value(series: &CalendarEntry, date: NaiveDate) -> String {
+    match series.kind.start_time() {
+        Some(time) => format_local(date, time),
+        None => format_date(date),
+    }
+}
+
+/// Formats a DATE-TIME property's parameters and value, with a `TZID` when
+/// the time is in a zone and floating otherwise.
+fn datetime_value(date: NaiveDate, time: NaiveTime, tz: Option<Tz>) -> String {
+    let local = format_local(date, time);
+    match tz {
+        Some(tz) => format!(";TZID={}:{local}", tz.name()),
+        None => format!(":{local}"),
+    }
+}
+
+/// Formats an RRULE `UNTIL`, which must match the value type of `DTSTART`
+/// and be in UTC when `DTSTART` has a zone.
+fn until_value(entry: &CalendarEntry, until: NaiveDate, tz: Option<Tz>) -> String {
+    match (entry.kind.start_time(), tz) {
+        (None, _) => format_date(until),
+        (Some(time), Some(tz)) => format_utc(resolve_local(until, time, tz).with_timezone(&Utc)),
+        (Some(time), None) => format_local(until, time),
+    }
+}
+
+/// Formats an ATTENDEE property, quoting the name when it needs to be.
+fn attendee_line(attendee: &Attendee) -> String {
+    let mut line = "ATTENDEE".to_string();
+    if let Some(name) = &attendee.name {
+        let name = name.replace('"', "");
+        if name.contains([':', ';', ',']) {
+            line.push_str(&format!(";CN=\"{name}\""));
+        } else {
+            line.push_str(&format!(";CN={name}"));
+        }
+    }
+    line.push_str(&format!(
+        ";PARTSTAT={}:mailto:{}",
+        attendee.status.as_str().to_ascii_uppercase(),
+        attendee.email
+    ));
+    line
+}
+
+/// Minutes from the entry's start to when a reminder fires (negative before).
+///
+/// Entries without a start time start at midnight.
+fn trigger_minutes(reminder: &Reminder, entry: &CalendarEntry) -> i64 {
+    match reminder {
+        Reminder::Before { minutes } => -i64::from(*minutes),
+        Reminder::DaysBeforeAt { days, time } => {
+            let start = entry.kind.start_time().unwrap_or(NaiveTime::MIN);
+            (*time - start).num_minutes() - i64::from(*days) * 24 * 60
+        }
+    }
+}
+
+/// Formats a DURATION value, e.g. `-PT15M` or `-P1DT15H`.
+fn format_duration(minutes: i64) -> String {
+    let sign = if minutes < 0 { "-" } else { "" };
+    let minutes = minutes.unsigned_abs();
+    let (days, hours, minutes) = (minutes / 1440, minutes % 1440 / 60, minutes % 60);
+
+    let mut value = format!("{sign}P");
+    if days > 0 {
+        value.push_str(&format!("{days}D"));
+    }
+    if hours > 0 || minutes > 0 || days == 0 {
+        value.push('T');
+        if hours > 0 {
+            value.push_str(&format!("{hours}H"));
+        }
+        if minutes > 0 || hours == 0 {
+            value.push_str(&format!("{minutes}M"));
+        }
+    }
+    value
+}
+
+/// Maps a task priority onto the iCalendar 1 (highest) to 9 (lowest) scale.
+fn ics_priority(priority: TaskPriority) -> u8 {
+    match priority {
+        TaskPriority::Urgent => 1,
+        TaskPriority::High => 3,
+        TaskPriority::Normal => 5,
+        TaskPriority::Low => 9,
+    }
+}
+
+/// Formats a DATE value, e.g. `20240115`.
+fn format_date(date: NaiveDate) -> String {
+    date.format("%Y%m%d").to_string()
+}
+
+/// Formats a local or floating DATE-TIME value, e.g. `20240115T093000`.
+fn format_local(date: NaiveDate, time: NaiveTime) -> String {
+    date.and_time(time).format("%Y%m%dT%H%M%S").to_string()
+}
+
+/// Formats a UTC DATE-TIME value, e.g. `20240115T093000Z`.
+fn format_utc(at: DateTime<Utc>) -> String {
+    at.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::{Attendee, ResponseStatus};
+    use chrono::TimeZone;
+    use chrono_tz::Europe::Madrid;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    fn stamp() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 14, 12, 0, 0).unwrap()
+    }
+
+    /// Unfolds the output back into content lines.
+    fn content_lines(ics: &str) -> Vec<String> {
+        ics.replace("\r\n ", "")
+            .split("\r\n")
+            .filter(|l| !l.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_format_ics_events() {
+        let calendar = Calendar::new("Team; Offsite", "#3B82F6").with_timezone(Madrid);
+        let holiday = CalendarEntry::all_day(calendar.id, "Holiday", date(15));
+        let trip = CalendarEntry::multi_day(calendar.id, "Trip", date(15), date(17))
+            .with_location("Lisbon, PT");
+        let meeting = CalendarEntry::timed(calendar.id, "Sync", date(16), time(9, 0), time(10, 30));
+
+        let ics = format_ics(&calendar, &[holiday, trip, meeting.clone()], &[], stamp());
+        let lines = content_lines(&ics);
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(lines.contains(&"X-WR-CALNAME:Team\\; Offsite".to_string()));
+        assert!(lines.contains(&"DTSTART;VALUE=DATE:20240115".to_string()));
+        assert!(lines.contains(&"DTEND;VALUE=DATE:20240116".to_string()));
+        assert!(lines.contains(&"DTEND;VALUE=DATE:20240118".to_string()));
+        assert!(lines.contains(&"LOCATION:Lisbon\\, PT".to_string()));
+        assert!(lines.contains(&format!("UID:{}", meeting.id)));
+        assert!(lines.contains(&"DTSTART;TZID=Europe/Madrid:20240116T090000".to_string()));
+        assert!(lines.contains(&"DTEND;TZID=Europe/Madrid:20240116T103000".to_string()));
+        assert_eq!(lines.iter().filter(|l| *l == "BEGIN:VEVENT").count(), 3);
+    }
+
+    #[test]
+    fn test_format_ics_task() {
+        let calendar = Calendar::new("Chores", "#3B82F6");
+        let task = CalendarEntry::task(calendar.id, "Laundry", date(15), true)
+            .with_due_time(time(18, 0))
+            .with_priority(TaskPriority::High);
+        let open = CalendarEntry::task(calendar.id, "Dishes", date(15), false);
+
+        let ics = format_ics(&calendar, &[task, open], &[], stamp());
+        let lines = content_lines(&ics);
+
+        assert_eq!(lines.iter().filter(|l| *l == "BEGIN:VTODO").count(), 2);
+        assert!(lines.contains(&"DUE:20240115T180000".to_string()));
+        assert!(lines.contains(&"PRIORITY:3".to_string()));
+        assert!(lines.contains(&"STATUS:COMPLETED".to_string()));
+        assert!(lines.contains(&"DUE;VALUE=DATE:20240115".to_string()));
+        assert!(lines.contains(&"STATUS:NEEDS-ACTION".to_string()));
+        assert!(!lines.iter().any(|l| l.starts_with("DTSTART")));
+    }
+
+    #[test]
+    fn test_format_ics_recurrence_and_details() {
+        let calendar = Calendar::new("Work", "#3B82F6").with_timezone(Madrid);
+        let series = CalendarEntry::timed(calendar.id, "Standup", date(1), time(9, 0), time(9, 15))
+            .with_recurrence("FREQ=WEEKLY;UNTIL=20240129".parse().unwrap())
+            .with_reminder(Reminder::minutes_before(15))
+            .with_reminder(Reminder::days_before_at(1, time(18, 0)))
+            .with_attendee(Attendee {
+                name: Some("Lovelace, Ada".to_string()),
+                status: ResponseStatus::Accepted,
+                ..Attendee::new("ada@example.com")
+            });
+        let overrides = vec![
+            OccurrenceOverride::cancelled(series.id, date(8)),
+            OccurrenceOverride::replaced(
+                series.id,
+                date(15),
+                CalendarEntry::timed(calendar.id, "Planning", date(15), time(11, 0), time(12, 0)),
+            ),
+        ];
+
+        let ics = format_ics(
+            &calendar,
+            std::slice::from_ref(&series),
+            &overrides,
+            stamp(),
+        );
+        let lines = content_lines(&ics);
+
+        assert!(lines.contains(&"RRULE:FREQ=WEEKLY;UNTIL=20240129T080000Z".to_string()));
+        assert!(lines.contains(&"EXDATE;TZID=Europe/Madrid:20240108T090000".to_string()));
+        assert!(lines.contains(&"RECURRENCE-ID;TZID=Europe/Madrid:20240115T090000".to_string()));
+        assert_eq!(
+            lines
+                .iter()
+                .filter(|l| **l == format!("UID:{}", series.id))
+                .count(),
+            2
+        );
+        assert!(lines.contains(&"TRIGGER:-PT15M".to_string()));
+        assert!(lines.contains(&"TRIGGER:-PT15H".to_string()));
+        assert!(lines.contains(
+            &"ATTENDEE;CN=\"Lovelace, Ada\";PARTSTAT=ACCEPTED:mailto:ada@example.com".to_string()
+        ));
+    }
+
+    #[test]
+    fn test_fold_line() {
+        let line = format!("DESCRIPTION:{}", "é".repeat(60));
+        let folded = fold_line(&line);
+
+        assert!(folded.split("\r\n").all(|l| l.len() <= MAX_LINE_OCTETS));
+        assert!(folded.split("\r\n").skip(1).all(|l| l.starts_with(' ')));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+        assert_eq!(fold_line("SUMMARY:Short"), "SUMMARY:Short");
+    }
+
+    #[test]
+    fn test_ics_filename() {
+        let calendar = Calendar::new("Team Offsite 2024!", "#3B82F6");
+        assert_eq!(ics_filename(&calendar), "team-offsite-2024.ics");
+        assert_eq!(
+            ics_filename(&Calendar::new("***", "#3B82F6")),
+            "calendar.ics"
+        );
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(0), "PT0M");
+        assert_eq!(format_duration(-15), "-PT15M");
+        assert_eq!(format_duration(-(24 * 60 + 90)), "-P1DT1H30M");
+        assert_eq!(format_duration(-2 * 24 * 60), "-P2D");
+    }
+}
@@ -0,0 +1,1026 @@
+//! iCalendar (RFC 5545) import.
+//!
+//! Parses an iCalendar object into entry requests for one calendar. VEVENTs
+//! become all-day, multi-day or timed entries depending on their DTSTART and
+//! DTEND, and VTODOs become tasks. Recurring components keep their RRULE,
+//! EXDATE lists the occurrences to cancel, and components with a
+//! RECURRENCE-ID replace single occurrences of the series with the same UID.
+//!
+//! Anything that can't be represented is reported instead of failing the
+//! import: unknown properties and components are counted by name, and
+//! components that can't become an entry are listed with the reason.
+//!
+//! Each UID maps to a fixed entry ID per calendar ([`import_entry_id`]), so
+//! importing the same file twice updates the entries instead of duplicating
+//! them.
+
+use std::collections::BTreeMap;
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::attendee::{Attendee, ResponseStatus};
+use super::error::IcsError;
+use super::operations::validate_entry;
+use super::recurrence::RecurrenceRule;
+use super::reminder::Reminder;
+use super::requests::CreateEntryRequest;
+use super::tag::Tag;
+use super::task::TaskPriority;
+use super::timezone::resolve_local;
+use super::types::{CalendarEntry, EntryKind};
+
+/// Properties that are understood but have nothing to map onto.
+const IGNORED_PROPERTIES: &[&str] = &[
+    "UID",
+    "DTSTAMP",
+    "CREATED",
+    "LAST-MODIFIED",
+    "SEQUENCE",
+    "TRANSP",
+    "CLASS",
+    "STATUS",
+    "COMPLETED",
+    "PERCENT-COMPLETE",
+];
+
+/// A component turned into an entry request.
+#[derive(Debug, Clone)]
+pub struct ImportedEntry {
+    /// The component's UID, shared by a series and its replaced occurrences.
+    pub uid: String,
+    pub request: CreateEntryRequest,
+    /// Whether a task was already completed.
+    pub completed: bool,
+    /// Original start date of the occurrence this component replaces.
+    pub recurrence_id: Option<NaiveDate>,
+    /// Start dates of the occurrences the series skips (EXDATE).
+    pub exdates: Vec<NaiveDate>,
+}
+
+impl ImportedEntry {
+    /// Builds the entry with ID `id`.
+    pub fn to_entry(&self, id: Uuid) -> CalendarEntry {
+        let mut entry = self
+            .request
+            .clone()
+            .into_entry()
+            .expect("imported requests are checked when parsed");
+        entry.id = id;
+        if let EntryKind::Task { completed, .. } = &mut entry.kind {
+            *completed = self.completed;
+        }
+        entry
+    }
+}
+
+/// A VEVENT or VTODO that could not be imported.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedComponent {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uid: Option<String>,
+    pub reason: String,
+}
+
+/// The result of parsing an iCalendar object.
+#[derive(Debug, Clone, Default)]
+pub struct IcsImport {
+    pub entries: Vec<ImportedEntry>,
+    pub skipped: Vec<SkippedComponent>,
+    /// How many times each property or component that was left out appeared.
+    pub unsupported: BTreeMap<String, usize>,
+}
+
+/// What an import did, or would do in a dry run, with one entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportAction {
+    Create,
+    Update,
+    Unchanged,
+}
+
+/// One imported entry in an [`ImportReport`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportedItem {
+    pub uid: String,
+    pub id: Uuid,
+    pub title: String,
+    pub start_date: NaiveDate,
+    pub action: ImportAction,
+}
+
+/// Summary of an import.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    /// Cancelled and replaced occurrences of recurring entries.
+    pub overrides: usize,
+    pub entries: Vec<ImportedItem>,
+    pub skipped: Vec<SkippedComponent>,
+    pub unsupported: BTreeMap<String, usize>,
+}
+
+impl ImportReport {
+    /// Records what happened to an entry.
+    pub fn push(&mut self, item: ImportedItem) {
+        match item.action {
+            ImportAction::Create => self.created += 1,
+            ImportAction::Update => self.updated += 1,
+            ImportAction::Unchanged => self.unchanged += 1,
+        }
+        self.entries.push(item);
+    }
+}
+
+/// Returns the ID the entry for `uid` has in calendar `calendar_id`.
+///
+/// The same UID always maps to the same ID within a calendar, and to
+/// different IDs in different calendars.
+pub fn import_entry_id(calendar_id: Uuid, uid: &str) -> Uuid {
+    Uuid::new_v5(&calendar_id, uid.as_bytes())
+}
+
+/// Returns `imported` as an update of `stored`.
+///
+/// What iCalendar doesn't carry - the color and a task's checklist - is kept
+/// from `stored`, along with its version and creation time.
+pub fn reimport_entry(imported: CalendarEntry, stored: &CalendarEntry) -> CalendarEntry {
+    let mut entry = CalendarEntry {
+        color: stored.color.clone(),
+        version: stored.version,
+        created_at: stored.created_at,
+        ..imported
+    };
+    if let (
+        EntryKind::Task { checklist, .. },
+        EntryKind::Task {
+            checklist: kept, ..
+        },
+    ) = (&mut entry.kind, &stored.kind)
+    {
+        checklist.clone_from(kept);
+    }
+    entry
+}
+
+/// Parses an iCalendar object into requests for entries in `calendar_id`.
+///
+/// Fails only when the input isn't an iCalendar object at all.
+pub fn parse_ics(input: &str, calendar_id: Uuid) -> Result<IcsImport, IcsError> {
+    let lines: Vec<ContentLine> = unfold(input)
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| ContentLine::parse(line))
+        .collect::<Result<_, _>>()?;
+
+    let mut lines = lines.into_iter();
+    match lines.next() {
+        Some(line) if line.is_begin("VCALENDAR") => {}
+        _ => return Err(IcsError::NotCalendar),
+    }
+
+    let mut import = IcsImport::default();
+    while let Some(line) = lines.next() {
+        if line.is_end("VCALENDAR") {
+            return Ok(import);
+        }
+        if line.name != "BEGIN" {
+            // Calendar properties (VERSION, PRODID, X-WR-CALNAME...) describe
+            // the file, not the entries
+            continue;
+        }
+        let name = line.value.to_ascii_uppercase();
+        let component = Component::read(&name, &mut lines)?;
+        match name.as_str() {
+            "VEVENT" | "VTODO" => match component.to_entry(calendar_id, &mut import.unsupported) {
+                Ok(entry) => import.entries.push(entry),
+                Err(reason) => import.skipped.push(SkippedComponent {
+                    uid: component.value("UID").map(unescape_text),
+                    reason,
+                }),
+            },
+            // Zones are taken from TZID names, which are IANA names in practice
+            "VTIMEZONE" => {}
+            _ => *import.unsupported.entry(name).or_default() += 1,
+        }
+    }
+    Err(IcsError::Unterminated("VCALENDAR".to_string()))
+}
+
+/// Undoes TEXT escaping (RFC 5545 section 3.3.11).
+pub fn unescape_text(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => text.push('\n'),
+            Some(escaped) => text.push(escaped),
+            None => text.push('\\'),
+        }
+    }
+    text
+}
+
+/// Joins folded lines back into content lines.
+fn unfold(input: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in input.split('\n') {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        match (raw.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(raw.to_string()),
+        }
+    }
+    lines
+}
+
+/// Splits a list value on commas that aren't escaped.
+fn split_list(value: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in value.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            ',' => {
+                parts.push(&value[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&value[start..]);
+    parts
+}
+
+/// A property: `NAME;PARAM=value:value`.
+#[derive(Debug, Clone)]
+struct ContentLine {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl ContentLine {
+    fn parse(line: &str) -> Result<Self, IcsError> {
+        let invalid = || IcsError::InvalidLine(line.chars().take(80).collect());
+
+        // The value starts at the first colon outside a quoted parameter
+        let mut quoted = false;
+        let split = line
+            .char_indices()
+            .find(|(_, c)| {
+                if *c == '"' {
+                    quoted = !quoted;
+                }
+                *c == ':' && !quoted
+            })
+            .map(|(i, _)| i)
+            .ok_or_else(invalid)?;
+        let (head, value) = (&line[..split], &line[split + 1..]);
+
+        let mut parts = Vec::new();
+        let mut start = 0;
+        let mut quoted = false;
+        for (i, c) in head.char_indices() {
+            match c {
+                '"' => quoted = !quoted,
+                ';' if !quoted => {
+                    parts.push(&head[start..i]);
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        parts.push(&head[start..]);
+
+        let name = parts[0].trim().to_ascii_uppercase();
+        if name.is_empty() {
+            return Err(invalid());
+        }
+        let params = parts[1..]
+            .iter()
+            .filter_map(|param| param.split_once('='))
+            .map(|(key, value)| {
+                (
+                    key.trim().to_ascii_uppercase(),
+                    value.trim().trim_matches('"').to_string(),
+                )
+            })
+            .collect();
+
+        Ok(Self {
+            name,
+            params,
+            value: value.to_string(),
+        })
+    }
+
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn is_begin(&self, component: &str) -> bool {
+        self.name == "BEGIN" && self.value.eq_ignore_ascii_case(component)
+    }
+
+    fn is_end(&self, component: &str) -> bool {
+        self.name == "END" && self.value.eq_ignore_ascii_case(component)
+    }
+}
+
+/// A DATE or DATE-TIME value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IcsTime {
+    Date(NaiveDate),
+    /// A time in a zone, or floating when `tz` is `None`.
+    DateTime {
+        at: NaiveDateTime,
+        tz: Option<Tz>,
+    },
+}
+
+impl IcsTime {
+    fn date(&self) -> NaiveDate {
+        match self {
+            Self::Date(date) => *date,
+            Self::DateTime { at, .. } => at.date(),
+        }
+    }
+
+    /// Returns the local date and time in `tz`, converting from this value's
+    /// zone when both are known.
+    fn local_in(&self, tz: Option<Tz>) -> NaiveDateTime {
+        match (self, tz) {
+            (Self::Date(date), _) => date.and_time(NaiveTime::MIN),
+            (Self::DateTime { at, tz: Some(from) }, Some(to)) if *from != to => {
+                resolve_local(at.date(), at.time(), *from)
+                    .with_timezone(&to)
+                    .naive_local()
+            }
+            (Self::DateTime { at, .. }, _) => *at,
+        }
+    }
+}
+
+/// Parses one DATE or DATE-TIME value of `line`.
+fn parse_time(value: &str, line: &ContentLine) -> Result<IcsTime, String> {
+    let value = value.trim();
+    let invalid = || format!("invalid {} value {value:?}", line.name);
+    if line.param("VALUE") == Some("DATE") || value.len() == 8 {
+        return NaiveDate::parse_from_str(value, "%Y%m%d")
+            .map(IcsTime::Date)
+            .map_err(|_| invalid());
+    }
+
+    let (local, utc) = match value.strip_suffix(['Z', 'z']) {
+        Some(local) => (local, true),
+        None => (value, false),
+    };
+    let at = NaiveDateTime::parse_from_str(local, "%Y%m%dT%H%M%S").map_err(|_| invalid())?;
+    let tz = match (utc, line.param("TZID")) {
+        (true, _) => Some(Tz::UTC),
+        (false, Some(name)) => Some(
+            name.trim_start_matches('/')
+                .parse()
+                .map_err(|_| format!("unknown time zone {name:?}"))?,
+        ),
+        (false, None) => None,
+    };
+    Ok(IcsTime::DateTime { at, tz })
+}
+
+/// Parses a DURATION value such as `-PT15M` or `P1DT2H`.
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let value = value.strip_prefix(['P', 'p'])?;
+
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    let mut in_time = false;
+    for c in value.chars() {
+        match c.to_ascii_uppercase() {
+            digit @ '0'..='9' => number.push(digit),
+            'T' => in_time = true,
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total += match (unit, in_time) {
+                    ('W', false) => Duration::weeks(n),
+                    ('D', false) => Duration::days(n),
+                    ('H', true) => Duration::hours(n),
+                    ('M', true) => Duration::minutes(n),
+                    ('S', true) => Duration::seconds(n),
+                    _ => return None,
+                };
+            }
+        }
+    }
+    if !number.is_empty() {
+        return None;
+    }
+    Some(if negative { -total } else { total })
+}
+
+/// Maps an iCalendar 1 (highest) to 9 (lowest) priority onto a task priority.
+fn task_priority(value: &str) -> TaskPriority {
+    match value.trim().parse::<u8>() {
+        Ok(1) => TaskPriority::Urgent,
+        Ok(2..=4) => TaskPriority::High,
+        Ok(6..=9) => TaskPriority::Low,
+        _ => TaskPriority::Normal,
+    }
+}
+
+/// Turns a category into a tag, replacing spaces with dashes.
+fn category_tag(category: &str) -> Option<Tag> {
+    let category = unescape_text(category);
+    category
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .parse()
+        .ok()
+}
+
+/// A VEVENT or VTODO with its alarms.
+struct Component {
+    name: String,
+    properties: Vec<ContentLine>,
+    alarms: Vec<Vec<ContentLine>>,
+}
+
+impl Component {
+    /// Reads a component's lines up to its END, keeping alarms and skipping
+    /// any other nested component.
+    fn read(name: &str, lines: &mut impl Iterator<Item = ContentLine>) -> Result<Self, IcsError> {
+        let mut component = Self {
+            name: name.to_string(),
+            properties: Vec::new(),
+            alarms: Vec::new(),
+        };
+        let unterminated = || IcsError::Unterminated(name.to_string());
+
+        while let Some(line) = lines.next() {
+            if line.is_end(name) {
+                return Ok(component);
+            }
+            if line.name != "BEGIN" {
+                component.properties.push(line);
+                continue;
+            }
+            let nested = line.value.to_ascii_uppercase();
+            let mut alarm = Vec::new();
+            loop {
+                let line = lines.next().ok_or_else(unterminated)?;
+                if line.is_end(&nested) {
+                    break;
+                }
+                alarm.push(line);
+            }
+            if nested == "VALARM" {
+                component.alarms.push(alarm);
+            }
+        }
+        Err(unterminated())
+    }
+
+    fn property(&self, name: &str) -> Option<&ContentLine> {
+        self.properties.iter().find(|line| line.name == name)
+    }
+
+    fn value(&self, name: &str) -> Option<&str> {
+        self.property(name).map(|line| line.value.as_str())
+    }
+
+    fn time(&self, name: &str) -> Result<Option<IcsTime>, String> {
+        self.property(name)
+            .map(|line| parse_time(&line.value, line))
+            .transpose()
+    }
+
+    /// Builds the entry request for this component, counting the properties
+    /// it leaves out in `unsupported`.
+    fn to_entry(
+        &self,
+        calendar_id: Uuid,
+        unsupported: &mut BTreeMap<String, usize>,
+    ) -> Result<ImportedEntry, String> {
+        let mut unsupported_property =
+            |name: &str| *unsupported.entry(name.to_string()).or_default() += 1;
+        let title = self
+            .value("SUMMARY")
+            .map(unescape_text)
+            .filter(|title| !title.trim().is_empty())
+            .unwrap_or_else(|| "Untitled".to_string());
+        let start = self.time("DTSTART")?;
+
+        let (mut request, tz, completed) = if self.name == "VTODO" {
+            let due = self.time("DUE")?;
+            let on = due.or(start).ok_or("missing DUE or DTSTART")?;
+            let mut request = CreateEntryRequest::task(calendar_id, &title, on.date());
+            let tz = match on {
+                IcsTime::DateTime { at, tz } => {
+                    request = request.with_due_time(at.time());
+                    tz
+                }
+                IcsTime::Date(_) => None,
+            };
+            if let Some(priority) = self.value("PRIORITY") {
+                request = request.with_priority(task_priority(priority));
+            }
+            let completed = self
+                .value("STATUS")
+                .is_some_and(|s| s.eq_ignore_ascii_case("COMPLETED"))
+                || self.property("COMPLETED").is_some();
+            (request, tz, completed)
+        } else {
+            if self
+                .value("STATUS")
+                .is_some_and(|s| s.eq_ignore_ascii_case("CANCELLED"))
+            {
+                return Err("event is cancelled".to_string());
+            }
+            let start = start.ok_or("missing DTSTART")?;
+            let end = self.time("DTEND")?;
+            let duration = match self.value("DURATION") {
+                Some(value) => Some(parse_duration(value).ok_or("invalid DURATION")?),
+                None => None,
+            };
+            match start {
+                IcsTime::Date(date) => {
+                    // DTEND is exclusive for dates
+                    let last = match (end, duration) {
+                        (Some(end), _) => end.date() - Duration::days(1),
+                        (None, Some(duration)) => date + duration - Duration::days(1),
+                        (None, None) => date,
+                    };
+                    let request = if last > date {
+                        CreateEntryRequest::multi_day(calendar_id, &title, date, last)
+                    } else {
+                        CreateEntryRequest::all_day(calendar_id, &title, date)
+                    };
+                    (request, None, false)
+                }
+                IcsTime::DateTime { at, tz } => {
+                    let end = match (end, duration) {
+                        (Some(end), _) => end.local_in(tz),
+                        (None, Some(duration)) => at + duration,
+                        (None, None) => at,
+                    };
+                    let request = CreateEntryRequest::timed(
+                        calendar_id,
+                        &title,
+                        at.date(),
+                        at.time(),
+                        end.time(),
+                    )
+                    .with_end_date(end.date());
+                    (request, tz, false)
+                }
+            }
+        };
+        if let Some(tz) = tz {
+            request = request.with_timezone(tz);
+        }
+
+        for line in &self.properties {
+            match line.name.as_str() {
+                "SUMMARY" | "DTSTART" | "DTEND" | "DUE" | "DURATION" | "RRULE" | "EXDATE"
+                | "RECURRENCE-ID" | "CATEGORIES" | "ATTENDEE" => {}
+                "PRIORITY" if self.name == "VTODO" => {}
+                "DESCRIPTION" => request = request.with_description(unescape_text(&line.value)),
+                "LOCATION" => request = request.with_location(unescape_text(&line.value)),
+                name if IGNORED_PROPERTIES.contains(&name) => {}
+                name => unsupported_property(name),
+            }
+        }
+
+        if let Some(line) = self.property("RRULE") {
+            let mut rule: RecurrenceRule = line
+                .value
+                .parse()
+                .map_err(|e| format!("unsupported RRULE: {e}"))?;
+            // A UTC UNTIL names the last start in UTC; keep its local date
+            if let (Some(until), Some(tz)) = (rrule_until(&line.value), tz) {
+                rule.until = Some(
+                    IcsTime::DateTime {
+                        at: until,
+                        tz: Some(Tz::UTC),
+                    }
+                    .local_in(Some(tz))
+                    .date(),
+                );
+            }
+            request = request.with_recurrence(rule);
+        }
+
+        let occurrence_date = |line: &ContentLine, value: &str| -> Result<NaiveDate, String> {
+            Ok(parse_time(value, line)?.local_in(tz).date())
+        };
+        let mut exdates = Vec::new();
+        for line in self.properties.iter().filter(|l| l.name == "EXDATE") {
+            for value in split_list(&line.value) {
+                exdates.push(occurrence_date(line, value)?);
+            }
+        }
+        let recurrence_id = self
+            .property("RECURRENCE-ID")
+            .map(|line| occurrence_date(line, &line.value))
+            .transpose()?;
+
+        for line in self.properties.iter().filter(|l| l.name == "CATEGORIES") {
+            for category in split_list(&line.value) {
+                match category_tag(category) {
+                    Some(tag) => request = request.with_tag(tag),
+                    None => unsupported_property("CATEGORIES"),
+                }
+            }
+        }
+
+        for line in self.properties.iter().filter(|l| l.name == "ATTENDEE") {
+            let email = line
+                .value
+                .get(..7)
+                .filter(|scheme| scheme.eq_ignore_ascii_case("mailto:"))
+                .map(|_| &line.value[7..]);
+            let Some(mut attendee) = email.map(Attendee::new).filter(|a| a.validate().is_ok())
+            else {
+                unsupported_property("ATTENDEE");
+                continue;
+            };
+            attendee.name = line.param("CN").map(str::to_string);
+            if let Some(status) = line
+                .param("PARTSTAT")
+                .and_then(|s| s.parse::<ResponseStatus>().ok())
+            {
+                attendee.status = status;
+            }
+            if request
+                .attendees
+                .as_ref()
+                .is_none_or(|attendees| attendees.iter().all(|a| a.email != attendee.email))
+            {
+                request = request.with_attendee(attendee);
+            }
+        }
+
+        for alarm in &self.alarms {
+            match alarm_reminder(alarm) {
+                Some(reminder) => request = request.with_reminder(reminder),
+                None => unsupported_property("VALARM"),
+            }
+        }
+
+        if recurrence_id.is_some() {
+            // A replaced occurrence doesn't repeat on its own
+            request.recurrence = None;
+        }
+        let entry = request
+            .clone()
+            .into_entry()
+            .ok_or("missing required fields")?;
+        validate_entry(&entry).map_err(|e| e.to_string())?;
+
+        Ok(ImportedEntry {
+            uid: self
+                .value("UID")
+                .map(unescape_text)
+                .unwrap_or_else(|| fallback_uid(&request)),
+            request,
+            completed,
+            recurrence_id,
+            exdates,
+        })
+    }
+}
+
+/// Returns the UNTIL of an RRULE value when it is a UTC date-time.
+fn rrule_until(rrule: &str) -> Option<NaiveDateTime> {
+    rrule
+        .split(';')
+        .filter_map(|part| part.split_once('='))
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case("UNTIL"))
+        .and_then(|(_, value)| value.trim().strip_suffix(['Z', 'z']))
+        .and_then(|value| NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok())
+}
+
+/// Turns an alarm that fires a while before the start into a reminder.
+fn alarm_reminder(alarm: &[ContentLine]) -> Option<Reminder> {
+    let trigger = alarm.iter().find(|line| line.name == "TRIGGER")?;
+    if trigger.param("VALUE") == Some("DATE-TIME") || trigger.param("RELATED") == Some("END") {
+        return None;
+    }
+    let offset = parse_duration(&trigger.value)?;
+    if offset > Duration::zero() {
+        return None;
+    }
+    let reminder = Reminder::minutes_before(u32::try_from(-offset.num_minutes()).ok()?);
+    reminder.validate().ok()?;
+    Some(reminder)
+}
+
+/// A stable UID for a component without one, so re-imports still match it.
+fn fallback_uid(request: &CreateEntryRequest) -> String {
+    format!("{}-{}", request.start_date.format("%Y%m%d"), request.title)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::{format_ics, Calendar, ChecklistItem, Frequency};
+    use chrono::Utc;
+    use chrono_tz::Europe::Madrid;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    /// Wraps components in a VCALENDAR with CRLF line endings.
+    fn calendar(body: &str) -> String {
+        format!("BEGIN:VCALENDAR\nVERSION:2.0\nPRODID:-//Test//EN\n{body}END:VCALENDAR\n")
+            .replace('\n', "\r\n")
+    }
+
+    #[test]
+    fn test_parse_ics_events() {
+        let calendar_id = Uuid::new_v4();
+        let ics = calendar(
+            "BEGIN:VEVENT\n\
+             UID:holiday\n\
+             DTSTART;VALUE=DATE:20240115\n\
+             DTEND;VALUE=DATE:20240116\n\
+             SUMMARY:Holiday\n\
+             END:VEVENT\n\
+             BEGIN:VEVENT\n\
+             UID:trip\n\
+             DTSTART;VALUE=DATE:20240115\n\
+             DTEND;VALUE=DATE:20240118\n\
+             SUMMARY:Trip\n\
+             LOCATION:Lisbon\\, PT\n\
+             END:VEVENT\n\
+             BEGIN:VEVENT\n\
+             UID:meeting\n\
+             DTSTART;TZID=Europe/Madrid:20240116T090000\n\
+             DTEND:20240116T093000Z\n\
+             SUMMARY:Planning\n\
+             DESCRIPTION:Agenda:\\n- budget\n\
+             END:VEVENT\n",
+        );
+
+        let import = parse_ics(&ics, calendar_id).unwrap();
+        assert!(import.skipped.is_empty());
+        assert!(import.unsupported.is_empty());
+        assert_eq!(import.entries.len(), 3);
+
+        let holiday = import.entries[0].request.clone().into_entry().unwrap();
+        assert_eq!(holiday.calendar_id, calendar_id);
+        assert_eq!(holiday.kind, EntryKind::AllDay);
+        assert_eq!(holiday.end_date, date(15));
+
+        let trip = &import.entries[1];
+        assert_eq!(trip.uid, "trip");
+        assert_eq!(trip.request.end_date, Some(date(17)));
+        assert_eq!(trip.request.location.as_deref(), Some("Lisbon, PT"));
+
+        let meeting = &import.entries[2].request;
+        assert_eq!(meeting.timezone, Some(Madrid));
+        assert_eq!(meeting.start_time, Some(time(9, 0)));
+        // 09:30 UTC is 10:30 in Madrid
+        assert_eq!(meeting.end_time, Some(time(10, 30)));
+        assert_eq!(meeting.description.as_deref(), Some("Agenda:\n- budget"));
+    }
+
+    #[test]
+    fn test_parse_ics_tasks() {
+        let ics = calendar(
+            "BEGIN:VTODO\n\
+             UID:laundry\n\
+             DUE:20240115T180000\n\
+             SUMMARY:Laundry\n\
+             PRIORITY:2\n\
+             STATUS:COMPLETED\n\
+             END:VTODO\n\
+             BEGIN:VTODO\n\
+             UID:dishes\n\
+             DTSTART;VALUE=DATE:20240116\n\
+             SUMMARY:Dishes\n\
+             END:VTODO\n",
+        );
+
+        let import = parse_ics(&ics, Uuid::new_v4()).unwrap();
+        let laundry = &import.entries[0];
+        assert!(laundry.completed);
+        assert_eq!(laundry.request.start_date, date(15));
+        assert_eq!(laundry.request.due_time, Some(time(18, 0)));
+        assert_eq!(laundry.request.priority, Some(TaskPriority::High));
+
+        let dishes = &import.entries[1];
+        assert!(!dishes.completed);
+        assert_eq!(dishes.request.start_date, date(16));
+        assert_eq!(dishes.request.due_time, None);
+    }
+
+    #[test]
+    fn test_parse_ics_recurrence() {
+        let ics = calendar(
+            "BEGIN:VEVENT\n\
+             UID:standup\n\
+             DTSTART;TZID=Europe/Madrid:20240101T090000\n\
+             DURATION:PT15M\n\
+             RRULE:FREQ=WEEKLY;UNTIL=20240129T080000Z\n\
+             EXDATE;TZID=Europe/Madrid:20240108T090000,20240115T090000\n\
+             SUMMARY:Standup\n\
+             END:VEVENT\n\
+             BEGIN:VEVENT\n\
+             UID:standup\n\
+             RECURRENCE-ID;TZID=Europe/Madrid:20240122T090000\n\
+             DTSTART;TZID=Europe/Madrid:20240123T100000\n\
+             DTEND;TZID=Europe/Madrid:20240123T101500\n\
+             SUMMARY:Standup (moved)\n\
+             END:VEVENT\n",
+        );
+
+        let import = parse_ics(&ics, Uuid::new_v4()).unwrap();
+        let series = &import.entries[0];
+        let rule = series.request.recurrence.as_ref().unwrap();
+        assert_eq!(rule.frequency, Frequency::Weekly);
+        assert_eq!(rule.until, Some(date(29)));
+        assert_eq!(series.request.end_time, Some(time(9, 15)));
+        assert_eq!(series.exdates, vec![date(8), date(15)]);
+
+        let moved = &import.entries[1];
+        assert_eq!(moved.uid, "standup");
+        assert_eq!(moved.recurrence_id, Some(date(22)));
+        assert_eq!(moved.request.start_date, date(23));
+        assert!(moved.request.recurrence.is_none());
+    }
+
+    #[test]
+    fn test_parse_ics_details_and_report() {
+        let ics = calendar(
+            "BEGIN:VEVENT\n\
+             UID:review\n\
+             DTSTART:20240115T140000Z\n\
+             DTEND:20240115T150000Z\n\
+             SUMMARY:Review\n\
+             CATEGORIES:Work,Q1 Planning\n\
+             ATTENDEE;CN=\"Lovelace, Ada\";PARTSTAT=ACCEPTED:mailto:ada@example.com\n\
+             ATTENDEE:urn:uuid:room-1\n\
+             X-MICROSOFT-CDO-BUSYSTATUS:BUSY\n\
+             URL:https://example.com\n\
+             BEGIN:VALARM\n\
+             ACTION:DISPLAY\n\
+             TRIGGER:-PT15M\n\
+             END:VALARM\n\
+             BEGIN:VALARM\n\
+             ACTION:DISPLAY\n\
+             TRIGGER;VALUE=DATE-TIME:20240115T120000Z\n\
+             END:VALARM\n\
+             END:VEVENT\n\
+             BEGIN:VEVENT\n\
+             UID:cancelled\n\
+             DTSTART;VALUE=DATE:20240116\n\
+             STATUS:CANCELLED\n\
+             END:VEVENT\n\
+             BEGIN:VEVENT\n\
+             UID:undated\n\
+             SUMMARY:Someday\n\
+             END:VEVENT\n\
+             BEGIN:VJOURNAL\n\
+             UID:notes\n\
+             END:VJOURNAL\n",
+        );
+
+        let import = parse_ics(&ics, Uuid::new_v4()).unwrap();
+        assert_eq!(import.entries.len(), 1);
+        let review = &import.entries[0].request;
+        assert_eq!(review.timezone, Some(Tz::UTC));
+        let tags: Vec<_> = review.tags.iter().flatten().map(Tag::as_str).collect();
+        assert_eq!(tags, vec!["q1-planning", "work"]);
+        let ada = &review.attendees.as_ref().unwrap()[0];
+        assert_eq!(ada.email, "ada@example.com");
+        assert_eq!(ada.name.as_deref(), Some("Lovelace, Ada"));
+        assert_eq!(ada.status, ResponseStatus::Accepted);
+        assert_eq!(review.reminders, Some(vec![Reminder::minutes_before(15)]));
+
+        let skipped: Vec<_> = import.skipped.iter().map(|s| s.uid.as_deref()).collect();
+        assert_eq!(skipped, vec![Some("cancelled"), Some("undated")]);
+        let unsupported: Vec<_> = import.unsupported.into_iter().collect();
+        assert_eq!(
+            unsupported,
+            vec![
+                ("ATTENDEE".to_string(), 1),
+                ("URL".to_string(), 1),
+                ("VALARM".to_string(), 1),
+                ("VJOURNAL".to_string(), 1),
+                ("X-MICROSOFT-CDO-BUSYSTATUS".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_ics_unfolds_lines() {
+        let ics = "BEGIN:VCALENDAR\nBEGIN:VEVENT\nUID:a\nDTSTART;VALUE=DATE:20240115\n\
+                   SUMMARY:A very long\n  title\nEND:VEVENT\nEND:VCALENDAR";
+
+        let import = parse_ics(ics, Uuid::new_v4()).unwrap();
+        assert_eq!(import.entries[0].request.title, "A very long title");
+    }
+
+    #[test]
+    fn test_parse_ics_errors() {
+        let id = Uuid::new_v4();
+        assert_eq!(parse_ics("", id).unwrap_err(), IcsError::NotCalendar);
+        assert_eq!(
+            parse_ics("BEGIN:VEVENT\r\nEND:VEVENT\r\n", id).unwrap_err(),
+            IcsError::NotCalendar
+        );
+        assert_eq!(
+            parse_ics("BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:a\r\n", id).unwrap_err(),
+            IcsError::Unterminated("VEVENT".to_string())
+        );
+    }
+
+    #[test]
+    fn test_export_round_trip() {
+        let calendar = Calendar::new("Work", "#3B82F6").with_timezone(Madrid);
+        let entries = vec![
+            CalendarEntry::multi_day(calendar.id, "Trip", date(15), date(17)),
+            CalendarEntry::timed(calendar.id, "Standup", date(1), time(9, 0), time(9, 15))
+                .with_recurrence("FREQ=WEEKLY;UNTIL=20240129".parse().unwrap())
+                .with_timezone(Madrid)
+                .with_reminder(Reminder::minutes_before(15)),
+            CalendarEntry::task(calendar.id, "Laundry", date(15), true)
+                .with_due_time(time(18, 0))
+                .with_timezone(Madrid)
+                .with_priority(TaskPriority::High),
+            CalendarEntry::task(calendar.id, "Dishes", date(16), false),
+        ];
+
+        let ics = format_ics(&calendar, &entries, &[], Utc::now());
+        let import = parse_ics(&ics, calendar.id).unwrap();
+        assert!(import.skipped.is_empty());
+        assert!(import.unsupported.is_empty());
+
+        for (original, imported) in entries.iter().zip(&import.entries) {
+            assert_eq!(imported.uid, original.id.to_string());
+            let entry = imported.to_entry(original.id);
+            assert_eq!(entry.kind.is_completed(), original.kind.is_completed());
+            assert_eq!(
+                crate::calendar::changed_fields(original, &entry),
+                Vec::<&str>::new(),
+                "{}",
+                original.title
+            );
+        }
+    }
+
+    #[test]
+    fn test_reimport_entry_keeps_local_fields() {
+        let calendar_id = Uuid::new_v4();
+        let mut stored = CalendarEntry::task(calendar_id, "Laundry", date(15), false)
+            .with_color("#10B981")
+            .with_checklist_item(ChecklistItem::new("Whites"));
+        stored.version = 3;
+        let imported = CalendarEntry::task(calendar_id, "Laundry", date(16), true);
+
+        let entry = reimport_entry(imported, &stored);
+        assert_eq!(entry.start_date, date(16));
+        assert!(entry.kind.is_completed());
+        assert_eq!(entry.color.as_deref(), Some("#10B981"));
+        assert_eq!(entry.kind.checklist(), stored.kind.checklist());
+        assert_eq!(entry.version, 3);
+        assert_eq!(entry.created_at, stored.created_at);
+    }
+
+    #[test]
+    fn test_import_entry_id() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        assert_eq!(import_entry_id(a, "uid-1"), import_entry_id(a, "uid-1"));
+        assert_ne!(import_entry_id(a, "uid-1"), import_entry_id(a, "uid-2"));
+        assert_ne!(import_entry_id(a, "uid-1"), import_entry_id(b, "uid-1"));
+    }
+}
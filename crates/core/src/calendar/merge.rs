@@ -1,8 +1,12 @@
-//! Last-Write-Wins (LWW) merge strategy for calendar entries.
+//! Merge strategies for calendar entries.
 //!
-//! This module provides pure functions for merging concurrent updates to calendar entries
-//! using the Last-Write-Wins conflict resolution strategy. The entry with the more recent
-//! `updated_at` timestamp wins.
+//! This module provides pure functions for merging concurrent updates to calendar entries:
+//!
+//! - **Three-way merge**: given the version both sides started from, each field is merged
+//!   on its own, so edits to different fields never overwrite each other. Fields both sides
+//!   changed differently are reported as conflicts.
+//! - **Last-Write-Wins (LWW)**: without a common base, the entry with the more recent
+//!   `updated_at` timestamp wins as a whole.
 //!
 //! This is part of the Functional Core - all functions are pure with no side effects.
 
@@ -86,6 +90,148 @@ pub fn merge_entry(server: &CalendarEntry, client: &CalendarEntry) -> MergeResul
     }
 }
 
+/// Result of a three-way merge of two edits to the same entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreeWayMerge {
+    /// The server entry with every cleanly merged client change applied.
+    /// Conflicting fields keep the server's value.
+    pub entry: CalendarEntry,
+    /// Fields both sides changed to different values, by their JSON names.
+    pub conflicts: Vec<&'static str>,
+}
+
+impl ThreeWayMerge {
+    /// Returns true if every field merged cleanly.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Merges a client's edit into the server's entry, given the version the client
+/// started from.
+///
+/// Each user-editable field is merged on its own: a field only the client
+/// changed takes the client's value, a field only the server changed keeps the
+/// server's, and a field both changed to the same value is not a conflict.
+/// Identity, timestamps and occurrence details come from the server entry; the
+/// caller sets `updated_at`.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use uuid::Uuid;
+/// use calendsync_core::calendar::{three_way_merge, CalendarEntry};
+///
+/// let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+/// let base = CalendarEntry::all_day(Uuid::new_v4(), "Standup", date);
+/// let server = base.clone().with_location("Room 1");
+/// let mut client = base.clone();
+/// client.title = "Daily standup".to_string();
+///
+/// let merged = three_way_merge(&base, &server, &client);
+/// assert!(merged.is_clean());
+/// assert_eq!(merged.entry.title, "Daily standup");
+/// assert_eq!(merged.entry.location.as_deref(), Some("Room 1"));
+/// ```
+pub fn three_way_merge(
+    base: &CalendarEntry,
+    server: &CalendarEntry,
+    client: &CalendarEntry,
+) -> ThreeWayMerge {
+    let mut entry = server.clone();
+    let mut conflicts = Vec::new();
+
+    let c = &mut conflicts;
+    merge_field(
+        "calendar_id",
+        &base.calendar_id,
+        &client.calendar_id,
+        &mut entry.calendar_id,
+        c,
+    );
+    merge_field("title", &base.title, &client.title, &mut entry.title, c);
+    merge_field(
+        "description",
+        &base.description,
+        &client.description,
+        &mut entry.description,
+        c,
+    );
+    merge_field(
+        "location",
+        &base.location,
+        &client.location,
+        &mut entry.location,
+        c,
+    );
+    merge_field("kind", &base.kind, &client.kind, &mut entry.kind, c);
+    merge_field(
+        "start_date",
+        &base.start_date,
+        &client.start_date,
+        &mut entry.start_date,
+        c,
+    );
+    merge_field(
+        "end_date",
+        &base.end_date,
+        &client.end_date,
+        &mut entry.end_date,
+        c,
+    );
+    merge_field("color", &base.color, &client.color, &mut entry.color, c);
+    merge_field(
+        "timezone",
+        &base.timezone,
+        &client.timezone,
+        &mut entry.timezone,
+        c,
+    );
+    merge_field(
+        "recurrence",
+        &base.recurrence,
+        &client.recurrence,
+        &mut entry.recurrence,
+        c,
+    );
+    merge_field(
+        "reminders",
+        &base.reminders,
+        &client.reminders,
+        &mut entry.reminders,
+        c,
+    );
+    merge_field(
+        "attendees",
+        &base.attendees,
+        &client.attendees,
+        &mut entry.attendees,
+        c,
+    );
+    merge_field("tags", &base.tags, &client.tags, &mut entry.tags, c);
+
+    ThreeWayMerge { entry, conflicts }
+}
+
+/// Merges one field; `merged` holds the server's value on entry.
+fn merge_field<T: Clone + PartialEq>(
+    field: &'static str,
+    base: &T,
+    client: &T,
+    merged: &mut T,
+    conflicts: &mut Vec<&'static str>,
+) {
+    if client == base || client == merged {
+        return;
+    }
+    if merged == base {
+        *merged = client.clone();
+    } else {
+        conflicts.push(field);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -159,4 +305,53 @@ mod tests {
 
         assert_eq!(result, cloned);
     }
+
+    fn base_entry() -> CalendarEntry {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        CalendarEntry::all_day(Uuid::new_v4(), "Standup", date)
+    }
+
+    #[test]
+    fn test_three_way_merge_combines_disjoint_edits() {
+        let base = base_entry();
+        let server = base.clone().with_location("Room 1");
+        let mut client = base.clone();
+        client.title = "Daily standup".to_string();
+
+        let merged = three_way_merge(&base, &server, &client);
+
+        assert!(merged.is_clean());
+        assert_eq!(merged.entry.title, "Daily standup");
+        assert_eq!(merged.entry.location.as_deref(), Some("Room 1"));
+        assert_eq!(merged.entry.id, server.id);
+    }
+
+    #[test]
+    fn test_three_way_merge_same_change_is_not_a_conflict() {
+        let base = base_entry();
+        let server = base.clone().with_location("Room 1");
+        let client = base.clone().with_location("Room 1");
+
+        let merged = three_way_merge(&base, &server, &client);
+
+        assert!(merged.is_clean());
+        assert_eq!(merged.entry.location.as_deref(), Some("Room 1"));
+    }
+
+    #[test]
+    fn test_three_way_merge_reports_conflicts() {
+        let base = base_entry();
+        let mut server = base.clone().with_location("Room 1");
+        server.title = "Team sync".to_string();
+        let mut client = base.clone().with_location("Room 2");
+        client.color = Some("#EF4444".to_string());
+
+        let merged = three_way_merge(&base, &server, &client);
+
+        assert_eq!(merged.conflicts, vec!["location"]);
+        // Conflicting fields keep the server's value; the rest merge
+        assert_eq!(merged.entry.location.as_deref(), Some("Room 1"));
+        assert_eq!(merged.entry.title, "Team sync");
+        assert_eq!(merged.entry.color.as_deref(), Some("#EF4444"));
+    }
 }
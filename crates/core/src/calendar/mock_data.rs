@@ -218,9 +218,6 @@ mod tests {
             }),
             "timed"
         );
-        assert_eq!(
-            format_entry_kind(&EntryKind::Task { completed: false }),
-            "task"
-        );
+        assert_eq!(format_entry_kind(&EntryKind::task(false)), "task");
     }
 }
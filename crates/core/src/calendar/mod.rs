@@ -1,27 +1,102 @@
+mod attendee;
+mod batch;
+mod duplicate;
 mod error;
+mod feed;
+mod freebusy;
+mod ics;
+mod ics_import;
 mod merge;
 mod mock_data;
+mod occurrence;
 mod operations;
+mod recurrence;
+mod reminder;
 mod requests;
+mod revision;
+mod search;
 mod sorting;
+mod subscription;
+mod tag;
+mod task;
+mod timezone;
+mod trash;
 mod types;
+mod version;
 
-pub use error::{CalendarError, EntryError};
-pub use merge::{merge_entry, MergeResult};
+pub use attendee::{
+    find_attendee, format_attendees, merge_attendees, parse_attendees, respond, validate_attendees,
+    Attendee, ResponseStatus,
+};
+pub use batch::{validate_batch, EntryWrite, MAX_BATCH_SIZE};
+pub use duplicate::{
+    duplicate_calendar, duplicate_contents, validate_shift, CalendarCopy, DuplicateOptions,
+    DuplicateSummary, MAX_SHIFT_DAYS,
+};
+pub use error::{
+    AttendeeError, BatchError, CalendarError, ConflictError, DuplicateError, EntryError,
+    FreeBusyError, IcsError, MergeError, RecurrenceError, ReminderError, RevisionError,
+    SearchError, SubscriptionError, TagError, TaskError,
+};
+pub use feed::{
+    feed_path, generate_feed_token, parse_feed_file, webcal_url, FeedSubscription, FeedToken,
+    FEED_TOKEN_LENGTH,
+};
+pub use freebusy::{
+    entry_busy_interval, format_vfreebusy, free_busy, freebusy_search_range, merge_busy_intervals,
+    range_window, validate_freebusy_range, BusyEntry, BusyInterval, FreeBusy, MAX_FREEBUSY_DAYS,
+};
+pub use ics::{escape_text, fold_line, format_ics, ics_filename, ICS_CONTENT_TYPE};
+pub use ics_import::{
+    import_entry_id, parse_ics, reimport_entry, unescape_text, IcsImport, ImportAction,
+    ImportReport, ImportedEntry, ImportedItem, SkippedComponent,
+};
+pub use merge::{merge_entry, three_way_merge, MergeResult, ThreeWayMerge};
 pub use mock_data::{format_entry_kind, generate_seed_entries};
+pub use occurrence::{
+    is_occurrence, occurrence_of, split_series, truncate_series, OccurrenceOverride,
+    RecurrenceScope,
+};
 pub use operations::{
-    filter_entries, filter_entries_by_calendar, filter_entries_by_date_range, validate_calendar,
-    validate_entry,
+    filter_entries, filter_entries_by_calendar, filter_entries_by_date_range, find_conflicts,
+    validate_calendar, validate_entry, ConflictScope,
+};
+pub use recurrence::{expand_recurring_entries, ByDay, Frequency, RecurrenceRule};
+pub use reminder::{
+    due_reminders, format_reminders, parse_reminders, reminder_search_range, DueReminder, Reminder,
+    MAX_REMINDER_LEAD_DAYS,
 };
 pub use requests::{
-    CreateCalendarRequest, CreateEntryRequest, EntryType, ListEntriesQuery, UpdateCalendarRequest,
-    UpdateEntryRequest,
+    CreateCalendarRequest, CreateEntryRequest, DuplicateCalendarRequest, EntryType,
+    ListEntriesQuery, UpdateCalendarRequest, UpdateEntryRequest,
+};
+pub use revision::{
+    changed_fields, next_revision_number, revert_to, EntryRevision, RevisionAction,
+};
+pub use search::{
+    entry_search_terms, rank_entries, tokenize, Highlight, SearchField, SearchHit, SearchQuery,
+    Snippet, DEFAULT_SEARCH_LIMIT, MAX_SEARCH_LIMIT, MAX_SEARCH_TERMS,
 };
 pub use sorting::{
     build_day_data, expand_multi_day_entries, get_calendar_week, get_week_dates,
-    group_entries_by_date, sort_entries_by_hierarchy,
+    group_entries_by_date, segment_entry, sort_entries_by_hierarchy,
+};
+pub use subscription::{
+    check_subscription_host, is_public_address, normalize_subscription_url, overrides_changed,
+    plan_subscription_sync, subscription_entry_ids, CalendarSubscription, RefreshReport,
+    SubscriptionSync, MAX_SUBSCRIPTION_URL_LENGTH,
+};
+pub use tag::{format_tags, matches_any_tag, parse_tags, Tag, MAX_TAG_LENGTH};
+pub use task::{
+    compare_tasks, format_checklist, parse_checklist, roll_over_tasks, rolls_over_to,
+    toggle_checklist_item, ChecklistItem, TaskPriority,
+};
+pub use timezone::{
+    convert_entry, entry_timezone, localize_entries, localize_entry, resolve_local,
 };
+pub use trash::{purge_cutoff, Trash, TrashedCalendar, TrashedEntry, DEFAULT_TRASH_RETENTION_DAYS};
 pub use types::{
     Calendar, CalendarEntry, CalendarEvent, CalendarMembership, CalendarRole, CalendarSettings,
-    DayData, EntryKind, EntryStyle, User, ViewMode,
+    DayData, DaySegment, EntryKind, EntryStyle, User, ViewMode,
 };
+pub use version::{format_etag, if_match_satisfied, parse_etag, INITIAL_VERSION};
@@ -0,0 +1,327 @@
+//! Per-occurrence exceptions to recurring series.
+//!
+//! A series is stored once; changes to individual occurrences are stored as
+//! [`OccurrenceOverride`]s keyed by `(series id, original occurrence date)`.
+//! An override either cancels the occurrence (RFC 5545 `EXDATE`) or replaces
+//! it with a modified copy (RFC 5545 `RECURRENCE-ID`). "This and following"
+//! edits split the series in two with [`split_series`].
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::error::RecurrenceError;
+use super::types::CalendarEntry;
+
+/// Which occurrences of a recurring series an edit or deletion applies to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecurrenceScope {
+    /// Only the selected occurrence.
+    This,
+    /// The selected occurrence and every later one.
+    Following,
+    /// The whole series.
+    #[default]
+    All,
+}
+
+impl RecurrenceScope {
+    /// Returns the scope as a lowercase string.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RecurrenceScope::This => "this",
+            RecurrenceScope::Following => "following",
+            RecurrenceScope::All => "all",
+        }
+    }
+}
+
+impl fmt::Display for RecurrenceScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RecurrenceScope {
+    type Err = RecurrenceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "this" => Ok(RecurrenceScope::This),
+            "following" => Ok(RecurrenceScope::Following),
+            "all" => Ok(RecurrenceScope::All),
+            other => Err(RecurrenceError::InvalidScope(other.to_string())),
+        }
+    }
+}
+
+/// An exception to a single occurrence of a recurring series.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OccurrenceOverride {
+    /// ID of the recurring series.
+    pub series_id: Uuid,
+    /// Date the occurrence would have started on according to the rule.
+    pub original_date: NaiveDate,
+    /// The modified occurrence, or `None` if the occurrence is cancelled.
+    pub replacement: Option<CalendarEntry>,
+}
+
+impl OccurrenceOverride {
+    /// Creates an override that cancels an occurrence.
+    pub fn cancelled(series_id: Uuid, original_date: NaiveDate) -> Self {
+        Self {
+            series_id,
+            original_date,
+            replacement: None,
+        }
+    }
+
+    /// Creates an override that replaces an occurrence with `replacement`.
+    ///
+    /// The replacement is normalized to carry the series ID and the original
+    /// date; its rule is dropped since it always follows the series' rule.
+    pub fn replaced(series_id: Uuid, original_date: NaiveDate, replacement: CalendarEntry) -> Self {
+        let mut replacement = replacement;
+        replacement.id = series_id;
+        replacement.occurrence_date = Some(original_date);
+        replacement.recurrence = None;
+        Self {
+            series_id,
+            original_date,
+            replacement: Some(replacement),
+        }
+    }
+
+    /// Returns true if the occurrence is cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.replacement.is_none()
+    }
+}
+
+/// Returns true if a series has an occurrence starting on `date`.
+pub fn is_occurrence(series: &CalendarEntry, date: NaiveDate) -> bool {
+    series
+        .recurrence
+        .as_ref()
+        .is_some_and(|rule| !rule.occurrences(series.start_date, date, date).is_empty())
+}
+
+/// Builds the occurrence of `series` that starts on `date`, ignoring overrides.
+pub fn occurrence_of(
+    series: &CalendarEntry,
+    date: NaiveDate,
+) -> Result<CalendarEntry, RecurrenceError> {
+    if !series.is_recurring() {
+        return Err(RecurrenceError::NotRecurring);
+    }
+    if !is_occurrence(series, date) {
+        return Err(RecurrenceError::NotAnOccurrence(date));
+    }
+
+    let span = series.end_date - series.start_date;
+    let mut occurrence = series.clone();
+    occurrence.start_date = date;
+    occurrence.end_date = date + span;
+    occurrence.occurrence_date = Some(date);
+    Ok(occurrence)
+}
+
+/// Ends a series just before the occurrence on `at`.
+///
+/// Returns `None` when `at` is the first occurrence, i.e. nothing would remain.
+/// A `COUNT` limit is rewritten to the number of occurrences kept; otherwise
+/// `UNTIL` is set to the day before `at`.
+pub fn truncate_series(
+    series: &CalendarEntry,
+    at: NaiveDate,
+) -> Result<Option<CalendarEntry>, RecurrenceError> {
+    let rule = series
+        .recurrence
+        .as_ref()
+        .ok_or(RecurrenceError::NotRecurring)?;
+    if !is_occurrence(series, at) {
+        return Err(RecurrenceError::NotAnOccurrence(at));
+    }
+    if at <= series.start_date {
+        return Ok(None);
+    }
+
+    let before = at - Duration::days(1);
+    let mut rule = rule.clone();
+    if rule.count.is_some() {
+        let kept = rule
+            .occurrences(series.start_date, series.start_date, before)
+            .len();
+        rule.count = Some(kept as u32);
+    } else {
+        rule.until = Some(before);
+    }
+
+    let mut head = series.clone();
+    head.recurrence = Some(rule);
+    head.touch();
+    Ok(Some(head))
+}
+
+/// Splits a series at the occurrence on `at` for a "this and following" edit.
+///
+/// Returns the truncated original series (`None` if `at` is its first
+/// occurrence) and a new series, with a fresh ID, that starts on `at` and
+/// carries on with the original rule. Any remaining `COUNT` moves to the new
+/// series. Overrides of the original series are not carried over.
+pub fn split_series(
+    series: &CalendarEntry,
+    at: NaiveDate,
+) -> Result<(Option<CalendarEntry>, CalendarEntry), RecurrenceError> {
+    let head = truncate_series(series, at)?;
+
+    let mut rule = series
+        .recurrence
+        .clone()
+        .ok_or(RecurrenceError::NotRecurring)?;
+    if let (Some(total), Some(head)) = (rule.count, &head) {
+        let kept = head.recurrence.as_ref().and_then(|r| r.count).unwrap_or(0);
+        rule.count = Some(total - kept);
+    }
+
+    let now = Utc::now();
+    let mut tail = occurrence_of(series, at)?;
+    tail.id = Uuid::new_v4();
+    tail.recurrence = Some(rule);
+    tail.occurrence_date = None;
+    tail.created_at = now;
+    tail.updated_at = now;
+
+    Ok((head, tail))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::RecurrenceRule;
+    use chrono::NaiveTime;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn weekly_series() -> CalendarEntry {
+        CalendarEntry::timed(
+            Uuid::new_v4(),
+            "Standup",
+            date(2024, 1, 1),
+            NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            NaiveTime::from_hms_opt(9, 15, 0).unwrap(),
+        )
+        .with_recurrence(RecurrenceRule::weekly())
+    }
+
+    #[test]
+    fn test_scope_parse_and_display() {
+        assert_eq!("this".parse(), Ok(RecurrenceScope::This));
+        assert_eq!("Following".parse(), Ok(RecurrenceScope::Following));
+        assert_eq!("ALL".parse(), Ok(RecurrenceScope::All));
+        assert_eq!(
+            "some".parse::<RecurrenceScope>(),
+            Err(RecurrenceError::InvalidScope("some".to_string()))
+        );
+        assert_eq!(RecurrenceScope::default(), RecurrenceScope::All);
+        assert_eq!(RecurrenceScope::Following.to_string(), "following");
+    }
+
+    #[test]
+    fn test_occurrence_of() {
+        let series = weekly_series();
+
+        let occurrence = occurrence_of(&series, date(2024, 1, 15)).unwrap();
+        assert_eq!(occurrence.id, series.id);
+        assert_eq!(occurrence.start_date, date(2024, 1, 15));
+        assert_eq!(occurrence.occurrence_date, Some(date(2024, 1, 15)));
+
+        assert_eq!(
+            occurrence_of(&series, date(2024, 1, 16)),
+            Err(RecurrenceError::NotAnOccurrence(date(2024, 1, 16)))
+        );
+
+        let single = CalendarEntry::all_day(Uuid::new_v4(), "Once", date(2024, 1, 1));
+        assert_eq!(
+            occurrence_of(&single, date(2024, 1, 1)),
+            Err(RecurrenceError::NotRecurring)
+        );
+    }
+
+    #[test]
+    fn test_replaced_override_is_normalized() {
+        let series = weekly_series();
+        let mut moved = occurrence_of(&series, date(2024, 1, 8)).unwrap();
+        moved.id = Uuid::new_v4();
+        moved.start_date = date(2024, 1, 9);
+        moved.end_date = date(2024, 1, 9);
+
+        let over = OccurrenceOverride::replaced(series.id, date(2024, 1, 8), moved);
+        let replacement = over.replacement.as_ref().unwrap();
+        assert!(!over.is_cancelled());
+        assert_eq!(replacement.id, series.id);
+        assert_eq!(replacement.occurrence_date, Some(date(2024, 1, 8)));
+        assert!(replacement.recurrence.is_none());
+
+        assert!(OccurrenceOverride::cancelled(series.id, date(2024, 1, 8)).is_cancelled());
+    }
+
+    #[test]
+    fn test_truncate_series() {
+        let series = weekly_series();
+
+        let head = truncate_series(&series, date(2024, 1, 22))
+            .unwrap()
+            .unwrap();
+        assert_eq!(head.id, series.id);
+        assert_eq!(
+            head.recurrence.as_ref().unwrap().until,
+            Some(date(2024, 1, 21))
+        );
+
+        assert_eq!(truncate_series(&series, date(2024, 1, 1)), Ok(None));
+        assert_eq!(
+            truncate_series(&series, date(2024, 1, 2)),
+            Err(RecurrenceError::NotAnOccurrence(date(2024, 1, 2)))
+        );
+    }
+
+    #[test]
+    fn test_split_series_with_until() {
+        let series = weekly_series();
+        let (head, tail) = split_series(&series, date(2024, 1, 15)).unwrap();
+        let head = head.unwrap();
+
+        assert_eq!(head.recurrence.unwrap().until, Some(date(2024, 1, 14)));
+        assert_ne!(tail.id, series.id);
+        assert_eq!(tail.start_date, date(2024, 1, 15));
+        assert_eq!(tail.kind, series.kind);
+        assert!(tail.occurrence_date.is_none());
+        assert_eq!(tail.recurrence, series.recurrence);
+    }
+
+    #[test]
+    fn test_split_series_redistributes_count() {
+        let mut series = weekly_series();
+        series.recurrence = Some(RecurrenceRule::weekly().with_count(10));
+
+        let (head, tail) = split_series(&series, date(2024, 1, 22)).unwrap();
+        assert_eq!(head.unwrap().recurrence.unwrap().count, Some(3));
+        assert_eq!(tail.recurrence.unwrap().count, Some(7));
+    }
+
+    #[test]
+    fn test_split_series_at_first_occurrence() {
+        let series = weekly_series();
+        let (head, tail) = split_series(&series, date(2024, 1, 1)).unwrap();
+        assert!(head.is_none());
+        assert_eq!(tail.start_date, series.start_date);
+        assert_eq!(tail.recurrence, series.recurrence);
+    }
+}
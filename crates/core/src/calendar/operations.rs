@@ -1,7 +1,17 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
 use chrono::NaiveDate;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use super::error::{CalendarError, EntryError};
+use super::attendee::validate_attendees;
+use super::error::{CalendarError, ConflictError, EntryError, RecurrenceError};
+use super::freebusy::{entry_busy_interval, BusyInterval};
+use super::tag::{matches_any_tag, Tag};
+use super::timezone::entry_timezone;
 use super::types::{Calendar, CalendarEntry, EntryKind};
 
 /// Filters entries by calendar ID.
@@ -28,20 +38,99 @@ pub fn filter_entries_by_date_range(
         .collect()
 }
 
-/// Filters entries by calendar ID and date range.
+/// Filters entries by calendar ID, date range and tags.
 /// Uses overlap logic: an entry is included if it overlaps with the date range.
-pub fn filter_entries(
-    entries: &[CalendarEntry],
+/// When `tags` is not empty, only entries with at least one of them are included.
+pub fn filter_entries<'a>(
+    entries: &'a [CalendarEntry],
     calendar_id: Option<Uuid>,
     start: Option<NaiveDate>,
     end: Option<NaiveDate>,
-) -> Vec<&CalendarEntry> {
+    tags: &BTreeSet<Tag>,
+) -> Vec<&'a CalendarEntry> {
     entries
         .iter()
         .filter(|entry| {
             calendar_id.is_none_or(|id| entry.calendar_id == id)
                 && start.is_none_or(|s| entry.end_date >= s)
                 && end.is_none_or(|e| entry.start_date <= e)
+                && matches_any_tag(&entry.tags, tags)
+        })
+        .collect()
+}
+
+/// Which calendars a new or moved entry is checked against for conflicts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictScope {
+    /// Only the entry's own calendar.
+    #[default]
+    Calendar,
+    /// Every calendar the acting user is a member of.
+    All,
+}
+
+impl ConflictScope {
+    /// Returns the scope as a lowercase string.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConflictScope::Calendar => "calendar",
+            ConflictScope::All => "all",
+        }
+    }
+}
+
+impl fmt::Display for ConflictScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ConflictScope {
+    type Err = ConflictError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "calendar" => Ok(ConflictScope::Calendar),
+            "all" => Ok(ConflictScope::All),
+            other => Err(ConflictError::InvalidScope(other.to_string())),
+        }
+    }
+}
+
+/// Returns when a timed entry takes place, resolving its zone like the
+/// reminder scheduler does: the entry's, then its calendar's, then UTC.
+fn timed_interval(
+    entry: &CalendarEntry,
+    calendar_timezones: &HashMap<Uuid, Tz>,
+) -> Option<BusyInterval> {
+    if !entry.kind.is_timed() {
+        return None;
+    }
+    let calendar_tz = calendar_timezones.get(&entry.calendar_id).copied();
+    entry_busy_interval(entry, entry_timezone(entry, calendar_tz).unwrap_or(Tz::UTC))
+}
+
+/// Finds the timed entries in `others` that overlap `entry` in time.
+///
+/// Only timed entries conflict, and entries that merely touch (one ends as
+/// the other starts) do not. `others` may include `entry` itself or
+/// occurrences of its own series; those are skipped. Entries in different
+/// zones are compared by the instants they take place at.
+pub fn find_conflicts<'a>(
+    entry: &CalendarEntry,
+    others: &'a [CalendarEntry],
+    calendar_timezones: &HashMap<Uuid, Tz>,
+) -> Vec<&'a CalendarEntry> {
+    let Some(interval) = timed_interval(entry, calendar_timezones) else {
+        return Vec::new();
+    };
+    others
+        .iter()
+        .filter(|other| other.id != entry.id)
+        .filter(|other| {
+            timed_interval(other, calendar_timezones)
+                .is_some_and(|o| o.start < interval.end && interval.start < o.end)
         })
         .collect()
 }
@@ -71,19 +160,28 @@ pub fn validate_entry(entry: &CalendarEntry) -> Result<(), EntryError> {
 
     // Validate date/time ranges for specific entry kinds
     match &entry.kind {
-        EntryKind::MultiDay => {
-            if entry.end_date < entry.start_date {
-                return Err(EntryError::InvalidDateRange);
-            }
+        EntryKind::MultiDay | EntryKind::Timed { .. } if entry.end_date < entry.start_date => {
+            return Err(EntryError::InvalidDateRange);
         }
-        EntryKind::Timed { start, end } => {
-            if end <= start {
-                return Err(EntryError::InvalidTimeRange);
-            }
+        EntryKind::Timed { start, end } if entry.end_date == entry.start_date && end <= start => {
+            return Err(EntryError::InvalidTimeRange);
         }
         _ => {}
     }
 
+    if let Some(rule) = &entry.recurrence {
+        rule.validate()?;
+        if rule.until.is_some_and(|until| until < entry.start_date) {
+            return Err(RecurrenceError::UntilBeforeStart.into());
+        }
+    }
+
+    for reminder in &entry.reminders {
+        reminder.validate()?;
+    }
+
+    validate_attendees(&entry.attendees)?;
+
     Ok(())
 }
 
@@ -175,6 +273,28 @@ mod tests {
         assert!(filtered.iter().any(|e| e.title == "End"));
     }
 
+    #[test]
+    fn test_filter_entries_by_tags() {
+        let cal_id = test_calendar_id();
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let tag = |s: &str| s.parse::<Tag>().unwrap();
+        let entries = vec![
+            CalendarEntry::all_day(cal_id, "Pager", date).with_tag(tag("oncall")),
+            CalendarEntry::all_day(cal_id, "Flight", date)
+                .with_tag(tag("travel"))
+                .with_tag(tag("work")),
+            CalendarEntry::all_day(cal_id, "Untagged", date),
+        ];
+
+        let all = filter_entries(&entries, Some(cal_id), None, None, &BTreeSet::new());
+        assert_eq!(all.len(), 3);
+
+        let wanted = BTreeSet::from([tag("travel"), tag("oncall")]);
+        let tagged = filter_entries(&entries, Some(cal_id), None, None, &wanted);
+        let titles: Vec<_> = tagged.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, vec!["Pager", "Flight"]);
+    }
+
     #[test]
     fn test_validate_calendar_success() {
         let calendar = Calendar::new("Work", "#3B82F6");
@@ -234,6 +354,75 @@ mod tests {
         assert_eq!(validate_entry(&entry), Err(EntryError::InvalidTimeRange));
     }
 
+    #[test]
+    fn test_validate_entry_timed_across_days() {
+        let cal_id = test_calendar_id();
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let start = NaiveTime::from_hms_opt(22, 0, 0).unwrap();
+        let end = NaiveTime::from_hms_opt(6, 0, 0).unwrap();
+
+        let overnight = CalendarEntry::timed(cal_id, "Night shift", date, start, end)
+            .with_end_date(date.succ_opt().unwrap());
+        assert!(validate_entry(&overnight).is_ok());
+
+        let backwards = CalendarEntry::timed(cal_id, "Invalid", date, start, end)
+            .with_end_date(date.pred_opt().unwrap());
+        assert_eq!(
+            validate_entry(&backwards),
+            Err(EntryError::InvalidDateRange)
+        );
+    }
+
+    #[test]
+    fn test_validate_entry_recurrence() {
+        use crate::calendar::RecurrenceRule;
+
+        let cal_id = test_calendar_id();
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let entry = CalendarEntry::all_day(cal_id, "Weekly", date)
+            .with_recurrence(RecurrenceRule::weekly().with_count(4));
+        assert!(validate_entry(&entry).is_ok());
+
+        let entry = CalendarEntry::all_day(cal_id, "Ends early", date).with_recurrence(
+            RecurrenceRule::weekly().with_until(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()),
+        );
+        assert_eq!(
+            validate_entry(&entry),
+            Err(EntryError::InvalidRecurrence(
+                RecurrenceError::UntilBeforeStart
+            ))
+        );
+
+        let entry = CalendarEntry::all_day(cal_id, "Never", date)
+            .with_recurrence(RecurrenceRule::daily().with_interval(0));
+        assert_eq!(
+            validate_entry(&entry),
+            Err(EntryError::InvalidRecurrence(
+                RecurrenceError::InvalidInterval
+            ))
+        );
+    }
+
+    #[test]
+    fn test_validate_entry_reminders() {
+        use crate::calendar::{Reminder, ReminderError, MAX_REMINDER_LEAD_DAYS};
+
+        let cal_id = test_calendar_id();
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let entry = CalendarEntry::all_day(cal_id, "Dentist", date)
+            .with_reminder(Reminder::minutes_before(30));
+        assert!(validate_entry(&entry).is_ok());
+
+        let entry = CalendarEntry::all_day(cal_id, "Far off", date)
+            .with_reminder(Reminder::minutes_before(60 * 24 * 60));
+        assert_eq!(
+            validate_entry(&entry),
+            Err(EntryError::InvalidReminder(ReminderError::TooEarly(
+                MAX_REMINDER_LEAD_DAYS
+            )))
+        );
+    }
+
     #[test]
     fn test_is_valid_color() {
         // Valid hex colors
@@ -253,4 +442,44 @@ mod tests {
         assert!(!is_valid_color("not-a-color"));
         assert!(!is_valid_color("#12345")); // Wrong length
     }
+
+    #[test]
+    fn test_find_conflicts() {
+        let cal_id = test_calendar_id();
+        let other_id = other_calendar_id();
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let time = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+
+        let meeting = CalendarEntry::timed(cal_id, "Meeting", date, time(9, 0), time(10, 0));
+        let others = vec![
+            meeting.clone(),
+            CalendarEntry::timed(cal_id, "Standup", date, time(9, 30), time(9, 45)),
+            CalendarEntry::timed(cal_id, "Lunch", date, time(10, 0), time(11, 0)),
+            CalendarEntry::all_day(cal_id, "Holiday", date),
+            // 09:00 in Madrid is 08:00 UTC, before the meeting (read as UTC)
+            CalendarEntry::timed(other_id, "Call", date, time(9, 0), time(9, 30))
+                .with_timezone(chrono_tz::Europe::Madrid),
+        ];
+
+        let conflicts = find_conflicts(&meeting, &others, &HashMap::new());
+        let titles: Vec<&str> = conflicts.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, vec!["Standup"]);
+
+        // With the meeting's calendar in Madrid too, the call overlaps
+        let timezones = HashMap::from([(cal_id, chrono_tz::Europe::Madrid)]);
+        assert_eq!(find_conflicts(&meeting, &others, &timezones).len(), 2);
+
+        let holiday = CalendarEntry::all_day(cal_id, "Holiday", date);
+        assert!(find_conflicts(&holiday, &others, &HashMap::new()).is_empty());
+    }
+
+    #[test]
+    fn test_conflict_scope_parse() {
+        assert_eq!("all".parse::<ConflictScope>(), Ok(ConflictScope::All));
+        assert_eq!(
+            "Calendar".parse::<ConflictScope>(),
+            Ok(ConflictScope::Calendar)
+        );
+        assert!("everything".parse::<ConflictScope>().is_err());
+    }
 }
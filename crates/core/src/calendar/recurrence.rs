@@ -0,0 +1,941 @@
+//! Recurrence rules (RFC 5545 RRULE subset) for repeating calendar entries.
+//!
+//! Supports `FREQ`, `INTERVAL`, `BYDAY`, `BYMONTHDAY`, `COUNT` and `UNTIL`.
+//! Rules are serialized as their RRULE string (e.g. `FREQ=WEEKLY;BYDAY=MO,WE`)
+//! so they round-trip unchanged through JSON, SQLite and DynamoDB.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+use super::error::RecurrenceError;
+use super::occurrence::OccurrenceOverride;
+use super::types::CalendarEntry;
+use crate::storage::DateRange;
+
+/// How often a recurring entry repeats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Frequency {
+    /// Returns the RRULE `FREQ` value.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        }
+    }
+}
+
+impl FromStr for Frequency {
+    type Err = RecurrenceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "DAILY" => Ok(Frequency::Daily),
+            "WEEKLY" => Ok(Frequency::Weekly),
+            "MONTHLY" => Ok(Frequency::Monthly),
+            "YEARLY" => Ok(Frequency::Yearly),
+            other => Err(RecurrenceError::InvalidFrequency(other.to_string())),
+        }
+    }
+}
+
+/// A `BYDAY` value: a weekday with an optional ordinal (e.g. `2MO`, `-1FR`).
+///
+/// Ordinals are only meaningful for monthly rules, where they select the
+/// n-th (or n-th from last) matching weekday of the month.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ByDay {
+    pub ordinal: Option<i8>,
+    pub weekday: Weekday,
+}
+
+impl ByDay {
+    /// Creates a `BYDAY` value matching every occurrence of the weekday.
+    pub fn every(weekday: Weekday) -> Self {
+        Self {
+            ordinal: None,
+            weekday,
+        }
+    }
+
+    /// Creates a `BYDAY` value matching the n-th occurrence of the weekday in a month.
+    pub fn nth(ordinal: i8, weekday: Weekday) -> Self {
+        Self {
+            ordinal: Some(ordinal),
+            weekday,
+        }
+    }
+}
+
+impl fmt::Display for ByDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(ordinal) = self.ordinal {
+            write!(f, "{ordinal}")?;
+        }
+        f.write_str(weekday_code(self.weekday))
+    }
+}
+
+impl FromStr for ByDay {
+    type Err = RecurrenceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || RecurrenceError::InvalidByDay(s.to_string());
+        let upper = s.trim().to_ascii_uppercase();
+        if upper.len() < 2 {
+            return Err(invalid());
+        }
+        let (ordinal, code) = upper.split_at(upper.len() - 2);
+        let weekday = parse_weekday_code(code).ok_or_else(invalid)?;
+        let ordinal = if ordinal.is_empty() {
+            None
+        } else {
+            let n: i8 = ordinal.parse().map_err(|_| invalid())?;
+            if n == 0 || !(-5..=5).contains(&n) {
+                return Err(invalid());
+            }
+            Some(n)
+        };
+        Ok(Self { ordinal, weekday })
+    }
+}
+
+/// A recurrence rule describing how an entry repeats.
+///
+/// The first occurrence is always the entry's own `start_date` (the RFC 5545
+/// `DTSTART`); `COUNT` includes it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    /// Repeat every `interval` periods (always >= 1).
+    pub interval: u32,
+    pub by_day: Vec<ByDay>,
+    /// Days of the month (1..=31, or -1..=-31 counting from the end).
+    pub by_month_day: Vec<i8>,
+    /// Total number of occurrences, including the first.
+    pub count: Option<u32>,
+    /// Last date (inclusive) an occurrence may start on.
+    pub until: Option<NaiveDate>,
+}
+
+impl RecurrenceRule {
+    /// Creates a rule repeating every period of the given frequency.
+    pub fn new(frequency: Frequency) -> Self {
+        Self {
+            frequency,
+            interval: 1,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            count: None,
+            until: None,
+        }
+    }
+
+    /// Creates a daily rule.
+    pub fn daily() -> Self {
+        Self::new(Frequency::Daily)
+    }
+
+    /// Creates a weekly rule.
+    pub fn weekly() -> Self {
+        Self::new(Frequency::Weekly)
+    }
+
+    /// Creates a monthly rule.
+    pub fn monthly() -> Self {
+        Self::new(Frequency::Monthly)
+    }
+
+    /// Creates a yearly rule.
+    pub fn yearly() -> Self {
+        Self::new(Frequency::Yearly)
+    }
+
+    /// Sets the interval between periods.
+    pub fn with_interval(mut self, interval: u32) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets the `BYDAY` values.
+    pub fn with_by_day(mut self, by_day: impl IntoIterator<Item = ByDay>) -> Self {
+        self.by_day = by_day.into_iter().collect();
+        self
+    }
+
+    /// Sets the `BYMONTHDAY` values.
+    pub fn with_by_month_day(mut self, days: impl IntoIterator<Item = i8>) -> Self {
+        self.by_month_day = days.into_iter().collect();
+        self
+    }
+
+    /// Limits the series to `count` occurrences.
+    pub fn with_count(mut self, count: u32) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    /// Ends the series on `until` (inclusive).
+    pub fn with_until(mut self, until: NaiveDate) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    /// Checks the rule for combinations this implementation cannot expand.
+    pub fn validate(&self) -> Result<(), RecurrenceError> {
+        if self.interval == 0 {
+            return Err(RecurrenceError::InvalidInterval);
+        }
+        if self.count == Some(0) {
+            return Err(RecurrenceError::InvalidCount);
+        }
+        if self.count.is_some() && self.until.is_some() {
+            return Err(RecurrenceError::CountAndUntil);
+        }
+        if let Some(day) = self
+            .by_month_day
+            .iter()
+            .find(|d| **d == 0 || !(-31..=31).contains(*d))
+        {
+            return Err(RecurrenceError::InvalidByMonthDay(day.to_string()));
+        }
+        if self.frequency != Frequency::Monthly {
+            if let Some(by_day) = self.by_day.iter().find(|d| d.ordinal.is_some()) {
+                return Err(RecurrenceError::InvalidByDay(by_day.to_string()));
+            }
+        }
+        match self.frequency {
+            Frequency::Weekly if !self.by_month_day.is_empty() => {
+                Err(RecurrenceError::Unsupported("BYMONTHDAY with FREQ=WEEKLY"))
+            }
+            Frequency::Yearly if !self.by_day.is_empty() || !self.by_month_day.is_empty() => Err(
+                RecurrenceError::Unsupported("BYDAY/BYMONTHDAY with FREQ=YEARLY"),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the start dates of every occurrence that begins within
+    /// `[from, to]`, for a series whose first occurrence is `dtstart`.
+    ///
+    /// `COUNT` and `UNTIL` are applied from `dtstart`, so occurrences before
+    /// `from` still count towards the limit.
+    pub fn occurrences(
+        &self,
+        dtstart: NaiveDate,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Vec<NaiveDate> {
+        let mut result = Vec::new();
+        if self.interval == 0 || to < dtstart {
+            return result;
+        }
+
+        let last = match self.until {
+            Some(until) => until.min(to),
+            None => to,
+        };
+        let mut emitted: u32 = 0;
+        let mut period: u32 = 0;
+
+        while let Some(period_start) = self.period_start(dtstart, period) {
+            if period_start > last {
+                break;
+            }
+            for date in self.period_dates(dtstart, period_start) {
+                if date < dtstart {
+                    continue;
+                }
+                if date > last {
+                    return result;
+                }
+                emitted += 1;
+                if date >= from {
+                    result.push(date);
+                }
+                if self.count.is_some_and(|count| emitted >= count) {
+                    return result;
+                }
+            }
+            period += 1;
+        }
+
+        result
+    }
+
+    /// First day of the `period`-th period after `dtstart`'s period.
+    fn period_start(&self, dtstart: NaiveDate, period: u32) -> Option<NaiveDate> {
+        let step = i64::from(period) * i64::from(self.interval);
+        match self.frequency {
+            Frequency::Daily => dtstart.checked_add_signed(Duration::days(step)),
+            Frequency::Weekly => {
+                let monday =
+                    dtstart - Duration::days(dtstart.weekday().num_days_from_monday() as i64);
+                monday.checked_add_signed(Duration::weeks(step))
+            }
+            Frequency::Monthly => {
+                let months = i64::from(dtstart.month0()) + step;
+                let year = dtstart.year() as i64 + months.div_euclid(12);
+                let month = months.rem_euclid(12) as u32 + 1;
+                NaiveDate::from_ymd_opt(i32::try_from(year).ok()?, month, 1)
+            }
+            Frequency::Yearly => {
+                let year = i32::try_from(dtstart.year() as i64 + step).ok()?;
+                NaiveDate::from_ymd_opt(year, 1, 1)
+            }
+        }
+    }
+
+    /// Candidate dates within a single period, in ascending order.
+    fn period_dates(&self, dtstart: NaiveDate, period_start: NaiveDate) -> Vec<NaiveDate> {
+        let mut dates = match self.frequency {
+            Frequency::Daily => {
+                let matches_day = self.by_day.is_empty()
+                    || self
+                        .by_day
+                        .iter()
+                        .any(|d| d.weekday == period_start.weekday());
+                let matches_month_day = self.by_month_day.is_empty()
+                    || self
+                        .by_month_day
+                        .iter()
+                        .any(|d| resolve_month_day(period_start, *d) == Some(period_start));
+                if matches_day && matches_month_day {
+                    vec![period_start]
+                } else {
+                    Vec::new()
+                }
+            }
+            Frequency::Weekly => {
+                if self.by_day.is_empty() {
+                    vec![
+                        period_start
+                            + Duration::days(dtstart.weekday().num_days_from_monday() as i64),
+                    ]
+                } else {
+                    self.by_day
+                        .iter()
+                        .map(|d| {
+                            period_start + Duration::days(d.weekday.num_days_from_monday() as i64)
+                        })
+                        .collect()
+                }
+            }
+            Frequency::Monthly => self.monthly_dates(dtstart, period_start),
+            Frequency::Yearly => {
+                NaiveDate::from_ymd_opt(period_start.year(), dtstart.month(), dtstart.day())
+                    .into_iter()
+                    .collect()
+            }
+        };
+        dates.sort();
+        dates.dedup();
+        dates
+    }
+
+    fn monthly_dates(&self, dtstart: NaiveDate, month_start: NaiveDate) -> Vec<NaiveDate> {
+        let by_month_day: Vec<NaiveDate> = self
+            .by_month_day
+            .iter()
+            .filter_map(|d| resolve_month_day(month_start, *d))
+            .collect();
+
+        if self.by_day.is_empty() {
+            if self.by_month_day.is_empty() {
+                // Months without dtstart's day (e.g. the 31st) are skipped, per RFC 5545.
+                return NaiveDate::from_ymd_opt(
+                    month_start.year(),
+                    month_start.month(),
+                    dtstart.day(),
+                )
+                .into_iter()
+                .collect();
+            }
+            return by_month_day;
+        }
+
+        let by_day = self
+            .by_day
+            .iter()
+            .flat_map(|d| weekdays_in_month(month_start, *d));
+        if self.by_month_day.is_empty() {
+            by_day.collect()
+        } else {
+            by_day.filter(|date| by_month_day.contains(date)).collect()
+        }
+    }
+}
+
+impl fmt::Display for RecurrenceRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FREQ={}", self.frequency.as_str())?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<String> = self.by_day.iter().map(ByDay::to_string).collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if !self.by_month_day.is_empty() {
+            let days: Vec<String> = self.by_month_day.iter().map(i8::to_string).collect();
+            write!(f, ";BYMONTHDAY={}", days.join(","))?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={count}")?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format("%Y%m%d"))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for RecurrenceRule {
+    type Err = RecurrenceError;
+
+    /// Parses an RRULE value, with or without the leading `RRULE:`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s
+            .get(..6)
+            .filter(|prefix| prefix.eq_ignore_ascii_case("RRULE:"))
+            .map_or(s, |_| &s[6..]);
+
+        let mut frequency = None;
+        let mut rule = RecurrenceRule::daily();
+
+        for part in s.split(';').filter(|p| !p.trim().is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| RecurrenceError::InvalidPart(part.to_string()))?;
+            let value = value.trim();
+            match key.trim().to_ascii_uppercase().as_str() {
+                "FREQ" => frequency = Some(value.parse()?),
+                "INTERVAL" => {
+                    rule.interval = value
+                        .parse()
+                        .map_err(|_| RecurrenceError::InvalidInterval)?
+                }
+                "BYDAY" => {
+                    rule.by_day = value.split(',').map(str::parse).collect::<Result<_, _>>()?
+                }
+                "BYMONTHDAY" => {
+                    rule.by_month_day = value
+                        .split(',')
+                        .map(|d| {
+                            d.trim()
+                                .parse()
+                                .map_err(|_| RecurrenceError::InvalidByMonthDay(d.to_string()))
+                        })
+                        .collect::<Result<_, _>>()?
+                }
+                "COUNT" => {
+                    rule.count = Some(value.parse().map_err(|_| RecurrenceError::InvalidCount)?)
+                }
+                "UNTIL" => rule.until = Some(parse_until(value)?),
+                // Only Monday week starts are supported; accept the default explicitly.
+                "WKST" if value.eq_ignore_ascii_case("MO") => {}
+                other => return Err(RecurrenceError::UnsupportedPart(other.to_string())),
+            }
+        }
+
+        rule.frequency = frequency.ok_or(RecurrenceError::MissingFrequency)?;
+        rule.validate()?;
+        Ok(rule)
+    }
+}
+
+impl TryFrom<String> for RecurrenceRule {
+    type Error = RecurrenceError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<RecurrenceRule> for String {
+    fn from(rule: RecurrenceRule) -> Self {
+        rule.to_string()
+    }
+}
+
+/// Expands recurring entries into one entry per occurrence overlapping `range`.
+///
+/// Each occurrence is a clone of its series with `start_date`/`end_date`
+/// shifted and `occurrence_date` set to the occurrence's original start date.
+/// Non-recurring entries and already-expanded occurrences pass through untouched,
+/// so expanding twice is harmless.
+///
+/// `overrides` are applied per `(series id, original date)`: cancelled
+/// occurrences are dropped and replaced ones are swapped for their replacement,
+/// which is included whenever it overlaps `range` - even if it was moved there
+/// from a date outside it. Overrides whose original date is no longer an
+/// occurrence of the series (e.g. after the rule changed) are ignored.
+pub fn expand_recurring_entries(
+    entries: Vec<CalendarEntry>,
+    overrides: &[OccurrenceOverride],
+    range: &DateRange,
+) -> Vec<CalendarEntry> {
+    let mut expanded = Vec::with_capacity(entries.len());
+
+    for entry in entries {
+        let rule = match &entry.recurrence {
+            Some(rule) if entry.occurrence_date.is_none() => rule,
+            _ => {
+                expanded.push(entry);
+                continue;
+            }
+        };
+
+        let series_overrides: HashMap<NaiveDate, &OccurrenceOverride> = overrides
+            .iter()
+            .filter(|o| o.series_id == entry.id)
+            .filter(|o| {
+                !rule
+                    .occurrences(entry.start_date, o.original_date, o.original_date)
+                    .is_empty()
+            })
+            .map(|o| (o.original_date, o))
+            .collect();
+
+        // An occurrence that started before the range can still overlap it.
+        let span = entry.end_date - entry.start_date;
+        let from = range.start - span;
+        let mut occurrences: Vec<CalendarEntry> = rule
+            .occurrences(entry.start_date, from, range.end)
+            .into_iter()
+            .filter(|date| !series_overrides.contains_key(date))
+            .map(|date| {
+                let mut occurrence = entry.clone();
+                occurrence.start_date = date;
+                occurrence.end_date = date + span;
+                occurrence.occurrence_date = Some(date);
+                occurrence
+            })
+            .collect();
+
+        occurrences.extend(
+            series_overrides
+                .values()
+                .filter_map(|o| o.replacement.as_ref())
+                .filter(|r| r.start_date <= range.end && r.end_date >= range.start)
+                .map(|r| {
+                    let mut replacement = r.clone();
+                    replacement.id = entry.id;
+                    replacement.calendar_id = entry.calendar_id;
+                    replacement.recurrence = entry.recurrence.clone();
+                    replacement
+                }),
+        );
+        occurrences.sort_by_key(|o| (o.start_date, o.occurrence_date));
+        expanded.extend(occurrences);
+    }
+
+    expanded
+}
+
+/// Resolves a `BYMONTHDAY` value to a date in the month starting at `month_start`.
+fn resolve_month_day(month_start: NaiveDate, day: i8) -> Option<NaiveDate> {
+    let (year, month) = (month_start.year(), month_start.month());
+    if day > 0 {
+        NaiveDate::from_ymd_opt(year, month, day as u32)
+    } else {
+        let days_in_month = last_day_of_month(month_start).day() as i32;
+        let resolved = days_in_month + 1 + day as i32;
+        if resolved < 1 {
+            return None;
+        }
+        NaiveDate::from_ymd_opt(year, month, resolved as u32)
+    }
+}
+
+/// All dates in the month matching a `BYDAY` value.
+fn weekdays_in_month(month_start: NaiveDate, by_day: ByDay) -> Vec<NaiveDate> {
+    let offset = (7 + by_day.weekday.num_days_from_monday() as i64
+        - month_start.weekday().num_days_from_monday() as i64)
+        % 7;
+    let last = last_day_of_month(month_start);
+    let all: Vec<NaiveDate> = (0..)
+        .map(|week| month_start + Duration::days(offset + 7 * week))
+        .take_while(|date| *date <= last)
+        .collect();
+
+    match by_day.ordinal {
+        None => all,
+        Some(n) if n > 0 => all.get(n as usize - 1).copied().into_iter().collect(),
+        Some(n) => all
+            .len()
+            .checked_sub(n.unsigned_abs() as usize)
+            .and_then(|i| all.get(i).copied())
+            .into_iter()
+            .collect(),
+    }
+}
+
+fn last_day_of_month(month_start: NaiveDate) -> NaiveDate {
+    let (year, month) = if month_start.month() == 12 {
+        (month_start.year() + 1, 1)
+    } else {
+        (month_start.year(), month_start.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|d| d.pred_opt())
+        .unwrap_or(month_start)
+}
+
+/// Parses an `UNTIL` value in `YYYYMMDD` or `YYYYMMDDTHHMMSS[Z]` form.
+fn parse_until(value: &str) -> Result<NaiveDate, RecurrenceError> {
+    value
+        .get(..8)
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y%m%d").ok())
+        .ok_or_else(|| RecurrenceError::InvalidUntil(value.to_string()))
+}
+
+fn weekday_code(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn parse_weekday_code(code: &str) -> Option<Weekday> {
+    match code {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveTime;
+    use uuid::Uuid;
+
+    fn test_calendar_id() -> Uuid {
+        Uuid::parse_str("00000000-0000-0000-0000-000000000001").unwrap()
+    }
+
+    fn make_date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn make_time(hour: u32, min: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, min, 0).unwrap()
+    }
+
+    fn parse(rule: &str) -> RecurrenceRule {
+        rule.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display_roundtrip() {
+        let rule = parse("RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;COUNT=10");
+        assert_eq!(rule.frequency, Frequency::Weekly);
+        assert_eq!(rule.interval, 2);
+        assert_eq!(
+            rule.by_day,
+            vec![ByDay::every(Weekday::Mon), ByDay::every(Weekday::Wed)]
+        );
+        assert_eq!(rule.count, Some(10));
+        assert_eq!(
+            rule.to_string(),
+            "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;COUNT=10"
+        );
+
+        let rule = parse("freq=monthly;byday=-1fr;until=20240630T235959Z");
+        assert_eq!(rule.by_day, vec![ByDay::nth(-1, Weekday::Fri)]);
+        assert_eq!(rule.until, Some(make_date(2024, 6, 30)));
+        assert_eq!(rule.to_string(), "FREQ=MONTHLY;BYDAY=-1FR;UNTIL=20240630");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "INTERVAL=2".parse::<RecurrenceRule>(),
+            Err(RecurrenceError::MissingFrequency)
+        );
+        assert_eq!(
+            "FREQ=HOURLY".parse::<RecurrenceRule>(),
+            Err(RecurrenceError::InvalidFrequency("HOURLY".to_string()))
+        );
+        assert_eq!(
+            "FREQ=WEEKLY;BYDAY=XX".parse::<RecurrenceRule>(),
+            Err(RecurrenceError::InvalidByDay("XX".to_string()))
+        );
+        assert_eq!(
+            "FREQ=WEEKLY;BYDAY=1MO".parse::<RecurrenceRule>(),
+            Err(RecurrenceError::InvalidByDay("1MO".to_string()))
+        );
+        assert_eq!(
+            "FREQ=DAILY;COUNT=3;UNTIL=20240101".parse::<RecurrenceRule>(),
+            Err(RecurrenceError::CountAndUntil)
+        );
+        assert_eq!(
+            "FREQ=YEARLY;BYMONTH=3".parse::<RecurrenceRule>(),
+            Err(RecurrenceError::UnsupportedPart("BYMONTH".to_string()))
+        );
+        assert_eq!(
+            "FREQ=DAILY;INTERVAL=0".parse::<RecurrenceRule>(),
+            Err(RecurrenceError::InvalidInterval)
+        );
+    }
+
+    #[test]
+    fn test_serde_uses_rrule_string() {
+        let rule = RecurrenceRule::weekly().with_by_day([ByDay::every(Weekday::Fri)]);
+        let json = serde_json::to_string(&rule).unwrap();
+        assert_eq!(json, r#""FREQ=WEEKLY;BYDAY=FR""#);
+
+        let restored: RecurrenceRule = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, rule);
+
+        assert!(serde_json::from_str::<RecurrenceRule>(r#""FREQ=NEVER""#).is_err());
+    }
+
+    #[test]
+    fn test_daily_with_interval_and_count() {
+        let rule = RecurrenceRule::daily().with_interval(2).with_count(3);
+        let start = make_date(2024, 1, 1);
+        let dates = rule.occurrences(start, start, make_date(2024, 12, 31));
+        assert_eq!(
+            dates,
+            vec![
+                make_date(2024, 1, 1),
+                make_date(2024, 1, 3),
+                make_date(2024, 1, 5)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_count_includes_occurrences_before_window() {
+        let rule = RecurrenceRule::daily().with_count(5);
+        let start = make_date(2024, 1, 1);
+        let dates = rule.occurrences(start, make_date(2024, 1, 4), make_date(2024, 1, 31));
+        assert_eq!(dates, vec![make_date(2024, 1, 4), make_date(2024, 1, 5)]);
+    }
+
+    #[test]
+    fn test_weekly_by_day_with_until() {
+        let rule = parse("FREQ=WEEKLY;BYDAY=MO,WE,FR;UNTIL=20240112");
+        // 2024-01-03 is a Wednesday; the Monday of that week precedes dtstart.
+        let start = make_date(2024, 1, 3);
+        let dates = rule.occurrences(start, start, make_date(2024, 1, 31));
+        assert_eq!(
+            dates,
+            vec![
+                make_date(2024, 1, 3),
+                make_date(2024, 1, 5),
+                make_date(2024, 1, 8),
+                make_date(2024, 1, 10),
+                make_date(2024, 1, 12),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_biweekly_defaults_to_start_weekday() {
+        let rule = RecurrenceRule::weekly().with_interval(2);
+        let start = make_date(2024, 1, 2); // Tuesday
+        let dates = rule.occurrences(start, start, make_date(2024, 2, 1));
+        assert_eq!(
+            dates,
+            vec![
+                make_date(2024, 1, 2),
+                make_date(2024, 1, 16),
+                make_date(2024, 1, 30)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_monthly_by_month_day_skips_short_months() {
+        let rule = RecurrenceRule::monthly();
+        let start = make_date(2024, 1, 31);
+        let dates = rule.occurrences(start, start, make_date(2024, 5, 31));
+        assert_eq!(
+            dates,
+            vec![
+                make_date(2024, 1, 31),
+                make_date(2024, 3, 31),
+                make_date(2024, 5, 31)
+            ]
+        );
+
+        let rule = parse("FREQ=MONTHLY;BYMONTHDAY=-1");
+        let dates = rule.occurrences(start, start, make_date(2024, 3, 31));
+        assert_eq!(
+            dates,
+            vec![
+                make_date(2024, 1, 31),
+                make_date(2024, 2, 29),
+                make_date(2024, 3, 31)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_monthly_by_day_ordinals() {
+        let rule = parse("FREQ=MONTHLY;BYDAY=2TU,-1FR;COUNT=4");
+        let start = make_date(2024, 1, 1);
+        let dates = rule.occurrences(start, start, make_date(2024, 12, 31));
+        assert_eq!(
+            dates,
+            vec![
+                make_date(2024, 1, 9),
+                make_date(2024, 1, 26),
+                make_date(2024, 2, 13),
+                make_date(2024, 2, 23),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_monthly_friday_the_thirteenth() {
+        let rule = parse("FREQ=MONTHLY;BYDAY=FR;BYMONTHDAY=13");
+        let start = make_date(2024, 1, 1);
+        let dates = rule.occurrences(start, start, make_date(2024, 12, 31));
+        assert_eq!(dates, vec![make_date(2024, 9, 13), make_date(2024, 12, 13)]);
+    }
+
+    #[test]
+    fn test_yearly_skips_missing_leap_day() {
+        let rule = RecurrenceRule::yearly();
+        let start = make_date(2024, 2, 29);
+        let dates = rule.occurrences(start, start, make_date(2028, 12, 31));
+        assert_eq!(dates, vec![make_date(2024, 2, 29), make_date(2028, 2, 29)]);
+    }
+
+    #[test]
+    fn test_daily_by_day_filters_weekends() {
+        let rule = parse("FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR");
+        let start = make_date(2024, 1, 5); // Friday
+        let dates = rule.occurrences(start, start, make_date(2024, 1, 9));
+        assert_eq!(
+            dates,
+            vec![
+                make_date(2024, 1, 5),
+                make_date(2024, 1, 8),
+                make_date(2024, 1, 9)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_recurring_entries() {
+        let cal_id = test_calendar_id();
+        let start = make_date(2024, 1, 1); // Monday
+        let standup =
+            CalendarEntry::timed(cal_id, "Standup", start, make_time(9, 0), make_time(9, 15))
+                .with_recurrence(parse("FREQ=WEEKLY;BYDAY=MO,TH"));
+        let one_off = CalendarEntry::all_day(cal_id, "Dentist", make_date(2024, 1, 16));
+
+        let range = DateRange::new(make_date(2024, 1, 15), make_date(2024, 1, 21)).unwrap();
+        let expanded = expand_recurring_entries(vec![standup.clone(), one_off], &[], &range);
+
+        let standups: Vec<_> = expanded.iter().filter(|e| e.title == "Standup").collect();
+        assert_eq!(standups.len(), 2);
+        assert_eq!(standups[0].start_date, make_date(2024, 1, 15));
+        assert_eq!(standups[0].occurrence_date, Some(make_date(2024, 1, 15)));
+        assert_eq!(standups[1].start_date, make_date(2024, 1, 18));
+        assert!(standups.iter().all(|e| e.id == standup.id));
+        assert_eq!(expanded.iter().filter(|e| e.title == "Dentist").count(), 1);
+
+        // Expanding already-expanded occurrences is a no-op.
+        let again = expand_recurring_entries(expanded.clone(), &[], &range);
+        assert_eq!(again, expanded);
+    }
+
+    #[test]
+    fn test_expand_multi_day_occurrence_overlapping_range_start() {
+        let cal_id = test_calendar_id();
+        let trip =
+            CalendarEntry::multi_day(cal_id, "Trip", make_date(2024, 1, 5), make_date(2024, 1, 7))
+                .with_recurrence(RecurrenceRule::weekly());
+
+        let range = DateRange::new(make_date(2024, 1, 14), make_date(2024, 1, 14)).unwrap();
+        let expanded = expand_recurring_entries(vec![trip], &[], &range);
+
+        assert_eq!(expanded.len(), 1);
+        assert_eq!(expanded[0].start_date, make_date(2024, 1, 12));
+        assert_eq!(expanded[0].end_date, make_date(2024, 1, 14));
+    }
+
+    #[test]
+    fn test_expand_drops_series_without_occurrences_in_range() {
+        let cal_id = test_calendar_id();
+        let entry = CalendarEntry::all_day(cal_id, "Short series", make_date(2024, 1, 1))
+            .with_recurrence(RecurrenceRule::daily().with_count(2));
+
+        let range = DateRange::new(make_date(2024, 2, 1), make_date(2024, 2, 29)).unwrap();
+        assert!(expand_recurring_entries(vec![entry], &[], &range).is_empty());
+    }
+
+    #[test]
+    fn test_expand_applies_occurrence_overrides() {
+        let cal_id = test_calendar_id();
+        let series = CalendarEntry::all_day(cal_id, "Gym", make_date(2024, 1, 1))
+            .with_recurrence(RecurrenceRule::weekly());
+
+        let mut moved = series.clone();
+        moved.title = "Gym (moved)".to_string();
+        moved.start_date = make_date(2024, 1, 16);
+        moved.end_date = make_date(2024, 1, 16);
+        let mut into_range = series.clone();
+        into_range.start_date = make_date(2024, 1, 20);
+        into_range.end_date = make_date(2024, 1, 20);
+
+        let overrides = vec![
+            OccurrenceOverride::cancelled(series.id, make_date(2024, 1, 8)),
+            OccurrenceOverride::replaced(series.id, make_date(2024, 1, 15), moved),
+            // Moved from outside the range into it.
+            OccurrenceOverride::replaced(series.id, make_date(2024, 1, 29), into_range),
+            // Not an occurrence of the series; ignored.
+            OccurrenceOverride::cancelled(series.id, make_date(2024, 1, 2)),
+        ];
+
+        let range = DateRange::new(make_date(2024, 1, 1), make_date(2024, 1, 21)).unwrap();
+        let expanded = expand_recurring_entries(vec![series.clone()], &overrides, &range);
+        let dates: Vec<_> = expanded
+            .iter()
+            .map(|e| (e.start_date, e.occurrence_date.unwrap()))
+            .collect();
+
+        assert_eq!(
+            dates,
+            vec![
+                (make_date(2024, 1, 1), make_date(2024, 1, 1)),
+                (make_date(2024, 1, 16), make_date(2024, 1, 15)),
+                (make_date(2024, 1, 20), make_date(2024, 1, 29)),
+            ]
+        );
+        assert_eq!(expanded[1].title, "Gym (moved)");
+        assert!(expanded
+            .iter()
+            .all(|e| e.id == series.id && e.is_recurring()));
+    }
+}
@@ -0,0 +1,441 @@
+//! Reminders for calendar entries and the computation of when they fire.
+//!
+//! A reminder is either an offset before the entry starts (`10m`, `2h`, `1d`,
+//! `1w`) or a wall-clock time a number of days before its start date
+//! (`1d@09:00`, or `0d@08:00` for the morning of). Reminders are serialized
+//! as these strings so they round-trip unchanged through JSON, forms, SQLite
+//! and DynamoDB.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::error::ReminderError;
+use super::timezone::{entry_timezone, resolve_local};
+use super::types::CalendarEntry;
+use crate::storage::DateRange;
+
+/// How far ahead of an entry a reminder may fire, in days.
+pub const MAX_REMINDER_LEAD_DAYS: u32 = 28;
+
+const MINUTES_PER_HOUR: u32 = 60;
+const MINUTES_PER_DAY: u32 = 24 * MINUTES_PER_HOUR;
+const MINUTES_PER_WEEK: u32 = 7 * MINUTES_PER_DAY;
+
+/// When to remind about an entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Reminder {
+    /// Fires a number of minutes before the entry starts.
+    Before { minutes: u32 },
+    /// Fires at a wall-clock time a number of days before the entry's start date.
+    DaysBeforeAt { days: u32, time: NaiveTime },
+}
+
+impl Reminder {
+    /// Fires `minutes` before the entry starts.
+    pub fn minutes_before(minutes: u32) -> Self {
+        Self::Before { minutes }
+    }
+
+    /// Fires at `time` on the day `days` before the entry's start date.
+    pub fn days_before_at(days: u32, time: NaiveTime) -> Self {
+        Self::DaysBeforeAt { days, time }
+    }
+
+    /// Checks the reminder does not fire more than [`MAX_REMINDER_LEAD_DAYS`] ahead.
+    pub fn validate(&self) -> Result<(), ReminderError> {
+        let too_early = match self {
+            Self::Before { minutes } => *minutes > MAX_REMINDER_LEAD_DAYS * MINUTES_PER_DAY,
+            Self::DaysBeforeAt { days, .. } => *days > MAX_REMINDER_LEAD_DAYS,
+        };
+        match too_early {
+            true => Err(ReminderError::TooEarly(MAX_REMINDER_LEAD_DAYS)),
+            false => Ok(()),
+        }
+    }
+
+    /// Returns the instant this reminder fires for `entry`, whose dates and
+    /// times are expressed in `tz`.
+    ///
+    /// Entries without a start time (all-day entries, tasks, multi-day events)
+    /// start at midnight.
+    pub fn fire_at(&self, entry: &CalendarEntry, tz: Tz) -> DateTime<Utc> {
+        match self {
+            Self::Before { minutes } => {
+                let start_time = entry.kind.start_time().unwrap_or(NaiveTime::MIN);
+                let starts_at = resolve_local(entry.start_date, start_time, tz);
+                starts_at.with_timezone(&Utc) - Duration::minutes(i64::from(*minutes))
+            }
+            Self::DaysBeforeAt { days, time } => {
+                let date = entry.start_date - Duration::days(i64::from(*days));
+                resolve_local(date, *time, tz).with_timezone(&Utc)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Reminder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Before { minutes } => {
+                let (value, unit) = match *minutes {
+                    0 => (0, 'm'),
+                    m if m % MINUTES_PER_WEEK == 0 => (m / MINUTES_PER_WEEK, 'w'),
+                    m if m % MINUTES_PER_DAY == 0 => (m / MINUTES_PER_DAY, 'd'),
+                    m if m % MINUTES_PER_HOUR == 0 => (m / MINUTES_PER_HOUR, 'h'),
+                    m => (m, 'm'),
+                };
+                write!(f, "{value}{unit}")
+            }
+            Self::DaysBeforeAt { days, time } => write!(f, "{days}d@{}", time.format("%H:%M")),
+        }
+    }
+}
+
+impl FromStr for Reminder {
+    type Err = ReminderError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let invalid = || ReminderError::Invalid(s.to_string());
+
+        let reminder = match s.split_once('@') {
+            Some((days, time)) => {
+                let days = days.strip_suffix('d').ok_or_else(invalid)?;
+                Self::DaysBeforeAt {
+                    days: days.parse().map_err(|_| invalid())?,
+                    time: NaiveTime::parse_from_str(time, "%H:%M").map_err(|_| invalid())?,
+                }
+            }
+            None => {
+                let unit = s.chars().last().ok_or_else(invalid)?;
+                let factor = match unit {
+                    'm' => 1,
+                    'h' => MINUTES_PER_HOUR,
+                    'd' => MINUTES_PER_DAY,
+                    'w' => MINUTES_PER_WEEK,
+                    _ => return Err(invalid()),
+                };
+                let value: u32 = s[..s.len() - 1].parse().map_err(|_| invalid())?;
+                Self::Before {
+                    minutes: value.checked_mul(factor).ok_or_else(invalid)?,
+                }
+            }
+        };
+
+        reminder.validate()?;
+        Ok(reminder)
+    }
+}
+
+impl TryFrom<String> for Reminder {
+    type Error = ReminderError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Reminder> for String {
+    fn from(reminder: Reminder) -> Self {
+        reminder.to_string()
+    }
+}
+
+/// Parses a comma-separated list of reminders (e.g. `10m,1d@09:00`).
+pub fn parse_reminders(s: &str) -> Result<Vec<Reminder>, ReminderError> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+/// Formats reminders as a comma-separated list. See [`parse_reminders`].
+pub fn format_reminders(reminders: &[Reminder]) -> String {
+    reminders
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// A reminder that is due for one entry (or occurrence of a recurring series).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DueReminder {
+    pub entry: CalendarEntry,
+    pub reminder: Reminder,
+    pub fire_at: DateTime<Utc>,
+}
+
+/// Returns the entry dates whose reminders can fire between `after` and `until`.
+///
+/// Covers a day on either side for entries in zones away from UTC, plus the
+/// longest allowed reminder lead.
+pub fn reminder_search_range(after: DateTime<Utc>, until: DateTime<Utc>) -> DateRange {
+    let start = after.date_naive() - Duration::days(1);
+    let end = until.date_naive() + Duration::days(i64::from(MAX_REMINDER_LEAD_DAYS) + 1);
+    DateRange {
+        start,
+        end: end.max(start),
+    }
+}
+
+/// Returns the reminders that fire after `after` and no later than `until`,
+/// ordered by when they fire.
+///
+/// Entries use their own zone, then their calendar's zone from
+/// `calendar_timezones`, and are read as UTC when they have neither. Reminders
+/// of the same entry that fire at the same instant are reported once, and
+/// reminders that fell due before their entry was created are skipped.
+pub fn due_reminders(
+    entries: &[CalendarEntry],
+    calendar_timezones: &HashMap<Uuid, Tz>,
+    after: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> Vec<DueReminder> {
+    let mut seen = HashSet::new();
+    let mut due: Vec<DueReminder> = entries
+        .iter()
+        .flat_map(|entry| {
+            let calendar_tz = calendar_timezones.get(&entry.calendar_id).copied();
+            let tz = entry_timezone(entry, calendar_tz).unwrap_or(Tz::UTC);
+            entry.reminders.iter().map(move |reminder| DueReminder {
+                entry: entry.clone(),
+                reminder: *reminder,
+                fire_at: reminder.fire_at(entry, tz),
+            })
+        })
+        .filter(|due| due.fire_at > after && due.fire_at <= until)
+        .filter(|due| due.fire_at >= due.entry.created_at)
+        .filter(|due| seen.insert((due.entry.id, due.fire_at)))
+        .collect();
+
+    due.sort_by_key(|due| due.fire_at);
+    due
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, TimeZone};
+    use chrono_tz::Europe::Madrid;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display_round_trip() {
+        for s in ["10m", "90m", "2h", "1d", "1w", "0m", "1d@09:00", "0d@08:30"] {
+            let reminder: Reminder = s.parse().unwrap();
+            assert_eq!(reminder.to_string(), s);
+        }
+
+        assert_eq!("120m".parse::<Reminder>().unwrap().to_string(), "2h");
+        assert_eq!(
+            "1d@09:00".parse::<Reminder>().unwrap(),
+            Reminder::days_before_at(1, time(9, 0))
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for s in ["", "10", "m", "10s", "-5m", "1d@9", "1@09:00", "xd@09:00"] {
+            assert!(
+                matches!(s.parse::<Reminder>(), Err(ReminderError::Invalid(_))),
+                "{s} should be invalid"
+            );
+        }
+        assert_eq!(
+            "5w".parse::<Reminder>(),
+            Err(ReminderError::TooEarly(MAX_REMINDER_LEAD_DAYS))
+        );
+        assert_eq!(
+            "29d@09:00".parse::<Reminder>(),
+            Err(ReminderError::TooEarly(MAX_REMINDER_LEAD_DAYS))
+        );
+    }
+
+    #[test]
+    fn test_parse_and_format_list() {
+        let reminders = parse_reminders("10m, 1d@09:00,").unwrap();
+        assert_eq!(
+            reminders,
+            vec![
+                Reminder::minutes_before(10),
+                Reminder::days_before_at(1, time(9, 0))
+            ]
+        );
+        assert_eq!(format_reminders(&reminders), "10m,1d@09:00");
+        assert!(parse_reminders("").unwrap().is_empty());
+        assert!(parse_reminders("10m,soon").is_err());
+    }
+
+    #[test]
+    fn test_serde_uses_reminder_string() {
+        let json = serde_json::to_string(&Reminder::minutes_before(60)).unwrap();
+        assert_eq!(json, r#""1h""#);
+        let restored: Reminder = serde_json::from_str(r#""1d@09:00""#).unwrap();
+        assert_eq!(restored, Reminder::days_before_at(1, time(9, 0)));
+        assert!(serde_json::from_str::<Reminder>(r#""soon""#).is_err());
+    }
+
+    #[test]
+    fn test_fire_at() {
+        let meeting = CalendarEntry::timed(
+            Uuid::new_v4(),
+            "Meeting",
+            date(2024, 7, 1),
+            time(10, 0),
+            time(11, 0),
+        );
+
+        assert_eq!(
+            Reminder::minutes_before(10).fire_at(&meeting, Tz::UTC),
+            utc(2024, 7, 1, 9, 50)
+        );
+        // Madrid is UTC+2 in summer
+        assert_eq!(
+            Reminder::minutes_before(10).fire_at(&meeting, Madrid),
+            utc(2024, 7, 1, 7, 50)
+        );
+        assert_eq!(
+            Reminder::days_before_at(1, time(9, 0)).fire_at(&meeting, Madrid),
+            utc(2024, 6, 30, 7, 0)
+        );
+
+        // All-day entries start at midnight
+        let birthday = CalendarEntry::all_day(Uuid::new_v4(), "Birthday", date(2024, 7, 1));
+        assert_eq!(
+            Reminder::minutes_before(60).fire_at(&birthday, Tz::UTC),
+            utc(2024, 6, 30, 23, 0)
+        );
+    }
+
+    #[test]
+    fn test_due_reminders_window() {
+        let calendar_id = Uuid::new_v4();
+        let meeting = CalendarEntry::timed(
+            calendar_id,
+            "Meeting",
+            date(2024, 7, 1),
+            time(10, 0),
+            time(11, 0),
+        )
+        .with_reminder(Reminder::minutes_before(10))
+        .with_reminder(Reminder::days_before_at(1, time(9, 0)))
+        .with_created_at(utc(2024, 1, 1, 0, 0));
+        let silent = CalendarEntry::all_day(calendar_id, "Silent", date(2024, 7, 1));
+        let entries = vec![meeting.clone(), silent];
+
+        let due = due_reminders(
+            &entries,
+            &HashMap::new(),
+            utc(2024, 6, 30, 0, 0),
+            utc(2024, 7, 1, 9, 50),
+        );
+        let fired: Vec<_> = due.iter().map(|d| d.fire_at).collect();
+        assert_eq!(fired, vec![utc(2024, 6, 30, 9, 0), utc(2024, 7, 1, 9, 50)]);
+        assert!(due.iter().all(|d| d.entry.id == meeting.id));
+
+        // The start of the window is exclusive
+        let due = due_reminders(
+            &entries,
+            &HashMap::new(),
+            utc(2024, 7, 1, 9, 50),
+            utc(2024, 7, 1, 12, 0),
+        );
+        assert!(due.is_empty());
+    }
+
+    #[test]
+    fn test_due_reminders_skips_reminders_before_creation() {
+        let meeting = CalendarEntry::timed(
+            Uuid::new_v4(),
+            "Meeting",
+            date(2024, 7, 1),
+            time(10, 0),
+            time(11, 0),
+        )
+        .with_reminder(Reminder::minutes_before(10))
+        .with_reminder(Reminder::days_before_at(1, time(9, 0)))
+        .with_created_at(utc(2024, 7, 1, 8, 0));
+
+        let due = due_reminders(
+            &[meeting],
+            &HashMap::new(),
+            utc(2024, 6, 30, 0, 0),
+            utc(2024, 7, 1, 12, 0),
+        );
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].reminder, Reminder::minutes_before(10));
+    }
+
+    #[test]
+    fn test_due_reminders_uses_calendar_timezone() {
+        let calendar_id = Uuid::new_v4();
+        let meeting = CalendarEntry::timed(
+            calendar_id,
+            "Meeting",
+            date(2024, 7, 1),
+            time(10, 0),
+            time(11, 0),
+        )
+        .with_reminder(Reminder::minutes_before(0))
+        .with_created_at(utc(2024, 1, 1, 0, 0));
+        let timezones = HashMap::from([(calendar_id, Madrid)]);
+
+        let due = due_reminders(
+            &[meeting],
+            &timezones,
+            utc(2024, 7, 1, 7, 0),
+            utc(2024, 7, 1, 8, 0),
+        );
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].fire_at, utc(2024, 7, 1, 8, 0));
+    }
+
+    #[test]
+    fn test_due_reminders_deduplicates_same_instant() {
+        let meeting = CalendarEntry::timed(
+            Uuid::new_v4(),
+            "Meeting",
+            date(2024, 7, 1),
+            time(10, 0),
+            time(11, 0),
+        )
+        .with_reminder(Reminder::minutes_before(60))
+        .with_reminder(Reminder::days_before_at(0, time(9, 0)))
+        .with_created_at(utc(2024, 1, 1, 0, 0));
+
+        let due = due_reminders(
+            &[meeting],
+            &HashMap::new(),
+            utc(2024, 7, 1, 0, 0),
+            utc(2024, 7, 1, 12, 0),
+        );
+        assert_eq!(due.len(), 1);
+    }
+
+    #[test]
+    fn test_reminder_search_range() {
+        let range = reminder_search_range(utc(2024, 7, 1, 9, 0), utc(2024, 7, 1, 9, 1));
+        assert_eq!(range.start, date(2024, 6, 30));
+        assert_eq!(range.end, date(2024, 7, 30));
+    }
+}
@@ -3,11 +3,28 @@
 //! These types are shared between the server and client for type-safe API communication.
 //! Following the Functional Core pattern, these are pure data types with no I/O.
 
+use std::collections::BTreeSet;
+
 use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::attendee::{merge_attendees, Attendee};
+use super::occurrence::RecurrenceScope;
+use super::operations::ConflictScope;
+use super::recurrence::RecurrenceRule;
+use super::reminder::Reminder;
+use super::subscription::CalendarSubscription;
+use super::tag::Tag;
+use super::task::{ChecklistItem, TaskPriority};
 use super::types::{Calendar, CalendarEntry, EntryKind};
+use super::version::INITIAL_VERSION;
+use crate::serde::{
+    deserialize_optional_attendees, deserialize_optional_checklist, deserialize_optional_reminders,
+    deserialize_optional_tags, serialize_optional_attendees, serialize_optional_checklist,
+    serialize_optional_reminders, serialize_optional_tags,
+};
 
 /// Entry type discriminant for API requests.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -39,6 +56,12 @@ pub struct CreateCalendarRequest {
     pub color: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// IANA time zone for the calendar's timed entries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<Tz>,
+    /// iCalendar feed to mirror, making the calendar read-only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscription_url: Option<String>,
 }
 
 impl CreateCalendarRequest {
@@ -48,6 +71,8 @@ impl CreateCalendarRequest {
             name: name.into(),
             color: None,
             description: None,
+            timezone: None,
+            subscription_url: None,
         }
     }
 
@@ -63,6 +88,18 @@ impl CreateCalendarRequest {
         self
     }
 
+    /// Set the calendar time zone.
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = Some(timezone);
+        self
+    }
+
+    /// Subscribe the calendar to an iCalendar feed.
+    pub fn with_subscription_url(mut self, url: impl Into<String>) -> Self {
+        self.subscription_url = Some(url.into());
+        self
+    }
+
     /// Convert into a Calendar, using a default color if none specified.
     pub fn into_calendar(self) -> Calendar {
         let color = self.color.unwrap_or_else(|| "#3B82F6".to_string());
@@ -70,6 +107,8 @@ impl CreateCalendarRequest {
         if let Some(description) = self.description {
             calendar = calendar.with_description(description);
         }
+        calendar.timezone = self.timezone;
+        calendar.subscription = self.subscription_url.map(CalendarSubscription::new);
         calendar
     }
 }
@@ -83,6 +122,8 @@ pub struct UpdateCalendarRequest {
     pub color: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<Tz>,
 }
 
 impl UpdateCalendarRequest {
@@ -109,6 +150,12 @@ impl UpdateCalendarRequest {
         self
     }
 
+    /// Set the calendar time zone.
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = Some(timezone);
+        self
+    }
+
     /// Apply updates to an existing calendar.
     pub fn apply_to(self, calendar: &mut Calendar) {
         if let Some(name) = self.name {
@@ -120,9 +167,27 @@ impl UpdateCalendarRequest {
         if let Some(description) = self.description {
             calendar.description = Some(description);
         }
+        if let Some(timezone) = self.timezone {
+            calendar.timezone = Some(timezone);
+        }
     }
 }
 
+/// Request payload for duplicating a calendar.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DuplicateCalendarRequest {
+    /// Name of the copy; defaults to "<source name> (copy)".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_entries: Option<bool>,
+    /// Days to move copied entries by; negative moves them earlier.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shift_days: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_members: Option<bool>,
+}
+
 /// Request payload for creating a new entry.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateEntryRequest {
@@ -142,6 +207,58 @@ pub struct CreateEntryRequest {
     pub end_date: Option<NaiveDate>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<RecurrenceRule>,
+    /// IANA time zone of the dates and times; defaults to the calendar's zone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<Tz>,
+    /// Reminders, sent as a comma-separated list (e.g. `10m,1d@09:00`).
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_optional_reminders",
+        deserialize_with = "deserialize_optional_reminders"
+    )]
+    pub reminders: Option<Vec<Reminder>>,
+    /// Attendee email addresses, sent as a comma-separated list.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_optional_attendees",
+        deserialize_with = "deserialize_optional_attendees"
+    )]
+    pub attendees: Option<Vec<Attendee>>,
+    /// Tags, sent as a comma-separated list (e.g. "oncall,travel").
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_optional_tags",
+        deserialize_with = "deserialize_optional_tags"
+    )]
+    pub tags: Option<BTreeSet<Tag>>,
+    /// Time of day a task is due.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_time: Option<NaiveTime>,
+    /// Task priority; defaults to normal.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<TaskPriority>,
+    /// Task checklist, sent with one item per line (e.g. `[x] Book flight`).
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_optional_checklist",
+        deserialize_with = "deserialize_optional_checklist"
+    )]
+    pub checklist: Option<Vec<ChecklistItem>>,
+    /// When true, the task is shown on today until it is completed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub roll_over: Option<bool>,
+    /// Which calendars to check for overlapping timed entries; defaults to the entry's own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conflict_scope: Option<ConflictScope>,
+    /// When true, the request fails with 409 instead of saving an entry with conflicts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reject_on_conflict: Option<bool>,
 }
 
 impl CreateEntryRequest {
@@ -158,6 +275,17 @@ impl CreateEntryRequest {
             end_time: None,
             end_date: None,
             color: None,
+            recurrence: None,
+            timezone: None,
+            reminders: None,
+            attendees: None,
+            tags: None,
+            due_time: None,
+            priority: None,
+            checklist: None,
+            roll_over: None,
+            conflict_scope: None,
+            reject_on_conflict: None,
         }
     }
 
@@ -180,6 +308,17 @@ impl CreateEntryRequest {
             end_time: Some(end),
             end_date: None,
             color: None,
+            recurrence: None,
+            timezone: None,
+            reminders: None,
+            attendees: None,
+            tags: None,
+            due_time: None,
+            priority: None,
+            checklist: None,
+            roll_over: None,
+            conflict_scope: None,
+            reject_on_conflict: None,
         }
     }
 
@@ -196,6 +335,17 @@ impl CreateEntryRequest {
             end_time: None,
             end_date: None,
             color: None,
+            recurrence: None,
+            timezone: None,
+            reminders: None,
+            attendees: None,
+            tags: None,
+            due_time: None,
+            priority: None,
+            checklist: None,
+            roll_over: None,
+            conflict_scope: None,
+            reject_on_conflict: None,
         }
     }
 
@@ -217,6 +367,17 @@ impl CreateEntryRequest {
             end_time: None,
             end_date: Some(end_date),
             color: None,
+            recurrence: None,
+            timezone: None,
+            reminders: None,
+            attendees: None,
+            tags: None,
+            due_time: None,
+            priority: None,
+            checklist: None,
+            roll_over: None,
+            conflict_scope: None,
+            reject_on_conflict: None,
         }
     }
 
@@ -238,11 +399,81 @@ impl CreateEntryRequest {
         self
     }
 
+    /// Make the entry repeat according to the given rule.
+    pub fn with_recurrence(mut self, rule: RecurrenceRule) -> Self {
+        self.recurrence = Some(rule);
+        self
+    }
+
+    /// Set the time zone the entry's dates and times are expressed in.
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = Some(timezone);
+        self
+    }
+
+    /// Add a reminder to the entry.
+    pub fn with_reminder(mut self, reminder: Reminder) -> Self {
+        self.reminders.get_or_insert_with(Vec::new).push(reminder);
+        self
+    }
+
+    /// Invite an attendee to the entry.
+    pub fn with_attendee(mut self, attendee: Attendee) -> Self {
+        self.attendees.get_or_insert_with(Vec::new).push(attendee);
+        self
+    }
+
+    /// Add a tag to the entry.
+    pub fn with_tag(mut self, tag: Tag) -> Self {
+        self.tags.get_or_insert_with(BTreeSet::new).insert(tag);
+        self
+    }
+
+    /// Set the time a task is due.
+    pub fn with_due_time(mut self, due_time: NaiveTime) -> Self {
+        self.due_time = Some(due_time);
+        self
+    }
+
+    /// Set the task priority.
+    pub fn with_priority(mut self, priority: TaskPriority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Add an item to the task's checklist.
+    pub fn with_checklist_item(mut self, item: ChecklistItem) -> Self {
+        self.checklist.get_or_insert_with(Vec::new).push(item);
+        self
+    }
+
+    /// Make the task roll over to today until it is completed.
+    pub fn with_roll_over(mut self) -> Self {
+        self.roll_over = Some(true);
+        self
+    }
+
+    /// Check for overlapping timed entries in `scope`, failing instead of
+    /// saving when `reject` is true.
+    pub fn with_conflict_check(mut self, scope: ConflictScope, reject: bool) -> Self {
+        self.conflict_scope = Some(scope);
+        self.reject_on_conflict = Some(reject);
+        self
+    }
+
+    /// Set the end date for a multi-day entry or a timed entry that ends on a later day.
+    pub fn with_end_date(mut self, end_date: NaiveDate) -> Self {
+        self.end_date = Some(end_date);
+        self
+    }
+
     /// Convert into a CalendarEntry.
     /// Returns None if required fields for the entry type are missing.
     pub fn into_entry(self) -> Option<CalendarEntry> {
         let end_date = match self.entry_type {
             EntryType::MultiDay => self.end_date?,
+            // Timed entries may end on a later day (e.g. overnight)
+            EntryType::Timed => self.end_date.unwrap_or(self.start_date),
             _ => self.start_date,
         };
 
@@ -253,7 +484,13 @@ impl CreateEntryRequest {
                 let end = self.end_time?;
                 EntryKind::Timed { start, end }
             }
-            EntryType::Task => EntryKind::Task { completed: false },
+            EntryType::Task => EntryKind::Task {
+                completed: false,
+                due: self.due_time,
+                priority: self.priority.unwrap_or_default(),
+                checklist: self.checklist.unwrap_or_default(),
+                roll_over: self.roll_over.unwrap_or(false),
+            },
             EntryType::MultiDay => EntryKind::MultiDay,
         };
 
@@ -268,6 +505,14 @@ impl CreateEntryRequest {
             start_date: self.start_date,
             end_date,
             color: self.color,
+            timezone: self.timezone,
+            recurrence: self.recurrence,
+            reminders: self.reminders.unwrap_or_default(),
+            attendees: self.attendees.unwrap_or_default(),
+            tags: self.tags.unwrap_or_default(),
+            occurrence_date: None,
+            segment: None,
+            version: INITIAL_VERSION,
             created_at: now,
             updated_at: now,
         })
@@ -297,11 +542,93 @@ pub struct UpdateEntryRequest {
     pub color: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub completed: Option<bool>,
+    /// New recurrence rule for the series.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<RecurrenceRule>,
+    /// When true, removes the recurrence rule so the entry no longer repeats.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clear_recurrence: Option<bool>,
+    /// Replaces the entry's reminders, sent as a comma-separated list.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_optional_reminders",
+        deserialize_with = "deserialize_optional_reminders"
+    )]
+    pub reminders: Option<Vec<Reminder>>,
+    /// When true, removes all reminders from the entry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clear_reminders: Option<bool>,
+    /// Replaces the attendee list, sent as a comma-separated list of email
+    /// addresses. People who stay invited keep their response.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_optional_attendees",
+        deserialize_with = "deserialize_optional_attendees"
+    )]
+    pub attendees: Option<Vec<Attendee>>,
+    /// When true, removes all attendees from the entry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clear_attendees: Option<bool>,
+    /// Replaces the tags, sent as a comma-separated list.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_optional_tags",
+        deserialize_with = "deserialize_optional_tags"
+    )]
+    pub tags: Option<BTreeSet<Tag>>,
+    /// When true, removes all tags from the entry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clear_tags: Option<bool>,
+    /// New time of day a task is due.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_time: Option<NaiveTime>,
+    /// When true, removes a task's due time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clear_due_time: Option<bool>,
+    /// New task priority.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<TaskPriority>,
+    /// Replaces a task's checklist, sent with one item per line.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_optional_checklist",
+        deserialize_with = "deserialize_optional_checklist"
+    )]
+    pub checklist: Option<Vec<ChecklistItem>>,
+    /// When true, removes all checklist items from a task.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clear_checklist: Option<bool>,
+    /// Whether the task rolls over to today until it is completed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub roll_over: Option<bool>,
+    /// Which occurrences of a recurring series the update applies to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<RecurrenceScope>,
+    /// Original date of the occurrence being edited (for `this` and `following`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub occurrence_date: Option<NaiveDate>,
+    /// IANA time zone the dates and times in this request are expressed in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<Tz>,
     /// Client's timestamp for LWW merge conflict resolution.
     /// If provided, the server will compare this with its own `updated_at`
     /// and only apply the update if the client's timestamp is newer.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub updated_at: Option<DateTime<Utc>>,
+    /// Revision the edit started from. When set, the edit is three-way merged
+    /// with any changes made since then instead of using `updated_at`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_revision: Option<u32>,
+    /// Which calendars to check for overlapping timed entries; defaults to the entry's own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conflict_scope: Option<ConflictScope>,
+    /// When true, the request fails with 409 instead of saving an entry with conflicts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reject_on_conflict: Option<bool>,
 }
 
 impl UpdateEntryRequest {
@@ -370,12 +697,123 @@ impl UpdateEntryRequest {
         self
     }
 
+    /// Set the recurrence rule.
+    pub fn with_recurrence(mut self, rule: RecurrenceRule) -> Self {
+        self.recurrence = Some(rule);
+        self
+    }
+
+    /// Remove the recurrence rule.
+    pub fn without_recurrence(mut self) -> Self {
+        self.clear_recurrence = Some(true);
+        self
+    }
+
+    /// Replace the entry's reminders.
+    pub fn with_reminders(mut self, reminders: Vec<Reminder>) -> Self {
+        self.reminders = Some(reminders);
+        self
+    }
+
+    /// Remove all reminders.
+    pub fn without_reminders(mut self) -> Self {
+        self.clear_reminders = Some(true);
+        self
+    }
+
+    /// Replace the attendee list.
+    pub fn with_attendees(mut self, attendees: Vec<Attendee>) -> Self {
+        self.attendees = Some(attendees);
+        self
+    }
+
+    /// Remove all attendees.
+    pub fn without_attendees(mut self) -> Self {
+        self.clear_attendees = Some(true);
+        self
+    }
+
+    /// Replace the tags.
+    pub fn with_tags(mut self, tags: BTreeSet<Tag>) -> Self {
+        self.tags = Some(tags);
+        self
+    }
+
+    /// Remove all tags.
+    pub fn without_tags(mut self) -> Self {
+        self.clear_tags = Some(true);
+        self
+    }
+
+    /// Set the time a task is due.
+    pub fn with_due_time(mut self, due_time: NaiveTime) -> Self {
+        self.due_time = Some(due_time);
+        self
+    }
+
+    /// Remove a task's due time.
+    pub fn without_due_time(mut self) -> Self {
+        self.clear_due_time = Some(true);
+        self
+    }
+
+    /// Set the task priority.
+    pub fn with_priority(mut self, priority: TaskPriority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Replace the task's checklist.
+    pub fn with_checklist(mut self, checklist: Vec<ChecklistItem>) -> Self {
+        self.checklist = Some(checklist);
+        self
+    }
+
+    /// Remove all checklist items.
+    pub fn without_checklist(mut self) -> Self {
+        self.clear_checklist = Some(true);
+        self
+    }
+
+    /// Set whether the task rolls over to today until it is completed.
+    pub fn with_roll_over(mut self, roll_over: bool) -> Self {
+        self.roll_over = Some(roll_over);
+        self
+    }
+
+    /// Limit the update to some occurrences of a recurring series.
+    pub fn with_scope(mut self, scope: RecurrenceScope, occurrence_date: NaiveDate) -> Self {
+        self.scope = Some(scope);
+        self.occurrence_date = Some(occurrence_date);
+        self
+    }
+
+    /// Set the time zone the dates and times in this request are expressed in.
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = Some(timezone);
+        self
+    }
+
     /// Set the updated_at timestamp for LWW merge conflict resolution.
     pub fn with_updated_at(mut self, updated_at: DateTime<Utc>) -> Self {
         self.updated_at = Some(updated_at);
         self
     }
 
+    /// Set the revision the edit started from, for a three-way merge.
+    pub fn with_base_revision(mut self, revision: u32) -> Self {
+        self.base_revision = Some(revision);
+        self
+    }
+
+    /// Check for overlapping timed entries in `scope`, failing instead of
+    /// saving when `reject` is true.
+    pub fn with_conflict_check(mut self, scope: ConflictScope, reject: bool) -> Self {
+        self.conflict_scope = Some(scope);
+        self.reject_on_conflict = Some(reject);
+        self
+    }
+
     /// Apply updates to an existing entry.
     pub fn apply_to(self, entry: &mut CalendarEntry) {
         if let Some(title) = self.title {
@@ -388,11 +826,14 @@ impl UpdateEntryRequest {
             entry.location = Some(location);
         }
         if let Some(start_date) = self.start_date {
-            entry.start_date = start_date;
-            // For non-multi-day entries, keep start_date == end_date
-            if !entry.kind.is_multi_day() {
+            // Timed entries keep their length in days; other single-day
+            // entries keep start_date == end_date
+            if entry.kind.is_timed() {
+                entry.end_date = start_date + (entry.end_date - entry.start_date);
+            } else if !entry.kind.is_multi_day() {
                 entry.end_date = start_date;
             }
+            entry.start_date = start_date;
         }
         if let Some(end_date) = self.end_date {
             entry.end_date = end_date;
@@ -400,6 +841,26 @@ impl UpdateEntryRequest {
         if let Some(color) = self.color {
             entry.color = Some(color);
         }
+        if self.clear_recurrence == Some(true) {
+            entry.recurrence = None;
+        } else if let Some(recurrence) = self.recurrence {
+            entry.recurrence = Some(recurrence);
+        }
+        if self.clear_reminders == Some(true) {
+            entry.reminders.clear();
+        } else if let Some(reminders) = self.reminders {
+            entry.reminders = reminders;
+        }
+        if self.clear_attendees == Some(true) {
+            entry.attendees.clear();
+        } else if let Some(attendees) = self.attendees {
+            entry.attendees = merge_attendees(&entry.attendees, attendees);
+        }
+        if self.clear_tags == Some(true) {
+            entry.tags.clear();
+        } else if let Some(tags) = self.tags {
+            entry.tags = tags;
+        }
 
         // Handle entry type changes
         if let Some(entry_type) = self.entry_type {
@@ -410,8 +871,8 @@ impl UpdateEntryRequest {
                     EntryKind::AllDay
                 }
                 EntryType::Timed => {
-                    // For Timed, ensure start_date == end_date
-                    entry.end_date = entry.start_date;
+                    // For Timed, end on the start date unless told otherwise
+                    entry.end_date = self.end_date.unwrap_or(entry.start_date);
                     let start = self.start_time.unwrap_or_else(|| {
                         entry
                             .kind
@@ -429,8 +890,16 @@ impl UpdateEntryRequest {
                 EntryType::Task => {
                     // For Task, ensure start_date == end_date
                     entry.end_date = entry.start_date;
-                    let completed = self.completed.unwrap_or(false);
-                    EntryKind::Task { completed }
+                    // An existing task keeps its details; see below for updates
+                    let mut kind = if entry.kind.is_task() {
+                        entry.kind.clone()
+                    } else {
+                        EntryKind::task(false)
+                    };
+                    if let EntryKind::Task { completed, .. } = &mut kind {
+                        *completed = self.completed.unwrap_or(false);
+                    }
+                    kind
                 }
                 EntryType::MultiDay => {
                     // For MultiDay, set end_date from request or default to existing end_date
@@ -455,7 +924,7 @@ impl UpdateEntryRequest {
                         *end = new_end;
                     }
                 }
-                EntryKind::Task { completed } => {
+                EntryKind::Task { completed, .. } => {
                     if let Some(new_completed) = self.completed {
                         *completed = new_completed;
                     }
@@ -463,6 +932,32 @@ impl UpdateEntryRequest {
                 _ => {}
             }
         }
+
+        if let EntryKind::Task {
+            due,
+            priority,
+            checklist,
+            roll_over,
+            ..
+        } = &mut entry.kind
+        {
+            if self.clear_due_time == Some(true) {
+                *due = None;
+            } else if let Some(due_time) = self.due_time {
+                *due = Some(due_time);
+            }
+            if let Some(new_priority) = self.priority {
+                *priority = new_priority;
+            }
+            if self.clear_checklist == Some(true) {
+                checklist.clear();
+            } else if let Some(items) = self.checklist {
+                *checklist = items;
+            }
+            if let Some(new_roll_over) = self.roll_over {
+                *roll_over = new_roll_over;
+            }
+        }
     }
 }
 
@@ -481,6 +976,17 @@ pub struct ListEntriesQuery {
     pub before: Option<u32>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub after: Option<u32>,
+    /// Viewer's IANA time zone; timed entries are converted into it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<Tz>,
+    /// Only entries with at least one of these tags, sent as a comma-separated list.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_optional_tags",
+        deserialize_with = "deserialize_optional_tags"
+    )]
+    pub tags: Option<BTreeSet<Tag>>,
 }
 
 impl ListEntriesQuery {
@@ -502,6 +1008,18 @@ impl ListEntriesQuery {
         self
     }
 
+    /// Only list entries with at least one of the given tags.
+    pub fn with_tags(mut self, tags: BTreeSet<Tag>) -> Self {
+        self.tags = Some(tags);
+        self
+    }
+
+    /// Convert timed entries into the given viewer time zone.
+    pub fn in_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = Some(timezone);
+        self
+    }
+
     /// Set highlighted day and relative range.
     pub fn with_highlighted_day(mut self, day: NaiveDate, before: u32, after: u32) -> Self {
         self.highlighted_day = Some(day);
@@ -574,6 +1092,32 @@ mod tests {
         assert!(matches!(entry.kind, EntryKind::Timed { .. }));
     }
 
+    #[test]
+    fn test_timed_entry_across_midnight() {
+        let calendar_id = Uuid::new_v4();
+        let date = NaiveDate::from_ymd_opt(2024, 6, 15).unwrap();
+        let start = NaiveTime::from_hms_opt(22, 0, 0).unwrap();
+        let end = NaiveTime::from_hms_opt(6, 0, 0).unwrap();
+
+        let mut entry = CreateEntryRequest::timed(calendar_id, "Night shift", date, start, end)
+            .with_end_date(date.succ_opt().unwrap())
+            .into_entry()
+            .unwrap();
+        assert_eq!(
+            entry.end_date,
+            NaiveDate::from_ymd_opt(2024, 6, 16).unwrap()
+        );
+
+        // Moving the start keeps the entry overnight
+        UpdateEntryRequest::new()
+            .with_start_date(NaiveDate::from_ymd_opt(2024, 6, 20).unwrap())
+            .apply_to(&mut entry);
+        assert_eq!(
+            entry.end_date,
+            NaiveDate::from_ymd_opt(2024, 6, 21).unwrap()
+        );
+    }
+
     #[test]
     fn test_create_entry_timed_missing_times() {
         let calendar_id = Uuid::new_v4();
@@ -591,6 +1135,17 @@ mod tests {
             end_time: None,   // Missing!
             end_date: None,
             color: None,
+            recurrence: None,
+            timezone: None,
+            reminders: None,
+            attendees: None,
+            tags: None,
+            due_time: None,
+            priority: None,
+            checklist: None,
+            roll_over: None,
+            conflict_scope: None,
+            reject_on_conflict: None,
         };
 
         assert!(req.into_entry().is_none()); // Should fail
@@ -612,6 +1167,177 @@ mod tests {
         assert_eq!(entry.description, Some("New description".to_string()));
     }
 
+    #[test]
+    fn test_recurrence_in_create_and_update_requests() {
+        let calendar_id = Uuid::new_v4();
+        let date = NaiveDate::from_ymd_opt(2024, 6, 3).unwrap();
+        let rule: RecurrenceRule = "FREQ=WEEKLY;BYDAY=MO".parse().unwrap();
+
+        let mut entry = CreateEntryRequest::all_day(calendar_id, "Standup", date)
+            .with_recurrence(rule.clone())
+            .into_entry()
+            .unwrap();
+        assert_eq!(entry.recurrence, Some(rule));
+
+        let daily = RecurrenceRule::daily();
+        UpdateEntryRequest::new()
+            .with_recurrence(daily.clone())
+            .apply_to(&mut entry);
+        assert_eq!(entry.recurrence, Some(daily));
+
+        UpdateEntryRequest::new()
+            .without_recurrence()
+            .apply_to(&mut entry);
+        assert_eq!(entry.recurrence, None);
+    }
+
+    #[test]
+    fn test_reminders_in_create_and_update_requests() {
+        let calendar_id = Uuid::new_v4();
+        let date = NaiveDate::from_ymd_opt(2024, 6, 3).unwrap();
+        let req = CreateEntryRequest::all_day(calendar_id, "Dentist", date)
+            .with_reminder(Reminder::minutes_before(60))
+            .with_reminder("1d@09:00".parse().unwrap());
+
+        // Sent as a single comma-separated form field
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["reminders"], "1h,1d@09:00");
+
+        let mut entry = req.into_entry().unwrap();
+        assert_eq!(entry.reminders.len(), 2);
+
+        UpdateEntryRequest::new()
+            .with_reminders(vec![Reminder::minutes_before(10)])
+            .apply_to(&mut entry);
+        assert_eq!(entry.reminders, vec![Reminder::minutes_before(10)]);
+
+        UpdateEntryRequest::new()
+            .without_reminders()
+            .apply_to(&mut entry);
+        assert!(entry.reminders.is_empty());
+    }
+
+    #[test]
+    fn test_attendees_in_create_and_update_requests() {
+        use crate::calendar::ResponseStatus;
+
+        let calendar_id = Uuid::new_v4();
+        let date = NaiveDate::from_ymd_opt(2024, 6, 3).unwrap();
+        let req = CreateEntryRequest::all_day(calendar_id, "Planning", date)
+            .with_attendee(Attendee::new("alice@example.com"))
+            .with_attendee(Attendee::new("bob@example.com"));
+
+        // Sent as a single comma-separated form field
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["attendees"], "alice@example.com,bob@example.com");
+
+        let mut entry = req.into_entry().unwrap();
+        entry.attendees[0].status = ResponseStatus::Accepted;
+
+        UpdateEntryRequest::new()
+            .with_attendees(vec![
+                Attendee::new("alice@example.com"),
+                Attendee::new("carol@example.com"),
+            ])
+            .apply_to(&mut entry);
+        assert_eq!(entry.attendees.len(), 2);
+        assert_eq!(entry.attendees[0].status, ResponseStatus::Accepted);
+        assert_eq!(entry.attendees[1].email, "carol@example.com");
+
+        UpdateEntryRequest::new()
+            .without_attendees()
+            .apply_to(&mut entry);
+        assert!(entry.attendees.is_empty());
+    }
+
+    #[test]
+    fn test_tags_in_requests() {
+        let calendar_id = Uuid::new_v4();
+        let date = NaiveDate::from_ymd_opt(2024, 6, 3).unwrap();
+        let tag = |s: &str| s.parse::<Tag>().unwrap();
+        let req = CreateEntryRequest::all_day(calendar_id, "Flight", date)
+            .with_tag(tag("work"))
+            .with_tag(tag("travel"));
+
+        // Sent as a single comma-separated form field
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["tags"], "travel,work");
+
+        let mut entry = req.into_entry().unwrap();
+        assert!(entry.has_tag(&tag("travel")));
+
+        UpdateEntryRequest::new()
+            .with_tags(BTreeSet::from([tag("personal")]))
+            .apply_to(&mut entry);
+        assert_eq!(entry.tags, BTreeSet::from([tag("personal")]));
+
+        UpdateEntryRequest::new()
+            .without_tags()
+            .apply_to(&mut entry);
+        assert!(entry.tags.is_empty());
+
+        let query = ListEntriesQuery::new().with_tags(BTreeSet::from([tag("oncall")]));
+        assert_eq!(serde_json::to_value(&query).unwrap()["tags"], "oncall");
+    }
+
+    #[test]
+    fn test_task_details_in_requests() {
+        let calendar_id = Uuid::new_v4();
+        let date = NaiveDate::from_ymd_opt(2024, 6, 3).unwrap();
+        let five = NaiveTime::from_hms_opt(17, 0, 0).unwrap();
+        let req = CreateEntryRequest::task(calendar_id, "Pack", date)
+            .with_due_time(five)
+            .with_priority(TaskPriority::High)
+            .with_checklist_item(ChecklistItem::new("Passport").completed())
+            .with_checklist_item(ChecklistItem::new("Charger"))
+            .with_roll_over();
+
+        // The checklist is sent as a single form field, one item per line
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["checklist"], "[x] Passport\n[ ] Charger");
+        assert_eq!(json["priority"], "high");
+
+        let mut entry = req.into_entry().unwrap();
+        assert_eq!(entry.kind.due_time(), Some(five));
+        assert_eq!(entry.kind.priority(), Some(TaskPriority::High));
+        assert_eq!(entry.kind.checklist().len(), 2);
+        assert!(entry.kind.rolls_over());
+
+        UpdateEntryRequest::new()
+            .with_priority(TaskPriority::Low)
+            .without_due_time()
+            .without_checklist()
+            .with_roll_over(false)
+            .apply_to(&mut entry);
+        assert_eq!(entry.kind.priority(), Some(TaskPriority::Low));
+        assert_eq!(entry.kind.due_time(), None);
+        assert!(entry.kind.checklist().is_empty());
+        assert!(!entry.kind.rolls_over());
+
+        // Re-sending the task type keeps the task's details
+        UpdateEntryRequest::new()
+            .with_entry_type(EntryType::Task)
+            .with_completed(true)
+            .apply_to(&mut entry);
+        assert!(entry.kind.is_completed());
+        assert_eq!(entry.kind.priority(), Some(TaskPriority::Low));
+    }
+
+    #[test]
+    fn test_update_request_scope() {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+        let update = UpdateEntryRequest::new()
+            .with_title("Moved")
+            .with_scope(RecurrenceScope::This, date);
+
+        let json = serde_json::to_value(&update).unwrap();
+        assert_eq!(json["scope"], "this");
+        assert_eq!(json["occurrence_date"], "2024-06-10");
+
+        let json = serde_json::to_value(UpdateEntryRequest::new()).unwrap();
+        assert!(json.get("scope").is_none());
+    }
+
     #[test]
     fn test_list_entries_query() {
         let calendar_id = Uuid::new_v4();
@@ -638,7 +1364,7 @@ mod tests {
             EntryType::Timed
         );
         assert_eq!(
-            EntryType::from_kind(&EntryKind::Task { completed: false }),
+            EntryType::from_kind(&EntryKind::task(false)),
             EntryType::Task
         );
         assert_eq!(
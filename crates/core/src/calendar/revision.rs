@@ -0,0 +1,282 @@
+//! Entry revision history.
+//!
+//! Every create, update, delete and restore of an entry appends an immutable
+//! revision holding who made the change, when, which fields changed and a
+//! full snapshot of the entry afterwards. A recurring series' revisions also
+//! hold its occurrence overrides, so editing or cancelling one occurrence is
+//! a revision of the series too. An entry can be reverted to any earlier
+//! snapshot, which itself is recorded as a new revision.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::error::RevisionError;
+use super::occurrence::OccurrenceOverride;
+use super::types::CalendarEntry;
+
+/// What happened to an entry in a revision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RevisionAction {
+    Created,
+    Updated,
+    /// Moved to the trash.
+    Deleted,
+    /// Taken out of the trash.
+    Restored,
+}
+
+impl RevisionAction {
+    /// Returns the action as its lowercase name.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Updated => "updated",
+            Self::Deleted => "deleted",
+            Self::Restored => "restored",
+        }
+    }
+}
+
+impl fmt::Display for RevisionAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RevisionAction {
+    type Err = RevisionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "created" => Ok(Self::Created),
+            "updated" => Ok(Self::Updated),
+            "deleted" => Ok(Self::Deleted),
+            "restored" => Ok(Self::Restored),
+            _ => Err(RevisionError::InvalidAction(s.to_string())),
+        }
+    }
+}
+
+/// One change to an entry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntryRevision {
+    pub entry_id: Uuid,
+    /// Position in the entry's history, starting at 1.
+    pub revision: u32,
+    pub action: RevisionAction,
+    /// The user who made the change, if it was made on someone's behalf.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor_id: Option<Uuid>,
+    pub recorded_at: DateTime<Utc>,
+    /// Fields that differ from the entry before the change, for updates.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changed_fields: Vec<String>,
+    /// The entry as it was after the change (before it, for deletions).
+    pub snapshot: CalendarEntry,
+    /// The series' occurrence overrides alongside the snapshot. `None` for
+    /// entries that don't recur and for revisions recorded before overrides
+    /// were kept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overrides: Option<Vec<OccurrenceOverride>>,
+}
+
+impl EntryRevision {
+    /// Creates revision number `revision` of `snapshot`'s entry.
+    pub fn new(
+        revision: u32,
+        action: RevisionAction,
+        snapshot: CalendarEntry,
+        actor_id: Option<Uuid>,
+        recorded_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            entry_id: snapshot.id,
+            revision,
+            action,
+            actor_id,
+            recorded_at,
+            changed_fields: Vec::new(),
+            snapshot,
+            overrides: None,
+        }
+    }
+
+    /// Records which fields of the snapshot differ from `before`.
+    pub fn with_changes_from(mut self, before: &CalendarEntry) -> Self {
+        self.changed_fields = changed_fields(before, &self.snapshot)
+            .into_iter()
+            .map(String::from)
+            .collect();
+        self
+    }
+
+    /// Records the series' overrides alongside the snapshot, noting
+    /// `overrides` as changed when they differ from `before`.
+    pub fn with_overrides(
+        mut self,
+        overrides: Vec<OccurrenceOverride>,
+        before: Option<&[OccurrenceOverride]>,
+    ) -> Self {
+        if before.is_some_and(|before| before != overrides.as_slice()) {
+            self.changed_fields.push("overrides".to_string());
+        }
+        self.overrides = Some(overrides);
+        self
+    }
+}
+
+/// Returns the number the next revision of an entry gets, given its history
+/// so far (oldest first).
+pub fn next_revision_number(history: &[EntryRevision]) -> u32 {
+    history.last().map_or(1, |last| last.revision + 1)
+}
+
+/// Lists the user-editable fields that differ between two versions of an
+/// entry, by their JSON names. Timestamps are ignored.
+pub fn changed_fields(before: &CalendarEntry, after: &CalendarEntry) -> Vec<&'static str> {
+    let checks = [
+        ("calendar_id", before.calendar_id != after.calendar_id),
+        ("title", before.title != after.title),
+        ("description", before.description != after.description),
+        ("location", before.location != after.location),
+        ("kind", before.kind != after.kind),
+        ("start_date", before.start_date != after.start_date),
+        ("end_date", before.end_date != after.end_date),
+        ("color", before.color != after.color),
+        ("timezone", before.timezone != after.timezone),
+        ("recurrence", before.recurrence != after.recurrence),
+        ("reminders", before.reminders != after.reminders),
+        ("attendees", before.attendees != after.attendees),
+        ("tags", before.tags != after.tags),
+    ];
+    checks
+        .into_iter()
+        .filter(|(_, changed)| *changed)
+        .map(|(field, _)| field)
+        .collect()
+}
+
+/// Returns `current` with its content put back to `revision`'s snapshot.
+///
+/// The entry keeps its ID, calendar, version and creation time; only what a
+/// user can edit is reverted.
+pub fn revert_to(
+    current: &CalendarEntry,
+    revision: &EntryRevision,
+    now: DateTime<Utc>,
+) -> CalendarEntry {
+    CalendarEntry {
+        id: current.id,
+        calendar_id: current.calendar_id,
+        occurrence_date: None,
+        segment: None,
+        version: current.version,
+        created_at: current.created_at,
+        updated_at: now,
+        ..revision.snapshot.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::RecurrenceRule;
+    use chrono::NaiveDate;
+
+    fn standup() -> CalendarEntry {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        CalendarEntry::all_day(Uuid::new_v4(), "Standup", date)
+    }
+
+    #[test]
+    fn test_revision_action_round_trip() {
+        for action in [
+            RevisionAction::Created,
+            RevisionAction::Updated,
+            RevisionAction::Deleted,
+            RevisionAction::Restored,
+        ] {
+            assert_eq!(action.as_str().parse::<RevisionAction>(), Ok(action));
+        }
+        assert!("renamed".parse::<RevisionAction>().is_err());
+    }
+
+    #[test]
+    fn test_changed_fields() {
+        let before = standup();
+        let mut after = before.clone().with_location("Room 1");
+        after.title = "Daily standup".to_string();
+        after.updated_at = Utc::now();
+
+        assert_eq!(changed_fields(&before, &after), vec!["title", "location"]);
+        assert!(changed_fields(&before, &before).is_empty());
+    }
+
+    #[test]
+    fn test_next_revision_number() {
+        let entry = standup();
+        assert_eq!(next_revision_number(&[]), 1);
+
+        let first = EntryRevision::new(1, RevisionAction::Created, entry.clone(), None, Utc::now());
+        let second = EntryRevision::new(2, RevisionAction::Updated, entry, None, Utc::now());
+        assert_eq!(next_revision_number(&[first, second]), 3);
+    }
+
+    #[test]
+    fn test_revert_to_keeps_identity() {
+        let original = standup();
+        let revision = EntryRevision::new(
+            1,
+            RevisionAction::Created,
+            original.clone(),
+            None,
+            original.created_at,
+        );
+
+        let mut current = original.clone().with_location("Room 1");
+        current.title = "Daily standup".to_string();
+        current.calendar_id = Uuid::new_v4();
+
+        let now = Utc::now();
+        let reverted = revert_to(&current, &revision, now);
+        assert_eq!(reverted.title, "Standup");
+        assert_eq!(reverted.location, None);
+        assert_eq!(reverted.id, current.id);
+        assert_eq!(reverted.calendar_id, current.calendar_id);
+        assert_eq!(reverted.updated_at, now);
+    }
+
+    #[test]
+    fn test_with_changes_from() {
+        let before = standup();
+        let after = before.clone().with_location("Room 1");
+        let revision = EntryRevision::new(2, RevisionAction::Updated, after, None, Utc::now())
+            .with_changes_from(&before);
+
+        assert_eq!(revision.entry_id, before.id);
+        assert_eq!(revision.changed_fields, vec!["location".to_string()]);
+    }
+
+    #[test]
+    fn test_with_overrides() {
+        let series = standup().with_recurrence(RecurrenceRule::weekly());
+        let date = NaiveDate::from_ymd_opt(2024, 1, 22).unwrap();
+        let cancelled = vec![OccurrenceOverride::cancelled(series.id, date)];
+
+        let revision =
+            EntryRevision::new(2, RevisionAction::Updated, series.clone(), None, Utc::now())
+                .with_changes_from(&series)
+                .with_overrides(cancelled.clone(), Some(&[]));
+        assert_eq!(revision.changed_fields, vec!["overrides".to_string()]);
+        assert_eq!(revision.overrides, Some(cancelled.clone()));
+
+        let unchanged = EntryRevision::new(3, RevisionAction::Updated, series, None, Utc::now())
+            .with_overrides(cancelled.clone(), Some(&cancelled));
+        assert!(unchanged.changed_fields.is_empty());
+    }
+}
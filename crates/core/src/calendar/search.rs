@@ -0,0 +1,397 @@
+//! Full-text search over entry titles, descriptions and locations.
+//!
+//! Text is split into lowercase words of letters and digits. A query matches
+//! an entry when every query word is a prefix of some word in the entry's
+//! title, description or location, so "dent" finds "Dentist".
+//!
+//! Storage backends use these functions to index entries and to rank and
+//! highlight what they find, so results look the same on every backend.
+
+use std::cmp::Reverse;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::error::SearchError;
+use super::types::{Calendar, CalendarEntry};
+
+/// Number of results returned when a search does not ask for a limit.
+pub const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+/// Largest number of results a single search may return.
+pub const MAX_SEARCH_LIMIT: usize = 100;
+
+/// Largest number of words in a search query.
+pub const MAX_SEARCH_TERMS: usize = 10;
+
+/// Characters of context kept on each side of the first match in a snippet.
+const SNIPPET_CONTEXT: usize = 30;
+
+/// Splits text into lowercase words of letters and digits.
+pub fn tokenize(text: &str) -> Vec<String> {
+    words(text).map(|(_, word)| word.to_lowercase()).collect()
+}
+
+/// Returns the distinct words of an entry's title, description and location.
+///
+/// Backends without a full-text engine index entries by these words.
+pub fn entry_search_terms(entry: &CalendarEntry) -> BTreeSet<String> {
+    SearchField::ALL
+        .iter()
+        .filter_map(|field| field.text(entry))
+        .flat_map(tokenize)
+        .collect()
+}
+
+/// Yields each word of `text` with its byte offset.
+fn words(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut start = None;
+    let mut boundaries = text
+        .char_indices()
+        .chain(std::iter::once((text.len(), ' ')));
+    std::iter::from_fn(move || {
+        for (i, c) in boundaries.by_ref() {
+            match (start, c.is_alphanumeric()) {
+                (None, true) => start = Some(i),
+                (Some(s), false) => {
+                    start = None;
+                    return Some((s, &text[s..i]));
+                }
+                _ => {}
+            }
+        }
+        None
+    })
+}
+
+/// An entry field covered by search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchField {
+    Title,
+    Description,
+    Location,
+}
+
+impl SearchField {
+    /// Every searchable field, in the order snippets are returned.
+    pub const ALL: [SearchField; 3] = [Self::Title, Self::Description, Self::Location];
+
+    /// Returns the field name in lowercase.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Title => "title",
+            Self::Description => "description",
+            Self::Location => "location",
+        }
+    }
+
+    /// Returns the field's text in `entry`, if set.
+    pub fn text<'a>(&self, entry: &'a CalendarEntry) -> Option<&'a str> {
+        match self {
+            Self::Title => Some(entry.title.as_str()),
+            Self::Description => entry.description.as_deref(),
+            Self::Location => entry.location.as_deref(),
+        }
+    }
+
+    /// How much a match in this field counts towards an entry's score.
+    fn weight(&self) -> u32 {
+        match self {
+            Self::Title => 4,
+            Self::Location => 2,
+            Self::Description => 1,
+        }
+    }
+}
+
+impl fmt::Display for SearchField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A parsed search query: the distinct lowercase words to look for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchQuery {
+    terms: Vec<String>,
+}
+
+impl SearchQuery {
+    /// Returns the query words, in the order they were typed.
+    pub fn terms(&self) -> &[String] {
+        &self.terms
+    }
+
+    /// Returns true if every query word is a prefix of some word in the entry.
+    pub fn matches(&self, entry: &CalendarEntry) -> bool {
+        let words = entry_search_terms(entry);
+        self.terms
+            .iter()
+            .all(|term| words.iter().any(|word| word.starts_with(term.as_str())))
+    }
+
+    /// Scores how well an entry matches: title matches count most, then
+    /// location, then description. Whole-word matches count double.
+    pub fn score(&self, entry: &CalendarEntry) -> u32 {
+        let mut score = 0;
+        for field in SearchField::ALL {
+            let Some(text) = field.text(entry) else {
+                continue;
+            };
+            let field_words = tokenize(text);
+            for term in &self.terms {
+                if field_words.iter().any(|word| word == term) {
+                    score += 2 * field.weight();
+                } else if field_words
+                    .iter()
+                    .any(|word| word.starts_with(term.as_str()))
+                {
+                    score += field.weight();
+                }
+            }
+        }
+        score
+    }
+
+    /// Returns a highlighted snippet for each field of the entry that matches.
+    pub fn snippets(&self, entry: &CalendarEntry) -> Vec<Snippet> {
+        SearchField::ALL
+            .iter()
+            .filter_map(|field| self.snippet(*field, field.text(entry)?))
+            .collect()
+    }
+
+    /// Builds the snippet of one field, around its first matching word.
+    fn snippet(&self, field: SearchField, text: &str) -> Option<Snippet> {
+        let matched: Vec<(usize, usize)> = words(text)
+            .filter(|(_, word)| {
+                let word = word.to_lowercase();
+                self.terms
+                    .iter()
+                    .any(|term| word.starts_with(term.as_str()))
+            })
+            .map(|(start, word)| (start, start + word.len()))
+            .collect();
+        let &(first_start, first_end) = matched.first()?;
+
+        // Keep SNIPPET_CONTEXT characters on each side of the first match
+        let from = text[..first_start]
+            .char_indices()
+            .rev()
+            .nth(SNIPPET_CONTEXT - 1)
+            .map_or(0, |(i, _)| i);
+        let to = text[first_end..]
+            .char_indices()
+            .nth(SNIPPET_CONTEXT)
+            .map_or(text.len(), |(i, _)| first_end + i);
+
+        let prefix = if from > 0 { "…" } else { "" };
+        let suffix = if to < text.len() { "…" } else { "" };
+        let offset = prefix.len();
+        let highlights = matched
+            .into_iter()
+            .filter(|&(start, end)| start >= from && end <= to)
+            .map(|(start, end)| Highlight {
+                start: start - from + offset,
+                end: end - from + offset,
+            })
+            .collect();
+
+        Some(Snippet {
+            field,
+            text: format!("{prefix}{}{suffix}", &text[from..to]),
+            highlights,
+        })
+    }
+}
+
+impl FromStr for SearchQuery {
+    type Err = SearchError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut terms: Vec<String> = Vec::new();
+        for term in tokenize(s) {
+            if !terms.contains(&term) {
+                terms.push(term);
+            }
+        }
+
+        if terms.is_empty() {
+            return Err(SearchError::EmptyQuery);
+        }
+        if terms.len() > MAX_SEARCH_TERMS {
+            return Err(SearchError::TooManyTerms(MAX_SEARCH_TERMS));
+        }
+        Ok(Self { terms })
+    }
+}
+
+/// Formats the query as its words separated by spaces.
+impl fmt::Display for SearchQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.terms.join(" "))
+    }
+}
+
+/// A matching word within a snippet, as byte offsets into the snippet text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Highlight {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// An excerpt of a matching field, with the matching words marked.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snippet {
+    pub field: SearchField,
+    pub text: String,
+    pub highlights: Vec<Highlight>,
+}
+
+impl Snippet {
+    /// Returns the snippet text with each highlight wrapped in `open` and `close`.
+    pub fn marked(&self, open: &str, close: &str) -> String {
+        let mut output = String::with_capacity(self.text.len());
+        let mut last = 0;
+        for highlight in &self.highlights {
+            output.push_str(&self.text[last..highlight.start]);
+            output.push_str(open);
+            output.push_str(&self.text[highlight.start..highlight.end]);
+            output.push_str(close);
+            last = highlight.end;
+        }
+        output.push_str(&self.text[last..]);
+        output
+    }
+}
+
+/// A search result: the matching entry, its calendar and highlighted snippets.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub calendar: Calendar,
+    pub entry: CalendarEntry,
+    pub snippets: Vec<Snippet>,
+}
+
+impl SearchHit {
+    /// Creates a hit for `entry`, highlighting the words that match `query`.
+    pub fn new(query: &SearchQuery, calendar: Calendar, entry: CalendarEntry) -> Self {
+        let snippets = query.snippets(&entry);
+        Self {
+            calendar,
+            entry,
+            snippets,
+        }
+    }
+}
+
+/// Keeps the entries that match `query`, best matches first, at most `limit`.
+///
+/// Entries with the same score are ordered by start date, latest first.
+pub fn rank_entries(
+    query: &SearchQuery,
+    entries: Vec<CalendarEntry>,
+    limit: usize,
+) -> Vec<CalendarEntry> {
+    let mut scored: Vec<(u32, CalendarEntry)> = entries
+        .into_iter()
+        .filter(|entry| query.matches(entry))
+        .map(|entry| (query.score(&entry), entry))
+        .collect();
+    scored.sort_by_key(|(score, entry)| (Reverse(*score), Reverse(entry.start_date)));
+    scored
+        .into_iter()
+        .take(limit)
+        .map(|(_, entry)| entry)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use uuid::Uuid;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+    }
+
+    fn query(s: &str) -> SearchQuery {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_search_query() {
+        assert_eq!(
+            query("Dentist, dentist  appt!").terms(),
+            ["dentist", "appt"]
+        );
+        assert_eq!(query("Café 2024").to_string(), "café 2024");
+        assert_eq!("  ?! ".parse::<SearchQuery>(), Err(SearchError::EmptyQuery));
+        assert_eq!(
+            "a b c d e f g h i j k".parse::<SearchQuery>(),
+            Err(SearchError::TooManyTerms(MAX_SEARCH_TERMS))
+        );
+    }
+
+    #[test]
+    fn test_query_matches_word_prefixes() {
+        let entry = CalendarEntry::all_day(Uuid::new_v4(), "Dentist", date(4))
+            .with_location("Main St. clinic");
+
+        assert!(query("dent").matches(&entry));
+        assert!(query("dentist clinic").matches(&entry));
+        assert!(!query("tist").matches(&entry));
+        assert!(!query("dentist hospital").matches(&entry));
+        assert_eq!(
+            entry_search_terms(&entry),
+            BTreeSet::from(["clinic", "dentist", "main", "st"].map(String::from))
+        );
+    }
+
+    #[test]
+    fn test_rank_entries() {
+        let cal_id = Uuid::new_v4();
+        let in_title = CalendarEntry::all_day(cal_id, "Dentist", date(4));
+        let in_description = CalendarEntry::all_day(cal_id, "Checkup", date(20))
+            .with_description("Ask the dentist about X-rays");
+        let older_title = CalendarEntry::all_day(cal_id, "Dentist", date(1));
+        let unrelated = CalendarEntry::all_day(cal_id, "Gym", date(5));
+
+        let ranked = rank_entries(
+            &query("dentist"),
+            vec![
+                in_description.clone(),
+                older_title.clone(),
+                unrelated,
+                in_title.clone(),
+            ],
+            10,
+        );
+        assert_eq!(ranked, vec![in_title.clone(), older_title, in_description]);
+
+        let ranked = rank_entries(&query("dentist"), vec![in_title.clone()], 0);
+        assert!(ranked.is_empty());
+    }
+
+    #[test]
+    fn test_snippets_highlight_matching_words() {
+        let entry = CalendarEntry::all_day(Uuid::new_v4(), "Dentist", date(4)).with_description(
+            "Bring the insurance card and the referral letter, then ask the dentist about \
+             the follow-up visit next month",
+        );
+        let snippets = query("dent").snippets(&entry);
+
+        assert_eq!(snippets.len(), 2);
+        assert_eq!(snippets[0].field, SearchField::Title);
+        assert_eq!(snippets[0].marked("[", "]"), "[Dentist]");
+        assert_eq!(snippets[1].field, SearchField::Description);
+        assert_eq!(
+            snippets[1].marked("[", "]"),
+            "…referral letter, then ask the [dentist] about the follow-up visit nex…"
+        );
+    }
+}
@@ -1,11 +1,15 @@
 use std::collections::HashMap;
 
-use chrono::{Datelike, Duration, NaiveDate};
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime};
 
-use super::types::{CalendarEntry, DayData};
+use super::recurrence::expand_recurring_entries;
+use super::task::{compare_tasks, roll_over_tasks};
+use super::types::{CalendarEntry, DayData, DaySegment};
+use crate::storage::DateRange;
 
 /// Sorts entries by hierarchy: MultiDay -> AllDay -> Timed -> Task.
-/// Within timed entries, sorts by start time.
+/// Within timed entries, sorts by the time they start on the shown day.
+/// Within tasks, sorts by priority and then due time (see [`compare_tasks`]).
 pub fn sort_entries_by_hierarchy(entries: &mut [CalendarEntry]) {
     entries.sort_by(|a, b| {
         let priority_cmp = a.kind.sort_priority().cmp(&b.kind.sort_priority());
@@ -13,20 +17,24 @@ pub fn sort_entries_by_hierarchy(entries: &mut [CalendarEntry]) {
             return priority_cmp;
         }
 
+        if a.kind.is_task() && b.kind.is_task() {
+            return compare_tasks(&a.kind, &b.kind);
+        }
+
         // Within the same priority, sort timed entries by start time
-        match (a.kind.start_time(), b.kind.start_time()) {
+        match (a.display_start_time(), b.display_start_time()) {
             (Some(a_time), Some(b_time)) => a_time.cmp(&b_time),
             _ => std::cmp::Ordering::Equal,
         }
     });
 }
 
-/// Groups entries by their start date.
+/// Groups entries by the day they are shown on (see [`CalendarEntry::display_date`]).
 pub fn group_entries_by_date(entries: &[CalendarEntry]) -> HashMap<NaiveDate, Vec<&CalendarEntry>> {
     let mut grouped: HashMap<NaiveDate, Vec<&CalendarEntry>> = HashMap::new();
 
     for entry in entries {
-        grouped.entry(entry.start_date).or_default().push(entry);
+        grouped.entry(entry.display_date()).or_default().push(entry);
     }
 
     grouped
@@ -49,29 +57,62 @@ pub fn get_calendar_week(date: NaiveDate) -> Vec<NaiveDate> {
         .collect()
 }
 
-/// Expands multi-day events into separate entries for each day they span.
-pub fn expand_multi_day_entries(entries: Vec<CalendarEntry>) -> Vec<CalendarEntry> {
-    let mut expanded = Vec::new();
+/// Splits an entry that spans several days into one copy per day, each
+/// carrying its [`DaySegment`]. Entries within a single day are returned as is.
+///
+/// A timed entry ending exactly at midnight is not shown on its end day.
+pub fn segment_entry(entry: CalendarEntry) -> Vec<CalendarEntry> {
+    let last_day = if entry.spans_days() && entry.kind.end_time() == Some(NaiveTime::MIN) {
+        entry.end_date - Duration::days(1)
+    } else {
+        entry.end_date
+    };
+    if last_day <= entry.start_date {
+        return vec![entry];
+    }
 
-    for entry in entries {
-        if entry.kind.is_multi_day() {
-            let mut current = entry.start_date;
-            while current <= entry.end_date {
-                let day_entry = entry.clone();
-                expanded.push(day_entry);
-                current += Duration::days(1);
-            }
-        } else {
-            expanded.push(entry);
-        }
+    let mut segments = Vec::new();
+    let mut current = entry.start_date;
+    while current <= last_day {
+        let mut day_entry = entry.clone();
+        day_entry.segment = Some(DaySegment {
+            date: current,
+            continued: current > entry.start_date,
+            continues: current < last_day,
+        });
+        segments.push(day_entry);
+        current += Duration::days(1);
     }
 
-    expanded
+    segments
+}
+
+/// Expands multi-day events and timed entries crossing midnight into
+/// separate entries for each day they span.
+pub fn expand_multi_day_entries(entries: Vec<CalendarEntry>) -> Vec<CalendarEntry> {
+    entries.into_iter().flat_map(segment_entry).collect()
 }
 
 /// Builds DayData for a range of dates from the given entries.
-/// Entries are sorted by hierarchy within each day.
-pub fn build_day_data(dates: &[NaiveDate], entries: Vec<CalendarEntry>) -> Vec<DayData> {
+/// Recurring entries are expanded into their occurrences within `dates`.
+/// When `dates` include `today`, incomplete roll-over tasks from past days are
+/// shown on today. Entries are sorted by hierarchy within each day.
+pub fn build_day_data(
+    dates: &[NaiveDate],
+    entries: Vec<CalendarEntry>,
+    today: NaiveDate,
+) -> Vec<DayData> {
+    let entries = match (dates.iter().min(), dates.iter().max()) {
+        (Some(&start), Some(&end)) => {
+            expand_recurring_entries(entries, &[], &DateRange { start, end })
+        }
+        _ => entries,
+    };
+    let entries = if dates.contains(&today) {
+        roll_over_tasks(entries, today)
+    } else {
+        entries
+    };
     let expanded = expand_multi_day_entries(entries);
     let grouped = group_entries_by_date(&expanded);
 
@@ -94,7 +135,6 @@ pub fn build_day_data(dates: &[NaiveDate], entries: Vec<CalendarEntry>) -> Vec<D
 mod tests {
     use super::*;
     use crate::calendar::types::CalendarEntry;
-    use chrono::NaiveTime;
     use uuid::Uuid;
 
     fn test_calendar_id() -> Uuid {
@@ -132,6 +172,25 @@ mod tests {
         assert!(entries[4].kind.is_task());
     }
 
+    #[test]
+    fn test_sort_tasks_by_priority() {
+        use crate::calendar::TaskPriority;
+
+        let cal_id = test_calendar_id();
+        let date = make_date(2024, 1, 15);
+        let mut entries = vec![
+            CalendarEntry::task(cal_id, "Later", date, false),
+            CalendarEntry::task(cal_id, "Noon", date, false).with_due_time(make_time(12, 0)),
+            CalendarEntry::task(cal_id, "Urgent", date, false).with_priority(TaskPriority::Urgent),
+            CalendarEntry::task(cal_id, "Someday", date, false).with_priority(TaskPriority::Low),
+        ];
+
+        sort_entries_by_hierarchy(&mut entries);
+
+        let titles: Vec<&str> = entries.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, vec!["Urgent", "Noon", "Later", "Someday"]);
+    }
+
     #[test]
     fn test_get_week_dates() {
         let center = make_date(2024, 1, 15); // Monday
@@ -169,6 +228,111 @@ mod tests {
         assert_eq!(expanded.iter().filter(|e| e.title == "Retreat").count(), 3);
     }
 
+    #[test]
+    fn test_segment_entry_overnight() {
+        let cal_id = test_calendar_id();
+        let start = make_date(2024, 1, 15);
+        let shift = CalendarEntry::timed(
+            cal_id,
+            "Night shift",
+            start,
+            make_time(22, 0),
+            make_time(6, 0),
+        )
+        .with_end_date(start + Duration::days(1));
+
+        let segments = segment_entry(shift);
+
+        assert_eq!(segments.len(), 2);
+        assert_eq!(
+            segments[0].segment,
+            Some(DaySegment {
+                date: start,
+                continued: false,
+                continues: true,
+            })
+        );
+        assert_eq!(
+            segments[1].segment,
+            Some(DaySegment {
+                date: start + Duration::days(1),
+                continued: true,
+                continues: false,
+            })
+        );
+        assert_eq!(segments[1].display_start_time(), Some(NaiveTime::MIN));
+
+        let single = CalendarEntry::all_day(cal_id, "Single", start);
+        assert!(segment_entry(single)[0].segment.is_none());
+    }
+
+    #[test]
+    fn test_segment_entry_ending_at_midnight() {
+        let cal_id = test_calendar_id();
+        let start = make_date(2024, 1, 15);
+        let evening =
+            CalendarEntry::timed(cal_id, "Late show", start, make_time(22, 0), NaiveTime::MIN)
+                .with_end_date(start + Duration::days(1));
+
+        let segments = segment_entry(evening);
+        assert_eq!(segments.len(), 1);
+        assert!(segments[0].segment.is_none());
+        assert_eq!(segments[0].display_date(), start);
+
+        let long =
+            CalendarEntry::timed(cal_id, "Marathon", start, make_time(22, 0), NaiveTime::MIN)
+                .with_end_date(start + Duration::days(2));
+
+        let segments = segment_entry(long);
+        assert_eq!(segments.len(), 2);
+        assert_eq!(
+            segments[1].segment,
+            Some(DaySegment {
+                date: start + Duration::days(1),
+                continued: true,
+                continues: false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_build_day_data_splits_entries_across_days() {
+        let cal_id = test_calendar_id();
+        let monday = make_date(2024, 1, 15);
+        let dates = get_calendar_week(monday);
+        let entries = vec![
+            // Monday 14:00 to Wednesday 12:00
+            CalendarEntry::timed(
+                cal_id,
+                "Conference",
+                monday,
+                make_time(14, 0),
+                make_time(12, 0),
+            )
+            .with_end_date(monday + Duration::days(2)),
+            CalendarEntry::timed(
+                cal_id,
+                "Breakfast",
+                monday + Duration::days(1),
+                make_time(8, 0),
+                make_time(9, 0),
+            ),
+        ];
+
+        let day_data = build_day_data(&dates, entries, monday);
+
+        assert_eq!(day_data[0].entries.len(), 1);
+        assert_eq!(day_data[1].entries.len(), 2);
+        assert_eq!(day_data[2].entries.len(), 1);
+        assert!(day_data[3].is_empty());
+
+        // The continued segment starts at midnight, before breakfast
+        assert_eq!(day_data[1].entries[0].title, "Conference");
+        let middle = day_data[1].entries[0].segment.unwrap();
+        assert!(middle.continued && middle.continues);
+        assert!(!day_data[2].entries[0].segment.unwrap().continues);
+    }
+
     #[test]
     fn test_group_entries_by_date() {
         let cal_id = test_calendar_id();
@@ -199,7 +363,7 @@ mod tests {
             CalendarEntry::all_day(cal_id, "Meeting", date2),
         ];
 
-        let day_data = build_day_data(&dates, entries);
+        let day_data = build_day_data(&dates, entries, date1);
 
         assert_eq!(day_data.len(), 2);
 
@@ -211,4 +375,64 @@ mod tests {
         // Second day
         assert_eq!(day_data[1].entries.len(), 1);
     }
+
+    #[test]
+    fn test_build_day_data_expands_recurring_entries() {
+        use crate::calendar::RecurrenceRule;
+
+        let cal_id = test_calendar_id();
+        let series_start = make_date(2024, 1, 1);
+        let dates = get_calendar_week(make_date(2024, 1, 10));
+        let entries = vec![CalendarEntry::timed(
+            cal_id,
+            "Standup",
+            series_start,
+            make_time(9, 0),
+            make_time(9, 15),
+        )
+        .with_recurrence(RecurrenceRule::daily().with_interval(2))];
+
+        let day_data = build_day_data(&dates, entries, series_start);
+
+        // Every other day from Jan 1: Jan 9, 11, 13 fall within Jan 8-14.
+        let days_with_standup: Vec<NaiveDate> = day_data
+            .iter()
+            .filter(|d| !d.is_empty())
+            .map(|d| d.date)
+            .collect();
+        assert_eq!(
+            days_with_standup,
+            vec![
+                make_date(2024, 1, 9),
+                make_date(2024, 1, 11),
+                make_date(2024, 1, 13)
+            ]
+        );
+        assert_eq!(
+            day_data[1].entries[0].occurrence_date,
+            Some(make_date(2024, 1, 9))
+        );
+    }
+
+    #[test]
+    fn test_build_day_data_rolls_over_tasks() {
+        let cal_id = test_calendar_id();
+        let monday = make_date(2024, 1, 15);
+        let dates = get_calendar_week(monday);
+        let today = monday + Duration::days(2);
+        let entries = vec![
+            CalendarEntry::task(cal_id, "Overdue", monday, false).with_roll_over(),
+            CalendarEntry::task(cal_id, "Left behind", monday, false),
+        ];
+
+        let day_data = build_day_data(&dates, entries.clone(), today);
+        assert_eq!(day_data[0].entries.len(), 1);
+        assert_eq!(day_data[0].entries[0].title, "Left behind");
+        assert_eq!(day_data[2].entries[0].title, "Overdue");
+
+        // Viewing a week that doesn't include today leaves tasks on their date.
+        let next_week = today + Duration::days(7);
+        let day_data = build_day_data(&dates, entries, next_week);
+        assert_eq!(day_data[0].entries.len(), 2);
+    }
 }
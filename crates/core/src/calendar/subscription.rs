@@ -0,0 +1,536 @@
+//! Calendars that mirror an external iCalendar feed.
+//!
+//! Holidays, school terms and sports fixtures are published as .ics URLs. A
+//! subscribed calendar keeps a copy of one: the server fetches the feed
+//! periodically and brings the calendar's entries in line with it, while
+//! users can only read them. Each UID maps to a fixed entry ID
+//! ([`import_entry_id`]), so a refresh compares the feed with what is stored
+//! and only writes what actually changed.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::batch::EntryWrite;
+use super::error::SubscriptionError;
+use super::ics_import::{import_entry_id, reimport_entry, IcsImport, SkippedComponent};
+use super::occurrence::OccurrenceOverride;
+use super::revision::changed_fields;
+use super::types::CalendarEntry;
+
+/// Maximum length of a subscription URL.
+pub const MAX_SUBSCRIPTION_URL_LENGTH: usize = 2048;
+
+/// The feed a calendar mirrors and how its last refresh went.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CalendarSubscription {
+    /// `http` or `https` URL of the feed.
+    pub url: String,
+    /// When the feed was last fetched and applied.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refreshed_at: Option<DateTime<Utc>>,
+    /// Why the last refresh failed; cleared by the next one that succeeds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl CalendarSubscription {
+    /// Creates a subscription to a feed that has not been fetched yet.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            refreshed_at: None,
+            error: None,
+        }
+    }
+
+    /// Records a successful refresh.
+    pub fn refreshed(&mut self, at: DateTime<Utc>) {
+        self.refreshed_at = Some(at);
+        self.error = None;
+    }
+
+    /// Records a failed refresh, keeping the time of the last good one.
+    pub fn failed(&mut self, error: impl Into<String>) {
+        self.error = Some(error.into());
+    }
+}
+
+/// Checks a feed URL and returns it in the form it is fetched with.
+///
+/// `webcal://` and `webcals://` URLs, which calendar apps use for
+/// subscriptions, are fetched over `https`. URLs naming a private or loopback
+/// address are rejected unless their host is in `allowed_hosts` (see
+/// [`check_subscription_host`]).
+pub fn normalize_subscription_url(
+    url: &str,
+    allowed_hosts: &[String],
+) -> Result<String, SubscriptionError> {
+    let url = url.trim();
+    if url.len() > MAX_SUBSCRIPTION_URL_LENGTH {
+        return Err(SubscriptionError::UrlTooLong);
+    }
+    let (scheme, rest) = url
+        .split_once("://")
+        .ok_or_else(|| SubscriptionError::InvalidUrl(url.to_string()))?;
+    let scheme = match scheme.to_ascii_lowercase().as_str() {
+        "http" => "http",
+        "https" | "webcal" | "webcals" => "https",
+        _ => return Err(SubscriptionError::UnsupportedScheme(scheme.to_string())),
+    };
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = url_host(authority);
+    if host.is_empty() || rest.chars().any(char::is_whitespace) {
+        return Err(SubscriptionError::InvalidUrl(url.to_string()));
+    }
+    check_subscription_host(host, allowed_hosts)?;
+    Ok(format!("{scheme}://{rest}"))
+}
+
+/// Returns the host of a URL authority, without user info, port or the
+/// brackets around an IPv6 address.
+fn url_host(authority: &str) -> &str {
+    let host_port = authority.rsplit_once('@').map_or(authority, |(_, h)| h);
+    if let Some(bracketed) = host_port.strip_prefix('[') {
+        return bracketed.split(']').next().unwrap_or_default();
+    }
+    host_port.split(':').next().unwrap_or_default()
+}
+
+/// Rejects feed hosts that are private or loopback addresses, or `localhost`.
+///
+/// Feeds are fetched by the server, so without this check a subscription
+/// could read services that are only reachable from inside its network.
+/// Hosts in `allowed_hosts` (lowercased) pass regardless. Names that resolve
+/// to private addresses are caught when the feed is fetched, with
+/// [`is_public_address`].
+pub fn check_subscription_host(
+    host: &str,
+    allowed_hosts: &[String],
+) -> Result<(), SubscriptionError> {
+    let host = host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_ascii_lowercase();
+    if allowed_hosts.contains(&host) {
+        return Ok(());
+    }
+    let private = match host.parse::<IpAddr>() {
+        Ok(ip) => !is_public_address(ip),
+        Err(_) => host == "localhost" || host.ends_with(".localhost"),
+    };
+    if private {
+        return Err(SubscriptionError::PrivateAddress(host));
+    }
+    Ok(())
+}
+
+/// Returns true if `ip` is a globally reachable address that feeds may be
+/// fetched from: not loopback, private, link-local, shared (CGNAT),
+/// unspecified, broadcast, documentation or multicast.
+pub fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_ipv4(ip),
+            None => is_public_ipv6(ip),
+        },
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    let shared = a == 100 && (64..128).contains(&b);
+    !(ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || shared
+        || a == 0)
+}
+
+fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    let unique_local = first & 0xfe00 == 0xfc00;
+    let link_local = first & 0xffc0 == 0xfe80;
+    let documentation = first == 0x2001 && ip.segments()[1] == 0x0db8;
+    !(ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        || unique_local
+        || link_local
+        || documentation)
+}
+
+/// What a refresh did to a subscribed calendar.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RefreshReport {
+    pub created: usize,
+    pub updated: usize,
+    pub deleted: usize,
+    pub unchanged: usize,
+    /// Recurring entries whose cancelled or replaced occurrences changed.
+    pub overrides: usize,
+    pub skipped: Vec<SkippedComponent>,
+}
+
+/// The writes that bring a subscribed calendar in line with its feed.
+#[derive(Debug, Clone, Default)]
+pub struct SubscriptionSync {
+    /// Entry creates, updates and deletes, in no particular order.
+    pub writes: Vec<EntryWrite>,
+    /// The full set of occurrence overrides of each recurring entry whose
+    /// overrides changed, replacing the ones stored for it.
+    pub overrides: Vec<(Uuid, Vec<OccurrenceOverride>)>,
+    pub report: RefreshReport,
+}
+
+/// Returns the IDs of the entries a feed lists.
+///
+/// Used to restore entries that were removed from the feed and later added
+/// back before planning the sync, since a deleted entry keeps its ID in the
+/// trash.
+pub fn subscription_entry_ids(calendar_id: Uuid, import: &IcsImport) -> HashSet<Uuid> {
+    import
+        .entries
+        .iter()
+        .filter(|imported| imported.recurrence_id.is_none())
+        .map(|imported| import_entry_id(calendar_id, &imported.uid))
+        .collect()
+}
+
+/// Plans the writes that make calendar `calendar_id` match its feed.
+///
+/// `stored` are the calendar's live entries and `stored_overrides` the
+/// overrides of its recurring ones. Entries the feed no longer lists are
+/// deleted, and entries or overrides that didn't change are left alone so
+/// refreshing an unchanged feed writes nothing.
+pub fn plan_subscription_sync(
+    calendar_id: Uuid,
+    import: IcsImport,
+    stored: &[CalendarEntry],
+    stored_overrides: &HashMap<Uuid, Vec<OccurrenceOverride>>,
+) -> SubscriptionSync {
+    let mut sync = SubscriptionSync {
+        report: RefreshReport {
+            skipped: import.skipped,
+            ..RefreshReport::default()
+        },
+        ..SubscriptionSync::default()
+    };
+    let stored: HashMap<Uuid, &CalendarEntry> = stored.iter().map(|e| (e.id, e)).collect();
+    let skip = |sync: &mut SubscriptionSync, uid: &str, reason: &str| {
+        sync.report.skipped.push(SkippedComponent {
+            uid: Some(uid.to_string()),
+            reason: reason.to_string(),
+        });
+    };
+
+    let (series, occurrences): (Vec<_>, Vec<_>) = import
+        .entries
+        .into_iter()
+        .partition(|imported| imported.recurrence_id.is_none());
+
+    // Overrides wanted for each entry in the feed, by series ID
+    let mut wanted: BTreeMap<Uuid, Vec<OccurrenceOverride>> = BTreeMap::new();
+    let mut series_ids: HashMap<String, Uuid> = HashMap::new();
+    for imported in series {
+        if series_ids.contains_key(&imported.uid) {
+            skip(&mut sync, &imported.uid, "duplicate UID");
+            continue;
+        }
+        let id = import_entry_id(calendar_id, &imported.uid);
+        series_ids.insert(imported.uid.clone(), id);
+        wanted.insert(
+            id,
+            imported
+                .exdates
+                .iter()
+                .map(|date| OccurrenceOverride::cancelled(id, *date))
+                .collect(),
+        );
+
+        let entry = imported.to_entry(id);
+        match stored.get(&id) {
+            None => {
+                sync.report.created += 1;
+                sync.writes.push(EntryWrite::Create(entry));
+            }
+            Some(current) => {
+                let entry = reimport_entry(entry, current);
+                if changed_fields(current, &entry).is_empty() {
+                    sync.report.unchanged += 1;
+                } else {
+                    sync.report.updated += 1;
+                    sync.writes.push(EntryWrite::Update {
+                        expected_version: entry.version,
+                        entry,
+                    });
+                }
+            }
+        }
+    }
+
+    for imported in occurrences {
+        let Some(&series_id) = series_ids.get(&imported.uid) else {
+            skip(&mut sync, &imported.uid, "no recurring entry with this UID");
+            continue;
+        };
+        let original_date = imported
+            .recurrence_id
+            .expect("partitioned on recurrence_id");
+        let replacement = imported.to_entry(series_id);
+        wanted
+            .entry(series_id)
+            .or_default()
+            .push(OccurrenceOverride::replaced(
+                series_id,
+                original_date,
+                replacement,
+            ));
+    }
+
+    for (series_id, overrides) in wanted {
+        let current = stored_overrides
+            .get(&series_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        if overrides_changed(current, &overrides) {
+            sync.report.overrides += 1;
+            sync.overrides.push((series_id, overrides));
+        }
+    }
+
+    let listed: HashSet<Uuid> = series_ids.into_values().collect();
+    let mut removed: Vec<Uuid> = stored
+        .keys()
+        .filter(|id| !listed.contains(id))
+        .copied()
+        .collect();
+    removed.sort();
+    sync.report.deleted = removed.len();
+    sync.writes
+        .extend(removed.into_iter().map(EntryWrite::Delete));
+
+    sync
+}
+
+/// Returns true unless both lists cancel and replace the same occurrences
+/// the same way. Timestamps of replacements are ignored.
+pub fn overrides_changed(stored: &[OccurrenceOverride], wanted: &[OccurrenceOverride]) -> bool {
+    if stored.len() != wanted.len() {
+        return true;
+    }
+    let stored: HashMap<_, _> = stored
+        .iter()
+        .map(|o| (o.original_date, &o.replacement))
+        .collect();
+    wanted
+        .iter()
+        .any(|o| match (stored.get(&o.original_date), &o.replacement) {
+            (Some(None), None) => false,
+            (Some(Some(before)), Some(after)) => !changed_fields(before, after).is_empty(),
+            _ => true,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::parse_ics;
+    use chrono::NaiveDate;
+
+    const FEED: &str = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\nUID:new-year\r\nDTSTART;VALUE=DATE:20250101\r\nSUMMARY:New Year's Day\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nUID:standup\r\nDTSTART;VALUE=DATE:20250106\r\nRRULE:FREQ=WEEKLY\r\n\
+EXDATE;VALUE=DATE:20250113\r\nSUMMARY:Standup\r\nEND:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    /// Applies a sync to stored entries and overrides the way storage would.
+    fn apply(
+        sync: &SubscriptionSync,
+        stored: &mut Vec<CalendarEntry>,
+        overrides: &mut HashMap<Uuid, Vec<OccurrenceOverride>>,
+    ) {
+        for write in &sync.writes {
+            stored.retain(|e| e.id != write.entry_id());
+            match write {
+                EntryWrite::Create(entry) | EntryWrite::Update { entry, .. } => {
+                    stored.push(entry.clone())
+                }
+                EntryWrite::Delete(_) => {}
+            }
+        }
+        for (series_id, wanted) in &sync.overrides {
+            overrides.insert(*series_id, wanted.clone());
+        }
+    }
+
+    #[test]
+    fn test_normalize_subscription_url() {
+        assert_eq!(
+            normalize_subscription_url(" webcal://example.com/holidays.ics ", &[]).unwrap(),
+            "https://example.com/holidays.ics"
+        );
+        assert_eq!(
+            normalize_subscription_url("http://93.184.216.34:8080/a.ics", &[]).unwrap(),
+            "http://93.184.216.34:8080/a.ics"
+        );
+        assert_eq!(
+            normalize_subscription_url("ftp://example.com/a.ics", &[]),
+            Err(SubscriptionError::UnsupportedScheme("ftp".to_string()))
+        );
+        assert!(normalize_subscription_url("https:///a.ics", &[]).is_err());
+        assert!(normalize_subscription_url("example.com/a.ics", &[]).is_err());
+        assert!(normalize_subscription_url("https://example.com/a b.ics", &[]).is_err());
+    }
+
+    #[test]
+    fn test_normalize_subscription_url_rejects_private_hosts() {
+        for url in [
+            "http://127.0.0.1:8080/a.ics",
+            "http://localhost/a.ics",
+            "http://user@10.0.0.5/a.ics",
+            "http://169.254.169.254/latest/meta-data",
+            "http://[::1]:8080/a.ics",
+            "http://[::ffff:192.168.1.1]/a.ics",
+            "webcal://calendar.localhost/a.ics",
+        ] {
+            assert!(
+                matches!(
+                    normalize_subscription_url(url, &[]),
+                    Err(SubscriptionError::PrivateAddress(_))
+                ),
+                "{url} should be rejected"
+            );
+        }
+
+        let allowed = ["127.0.0.1".to_string()];
+        assert_eq!(
+            normalize_subscription_url("http://127.0.0.1:8080/a.ics", &allowed).unwrap(),
+            "http://127.0.0.1:8080/a.ics"
+        );
+    }
+
+    #[test]
+    fn test_is_public_address() {
+        for ip in ["93.184.216.34", "2606:2800:220:1:248:1893:25c8:1946"] {
+            assert!(is_public_address(ip.parse().unwrap()), "{ip}");
+        }
+        for ip in [
+            "0.0.0.0",
+            "10.1.2.3",
+            "100.64.0.1",
+            "172.16.0.1",
+            "192.168.0.1",
+            "fc00::1",
+            "fe80::1",
+        ] {
+            assert!(!is_public_address(ip.parse().unwrap()), "{ip}");
+        }
+    }
+
+    #[test]
+    fn test_subscription_refreshed_and_failed() {
+        let mut subscription = CalendarSubscription::new("https://example.com/a.ics");
+        let at = Utc::now();
+        subscription.failed("timed out");
+        assert_eq!(subscription.refreshed_at, None);
+        subscription.refreshed(at);
+        assert_eq!(subscription.refreshed_at, Some(at));
+        assert_eq!(subscription.error, None);
+        subscription.failed("HTTP 500");
+        assert_eq!(subscription.refreshed_at, Some(at));
+        assert_eq!(subscription.error.as_deref(), Some("HTTP 500"));
+    }
+
+    #[test]
+    fn test_plan_subscription_sync_only_writes_changes() {
+        let calendar_id = Uuid::new_v4();
+        let mut stored = Vec::new();
+        let mut overrides = HashMap::new();
+
+        let first = plan_subscription_sync(
+            calendar_id,
+            parse_ics(FEED, calendar_id).unwrap(),
+            &stored,
+            &overrides,
+        );
+        assert_eq!(first.report.created, 2);
+        assert_eq!(first.report.overrides, 1);
+        apply(&first, &mut stored, &mut overrides);
+
+        // The same feed again changes nothing
+        let again = plan_subscription_sync(
+            calendar_id,
+            parse_ics(FEED, calendar_id).unwrap(),
+            &stored,
+            &overrides,
+        );
+        assert!(again.writes.is_empty());
+        assert!(again.overrides.is_empty());
+        assert_eq!(again.report.unchanged, 2);
+
+        // Renamed, dropped and cancelled-occurrence changes
+        let changed = FEED
+            .replace("SUMMARY:Standup", "SUMMARY:Daily standup")
+            .replace("20250113", "20250120")
+            .replace(
+                "BEGIN:VEVENT\r\nUID:new-year\r\nDTSTART;VALUE=DATE:20250101\r\nSUMMARY:New Year's Day\r\nEND:VEVENT\r\n",
+                "",
+            );
+        let sync = plan_subscription_sync(
+            calendar_id,
+            parse_ics(&changed, calendar_id).unwrap(),
+            &stored,
+            &overrides,
+        );
+        assert_eq!(sync.report.updated, 1);
+        assert_eq!(sync.report.deleted, 1);
+        assert_eq!(sync.report.overrides, 1);
+        assert!(sync.writes.contains(&EntryWrite::Delete(import_entry_id(
+            calendar_id,
+            "new-year"
+        ))));
+        let (_, wanted) = &sync.overrides[0];
+        assert_eq!(wanted[0].original_date, date(2025, 1, 20));
+    }
+
+    #[test]
+    fn test_plan_subscription_sync_skips_orphan_occurrences() {
+        let calendar_id = Uuid::new_v4();
+        let feed = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\nUID:a\r\nDTSTART;VALUE=DATE:20250101\r\nSUMMARY:A\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nUID:a\r\nDTSTART;VALUE=DATE:20250102\r\nSUMMARY:A again\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nUID:b\r\nRECURRENCE-ID;VALUE=DATE:20250103\r\nDTSTART;VALUE=DATE:20250104\r\nSUMMARY:B\r\nEND:VEVENT\r\n\
+END:VCALENDAR\r\n";
+        let import = parse_ics(feed, calendar_id).unwrap();
+        assert_eq!(
+            subscription_entry_ids(calendar_id, &import),
+            HashSet::from([import_entry_id(calendar_id, "a")])
+        );
+
+        let sync = plan_subscription_sync(calendar_id, import, &[], &HashMap::new());
+        assert_eq!(sync.report.created, 1);
+        let reasons: Vec<&str> = sync
+            .report
+            .skipped
+            .iter()
+            .map(|s| s.reason.as_str())
+            .collect();
+        assert_eq!(
+            reasons,
+            vec!["duplicate UID", "no recurring entry with this UID"]
+        );
+    }
+}
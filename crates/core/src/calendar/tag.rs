@@ -0,0 +1,136 @@
+//! Tags (labels) on calendar entries.
+//!
+//! A tag is a short lowercase label such as `oncall`, `travel` or
+//! `team/platform`. Tags are normalized on parse so `Travel` and ` travel `
+//! name the same tag.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::error::TagError;
+
+/// Maximum length of a tag, in characters.
+pub const MAX_TAG_LENGTH: usize = 32;
+
+/// A normalized entry tag.
+///
+/// Tags are lowercase and made of letters, digits, `-`, `_`, `.` and `/`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Tag(String);
+
+impl Tag {
+    /// Returns the tag as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for Tag {
+    type Err = TagError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tag = s.trim().to_lowercase();
+        if tag.is_empty() {
+            return Err(TagError::Empty);
+        }
+        if tag.chars().count() > MAX_TAG_LENGTH {
+            return Err(TagError::TooLong(tag));
+        }
+        let valid = tag
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | '/'));
+        match valid {
+            true => Ok(Self(tag)),
+            false => Err(TagError::Invalid(s.trim().to_string())),
+        }
+    }
+}
+
+impl TryFrom<String> for Tag {
+    type Error = TagError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Tag> for String {
+    fn from(tag: Tag) -> Self {
+        tag.0
+    }
+}
+
+/// Parses a comma-separated list of tags. Duplicates collapse into one tag.
+pub fn parse_tags(s: &str) -> Result<BTreeSet<Tag>, TagError> {
+    s.split(',')
+        .filter(|part| !part.trim().is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+/// Formats tags as a comma-separated list.
+pub fn format_tags(tags: &BTreeSet<Tag>) -> String {
+    tags.iter().map(Tag::as_str).collect::<Vec<_>>().join(",")
+}
+
+/// Returns true if `tags` contains any of `wanted`, or if `wanted` is empty.
+pub fn matches_any_tag(tags: &BTreeSet<Tag>, wanted: &BTreeSet<Tag>) -> bool {
+    wanted.is_empty() || !tags.is_disjoint(wanted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(s: &str) -> Tag {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_tag_normalizes() {
+        assert_eq!(tag(" OnCall ").as_str(), "oncall");
+        assert_eq!(tag("team/platform").as_str(), "team/platform");
+        assert_eq!("".parse::<Tag>(), Err(TagError::Empty));
+        assert_eq!(
+            "two words".parse::<Tag>(),
+            Err(TagError::Invalid("two words".to_string()))
+        );
+        assert!(matches!(
+            "x".repeat(MAX_TAG_LENGTH + 1).parse::<Tag>(),
+            Err(TagError::TooLong(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_and_format_tags() {
+        let tags = parse_tags("travel, Personal,travel,").unwrap();
+        assert_eq!(tags.len(), 2);
+        assert_eq!(format_tags(&tags), "personal,travel");
+        assert!(parse_tags("").unwrap().is_empty());
+        assert!(parse_tags("ok,not ok").is_err());
+
+        // Serde validates like FromStr
+        assert_eq!(serde_json::to_string(&tag("travel")).unwrap(), "\"travel\"");
+        assert!(serde_json::from_str::<Tag>("\"not ok\"").is_err());
+    }
+
+    #[test]
+    fn test_matches_any_tag() {
+        let tags = parse_tags("oncall,travel").unwrap();
+        let wanted = |s: &str| parse_tags(s).unwrap();
+        assert!(matches_any_tag(&tags, &BTreeSet::new()));
+        assert!(matches_any_tag(&tags, &wanted("personal,travel")));
+        assert!(!matches_any_tag(&tags, &wanted("personal")));
+        assert!(!matches_any_tag(&BTreeSet::new(), &wanted("travel")));
+    }
+}
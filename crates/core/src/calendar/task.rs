@@ -0,0 +1,279 @@
+//! Task details: priority, checklists and carry-over.
+//!
+//! Besides its completed flag, a task can have a due time on its date, a
+//! priority, a checklist of sub-items and an opt-in roll-over flag. Tasks that
+//! roll over are shown on today until they are completed.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use super::error::TaskError;
+use super::types::{CalendarEntry, DaySegment, EntryKind};
+
+/// How urgent a task is. Higher priorities sort first within a day.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskPriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Urgent,
+}
+
+impl TaskPriority {
+    /// Returns the priority as its lowercase name.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Normal => "normal",
+            Self::High => "high",
+            Self::Urgent => "urgent",
+        }
+    }
+}
+
+impl fmt::Display for TaskPriority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TaskPriority {
+    type Err = TaskError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "low" => Ok(Self::Low),
+            "normal" => Ok(Self::Normal),
+            "high" => Ok(Self::High),
+            "urgent" => Ok(Self::Urgent),
+            _ => Err(TaskError::InvalidPriority(s.to_string())),
+        }
+    }
+}
+
+/// A sub-item of a task's checklist.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    pub text: String,
+    #[serde(default)]
+    pub completed: bool,
+}
+
+impl ChecklistItem {
+    /// Creates an item that is not completed yet.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            completed: false,
+        }
+    }
+
+    /// Marks this item as completed.
+    pub fn completed(mut self) -> Self {
+        self.completed = true;
+        self
+    }
+}
+
+/// Formats an item as `[x] text` or `[ ] text`.
+impl fmt::Display for ChecklistItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mark = if self.completed { 'x' } else { ' ' };
+        write!(f, "[{mark}] {}", self.text)
+    }
+}
+
+/// Parses `[x] text` (completed), `[ ] text` or plain `text`.
+impl FromStr for ChecklistItem {
+    type Err = TaskError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (completed, text) = if let Some(rest) = s.strip_prefix("[ ]") {
+            (false, rest)
+        } else if let Some(rest) = s.strip_prefix("[x]").or_else(|| s.strip_prefix("[X]")) {
+            (true, rest)
+        } else {
+            (false, s)
+        };
+
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(TaskError::EmptyChecklistItem);
+        }
+        Ok(Self {
+            text: text.to_string(),
+            completed,
+        })
+    }
+}
+
+/// Parses a checklist with one item per line. Blank lines are skipped.
+pub fn parse_checklist(s: &str) -> Result<Vec<ChecklistItem>, TaskError> {
+    s.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+/// Formats a checklist with one item per line. See [`parse_checklist`].
+pub fn format_checklist(items: &[ChecklistItem]) -> String {
+    items
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Toggles the completed flag of a task's checklist item (zero-based index).
+///
+/// Returns the item's new completed state.
+pub fn toggle_checklist_item(kind: &mut EntryKind, index: usize) -> Result<bool, TaskError> {
+    let EntryKind::Task { checklist, .. } = kind else {
+        return Err(TaskError::NotATask);
+    };
+    let item = checklist
+        .get_mut(index)
+        .ok_or(TaskError::NoSuchChecklistItem(index))?;
+    item.completed = !item.completed;
+    Ok(item.completed)
+}
+
+/// Orders two tasks: higher priority first, then earlier due time, with
+/// tasks that have a due time before those without.
+pub fn compare_tasks(a: &EntryKind, b: &EntryKind) -> Ordering {
+    b.priority()
+        .cmp(&a.priority())
+        .then_with(|| match (a.due_time(), b.due_time()) {
+            (Some(a_due), Some(b_due)) => a_due.cmp(&b_due),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        })
+}
+
+/// Returns true if `entry` is an incomplete roll-over task from before `today`.
+///
+/// Occurrences of recurring tasks never roll over; the next one takes their place.
+pub fn rolls_over_to(entry: &CalendarEntry, today: NaiveDate) -> bool {
+    entry.kind.rolls_over()
+        && !entry.kind.is_completed()
+        && entry.occurrence_date.is_none()
+        && !entry.is_recurring()
+        && entry.start_date < today
+}
+
+/// Moves incomplete roll-over tasks from past days onto `today`.
+///
+/// A carried-over task keeps its dates and gets a [`DaySegment`] on `today`
+/// marked as `continued`, so it is shown on today instead of its own date.
+pub fn roll_over_tasks(entries: Vec<CalendarEntry>, today: NaiveDate) -> Vec<CalendarEntry> {
+    entries
+        .into_iter()
+        .map(|mut entry| {
+            if rolls_over_to(&entry, today) {
+                entry.segment = Some(DaySegment {
+                    date: today,
+                    continued: true,
+                    continues: false,
+                });
+            }
+            entry
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveTime;
+    use uuid::Uuid;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_format_checklist() {
+        let items = parse_checklist("[x] Book flight\n\n[ ] Pack\nCharge laptop").unwrap();
+        assert_eq!(
+            items,
+            vec![
+                ChecklistItem::new("Book flight").completed(),
+                ChecklistItem::new("Pack"),
+                ChecklistItem::new("Charge laptop"),
+            ]
+        );
+        assert_eq!(
+            format_checklist(&items),
+            "[x] Book flight\n[ ] Pack\n[ ] Charge laptop"
+        );
+        assert_eq!(
+            "[x]  ".parse::<ChecklistItem>(),
+            Err(TaskError::EmptyChecklistItem)
+        );
+        assert_eq!("High".parse::<TaskPriority>(), Ok(TaskPriority::High));
+        assert!("someday".parse::<TaskPriority>().is_err());
+    }
+
+    #[test]
+    fn test_toggle_checklist_item() {
+        let mut task = CalendarEntry::task(Uuid::new_v4(), "Trip", date(15), false)
+            .with_checklist_item(ChecklistItem::new("Pack"));
+
+        assert_eq!(toggle_checklist_item(&mut task.kind, 0), Ok(true));
+        assert!(task.kind.checklist()[0].completed);
+        assert_eq!(toggle_checklist_item(&mut task.kind, 0), Ok(false));
+        assert_eq!(
+            toggle_checklist_item(&mut task.kind, 1),
+            Err(TaskError::NoSuchChecklistItem(1))
+        );
+        assert_eq!(
+            toggle_checklist_item(&mut EntryKind::AllDay, 0),
+            Err(TaskError::NotATask)
+        );
+    }
+
+    #[test]
+    fn test_compare_tasks() {
+        let nine = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+        let cal_id = Uuid::new_v4();
+        let high =
+            CalendarEntry::task(cal_id, "High", date(15), false).with_priority(TaskPriority::High);
+        let due = CalendarEntry::task(cal_id, "Due", date(15), false).with_due_time(nine);
+        let plain = CalendarEntry::task(cal_id, "Plain", date(15), false);
+
+        assert_eq!(compare_tasks(&high.kind, &due.kind), Ordering::Less);
+        assert_eq!(compare_tasks(&due.kind, &plain.kind), Ordering::Less);
+        assert_eq!(
+            compare_tasks(&plain.kind, &EntryKind::task(false)),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn test_roll_over_tasks() {
+        let cal_id = Uuid::new_v4();
+        let today = date(15);
+        let entries = vec![
+            CalendarEntry::task(cal_id, "Overdue", date(12), false).with_roll_over(),
+            CalendarEntry::task(cal_id, "Done", date(12), true).with_roll_over(),
+            CalendarEntry::task(cal_id, "Stays", date(12), false),
+            CalendarEntry::task(cal_id, "Upcoming", date(16), false).with_roll_over(),
+        ];
+
+        let rolled = roll_over_tasks(entries, today);
+        let shown_on: Vec<_> = rolled.iter().map(CalendarEntry::display_date).collect();
+        assert_eq!(shown_on, vec![today, date(12), date(12), date(16)]);
+        assert!(rolled[0].segment.unwrap().continued);
+        assert_eq!(rolled[0].start_date, date(12));
+    }
+}
@@ -0,0 +1,200 @@
+//! Time zone conversion for timed entries.
+//!
+//! Timed entries store wall-clock dates and times in the zone of the entry,
+//! or of its calendar when the entry has none. Before they are shown, they are
+//! converted into the viewer's zone so they land on the viewer's local days.
+//! All-day entries, tasks and multi-day events are date-only and are never
+//! converted. Entries without any zone stay floating.
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Offset, TimeZone};
+use chrono_tz::Tz;
+
+use super::types::{CalendarEntry, EntryKind};
+
+/// Resolves a wall-clock date and time in `tz` to an instant.
+///
+/// Follows RFC 5545 for DST transitions: an ambiguous time (clocks going back)
+/// resolves to its first occurrence, and a non-existent time (clocks going
+/// forward) is interpreted with the offset in effect before the gap, which
+/// moves it forward by the length of the gap.
+pub fn resolve_local(date: NaiveDate, time: NaiveTime, tz: Tz) -> DateTime<Tz> {
+    let local = date.and_time(time);
+    if let Some(resolved) = tz.from_local_datetime(&local).earliest() {
+        return resolved;
+    }
+
+    // Inside a gap: use the offset from before the transition. Gaps are at
+    // most a few hours long, so a day earlier is always before it.
+    let offset = tz
+        .offset_from_utc_datetime(&(local - Duration::days(1)))
+        .fix();
+    tz.from_utc_datetime(&(local - offset))
+}
+
+/// Returns the zone an entry's times are expressed in, if any.
+pub fn entry_timezone(entry: &CalendarEntry, calendar_tz: Option<Tz>) -> Option<Tz> {
+    entry.timezone.or(calendar_tz)
+}
+
+/// Re-expresses a timed entry's dates and times from `source` into `target`.
+///
+/// The result carries `target` as its zone. Entries that are not timed only
+/// have their zone replaced. A timed entry may move to a different date, and
+/// its end may fall on the day after its start.
+pub fn convert_entry(entry: &CalendarEntry, source: Tz, target: Tz) -> CalendarEntry {
+    let mut converted = entry.clone();
+    converted.timezone = Some(target);
+
+    if let EntryKind::Timed { start, end } = entry.kind {
+        let start_at = resolve_local(entry.start_date, start, source).with_timezone(&target);
+        let end_at = resolve_local(entry.end_date, end, source).with_timezone(&target);
+
+        converted.start_date = start_at.date_naive();
+        converted.end_date = end_at.date_naive();
+        converted.kind = EntryKind::Timed {
+            start: start_at.time(),
+            end: end_at.time(),
+        };
+    }
+
+    converted
+}
+
+/// Converts an entry into the viewer's zone for display.
+///
+/// An entry with no zone of its own and no calendar zone, or a request
+/// without a viewer zone, is returned unchanged.
+pub fn localize_entry(
+    entry: CalendarEntry,
+    calendar_tz: Option<Tz>,
+    viewer_tz: Option<Tz>,
+) -> CalendarEntry {
+    match (entry_timezone(&entry, calendar_tz), viewer_tz) {
+        (Some(source), Some(target)) => convert_entry(&entry, source, target),
+        _ => entry,
+    }
+}
+
+/// Converts entries into the viewer's zone for display. See [`localize_entry`].
+pub fn localize_entries(
+    entries: Vec<CalendarEntry>,
+    calendar_tz: Option<Tz>,
+    viewer_tz: Option<Tz>,
+) -> Vec<CalendarEntry> {
+    entries
+        .into_iter()
+        .map(|entry| localize_entry(entry, calendar_tz, viewer_tz))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Timelike;
+    use chrono_tz::{America::Montevideo, America::New_York, Europe::Madrid};
+    use uuid::Uuid;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    fn timed(on: NaiveDate, start: NaiveTime, end: NaiveTime) -> CalendarEntry {
+        CalendarEntry::timed(Uuid::new_v4(), "Meeting", on, start, end)
+    }
+
+    #[test]
+    fn test_convert_montevideo_to_madrid() {
+        // Montevideo is UTC-3, Madrid is UTC+1 in winter
+        let entry = timed(date(2024, 1, 15), time(9, 0), time(10, 0));
+        let converted = convert_entry(&entry, Montevideo, Madrid);
+
+        assert_eq!(converted.start_date, date(2024, 1, 15));
+        assert_eq!(converted.kind.start_time(), Some(time(13, 0)));
+        assert_eq!(converted.kind.end_time(), Some(time(14, 0)));
+        assert_eq!(converted.timezone, Some(Madrid));
+    }
+
+    #[test]
+    fn test_convert_moves_entry_to_next_day() {
+        let entry = timed(date(2024, 1, 15), time(21, 0), time(23, 30));
+        let converted = convert_entry(&entry, Montevideo, Madrid);
+
+        assert_eq!(converted.start_date, date(2024, 1, 16));
+        assert_eq!(converted.end_date, date(2024, 1, 16));
+        assert_eq!(converted.kind.start_time(), Some(time(1, 0)));
+    }
+
+    #[test]
+    fn test_convert_end_crosses_midnight() {
+        let entry = timed(date(2024, 1, 15), time(18, 0), time(21, 0));
+        let converted = convert_entry(&entry, Montevideo, Madrid);
+
+        assert_eq!(converted.start_date, date(2024, 1, 15));
+        assert_eq!(converted.end_date, date(2024, 1, 16));
+        assert_eq!(converted.kind.end_time(), Some(time(1, 0)));
+    }
+
+    #[test]
+    fn test_convert_leaves_all_day_dates() {
+        let entry = CalendarEntry::all_day(Uuid::new_v4(), "Holiday", date(2024, 1, 15));
+        let converted = convert_entry(&entry, Montevideo, Madrid);
+
+        assert_eq!(converted.start_date, entry.start_date);
+        assert_eq!(converted.kind, EntryKind::AllDay);
+    }
+
+    #[test]
+    fn test_resolve_local_in_spring_forward_gap() {
+        // 2024-03-10 02:30 does not exist in New York; clocks jump 02:00 -> 03:00
+        let resolved = resolve_local(date(2024, 3, 10), time(2, 30), New_York);
+        assert_eq!(resolved.date_naive(), date(2024, 3, 10));
+        assert_eq!((resolved.hour(), resolved.minute()), (3, 30));
+    }
+
+    #[test]
+    fn test_resolve_local_in_fall_back_overlap() {
+        // 2024-11-03 01:30 happens twice in New York; the first (EDT) one wins
+        let resolved = resolve_local(date(2024, 11, 3), time(1, 30), New_York);
+        assert_eq!(resolved.offset().fix().local_minus_utc(), -4 * 3600);
+    }
+
+    #[test]
+    fn test_convert_across_dst_change() {
+        // Madrid switches to summer time on 2024-03-31, Montevideo has no DST
+        let before = timed(date(2024, 3, 30), time(9, 0), time(10, 0));
+        let after = timed(date(2024, 4, 1), time(9, 0), time(10, 0));
+
+        assert_eq!(
+            convert_entry(&before, Montevideo, Madrid).kind.start_time(),
+            Some(time(13, 0))
+        );
+        assert_eq!(
+            convert_entry(&after, Montevideo, Madrid).kind.start_time(),
+            Some(time(14, 0))
+        );
+    }
+
+    #[test]
+    fn test_localize_entries_falls_back_to_calendar_zone() {
+        let floating = timed(date(2024, 1, 15), time(9, 0), time(10, 0));
+        let own_zone = floating.clone().with_timezone(Madrid);
+
+        let localized = localize_entries(vec![floating.clone()], None, Some(Madrid));
+        assert_eq!(localized[0], floating);
+
+        let localized = localize_entries(
+            vec![floating.clone(), own_zone],
+            Some(Montevideo),
+            Some(Madrid),
+        );
+        assert_eq!(localized[0].kind.start_time(), Some(time(13, 0)));
+        assert_eq!(localized[1].kind.start_time(), Some(time(9, 0)));
+
+        let localized = localize_entries(vec![floating.clone()], Some(Montevideo), None);
+        assert_eq!(localized[0], floating);
+    }
+}
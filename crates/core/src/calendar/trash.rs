@@ -0,0 +1,106 @@
+//! Trash for deleted calendars and entries.
+//!
+//! Deleting a calendar or an entry moves it to the trash, stamped with when it
+//! was deleted. It can be restored until the retention period runs out, after
+//! which it is purged for good.
+
+use std::cmp::Reverse;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::types::{Calendar, CalendarEntry};
+
+/// Days a deleted calendar or entry stays in the trash by default.
+pub const DEFAULT_TRASH_RETENTION_DAYS: u32 = 30;
+
+/// An entry in the trash.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrashedEntry {
+    pub entry: CalendarEntry,
+    pub deleted_at: DateTime<Utc>,
+}
+
+/// A calendar in the trash. Its entries are trashed and restored with it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrashedCalendar {
+    pub calendar: Calendar,
+    pub deleted_at: DateTime<Utc>,
+}
+
+/// The calendars and entries someone can restore, most recently deleted first.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Trash {
+    pub calendars: Vec<TrashedCalendar>,
+    pub entries: Vec<TrashedEntry>,
+    /// Days items are kept before they are purged.
+    pub retention_days: u32,
+}
+
+impl Trash {
+    /// Creates a trash listing, sorting each list most recently deleted first.
+    pub fn new(
+        mut calendars: Vec<TrashedCalendar>,
+        mut entries: Vec<TrashedEntry>,
+        retention_days: u32,
+    ) -> Self {
+        calendars.sort_by_key(|t| Reverse(t.deleted_at));
+        entries.sort_by_key(|t| Reverse(t.deleted_at));
+        Self {
+            calendars,
+            entries,
+            retention_days,
+        }
+    }
+}
+
+/// Returns the instant before which trashed items are purged at `now`.
+pub fn purge_cutoff(now: DateTime<Utc>, retention_days: u32) -> DateTime<Utc> {
+    now - Duration::days(i64::from(retention_days))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use uuid::Uuid;
+
+    fn trashed(title: &str, deleted_at: DateTime<Utc>) -> TrashedEntry {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        TrashedEntry {
+            entry: CalendarEntry::all_day(Uuid::new_v4(), title, date),
+            deleted_at,
+        }
+    }
+
+    #[test]
+    fn test_trash_sorts_most_recent_first() {
+        let now = Utc::now();
+        let trash = Trash::new(
+            Vec::new(),
+            vec![
+                trashed("Old", now - Duration::days(3)),
+                trashed("New", now),
+                trashed("Middle", now - Duration::days(1)),
+            ],
+            DEFAULT_TRASH_RETENTION_DAYS,
+        );
+
+        let titles: Vec<&str> = trash
+            .entries
+            .iter()
+            .map(|t| t.entry.title.as_str())
+            .collect();
+        assert_eq!(titles, vec!["New", "Middle", "Old"]);
+    }
+
+    #[test]
+    fn test_purge_cutoff() {
+        let now = DateTime::parse_from_rfc3339("2024-03-31T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let cutoff = purge_cutoff(now, 30);
+        assert_eq!(cutoff.to_rfc3339(), "2024-03-01T12:00:00+00:00");
+        assert_eq!(purge_cutoff(now, 0), now);
+    }
+}
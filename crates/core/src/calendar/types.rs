@@ -1,7 +1,18 @@
+use std::collections::BTreeSet;
+
 use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::attendee::Attendee;
+use super::recurrence::RecurrenceRule;
+use super::reminder::{DueReminder, Reminder};
+use super::subscription::CalendarSubscription;
+use super::tag::Tag;
+use super::task::{ChecklistItem, TaskPriority};
+use super::version::{initial_version, INITIAL_VERSION};
+
 /// A user who can access calendars.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct User {
@@ -12,6 +23,9 @@ pub struct User {
     pub provider: Option<String>,
     /// Provider's unique user identifier (subject claim from ID token).
     pub provider_subject: Option<String>,
+    /// IANA time zone entries are displayed in for this user.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<Tz>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -26,6 +40,7 @@ impl User {
             email: email.into(),
             provider: None,
             provider_subject: None,
+            timezone: None,
             created_at: now,
             updated_at: now,
         }
@@ -43,6 +58,12 @@ impl User {
         self
     }
 
+    /// Sets the display time zone for this user.
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = Some(timezone);
+        self
+    }
+
     /// Sets a specific ID for this user (useful for testing).
     pub fn with_id(mut self, id: Uuid) -> Self {
         self.id = id;
@@ -158,6 +179,17 @@ pub struct Calendar {
     /// Whether this is the user's default personal calendar (cannot be deleted).
     #[serde(default)]
     pub is_default: bool,
+    /// IANA time zone timed entries are expressed in, unless the entry sets its own.
+    /// `None` keeps times floating (shown as stored in every zone).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<Tz>,
+    /// The external iCalendar feed this calendar mirrors, if any. Entries of
+    /// subscribed calendars are read-only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscription: Option<CalendarSubscription>,
+    /// Goes up by one on every update; sent to clients as the `ETag`.
+    #[serde(default = "initial_version")]
+    pub version: u64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -172,6 +204,9 @@ impl Calendar {
             color: color.into(),
             description: None,
             is_default: false,
+            timezone: None,
+            subscription: None,
+            version: INITIAL_VERSION,
             created_at: now,
             updated_at: now,
         }
@@ -189,6 +224,24 @@ impl Calendar {
         self
     }
 
+    /// Sets the time zone for this calendar.
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = Some(timezone);
+        self
+    }
+
+    /// Makes this calendar mirror the iCalendar feed at `url`.
+    pub fn with_subscription(mut self, url: impl Into<String>) -> Self {
+        self.subscription = Some(CalendarSubscription::new(url));
+        self
+    }
+
+    /// Returns true if users can't edit this calendar's entries because they
+    /// mirror an external feed.
+    pub fn is_read_only(&self) -> bool {
+        self.subscription.is_some()
+    }
+
     /// Sets a specific ID for this calendar (useful for testing).
     pub fn with_id(mut self, id: Uuid) -> Self {
         self.id = id;
@@ -221,12 +274,39 @@ pub enum EntryKind {
     /// An all-day event (no specific time).
     AllDay,
     /// A timed activity with start and end times.
+    ///
+    /// `start` is on the entry's `start_date` and `end` on its `end_date`, so
+    /// an activity can run past midnight or over several days.
     Timed { start: NaiveTime, end: NaiveTime },
     /// A task that can be marked as completed.
-    Task { completed: bool },
+    Task {
+        completed: bool,
+        /// Time of day the task is due on its date.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        due: Option<NaiveTime>,
+        #[serde(default)]
+        priority: TaskPriority,
+        /// Sub-items, each with its own completed flag.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        checklist: Vec<ChecklistItem>,
+        /// When true, the task is shown on today until it is completed.
+        #[serde(default)]
+        roll_over: bool,
+    },
 }
 
 impl EntryKind {
+    /// Creates a task with normal priority, no due time and no checklist.
+    pub fn task(completed: bool) -> Self {
+        EntryKind::Task {
+            completed,
+            due: None,
+            priority: TaskPriority::Normal,
+            checklist: Vec::new(),
+            roll_over: false,
+        }
+    }
+
     /// Returns the sort priority for this entry kind.
     /// Lower values appear first in the hierarchy.
     pub fn sort_priority(&self) -> u8 {
@@ -286,7 +366,48 @@ impl EntryKind {
 
     /// Returns true if this is a completed task.
     pub fn is_completed(&self) -> bool {
-        matches!(self, EntryKind::Task { completed: true })
+        matches!(
+            self,
+            EntryKind::Task {
+                completed: true,
+                ..
+            }
+        )
+    }
+
+    /// Returns the due time if this is a task with one.
+    pub fn due_time(&self) -> Option<NaiveTime> {
+        match self {
+            EntryKind::Task { due, .. } => *due,
+            _ => None,
+        }
+    }
+
+    /// Returns the priority if this is a task.
+    pub fn priority(&self) -> Option<TaskPriority> {
+        match self {
+            EntryKind::Task { priority, .. } => Some(*priority),
+            _ => None,
+        }
+    }
+
+    /// Returns the checklist if this is a task (empty otherwise).
+    pub fn checklist(&self) -> &[ChecklistItem] {
+        match self {
+            EntryKind::Task { checklist, .. } => checklist,
+            _ => &[],
+        }
+    }
+
+    /// Returns true if this is a task that rolls over to today until completed.
+    pub fn rolls_over(&self) -> bool {
+        matches!(
+            self,
+            EntryKind::Task {
+                roll_over: true,
+                ..
+            }
+        )
     }
 }
 
@@ -302,14 +423,40 @@ pub struct CalendarEntry {
     pub kind: EntryKind,
     /// The start date for this entry.
     /// For single-day entries: `start_date == end_date`
-    /// For multi-day entries: `start_date < end_date`
+    /// For multi-day entries and timed entries crossing midnight: `start_date < end_date`
     pub start_date: NaiveDate,
     /// The end date for this entry (inclusive).
     /// For single-day entries: `start_date == end_date`
-    /// For multi-day entries: `start_date < end_date`
+    /// For multi-day entries and timed entries crossing midnight: `start_date < end_date`
     pub end_date: NaiveDate,
     /// Optional accent color for the entry tile (CSS color value).
     pub color: Option<String>,
+    /// IANA time zone of the entry's dates and times; falls back to the calendar's zone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<Tz>,
+    /// Recurrence rule; the entry's own dates are the first occurrence.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<RecurrenceRule>,
+    /// Reminders to deliver before the entry (or each occurrence) starts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reminders: Vec<Reminder>,
+    /// People invited to the entry and their responses.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attendees: Vec<Attendee>,
+    /// Labels used to group and filter entries.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub tags: BTreeSet<Tag>,
+    /// Original start date of this occurrence when the entry was expanded
+    /// from a recurring series. Never persisted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub occurrence_date: Option<NaiveDate>,
+    /// The day this copy is shown on when an entry spanning several days was
+    /// split per day. Never persisted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub segment: Option<DaySegment>,
+    /// Goes up by one on every update; sent to clients as the `ETag`.
+    #[serde(default = "initial_version")]
+    pub version: u64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -333,6 +480,14 @@ impl CalendarEntry {
             start_date: start,
             end_date: end,
             color: None,
+            timezone: None,
+            recurrence: None,
+            reminders: Vec::new(),
+            attendees: Vec::new(),
+            tags: BTreeSet::new(),
+            occurrence_date: None,
+            segment: None,
+            version: INITIAL_VERSION,
             created_at: now,
             updated_at: now,
         }
@@ -351,6 +506,14 @@ impl CalendarEntry {
             start_date: date,
             end_date: date,
             color: None,
+            timezone: None,
+            recurrence: None,
+            reminders: Vec::new(),
+            attendees: Vec::new(),
+            tags: BTreeSet::new(),
+            occurrence_date: None,
+            segment: None,
+            version: INITIAL_VERSION,
             created_at: now,
             updated_at: now,
         }
@@ -375,6 +538,14 @@ impl CalendarEntry {
             start_date: date,
             end_date: date,
             color: None,
+            timezone: None,
+            recurrence: None,
+            reminders: Vec::new(),
+            attendees: Vec::new(),
+            tags: BTreeSet::new(),
+            occurrence_date: None,
+            segment: None,
+            version: INITIAL_VERSION,
             created_at: now,
             updated_at: now,
         }
@@ -394,10 +565,18 @@ impl CalendarEntry {
             title: title.into(),
             description: None,
             location: None,
-            kind: EntryKind::Task { completed },
+            kind: EntryKind::task(completed),
             start_date: date,
             end_date: date,
             color: None,
+            timezone: None,
+            recurrence: None,
+            reminders: Vec::new(),
+            attendees: Vec::new(),
+            tags: BTreeSet::new(),
+            occurrence_date: None,
+            segment: None,
+            version: INITIAL_VERSION,
             created_at: now,
             updated_at: now,
         }
@@ -427,6 +606,111 @@ impl CalendarEntry {
         self
     }
 
+    /// Sets the time zone for this entry's dates and times.
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = Some(timezone);
+        self
+    }
+
+    /// Sets the end date, e.g. for a timed activity that ends on a later day.
+    pub fn with_end_date(mut self, end_date: NaiveDate) -> Self {
+        self.end_date = end_date;
+        self
+    }
+
+    /// Returns true if this entry ends on a later day than it starts.
+    pub fn spans_days(&self) -> bool {
+        self.end_date > self.start_date
+    }
+
+    /// Returns the day this entry is shown on: its segment's day if it was
+    /// split per day, otherwise its start date.
+    pub fn display_date(&self) -> NaiveDate {
+        self.segment.map_or(self.start_date, |segment| segment.date)
+    }
+
+    /// Returns the time this entry starts at on the day it is shown on.
+    ///
+    /// A segment continued from an earlier day starts at midnight.
+    pub fn display_start_time(&self) -> Option<NaiveTime> {
+        match self.segment {
+            Some(segment) if segment.continued => self.kind.start_time().map(|_| NaiveTime::MIN),
+            _ => self.kind.start_time(),
+        }
+    }
+
+    /// Makes this entry repeat according to the given rule.
+    pub fn with_recurrence(mut self, rule: RecurrenceRule) -> Self {
+        self.recurrence = Some(rule);
+        self
+    }
+
+    /// Returns true if this entry is (or belongs to) a recurring series.
+    pub fn is_recurring(&self) -> bool {
+        self.recurrence.is_some()
+    }
+
+    /// Adds a reminder to this entry.
+    pub fn with_reminder(mut self, reminder: Reminder) -> Self {
+        self.reminders.push(reminder);
+        self
+    }
+
+    /// Returns true if this entry has any reminders.
+    pub fn has_reminders(&self) -> bool {
+        !self.reminders.is_empty()
+    }
+
+    /// Adds a tag to this entry.
+    pub fn with_tag(mut self, tag: Tag) -> Self {
+        self.tags.insert(tag);
+        self
+    }
+
+    /// Returns true if this entry has the given tag.
+    pub fn has_tag(&self, tag: &Tag) -> bool {
+        self.tags.contains(tag)
+    }
+
+    /// Sets the time a task is due on its date. Has no effect on other kinds.
+    pub fn with_due_time(mut self, due_time: NaiveTime) -> Self {
+        if let EntryKind::Task { due, .. } = &mut self.kind {
+            *due = Some(due_time);
+        }
+        self
+    }
+
+    /// Sets a task's priority. Has no effect on other kinds.
+    pub fn with_priority(mut self, task_priority: TaskPriority) -> Self {
+        if let EntryKind::Task { priority, .. } = &mut self.kind {
+            *priority = task_priority;
+        }
+        self
+    }
+
+    /// Adds an item to a task's checklist. Has no effect on other kinds.
+    pub fn with_checklist_item(mut self, item: ChecklistItem) -> Self {
+        if let EntryKind::Task { checklist, .. } = &mut self.kind {
+            checklist.push(item);
+        }
+        self
+    }
+
+    /// Makes a task roll over to today until it is completed. Has no effect
+    /// on other kinds.
+    pub fn with_roll_over(mut self) -> Self {
+        if let EntryKind::Task { roll_over, .. } = &mut self.kind {
+            *roll_over = true;
+        }
+        self
+    }
+
+    /// Invites an attendee to this entry.
+    pub fn with_attendee(mut self, attendee: Attendee) -> Self {
+        self.attendees.push(attendee);
+        self
+    }
+
     /// Sets a specific ID for this entry (useful for testing).
     pub fn with_id(mut self, id: Uuid) -> Self {
         self.id = id;
@@ -451,6 +735,17 @@ impl CalendarEntry {
     }
 }
 
+/// The part of an entry spanning several days that falls on one of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DaySegment {
+    /// The day this segment is shown on.
+    pub date: NaiveDate,
+    /// True if the entry started on an earlier day.
+    pub continued: bool,
+    /// True if the entry goes on past this day.
+    pub continues: bool,
+}
+
 /// Data for a single day in the calendar view.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DayData {
@@ -530,10 +825,13 @@ impl Default for CalendarSettings {
 /// SSE event types for real-time calendar updates.
 ///
 /// These events are sent from the server to clients via Server-Sent Events (SSE)
-/// when calendar entries are created, updated, or deleted.
+/// when calendar entries are created, updated, deleted or restored from the
+/// trash, and when one of an entry's reminders is due.
 ///
 /// The `date` field is included to help clients update their view without
-/// needing to re-query for entries.
+/// needing to re-query for entries. When a change only affects one occurrence
+/// of a recurring series, `occurrence_date` holds that occurrence's original
+/// date; otherwise the whole entry (or series) changed.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum CalendarEvent {
@@ -550,6 +848,9 @@ pub enum CalendarEvent {
         entry: CalendarEntry,
         /// The date string for client-side view updates.
         date: String,
+        /// Original date of the single occurrence that changed, if any.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        occurrence_date: Option<NaiveDate>,
     },
     /// An entry was deleted from the calendar.
     EntryDeleted {
@@ -557,6 +858,41 @@ pub enum CalendarEvent {
         entry_id: Uuid,
         /// The date string for client-side view updates.
         date: String,
+        /// Original date of the single occurrence that was cancelled, if any.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        occurrence_date: Option<NaiveDate>,
+    },
+    /// A deleted entry was restored from the trash.
+    EntryRestored {
+        /// The restored entry.
+        entry: CalendarEntry,
+        /// The date string for client-side view updates.
+        date: String,
+    },
+    /// A reminder for an entry is due.
+    ReminderDue {
+        /// The entry (or occurrence) the reminder is for.
+        entry: CalendarEntry,
+        /// The date string for client-side view updates.
+        date: String,
+        /// The reminder that fired.
+        reminder: Reminder,
+        /// When the reminder was scheduled to fire.
+        fire_at: DateTime<Utc>,
+    },
+    /// Several entries changed at once through a batch.
+    ///
+    /// Sent once per calendar instead of an event per entry, so clients can
+    /// apply a bulk import in a single pass.
+    EntryBatch {
+        /// The newly created entries.
+        added: Vec<CalendarEntry>,
+        /// The updated entries.
+        updated: Vec<CalendarEntry>,
+        /// The IDs of the deleted entries.
+        deleted: Vec<Uuid>,
+        /// The earliest date the batch touched, for client-side view updates.
+        date: String,
     },
 }
 
@@ -570,7 +906,11 @@ impl CalendarEvent {
     /// Creates an EntryUpdated event.
     pub fn entry_updated(entry: CalendarEntry) -> Self {
         let date = entry.start_date.to_string();
-        Self::EntryUpdated { entry, date }
+        Self::EntryUpdated {
+            entry,
+            date,
+            occurrence_date: None,
+        }
     }
 
     /// Creates an EntryDeleted event.
@@ -578,14 +918,97 @@ impl CalendarEvent {
         Self::EntryDeleted {
             entry_id,
             date: date.to_string(),
+            occurrence_date: None,
+        }
+    }
+
+    /// Creates an EntryRestored event.
+    pub fn entry_restored(entry: CalendarEntry) -> Self {
+        let date = entry.start_date.to_string();
+        Self::EntryRestored { entry, date }
+    }
+
+    /// Creates an EntryUpdated event for a single modified occurrence.
+    ///
+    /// The occurrence's `occurrence_date` identifies which one changed.
+    pub fn occurrence_updated(occurrence: CalendarEntry) -> Self {
+        let date = occurrence.start_date.to_string();
+        let occurrence_date = occurrence.occurrence_date;
+        Self::EntryUpdated {
+            entry: occurrence,
+            date,
+            occurrence_date,
         }
     }
 
-    /// Returns the calendar entry if this is an add or update event.
+    /// Creates an EntryDeleted event for a single cancelled occurrence.
+    pub fn occurrence_deleted(series_id: Uuid, original_date: NaiveDate) -> Self {
+        Self::EntryDeleted {
+            entry_id: series_id,
+            date: original_date.to_string(),
+            occurrence_date: Some(original_date),
+        }
+    }
+
+    /// Creates a ReminderDue event.
+    pub fn reminder_due(due: DueReminder) -> Self {
+        let date = due.entry.start_date.to_string();
+        Self::ReminderDue {
+            entry: due.entry,
+            date,
+            reminder: due.reminder,
+            fire_at: due.fire_at,
+        }
+    }
+
+    /// Creates an EntryBatch event.
+    ///
+    /// Deleted entries are given with their start date so the event's date
+    /// covers them too.
+    pub fn entry_batch(
+        added: Vec<CalendarEntry>,
+        updated: Vec<CalendarEntry>,
+        deleted: Vec<(Uuid, NaiveDate)>,
+    ) -> Self {
+        let date = added
+            .iter()
+            .chain(&updated)
+            .map(|entry| entry.start_date)
+            .chain(deleted.iter().map(|(_, date)| *date))
+            .min()
+            .map(|date| date.to_string())
+            .unwrap_or_default();
+        Self::EntryBatch {
+            added,
+            updated,
+            deleted: deleted.into_iter().map(|(id, _)| id).collect(),
+            date,
+        }
+    }
+
+    /// Returns the original date of the affected occurrence, if the event
+    /// concerns a single occurrence of a recurring series.
+    pub fn occurrence_date(&self) -> Option<NaiveDate> {
+        match self {
+            Self::EntryUpdated {
+                occurrence_date, ..
+            }
+            | Self::EntryDeleted {
+                occurrence_date, ..
+            } => *occurrence_date,
+            Self::ReminderDue { entry, .. } => entry.occurrence_date,
+            Self::EntryAdded { .. } | Self::EntryRestored { .. } | Self::EntryBatch { .. } => None,
+        }
+    }
+
+    /// Returns the calendar entry if this is an add, update, restore or reminder event.
     pub fn entry(&self) -> Option<&CalendarEntry> {
         match self {
-            Self::EntryAdded { entry, .. } | Self::EntryUpdated { entry, .. } => Some(entry),
-            Self::EntryDeleted { .. } => None,
+            Self::EntryAdded { entry, .. }
+            | Self::EntryUpdated { entry, .. }
+            | Self::EntryRestored { entry, .. }
+            | Self::ReminderDue { entry, .. } => Some(entry),
+            Self::EntryDeleted { .. } | Self::EntryBatch { .. } => None,
         }
     }
 
@@ -594,7 +1017,10 @@ impl CalendarEvent {
         match self {
             Self::EntryAdded { date, .. }
             | Self::EntryUpdated { date, .. }
-            | Self::EntryDeleted { date, .. } => date,
+            | Self::EntryDeleted { date, .. }
+            | Self::EntryRestored { date, .. }
+            | Self::ReminderDue { date, .. }
+            | Self::EntryBatch { date, .. } => date,
         }
     }
 }
@@ -612,7 +1038,7 @@ mod tests {
             start: NaiveTime::from_hms_opt(10, 0, 0).unwrap(),
             end: NaiveTime::from_hms_opt(11, 0, 0).unwrap(),
         };
-        let task = EntryKind::Task { completed: false };
+        let task = EntryKind::task(false);
 
         assert!(multi_day.sort_priority() < all_day.sort_priority());
         assert!(all_day.sort_priority() < timed.sort_priority());
@@ -797,4 +1223,111 @@ mod tests {
         assert_eq!(json["showTasks"], true);
         assert_eq!(json["entryStyle"], "compact");
     }
+
+    #[test]
+    fn test_occurrence_events_carry_original_date() {
+        let series_id = Uuid::new_v4();
+        let original = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+
+        let deleted = CalendarEvent::occurrence_deleted(series_id, original);
+        assert_eq!(deleted.occurrence_date(), Some(original));
+        let json = serde_json::to_value(&deleted).unwrap();
+        assert_eq!(json["type"], "entry_deleted");
+        assert_eq!(json["occurrence_date"], "2024-01-15");
+
+        // Whole-entry events omit the field and still deserialize without it.
+        let whole = CalendarEvent::entry_deleted(series_id, original);
+        let json = serde_json::to_value(&whole).unwrap();
+        assert!(json.get("occurrence_date").is_none());
+        let restored: CalendarEvent = serde_json::from_value(json).unwrap();
+        assert_eq!(restored.occurrence_date(), None);
+
+        let mut occurrence =
+            CalendarEntry::all_day(Uuid::new_v4(), "Moved", original.succ_opt().unwrap());
+        occurrence.occurrence_date = Some(original);
+        let updated = CalendarEvent::occurrence_updated(occurrence);
+        assert_eq!(updated.occurrence_date(), Some(original));
+        assert_eq!(updated.date(), "2024-01-16");
+    }
+
+    #[test]
+    fn test_entry_restored_event() {
+        let entry = CalendarEntry::all_day(
+            Uuid::new_v4(),
+            "Dentist",
+            NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+        );
+
+        let event = CalendarEvent::entry_restored(entry.clone());
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "entry_restored");
+        assert_eq!(event.entry(), Some(&entry));
+        assert_eq!(event.date(), "2024-01-15");
+        assert_eq!(event.occurrence_date(), None);
+    }
+
+    #[test]
+    fn test_reminder_due_event_roundtrip() {
+        let reminder = Reminder::minutes_before(10);
+        let entry = CalendarEntry::timed(
+            Uuid::new_v4(),
+            "Standup",
+            NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+            NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            NaiveTime::from_hms_opt(9, 15, 0).unwrap(),
+        )
+        .with_reminder(reminder);
+        let fire_at = DateTime::parse_from_rfc3339("2024-01-15T08:50:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let event = CalendarEvent::reminder_due(DueReminder {
+            entry: entry.clone(),
+            reminder,
+            fire_at,
+        });
+        assert_eq!(event.entry(), Some(&entry));
+        assert_eq!(event.date(), "2024-01-15");
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "reminder_due");
+        assert_eq!(json["reminder"], "10m");
+        assert_eq!(json["entry"]["reminders"][0], "10m");
+
+        let restored: CalendarEvent = serde_json::from_value(json).unwrap();
+        assert_eq!(restored, event);
+    }
+
+    #[test]
+    fn test_entry_batch_event_uses_earliest_date() {
+        let calendar_id = Uuid::new_v4();
+        let added = CalendarEntry::all_day(
+            calendar_id,
+            "Physics",
+            NaiveDate::from_ymd_opt(2024, 9, 3).unwrap(),
+        );
+        let updated = CalendarEntry::all_day(
+            calendar_id,
+            "Chemistry",
+            NaiveDate::from_ymd_opt(2024, 9, 5).unwrap(),
+        );
+        let deleted_id = Uuid::new_v4();
+        let deleted_date = NaiveDate::from_ymd_opt(2024, 9, 2).unwrap();
+
+        let event = CalendarEvent::entry_batch(
+            vec![added.clone()],
+            vec![updated],
+            vec![(deleted_id, deleted_date)],
+        );
+        assert_eq!(event.date(), "2024-09-02");
+        assert_eq!(event.entry(), None);
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "entry_batch");
+        assert_eq!(json["added"][0]["id"], added.id.to_string());
+        assert_eq!(json["deleted"][0], deleted_id.to_string());
+
+        let restored: CalendarEvent = serde_json::from_value(json).unwrap();
+        assert_eq!(restored, event);
+    }
 }
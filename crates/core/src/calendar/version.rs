@@ -0,0 +1,62 @@
+//! Versions for optimistic concurrency.
+//!
+//! Every calendar and entry carries a version that goes up by one on each
+//! update. Clients get it as an `ETag` and send it back in `If-Match`; a write
+//! is only applied when the stored version still matches, so two writers can't
+//! silently overwrite each other.
+
+/// Version of a calendar or entry that was just created.
+pub const INITIAL_VERSION: u64 = 1;
+
+/// Serde default for records stored before versions existed.
+pub(crate) fn initial_version() -> u64 {
+    INITIAL_VERSION
+}
+
+/// Formats a version as a strong `ETag` value, e.g. `"3"`.
+pub fn format_etag(version: u64) -> String {
+    format!("\"{version}\"")
+}
+
+/// Parses a strong `ETag` value produced by [`format_etag`].
+pub fn parse_etag(etag: &str) -> Option<u64> {
+    etag.trim()
+        .strip_prefix('"')?
+        .strip_suffix('"')?
+        .parse()
+        .ok()
+}
+
+/// Returns whether an `If-Match` header value allows writing `version`.
+///
+/// `*` matches any version. Otherwise the header is a comma-separated list of
+/// entity tags compared strongly, so weak (`W/`) tags never match.
+pub fn if_match_satisfied(header: &str, version: u64) -> bool {
+    header
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || (!tag.starts_with("W/") && parse_etag(tag) == Some(version)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_etag_round_trip() {
+        assert_eq!(format_etag(3), "\"3\"");
+        assert_eq!(parse_etag("\"3\""), Some(3));
+        assert_eq!(parse_etag("3"), None);
+        assert_eq!(parse_etag("\"abc\""), None);
+    }
+
+    #[test]
+    fn test_if_match_satisfied() {
+        assert!(if_match_satisfied("\"2\"", 2));
+        assert!(if_match_satisfied("*", 7));
+        assert!(if_match_satisfied("\"1\", \"2\"", 2));
+        assert!(!if_match_satisfied("\"1\"", 2));
+        assert!(!if_match_satisfied("W/\"2\"", 2));
+        assert!(!if_match_satisfied("", 2));
+    }
+}
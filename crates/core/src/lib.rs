@@ -1,6 +1,8 @@
 #[cfg(feature = "auth")]
 pub mod auth;
+pub mod backup;
 pub mod cache;
+pub mod caldav;
 pub mod calendar;
 pub mod serde;
 pub mod storage;
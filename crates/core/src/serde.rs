@@ -3,8 +3,17 @@
 //! These functions handle the quirks of HTML form submissions where
 //! empty strings should be treated as None for optional fields.
 
+use std::collections::BTreeSet;
+
 use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
-use serde::{Deserialize, Deserializer};
+use chrono_tz::Tz;
+use serde::{Deserialize, Deserializer, Serializer};
+
+use crate::calendar::{
+    format_attendees, format_checklist, format_reminders, format_tags, parse_attendees,
+    parse_checklist, parse_reminders, parse_tags, Attendee, ChecklistItem, RecurrenceRule,
+    Reminder, Tag, TaskPriority,
+};
 
 /// Deserialize an optional string, treating empty strings as None.
 pub fn deserialize_optional_string<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
@@ -63,6 +72,177 @@ where
     }
 }
 
+/// Deserialize an optional RRULE string, treating empty strings as None.
+/// Accepts the RRULE value with or without the `RRULE:` prefix.
+pub fn deserialize_optional_recurrence<'de, D>(
+    deserializer: D,
+) -> Result<Option<RecurrenceRule>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: Option<String> = Option::deserialize(deserializer)?;
+    match s {
+        Some(s) if !s.trim().is_empty() => s.parse().map(Some).map_err(serde::de::Error::custom),
+        _ => Ok(None),
+    }
+}
+
+/// Deserialize an optional IANA time zone name, treating empty strings as None.
+/// Accepts names such as "America/Montevideo" or "UTC".
+pub fn deserialize_optional_timezone<'de, D>(deserializer: D) -> Result<Option<Tz>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: Option<String> = Option::deserialize(deserializer)?;
+    match s {
+        Some(s) if !s.trim().is_empty() => {
+            s.trim().parse().map(Some).map_err(serde::de::Error::custom)
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Deserialize an optional comma-separated list of reminders, treating empty
+/// strings as None. Accepts values such as "10m,1d@09:00".
+pub fn deserialize_optional_reminders<'de, D>(
+    deserializer: D,
+) -> Result<Option<Vec<Reminder>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: Option<String> = Option::deserialize(deserializer)?;
+    match s {
+        Some(s) if !s.trim().is_empty() => parse_reminders(&s)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+        _ => Ok(None),
+    }
+}
+
+/// Serialize optional reminders as a comma-separated list, so they can be
+/// sent as a single form field. See [`deserialize_optional_reminders`].
+pub fn serialize_optional_reminders<S>(
+    reminders: &Option<Vec<Reminder>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match reminders {
+        Some(reminders) => serializer.serialize_some(&format_reminders(reminders)),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Deserialize an optional comma-separated list of attendee email addresses,
+/// treating empty strings as None. Accepts values such as "a@example.com,b@example.com".
+pub fn deserialize_optional_attendees<'de, D>(
+    deserializer: D,
+) -> Result<Option<Vec<Attendee>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: Option<String> = Option::deserialize(deserializer)?;
+    match s {
+        Some(s) if !s.trim().is_empty() => parse_attendees(&s)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+        _ => Ok(None),
+    }
+}
+
+/// Serialize optional attendees as a comma-separated list of email addresses.
+/// See [`deserialize_optional_attendees`].
+pub fn serialize_optional_attendees<S>(
+    attendees: &Option<Vec<Attendee>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match attendees {
+        Some(attendees) => serializer.serialize_some(&format_attendees(attendees)),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Deserialize an optional comma-separated list of tags, treating empty
+/// strings as None. Accepts values such as "oncall,travel".
+pub fn deserialize_optional_tags<'de, D>(deserializer: D) -> Result<Option<BTreeSet<Tag>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: Option<String> = Option::deserialize(deserializer)?;
+    match s {
+        Some(s) if !s.trim().is_empty() => {
+            parse_tags(&s).map(Some).map_err(serde::de::Error::custom)
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Serialize optional tags as a comma-separated list, so they can be sent as
+/// a single form field or query parameter. See [`deserialize_optional_tags`].
+pub fn serialize_optional_tags<S>(
+    tags: &Option<BTreeSet<Tag>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match tags {
+        Some(tags) => serializer.serialize_some(&format_tags(tags)),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Deserialize an optional task priority, treating empty strings as None.
+/// Accepts "low", "normal", "high" or "urgent" in any case.
+pub fn deserialize_optional_priority<'de, D>(
+    deserializer: D,
+) -> Result<Option<TaskPriority>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: Option<String> = Option::deserialize(deserializer)?;
+    match s {
+        Some(s) if !s.trim().is_empty() => s.parse().map(Some).map_err(serde::de::Error::custom),
+        _ => Ok(None),
+    }
+}
+
+/// Deserialize an optional task checklist with one item per line, treating
+/// empty strings as None. Items may be prefixed with `[x]` or `[ ]`.
+pub fn deserialize_optional_checklist<'de, D>(
+    deserializer: D,
+) -> Result<Option<Vec<ChecklistItem>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: Option<String> = Option::deserialize(deserializer)?;
+    match s {
+        Some(s) if !s.trim().is_empty() => parse_checklist(&s)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+        _ => Ok(None),
+    }
+}
+
+/// Serialize an optional checklist with one item per line, so it can be sent
+/// as a single form field. See [`deserialize_optional_checklist`].
+pub fn serialize_optional_checklist<S>(
+    checklist: &Option<Vec<ChecklistItem>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match checklist {
+        Some(items) => serializer.serialize_some(&format_checklist(items)),
+        None => serializer.serialize_none(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -77,8 +257,20 @@ mod tests {
         date_field: Option<NaiveDate>,
         #[serde(default, deserialize_with = "deserialize_optional_time")]
         time_field: Option<NaiveTime>,
+        #[serde(default, deserialize_with = "deserialize_optional_recurrence")]
+        recurrence_field: Option<RecurrenceRule>,
         #[serde(default, deserialize_with = "deserialize_optional_datetime")]
         datetime_field: Option<DateTime<Utc>>,
+        #[serde(default, deserialize_with = "deserialize_optional_timezone")]
+        timezone_field: Option<Tz>,
+        #[serde(default, deserialize_with = "deserialize_optional_reminders")]
+        reminders_field: Option<Vec<Reminder>>,
+        #[serde(default, deserialize_with = "deserialize_optional_tags")]
+        tags_field: Option<BTreeSet<Tag>>,
+        #[serde(default, deserialize_with = "deserialize_optional_priority")]
+        priority_field: Option<TaskPriority>,
+        #[serde(default, deserialize_with = "deserialize_optional_checklist")]
+        checklist_field: Option<Vec<ChecklistItem>>,
     }
 
     #[test]
@@ -191,4 +383,104 @@ mod tests {
         let result: Result<TestStruct, _> = serde_json::from_str(json);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_deserialize_optional_recurrence_valid() {
+        let json = r#"{"recurrence_field": "FREQ=WEEKLY;BYDAY=MO"}"#;
+        let result: TestStruct = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            result.recurrence_field.map(|r| r.to_string()),
+            Some("FREQ=WEEKLY;BYDAY=MO".to_string())
+        );
+    }
+
+    #[test]
+    fn test_deserialize_optional_recurrence_empty() {
+        let json = r#"{"recurrence_field": ""}"#;
+        let result: TestStruct = serde_json::from_str(json).unwrap();
+        assert_eq!(result.recurrence_field, None);
+    }
+
+    #[test]
+    fn test_deserialize_optional_recurrence_invalid() {
+        let json = r#"{"recurrence_field": "FREQ=SOMETIMES"}"#;
+        let result: Result<TestStruct, _> = serde_json::from_str(json);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_deserialize_optional_timezone_valid() {
+        let json = r#"{"timezone_field": "America/Montevideo"}"#;
+        let result: TestStruct = serde_json::from_str(json).unwrap();
+        assert_eq!(result.timezone_field, Some(Tz::America__Montevideo));
+    }
+
+    #[test]
+    fn test_deserialize_optional_timezone_empty() {
+        let json = r#"{"timezone_field": ""}"#;
+        let result: TestStruct = serde_json::from_str(json).unwrap();
+        assert_eq!(result.timezone_field, None);
+    }
+
+    #[test]
+    fn test_deserialize_optional_timezone_invalid() {
+        let json = r#"{"timezone_field": "Mars/Olympus_Mons"}"#;
+        let result: Result<TestStruct, _> = serde_json::from_str(json);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_deserialize_optional_reminders_valid() {
+        let json = r#"{"reminders_field": "10m, 1d@09:00"}"#;
+        let result: TestStruct = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            result.reminders_field.map(|r| format_reminders(&r)),
+            Some("10m,1d@09:00".to_string())
+        );
+    }
+
+    #[test]
+    fn test_deserialize_optional_reminders_empty() {
+        let json = r#"{"reminders_field": ""}"#;
+        let result: TestStruct = serde_json::from_str(json).unwrap();
+        assert_eq!(result.reminders_field, None);
+    }
+
+    #[test]
+    fn test_deserialize_optional_reminders_invalid() {
+        let json = r#"{"reminders_field": "10m,soon"}"#;
+        let result: Result<TestStruct, _> = serde_json::from_str(json);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_deserialize_optional_priority() {
+        let json = r#"{"priority_field": "High"}"#;
+        let result: TestStruct = serde_json::from_str(json).unwrap();
+        assert_eq!(result.priority_field, Some(TaskPriority::High));
+
+        let json = r#"{"priority_field": ""}"#;
+        let result: TestStruct = serde_json::from_str(json).unwrap();
+        assert_eq!(result.priority_field, None);
+
+        let json = r#"{"priority_field": "someday"}"#;
+        assert!(serde_json::from_str::<TestStruct>(json).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_optional_checklist() {
+        let json = r#"{"checklist_field": "[x] Book flight\nPack"}"#;
+        let result: TestStruct = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            result.checklist_field,
+            Some(vec![
+                ChecklistItem::new("Book flight").completed(),
+                ChecklistItem::new("Pack"),
+            ])
+        );
+
+        let json = r#"{"checklist_field": ""}"#;
+        let result: TestStruct = serde_json::from_str(json).unwrap();
+        assert_eq!(result.checklist_field, None);
+    }
 }
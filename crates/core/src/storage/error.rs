@@ -20,6 +20,12 @@ pub enum RepositoryError {
         entity_type: &'static str,
         id: String,
     },
+    #[error("{entity_type} {id} is no longer at version {expected}")]
+    VersionMismatch {
+        entity_type: &'static str,
+        id: String,
+        expected: u64,
+    },
     #[error("Connection failed: {0}")]
     ConnectionFailed(String),
     #[error("Query failed: {0}")]
@@ -63,6 +69,19 @@ mod tests {
         assert_eq!(error.to_string(), "Calendar already exists: my-calendar");
     }
 
+    #[test]
+    fn test_repository_error_version_mismatch_display() {
+        let error = RepositoryError::VersionMismatch {
+            entity_type: "CalendarEntry",
+            id: "abc-123".to_string(),
+            expected: 3,
+        };
+        assert_eq!(
+            error.to_string(),
+            "CalendarEntry abc-123 is no longer at version 3"
+        );
+    }
+
     #[test]
     fn test_repository_error_connection_failed_display() {
         let error = RepositoryError::ConnectionFailed("timeout after 30s".to_string());
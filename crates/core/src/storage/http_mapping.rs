@@ -12,6 +12,7 @@ use super::RepositoryError;
 ///
 /// - `NotFound` -> 404 (Not Found)
 /// - `AlreadyExists` -> 409 (Conflict)
+/// - `VersionMismatch` -> 412 (Precondition Failed)
 /// - `ConnectionFailed` -> 503 (Service Unavailable)
 /// - `QueryFailed` -> 500 (Internal Server Error)
 /// - `Serialization` -> 500 (Internal Server Error)
@@ -32,6 +33,7 @@ pub fn repository_error_to_status_code(error: &RepositoryError) -> u16 {
     match error {
         RepositoryError::NotFound { .. } => 404,
         RepositoryError::AlreadyExists { .. } => 409,
+        RepositoryError::VersionMismatch { .. } => 412,
         RepositoryError::ConnectionFailed(_) => 503,
         RepositoryError::QueryFailed(_) => 500,
         RepositoryError::Serialization(_) => 500,
@@ -61,6 +63,16 @@ mod tests {
         assert_eq!(repository_error_to_status_code(&error), 409);
     }
 
+    #[test]
+    fn test_version_mismatch_maps_to_412() {
+        let error = RepositoryError::VersionMismatch {
+            entity_type: "CalendarEntry",
+            id: "entry-456".to_string(),
+            expected: 2,
+        };
+        assert_eq!(repository_error_to_status_code(&error), 412);
+    }
+
     #[test]
     fn test_connection_failed_maps_to_503() {
         let error = RepositoryError::ConnectionFailed("database connection timeout".to_string());
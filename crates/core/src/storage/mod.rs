@@ -6,6 +6,7 @@ mod types;
 pub use error::{DateRangeError, RepositoryError, Result};
 pub use http_mapping::repository_error_to_status_code;
 pub use traits::{
-    CalendarRepository, EntryRepository, MembershipRepository, SettingsRepository, UserRepository,
+    CalendarRepository, EntryRepository, FeedTokenRepository, MembershipRepository,
+    SettingsRepository, UserRepository,
 };
 pub use types::DateRange;
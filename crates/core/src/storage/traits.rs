@@ -1,11 +1,16 @@
+use std::collections::BTreeSet;
+
 use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
 use uuid::Uuid;
 
 use crate::calendar::{
-    Calendar, CalendarEntry, CalendarMembership, CalendarRole, CalendarSettings, User,
+    Calendar, CalendarEntry, CalendarMembership, CalendarRole, CalendarSettings,
+    CalendarSubscription, DuplicateOptions, DuplicateSummary, EntryRevision, EntryWrite, FeedToken,
+    OccurrenceOverride, SearchQuery, Tag, TrashedCalendar, TrashedEntry, User,
 };
 
-use super::{DateRange, Result};
+use super::{DateRange, RepositoryError, Result};
 
 /// Repository for calendar entry operations.
 #[async_trait]
@@ -14,20 +19,328 @@ pub trait EntryRepository: Send + Sync {
     async fn get_entry(&self, id: Uuid) -> Result<Option<CalendarEntry>>;
 
     /// Gets all entries for a calendar within a date range.
+    ///
+    /// Recurring series are expanded into their occurrences within the range,
+    /// with occurrence overrides applied.
     async fn get_entries_by_calendar(
         &self,
         calendar_id: Uuid,
         date_range: DateRange,
     ) -> Result<Vec<CalendarEntry>>;
 
+    /// Gets the entries of a calendar within a date range that have at least
+    /// one of `tags`.
+    ///
+    /// Recurring series are expanded into their occurrences within the range,
+    /// with occurrence overrides applied.
+    async fn get_entries_by_tags(
+        &self,
+        calendar_id: Uuid,
+        tags: &BTreeSet<Tag>,
+        date_range: DateRange,
+    ) -> Result<Vec<CalendarEntry>>;
+
+    /// Gets every live entry of a calendar as stored, by start date.
+    ///
+    /// Recurring series are returned once rather than expanded; their
+    /// overrides are read with `get_occurrence_overrides`.
+    async fn get_stored_entries_by_calendar(&self, calendar_id: Uuid)
+        -> Result<Vec<CalendarEntry>>;
+
+    /// Counts the live entries of a calendar.
+    ///
+    /// Backends count without `get_stored_entries_by_calendar`, so the count
+    /// can check that a backup read every entry.
+    async fn count_entries_by_calendar(&self, calendar_id: Uuid) -> Result<usize> {
+        Ok(self
+            .get_stored_entries_by_calendar(calendar_id)
+            .await?
+            .len())
+    }
+
+    /// Searches the title, description and location of the entries in
+    /// `calendar_ids`, returning at most `limit` matches, best first.
+    ///
+    /// Recurring series are returned once, as stored, rather than expanded.
+    async fn search_entries(
+        &self,
+        calendar_ids: &[Uuid],
+        query: &SearchQuery,
+        limit: usize,
+    ) -> Result<Vec<CalendarEntry>>;
+
     /// Creates a new entry.
     async fn create_entry(&self, entry: &CalendarEntry) -> Result<()>;
 
-    /// Updates an existing entry.
+    /// Updates an existing entry, storing it with the next version.
     async fn update_entry(&self, entry: &CalendarEntry) -> Result<()>;
 
-    /// Deletes an entry by its ID.
+    /// Updates an existing entry only if its stored version is still
+    /// `expected_version`, storing it with the version after that.
+    ///
+    /// Fails with `VersionMismatch` if another writer updated the entry first.
+    async fn update_entry_if_version(
+        &self,
+        entry: &CalendarEntry,
+        expected_version: u64,
+    ) -> Result<()>;
+
+    /// Moves a live entry to another calendar, keeping its ID, if its stored
+    /// version is still `expected_version`. Returns the entry as stored.
+    ///
+    /// Fails with `VersionMismatch` if another writer updated the entry first.
+    async fn move_entry(
+        &self,
+        id: Uuid,
+        calendar_id: Uuid,
+        expected_version: u64,
+    ) -> Result<CalendarEntry>;
+
+    /// Moves an entry to the trash.
+    ///
+    /// The entry disappears from every other read until it is restored. Its
+    /// occurrence overrides are kept so a restored series comes back unchanged.
     async fn delete_entry(&self, id: Uuid) -> Result<()>;
+
+    /// Moves an entry to the trash only if its stored version is still
+    /// `expected_version`.
+    ///
+    /// Fails with `VersionMismatch` if another writer updated the entry first.
+    async fn delete_entry_if_version(&self, id: Uuid, expected_version: u64) -> Result<()>;
+
+    /// Gets the trashed entries of the given calendars.
+    ///
+    /// Entries trashed along with their calendar are only listed with it.
+    async fn get_trashed_entries(&self, calendar_ids: &[Uuid]) -> Result<Vec<TrashedEntry>>;
+
+    /// Takes an entry out of the trash and returns it.
+    ///
+    /// Fails with `NotFound` if the entry is not in the trash.
+    async fn restore_entry(&self, id: Uuid) -> Result<CalendarEntry>;
+
+    /// Permanently deletes the entries trashed before `deleted_before`, along
+    /// with their occurrence overrides and revision history. Returns how many
+    /// were deleted.
+    async fn purge_entries(&self, deleted_before: DateTime<Utc>) -> Result<usize>;
+
+    /// Gets all occurrence overrides of a recurring series.
+    async fn get_occurrence_overrides(&self, series_id: Uuid) -> Result<Vec<OccurrenceOverride>>;
+
+    /// Creates or replaces the override for `(series_id, original_date)`.
+    async fn upsert_occurrence_override(&self, occurrence: &OccurrenceOverride) -> Result<()>;
+
+    /// Deletes a series' overrides for occurrences on or after `from`.
+    async fn delete_occurrence_overrides(&self, series_id: Uuid, from: NaiveDate) -> Result<()>;
+
+    /// Gets the entries of every calendar that have reminders and fall within
+    /// a date range.
+    ///
+    /// Recurring series are expanded into their occurrences within the range,
+    /// with occurrence overrides applied.
+    async fn get_entries_with_reminders(&self, date_range: DateRange)
+        -> Result<Vec<CalendarEntry>>;
+
+    /// Records that the reminder for `entry_id` due at `fire_at` is being delivered.
+    ///
+    /// Returns `false` if it was already claimed, so a reminder is delivered
+    /// once even when several server instances share the same storage.
+    async fn claim_reminder(&self, entry_id: Uuid, fire_at: DateTime<Utc>) -> Result<bool>;
+
+    /// Appends a revision to an entry's history.
+    ///
+    /// Fails with `AlreadyExists` if the entry already has a revision with
+    /// the same number.
+    async fn append_entry_revision(&self, revision: &EntryRevision) -> Result<()>;
+
+    /// Gets an entry's revisions, oldest first.
+    ///
+    /// History is kept while the entry is in the trash and purged with it.
+    async fn get_entry_revisions(&self, entry_id: Uuid) -> Result<Vec<EntryRevision>>;
+
+    /// Applies a batch of writes that passed `validate_batch`.
+    ///
+    /// Backends with transactions apply all of the writes or none of them.
+    /// By default they are applied one at a time, stopping at the first
+    /// failure.
+    async fn apply_entry_batch(&self, writes: &[EntryWrite]) -> Result<()> {
+        for write in writes {
+            match write {
+                EntryWrite::Create(entry) => self.create_entry(entry).await?,
+                EntryWrite::Update {
+                    entry,
+                    expected_version,
+                } => {
+                    self.update_entry_if_version(entry, *expected_version)
+                        .await?
+                }
+                EntryWrite::Delete(id) => self.delete_entry(*id).await?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates or replaces an occurrence override as an edit of its series,
+    /// only if the series' stored version is still `expected_version`. The
+    /// series is stored again with the version after that.
+    ///
+    /// Fails with `VersionMismatch` if another writer updated the series
+    /// first, and with `NotFound` if the series is gone.
+    async fn upsert_occurrence_override_if_version(
+        &self,
+        occurrence: &OccurrenceOverride,
+        expected_version: u64,
+    ) -> Result<()> {
+        let series = self.get_entry(occurrence.series_id).await?.ok_or_else(|| {
+            RepositoryError::NotFound {
+                entity_type: "CalendarEntry",
+                id: occurrence.series_id.to_string(),
+            }
+        })?;
+        self.update_entry_if_version(&series, expected_version)
+            .await?;
+        self.upsert_occurrence_override(occurrence).await
+    }
+
+    /// Updates a recurring series and replaces all of its occurrence
+    /// overrides with `overrides`, only if its stored version is still
+    /// `expected_version`.
+    ///
+    /// Fails with `VersionMismatch` if another writer updated the series
+    /// first, before any override is touched.
+    async fn update_series_if_version(
+        &self,
+        entry: &CalendarEntry,
+        overrides: &[OccurrenceOverride],
+        expected_version: u64,
+    ) -> Result<()> {
+        self.update_entry_if_version(entry, expected_version)
+            .await?;
+        self.delete_occurrence_overrides(entry.id, NaiveDate::MIN)
+            .await?;
+        for occurrence in overrides {
+            self.upsert_occurrence_override(occurrence).await?;
+        }
+        Ok(())
+    }
+
+    /// Creates a new entry on behalf of `acting_user`.
+    ///
+    /// Repositories that record revision history note who made the change;
+    /// by default this is the same as `create_entry`.
+    async fn create_entry_as(
+        &self,
+        entry: &CalendarEntry,
+        _acting_user: Option<Uuid>,
+    ) -> Result<()> {
+        self.create_entry(entry).await
+    }
+
+    /// Updates an existing entry on behalf of `acting_user`.
+    ///
+    /// See `create_entry_as`.
+    async fn update_entry_as(
+        &self,
+        entry: &CalendarEntry,
+        _acting_user: Option<Uuid>,
+    ) -> Result<()> {
+        self.update_entry(entry).await
+    }
+
+    /// Updates an existing entry on behalf of `acting_user` if its stored
+    /// version is still `expected_version`.
+    ///
+    /// See `create_entry_as` and `update_entry_if_version`.
+    async fn update_entry_if_version_as(
+        &self,
+        entry: &CalendarEntry,
+        expected_version: u64,
+        _acting_user: Option<Uuid>,
+    ) -> Result<()> {
+        self.update_entry_if_version(entry, expected_version).await
+    }
+
+    /// Moves an entry to another calendar on behalf of `acting_user`.
+    ///
+    /// See `create_entry_as` and `move_entry`.
+    async fn move_entry_as(
+        &self,
+        id: Uuid,
+        calendar_id: Uuid,
+        expected_version: u64,
+        _acting_user: Option<Uuid>,
+    ) -> Result<CalendarEntry> {
+        self.move_entry(id, calendar_id, expected_version).await
+    }
+
+    /// Moves an entry to the trash on behalf of `acting_user`.
+    ///
+    /// See `create_entry_as`.
+    async fn delete_entry_as(&self, id: Uuid, _acting_user: Option<Uuid>) -> Result<()> {
+        self.delete_entry(id).await
+    }
+
+    /// Moves an entry to the trash on behalf of `acting_user` if its stored
+    /// version is still `expected_version`.
+    ///
+    /// See `create_entry_as` and `delete_entry_if_version`.
+    async fn delete_entry_if_version_as(
+        &self,
+        id: Uuid,
+        expected_version: u64,
+        _acting_user: Option<Uuid>,
+    ) -> Result<()> {
+        self.delete_entry_if_version(id, expected_version).await
+    }
+
+    /// Writes an occurrence override as an edit of its series on behalf of
+    /// `acting_user`.
+    ///
+    /// See `create_entry_as` and `upsert_occurrence_override_if_version`.
+    async fn upsert_occurrence_override_if_version_as(
+        &self,
+        occurrence: &OccurrenceOverride,
+        expected_version: u64,
+        _acting_user: Option<Uuid>,
+    ) -> Result<()> {
+        self.upsert_occurrence_override_if_version(occurrence, expected_version)
+            .await
+    }
+
+    /// Updates a series and its overrides on behalf of `acting_user`.
+    ///
+    /// See `create_entry_as` and `update_series_if_version`.
+    async fn update_series_if_version_as(
+        &self,
+        entry: &CalendarEntry,
+        overrides: &[OccurrenceOverride],
+        expected_version: u64,
+        _acting_user: Option<Uuid>,
+    ) -> Result<()> {
+        self.update_series_if_version(entry, overrides, expected_version)
+            .await
+    }
+
+    /// Applies a batch of writes on behalf of `acting_user`.
+    ///
+    /// See `create_entry_as` and `apply_entry_batch`.
+    async fn apply_entry_batch_as(
+        &self,
+        writes: &[EntryWrite],
+        _acting_user: Option<Uuid>,
+    ) -> Result<()> {
+        self.apply_entry_batch(writes).await
+    }
+
+    /// Takes an entry out of the trash on behalf of `acting_user`.
+    ///
+    /// See `create_entry_as`.
+    async fn restore_entry_as(
+        &self,
+        id: Uuid,
+        _acting_user: Option<Uuid>,
+    ) -> Result<CalendarEntry> {
+        self.restore_entry(id).await
+    }
 }
 
 /// Repository for calendar operations.
@@ -39,11 +352,65 @@ pub trait CalendarRepository: Send + Sync {
     /// Creates a new calendar.
     async fn create_calendar(&self, calendar: &Calendar) -> Result<()>;
 
-    /// Updates an existing calendar.
+    /// Updates an existing calendar, storing it with the next version.
     async fn update_calendar(&self, calendar: &Calendar) -> Result<()>;
 
-    /// Deletes a calendar by its ID.
+    /// Updates an existing calendar only if its stored version is still
+    /// `expected_version`, storing it with the version after that.
+    ///
+    /// Fails with `VersionMismatch` if another writer updated the calendar first.
+    async fn update_calendar_if_version(
+        &self,
+        calendar: &Calendar,
+        expected_version: u64,
+    ) -> Result<()>;
+
+    /// Stores how a subscribed calendar's last refresh went, provided its
+    /// stored version is still `expected_version`.
+    ///
+    /// Only the subscription is written and the version is kept, so a refresh
+    /// doesn't change the calendar's ETag or CalDAV ctag. Fails with
+    /// `VersionMismatch` if another writer updated the calendar first.
+    async fn record_subscription_refresh(
+        &self,
+        id: Uuid,
+        subscription: &CalendarSubscription,
+        expected_version: u64,
+    ) -> Result<()>;
+
+    /// Moves a calendar to the trash, along with its entries.
     async fn delete_calendar(&self, id: Uuid) -> Result<()>;
+
+    /// Takes a calendar out of the trash and returns it.
+    ///
+    /// The entries trashed along with it are restored too; entries deleted
+    /// on their own before that stay in the trash. Fails with `NotFound` if
+    /// the calendar is not in the trash.
+    async fn restore_calendar(&self, id: Uuid) -> Result<Calendar>;
+
+    /// Permanently deletes the calendars trashed before `deleted_before`,
+    /// along with all of their entries. Returns how many were deleted.
+    async fn purge_calendars(&self, deleted_before: DateTime<Utc>) -> Result<usize>;
+
+    /// Gets every live calendar that mirrors an iCalendar feed.
+    async fn get_subscribed_calendars(&self) -> Result<Vec<Calendar>>;
+
+    /// Gets every live calendar, for backups.
+    ///
+    /// Reads the whole store, so it isn't meant for request handling.
+    async fn list_calendars(&self) -> Result<Vec<Calendar>>;
+
+    /// Creates `copy` as a new calendar holding copies of the live entries,
+    /// occurrence overrides and memberships of calendar `source_id`, as
+    /// `options` asks, in a single operation.
+    ///
+    /// Fails with `NotFound` if the source calendar doesn't exist.
+    async fn duplicate_calendar(
+        &self,
+        source_id: Uuid,
+        copy: &Calendar,
+        options: &DuplicateOptions,
+    ) -> Result<DuplicateSummary>;
 }
 
 /// Repository for user operations.
@@ -67,6 +434,11 @@ pub trait UserRepository: Send + Sync {
 
     /// Updates an existing user.
     async fn update_user(&self, user: &User) -> Result<()>;
+
+    /// Gets every user, for backups.
+    ///
+    /// Reads the whole store, so it isn't meant for request handling.
+    async fn list_users(&self) -> Result<Vec<User>>;
 }
 
 /// Repository for calendar membership operations.
@@ -80,8 +452,16 @@ pub trait MembershipRepository: Send + Sync {
     ) -> Result<Option<CalendarMembership>>;
 
     /// Gets all calendars for a user with their roles.
+    ///
+    /// Calendars in the trash are left out.
     async fn get_calendars_for_user(&self, user_id: Uuid) -> Result<Vec<(Calendar, CalendarRole)>>;
 
+    /// Gets the trashed calendars a user is a member of, with their roles.
+    async fn get_trashed_calendars_for_user(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<(TrashedCalendar, CalendarRole)>>;
+
     /// Gets all users for a calendar with their roles.
     async fn get_users_for_calendar(&self, calendar_id: Uuid) -> Result<Vec<(User, CalendarRole)>>;
 
@@ -110,3 +490,25 @@ pub trait SettingsRepository: Send + Sync {
         settings: &CalendarSettings,
     ) -> Result<()>;
 }
+
+/// Repository for the secret tokens of calendar subscription feeds.
+#[async_trait]
+pub trait FeedTokenRepository: Send + Sync {
+    /// Gets a feed token by its secret value.
+    async fn get_feed_token(&self, token: &str) -> Result<Option<FeedToken>>;
+
+    /// Gets a user's feed token for a calendar.
+    async fn get_feed_token_for_user(
+        &self,
+        calendar_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<FeedToken>>;
+
+    /// Stores a feed token, replacing the user's previous token for the calendar.
+    async fn put_feed_token(&self, token: &FeedToken) -> Result<()>;
+
+    /// Deletes a user's feed token for a calendar.
+    ///
+    /// Fails with `NotFound` if the user has no token for the calendar.
+    async fn delete_feed_token(&self, calendar_id: Uuid, user_id: Uuid) -> Result<()>;
+}
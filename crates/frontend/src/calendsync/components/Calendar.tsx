@@ -109,6 +109,9 @@ function CalendarRoot({ initialData, children }: CalendarProps) {
       // Trigger visual feedback - entry is also removed from Dexie
       actions.onSseEntryDeleted(entryId, date)
     },
+    onReminderDue: (entry, reminder, date) => {
+      actions.onSseReminderDue(entry, reminder, date)
+    },
     onConnectionChange: (state) => {
       actions.onSseConnectionChange(state)
     },
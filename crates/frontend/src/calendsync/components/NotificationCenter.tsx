@@ -67,6 +67,24 @@ function NotificationIcon({ type }: { type: ChangeType }) {
           <line x1="5" y1="12" x2="19" y2="12" />
         </svg>
       )
+    case "reminder":
+      return (
+        <svg
+          width={12}
+          height={12}
+          viewBox="0 0 24 24"
+          fill="none"
+          stroke="currentColor"
+          strokeWidth={2.5}
+          strokeLinecap="round"
+          strokeLinejoin="round"
+          aria-hidden="true"
+        >
+          <title>Reminder</title>
+          <path d="M6 8a6 6 0 0 1 12 0c0 7 3 9 3 9H3s3-2 3-9" />
+          <path d="M10.3 21a1.94 1.94 0 0 0 3.4 0" />
+        </svg>
+      )
   }
 }
 
@@ -4,7 +4,11 @@
  * Supports sync status indicators for offline-first operations.
  */
 
-import { calculateTimePositionPercent, type OverlapColumn } from "@core/calendar"
+import {
+  calculateTimePositionPercent,
+  getDisplayTimes,
+  type OverlapColumn,
+} from "@core/calendar"
 import type { ServerEntry } from "@core/calendar/types"
 import { useCalendarContext } from "../contexts"
 import { useEntrySyncStatus } from "../hooks/useEntrySyncStatus"
@@ -26,7 +30,11 @@ export function ScheduleTimedEntry({ entry, overlapColumn }: ScheduleTimedEntryP
   const syncStatus = useEntrySyncStatus(entry.id)
   const { entryStyle } = settings
 
-  const { topPercent, heightPercent } = calculateTimePositionPercent(entry.startTime, entry.endTime)
+  const displayTimes = getDisplayTimes(entry)
+  const { topPercent, heightPercent } = calculateTimePositionPercent(
+    displayTimes.startTime,
+    displayTimes.endTime,
+  )
 
   const flashState = flashStates.get(entry.id)
   const flashClass = flashState ? `flash-${flashState}` : ""
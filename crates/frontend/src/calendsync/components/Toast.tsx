@@ -4,7 +4,7 @@
 
 import { useEffect, useState } from "react"
 
-export type ToastType = "added" | "updated" | "deleted"
+export type ToastType = "added" | "updated" | "deleted" | "reminder"
 
 export interface ToastData {
   id: string
@@ -36,12 +36,14 @@ function Toast({ toast, onRemove }: ToastProps) {
     added: "+",
     updated: "~",
     deleted: "-",
+    reminder: "!",
   }
 
   const labels: Record<ToastType, string> = {
     added: "Added",
     updated: "Updated",
     deleted: "Deleted",
+    reminder: "Reminder",
   }
 
   return (
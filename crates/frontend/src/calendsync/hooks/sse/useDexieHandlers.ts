@@ -5,6 +5,7 @@
  * - handleEntryAdded: Adds entry or confirms pending create
  * - handleEntryUpdated: Updates entry or confirms pending update
  * - handleEntryDeleted: Removes entry from database
+ * - handleEntryBatch: Applies a batch of changes in one transaction
  *
  * Used by: useWebSse, useTauriSse
  */
@@ -38,6 +39,12 @@ export interface DexieHandlers {
    * Removes the entry from Dexie.
    */
   handleEntryDeleted: (entryId: string) => Promise<void>
+
+  /**
+   * Handle entry_batch SSE event.
+   * Applies every change in one Dexie transaction so live queries update once.
+   */
+  handleEntryBatch: (added: ServerEntry[], updated: ServerEntry[], deleted: string[]) => Promise<void>
 }
 
 /**
@@ -53,7 +60,7 @@ export interface DexieHandlers {
  * is typically done in the SSE connection hook (e.g., useWebSse) by
  * storing the event ID from each received message.
  *
- * @returns Memoized handlers for entry_added, entry_updated, entry_deleted
+ * @returns Memoized handlers for entry_added, entry_updated, entry_deleted, entry_batch
  *
  * @example
  * ```typescript
@@ -130,9 +137,29 @@ export function useDexieHandlers(): DexieHandlers {
     await db.entries.delete(entryId)
   }, [])
 
+  /**
+   * Handle entry_batch event.
+   * Reuses the single-entry handlers inside one transaction.
+   */
+  const handleEntryBatch = useCallback(
+    async (added: ServerEntry[], updated: ServerEntry[], deleted: string[]) => {
+      await db.transaction("rw", db.entries, async () => {
+        for (const entry of added) {
+          await handleEntryAdded(entry)
+        }
+        for (const entry of updated) {
+          await handleEntryUpdated(entry)
+        }
+        await db.entries.bulkDelete(deleted)
+      })
+    },
+    [handleEntryAdded, handleEntryUpdated],
+  )
+
   return {
     handleEntryAdded,
     handleEntryUpdated,
     handleEntryDeleted,
+    handleEntryBatch,
   }
 }
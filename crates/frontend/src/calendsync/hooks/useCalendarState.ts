@@ -255,6 +255,18 @@ export function useCalendarState(config: UseCalendarStateConfig): [CalendarState
     [addFlashState, addToast, onNotification],
   )
 
+  /**
+   * Handle reminder due event from SSE.
+   * Reminders don't change the entry, so there is no flash animation.
+   */
+  const handleReminderDue = useCallback(
+    (entry: ServerEntry, _reminder: string, date: string) => {
+      addToast("reminder", entry.title, date)
+      onNotification?.("reminder", entry.id, entry.title, date)
+    },
+    [addToast, onNotification],
+  )
+
   /**
    * Handle SSE connection state change.
    */
@@ -455,6 +467,7 @@ export function useCalendarState(config: UseCalendarStateConfig): [CalendarState
     onSseEntryAdded: handleEntryAdded,
     onSseEntryUpdated: handleEntryUpdated,
     onSseEntryDeleted: handleEntryDeleted,
+    onSseReminderDue: handleReminderDue,
     onSseConnectionChange: handleConnectionChange,
   }
 
@@ -26,8 +26,11 @@ import type {
   BaseSseConfig,
   BaseSseResult,
   EntryAddedEvent,
+  EntryBatchEvent,
   EntryDeletedEvent,
+  EntryRestoredEvent,
   EntryUpdatedEvent,
+  ReminderDueEvent,
 } from "@core/sse/types"
 import { useLiveQuery } from "dexie-react-hooks"
 import { useCallback, useEffect, useRef } from "react"
@@ -80,6 +83,7 @@ export function useWebSse(config: UseWebSseConfig): UseWebSseResult {
     onEntryAdded,
     onEntryUpdated,
     onEntryDeleted,
+    onReminderDue,
     onConnectionChange,
     onError,
   } = config
@@ -94,6 +98,7 @@ export function useWebSse(config: UseWebSseConfig): UseWebSseResult {
     handleEntryAdded: dexieHandleAdded,
     handleEntryUpdated: dexieHandleUpdated,
     handleEntryDeleted: dexieHandleDeleted,
+    handleEntryBatch: dexieHandleBatch,
   } = useDexieHandlers()
 
   // Refs for EventSource and reconnection
@@ -106,6 +111,7 @@ export function useWebSse(config: UseWebSseConfig): UseWebSseResult {
     onEntryAdded,
     onEntryUpdated,
     onEntryDeleted,
+    onReminderDue,
     onError,
   })
 
@@ -115,9 +121,10 @@ export function useWebSse(config: UseWebSseConfig): UseWebSseResult {
       onEntryAdded,
       onEntryUpdated,
       onEntryDeleted,
+      onReminderDue,
       onError,
     }
-  }, [onEntryAdded, onEntryUpdated, onEntryDeleted, onError])
+  }, [onEntryAdded, onEntryUpdated, onEntryDeleted, onReminderDue, onError])
 
   // Load last event ID from Dexie reactively
   const syncState = useLiveQuery(() => db.sync_state.get(calendarId), [calendarId])
@@ -146,11 +153,11 @@ export function useWebSse(config: UseWebSseConfig): UseWebSseResult {
   )
 
   /**
-   * Handle entry_added event.
+   * Handle entry_added and entry_restored events.
    * Updates Dexie via shared handler, then calls user callback.
    */
   const handleEntryAdded = useCallback(
-    async (data: EntryAddedEvent, eventId: string) => {
+    async (data: EntryAddedEvent | EntryRestoredEvent, eventId: string) => {
       await dexieHandleAdded(data.entry)
       await saveLastEventId(eventId)
       eventCallbacksRef.current.onEntryAdded?.(data.entry, data.date)
@@ -184,6 +191,34 @@ export function useWebSse(config: UseWebSseConfig): UseWebSseResult {
     [dexieHandleDeleted, saveLastEventId],
   )
 
+  /**
+   * Handle entry_batch event.
+   * Applies the whole batch to Dexie at once, then calls the per-entry callbacks.
+   */
+  const handleEntryBatch = useCallback(
+    async (data: EntryBatchEvent, eventId: string) => {
+      await dexieHandleBatch(data.added, data.updated, data.deleted)
+      await saveLastEventId(eventId)
+      const callbacks = eventCallbacksRef.current
+      for (const entry of data.added) callbacks.onEntryAdded?.(entry, data.date)
+      for (const entry of data.updated) callbacks.onEntryUpdated?.(entry, data.date)
+      for (const entryId of data.deleted) callbacks.onEntryDeleted?.(entryId, data.date)
+    },
+    [dexieHandleBatch, saveLastEventId],
+  )
+
+  /**
+   * Handle reminder_due event.
+   * Reminders don't change entries, so only the event ID is saved.
+   */
+  const handleReminderDue = useCallback(
+    async (data: ReminderDueEvent, eventId: string) => {
+      await saveLastEventId(eventId)
+      eventCallbacksRef.current.onReminderDue?.(data.entry, data.reminder, data.date)
+    },
+    [saveLastEventId],
+  )
+
   /**
    * Disconnect from SSE.
    */
@@ -264,6 +299,18 @@ export function useWebSse(config: UseWebSseConfig): UseWebSseResult {
       "entry_deleted",
       createEventHandler<EntryDeletedEvent>("entry_deleted", handleEntryDeleted),
     )
+    eventSource.addEventListener(
+      "entry_restored",
+      createEventHandler<EntryRestoredEvent>("entry_restored", handleEntryAdded),
+    )
+    eventSource.addEventListener(
+      "entry_batch",
+      createEventHandler<EntryBatchEvent>("entry_batch", handleEntryBatch),
+    )
+    eventSource.addEventListener(
+      "reminder_due",
+      createEventHandler<ReminderDueEvent>("reminder_due", handleReminderDue),
+    )
 
     // Handle errors and reconnection
     eventSource.onerror = () => {
@@ -293,6 +340,8 @@ export function useWebSse(config: UseWebSseConfig): UseWebSseResult {
     handleEntryAdded,
     handleEntryUpdated,
     handleEntryDeleted,
+    handleEntryBatch,
+    handleReminderDue,
   ])
 
   /**
@@ -28,6 +28,8 @@ export interface UserInfo {
   name: string
   /** User's email address */
   email: string
+  /** IANA time zone entries are displayed in */
+  timezone?: string
 }
 
 /**
@@ -91,7 +93,7 @@ export interface CalendarProps {
 }
 
 /** Represents the type of change for entries, notifications, and flash states */
-export type ChangeType = "added" | "updated" | "deleted"
+export type ChangeType = "added" | "updated" | "deleted" | "reminder"
 
 /** Notification stored in the notification center */
 export interface Notification {
@@ -163,6 +165,12 @@ export interface CalendarActions {
   onSseEntryUpdated: (entry: import("@core/calendar/types").ServerEntry, date: string) => void
   /** Handle SSE entry_deleted event with visual feedback (flash, toast, notification) */
   onSseEntryDeleted: (entryId: string, date: string) => void
+  /** Handle SSE reminder_due event with a toast and notification */
+  onSseReminderDue: (
+    entry: import("@core/calendar/types").ServerEntry,
+    reminder: string,
+    date: string,
+  ) => void
   /** Handle SSE connection state change */
   onSseConnectionChange: (state: SseConnectionState) => void
 }
@@ -1,6 +1,11 @@
 import { describe, expect, test } from "bun:test"
 import type { Notification } from "@calendsync/types"
-import { addNotification, createNotification } from "../notifications"
+import {
+  addNotification,
+  createNotification,
+  getNotificationIcon,
+  getNotificationLabel,
+} from "../notifications"
 
 describe("createNotification", () => {
   test("generates deterministic ID from type and entryId", () => {
@@ -21,6 +26,15 @@ describe("createNotification", () => {
   })
 })
 
+describe("reminder notifications", () => {
+  test("has its own ID, icon and label", () => {
+    const n = createNotification("reminder", "e1", "Standup", "2026-03-04")
+    expect(n.id).toBe("notif-reminder-e1")
+    expect(getNotificationIcon("reminder")).toBe("!")
+    expect(getNotificationLabel("reminder")).toBe("Reminder")
+  })
+})
+
 describe("addNotification", () => {
   const makeNotif = (id: string): Notification => ({
     id,
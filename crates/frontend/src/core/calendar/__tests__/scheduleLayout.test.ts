@@ -10,6 +10,7 @@ import {
   detectOverlappingEntries,
   formatHourLabel,
   generateHourLabels,
+  getDisplayTimes,
   HOUR_HEIGHT_PX,
   HOURS_IN_DAY,
   parseTimeToMinutes,
@@ -277,6 +278,40 @@ describe("detectOverlappingEntries", () => {
   })
 })
 
+describe("getDisplayTimes", () => {
+  test("uses the entry times for single-day entries", () => {
+    expect(getDisplayTimes(createEntry())).toEqual({ startTime: "10:00:00", endTime: "11:00:00" })
+  })
+
+  test("uses segment times for entries crossing midnight", () => {
+    const continued = createEntry({
+      startTime: "22:00",
+      endTime: "06:00",
+      endDate: "2024-01-16",
+      segmentDate: "2024-01-16",
+      segmentStartTime: "00:00",
+      segmentEndTime: "06:00",
+      continued: true,
+    })
+    expect(getDisplayTimes(continued)).toEqual({ startTime: "00:00", endTime: "06:00" })
+  })
+
+  test("segments do not overlap entries later in the day", () => {
+    const continued = createEntry({
+      id: "shift",
+      startTime: "22:00",
+      endTime: "06:00",
+      segmentStartTime: "00:00",
+      segmentEndTime: "06:00",
+    })
+    const breakfast = createEntry({ id: "breakfast", startTime: "08:00", endTime: "09:00" })
+
+    const columns = detectOverlappingEntries([continued, breakfast])
+    expect(columns.get("shift")?.columnIndex).toBe(0)
+    expect(columns.get("breakfast")?.columnIndex).toBe(0)
+  })
+})
+
 describe("calculateGridHeight", () => {
   test("calculates total grid height", () => {
     expect(calculateGridHeight()).toBe(HOURS_IN_DAY * HOUR_HEIGHT_PX)
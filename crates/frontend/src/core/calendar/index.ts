@@ -142,6 +142,7 @@ export {
   detectOverlappingEntries,
   formatHourLabel,
   generateHourLabels,
+  getDisplayTimes,
   getOverlappingEntries,
   HOUR_HEIGHT_PX,
   HOURS_IN_DAY,
@@ -97,7 +97,10 @@ export function parseNotificationsJson(json: string | null): Notification[] {
         n !== null &&
         typeof n.id === "string" &&
         typeof n.type === "string" &&
-        (n.type === "added" || n.type === "updated" || n.type === "deleted") &&
+        (n.type === "added" ||
+          n.type === "updated" ||
+          n.type === "deleted" ||
+          n.type === "reminder") &&
         typeof n.entryId === "string" &&
         typeof n.entryTitle === "string" &&
         typeof n.date === "string" &&
@@ -146,6 +149,8 @@ export function getNotificationIcon(type: ChangeType): string {
       return "~"
     case "deleted":
       return "-"
+    case "reminder":
+      return "!"
   }
 }
 
@@ -160,5 +165,7 @@ export function getNotificationLabel(type: ChangeType): string {
       return "Updated"
     case "deleted":
       return "Deleted"
+    case "reminder":
+      return "Reminder"
   }
 }
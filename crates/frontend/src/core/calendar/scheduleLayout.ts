@@ -114,6 +114,20 @@ export function calculateDuration(startTime: string | null, endTime: string | nu
   return endMinutes - startMinutes
 }
 
+/**
+ * Get the times an entry covers on the day it is shown on.
+ * Segments of entries crossing midnight use their segment times.
+ */
+export function getDisplayTimes(entry: ServerEntry): {
+  startTime: string | null
+  endTime: string | null
+} {
+  return {
+    startTime: entry.segmentStartTime ?? entry.startTime,
+    endTime: entry.segmentEndTime ?? entry.endTime,
+  }
+}
+
 // ============================================================================
 // Position Calculation Functions
 // ============================================================================
@@ -231,12 +245,14 @@ export function detectOverlappingEntries(entries: ServerEntry[]): Map<string, Ov
 
   // Sort by start time, then by duration (longer first)
   const sorted = [...entries].sort((a, b) => {
-    const startA = parseTimeToMinutes(a.startTime)
-    const startB = parseTimeToMinutes(b.startTime)
+    const timesA = getDisplayTimes(a)
+    const timesB = getDisplayTimes(b)
+    const startA = parseTimeToMinutes(timesA.startTime)
+    const startB = parseTimeToMinutes(timesB.startTime)
     if (startA !== startB) return startA - startB
 
-    const durA = calculateDuration(a.startTime, a.endTime)
-    const durB = calculateDuration(b.startTime, b.endTime)
+    const durA = calculateDuration(timesA.startTime, timesA.endTime)
+    const durB = calculateDuration(timesB.startTime, timesB.endTime)
     return durB - durA // Longer duration first
   })
 
@@ -244,8 +260,9 @@ export function detectOverlappingEntries(entries: ServerEntry[]): Map<string, Ov
   const columns: { entry: ServerEntry; endMinutes: number; columnIndex: number }[] = []
 
   for (const entry of sorted) {
-    const startMinutes = parseTimeToMinutes(entry.startTime)
-    const endMinutes = startMinutes + calculateDuration(entry.startTime, entry.endTime)
+    const { startTime, endTime } = getDisplayTimes(entry)
+    const startMinutes = parseTimeToMinutes(startTime)
+    const endMinutes = startMinutes + calculateDuration(startTime, endTime)
 
     // Remove entries that have ended before this one starts
     const activeColumns = columns.filter((col) => col.endMinutes > startMinutes)
@@ -283,14 +300,16 @@ export function getOverlappingEntries(
   entry: ServerEntry,
   allEntries: ServerEntry[],
 ): ServerEntry[] {
-  const entryStart = parseTimeToMinutes(entry.startTime)
-  const entryEnd = entryStart + calculateDuration(entry.startTime, entry.endTime)
+  const entryTimes = getDisplayTimes(entry)
+  const entryStart = parseTimeToMinutes(entryTimes.startTime)
+  const entryEnd = entryStart + calculateDuration(entryTimes.startTime, entryTimes.endTime)
 
   return allEntries.filter((other) => {
     if (other.id === entry.id) return false
 
-    const otherStart = parseTimeToMinutes(other.startTime)
-    const otherEnd = otherStart + calculateDuration(other.startTime, other.endTime)
+    const otherTimes = getDisplayTimes(other)
+    const otherStart = parseTimeToMinutes(otherTimes.startTime)
+    const otherEnd = otherStart + calculateDuration(otherTimes.startTime, otherTimes.endTime)
 
     return timeRangesOverlap(entryStart, entryEnd, otherStart, otherEnd)
   })
@@ -1,3 +1,27 @@
+/**
+ * Response of an attendee to an invitation.
+ */
+export type ResponseStatus = "needs-action" | "accepted" | "declined" | "tentative"
+
+/**
+ * Person invited to an entry.
+ */
+export interface ServerAttendee {
+  email: string
+  /** Linked user account, when the address belongs to one */
+  userId: string | null
+  name: string | null
+  status: ResponseStatus
+}
+
+/**
+ * Sub-item of a task's checklist.
+ */
+export interface ServerChecklistItem {
+  text: string
+  completed: boolean
+}
+
 /**
  * Calendar entry from the server.
  * This matches the data structure rendered by Askama templates.
@@ -19,6 +43,38 @@ export interface ServerEntry {
   endDate: string
   startTime: string | null
   endTime: string | null
+  /** RRULE value when the entry belongs to a recurring series */
+  recurrence?: string | null
+  /** Original date of this occurrence within its recurring series */
+  occurrenceDate?: string | null
+  /** IANA time zone the dates and times are expressed in */
+  timezone?: string | null
+  /** Reminders before the start (e.g. "10m", "1d@09:00") */
+  reminders?: string[]
+  /** People invited to the entry, with their responses */
+  attendees?: ServerAttendee[]
+  /** Lowercase labels such as "oncall" or "travel" */
+  tags?: string[]
+  /** Task priority: "low", "normal", "high" or "urgent" (null for other kinds) */
+  priority?: string | null
+  /** Time a task is due (HH:MM) */
+  dueTime?: string | null
+  /** Checklist items of a task */
+  checklist?: ServerChecklistItem[]
+  /** True if the task is shown on today until it is completed */
+  rollOver?: boolean
+  /** Day shown for one segment of a timed entry crossing midnight */
+  segmentDate?: string | null
+  /** Time the segment starts on its day ("00:00" if continued) */
+  segmentStartTime?: string | null
+  /** Time the segment ends on its day ("24:00" if it continues) */
+  segmentEndTime?: string | null
+  /** True if the entry started on an earlier day */
+  continued?: boolean
+  /** True if the entry goes on past this day */
+  continues?: boolean
+  /** Timed entries this one overlaps (only in create and update responses) */
+  conflicts?: ServerEntry[]
 }
 
 /**
@@ -13,7 +13,13 @@ export type SseConnectionState = "disconnected" | "connecting" | "connected" | "
 /**
  * SSE event types from the server.
  */
-export type SseEventType = "entry_added" | "entry_updated" | "entry_deleted"
+export type SseEventType =
+  | "entry_added"
+  | "entry_updated"
+  | "entry_deleted"
+  | "entry_restored"
+  | "entry_batch"
+  | "reminder_due"
 
 /**
  * Base structure for all SSE events.
@@ -37,6 +43,8 @@ export interface EntryAddedEvent extends SseEventBase {
 export interface EntryUpdatedEvent extends SseEventBase {
   type: "entry_updated"
   entry: ServerEntry
+  /** Original date of the single recurring occurrence that changed, if any */
+  occurrence_date?: string | null
 }
 
 /**
@@ -45,12 +53,52 @@ export interface EntryUpdatedEvent extends SseEventBase {
 export interface EntryDeletedEvent extends SseEventBase {
   type: "entry_deleted"
   entry_id: string
+  /** Original date of the single recurring occurrence that was cancelled, if any */
+  occurrence_date?: string | null
+}
+
+/**
+ * Event emitted when a deleted entry is restored from the trash.
+ * Clients treat it like an added entry.
+ */
+export interface EntryRestoredEvent extends SseEventBase {
+  type: "entry_restored"
+  entry: ServerEntry
+}
+
+/**
+ * Event emitted once for a batch of entry writes in one calendar.
+ * `date` is the earliest date the batch touched.
+ */
+export interface EntryBatchEvent extends SseEventBase {
+  type: "entry_batch"
+  added: ServerEntry[]
+  updated: ServerEntry[]
+  deleted: string[]
+}
+
+/**
+ * Event emitted when one of an entry's reminders falls due.
+ */
+export interface ReminderDueEvent extends SseEventBase {
+  type: "reminder_due"
+  entry: ServerEntry
+  /** The reminder that fired (e.g. "10m", "1d@09:00") */
+  reminder: string
+  /** When the reminder fell due (RFC 3339) */
+  fire_at: string
 }
 
 /**
  * Union of all SSE event types.
  */
-export type SseEvent = EntryAddedEvent | EntryUpdatedEvent | EntryDeletedEvent
+export type SseEvent =
+  | EntryAddedEvent
+  | EntryUpdatedEvent
+  | EntryDeletedEvent
+  | EntryRestoredEvent
+  | EntryBatchEvent
+  | ReminderDueEvent
 
 /**
  * Base configuration for SSE hooks (web and Tauri).
@@ -67,6 +115,8 @@ export interface BaseSseConfig {
   onEntryUpdated?: (entry: ServerEntry, date: string) => void
   /** Callback when an entry is deleted (for notifications, etc.) */
   onEntryDeleted?: (entryId: string, date: string) => void
+  /** Callback when a reminder for an entry falls due */
+  onReminderDue?: (entry: ServerEntry, reminder: string, date: string) => void
   /** Callback when connection state changes */
   onConnectionChange?: (state: SseConnectionState) => void
   /** Callback when an error occurs */
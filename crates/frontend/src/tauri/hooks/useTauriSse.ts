@@ -74,6 +74,7 @@ export function useTauriSse(config: UseTauriSseConfig): UseTauriSseResult {
     onEntryAdded,
     onEntryUpdated,
     onEntryDeleted,
+    onReminderDue,
     onConnectionChange,
     onError,
   } = config
@@ -90,13 +91,26 @@ export function useTauriSse(config: UseTauriSseConfig): UseTauriSseResult {
     handleEntryAdded: dexieHandleAdded,
     handleEntryUpdated: dexieHandleUpdated,
     handleEntryDeleted: dexieHandleDeleted,
+    handleEntryBatch: dexieHandleBatch,
   } = useDexieHandlers()
 
   // Store entry callbacks in refs (connection callback handled by useConnectionManager)
-  const entryCallbacksRef = useRef({ onEntryAdded, onEntryUpdated, onEntryDeleted, onError })
+  const entryCallbacksRef = useRef({
+    onEntryAdded,
+    onEntryUpdated,
+    onEntryDeleted,
+    onReminderDue,
+    onError,
+  })
   useEffect(() => {
-    entryCallbacksRef.current = { onEntryAdded, onEntryUpdated, onEntryDeleted, onError }
-  }, [onEntryAdded, onEntryUpdated, onEntryDeleted, onError])
+    entryCallbacksRef.current = {
+      onEntryAdded,
+      onEntryUpdated,
+      onEntryDeleted,
+      onReminderDue,
+      onError,
+    }
+  }, [onEntryAdded, onEntryUpdated, onEntryDeleted, onReminderDue, onError])
 
   /**
    * Handle entry_added event: update Dexie via shared handler, then call callback.
@@ -131,6 +145,20 @@ export function useTauriSse(config: UseTauriSseConfig): UseTauriSseResult {
     [dexieHandleDeleted],
   )
 
+  /**
+   * Handle entry_batch event: apply the batch to Dexie at once, then call callbacks.
+   */
+  const handleEntryBatch = useCallback(
+    async (added: ServerEntry[], updated: ServerEntry[], deleted: string[], date: string) => {
+      await dexieHandleBatch(added, updated, deleted)
+      const callbacks = entryCallbacksRef.current
+      for (const entry of added) callbacks.onEntryAdded?.(entry, date)
+      for (const entry of updated) callbacks.onEntryUpdated?.(entry, date)
+      for (const entryId of deleted) callbacks.onEntryDeleted?.(entryId, date)
+    },
+    [dexieHandleBatch],
+  )
+
   /**
    * Reconnect to SSE (manual trigger).
    */
@@ -195,6 +223,20 @@ export function useTauriSse(config: UseTauriSseConfig): UseTauriSseResult {
         }),
       )
 
+      // Entry restored events (restored entries are added back like new ones)
+      unlisteners.push(
+        await listen<{ data: { entry: ServerEntry; date: string } }>(
+          "sse:entry_restored",
+          (event) => {
+            const { entry, date } = event.payload.data
+            handleEntryAdded(entry, date).catch((e) => {
+              const error = e instanceof Error ? e : new Error(String(e))
+              entryCallbacksRef.current.onError?.(error, "handle_entry_restored")
+            })
+          },
+        ),
+      )
+
       // Entry updated events
       unlisteners.push(
         await listen<{ data: { entry: ServerEntry; date: string } }>(
@@ -220,6 +262,30 @@ export function useTauriSse(config: UseTauriSseConfig): UseTauriSseResult {
         }),
       )
 
+      // Entry batch events
+      unlisteners.push(
+        await listen<{
+          data: { added: ServerEntry[]; updated: ServerEntry[]; deleted: string[]; date: string }
+        }>("sse:entry_batch", (event) => {
+          const { added, updated, deleted, date } = event.payload.data
+          handleEntryBatch(added, updated, deleted, date).catch((e) => {
+            const error = e instanceof Error ? e : new Error(String(e))
+            entryCallbacksRef.current.onError?.(error, "handle_entry_batch")
+          })
+        }),
+      )
+
+      // Reminder due events (no Dexie changes, notify only)
+      unlisteners.push(
+        await listen<{ data: { entry: ServerEntry; reminder: string; date: string } }>(
+          "sse:reminder_due",
+          (event) => {
+            const { entry, reminder, date } = event.payload.data
+            entryCallbacksRef.current.onReminderDue?.(entry, reminder, date)
+          },
+        ),
+      )
+
       unlistenersRef.current = unlisteners
 
       // Start SSE connection
@@ -254,6 +320,7 @@ export function useTauriSse(config: UseTauriSseConfig): UseTauriSseResult {
     handleEntryAdded,
     handleEntryUpdated,
     handleEntryDeleted,
+    handleEntryBatch,
     updateConnectionState,
   ])
 
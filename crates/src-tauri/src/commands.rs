@@ -193,6 +193,9 @@ pub async fn fetch_entry(app: AppHandle, id: String) -> Result<ServerEntry, Stri
 /// * `sse:entry_added` - Entry added event with entry data
 /// * `sse:entry_updated` - Entry updated event with entry data
 /// * `sse:entry_deleted` - Entry deleted event with entry ID
+/// * `sse:entry_restored` - Entry restored from the trash, with entry data
+/// * `sse:entry_batch` - Batch of entry writes, with added/updated entries and deleted IDs
+/// * `sse:reminder_due` - Reminder due event with entry data and the reminder that fired
 #[tauri::command]
 pub async fn start_sse(
     app: AppHandle,
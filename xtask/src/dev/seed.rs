@@ -116,10 +116,7 @@ mod tests {
             }),
             "timed"
         );
-        assert_eq!(
-            entry_type_string(&EntryKind::Task { completed: false }),
-            "task"
-        );
+        assert_eq!(entry_type_string(&EntryKind::task(false)), "task");
         assert_eq!(entry_type_string(&EntryKind::MultiDay), "multi_day");
     }
 
@@ -12,6 +12,7 @@ mod dev;
 mod dynamodb;
 mod integration;
 mod lint;
+mod migrate;
 mod prelude;
 mod seed;
 
@@ -73,6 +74,9 @@ enum Commands {
     /// Seed a calendar with demo entries (requires authentication)
     Seed(seed::SeedCommand),
 
+    /// Copy all data from one storage backend to another
+    Migrate(migrate::MigrateCommand),
+
     /// Documentation tasks (coming soon)
     #[command(hide = true)]
     Docs {
@@ -106,6 +110,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Commands::Seed(seed_cmd) => {
             seed::run(seed_cmd, cli.global).await?;
         }
+        Commands::Migrate(migrate_cmd) => {
+            migrate::run(migrate_cmd, cli.global).await?;
+        }
         Commands::Docs { .. } => {
             println!("Documentation automation coming soon!");
             println!("This will generate and validate documentation.");
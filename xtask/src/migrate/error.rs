@@ -0,0 +1,33 @@
+//! Error types for migrate operations.
+
+use thiserror::Error;
+
+/// Result type alias for migrate module.
+pub type Result<T> = std::result::Result<T, MigrateError>;
+
+/// Errors that can occur while migrating between storage backends.
+#[derive(Error, Debug)]
+pub enum MigrateError {
+    #[error("Invalid backends: {0}")]
+    InvalidBackends(String),
+
+    #[error("`calendsync {command}` failed; run the migration again to resume")]
+    CommandFailed { command: &'static str },
+
+    #[error("Invalid backup: {0}")]
+    InvalidBackup(String),
+
+    #[error(
+        "The backup holds {backup} entries but the source has {counted}; run again with --fresh"
+    )]
+    IncompleteBackup { backup: usize, counted: usize },
+
+    #[error("{count} records did not verify")]
+    Unverified { count: usize },
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
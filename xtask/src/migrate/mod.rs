@@ -0,0 +1,567 @@
+//! Migration between storage backends.
+//!
+//! The storage backends are mutually exclusive features of the server, so no
+//! single binary can hold two of them. A migration runs the server's own
+//! maintenance commands instead: `calendsync backup` built with the source
+//! backend, then `calendsync restore` built with the target backend. Both go
+//! through the repository implementations in `storage/`, so the data lands
+//! exactly as the server would write it.
+//!
+//! Like any backup, a migration only carries live data: the trash, entry
+//! revision history, feed tokens and delivered-reminder claims stay behind,
+//! and the command warns about it before reading the source.
+//!
+//! Every step leaves its result in the work directory, so an interrupted
+//! migration picks up where it stopped when run again:
+//!
+//! - `backup.jsonl` - the source data, read once
+//! - `source-count.json` - the source's live entries, counted without the
+//!   backup so a backup that missed some is caught before it is restored
+//! - `checkpoint.json` - how far the restore got
+//! - `report.json` - what the restore did and what didn't verify
+//!
+//! # Usage
+//!
+//! ```bash
+//! # SQLite database to DynamoDB Local
+//! cargo xtask migrate --from sqlite --to dynamodb --dynamodb-endpoint http://localhost:8000
+//!
+//! # Provisioned DynamoDB table: smaller batches with a pause between them
+//! cargo xtask migrate --from sqlite --to dynamodb --batch-size 10 --pause-ms 200
+//!
+//! # Start over instead of resuming
+//! cargo xtask migrate --from sqlite --to dynamodb --fresh
+//! ```
+
+pub mod error;
+
+pub use error::{MigrateError, Result};
+
+use std::path::{Path, PathBuf};
+
+use calendsync_core::backup::{Backup, BackupCounts, RecordKind, RestoreReport};
+use tokio::process::Command;
+
+use crate::dev::containers::Storage;
+use crate::prelude::*;
+
+/// Mismatches listed before the rest are only counted.
+const MAX_LISTED_MISMATCHES: usize = 20;
+
+/// Data a backup leaves out, and so a migration doesn't copy.
+pub const NOT_MIGRATED: [&str; 4] = [
+    "trashed calendars and entries",
+    "entry revision history",
+    "calendar feed tokens",
+    "delivered-reminder claims",
+];
+
+/// Migrate data between storage backends.
+#[derive(Debug, clap::Parser)]
+#[command(long_about = "Copy the live data from one storage backend to another.
+
+Reads every user, calendar, membership, setting and entry from the source
+backend with `calendsync backup`, writes it to the target backend with
+`calendsync restore`, keeping all IDs, and verifies the target against the
+backup. The source's entries are also counted on their own, and a backup
+holding fewer is not restored. Prints per-entity counts and every record
+that did not verify.
+
+Only live data is copied: trashed calendars and entries, entry revision
+history, calendar feed tokens and delivered-reminder claims are not, so
+feed URLs have to be shared again and recently delivered reminders may be
+sent a second time.
+
+An interrupted migration resumes when run again with the same work
+directory. Use --fresh to read the source again instead.
+
+Environment variables:
+  AWS_ENDPOINT_URL    - DynamoDB endpoint (or use --dynamodb-endpoint)
+  AWS_REGION          - AWS region for DynamoDB (defaults to us-east-1)
+  AWS_PROFILE         - AWS profile to use for credentials")]
+pub struct MigrateCommand {
+    /// Backend to read from.
+    #[arg(long, value_enum)]
+    pub from: Storage,
+
+    /// Backend to write to.
+    #[arg(long, value_enum)]
+    pub to: Storage,
+
+    /// SQLite database file.
+    #[arg(long, default_value = ".local/data/calendsync.db")]
+    pub sqlite_path: PathBuf,
+
+    /// DynamoDB table name.
+    #[arg(long, default_value = "calendsync")]
+    pub table_name: String,
+
+    /// DynamoDB endpoint to use instead of AWS, such as DynamoDB Local.
+    /// Uses dummy credentials.
+    #[arg(long, value_name = "URL")]
+    pub dynamodb_endpoint: Option<String>,
+
+    /// Records written at once.
+    #[arg(long, default_value = "25")]
+    pub batch_size: usize,
+
+    /// Milliseconds to wait between batches.
+    #[arg(long, default_value = "0")]
+    pub pause_ms: u64,
+
+    /// Attempts per DynamoDB request when throttled.
+    #[arg(long, default_value = "10")]
+    pub max_attempts: u32,
+
+    /// Directory keeping the backup, checkpoint and report between runs.
+    #[arg(long, default_value = ".local/migrate")]
+    pub work_dir: PathBuf,
+
+    /// Discard an interrupted migration and read the source again.
+    #[arg(long)]
+    pub fresh: bool,
+
+    /// Build the server in release mode.
+    #[arg(long)]
+    pub release: bool,
+}
+
+// ============================================================================
+// Pure Functions (Functional Core)
+// ============================================================================
+
+/// Checks that a migration between `from` and `to` makes sense.
+pub fn validate_backends(from: Storage, to: Storage) -> Result<()> {
+    if from == Storage::Inmemory || to == Storage::Inmemory {
+        return Err(MigrateError::InvalidBackends(
+            "in-memory storage does not outlive the server".to_string(),
+        ));
+    }
+    if from == to {
+        return Err(MigrateError::InvalidBackends(
+            "source and target are the same backend".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the cargo feature of a storage backend.
+pub fn storage_feature(storage: Storage) -> &'static str {
+    match storage {
+        Storage::Inmemory => "inmemory",
+        Storage::Sqlite => "sqlite",
+        Storage::Dynamodb => "dynamodb",
+    }
+}
+
+/// Returns the cargo arguments running a server command on `storage`.
+pub fn server_args(storage: Storage, release: bool, command: &[String]) -> Vec<String> {
+    let mut args: Vec<String> = [
+        "run",
+        "-p",
+        "calendsync",
+        "--no-default-features",
+        "--features",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+    args.push(format!("{},memory", storage_feature(storage)));
+    if release {
+        args.push("--release".to_string());
+    }
+    args.push("--".to_string());
+    args.extend(command.iter().cloned());
+    args
+}
+
+/// Returns the environment variables pointing the server at `storage`.
+pub fn environment_variables(
+    storage: Storage,
+    cmd: &MigrateCommand,
+) -> Vec<(&'static str, String)> {
+    let mut vars = Vec::new();
+
+    match storage {
+        Storage::Sqlite => {
+            vars.push(("SQLITE_PATH", cmd.sqlite_path.display().to_string()));
+        }
+        Storage::Dynamodb => {
+            vars.push(("DYNAMODB_TABLE_NAME", cmd.table_name.clone()));
+            vars.push(("DYNAMODB_MAX_ATTEMPTS", cmd.max_attempts.to_string()));
+            if let Some(endpoint) = &cmd.dynamodb_endpoint {
+                vars.push(("AWS_ENDPOINT_URL", endpoint.clone()));
+                vars.push(("AWS_REGION", "us-east-1".to_string()));
+                vars.push(("AWS_ACCESS_KEY_ID", "test".to_string()));
+                vars.push(("AWS_SECRET_ACCESS_KEY", "test".to_string()));
+            }
+        }
+        Storage::Inmemory => {}
+    }
+
+    vars
+}
+
+/// Returns the warning listing what a migration leaves behind.
+pub fn not_migrated_warning() -> String {
+    format!("Not migrated: {}.", NOT_MIGRATED.join(", "))
+}
+
+/// Checks that the backup holds as many entries as the source counted.
+pub fn check_source_count(backup: &BackupCounts, source_entries: usize) -> Result<()> {
+    if backup.entries != source_entries {
+        return Err(MigrateError::IncompleteBackup {
+            backup: backup.entries,
+            counted: source_entries,
+        });
+    }
+    Ok(())
+}
+
+/// Formats per-entity counts as table rows, header first.
+///
+/// Only entries are counted in the source apart from the backup.
+pub fn format_counts(
+    expected: &BackupCounts,
+    source_entries: usize,
+    report: &RestoreReport,
+) -> Vec<String> {
+    let mut mismatched = BackupCounts::default();
+    for mismatch in &report.mismatches {
+        mismatched.add(mismatch.kind);
+    }
+
+    let row = |name: &str, cells: [String; 6]| {
+        format!(
+            "{:<12} {:>8} {:>8} {:>8} {:>8} {:>9} {:>10}",
+            name, cells[0], cells[1], cells[2], cells[3], cells[4], cells[5]
+        )
+    };
+    let header = [
+        "source",
+        "backup",
+        "restored",
+        "skipped",
+        "conflicts",
+        "mismatches",
+    ]
+    .map(String::from);
+
+    let mut lines = vec![row("", header)];
+    for kind in RecordKind::ALL {
+        let source = match kind {
+            RecordKind::Entry => source_entries.to_string(),
+            _ => "-".to_string(),
+        };
+        let [backup, restored, skipped, conflicts, mismatches] = [
+            expected.get(kind),
+            report.restored.get(kind),
+            report.skipped.get(kind),
+            report.conflicts.get(kind),
+            mismatched.get(kind),
+        ]
+        .map(|n| n.to_string());
+        let cells = [source, backup, restored, skipped, conflicts, mismatches];
+        lines.push(row(kind.as_str(), cells));
+    }
+    lines
+}
+
+// ============================================================================
+// I/O Functions (Imperative Shell)
+// ============================================================================
+
+/// Main entry point for migrate command.
+pub async fn run(cmd: MigrateCommand, global: crate::Global) -> Result<()> {
+    validate_backends(cmd.from, cmd.to)?;
+
+    let backup_path = cmd.work_dir.join("backup.jsonl");
+    let count_path = cmd.work_dir.join("source-count.json");
+    let checkpoint_path = cmd.work_dir.join("checkpoint.json");
+    let report_path = cmd.work_dir.join("report.json");
+
+    if !global.is_silent() {
+        aprintln!("{}", p_b("Migration"));
+        aprintln!();
+        aprintln!(
+            "{} {} → {}",
+            p_b("Backends:"),
+            storage_feature(cmd.from),
+            storage_feature(cmd.to)
+        );
+        aprintln!("{} {}", p_b("Work directory:"), cmd.work_dir.display());
+        aprintln!();
+    }
+    aprintln!("{} {}", p_y("⚠"), p_y(&not_migrated_warning()));
+    if !global.is_silent() {
+        aprintln!();
+    }
+
+    std::fs::create_dir_all(&cmd.work_dir)?;
+    if cmd.fresh {
+        for path in [&backup_path, &count_path, &checkpoint_path, &report_path] {
+            remove_if_exists(path)?;
+        }
+    }
+
+    // Read the source once; a later run reuses the same backup
+    if backup_path.exists() {
+        if !global.is_silent() {
+            aprintln!(
+                "{} Resuming with {} (--fresh to read the source again)",
+                p_y("→"),
+                backup_path.display()
+            );
+        }
+    } else {
+        if !global.is_silent() {
+            aprintln!("{} Reading {}...", p_b("→"), storage_feature(cmd.from));
+        }
+        remove_if_exists(&checkpoint_path)?;
+        remove_if_exists(&count_path)?;
+        let partial = backup_path.with_extension("jsonl.partial");
+        let command = ["backup".to_string(), partial.display().to_string()];
+        run_server(cmd.from, &cmd, &command, &global, "backup").await?;
+        std::fs::rename(&partial, &backup_path)?;
+    }
+
+    let contents = std::fs::read_to_string(&backup_path)?;
+    let backup =
+        Backup::from_jsonl(&contents).map_err(|e| MigrateError::InvalidBackup(e.to_string()))?;
+    let expected = backup.counts();
+
+    // Count the source's entries apart from the backup, so entries the
+    // backup missed aren't missed by the verification reading it too
+    if !count_path.exists() {
+        if !global.is_silent() {
+            aprintln!(
+                "{} Counting {} entries...",
+                p_b("→"),
+                storage_feature(cmd.from)
+            );
+        }
+        let command = ["count".to_string(), count_path.display().to_string()];
+        run_server(cmd.from, &cmd, &command, &global, "count").await?;
+    }
+    let source_entries: usize = serde_json::from_str(&std::fs::read_to_string(&count_path)?)?;
+    check_source_count(&expected, source_entries)?;
+
+    if !global.is_silent() {
+        aprintln!(
+            "{} Writing {} records to {}...",
+            p_b("→"),
+            expected.total(),
+            storage_feature(cmd.to)
+        );
+    }
+    remove_if_exists(&report_path)?;
+    let command = [
+        "restore".to_string(),
+        backup_path.display().to_string(),
+        "--batch-size".to_string(),
+        cmd.batch_size.to_string(),
+        "--pause-ms".to_string(),
+        cmd.pause_ms.to_string(),
+        "--checkpoint".to_string(),
+        checkpoint_path.display().to_string(),
+        "--report".to_string(),
+        report_path.display().to_string(),
+    ];
+    // A restore that ran to the end writes its report even when records
+    // didn't verify, and then exits with an error
+    let restored = run_server(cmd.to, &cmd, &command, &global, "restore").await;
+    if !report_path.exists() {
+        restored?;
+    }
+
+    let report: RestoreReport = serde_json::from_str(&std::fs::read_to_string(&report_path)?)?;
+
+    if !global.is_silent() {
+        aprintln!();
+        for line in format_counts(&expected, source_entries, &report) {
+            aprintln!("  {}", line);
+        }
+        aprintln!();
+    }
+
+    if !report.is_verified() {
+        for mismatch in report.mismatches.iter().take(MAX_LISTED_MISMATCHES) {
+            aprintln!("  {}", p_r(&mismatch.to_string()));
+        }
+        if report.mismatches.len() > MAX_LISTED_MISMATCHES {
+            aprintln!(
+                "  ... and {} more in {}",
+                report.mismatches.len() - MAX_LISTED_MISMATCHES,
+                report_path.display()
+            );
+        }
+        return Err(MigrateError::Unverified {
+            count: report.mismatches.len(),
+        });
+    }
+
+    if !global.is_silent() {
+        aprintln!(
+            "{} All records verified. {} can be deleted.",
+            p_g("✅"),
+            cmd.work_dir.display()
+        );
+    }
+
+    Ok(())
+}
+
+/// Runs a server maintenance command against `storage`.
+async fn run_server(
+    storage: Storage,
+    cmd: &MigrateCommand,
+    command: &[String],
+    global: &crate::Global,
+    name: &'static str,
+) -> Result<()> {
+    let mut process = Command::new("cargo");
+    process.args(server_args(storage, cmd.release, command));
+    for (key, value) in environment_variables(storage, cmd) {
+        process.env(key, value);
+    }
+    if !global.is_verbose() {
+        process.env("RUST_LOG", "calendsync=info");
+    }
+
+    let status = process.status().await?;
+    if !status.success() {
+        return Err(MigrateError::CommandFailed { command: name });
+    }
+    Ok(())
+}
+
+fn remove_if_exists(path: &Path) -> Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn command(args: &[&str]) -> MigrateCommand {
+        MigrateCommand::parse_from(
+            ["migrate", "--from", "sqlite", "--to", "dynamodb"]
+                .iter()
+                .chain(args),
+        )
+    }
+
+    #[test]
+    fn test_validate_backends() {
+        assert!(validate_backends(Storage::Sqlite, Storage::Dynamodb).is_ok());
+        assert!(validate_backends(Storage::Dynamodb, Storage::Sqlite).is_ok());
+        assert!(validate_backends(Storage::Sqlite, Storage::Sqlite).is_err());
+        assert!(validate_backends(Storage::Inmemory, Storage::Dynamodb).is_err());
+    }
+
+    #[test]
+    fn test_server_args() {
+        let args = server_args(Storage::Dynamodb, true, &["backup".to_string()]);
+        assert_eq!(
+            args,
+            [
+                "run",
+                "-p",
+                "calendsync",
+                "--no-default-features",
+                "--features",
+                "dynamodb,memory",
+                "--release",
+                "--",
+                "backup"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_environment_variables_sqlite() {
+        let cmd = command(&["--sqlite-path", "/tmp/data.db"]);
+        let vars = environment_variables(Storage::Sqlite, &cmd);
+        assert_eq!(vars, [("SQLITE_PATH", "/tmp/data.db".to_string())]);
+    }
+
+    #[test]
+    fn test_environment_variables_dynamodb_local() {
+        let cmd = command(&["--dynamodb-endpoint", "http://localhost:8000"]);
+        let vars = environment_variables(Storage::Dynamodb, &cmd);
+        assert!(vars.contains(&("DYNAMODB_TABLE_NAME", "calendsync".to_string())));
+        assert!(vars.contains(&("DYNAMODB_MAX_ATTEMPTS", "10".to_string())));
+        assert!(vars.contains(&("AWS_ENDPOINT_URL", "http://localhost:8000".to_string())));
+        assert!(vars.contains(&("AWS_ACCESS_KEY_ID", "test".to_string())));
+    }
+
+    #[test]
+    fn test_environment_variables_dynamodb_aws() {
+        let cmd = command(&[]);
+        let vars = environment_variables(Storage::Dynamodb, &cmd);
+        assert!(!vars.iter().any(|(key, _)| key.starts_with("AWS_")));
+    }
+
+    #[test]
+    fn test_not_migrated_warning() {
+        let warning = not_migrated_warning();
+        assert!(warning.starts_with("Not migrated: trashed calendars and entries,"));
+        assert!(warning.contains("revision history"));
+        assert!(warning.contains("feed tokens"));
+        assert!(warning.ends_with("delivered-reminder claims."));
+    }
+
+    #[test]
+    fn test_format_counts() {
+        let expected = BackupCounts {
+            users: 2,
+            entries: 3,
+            ..BackupCounts::default()
+        };
+        let report = RestoreReport {
+            restored: BackupCounts {
+                users: 1,
+                entries: 3,
+                ..BackupCounts::default()
+            },
+            skipped: BackupCounts {
+                users: 1,
+                ..BackupCounts::default()
+            },
+            ..RestoreReport::default()
+        };
+
+        let lines = format_counts(&expected, 3, &report);
+        assert_eq!(lines.len(), 7);
+        assert!(lines[0].contains("restored"));
+        assert_eq!(
+            lines[1].split_whitespace().collect::<Vec<_>>(),
+            ["user", "-", "2", "1", "1", "0", "0"]
+        );
+        assert_eq!(
+            lines[5].split_whitespace().collect::<Vec<_>>(),
+            ["entry", "3", "3", "3", "0", "0", "0"]
+        );
+    }
+
+    #[test]
+    fn test_check_source_count() {
+        let counts = BackupCounts {
+            entries: 3,
+            ..BackupCounts::default()
+        };
+        assert!(check_source_count(&counts, 3).is_ok());
+        let err = check_source_count(&counts, 5).unwrap_err();
+        assert!(matches!(
+            err,
+            MigrateError::IncompleteBackup {
+                backup: 3,
+                counted: 5
+            }
+        ));
+    }
+}